@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Embedding API: the agent loop without the terminal interface.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::Config;
+use crate::error::Error;
+use crate::output::{OutputContext, OutputEvent, OutputListener};
+use crate::provider::Message;
+use crate::providers::ProviderManager;
+use crate::services::Services;
+use crate::session::SessionInfo;
+use crate::tools::ToolDefinition;
+
+/// Progress reported while the agent works on a prompt.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Event {
+    /// Streamed response text.
+    Text(String),
+    /// Streamed reasoning text, for models that expose it.
+    Thinking(String),
+    /// A tool is about to run.
+    ToolCall { description: String },
+    /// Output streamed by a running tool.
+    ToolOutput(String),
+    /// A tool finished.
+    ToolResult {
+        tool_name: String,
+        is_error: bool,
+        summary: Option<String>,
+    },
+    /// Informational message, such as a retry notice.
+    Info(String),
+    /// Warning that did not stop the turn.
+    Warning(String),
+    /// Error reported by a provider or tool.
+    Error(String),
+}
+
+type Callback = Arc<dyn Fn(&Event) + Send + Sync>;
+
+/// Maps output events to [`Event`]s for the embedder's callback.
+struct EventListener {
+    callback: Option<Callback>,
+}
+
+impl OutputListener for EventListener {
+    fn on_event(&self, event: &OutputEvent) {
+        let Some(callback) = &self.callback else {
+            return;
+        };
+        let event = match event {
+            OutputEvent::Text(text) => Event::Text(text.clone()),
+            OutputEvent::Thinking(text) => Event::Thinking(text.clone()),
+            OutputEvent::ToolCall { description } => Event::ToolCall {
+                description: description.clone(),
+            },
+            OutputEvent::ToolOutput { text } => Event::ToolOutput(text.clone()),
+            OutputEvent::ToolResult {
+                tool_name,
+                is_error,
+                summary,
+                ..
+            } => Event::ToolResult {
+                tool_name: tool_name.clone(),
+                is_error: *is_error,
+                summary: summary.clone(),
+            },
+            OutputEvent::Info(message) => Event::Info(message.clone()),
+            OutputEvent::Warning(message) => Event::Warning(message.clone()),
+            OutputEvent::Error(message) => Event::Error(message.clone()),
+            _ => return,
+        };
+        callback(&event);
+    }
+}
+
+/// A conversation with a model that can run tools.
+///
+/// Uses the henri config file and stored credentials, like the CLI. Tools
+/// that would ask for approval are denied, as no user is present to answer.
+pub struct Agent {
+    provider_manager: ProviderManager,
+    services: Services,
+    messages: Vec<Message>,
+    interrupted: Arc<AtomicBool>,
+    callback: Option<Callback>,
+}
+
+impl Agent {
+    /// Create an agent for `model` (`provider/model-id`), or for the default
+    /// model from the config file when `None`.
+    pub fn new(model: Option<&str>) -> Result<Self, Error> {
+        let config = Config::load(model.map(String::from))?;
+        let services = Services::new();
+        services.set_session_id(Some(crate::session::generate_session_id()));
+        crate::sliding_window::reset();
+
+        let mut provider_manager = ProviderManager::new(&config, services.clone());
+        let thinking = provider_manager.default_thinking();
+        provider_manager.set_thinking_enabled(thinking.enabled);
+        provider_manager.set_thinking_mode(thinking.mode);
+
+        Ok(Self {
+            provider_manager,
+            services,
+            messages: Vec::new(),
+            interrupted: Arc::new(AtomicBool::new(false)),
+            callback: None,
+        })
+    }
+
+    /// Start the MCP and LSP servers configured for `working_dir`. Without
+    /// this, only the built-in and external tools are available.
+    pub async fn start_servers(&self, working_dir: &Path) {
+        crate::config::initialize_servers(working_dir, None).await;
+    }
+
+    /// Call `callback` for each [`Event`] while a prompt is processed.
+    pub fn on_event(&mut self, callback: impl Fn(&Event) + Send + Sync + 'static) {
+        self.callback = Some(Arc::new(callback));
+    }
+
+    /// The services shared with the tools, such as the security mode.
+    pub fn services(&self) -> &Services {
+        &self.services
+    }
+
+    /// The tools offered to the model, including MCP tools once
+    /// [`Agent::start_servers`] has run.
+    pub async fn tools(&self) -> Vec<ToolDefinition> {
+        crate::tools::all_definitions(&self.services).await
+    }
+
+    /// The conversation so far.
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Continue a saved session, such as one from
+    /// [`session::list_sessions`](crate::session::list_sessions), with this
+    /// agent's model. Replaces the conversation so far.
+    pub fn resume(&mut self, session: &SessionInfo) -> Result<(), Error> {
+        let state = crate::session::load_session_by_id(&session.working_directory, &session.id)
+            .ok_or_else(|| Error::Other(format!("Session {} could not be loaded", session.id)))?;
+        let restored = crate::session::RestoredSession::from_state(&state);
+        self.messages = restored.messages;
+        self.services.set_session_id(Some(restored.session_id));
+        Ok(())
+    }
+
+    /// Restrict the tools to reading files.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.services.set_read_only(read_only);
+    }
+
+    /// Enable or disable the sandbox for bash commands. Enabled by default.
+    pub fn set_sandbox_enabled(&self, enabled: bool) {
+        self.services.set_sandbox_enabled(enabled);
+    }
+
+    /// A flag that stops the current prompt when set. It is cleared at the
+    /// start of each [`Agent::send`].
+    pub fn interrupt_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.interrupted)
+    }
+
+    /// Send a prompt and run the agent loop until the model stops calling
+    /// tools. Returns the text of the final response.
+    ///
+    /// On error the conversation is left as it was before the prompt.
+    pub async fn send(&mut self, prompt: &str) -> Result<String, Error> {
+        self.interrupted.store(false, Ordering::SeqCst);
+        let output = OutputContext::new_cli(Arc::new(EventListener {
+            callback: self.callback.clone(),
+        }));
+
+        let mut messages = self.messages.clone();
+        messages.push(Message::user(prompt));
+        self.provider_manager
+            .chat(&mut messages, &self.interrupted, &output)
+            .await?;
+        let answer = crate::tools::task::final_answer(&messages).unwrap_or_default();
+        self.messages = messages;
+        Ok(answer)
+    }
+
+    /// Forget the conversation so far.
+    pub fn clear(&mut self) {
+        self.messages.clear();
+        self.services
+            .set_session_id(Some(crate::session::generate_session_id()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn test_event_listener_forwards_events() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let listener = EventListener {
+            callback: Some(Arc::new(move |event: &Event| {
+                sink.lock().unwrap().push(format!("{:?}", event));
+            })),
+        };
+        listener.on_event(&OutputEvent::Text("hello".to_string()));
+        listener.on_event(&OutputEvent::Waiting);
+        listener.on_event(&OutputEvent::ToolCall {
+            description: "bash: ls".to_string(),
+        });
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                r#"Text("hello")"#.to_string(),
+                r#"ToolCall { description: "bash: ls" }"#.to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,1301 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Jason Ish
+
+//! Command-line entry point: argument parsing and subcommands.
+
+use std::path::{Path, PathBuf};
+
+use clap::builder::styling::{AnsiColor, Effects, Styles};
+use clap::{Parser, Subcommand};
+
+use crate::{
+    auth, cli, config, daemon, feedback, output, prompts, provider_test, providers, services,
+    session, startup, tool_metrics, tools, upgrade, version,
+};
+
+const STYLES: Styles = Styles::styled()
+    .header(AnsiColor::Green.on_default().effects(Effects::BOLD))
+    .usage(AnsiColor::Green.on_default().effects(Effects::BOLD))
+    .literal(AnsiColor::Cyan.on_default().effects(Effects::BOLD))
+    .placeholder(AnsiColor::Cyan.on_default());
+
+/// Check for existing session and restore if requested.
+/// Returns (working_dir, Option<RestoredSession>)
+fn handle_session_restore(continue_session: bool) -> (PathBuf, Option<session::RestoredSession>) {
+    let working_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+    if !continue_session {
+        return (working_dir, None);
+    }
+
+    let Some(saved_session) = session::load_session(&working_dir) else {
+        println!("No saved session found.\n");
+        return (working_dir, None);
+    };
+
+    // Replay session history
+    // Note: rendering is done by the interactive CLI once the prompt is visible.
+    (
+        working_dir,
+        Some(session::RestoredSession::from_state(&saved_session)),
+    )
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "henri")]
+#[command(about = "Your Golden Retriever AI Coding Assistant")]
+#[command(version = version::VERSION, long_version = version::LONG_VERSION.as_str())]
+#[command(styles = STYLES, color = clap::ColorChoice::Always)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(
+        long = "config-dir",
+        value_name = "DIR",
+        help = "Alternate configuration directory (default: ~/.config/henri)"
+    )]
+    config_dir: Option<PathBuf>,
+
+    #[arg(short, long, help = "Model to use (e.g., claude/claude-sonnet-4-5)")]
+    model: Option<String>,
+
+    #[arg(short = 'c', long = "continue", help = "Continue previous session")]
+    continue_session: bool,
+
+    #[arg(long, help = "Enable LSP integration", conflicts_with = "no_lsp")]
+    lsp: bool,
+
+    #[arg(long, help = "Disable LSP integration", conflicts_with = "lsp")]
+    no_lsp: bool,
+
+    #[arg(long, help = "Enable read-only mode (disables file editing tools)")]
+    read_only: bool,
+
+    #[arg(
+        short = 'b',
+        long,
+        help = "Exit after processing the prompt (batch mode)"
+    )]
+    batch: bool,
+
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        value_parser = ["off", "low", "medium", "high"],
+        help = "Reasoning effort for the model"
+    )]
+    reasoning: Option<String>,
+
+    #[arg(
+        long = "thinking-budget",
+        value_name = "TOKENS",
+        value_parser = clap::value_parser!(u32).range(crate::providers::MIN_THINKING_BUDGET as i64..),
+        help = "Thinking token budget for budget-based providers (at least 1024)"
+    )]
+    thinking_budget: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "SEED",
+        help = "Sampling seed for providers that support one (OpenAI-compatible, OpenRouter)"
+    )]
+    seed: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Append streamed assistant text (no formatting) to FILE"
+    )]
+    tee: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_parser = ["text", "json"],
+        default_value = "text",
+        help = "Output format; json writes one event per line to stdout (implies --batch)"
+    )]
+    output: String,
+
+    #[arg(
+        long = "start-transaction-logging",
+        value_name = "FILE",
+        num_args = 0..=1,
+        help = "Start transaction logging on startup"
+    )]
+    start_transaction_logging: Option<Option<PathBuf>>,
+
+    #[arg(long, help = "Print how long each startup phase took")]
+    profile_startup: bool,
+
+    #[arg(
+        trailing_var_arg = true,
+        help = "Prompt to send (non-interactive mode)"
+    )]
+    prompt: Vec<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Manage providers
+    Provider {
+        #[command(subcommand)]
+        command: ProviderCommand,
+    },
+    /// Manage MCP servers
+    Mcp {
+        #[command(subcommand)]
+        command: McpCommand,
+    },
+    /// Manage the configuration file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Run a background agent that keeps servers and conversations warm
+    Daemon {
+        /// Model for new conversations (provider/model-id)
+        #[arg(short, long)]
+        model: Option<String>,
+    },
+    /// Talk to a conversation on the running daemon
+    Attach {
+        /// Conversation name, created on first use
+        name: String,
+        /// Prompt to send; without one, read prompts interactively
+        prompt: Vec<String>,
+    },
+    /// Submit a prompt to the interactive session running here
+    Send {
+        /// Prompt to send; read from stdin when omitted
+        prompt: Vec<String>,
+        /// Process ID of the session, when several are running
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    /// Let others watch the interactive session here, read-only
+    Mirror {
+        /// Serve the session to viewers at this address (e.g., 127.0.0.1:7777)
+        #[arg(long, value_name = "ADDR", required_unless_present = "follow")]
+        listen: Option<String>,
+        /// Watch a session mirrored at this address
+        #[arg(long, value_name = "ADDR", conflicts_with_all = ["listen", "pid"])]
+        follow: Option<String>,
+        /// Process ID of the session, when several are running
+        #[arg(long)]
+        pid: Option<u32>,
+        /// Allow listening on an address other than loopback
+        #[arg(long, requires = "listen")]
+        allow_remote: bool,
+    },
+    /// Stop the turn running in the interactive session here
+    Interrupt {
+        /// Process ID of the session, when several are running
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    /// Move sessions between machines
+    Session {
+        #[command(subcommand)]
+        command: SessionCommand,
+    },
+    /// List the sessions of every directory, or open one in its directory
+    Sessions {
+        /// Session ID or unique ID prefix to open
+        id: Option<String>,
+    },
+    /// Show usage statistics
+    Stats {
+        #[command(subcommand)]
+        command: StatsCommand,
+    },
+    /// Test built-in tools directly (for debugging/learning)
+    ToolCall {
+        #[command(subcommand)]
+        tool: ToolCommand,
+    },
+    /// Check for available upgrades
+    #[command(alias = "update", hide = true)]
+    Upgrade,
+}
+
+#[derive(Subcommand, Debug)]
+enum ProviderCommand {
+    /// Add a provider (OAuth/API key setup)
+    Add,
+    /// Remove a configured provider
+    Remove,
+    /// Import credentials from Claude Code, Codex, OpenCode and aider
+    Migrate,
+    /// Check streaming, tool calls and long output against a model
+    Test {
+        /// Model to test (provider/model-id); defaults to the default model
+        model: Option<String>,
+        /// Also check image input
+        #[arg(long)]
+        images: bool,
+    },
+    /// Print the system prompt assembled for a model
+    Prompt {
+        /// Model (provider/model-id); defaults to the default model
+        model: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Restore the configuration file from a backup
+    Rollback {
+        /// Backup file to restore (interactive selection if not provided)
+        backup: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SessionCommand {
+    /// Export a session of the current directory to a portable JSON file
+    Export {
+        /// Session ID or unique ID prefix (most recent session if not provided)
+        id: Option<String>,
+        /// File to write (stdout if not provided)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Import an exported session as the most recent session of the current directory
+    Import {
+        /// Exported session file ("-" for stdin)
+        file: PathBuf,
+    },
+    /// Compare two sessions started from the same prompt side by side
+    Diff {
+        /// First session ID or unique ID prefix
+        a: String,
+        /// Second session ID or unique ID prefix
+        b: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum StatsCommand {
+    /// Tool call counts, failure rates, and durations
+    Tools {
+        /// Only show this session (ID or ID prefix)
+        #[arg(short, long)]
+        session: Option<String>,
+    },
+    /// Ratings of answers per model, from /feedback
+    Feedback,
+}
+
+#[derive(Subcommand, Debug)]
+enum McpCommand {
+    /// Add an MCP server
+    Add {
+        /// Name for the MCP server
+        name: String,
+        /// URL of a remote MCP server, instead of a command
+        #[arg(long, conflicts_with = "command")]
+        url: Option<String>,
+        /// Use the older HTTP+SSE transport instead of Streamable HTTP
+        #[arg(long, requires = "url")]
+        sse: bool,
+        /// HTTP header to send to a remote server (repeatable)
+        #[arg(long = "header", value_name = "NAME=VALUE", requires = "url")]
+        headers: Vec<String>,
+        /// Command and arguments to run the MCP server
+        #[arg(trailing_var_arg = true, required_unless_present = "url")]
+        command: Vec<String>,
+    },
+    /// Remove an MCP server
+    Remove {
+        /// Name of the MCP server to remove (interactive selection if not provided)
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ToolCommand {
+    /// Test the bash tool to execute shell commands
+    Bash {
+        /// Command to execute (everything after `bash` is treated as the command)
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+
+        /// Timeout in seconds
+        #[arg(short, long)]
+        timeout: Option<u64>,
+
+        /// Working directory for the command
+        #[arg(short, long)]
+        cwd: Option<String>,
+    },
+    /// Test the file_read tool to read file contents
+    #[command(aliases = ["file_read", "read"])]
+    FileRead {
+        /// The path to the file to read
+        filename: String,
+
+        /// 0-based line number to start reading from
+        #[arg(short, long)]
+        offset: Option<usize>,
+
+        /// Maximum number of lines to read
+        #[arg(short, long)]
+        limit: Option<usize>,
+    },
+    /// Test the file_write tool to write a file
+    #[command(aliases = ["file_write", "write"])]
+    FileWrite {
+        /// The path to the file to write
+        file_path: String,
+
+        /// Content to write (read from stdin if not provided)
+        content: Option<String>,
+
+        /// The content is base64 encoded
+        #[arg(long)]
+        base64: bool,
+
+        /// Create parent directories if they don't exist
+        #[arg(short = 'p', long)]
+        create_directories: bool,
+    },
+    /// Test the file_edit tool to replace text in a file
+    #[command(aliases = ["file_edit", "edit"])]
+    FileEdit {
+        /// The path to the file to modify
+        file_path: String,
+
+        /// The text to replace
+        old_string: String,
+
+        /// The text to replace it with
+        new_string: String,
+
+        /// Replace all occurrences
+        #[arg(short = 'a', long)]
+        replace_all: bool,
+    },
+    /// Test the apply_patch tool to apply a diff
+    #[command(aliases = ["apply_patch", "patch"])]
+    ApplyPatch {
+        /// File holding the patch (read from stdin if not provided)
+        patch_file: Option<String>,
+
+        /// Only check that the patch applies
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+    /// Test the fetch tool to retrieve URL contents
+    Fetch {
+        /// The URL to fetch
+        url: String,
+
+        /// Return raw content without conversion
+        #[arg(short, long)]
+        raw: bool,
+    },
+    /// Test the glob tool to list files matching patterns
+    Glob {
+        /// Glob patterns; prefix with ! to exclude
+        #[arg(required = true)]
+        patterns: Vec<String>,
+
+        /// Directory to search (may be repeated)
+        #[arg(short, long = "path")]
+        paths: Vec<String>,
+
+        /// Order of the files
+        #[arg(short, long, default_value = "path", value_parser = ["path", "mtime", "size"])]
+        sort: String,
+
+        /// Maximum number of files to list
+        #[arg(short, long)]
+        limit: Option<usize>,
+    },
+}
+
+/// Run the command-line interface.
+#[tokio::main]
+pub(crate) async fn run() -> std::io::Result<()> {
+    let args = Args::parse();
+    if args.profile_startup {
+        startup::enable();
+    }
+    startup::mark("args");
+
+    config::set_config_dir_override(args.config_dir.clone());
+
+    // `henri sessions <id>` runs the interactive CLI in the session's directory
+    let mut opened_session = None;
+
+    // Handle subcommands first
+    if let Some(command) = &args.command {
+        match command {
+            Command::Provider { command } => match command {
+                ProviderCommand::Add => {
+                    return handle_add_command().await;
+                }
+                ProviderCommand::Remove => {
+                    return handle_provider_remove_command().await;
+                }
+                ProviderCommand::Migrate => {
+                    return handle_provider_migrate_command();
+                }
+                ProviderCommand::Test { model, images } => {
+                    return provider_test::run(model.clone(), *images).await;
+                }
+                ProviderCommand::Prompt { model } => {
+                    return handle_provider_prompt_command(model.clone());
+                }
+            },
+            Command::Mcp { command } => match command {
+                McpCommand::Add {
+                    name,
+                    url,
+                    sse,
+                    headers,
+                    command,
+                } => {
+                    let transport = if *sse {
+                        config::McpRemoteTransport::Sse
+                    } else {
+                        config::McpRemoteTransport::Http
+                    };
+                    return handle_mcp_add_command(
+                        name.clone(),
+                        command.clone(),
+                        url.clone(),
+                        transport,
+                        headers,
+                    );
+                }
+                McpCommand::Remove { name } => {
+                    return handle_mcp_remove_command(name.clone());
+                }
+            },
+            Command::Config { command } => match command {
+                ConfigCommand::Rollback { backup } => {
+                    return handle_config_rollback_command(backup.clone());
+                }
+            },
+            Command::Daemon { model } => {
+                return daemon::run(model.clone()).await;
+            }
+            Command::Attach { name, prompt } => {
+                let prompt = (!prompt.is_empty()).then(|| prompt.join(" "));
+                return daemon::attach(name, prompt).await;
+            }
+            Command::Send { prompt, pid } => {
+                let text = if prompt.is_empty() {
+                    std::io::read_to_string(std::io::stdin())?
+                } else {
+                    prompt.join(" ")
+                };
+                return cli::remote::send(cli::remote::Request::Send { text }, *pid).await;
+            }
+            Command::Mirror {
+                listen,
+                follow,
+                pid,
+                allow_remote,
+            } => {
+                return match (listen, follow) {
+                    (_, Some(addr)) => cli::mirror::follow(addr).await,
+                    (Some(addr), None) => cli::mirror::listen(addr, *pid, *allow_remote).await,
+                    (None, None) => Ok(()),
+                };
+            }
+            Command::Interrupt { pid } => {
+                return cli::remote::send(cli::remote::Request::Interrupt, *pid).await;
+            }
+            Command::Session { command } => match command {
+                SessionCommand::Export { id, output } => {
+                    return handle_session_export_command(id.as_deref(), output.as_deref());
+                }
+                SessionCommand::Import { file } => {
+                    return handle_session_import_command(file);
+                }
+                SessionCommand::Diff { a, b } => {
+                    handle_session_diff_command(a, b);
+                    return Ok(());
+                }
+            },
+            Command::Sessions { id: None } => {
+                handle_sessions_list_command();
+                return Ok(());
+            }
+            Command::Sessions { id: Some(id) } => {
+                opened_session = Some(open_session_directory(id));
+            }
+            Command::Stats { command } => match command {
+                StatsCommand::Tools { session } => {
+                    handle_stats_tools_command(session.as_deref());
+                    return Ok(());
+                }
+                StatsCommand::Feedback => {
+                    handle_stats_feedback_command();
+                    return Ok(());
+                }
+            },
+            Command::ToolCall { tool } => match tool {
+                ToolCommand::Bash {
+                    command,
+                    timeout,
+                    cwd,
+                } => {
+                    return handle_bash_command(command.clone(), *timeout, cwd.clone()).await;
+                }
+                ToolCommand::FileRead {
+                    filename,
+                    offset,
+                    limit,
+                } => {
+                    return handle_file_read_command(filename.clone(), *offset, *limit).await;
+                }
+                ToolCommand::FileWrite {
+                    file_path,
+                    content,
+                    base64,
+                    create_directories,
+                } => {
+                    return handle_file_write_command(
+                        file_path.clone(),
+                        content.clone(),
+                        *base64,
+                        *create_directories,
+                    )
+                    .await;
+                }
+                ToolCommand::FileEdit {
+                    file_path,
+                    old_string,
+                    new_string,
+                    replace_all,
+                } => {
+                    let input = serde_json::json!({
+                        "filePath": file_path,
+                        "oldString": old_string,
+                        "newString": new_string,
+                        "replaceAll": replace_all,
+                    });
+                    return run_tool_command(&tools::FileEdit, "file-edit-test", input).await;
+                }
+                ToolCommand::ApplyPatch {
+                    patch_file,
+                    dry_run,
+                } => {
+                    return handle_apply_patch_command(patch_file.clone(), *dry_run).await;
+                }
+                ToolCommand::Fetch { url, raw } => {
+                    return handle_fetch_command(url.clone(), *raw).await;
+                }
+                ToolCommand::Glob {
+                    patterns,
+                    paths,
+                    sort,
+                    limit,
+                } => {
+                    let input = serde_json::json!({
+                        "patterns": patterns,
+                        "paths": paths,
+                        "sort": sort,
+                        "limit": limit,
+                    });
+                    return run_tool_command(&tools::Glob, "glob-test", input).await;
+                }
+            },
+            Command::Upgrade => {
+                return handle_upgrade_command().await;
+            }
+        }
+    }
+
+    // Determine LSP override from args
+    let lsp_override: Option<bool> = if args.lsp {
+        Some(true)
+    } else if args.no_lsp {
+        Some(false)
+    } else {
+        None
+    };
+
+    // Handle session restoration
+    let working_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let restored_session = if let Some(session) = opened_session {
+        Some(session)
+    } else if args.prompt.is_empty() && args.continue_session {
+        let (_, session) = handle_session_restore(args.continue_session);
+        session
+    } else {
+        None
+    };
+
+    if let Some(opt_path) = args.start_transaction_logging {
+        let path = crate::provider::transaction_log::start(opt_path);
+        eprintln!("Transaction logging started: {}", path.display());
+    }
+    startup::mark("session");
+
+    // Run CLI
+    cli::run(cli::CliArgs {
+        model: args.model,
+        prompt: args.prompt,
+        working_dir,
+        restored_session,
+        lsp_override,
+        read_only: args.read_only,
+        batch: args.batch,
+        reasoning: args.reasoning,
+        thinking_budget: args.thinking_budget,
+        seed: args.seed,
+        tee: args.tee,
+        json: args.output == "json",
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn handle_file_read_command(
+    filename: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> std::io::Result<()> {
+    let input = serde_json::json!({
+        "filename": filename,
+        "offset": offset,
+        "limit": limit,
+    });
+    run_tool_command(&tools::FileRead, "file-read-test", input).await
+}
+
+async fn handle_file_write_command(
+    file_path: String,
+    content: Option<String>,
+    base64: bool,
+    create_directories: bool,
+) -> std::io::Result<()> {
+    use std::io::Read;
+
+    let content = match content {
+        Some(content) => content,
+        None => {
+            let mut content = String::new();
+            std::io::stdin().read_to_string(&mut content)?;
+            content
+        }
+    };
+    let input = serde_json::json!({
+        "filePath": file_path,
+        "content": content,
+        "encoding": if base64 { "base64" } else { "text" },
+        "createDirectories": create_directories,
+    });
+    run_tool_command(&tools::FileWrite, "file-write-test", input).await
+}
+
+async fn handle_apply_patch_command(
+    patch_file: Option<String>,
+    dry_run: bool,
+) -> std::io::Result<()> {
+    use std::io::Read;
+
+    let patch = match patch_file {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => {
+            let mut patch = String::new();
+            std::io::stdin().read_to_string(&mut patch)?;
+            patch
+        }
+    };
+    let input = serde_json::json!({
+        "patch": patch,
+        "dryRun": dry_run,
+    });
+    run_tool_command(&tools::ApplyPatch, "apply-patch-test", input).await
+}
+
+/// Run a tool with `input` and print its result as JSON, exiting non-zero on error.
+async fn run_tool_command(
+    tool: &impl tools::Tool,
+    tool_use_id: &str,
+    input: serde_json::Value,
+) -> std::io::Result<()> {
+    let output = output::OutputContext::new_quiet();
+    let services = services::Services::new();
+    let result = tool.execute(tool_use_id, input, &output, &services).await;
+
+    match serde_json::to_string(&result) {
+        Ok(json) => println!("{}", json),
+        Err(e) => {
+            eprintln!("Failed to serialize tool result: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if result.is_error {
+        std::process::exit(result.exit_code.unwrap_or(1));
+    }
+
+    Ok(())
+}
+
+async fn handle_bash_command(
+    command: Vec<String>,
+    timeout: Option<u64>,
+    cwd: Option<String>,
+) -> std::io::Result<()> {
+    let input = serde_json::json!({
+        "command": command.join(" "),
+        "timeout": timeout,
+        "cwd": cwd,
+    });
+    run_tool_command(&tools::Bash, "bash-test", input).await
+}
+
+async fn handle_fetch_command(url: String, raw: bool) -> std::io::Result<()> {
+    let input = serde_json::json!({
+        "url": url,
+        "raw": raw,
+    });
+    run_tool_command(&tools::Fetch, "fetch-test", input).await
+}
+
+async fn handle_add_command() -> std::io::Result<()> {
+    match auth::login().await {
+        Ok(Some(_)) => {
+            println!("Provider connected successfully.");
+            Ok(())
+        }
+        Ok(None) => {
+            println!("Connection cancelled.");
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Connection failed: {}", e);
+            std::process::exit(1)
+        }
+    }
+}
+
+fn handle_provider_migrate_command() -> std::io::Result<()> {
+    if let Err(e) = auth::migrate::migrate() {
+        eprintln!("Migration failed: {}", e);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+async fn handle_provider_remove_command() -> std::io::Result<()> {
+    use inquire::Select;
+
+    // Load config
+    let mut config = match config::ConfigFile::load() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("Failed to load configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Get all providers
+    let providers: Vec<(String, String)> = config
+        .providers
+        .entries
+        .iter()
+        .map(|(id, provider_config)| {
+            let provider_type = provider_config.provider_type();
+            let display = format!("{} ({})", id, provider_type.display_name());
+            (id.clone(), display)
+        })
+        .collect();
+
+    if providers.is_empty() {
+        println!("No providers configured.");
+        return Ok(());
+    }
+
+    // Create display list for inquire
+    let display_options: Vec<String> = providers
+        .iter()
+        .map(|(_, display)| display.clone())
+        .collect();
+
+    // Let user select a provider
+    let selection = match Select::new("Select a provider to remove:", display_options)
+        .with_page_size(output::menu_page_size())
+        .prompt()
+    {
+        Ok(selected) => selected,
+        Err(inquire::InquireError::OperationCanceled) => {
+            println!("Cancelled.");
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("Selection failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Find the provider ID from the selection
+    let provider_id = providers
+        .iter()
+        .find(|(_, display)| display == &selection)
+        .map(|(id, _)| id.clone())
+        .expect("Selected provider not found in list");
+
+    // Confirm removal
+    let confirm = match inquire::Confirm::new(&format!("Remove provider '{}'?", provider_id))
+        .with_default(false)
+        .prompt()
+    {
+        Ok(confirmed) => confirmed,
+        Err(inquire::InquireError::OperationCanceled) => {
+            println!("Cancelled.");
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("Confirmation failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if !confirm {
+        println!("Cancelled.");
+        return Ok(());
+    }
+
+    // Remove the provider
+    config.remove_provider(&provider_id);
+
+    // Save configuration
+    if let Err(e) = config.save() {
+        eprintln!("Failed to save configuration: {}", e);
+        std::process::exit(1);
+    }
+
+    println!("✓ Provider '{}' removed successfully.", provider_id);
+    Ok(())
+}
+
+async fn handle_upgrade_command() -> std::io::Result<()> {
+    println!("Checking for updates...");
+
+    match upgrade::check_for_upgrade().await {
+        Ok(upgrade::UpgradeStatus::Available { latest, url }) => {
+            println!();
+            println!(
+                "A new version of Henri is available: {} (current: {})",
+                latest,
+                version::VERSION
+            );
+            println!();
+            println!("To upgrade, run:");
+            println!("  cargo install --git https://github.com/jasonish/henri");
+            println!();
+            println!("Release notes: {}", url);
+        }
+        Ok(upgrade::UpgradeStatus::UpToDate) => {
+            println!("You're on the latest version ({}).", version::VERSION);
+        }
+        Err(e) => {
+            eprintln!("Failed to check for updates: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_mcp_add_command(
+    name: String,
+    command: Vec<String>,
+    url: Option<String>,
+    transport: config::McpRemoteTransport,
+    headers: &[String],
+) -> std::io::Result<()> {
+    if command.is_empty() && url.is_none() {
+        eprintln!("Error: command is required");
+        std::process::exit(1);
+    }
+
+    let mut header_map = std::collections::HashMap::new();
+    for header in headers {
+        let Some((key, value)) = header.split_once('=') else {
+            eprintln!("Error: header must be NAME=VALUE: {}", header);
+            std::process::exit(1);
+        };
+        header_map.insert(key.trim().to_string(), value.trim().to_string());
+    }
+
+    // Load config
+    let mut config = match config::ConfigFile::load() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("Failed to load configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Check if server with this name already exists
+    let mcp_config = config.mcp.get_or_insert_with(Default::default);
+    if mcp_config.servers.iter().any(|s| s.name == name) {
+        eprintln!("Error: MCP server '{}' already exists", name);
+        std::process::exit(1);
+    }
+
+    // Split command into command and args
+    let (cmd, args) = command
+        .split_first()
+        .map(|(cmd, args)| (cmd.clone(), args.to_vec()))
+        .unwrap_or_default();
+
+    // Add the new server
+    mcp_config.servers.push(config::McpServerConfig {
+        name: name.clone(),
+        command: cmd,
+        args,
+        env: std::collections::HashMap::new(),
+        url,
+        transport,
+        headers: header_map,
+        bearer_token: None,
+        enabled: true,
+    });
+
+    // Save configuration
+    if let Err(e) = config.save() {
+        eprintln!("Failed to save configuration: {}", e);
+        std::process::exit(1);
+    }
+
+    println!("✓ MCP server '{}' added successfully.", name);
+    Ok(())
+}
+
+fn handle_config_rollback_command(backup: Option<PathBuf>) -> std::io::Result<()> {
+    use inquire::Select;
+
+    let backup = match backup {
+        Some(path) => path,
+        None => {
+            let backups = config::list_config_backups();
+            if backups.is_empty() {
+                println!("No configuration backups found.");
+                return Ok(());
+            }
+
+            let names: Vec<String> = backups
+                .iter()
+                .filter_map(|p| p.file_name())
+                .map(|n| n.to_string_lossy().into_owned())
+                .collect();
+            match Select::new("Select a backup to restore:", names)
+                .with_page_size(output::menu_page_size())
+                .raw_prompt()
+            {
+                Ok(selected) => backups[selected.index].clone(),
+                Err(inquire::InquireError::OperationCanceled) => {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Selection failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+
+    if let Err(e) = config::rollback_config(&backup) {
+        eprintln!("Failed to restore configuration: {}", e);
+        std::process::exit(1);
+    }
+    println!("Restored configuration from {}", backup.display());
+    Ok(())
+}
+
+fn handle_session_export_command(id: Option<&str>, output: Option<&Path>) -> std::io::Result<()> {
+    let working_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let export = match session::export_session(&working_dir, id) {
+        Ok(export) => export,
+        Err(e) => {
+            eprintln!("Failed to export session: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let json = serde_json::to_string_pretty(&export).map_err(std::io::Error::other)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, json + "\n")?;
+            eprintln!(
+                "Exported session {} to {}",
+                export.meta.session_id,
+                path.display()
+            );
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+fn handle_session_import_command(file: &Path) -> std::io::Result<()> {
+    use std::io::Read;
+
+    let mut json = String::new();
+    if file == Path::new("-") {
+        std::io::stdin().read_to_string(&mut json)?;
+    } else {
+        json = std::fs::read_to_string(file)?;
+    }
+
+    let working_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let result = serde_json::from_str::<session::SessionExport>(&json)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        .and_then(|export| session::import_session(&working_dir, export));
+    match result {
+        Ok(session_id) => {
+            println!("Imported session {}", session_id);
+            println!("Resume it with: henri --continue");
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Failed to import session: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Load the session `id` (ID or unique prefix) from any directory. Exits if
+/// there's no such session.
+fn load_any_session(id: &str) -> session::SessionState {
+    let loaded = session::find_session(id).and_then(|info| {
+        session::load_session_by_id(&info.working_directory, &info.id).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("failed to read session {}", info.id),
+            )
+        })
+    });
+    match loaded {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("Failed to load session: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn handle_session_diff_command(a: &str, b: &str) {
+    let a = load_any_session(a);
+    let b = load_any_session(b);
+    let width = terminal_size::terminal_size()
+        .map(|(w, _)| w.0 as usize)
+        .unwrap_or(120);
+    println!("{}", crate::session_diff::render(&a, &b, width));
+}
+
+fn handle_provider_prompt_command(model: Option<String>) -> std::io::Result<()> {
+    let config = config::Config::load(model).map_err(|e| std::io::Error::other(e.to_string()))?;
+    let (provider, model_id, custom) = providers::parse_model_spec(&config.model);
+    // Only the OpenAI-compatible and Ollama requests shorten the prompt for
+    // small context windows.
+    let tier = match provider {
+        providers::ModelProvider::OpenAiCompat
+        | providers::ModelProvider::OpenRouter
+        | providers::ModelProvider::Mistral
+        | providers::ModelProvider::Ollama => prompts::PromptTier::for_context_limit(
+            config::ConfigFile::load()
+                .ok()
+                .and_then(|c| c.model_context_window(&model_id)),
+        ),
+        _ => prompts::PromptTier::Full,
+    };
+    let spec = format!(
+        "{}/{}",
+        custom.as_deref().unwrap_or(provider.id()),
+        model_id
+    );
+    let services = services::Services::new();
+    let prompt = prompts::system_prompt_for_tier(Some(&services), &spec, tier);
+    println!("{}", prompt.join("\n\n"));
+    Ok(())
+}
+
+fn handle_sessions_list_command() {
+    let sessions = session::list_all_sessions();
+    if sessions.is_empty() {
+        println!("No saved sessions.");
+        return;
+    }
+    let home = dirs::home_dir();
+    for info in &sessions {
+        let path = match home
+            .as_deref()
+            .and_then(|h| info.working_directory.strip_prefix(h).ok())
+        {
+            Some(rest) => format!("~/{}", rest.display()),
+            None => info.working_directory.display().to_string(),
+        };
+        let title = info
+            .title
+            .as_deref()
+            .or(info.preview.as_deref())
+            .unwrap_or("(no preview)");
+        println!(
+            "{:>10}  {:>4} msgs  {}  {}  {}",
+            session::format_age(&info.saved_at),
+            info.message_count,
+            path,
+            session::truncate_str(title, 50),
+            info.id
+        );
+    }
+    println!("\nOpen one with: henri sessions <id>");
+}
+
+/// Find the session to open with `henri sessions <id>` and change to its
+/// working directory. Exits if there's no such session or the directory is
+/// gone.
+fn open_session_directory(id: &str) -> session::RestoredSession {
+    let info = match session::find_session(id) {
+        Ok(info) => info,
+        Err(e) => {
+            eprintln!("Failed to open session: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = std::env::set_current_dir(&info.working_directory) {
+        eprintln!(
+            "Failed to open session: {}: {}",
+            info.working_directory.display(),
+            e
+        );
+        std::process::exit(1);
+    }
+    match session::load_session_by_id(&info.working_directory, &info.id) {
+        Some(state) => session::RestoredSession::from_state(&state),
+        None => {
+            eprintln!("Failed to load session {}", info.id);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn handle_stats_tools_command(session: Option<&str>) {
+    let records = tool_metrics::load();
+    if records.is_empty() {
+        println!("No tool calls recorded yet.");
+        return;
+    }
+
+    if let Some(prefix) = session {
+        let selected: Vec<_> = records
+            .iter()
+            .filter(|r| r.session_id.starts_with(prefix))
+            .collect();
+        if selected.is_empty() {
+            println!("No tool calls recorded for session '{}'.", prefix);
+            return;
+        }
+        print!(
+            "{}",
+            tool_metrics::format_table(&tool_metrics::aggregate(selected))
+        );
+        return;
+    }
+
+    // Records are appended in order, so the last one belongs to the latest session.
+    let latest = &records[records.len() - 1].session_id;
+    println!("Latest session ({}):", latest);
+    print!(
+        "{}",
+        tool_metrics::format_table(&tool_metrics::aggregate(
+            records.iter().filter(|r| &r.session_id == latest)
+        ))
+    );
+    let sessions: std::collections::HashSet<&str> =
+        records.iter().map(|r| r.session_id.as_str()).collect();
+    println!("\nAll sessions ({}):", sessions.len());
+    print!(
+        "{}",
+        tool_metrics::format_table(&tool_metrics::aggregate(&records))
+    );
+}
+
+fn handle_stats_feedback_command() {
+    let records = feedback::load();
+    if records.is_empty() {
+        println!("No answers rated yet. Rate answers with /feedback up or /feedback down.");
+        return;
+    }
+    print!("{}", feedback::format_table(&feedback::aggregate(&records)));
+}
+
+fn handle_mcp_remove_command(name: Option<String>) -> std::io::Result<()> {
+    use inquire::Select;
+
+    // Load config
+    let mut config = match config::ConfigFile::load() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("Failed to load configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mcp_config = match &mut config.mcp {
+        Some(cfg) if !cfg.servers.is_empty() => cfg,
+        _ => {
+            println!("No MCP servers configured.");
+            return Ok(());
+        }
+    };
+
+    // Determine which server to remove
+    let server_name = match name {
+        Some(n) => {
+            // Verify the server exists
+            if !mcp_config.servers.iter().any(|s| s.name == n) {
+                eprintln!("Error: MCP server '{}' not found", n);
+                std::process::exit(1);
+            }
+            n
+        }
+        None => {
+            // Interactive selection
+            let server_names: Vec<String> =
+                mcp_config.servers.iter().map(|s| s.name.clone()).collect();
+
+            match Select::new("Select an MCP server to remove:", server_names)
+                .with_page_size(output::menu_page_size())
+                .prompt()
+            {
+                Ok(selected) => selected,
+                Err(inquire::InquireError::OperationCanceled) => {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("Selection failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+
+    // Remove the server
+    mcp_config.servers.retain(|s| s.name != server_name);
+
+    // Save configuration
+    if let Err(e) = config.save() {
+        eprintln!("Failed to save configuration: {}", e);
+        std::process::exit(1);
+    }
+
+    println!("✓ MCP server '{}' removed successfully.", server_name);
+    Ok(())
+}
@@ -0,0 +1,491 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Importing credentials from other coding agents, for
+//! `henri provider migrate`.
+//!
+//! Looks for the sign-ins and API keys of Claude Code (the macOS keychain or
+//! `~/.claude/.credentials.json`), Codex (`~/.codex/auth.json`), OpenCode
+//! (`auth.json` and the `provider` section of `opencode.json`) and the
+//! environment variables aider reads. Each one henri has a provider for is
+//! offered for import; credentials already in the config are left out.
+
+use std::path::{Path, PathBuf};
+
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use colored::Colorize;
+use inquire::MultiSelect;
+use inquire::error::InquireError;
+use serde_json::Value;
+
+use super::{OPENAI_AUDIENCE, OPENAI_DEFAULT_CLIENT_ID, determine_local_id};
+use crate::config::{
+    ClaudeAuth, ClaudeProviderConfig, ConfigFile, CopilotProviderConfig, MistralProviderConfig,
+    OpenAiProviderConfig, OpenRouterConfig, ProviderConfig, ZenProviderConfig,
+};
+use crate::error::{Error, Result};
+
+/// Keychain service Claude Code stores its sign-in under on macOS.
+const CLAUDE_CODE_KEYCHAIN_SERVICE: &str = "Claude Code-credentials";
+
+/// A credential found in another tool.
+struct Found {
+    /// Where it was found, e.g. "Codex (~/.codex/auth.json)"
+    source: String,
+    config: ProviderConfig,
+}
+
+impl Found {
+    fn label(&self) -> String {
+        format!(
+            "{} from {}",
+            self.config.provider_type().display_name(),
+            self.source
+        )
+    }
+}
+
+/// What was found, and credentials henri can't use.
+#[derive(Default)]
+struct Detected {
+    found: Vec<Found>,
+    notes: Vec<String>,
+}
+
+impl Detected {
+    fn add(&mut self, source: &str, config: Option<ProviderConfig>) {
+        if let Some(config) = config {
+            self.found.push(Found {
+                source: source.to_string(),
+                config,
+            });
+        }
+    }
+}
+
+/// The token or key that identifies a provider's credentials, to tell
+/// whether they were imported already.
+fn credential(config: &ProviderConfig) -> Option<&str> {
+    match config {
+        ProviderConfig::Claude(c) => Some(&c.auth.refresh_token),
+        ProviderConfig::GithubCopilot(c) => Some(&c.access_token),
+        ProviderConfig::Openai(c) => Some(&c.refresh_token),
+        ProviderConfig::Zen(c) => Some(&c.api_key),
+        ProviderConfig::Openrouter(c) => Some(&c.api_key),
+        ProviderConfig::Mistral(c) => Some(&c.api_key),
+        _ => None,
+    }
+}
+
+fn string(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    let text = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// `path` relative to the home directory, for display.
+fn display_path(home: &Path, path: &Path) -> String {
+    match path.strip_prefix(home) {
+        Ok(rest) => format!("~/{}", rest.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+fn claude(auth: &Value) -> Option<ProviderConfig> {
+    let oauth = auth.get("claudeAiOauth")?;
+    Some(ProviderConfig::Claude(ClaudeProviderConfig {
+        enabled: true,
+        base_url: None,
+        betas: Default::default(),
+        auth: ClaudeAuth {
+            refresh_token: string(oauth, "refreshToken")?,
+            access_token: string(oauth, "accessToken").unwrap_or_default(),
+            expires_at: oauth.get("expiresAt").and_then(Value::as_u64).unwrap_or(0),
+        },
+    }))
+}
+
+fn openai(refresh_token: String, access_token: Option<String>) -> ProviderConfig {
+    // A token without a readable expiry is refreshed on first use.
+    let expires_at = access_token.as_deref().and_then(jwt_expiry).unwrap_or(0);
+    ProviderConfig::Openai(OpenAiProviderConfig {
+        enabled: true,
+        client_id: OPENAI_DEFAULT_CLIENT_ID.to_string(),
+        audience: OPENAI_AUDIENCE.to_string(),
+        refresh_token,
+        access_token: access_token.unwrap_or_default(),
+        expires_at,
+        project_id: None,
+    })
+}
+
+/// Expiry of a JWT in milliseconds since the epoch.
+fn jwt_expiry(token: &str) -> Option<u64> {
+    let payload = token.split('.').nth(1)?;
+    let decoded = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: Value = serde_json::from_slice(&decoded).ok()?;
+    claims.get("exp")?.as_u64().map(|exp| exp * 1000)
+}
+
+fn zen(api_key: String) -> ProviderConfig {
+    ProviderConfig::Zen(ZenProviderConfig {
+        enabled: true,
+        api_key,
+    })
+}
+
+fn openrouter(api_key: String) -> ProviderConfig {
+    ProviderConfig::Openrouter(OpenRouterConfig {
+        enabled: true,
+        api_key,
+        model_configs: Vec::new(),
+    })
+}
+
+fn mistral(api_key: String) -> ProviderConfig {
+    ProviderConfig::Mistral(MistralProviderConfig {
+        enabled: true,
+        api_key,
+        base_url: crate::provider::mistral::DEFAULT_BASE_URL.to_string(),
+        fim_model: crate::provider::mistral::DEFAULT_FIM_MODEL.to_string(),
+        model_configs: Vec::new(),
+    })
+}
+
+/// A note for an API key henri has no provider for.
+fn unsupported_key(name: &str, source: &str) -> String {
+    let hint = match name {
+        "anthropic" => "henri signs in to Claude with a Pro/Max account",
+        _ => "add it with `henri provider add` as an OpenAI compatible provider",
+    };
+    format!("Skipped the {} API key from {}: {}.", name, source, hint)
+}
+
+fn detect_claude_code(detected: &mut Detected, home: &Path, keychain: Option<&str>) {
+    if let Some(secret) = keychain
+        && let Ok(auth) = serde_json::from_str::<Value>(secret)
+    {
+        detected.add("Claude Code (keychain)", claude(&auth));
+        return;
+    }
+    let path = home.join(".claude/.credentials.json");
+    if let Some(auth) = read_json(&path) {
+        let source = format!("Claude Code ({})", display_path(home, &path));
+        detected.add(&source, claude(&auth));
+    }
+}
+
+fn detect_codex(detected: &mut Detected, home: &Path, codex_home: Option<PathBuf>) {
+    let path = codex_home
+        .unwrap_or_else(|| home.join(".codex"))
+        .join("auth.json");
+    let Some(auth) = read_json(&path) else {
+        return;
+    };
+    let source = format!("Codex ({})", display_path(home, &path));
+    if let Some(tokens) = auth.get("tokens")
+        && let Some(refresh_token) = string(tokens, "refresh_token")
+    {
+        detected.add(
+            &source,
+            Some(openai(refresh_token, string(tokens, "access_token"))),
+        );
+    } else if string(&auth, "OPENAI_API_KEY").is_some() {
+        detected.notes.push(unsupported_key("openai", &source));
+    }
+}
+
+/// A provider entry of OpenCode's `auth.json` or `opencode.json`.
+fn opencode_provider(
+    detected: &mut Detected,
+    source: &str,
+    name: &str,
+    api_key: Option<String>,
+    oauth: Option<&Value>,
+) {
+    if let Some(oauth) = oauth {
+        let config = match name {
+            "anthropic" => string(oauth, "refresh").map(|refresh_token| {
+                ProviderConfig::Claude(ClaudeProviderConfig {
+                    enabled: true,
+                    base_url: None,
+                    betas: Default::default(),
+                    auth: ClaudeAuth {
+                        refresh_token,
+                        access_token: string(oauth, "access").unwrap_or_default(),
+                        expires_at: oauth.get("expires").and_then(Value::as_u64).unwrap_or(0),
+                    },
+                })
+            }),
+            "openai" => string(oauth, "refresh")
+                .map(|refresh_token| openai(refresh_token, string(oauth, "access"))),
+            // OpenCode keeps the GitHub token as the refresh token and the
+            // Copilot token derived from it as the access token.
+            "github-copilot" => string(oauth, "refresh").map(|access_token| {
+                ProviderConfig::GithubCopilot(CopilotProviderConfig {
+                    enabled: true,
+                    access_token,
+                    refresh_token: None,
+                    expires_at: None,
+                    copilot_token: None,
+                    copilot_expires_at: None,
+                })
+            }),
+            _ => None,
+        };
+        detected.add(source, config);
+        return;
+    }
+    // `{env:NAME}` and `{file:path}` references aren't keys.
+    let Some(api_key) = api_key.filter(|key| !key.starts_with('{')) else {
+        return;
+    };
+    match name {
+        "opencode" => detected.add(source, Some(zen(api_key))),
+        "openrouter" => detected.add(source, Some(openrouter(api_key))),
+        "mistral" => detected.add(source, Some(mistral(api_key))),
+        "anthropic" | "openai" => detected.notes.push(unsupported_key(name, source)),
+        _ => {}
+    }
+}
+
+fn detect_opencode(detected: &mut Detected, home: &Path, data_dir: &Path, config_dir: &Path) {
+    let path = data_dir.join("opencode/auth.json");
+    if let Some(Value::Object(auth)) = read_json(&path) {
+        let source = format!("OpenCode ({})", display_path(home, &path));
+        for (name, entry) in &auth {
+            let oauth =
+                (entry.get("type").and_then(Value::as_str) == Some("oauth")).then_some(entry);
+            opencode_provider(detected, &source, name, string(entry, "key"), oauth);
+        }
+    }
+
+    let path = config_dir.join("opencode/opencode.json");
+    if let Some(config) = read_json(&path)
+        && let Some(Value::Object(providers)) = config.get("provider")
+    {
+        let source = format!("OpenCode ({})", display_path(home, &path));
+        for (name, provider) in providers {
+            let api_key = provider
+                .get("options")
+                .and_then(|options| string(options, "apiKey"));
+            opencode_provider(detected, &source, name, api_key, None);
+        }
+    }
+}
+
+/// The API keys aider reads from the environment.
+fn detect_environment(detected: &mut Detected, var: &dyn Fn(&str) -> Option<String>) {
+    let source = "the environment (aider)";
+    let var = |name: &str| {
+        var(name)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    detected.add(source, var("OPENROUTER_API_KEY").map(openrouter));
+    detected.add(source, var("MISTRAL_API_KEY").map(mistral));
+    if var("ANTHROPIC_API_KEY").is_some() {
+        detected.notes.push(unsupported_key("anthropic", source));
+    }
+    if var("OPENAI_API_KEY").is_some() {
+        detected.notes.push(unsupported_key("openai", source));
+    }
+}
+
+/// Look for credentials under `home`, with `var` reading environment
+/// variables and `keychain` the Claude Code keychain entry, if any.
+fn detect(home: &Path, var: &dyn Fn(&str) -> Option<String>, keychain: Option<&str>) -> Detected {
+    let mut detected = Detected::default();
+    detect_claude_code(&mut detected, home, keychain);
+    detect_codex(&mut detected, home, var("CODEX_HOME").map(PathBuf::from));
+    let data_dir = var("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".local/share"));
+    let config_dir = var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".config"));
+    detect_opencode(&mut detected, home, &data_dir, &config_dir);
+    detect_environment(&mut detected, var);
+    detected
+}
+
+/// Claude Code's sign-in from the macOS keychain.
+fn claude_code_keychain() -> Option<String> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let output = std::process::Command::new("security")
+        .args([
+            "find-generic-password",
+            "-s",
+            CLAUDE_CODE_KEYCHAIN_SERVICE,
+            "-w",
+        ])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Find credentials of other tools and import the ones the user picks.
+pub(crate) fn migrate() -> Result<()> {
+    let home = dirs::home_dir()
+        .ok_or_else(|| Error::Config("Could not determine home directory".to_string()))?;
+    let keychain = claude_code_keychain();
+    let detected = detect(&home, &|name| std::env::var(name).ok(), keychain.as_deref());
+
+    let mut config = ConfigFile::load()?;
+    let mut candidates = Vec::new();
+    for found in detected.found {
+        let key = credential(&found.config);
+        let existing = config
+            .providers
+            .entries
+            .iter()
+            .find(|(_, existing)| key.is_some() && credential(existing) == key);
+        match existing {
+            Some((id, _)) => println!(
+                "{}",
+                format!("{} is already configured as '{}'.", found.label(), id).bright_black()
+            ),
+            None if !candidates
+                .iter()
+                .any(|c: &Found| credential(&c.config) == key) =>
+            {
+                candidates.push(found)
+            }
+            None => {}
+        }
+    }
+    for note in &detected.notes {
+        println!("{}", note.bright_black());
+    }
+    if candidates.is_empty() {
+        println!("No credentials to import were found.");
+        return Ok(());
+    }
+
+    let labels: Vec<String> = candidates.iter().map(Found::label).collect();
+    let all: Vec<usize> = (0..labels.len()).collect();
+    let selected = match MultiSelect::new("Import credentials:", labels)
+        .with_default(&all)
+        .with_page_size(crate::output::menu_page_size())
+        .raw_prompt()
+    {
+        Ok(selected) => selected,
+        Err(InquireError::OperationCanceled | InquireError::OperationInterrupted) => {
+            println!("Cancelled.");
+            return Ok(());
+        }
+        Err(e) => return Err(Error::Prompt(e.to_string())),
+    };
+
+    let mut candidates: Vec<Option<Found>> = candidates.into_iter().map(Some).collect();
+    for option in selected {
+        let Some(found) = candidates[option.index].take() else {
+            continue;
+        };
+        let local_id = determine_local_id(&config, found.config.provider_type())?;
+        let label = found.label();
+        config.set_provider(local_id.clone(), found.config);
+        config.save()?;
+        println!(
+            "{}",
+            format!("✓ Imported {} as '{}'.", label, local_id).green()
+        );
+    }
+    println!(
+        "{}",
+        "Signed-in accounts share their refresh token with the tool they came from; \
+         if it stops working there, sign in to it again."
+            .yellow()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_detect() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path();
+        write(
+            &home.join(".claude/.credentials.json"),
+            r#"{"claudeAiOauth": {"accessToken": "sk-ant-oat", "refreshToken": "sk-ant-ort", "expiresAt": 1760000000000}}"#,
+        );
+        write(
+            &home.join(".codex/auth.json"),
+            r#"{"OPENAI_API_KEY": null, "tokens": {"access_token": "a.eyJleHAiOjE3NjAwMDAwMDB9.c", "refresh_token": "rt-codex"}}"#,
+        );
+        write(
+            &home.join(".local/share/opencode/auth.json"),
+            r#"{"opencode": {"type": "api", "key": "zen-key"}, "github-copilot": {"type": "oauth", "refresh": "gho_token", "access": "tid=1", "expires": 0}}"#,
+        );
+        write(
+            &home.join(".config/opencode/opencode.json"),
+            r#"{"provider": {"openrouter": {"options": {"apiKey": "{env:OPENROUTER_API_KEY}"}}}}"#,
+        );
+        let env = |name: &str| match name {
+            "MISTRAL_API_KEY" => Some("mistral-key".to_string()),
+            "ANTHROPIC_API_KEY" => Some("sk-ant-api".to_string()),
+            _ => None,
+        };
+
+        let detected = detect(home, &env, None);
+        let found: Vec<(String, Option<&str>)> = detected
+            .found
+            .iter()
+            .map(|f| (f.label(), credential(&f.config)))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "Anthropic Claude from Claude Code (~/.claude/.credentials.json)".to_string(),
+                    Some("sk-ant-ort")
+                ),
+                (
+                    "OpenAI from Codex (~/.codex/auth.json)".to_string(),
+                    Some("rt-codex")
+                ),
+                (
+                    "GitHub Copilot from OpenCode (~/.local/share/opencode/auth.json)".to_string(),
+                    Some("gho_token")
+                ),
+                (
+                    "OpenCode Zen from OpenCode (~/.local/share/opencode/auth.json)".to_string(),
+                    Some("zen-key")
+                ),
+                (
+                    "Mistral from the environment (aider)".to_string(),
+                    Some("mistral-key")
+                ),
+            ]
+        );
+        let ProviderConfig::Openai(codex) = &detected.found[1].config else {
+            panic!("expected an OpenAI config");
+        };
+        assert_eq!(codex.expires_at, 1_760_000_000_000);
+        assert_eq!(detected.notes.len(), 1);
+        assert!(detected.notes[0].contains("anthropic API key"));
+    }
+}
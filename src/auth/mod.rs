@@ -1,6 +1,9 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Jason Ish
 
+pub(crate) mod adc;
+pub(crate) mod migrate;
+
 use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use colored::Colorize;
 use inquire::error::InquireError;
@@ -18,8 +21,9 @@ use tokio::time::{sleep, timeout};
 
 use crate::config::{
     AntigravityProviderConfig, ClaudeAuth, ClaudeProviderConfig, ConfigFile, CopilotProviderConfig,
-    ModelConfig, OpenAiCompatProviderConfig, OpenAiProviderConfig, OpenRouterConfig,
-    ProviderConfig, ProviderType, ZenProviderConfig,
+    MistralProviderConfig, ModelConfig, OllamaProviderConfig, OpenAiCompatProviderConfig,
+    OpenAiProviderConfig, OpenRouterConfig, ProviderConfig, ProviderType, VertexProviderConfig,
+    ZenProviderConfig,
 };
 use crate::error::{Error, Result};
 
@@ -32,6 +36,9 @@ pub(crate) enum LoginProvider {
     OpenAi,
     OpenAiCompat,
     OpenRouter,
+    Ollama,
+    Vertex,
+    Mistral,
 }
 
 impl fmt::Display for LoginProvider {
@@ -44,6 +51,9 @@ impl fmt::Display for LoginProvider {
             LoginProvider::OpenAi => write!(f, "OpenAI"),
             LoginProvider::OpenAiCompat => write!(f, "OpenAI Compatible"),
             LoginProvider::OpenRouter => write!(f, "OpenRouter"),
+            LoginProvider::Ollama => write!(f, "Ollama"),
+            LoginProvider::Vertex => write!(f, "Google Vertex AI"),
+            LoginProvider::Mistral => write!(f, "Mistral"),
         }
     }
 }
@@ -57,6 +67,9 @@ impl LoginProvider {
             LoginProvider::OpenAi,
             LoginProvider::OpenAiCompat,
             LoginProvider::OpenRouter,
+            LoginProvider::Ollama,
+            LoginProvider::Vertex,
+            LoginProvider::Mistral,
             LoginProvider::Antigravity,
         ]
     }
@@ -139,6 +152,18 @@ pub(crate) async fn login() -> Result<Option<LoginProvider>> {
             login_openrouter().await?;
             Ok(Some(LoginProvider::OpenRouter))
         }
+        LoginProvider::Ollama => {
+            login_ollama().await?;
+            Ok(Some(LoginProvider::Ollama))
+        }
+        LoginProvider::Vertex => {
+            login_vertex().await?;
+            Ok(Some(LoginProvider::Vertex))
+        }
+        LoginProvider::Mistral => {
+            login_mistral().await?;
+            Ok(Some(LoginProvider::Mistral))
+        }
     }
 }
 
@@ -410,6 +435,7 @@ async fn login_openai_compat() -> Result<()> {
             temperature: None,
             top_p: None,
             max_tokens: None,
+            context_window: None,
         });
 
         println!("{}", format!("✓ Added model: {}", model_name).green());
@@ -535,6 +561,7 @@ async fn login_openrouter() -> Result<()> {
             temperature: None,
             top_p: None,
             max_tokens: None,
+            context_window: None,
         });
 
         println!("{}", format!("✓ Added model: {}", model_name).green());
@@ -585,6 +612,243 @@ async fn login_openrouter() -> Result<()> {
     Ok(())
 }
 
+async fn login_ollama() -> Result<()> {
+    println!("\n{}", "Ollama Setup".cyan().bold());
+    println!("{}", "═".repeat(50).cyan());
+
+    println!("{}", "Enter the URL of the Ollama server.".yellow());
+    let base_url = Text::new("Base URL:")
+        .with_default(crate::provider::ollama::DEFAULT_BASE_URL)
+        .with_validator(|input: &str| {
+            if !input.starts_with("http://") && !input.starts_with("https://") {
+                Err(Box::from("Base URL must start with http:// or https://"))
+            } else {
+                Ok(inquire::validator::Validation::Valid)
+            }
+        })
+        .prompt()
+        .map_err(|e| Error::Prompt(e.to_string()))?;
+    let base_url = base_url.trim().trim_end_matches('/').to_string();
+
+    let mut config = ConfigFile::load()?;
+    let local_id = determine_local_id(&config, ProviderType::Ollama)?;
+
+    // Check if provider ID already exists
+    if config.get_provider(&local_id).is_some() {
+        let overwrite = inquire::Confirm::new(&format!(
+            "Provider '{}' already exists. Overwrite?",
+            local_id
+        ))
+        .with_default(false)
+        .prompt()
+        .map_err(|e| Error::Prompt(e.to_string()))?;
+
+        if !overwrite {
+            println!("{}", "Setup cancelled.".yellow());
+            return Ok(());
+        }
+    }
+
+    // Models are discovered from the server rather than entered here.
+    match crate::provider::ollama::refresh_provider(&local_id, &base_url).await {
+        Ok(models) if models.is_empty() => {
+            println!(
+                "{}",
+                "No models are installed yet; pull one with `ollama pull <model>`.".yellow()
+            );
+        }
+        Ok(models) => {
+            println!("\n{}", "Installed models:".yellow());
+            for model in &models {
+                println!("  {}", model);
+            }
+        }
+        Err(e) => {
+            println!(
+                "{}",
+                format!(
+                    "Could not reach Ollama at {} ({}). Models will be listed once it is running.",
+                    base_url, e
+                )
+                .yellow()
+            );
+        }
+    }
+
+    config.set_provider(
+        local_id.clone(),
+        ProviderConfig::Ollama(OllamaProviderConfig {
+            enabled: true,
+            base_url,
+            model_configs: Vec::new(),
+        }),
+    );
+    config.save()?;
+
+    println!(
+        "\n{}",
+        format!("✓ Ollama '{}' configured successfully!", local_id)
+            .green()
+            .bold()
+    );
+    println!(
+        "{}",
+        format!("You can now select models like '{}/llama3.2'", local_id).blue()
+    );
+
+    Ok(())
+}
+
+async fn login_vertex() -> Result<()> {
+    println!("\n{}", "Google Vertex AI Setup".cyan().bold());
+    println!("{}", "═".repeat(50).cyan());
+
+    println!(
+        "{}",
+        "Enter the Google Cloud project to bill requests to.".yellow()
+    );
+    let default_project = std::env::var("GOOGLE_CLOUD_PROJECT").unwrap_or_default();
+    let project = Text::new("Project ID:")
+        .with_default(&default_project)
+        .with_validator(|input: &str| {
+            if input.trim().is_empty() {
+                Err(Box::from("Project ID cannot be empty"))
+            } else {
+                Ok(inquire::validator::Validation::Valid)
+            }
+        })
+        .prompt()
+        .map_err(|e| Error::Prompt(e.to_string()))?;
+
+    println!(
+        "{}",
+        "Enter the region the models are served from, or 'global'.".yellow()
+    );
+    let region = Text::new("Region:")
+        .with_default(crate::provider::vertex::DEFAULT_REGION)
+        .prompt()
+        .map_err(|e| Error::Prompt(e.to_string()))?;
+
+    println!(
+        "{}",
+        "Enter the path to a service account key, or leave empty to use Application Default Credentials."
+            .yellow()
+    );
+    let credentials = Text::new("Credentials file:")
+        .prompt()
+        .map_err(|e| Error::Prompt(e.to_string()))?;
+    let credentials = Some(credentials.trim())
+        .filter(|path| !path.is_empty())
+        .map(std::path::PathBuf::from);
+
+    // Check the credentials now rather than on the first request.
+    if let Err(e) = async {
+        adc::TokenSource::load(credentials.as_deref())?
+            .token()
+            .await
+    }
+    .await
+    {
+        println!(
+            "{}",
+            format!("Could not get an access token: {}", e).yellow()
+        );
+    }
+
+    let mut config = ConfigFile::load()?;
+    let local_id = determine_local_id(&config, ProviderType::Vertex)?;
+
+    config.set_provider(
+        local_id.clone(),
+        ProviderConfig::Vertex(VertexProviderConfig {
+            enabled: true,
+            project: project.trim().to_string(),
+            region: region.trim().to_string(),
+            credentials,
+            model_configs: Vec::new(),
+        }),
+    );
+    config.save()?;
+
+    println!(
+        "\n{}",
+        format!("✓ Vertex AI '{}' configured successfully!", local_id)
+            .green()
+            .bold()
+    );
+    println!(
+        "{}",
+        format!(
+            "You can now select models like '{}/claude-sonnet-4-5@20250929'",
+            local_id
+        )
+        .blue()
+    );
+
+    Ok(())
+}
+
+async fn login_mistral() -> Result<()> {
+    println!("\n{}", "Mistral Setup".cyan().bold());
+    println!("{}", "═".repeat(50).cyan());
+
+    println!(
+        "{}",
+        "Get your API key from https://console.mistral.ai/api-keys".yellow()
+    );
+    let api_key = Text::new("API key:")
+        .with_validator(|input: &str| {
+            if input.trim().is_empty() {
+                Err(Box::from("API key cannot be empty"))
+            } else {
+                Ok(inquire::validator::Validation::Valid)
+            }
+        })
+        .prompt()
+        .map_err(|e| Error::Prompt(e.to_string()))?;
+
+    println!(
+        "{}",
+        "Codestral keys use https://codestral.mistral.ai/v1 instead.".bright_black()
+    );
+    let base_url = Text::new("Base URL:")
+        .with_default(crate::provider::mistral::DEFAULT_BASE_URL)
+        .prompt()
+        .map_err(|e| Error::Prompt(e.to_string()))?;
+
+    let mut config = ConfigFile::load()?;
+    let local_id = determine_local_id(&config, ProviderType::Mistral)?;
+
+    config.set_provider(
+        local_id.clone(),
+        ProviderConfig::Mistral(MistralProviderConfig {
+            enabled: true,
+            api_key: api_key.trim().to_string(),
+            base_url: base_url.trim().to_string(),
+            fim_model: crate::provider::mistral::DEFAULT_FIM_MODEL.to_string(),
+            model_configs: Vec::new(),
+        }),
+    );
+    config.save()?;
+
+    println!(
+        "\n{}",
+        format!("✓ Mistral '{}' configured successfully!", local_id)
+            .green()
+            .bold()
+    );
+    println!(
+        "{}",
+        format!(
+            "You can now select models like '{}/devstral-medium-latest'",
+            local_id
+        )
+        .blue()
+    );
+
+    Ok(())
+}
+
 /// Determine the local identifier for a new provider account
 fn determine_local_id(config: &ConfigFile, provider_type: ProviderType) -> Result<String> {
     let existing_count = config.providers_of_type(provider_type).len();
@@ -1230,8 +1494,19 @@ async fn login_claude_oauth(auth_url: &str, target_provider_id: Option<String>)
         .as_millis() as u64
         + (expires_in * 1000);
 
+    let mut config = ConfigFile::load()?;
+    let local_id = if let Some(id) = target_provider_id {
+        id
+    } else {
+        determine_local_id(&config, ProviderType::Claude)?
+    };
+
+    // Keep the endpoint and beta settings of an existing instance
+    let existing = config.get_provider(&local_id).and_then(|p| p.as_claude());
     let claude_config = ClaudeProviderConfig {
         enabled: true,
+        base_url: existing.and_then(|c| c.base_url.clone()),
+        betas: existing.map(|c| c.betas.clone()).unwrap_or_default(),
         auth: ClaudeAuth {
             refresh_token: refresh_token.to_string(),
             access_token: access_token.to_string(),
@@ -1239,13 +1514,6 @@ async fn login_claude_oauth(auth_url: &str, target_provider_id: Option<String>)
         },
     };
 
-    let mut config = ConfigFile::load()?;
-    let local_id = if let Some(id) = target_provider_id {
-        id
-    } else {
-        determine_local_id(&config, ProviderType::Claude)?
-    };
-
     config.set_provider(local_id.clone(), ProviderConfig::Claude(claude_config));
     config.save()?;
 
@@ -13,6 +13,7 @@ use crate::provider::{
     ChatResponse, ContentBlock, Message, MessageContent, Provider, Role, StopReason,
 };
 use crate::services::Services;
+use crate::timeline::Timeline;
 use crate::tools;
 
 /// Maximum number of retries for the session-level "slow" retry loop.
@@ -22,22 +23,52 @@ const MAX_RETRIES: u32 = 5;
 /// Initial delay between retries (doubles with each attempt)
 const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(2);
 
+/// Longest wait a rate-limited response may ask for before the request
+/// fails instead.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// Maximum number of rate-limit waits for a single request.
+const MAX_RATE_LIMIT_WAITS: u32 = 10;
+
+/// Set to cancel only the tool that is currently running.
+static CANCEL_TOOL: AtomicBool = AtomicBool::new(false);
+
+/// Whether a tool is currently running.
+static TOOL_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Cancel the in-flight tool without ending the turn. The model receives a
+/// "Cancelled by user" tool result and carries on.
+///
+/// Returns false if no tool is running.
+pub(crate) fn cancel_current_tool() -> bool {
+    if !TOOL_RUNNING.load(Ordering::SeqCst) {
+        return false;
+    }
+    CANCEL_TOOL.store(true, Ordering::SeqCst);
+    true
+}
+
 /// Result of a single chat iteration
 pub(crate) enum ChatIterationResult {
     /// Model finished responding, no more tool calls
     Done,
     /// Tools were executed, should continue with another iteration
     Continue,
+    /// The response was cut off by the output token limit
+    Truncated,
 }
 
 /// Send a chat request to the provider with retry logic for transient errors.
+/// Retries are counted in `timeline` when it is given.
 async fn send_with_retry<P: Provider>(
     provider: &P,
     messages: Vec<Message>,
     interrupted: &Arc<AtomicBool>,
     output: &output::OutputContext,
+    timeline: Option<&Timeline>,
 ) -> Result<ChatResponse> {
     let mut attempts = 0;
+    let mut rate_limit_waits = 0;
     let mut delay = INITIAL_RETRY_DELAY;
 
     loop {
@@ -64,10 +95,29 @@ async fn send_with_retry<P: Provider>(
             result = provider.chat(messages.clone(), output) => result
         };
 
+        // Rate limits say when to come back, so wait that long without
+        // using up the retries.
+        if let Err(e) = &result
+            && let Error::RateLimited { retry_after, .. } = e
+            && *retry_after <= MAX_RATE_LIMIT_WAIT
+            && rate_limit_waits < MAX_RATE_LIMIT_WAITS
+        {
+            rate_limit_waits += 1;
+            if let Some(timeline) = timeline {
+                timeline.retry();
+            }
+            output::emit_rate_limited(output, &e.display_message(), *retry_after);
+            sleep_unless_interrupted(*retry_after, interrupted, output).await?;
+            continue;
+        }
+
         match result {
             Ok(response) => return Ok(response),
             Err(e) if e.is_retryable() && attempts < MAX_RETRIES => {
                 attempts += 1;
+                if let Some(timeline) = timeline {
+                    timeline.retry();
+                }
                 output::emit_warning(
                     output,
                     &format!(
@@ -80,18 +130,7 @@ async fn send_with_retry<P: Provider>(
                 );
 
                 // Wait before retrying, but check for interrupts
-                tokio::select! {
-                    biased;
-                    _ = async {
-                        while !interrupted.load(Ordering::SeqCst) {
-                            tokio::time::sleep(Duration::from_millis(100)).await;
-                        }
-                    } => {
-                        output::emit_interrupted(output);
-                        return Err(Error::Interrupted);
-                    }
-                    _ = tokio::time::sleep(delay) => {}
-                };
+                sleep_unless_interrupted(delay, interrupted, output).await?;
 
                 // Exponential backoff
                 delay *= 2;
@@ -104,10 +143,32 @@ async fn send_with_retry<P: Provider>(
     }
 }
 
+/// Sleep for `duration`, returning early with [`Error::Interrupted`] if the
+/// user interrupts.
+async fn sleep_unless_interrupted(
+    duration: Duration,
+    interrupted: &Arc<AtomicBool>,
+    output: &output::OutputContext,
+) -> Result<()> {
+    tokio::select! {
+        biased;
+        _ = async {
+            while !interrupted.load(Ordering::SeqCst) {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        } => {
+            output::emit_interrupted(output);
+            Err(Error::Interrupted)
+        }
+        _ = tokio::time::sleep(duration) => Ok(()),
+    }
+}
+
 /// Run a single chat iteration: one provider call plus tool execution if needed.
 ///
-/// Returns `Done` if the model finished, or `Continue` if tools were executed
-/// and another iteration should follow.
+/// Returns `Done` if the model finished, `Continue` if tools were executed
+/// and another iteration should follow, or `Truncated` if the response hit the
+/// output token limit.
 pub(crate) async fn run_chat_iteration<P: Provider>(
     provider: &P,
     messages: &mut Vec<Message>,
@@ -121,13 +182,51 @@ pub(crate) async fn run_chat_iteration<P: Provider>(
         return Err(Error::Interrupted);
     }
 
-    // Send the chat request with retry logic for transient errors
-    let response = send_with_retry(provider, messages.clone(), interrupted, output).await?;
+    // Send the chat request with retry logic for transient errors. The
+    // requests of delegated tasks are part of the task's tool call and
+    // aren't recorded in the timeline.
+    let timeline = (!tools::task::in_task()).then_some(&services.timeline);
+    let response = match timeline {
+        Some(timeline) => {
+            timeline.begin_request();
+            let observed = output.with_observer(timeline.clone());
+            let response = send_with_retry(
+                provider,
+                messages.clone(),
+                interrupted,
+                &observed,
+                Some(timeline),
+            )
+            .await;
+            timeline.end_request(response.is_err());
+            response?
+        }
+        None => send_with_retry(provider, messages.clone(), interrupted, output, None).await?,
+    };
+
+    // Journal the response and each tool result as they arrive, so a crash
+    // mid-turn doesn't lose them. A delegated task's conversation isn't part
+    // of the session.
+    let journal = (!tools::task::in_task()).then(|| services.journal.clone());
+    let journal_message = |message: &Message| {
+        if let Some(journal) = &journal {
+            journal.append(message);
+        }
+    };
+    if !response.content_blocks.is_empty() {
+        journal_message(&Message::assistant_blocks(response.content_blocks.clone()));
+    }
 
     // If no tool calls, add the response and we're done
     if response.stop_reason != StopReason::ToolUse || response.tool_calls.is_empty() {
-        if !response.content_blocks.is_empty() {
-            messages.push(Message::assistant_blocks(response.content_blocks.clone()));
+        if response.content_blocks.is_empty() {
+            output::emit_done(output);
+            return Ok(ChatIterationResult::Done);
+        }
+        messages.push(Message::assistant_blocks(response.content_blocks.clone()));
+        if response.stop_reason == StopReason::MaxTokens && response.tool_calls.is_empty() {
+            // The caller decides whether to continue, so don't signal completion yet.
+            return Ok(ChatIterationResult::Truncated);
         }
         output::emit_done(output);
         return Ok(ChatIterationResult::Done);
@@ -136,6 +235,8 @@ pub(crate) async fn run_chat_iteration<P: Provider>(
     // Execute each tool call and collect results
     let mut tool_results: Vec<ContentBlock> = Vec::new();
     let services = services.with_interrupted(interrupted.clone());
+    let definitions = tools::all_definitions(&services).await;
+    let hooks = crate::hooks::load();
 
     for tool_call in &response.tool_calls {
         // Check for interrupt before starting each tool
@@ -147,14 +248,91 @@ pub(crate) async fn run_chat_iteration<P: Provider>(
         let description = tools::format_tool_call_description(&tool_call.name, &tool_call.input);
         output::print_tool_call(output, &tool_call.name, &description);
 
-        let result = tools::execute(
+        // Check the arguments before running anything, so the model gets told
+        // what's wrong with them.
+        let mut input = tool_call.input.clone();
+        let invalid = definitions
+            .iter()
+            .find(|d| d.name == tool_call.name)
+            .and_then(|d| tools::schema::validate(&d.input_schema, &mut input).err())
+            .map(|errors| {
+                tools::schema::invalid_arguments(&tool_call.id, &tool_call.name, &errors)
+            });
+
+        let blocked = if invalid.is_none() {
+            crate::hooks::pre_tool_use(&hooks, &tool_call.name, &input, output, &services).await
+        } else {
+            None
+        };
+        let hook_input = input.clone();
+        let ran = invalid.is_none() && blocked.is_none();
+
+        CANCEL_TOOL.store(false, Ordering::SeqCst);
+        TOOL_RUNNING.store(true, Ordering::SeqCst);
+        let started = std::time::Instant::now();
+        let result = if invalid.is_some() {
+            invalid
+        } else if let Some(reason) = blocked {
+            Some(tools::ToolResult::error(&tool_call.id, reason).with_summary("Blocked by hook"))
+        } else {
+            tokio::select! {
+                biased;
+                _ = async {
+                    while !CANCEL_TOOL.load(Ordering::SeqCst) {
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                } => {
+                    Some(tools::ToolResult::error(&tool_call.id, "Cancelled by user")
+                        .with_summary("Cancelled"))
+                }
+                result = async {
+                    if tools::canonicalize_builtin_tool_name(&tool_call.name) == Some("task") {
+                        Some(tools::task::run(
+                            provider,
+                            &tool_call.id,
+                            input,
+                            interrupted,
+                            output,
+                            &services,
+                        ).await)
+                    } else {
+                        tools::execute(
+                            &tool_call.name,
+                            &tool_call.id,
+                            input,
+                            output,
+                            &services,
+                        ).await
+                    }
+                } => result,
+            }
+        };
+        // A delegated task is still running while its own tools finish.
+        TOOL_RUNNING.store(tools::task::in_task(), Ordering::SeqCst);
+        crate::tool_metrics::record(
             &tool_call.name,
-            &tool_call.id,
-            tool_call.input.clone(),
-            output,
-            &services,
-        )
-        .await;
+            result.as_ref().is_none_or(|r| r.is_error),
+            started.elapsed(),
+        );
+        if let Some(timeline) = timeline {
+            timeline.record_tool(
+                &description,
+                started,
+                result.as_ref().map_or(0, |r| r.content.len()),
+                result.as_ref().is_none_or(|r| r.is_error),
+            );
+        }
+        if ran && let Some(tool_result) = &result {
+            crate::hooks::post_tool_use(
+                &hooks,
+                &tool_call.name,
+                &hook_input,
+                tool_result,
+                output,
+                &services,
+            )
+            .await;
+        }
 
         match result {
             Some(tool_result) => {
@@ -201,6 +379,12 @@ pub(crate) async fn run_chat_iteration<P: Provider>(
                 });
             }
         }
+        if let Some(result) = tool_results.last() {
+            journal_message(&Message {
+                role: Role::User,
+                content: MessageContent::Blocks(vec![result.clone()]),
+            });
+        }
 
         // Check for interrupt after each tool execution
         if interrupted.load(Ordering::SeqCst) {
@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Per-turn checkpoints of files changed by the edit and write tools.
+//!
+//! Before a tool first modifies a file during a turn, its original content
+//! (or the fact that it did not exist) is recorded. `/revert` restores the
+//! files changed by the most recent turn. Changes made through bash are not
+//! tracked.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Number of turns kept for reverting.
+const MAX_TURNS: usize = 20;
+
+/// Original state of the files changed in one turn, in the order they were
+/// first changed. `None` means the file did not exist.
+type Turn = Vec<(PathBuf, Option<Vec<u8>>)>;
+
+/// Checkpoints of the most recent turns, shared through
+/// [`crate::services::Services`].
+#[derive(Default)]
+pub(crate) struct Checkpoints {
+    turns: Mutex<Vec<Turn>>,
+    /// When the most recent turn started
+    turn_started: Mutex<Option<SystemTime>>,
+}
+
+impl Checkpoints {
+    /// Start a new turn. Turns without changes are kept, one per turn of
+    /// the conversation, so `/rollback` stays in step with it; `/revert`
+    /// skips them.
+    pub(crate) fn begin_turn(&self) {
+        *self.turn_started.lock().unwrap() = Some(SystemTime::now());
+        let mut turns = self.turns.lock().unwrap();
+        turns.push(Vec::new());
+        if turns.len() > MAX_TURNS {
+            turns.remove(0);
+        }
+    }
+
+    /// Record the current state of `path` before it is modified, unless it
+    /// was already recorded this turn.
+    pub(crate) fn record(&self, path: &Path) {
+        let mut turns = self.turns.lock().unwrap();
+        if turns.is_empty() {
+            turns.push(Vec::new());
+        }
+        let turn = turns.last_mut().unwrap();
+        if turn.iter().any(|(p, _)| p == path) {
+            return;
+        }
+        let original = match std::fs::read(path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            // Unreadable files can't be restored, so leave them out
+            Err(_) => return,
+        };
+        turn.push((path.to_path_buf(), original));
+    }
+
+    /// When the most recent turn started, if one has.
+    pub(crate) fn turn_started(&self) -> Option<SystemTime> {
+        *self.turn_started.lock().unwrap()
+    }
+
+    /// The files changed by the most recent turn alone, which is empty when
+    /// that turn changed none.
+    pub(crate) fn latest(&self) -> Vec<PathBuf> {
+        let turns = self.turns.lock().unwrap();
+        turns
+            .last()
+            .map(|turn| turn.iter().map(|(path, _)| path.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Restore the files changed by the most recent turn that changed any.
+    ///
+    /// Returns the restored paths, or an empty list when there is nothing to
+    /// revert.
+    pub(crate) fn revert(&self) -> io::Result<Vec<PathBuf>> {
+        let mut turns = self.turns.lock().unwrap();
+        while turns.last().is_some_and(|turn| turn.is_empty()) {
+            turns.pop();
+        }
+        match turns.pop() {
+            Some(turn) => restore(turn),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Drop the most recent turn and restore the files it changed. Unlike
+    /// `revert`, a turn that changed nothing doesn't fall through to an
+    /// earlier one, but it is still dropped so the checkpoints keep in step
+    /// with the conversation.
+    pub(crate) fn revert_latest(&self) -> io::Result<Vec<PathBuf>> {
+        match self.turns.lock().unwrap().pop() {
+            Some(turn) => restore(turn),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Put the files of `turn` back the way they were, in reverse order of
+/// change.
+fn restore(turn: Turn) -> io::Result<Vec<PathBuf>> {
+    let mut restored = Vec::new();
+    for (path, original) in turn.into_iter().rev() {
+        match original {
+            Some(content) => std::fs::write(&path, content)?,
+            None => {
+                if let Err(e) = std::fs::remove_file(&path)
+                    && e.kind() != io::ErrorKind::NotFound
+                {
+                    return Err(e);
+                }
+            }
+        }
+        restored.push(path);
+    }
+    restored.reverse();
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revert_restores_last_turn() {
+        let dir = tempfile::tempdir().unwrap();
+        let edited = dir.path().join("edited.txt");
+        let created = dir.path().join("created.txt");
+        std::fs::write(&edited, "one").unwrap();
+
+        let checkpoints = Checkpoints::default();
+        checkpoints.begin_turn();
+        checkpoints.record(&edited);
+        std::fs::write(&edited, "two").unwrap();
+
+        checkpoints.begin_turn();
+        checkpoints.record(&edited);
+        std::fs::write(&edited, "three").unwrap();
+        checkpoints.record(&created);
+        std::fs::write(&created, "new").unwrap();
+        // A second change in the same turn keeps the first checkpoint
+        checkpoints.record(&edited);
+        std::fs::write(&edited, "four").unwrap();
+
+        // A turn without changes is skipped
+        checkpoints.begin_turn();
+
+        assert!(checkpoints.latest().is_empty());
+        assert_eq!(
+            checkpoints.revert().unwrap(),
+            vec![edited.clone(), created.clone()]
+        );
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "two");
+        assert!(!created.exists());
+
+        assert_eq!(checkpoints.revert().unwrap(), vec![edited.clone()]);
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "one");
+
+        assert!(checkpoints.revert().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_revert_latest_stops_at_empty_turn() {
+        let dir = tempfile::tempdir().unwrap();
+        let edited = dir.path().join("edited.txt");
+        std::fs::write(&edited, "one").unwrap();
+
+        let checkpoints = Checkpoints::default();
+        checkpoints.begin_turn();
+        checkpoints.record(&edited);
+        std::fs::write(&edited, "two").unwrap();
+        checkpoints.begin_turn();
+        checkpoints.begin_turn();
+
+        // The two latest turns changed nothing, so the earlier edit stays
+        // until the rollback that reaches its turn
+        assert!(checkpoints.latest().is_empty());
+        assert!(checkpoints.revert_latest().unwrap().is_empty());
+        assert!(checkpoints.latest().is_empty());
+        assert!(checkpoints.revert_latest().unwrap().is_empty());
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "two");
+
+        assert_eq!(checkpoints.latest(), vec![edited.clone()]);
+        assert_eq!(checkpoints.revert_latest().unwrap(), vec![edited.clone()]);
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "one");
+        assert!(checkpoints.revert_latest().unwrap().is_empty());
+    }
+}
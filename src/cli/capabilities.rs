@@ -0,0 +1,371 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Detection of terminal capabilities, and fallbacks for what is missing.
+//!
+//! - Without the kitty keyboard protocol, Ctrl+M can't be told apart from
+//!   Enter or Shift+Enter from Enter, so their alternatives are shown instead.
+//! - Without bracketed paste, an Enter that arrives with more input already
+//!   waiting is taken to be part of a paste and inserts a newline.
+//! - RGB colors are reduced to the 256-color palette, the 16 basic colors or
+//!   none, depending on `COLORTERM`, `TERM` and `NO_COLOR`.
+//!
+//! `/doctor terminal` shows what was detected.
+
+use std::io;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crossterm::event::{
+    DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
+};
+use crossterm::execute;
+use crossterm::style::Color;
+
+static DETECTED: OnceLock<Capabilities> = OnceLock::new();
+
+/// How many colors the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ColorDepth {
+    None,
+    Basic,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    fn label(self) -> &'static str {
+        match self {
+            ColorDepth::None => "none",
+            ColorDepth::Basic => "16 colors",
+            ColorDepth::Ansi256 => "256 colors",
+            ColorDepth::TrueColor => "truecolor",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Capabilities {
+    pub keyboard_enhancement: bool,
+    pub bracketed_paste: bool,
+    pub color_depth: ColorDepth,
+}
+
+/// Environment variables that detection is based on.
+#[derive(Debug, Default)]
+struct Env {
+    term: Option<String>,
+    colorterm: Option<String>,
+    term_program: Option<String>,
+    no_color: bool,
+    windows_terminal: bool,
+}
+
+impl Env {
+    fn current() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+        Self {
+            term: var("TERM"),
+            colorterm: var("COLORTERM"),
+            term_program: var("TERM_PROGRAM"),
+            no_color: var("NO_COLOR").is_some(),
+            windows_terminal: var("WT_SESSION").is_some(),
+        }
+    }
+
+    fn color_depth(&self) -> ColorDepth {
+        let term = self.term.as_deref().unwrap_or_default();
+        if self.no_color || term == "dumb" {
+            return ColorDepth::None;
+        }
+        if matches!(self.colorterm.as_deref(), Some("truecolor" | "24bit"))
+            || self.windows_terminal
+            || matches!(
+                self.term_program.as_deref(),
+                Some("iTerm.app" | "WezTerm" | "vscode" | "ghostty")
+            )
+            || ["kitty", "alacritty", "wezterm", "foot", "ghostty"]
+                .iter()
+                .any(|t| term.contains(t))
+        {
+            return ColorDepth::TrueColor;
+        }
+        if term.contains("256color") {
+            return ColorDepth::Ansi256;
+        }
+        ColorDepth::Basic
+    }
+
+    /// Bracketed paste can't be queried, so it is assumed except where it is
+    /// known to be missing: dumb terminals, the Linux console and the legacy
+    /// Windows console.
+    fn bracketed_paste(&self) -> bool {
+        match self.term.as_deref() {
+            Some("dumb" | "linux") => false,
+            Some(_) => true,
+            None => !cfg!(windows) || self.windows_terminal,
+        }
+    }
+}
+
+/// Query the terminal and record its capabilities. Call once in raw mode,
+/// before reading any events.
+pub(super) fn detect() {
+    let env = Env::current();
+    let keyboard_enhancement =
+        crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false);
+    let _ = DETECTED.set(Capabilities {
+        keyboard_enhancement,
+        bracketed_paste: env.bracketed_paste(),
+        color_depth: env.color_depth(),
+    });
+}
+
+/// The detected capabilities, or what the environment suggests if the
+/// terminal wasn't queried (as in batch mode).
+pub(super) fn get() -> &'static Capabilities {
+    DETECTED.get_or_init(|| {
+        let env = Env::current();
+        Capabilities {
+            keyboard_enhancement: false,
+            bracketed_paste: env.bracketed_paste(),
+            color_depth: env.color_depth(),
+        }
+    })
+}
+
+/// Turn on keyboard enhancement and bracketed paste where supported.
+pub(super) fn enable_input_modes() -> io::Result<()> {
+    let caps = get();
+    let mut stdout = io::stdout();
+    if caps.keyboard_enhancement {
+        execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )?;
+    }
+    if caps.bracketed_paste {
+        execute!(stdout, EnableBracketedPaste)?;
+    }
+    Ok(())
+}
+
+/// Undo [`enable_input_modes`].
+pub(super) fn disable_input_modes() -> io::Result<()> {
+    let caps = get();
+    let mut stdout = io::stdout();
+    if caps.keyboard_enhancement {
+        execute!(stdout, PopKeyboardEnhancementFlags)?;
+    }
+    if caps.bracketed_paste {
+        execute!(stdout, DisableBracketedPaste)?;
+    }
+    Ok(())
+}
+
+/// Without bracketed paste, turn an Enter that has more input right behind
+/// it into Ctrl+J, so a pasted line break inserts a newline rather than
+/// submitting the first line.
+pub(super) fn paste_fallback(event: Event) -> Event {
+    match event {
+        Event::Key(KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            ..
+        }) if !get().bracketed_paste && crossterm::event::poll(Duration::ZERO).unwrap_or(false) => {
+            Event::Key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL))
+        }
+        event => event,
+    }
+}
+
+/// The shortcut that opens the model menu.
+pub(super) fn model_menu_key() -> &'static str {
+    if get().keyboard_enhancement {
+        "Ctrl+M"
+    } else {
+        "Ctrl+P"
+    }
+}
+
+/// An RGB color, reduced to what the terminal can show.
+pub(super) fn rgb(r: u8, g: u8, b: u8) -> Color {
+    reduce(r, g, b, get().color_depth)
+}
+
+fn reduce(r: u8, g: u8, b: u8, depth: ColorDepth) -> Color {
+    match depth {
+        ColorDepth::TrueColor => Color::Rgb { r, g, b },
+        ColorDepth::Ansi256 => Color::AnsiValue(ansi256(r, g, b)),
+        ColorDepth::Basic => basic(r, g, b),
+        ColorDepth::None => Color::Reset,
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// Nearest entry of the 6x6x6 color cube or the grayscale ramp.
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |v: u8| {
+        (0..6)
+            .min_by_key(|&i| (LEVELS[i] as i32 - v as i32).abs())
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_index = (avg.saturating_sub(8) / 10).min(23) as u8;
+    let gray_value = 8 + 10 * gray_index;
+    let gray = (gray_value, gray_value, gray_value);
+
+    if distance((r, g, b), gray) < distance((r, g, b), cube) {
+        232 + gray_index
+    } else {
+        (16 + 36 * ri + 6 * gi + bi) as u8
+    }
+}
+
+/// Nearest of the 16 basic colors.
+fn basic(r: u8, g: u8, b: u8) -> Color {
+    const COLORS: [(Color, (u8, u8, u8)); 16] = [
+        (Color::Black, (0, 0, 0)),
+        (Color::DarkRed, (128, 0, 0)),
+        (Color::DarkGreen, (0, 128, 0)),
+        (Color::DarkYellow, (128, 128, 0)),
+        (Color::DarkBlue, (0, 0, 128)),
+        (Color::DarkMagenta, (128, 0, 128)),
+        (Color::DarkCyan, (0, 128, 128)),
+        (Color::Grey, (192, 192, 192)),
+        (Color::DarkGrey, (128, 128, 128)),
+        (Color::Red, (255, 0, 0)),
+        (Color::Green, (0, 255, 0)),
+        (Color::Yellow, (255, 255, 0)),
+        (Color::Blue, (0, 0, 255)),
+        (Color::Magenta, (255, 0, 255)),
+        (Color::Cyan, (0, 255, 255)),
+        (Color::White, (255, 255, 255)),
+    ];
+    COLORS
+        .iter()
+        .min_by_key(|(_, rgb)| distance((r, g, b), *rgb))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+/// The capability matrix for `/doctor terminal`.
+pub(super) fn report() -> Vec<String> {
+    let caps = get();
+    let env = Env::current();
+    let yes_no = |v: bool| if v { "yes" } else { "no" };
+    let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "(unset)".to_string());
+
+    let mut lines = vec![
+        format!(
+            "TERM={} COLORTERM={} TERM_PROGRAM={}",
+            show(&env.term),
+            show(&env.colorterm),
+            show(&env.term_program)
+        ),
+        format!(
+            "  Keyboard enhancement  {}",
+            yes_no(caps.keyboard_enhancement)
+        ),
+        format!(
+            "  Bracketed paste       {}",
+            if caps.bracketed_paste {
+                "yes (assumed)"
+            } else {
+                "no"
+            }
+        ),
+        format!("  Colors                {}", caps.color_depth.label()),
+    ];
+
+    let mut fallbacks = Vec::new();
+    if !caps.keyboard_enhancement {
+        fallbacks.push("Switch model: Ctrl+P instead of Ctrl+M");
+        fallbacks.push("Newline: Alt+Enter or Ctrl+J instead of Shift+Enter");
+        fallbacks.push("Cancel only the running tool: Ctrl+Esc is unavailable");
+    }
+    if !caps.bracketed_paste {
+        fallbacks
+            .push("Paste: line breaks are detected by timing; Ctrl+V pastes from the clipboard");
+    }
+    if caps.color_depth != ColorDepth::TrueColor {
+        fallbacks.push("Colors: RGB colors are reduced to the nearest available color");
+    }
+    if !fallbacks.is_empty() {
+        lines.push("Fallbacks in use:".to_string());
+        lines.extend(fallbacks.iter().map(|f| format!("  {}", f)));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(term: &str, colorterm: Option<&str>) -> Env {
+        Env {
+            term: Some(term.to_string()),
+            colorterm: colorterm.map(String::from),
+            ..Env::default()
+        }
+    }
+
+    #[test]
+    fn test_color_depth() {
+        assert_eq!(
+            env("xterm-256color", Some("truecolor")).color_depth(),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            env("xterm-256color", None).color_depth(),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(env("xterm", None).color_depth(), ColorDepth::Basic);
+        assert_eq!(
+            env("xterm-kitty", None).color_depth(),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(env("dumb", None).color_depth(), ColorDepth::None);
+        let no_color = Env {
+            no_color: true,
+            ..env("xterm-256color", Some("truecolor"))
+        };
+        assert_eq!(no_color.color_depth(), ColorDepth::None);
+
+        assert!(env("xterm", None).bracketed_paste());
+        assert!(!env("linux", None).bracketed_paste());
+    }
+
+    #[test]
+    fn test_reduce() {
+        assert_eq!(
+            reduce(20, 20, 20, ColorDepth::Ansi256),
+            Color::AnsiValue(233)
+        );
+        assert_eq!(
+            reduce(137, 180, 250, ColorDepth::Ansi256),
+            Color::AnsiValue(111)
+        );
+        assert_eq!(
+            reduce(255, 0, 0, ColorDepth::Ansi256),
+            Color::AnsiValue(196)
+        );
+        assert_eq!(reduce(20, 20, 20, ColorDepth::Basic), Color::Black);
+        assert_eq!(reduce(200, 200, 200, ColorDepth::Basic), Color::Grey);
+        assert_eq!(reduce(1, 2, 3, ColorDepth::None), Color::Reset);
+        assert_eq!(
+            reduce(1, 2, 3, ColorDepth::TrueColor),
+            Color::Rgb { r: 1, g: 2, b: 3 }
+        );
+    }
+}
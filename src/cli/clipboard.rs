@@ -3,8 +3,10 @@
 
 //! Clipboard operations for the CLI
 
-use std::io;
-use std::process::Command;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use base64::Engine;
 
 /// Try to paste text from clipboard
 pub(crate) fn paste_text() -> io::Result<String> {
@@ -31,6 +33,48 @@ pub(crate) fn paste_text() -> io::Result<String> {
     Err(io::Error::other("No text in clipboard"))
 }
 
+/// Copy text to the clipboard.
+///
+/// Uses wl-copy or xclip on a local display. Over SSH, or when neither is
+/// available, the text is sent to the terminal with OSC 52, which most
+/// terminals put on the clipboard of the machine they run on.
+pub(crate) fn copy_text(text: &str) -> io::Result<()> {
+    let remote = std::env::var_os("SSH_CONNECTION").is_some();
+    if !remote {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() && pipe_to("wl-copy", &[], text).is_ok() {
+            return Ok(());
+        }
+        if std::env::var_os("DISPLAY").is_some()
+            && pipe_to("xclip", &["-selection", "clipboard"], text).is_ok()
+        {
+            return Ok(());
+        }
+    }
+
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let _guard = super::terminal::lock_output();
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", encoded)?;
+    stdout.flush()
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> io::Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    if child.wait()?.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{} failed", program)))
+    }
+}
+
 /// Try to paste an image from clipboard
 pub(crate) fn paste_image() -> io::Result<(Vec<u8>, String)> {
     wl_paste_image()
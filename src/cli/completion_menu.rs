@@ -14,6 +14,8 @@ use crossterm::style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor
 use crossterm::terminal::{self, ClearType};
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+use super::capabilities;
+
 /// Maximum number of completion items to display at once.
 const MENU_MAX_VISIBLE: usize = 10;
 
@@ -63,29 +65,13 @@ pub(super) fn render(
 
         let (bg_color, fg_color) = if is_selected {
             (
-                Color::Rgb {
-                    r: 30,
-                    g: 30,
-                    b: 30,
-                },
-                Color::Rgb {
-                    r: 200,
-                    g: 200,
-                    b: 200,
-                },
+                capabilities::rgb(30, 30, 30),
+                capabilities::rgb(200, 200, 200),
             )
         } else {
             (
-                Color::Rgb {
-                    r: 20,
-                    g: 20,
-                    b: 20,
-                },
-                Color::Rgb {
-                    r: 150,
-                    g: 150,
-                    b: 150,
-                },
+                capabilities::rgb(20, 20, 20),
+                capabilities::rgb(150, 150, 150),
             )
         };
 
@@ -0,0 +1,355 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Copying output without selecting it with the mouse: `/copy` copies the
+//! last response or a code block from it, and copy mode (Alt+C) moves a
+//! cursor through the rendered output to select any part of it, like tmux.
+
+use std::io::{self, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+use unicode_width::UnicodeWidthChar;
+
+use super::escapes;
+use crate::provider::{ContentBlock, Message, MessageContent, Role};
+
+/// What `/copy` copies.
+#[derive(Debug, PartialEq)]
+pub(super) enum CopyTarget {
+    /// The whole response
+    Response,
+    /// A code block, counted from the last one (1 is the last)
+    CodeBlock(usize),
+}
+
+impl CopyTarget {
+    /// Parse the arguments of `/copy`: nothing, `code` or `code <n>`.
+    pub(super) fn parse(args: &str) -> Option<Self> {
+        let mut words = args.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (None, _, _) => Some(CopyTarget::Response),
+            (Some("code"), None, _) => Some(CopyTarget::CodeBlock(1)),
+            (Some("code"), Some(n), None) => {
+                n.parse().ok().filter(|n| *n > 0).map(CopyTarget::CodeBlock)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The text of the last response.
+pub(super) fn last_response(messages: &[Message]) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .filter(|m| m.role == Role::Assistant)
+        .find_map(|m| {
+            let text = match &m.content {
+                MessageContent::Text(text) => text.clone(),
+                MessageContent::Blocks(blocks) => blocks
+                    .iter()
+                    .filter_map(|b| match b {
+                        ContentBlock::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n\n"),
+            };
+            (!text.trim().is_empty()).then_some(text)
+        })
+}
+
+/// The contents of the fenced code blocks in `text`, without the fences.
+pub(super) fn code_blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            match current.take() {
+                Some(lines) => blocks.push(lines.join("\n")),
+                None => current = Some(Vec::new()),
+            }
+        } else if let Some(lines) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    blocks
+}
+
+/// What a key does in copy mode.
+#[derive(Debug, PartialEq)]
+pub(super) enum CopyModeAction {
+    None,
+    /// Copy this text and leave copy mode
+    Copy(String),
+    /// Leave copy mode without copying
+    Exit,
+}
+
+/// Cursor and selection over the rendered output, without escapes.
+pub(super) struct CopyMode {
+    lines: Vec<Vec<char>>,
+    row: usize,
+    col: usize,
+    /// Where the selection started, if `v` was pressed
+    anchor: Option<(usize, usize)>,
+    /// First line on screen
+    top: usize,
+}
+
+impl CopyMode {
+    /// Start on the last line of `rendered`.
+    pub(super) fn new(rendered: &str) -> Self {
+        let text = escapes::strip(rendered).replace('\t', "    ");
+        let mut lines: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
+        while lines
+            .last()
+            .is_some_and(|l| l.iter().all(|c| c.is_whitespace()))
+        {
+            lines.pop();
+        }
+        if lines.is_empty() {
+            lines.push(Vec::new());
+        }
+        let row = lines.len() - 1;
+        Self {
+            lines,
+            row,
+            col: 0,
+            anchor: None,
+            top: 0,
+        }
+    }
+
+    fn last_col(&self, row: usize) -> usize {
+        self.lines[row].len().saturating_sub(1)
+    }
+
+    fn move_rows(&mut self, delta: isize) {
+        self.row = self
+            .row
+            .saturating_add_signed(delta)
+            .min(self.lines.len() - 1);
+        self.col = self.col.min(self.last_col(self.row));
+    }
+
+    /// Handle a key; `page` is the number of lines on screen.
+    pub(super) fn handle_key(&mut self, key: KeyEvent, page: usize) -> CopyModeAction {
+        let page = page.max(1) as isize;
+        match (key.code, key.modifiers) {
+            (KeyCode::Char('q') | KeyCode::Esc, _) => return CopyModeAction::Exit,
+            (KeyCode::Char('c'), KeyModifiers::CONTROL) => return CopyModeAction::Exit,
+            (KeyCode::Char('y') | KeyCode::Enter, _) => {
+                let text = self.selection().unwrap_or_else(|| {
+                    self.lines[self.row]
+                        .iter()
+                        .collect::<String>()
+                        .trim_end()
+                        .to_string()
+                });
+                return CopyModeAction::Copy(text);
+            }
+            (KeyCode::Char('v') | KeyCode::Char(' '), _) => {
+                self.anchor = match self.anchor {
+                    Some(_) => None,
+                    None => Some((self.row, self.col)),
+                };
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => self.move_rows(-page / 2),
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) => self.move_rows(page / 2),
+            (KeyCode::Char('b'), KeyModifiers::CONTROL) | (KeyCode::PageUp, _) => {
+                self.move_rows(-page)
+            }
+            (KeyCode::Char('f'), KeyModifiers::CONTROL) | (KeyCode::PageDown, _) => {
+                self.move_rows(page)
+            }
+            (KeyCode::Char('k') | KeyCode::Up, _) => self.move_rows(-1),
+            (KeyCode::Char('j') | KeyCode::Down, _) => self.move_rows(1),
+            (KeyCode::Char('h') | KeyCode::Left, _) => self.col = self.col.saturating_sub(1),
+            (KeyCode::Char('l') | KeyCode::Right, _) => {
+                self.col = (self.col + 1).min(self.last_col(self.row))
+            }
+            (KeyCode::Char('0') | KeyCode::Home, _) => self.col = 0,
+            (KeyCode::Char('$') | KeyCode::End, _) => self.col = self.last_col(self.row),
+            (KeyCode::Char('g'), _) => {
+                self.row = 0;
+                self.col = self.col.min(self.last_col(0));
+            }
+            (KeyCode::Char('G'), _) => self.move_rows(isize::MAX),
+            _ => {}
+        }
+        CopyModeAction::None
+    }
+
+    /// The selected text, from the anchor to the cursor inclusive.
+    pub(super) fn selection(&self) -> Option<String> {
+        let (start, end) = self.selection_range()?;
+        let mut lines = Vec::new();
+        for row in start.0..=end.0 {
+            let line = &self.lines[row];
+            let from = if row == start.0 { start.1 } else { 0 };
+            let to = if row == end.0 {
+                (end.1 + 1).min(line.len())
+            } else {
+                line.len()
+            };
+            let text: String = line[from.min(to)..to].iter().collect();
+            lines.push(text.trim_end().to_string());
+        }
+        Some(lines.join("\n"))
+    }
+
+    fn selection_range(&self) -> Option<((usize, usize), (usize, usize))> {
+        let anchor = self.anchor?;
+        let cursor = (self.row, self.col);
+        Some((anchor.min(cursor), anchor.max(cursor)))
+    }
+
+    fn is_selected(&self, row: usize, col: usize) -> bool {
+        self.selection_range()
+            .is_some_and(|(start, end)| (row, col) >= start && (row, col) <= end)
+    }
+
+    /// Scroll so the cursor is on screen.
+    fn scroll_to_cursor(&mut self, height: usize) {
+        if self.row < self.top {
+            self.top = self.row;
+        } else if self.row >= self.top + height {
+            self.top = self.row + 1 - height;
+        }
+    }
+
+    fn draw(&mut self, out: &mut impl Write, width: usize, height: usize) -> io::Result<()> {
+        self.scroll_to_cursor(height);
+        queue!(out, Clear(ClearType::All))?;
+        for (i, line) in self.lines.iter().skip(self.top).take(height).enumerate() {
+            let row = self.top + i;
+            queue!(out, cursor::MoveTo(0, i as u16))?;
+            let mut used = 0;
+            for (col, &c) in line.iter().enumerate() {
+                used += c.width().unwrap_or(0);
+                if used > width {
+                    break;
+                }
+                let highlight = (row, col) == (self.row, self.col) || self.is_selected(row, col);
+                if highlight {
+                    queue!(out, SetAttribute(Attribute::Reverse), Print(c))?;
+                    queue!(out, SetAttribute(Attribute::NoReverse))?;
+                } else {
+                    queue!(out, Print(c))?;
+                }
+            }
+            if line.is_empty() && row == self.row {
+                queue!(out, SetAttribute(Attribute::Reverse), Print(' '))?;
+                queue!(out, SetAttribute(Attribute::NoReverse))?;
+            }
+        }
+        let status = format!(
+            " COPY  v select  y copy  q quit  [{}/{}]",
+            self.row + 1,
+            self.lines.len()
+        );
+        queue!(
+            out,
+            cursor::MoveTo(0, height as u16),
+            SetAttribute(Attribute::Reverse),
+            Print(format!("{:<width$}", status)),
+            SetAttribute(Attribute::Reset),
+        )?;
+        out.flush()
+    }
+}
+
+/// Run copy mode over `rendered` on the alternate screen until the user
+/// copies or quits. Returns the text to copy. Raw mode must be enabled.
+pub(super) fn run(rendered: &str) -> io::Result<Option<String>> {
+    let mut mode = CopyMode::new(rendered);
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, cursor::Hide)?;
+    let result = (|| loop {
+        let (width, height) = terminal::size()?;
+        let page = (height as usize).saturating_sub(1).max(1);
+        mode.draw(&mut stdout, width as usize, page)?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match mode.handle_key(key, page) {
+                CopyModeAction::None => {}
+                CopyModeAction::Copy(text) => return Ok(Some(text)),
+                CopyModeAction::Exit => return Ok(None),
+            }
+        }
+    })();
+    let _ = execute!(stdout, LeaveAlternateScreen, cursor::Show);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_targets() {
+        assert_eq!(CopyTarget::parse(""), Some(CopyTarget::Response));
+        assert_eq!(CopyTarget::parse("code"), Some(CopyTarget::CodeBlock(1)));
+        assert_eq!(CopyTarget::parse("code 2"), Some(CopyTarget::CodeBlock(2)));
+        assert_eq!(CopyTarget::parse("code 0"), None);
+        assert_eq!(CopyTarget::parse("all"), None);
+
+        let messages = vec![
+            Message::user("show me"),
+            Message::assistant_blocks(vec![ContentBlock::Text {
+                text: "First:\n```rust\nfn a() {}\n```\nThen:\n```\nb\nc\n```".to_string(),
+            }]),
+            Message::user("thanks"),
+        ];
+        let response = last_response(&messages).unwrap();
+        assert!(response.starts_with("First:"));
+        assert_eq!(code_blocks(&response), vec!["fn a() {}", "b\nc"]);
+        assert_eq!(last_response(&messages[..1]), None);
+    }
+
+    fn press(mode: &mut CopyMode, keys: &str) -> CopyModeAction {
+        let mut action = CopyModeAction::None;
+        for c in keys.chars() {
+            action = mode.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), 10);
+        }
+        action
+    }
+
+    #[test]
+    fn test_copy_mode_selection() {
+        let mut mode = CopyMode::new("\x1b[1mfirst line\x1b[0m\nsecond\n\nthird line\n\n");
+        // Starts on the last non-blank line
+        assert_eq!(
+            press(&mut mode, "y"),
+            CopyModeAction::Copy("third line".into())
+        );
+
+        // Select from "line" on the first line to "sec" on the second
+        assert_eq!(press(&mut mode, "gllllll"), CopyModeAction::None);
+        assert_eq!(press(&mut mode, "vj0ll"), CopyModeAction::None);
+        assert_eq!(
+            press(&mut mode, "y"),
+            CopyModeAction::Copy("line\nsec".into())
+        );
+
+        // Selecting backwards gives the same text, the cursor is clamped to
+        // short lines, and `v` again clears the selection
+        let mut mode = CopyMode::new("abc\nlonger line");
+        press(&mut mode, "$vk");
+        assert_eq!(mode.selection().as_deref(), Some("c\nlonger line"));
+        press(&mut mode, "v");
+        assert_eq!(mode.selection(), None);
+
+        assert_eq!(press(&mut mode, "q"), CopyModeAction::Exit);
+        let mut mode = CopyMode::new("");
+        assert_eq!(
+            press(&mut mode, "jjGy"),
+            CopyModeAction::Copy(String::new())
+        );
+    }
+}
@@ -0,0 +1,255 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Removal of terminal escape sequences from untrusted output.
+//!
+//! Model responses and tool results can contain text from anywhere, such as
+//! a web page returned by the fetch tool. Escape sequences in that text
+//! could otherwise set the window title, write to the clipboard (OSC 52),
+//! hide lines or reset the terminal. Everything except newlines, tabs and the
+//! carriage return of a CRLF is stripped before printing; a bare carriage
+//! return could overwrite a line already on screen. With `ansi-colors`
+//! enabled, SGR (color and style) sequences are kept.
+
+use std::borrow::Cow;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::output::OutputEvent;
+
+static ALLOW_COLORS: AtomicBool = AtomicBool::new(false);
+
+/// Reload the ansi-colors setting from config
+pub(crate) fn reload_config() {
+    let enabled = crate::config::ConfigFile::load()
+        .map(|c| c.ansi_colors)
+        .unwrap_or(false);
+    ALLOW_COLORS.store(enabled, Ordering::Relaxed);
+}
+
+fn is_unsafe(c: char) -> bool {
+    c.is_control() && !matches!(c, '\n' | '\t' | '\r')
+}
+
+/// Whether `text` has a carriage return that doesn't end a line.
+fn has_bare_cr(text: &str) -> bool {
+    text.match_indices('\r')
+        .any(|(i, _)| text.as_bytes().get(i + 1) != Some(&b'\n'))
+}
+
+/// Strip escape sequences and control characters from `text`.
+pub(crate) fn sanitize(text: &str) -> Cow<'_, str> {
+    sanitize_with(text, ALLOW_COLORS.load(Ordering::Relaxed))
+}
+
+/// Strip every escape sequence and control character from `text`, colors
+/// included.
+pub(crate) fn strip(text: &str) -> Cow<'_, str> {
+    sanitize_with(text, false)
+}
+
+/// Strip escape sequences and control characters from `text`, always
+/// keeping colors. For output the user asked for, like a statusline command.
+pub(crate) fn sanitize_keeping_colors(text: &str) -> Cow<'_, str> {
+    sanitize_with(text, true)
+}
+
+fn sanitize_with(text: &str, allow_colors: bool) -> Cow<'_, str> {
+    if !text.chars().any(is_unsafe) && !has_bare_cr(text) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => skip_csi(&mut chars, &mut out, allow_colors),
+                Some(']' | 'P' | 'X' | '^' | '_') => skip_string(&mut chars),
+                // nF sequences like `ESC ( B` have intermediates before the final byte
+                Some('\x20'..='\x2f') => {
+                    while chars.next_if(|c| ('\x20'..='\x2f').contains(c)).is_some() {}
+                    chars.next();
+                }
+                _ => {}
+            },
+            // 8-bit forms of CSI and the string introducers
+            '\u{9b}' => skip_csi(&mut chars, &mut out, allow_colors),
+            '\u{90}' | '\u{98}' | '\u{9d}' | '\u{9e}' | '\u{9f}' => skip_string(&mut chars),
+            '\r' if chars.peek() != Some(&'\n') => {}
+            c if is_unsafe(c) => {}
+            c => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Consume a control sequence after its introducer, copying it to `out` if
+/// it is an allowed SGR sequence.
+fn skip_csi(chars: &mut Peekable<Chars<'_>>, out: &mut String, allow_colors: bool) {
+    let mut params = String::new();
+    while let Some(c) = chars.next_if(|c| ('\x30'..='\x3f').contains(c)) {
+        params.push(c);
+    }
+    let mut has_intermediates = false;
+    while chars.next_if(|c| ('\x20'..='\x2f').contains(c)).is_some() {
+        has_intermediates = true;
+    }
+    let Some(fin) = chars.next_if(|c| ('\x40'..='\x7e').contains(c)) else {
+        return;
+    };
+    if allow_colors
+        && fin == 'm'
+        && !has_intermediates
+        && params
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == ';' || c == ':')
+    {
+        out.push_str("\x1b[");
+        out.push_str(&params);
+        out.push('m');
+    }
+}
+
+/// Consume an OSC, DCS, APC, PM or SOS string up to its terminator.
+fn skip_string(chars: &mut Peekable<Chars<'_>>) {
+    while let Some(c) = chars.next() {
+        match c {
+            '\x07' | '\u{9c}' => return,
+            '\x1b' if chars.next_if_eq(&'\\').is_some() => return,
+            _ => {}
+        }
+    }
+}
+
+fn sanitize_opt(text: &Option<String>) -> Cow<'_, Option<String>> {
+    match text.as_deref().map(sanitize) {
+        Some(Cow::Owned(s)) => Cow::Owned(Some(s)),
+        _ => Cow::Borrowed(text),
+    }
+}
+
+/// Sanitize the text carried by an output event. Returns the event unchanged
+/// when there was nothing to strip.
+pub(crate) fn sanitize_event(event: &OutputEvent) -> Cow<'_, OutputEvent> {
+    let changed = match event {
+        OutputEvent::Thinking(text) => match sanitize(text) {
+            Cow::Owned(text) => OutputEvent::Thinking(text),
+            Cow::Borrowed(_) => return Cow::Borrowed(event),
+        },
+        OutputEvent::Text(text) => match sanitize(text) {
+            Cow::Owned(text) => OutputEvent::Text(text),
+            Cow::Borrowed(_) => return Cow::Borrowed(event),
+        },
+        OutputEvent::ToolCall { description } => match sanitize(description) {
+            Cow::Owned(description) => OutputEvent::ToolCall { description },
+            Cow::Borrowed(_) => return Cow::Borrowed(event),
+        },
+        OutputEvent::ToolResult {
+            tool_name,
+            is_error,
+            error_preview,
+            exit_code,
+            summary,
+        } => {
+            let (error_preview, summary) = (sanitize_opt(error_preview), sanitize_opt(summary));
+            if matches!(
+                (&error_preview, &summary),
+                (Cow::Borrowed(_), Cow::Borrowed(_))
+            ) {
+                return Cow::Borrowed(event);
+            }
+            OutputEvent::ToolResult {
+                tool_name: tool_name.clone(),
+                is_error: *is_error,
+                error_preview: error_preview.into_owned(),
+                exit_code: *exit_code,
+                summary: summary.into_owned(),
+            }
+        }
+        OutputEvent::ToolOutput { text } => match sanitize(text) {
+            Cow::Owned(text) => OutputEvent::ToolOutput { text },
+            Cow::Borrowed(_) => return Cow::Borrowed(event),
+        },
+        OutputEvent::FileReadOutput { filename, text } => {
+            let (name, body) = (sanitize(filename), sanitize(text));
+            if matches!((&name, &body), (Cow::Borrowed(_), Cow::Borrowed(_))) {
+                return Cow::Borrowed(event);
+            }
+            OutputEvent::FileReadOutput {
+                filename: name.into_owned(),
+                text: body.into_owned(),
+            }
+        }
+        OutputEvent::Info(message) => match sanitize(message) {
+            Cow::Owned(message) => OutputEvent::Info(message),
+            Cow::Borrowed(_) => return Cow::Borrowed(event),
+        },
+        OutputEvent::Error(message) => match sanitize(message) {
+            Cow::Owned(message) => OutputEvent::Error(message),
+            Cow::Borrowed(_) => return Cow::Borrowed(event),
+        },
+        OutputEvent::Warning(message) => match sanitize(message) {
+            Cow::Owned(message) => OutputEvent::Warning(message),
+            Cow::Borrowed(_) => return Cow::Borrowed(event),
+        },
+        OutputEvent::FileDiff {
+            diff,
+            language,
+            summary,
+        } => {
+            let (body, summary) = (sanitize(diff), sanitize_opt(summary));
+            if matches!((&body, &summary), (Cow::Borrowed(_), Cow::Borrowed(_))) {
+                return Cow::Borrowed(event);
+            }
+            OutputEvent::FileDiff {
+                diff: body.into_owned(),
+                language: language.clone(),
+                summary: summary.into_owned(),
+            }
+        }
+        _ => return Cow::Borrowed(event),
+    };
+    Cow::Owned(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_escape_sequences() {
+        let text = "a\x1b]0;pwned\x07b\x1b]52;c;ZWNobw==\x1b\\c\x1b[2J\x1b[31md\x1b[0m\x1bce\x1b(Bf\x08\u{9b}1mg\n\th";
+        assert_eq!(sanitize_with(text, false), "abcdefg\n\th");
+        assert_eq!(
+            sanitize_with(text, true),
+            "abc\x1b[31md\x1b[0mef\x1b[1mg\n\th"
+        );
+    }
+
+    #[test]
+    fn test_keeping_colors() {
+        assert_eq!(
+            sanitize_keeping_colors("\x1b[1;32mmain\x1b[0m\x1b[2J\tok\x07"),
+            "\x1b[1;32mmain\x1b[0m\tok"
+        );
+    }
+
+    #[test]
+    fn test_strips_bare_carriage_return() {
+        assert_eq!(sanitize_with("fine\rforged\r\n", false), "fineforged\r\n");
+    }
+
+    #[test]
+    fn test_clean_text_is_borrowed() {
+        assert!(matches!(
+            sanitize_with("plain\ttext\r\n", false),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            sanitize_event(&OutputEvent::Text("plain".to_string())),
+            Cow::Borrowed(_)
+        ));
+    }
+}
@@ -89,6 +89,8 @@ pub(crate) enum HistoryEvent {
     },
     /// Auto-compaction notification
     AutoCompact { message: String },
+    /// User annotation added with /note (never sent to the model)
+    Note(String),
 }
 
 /// Stores the conversation history as semantic events.
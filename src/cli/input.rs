@@ -15,7 +15,7 @@ use unicode_width::UnicodeWidthChar;
 
 use super::slash_menu::{SlashMenuState, extract_query};
 use crate::cli::PastedImage;
-use crate::commands::Command;
+use crate::commands::{ArgCompleter, Command};
 use crate::completion::FileCompleter;
 
 pub(super) const PROMPT: &str = "";
@@ -452,6 +452,8 @@ pub(super) enum InputAction {
     HistorySearch,
     /// Edit the current prompt in $VISUAL/$EDITOR (Ctrl+G)
     EditInEditor,
+    /// Enter copy mode to select output with the keyboard (Alt+C)
+    CopyMode,
     /// Cycle forward through favorite models (Ctrl+Y)
     CycleFavoritesForward,
     /// Cycle backward through favorite models (Shift+Ctrl+Y)
@@ -642,10 +644,20 @@ impl InputState {
     }
 
     /// Initialize file completion based on word at cursor
+    ///
+    /// After a slash command name, the command's argument completer is used
+    /// instead.
     pub fn init_completion(&mut self) {
-        let (_, _, word) =
-            crate::completion::get_word_at_cursor(&self.content(), self.cursor_byte_offset());
-        if word.is_empty() {
+        let content = self.content();
+        let (word_start, _, word) =
+            crate::completion::get_word_at_cursor(&content, self.cursor_byte_offset());
+        if let Some((completer, previous)) = slash_argument(&content, word_start) {
+            let mut matches = completer.words(&previous, &word);
+            if completer.completes_files() {
+                matches.extend(self.file_completer.get_matches(&word));
+            }
+            self.file_completer.init_with(matches);
+        } else if word.is_empty() {
             self.file_completer.clear();
         } else {
             self.file_completer.init(&word);
@@ -792,8 +804,11 @@ impl InputState {
                             self.clear();
                             return InputAction::OpenLspMenu;
                         }
-                        // For custom commands, insert with trailing space
-                        if matches!(selected.command, Command::Custom { .. }) {
+                        // For custom commands and MCP prompts, insert with trailing space
+                        if matches!(
+                            selected.command,
+                            Command::Custom { .. } | Command::McpPrompt { .. }
+                        ) {
                             self.lines[0] = format!("/{} ", selected.name);
                             self.col_idx = self.lines[0].len();
                             self.slash_menu = None;
@@ -890,7 +905,12 @@ impl InputState {
                             //
                             // Match Enter behavior for custom commands: insert a trailing space so
                             // the user can immediately type args.
-                            if matches!(selected.command, Command::Custom { .. }) {
+                            if matches!(
+                                selected.command,
+                                Command::Custom { .. } | Command::McpPrompt { .. }
+                            ) || crate::commands::arg_completer(&selected.name)
+                                != ArgCompleter::None
+                            {
                                 self.lines[0] = format!("/{} ", selected.name);
                             } else {
                                 self.lines[0] = format!("/{}", selected.name);
@@ -933,6 +953,18 @@ impl InputState {
                 }
             }
 
+            // Alt+Up / Alt+Down - on an empty prompt, rate the last answer
+            (KeyCode::Up | KeyCode::Down, KeyModifiers::ALT) if self.is_empty() => {
+                let rating = if key.code == KeyCode::Up {
+                    "up"
+                } else {
+                    "down"
+                };
+                self.lines[0] = format!("/feedback {}", rating);
+                self.col_idx = self.lines[0].len();
+                InputAction::Submit
+            }
+
             // Up - menu navigation, line navigation, or history
             (KeyCode::Up, _) => {
                 if menu_active {
@@ -1115,6 +1147,16 @@ impl InputState {
             // Ctrl+R - history search with fzf
             (KeyCode::Char('r'), KeyModifiers::CONTROL) => InputAction::HistorySearch,
 
+            // Ctrl+F - on an empty prompt, ask to fix pending diagnostics
+            (KeyCode::Char('f'), KeyModifiers::CONTROL) => {
+                if !self.is_empty() || !crate::lsp::quickfix::has_pending() {
+                    return InputAction::None;
+                }
+                self.lines[0] = "/fix".to_string();
+                self.col_idx = self.lines[0].len();
+                InputAction::Submit
+            }
+
             // Ctrl+G - edit prompt in editor
             (KeyCode::Char('g'), KeyModifiers::CONTROL) => InputAction::EditInEditor,
 
@@ -1153,6 +1195,9 @@ impl InputState {
             // Ctrl+N - Toggle compact mode
             (KeyCode::Char('n'), KeyModifiers::CONTROL) => InputAction::ToggleCompactMode,
 
+            // Alt+C - copy mode
+            (KeyCode::Char('c'), KeyModifiers::ALT) => InputAction::CopyMode,
+
             // Alt+B - Backward word (treats image markers as single tokens)
             (KeyCode::Char('b'), KeyModifiers::ALT) => {
                 if self.col_idx > 0 {
@@ -1449,6 +1494,22 @@ impl InputState {
 
 /// Check if an entry should be included in history navigation.
 /// Returns true for regular input and custom slash commands, false for built-in commands.
+/// When `word_start` is in the arguments of a slash command, the command's
+/// argument completer and the arguments before the one being typed.
+fn slash_argument(content: &str, word_start: usize) -> Option<(ArgCompleter, Vec<&str>)> {
+    let rest = content.strip_prefix('/')?;
+    let name_end = rest.find(char::is_whitespace)? + 1;
+    if word_start <= name_end || content[..word_start].contains('\n') {
+        return None;
+    }
+    let completer = crate::commands::arg_completer(&rest[..name_end - 1]);
+    if completer == ArgCompleter::None {
+        return None;
+    }
+    let previous = content[name_end..word_start].split_whitespace().collect();
+    Some((completer, previous))
+}
+
 fn should_include_in_history(content: &str) -> bool {
     if let Some(cmd_str) = content.trim_start().strip_prefix('/') {
         // It's a slash command - check if it's a built-in command
@@ -1519,6 +1580,46 @@ mod tests {
         assert!(state.slash_menu.is_none());
     }
 
+    #[test]
+    fn test_slash_argument() {
+        assert_eq!(slash_argument("/tee", 0), None);
+        assert_eq!(
+            slash_argument("/tee ", 5),
+            Some((ArgCompleter::Files(&["off"]), vec![]))
+        );
+        assert_eq!(
+            slash_argument("/set seed 4", 10),
+            Some((ArgCompleter::Settings, vec!["seed"]))
+        );
+        assert_eq!(slash_argument("/echo hi", 6), None);
+        assert_eq!(slash_argument("tee of", 4), None);
+    }
+
+    #[test]
+    fn test_tab_completes_slash_command_argument() {
+        let mut state = test_state();
+        state.lines[0] = "/set se".to_string();
+        state.col_idx = state.lines[0].len();
+        state.update_slash_menu();
+
+        state.handle_key(key(KeyCode::Tab, KeyModifiers::NONE));
+
+        assert_eq!(state.lines[0], "/set seed");
+    }
+
+    #[test]
+    fn test_tab_completes_file_argument_with_keywords() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("out.txt"), "").unwrap();
+        let mut state = InputState::new(dir.path().to_path_buf());
+        state.lines[0] = "/tee o".to_string();
+        state.col_idx = state.lines[0].len();
+
+        state.init_completion();
+
+        assert_eq!(state.file_completer.matches, vec!["off", "out.txt"]);
+    }
+
     #[test]
     fn test_active_menu_height_uses_completion_when_no_slash_menu() {
         let mut state = test_state();
@@ -0,0 +1,255 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Newline-delimited JSON output for batch runs (`--output json`).
+//!
+//! Each output event is written to stdout as one JSON object with a `type`
+//! field. Streamed text and thinking deltas are collected and emitted as a
+//! single event when the block ends, so consumers don't have to reassemble
+//! them.
+
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use super::tee;
+use crate::output::{OutputEvent, OutputListener};
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonEvent<'a> {
+    Thinking {
+        text: &'a str,
+    },
+    Text {
+        text: &'a str,
+    },
+    ToolCall {
+        description: &'a str,
+    },
+    ToolResult {
+        tool_name: &'a str,
+        is_error: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        summary: Option<&'a str>,
+    },
+    ToolOutput {
+        text: &'a str,
+    },
+    FileRead {
+        filename: &'a str,
+        text: &'a str,
+    },
+    FileDiff {
+        diff: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        summary: Option<&'a str>,
+    },
+    Usage {
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_read_tokens: u64,
+        cache_write_tokens: u64,
+    },
+    Context {
+        input_tokens: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        context_limit: Option<u64>,
+    },
+    AutoCompactStarted {
+        current_usage: u64,
+        limit: u64,
+    },
+    AutoCompactCompleted {
+        messages_compacted: usize,
+    },
+    Info {
+        message: &'a str,
+    },
+    Warning {
+        message: &'a str,
+    },
+    RateLimited {
+        message: &'a str,
+        retry_after_secs: f64,
+    },
+    Error {
+        message: &'a str,
+    },
+    Done,
+    Interrupted,
+}
+
+#[derive(Default)]
+struct Buffers {
+    thinking: String,
+    text: String,
+}
+
+/// Output listener that writes events to stdout as NDJSON.
+pub(crate) struct JsonListener {
+    buffers: Mutex<Buffers>,
+}
+
+impl JsonListener {
+    pub(crate) fn new() -> Self {
+        Self {
+            buffers: Mutex::new(Buffers::default()),
+        }
+    }
+
+    fn write(event: &JsonEvent) {
+        if let Ok(line) = serde_json::to_string(event) {
+            let mut stdout = io::stdout().lock();
+            let _ = writeln!(stdout, "{}", line);
+            let _ = stdout.flush();
+        }
+    }
+
+    /// Emit any collected thinking or text that hasn't been closed yet.
+    fn flush(buffers: &mut Buffers) {
+        if !buffers.thinking.is_empty() {
+            Self::write(&JsonEvent::Thinking {
+                text: &buffers.thinking,
+            });
+            buffers.thinking.clear();
+        }
+        if !buffers.text.is_empty() {
+            Self::write(&JsonEvent::Text {
+                text: &buffers.text,
+            });
+            buffers.text.clear();
+            tee::end_response();
+        }
+    }
+}
+
+impl OutputListener for JsonListener {
+    fn on_event(&self, event: &OutputEvent) {
+        let Ok(mut buffers) = self.buffers.lock() else {
+            return;
+        };
+
+        match event {
+            OutputEvent::Thinking(text) => {
+                buffers.thinking.push_str(text);
+                return;
+            }
+            OutputEvent::Text(text) => {
+                buffers.text.push_str(text);
+                tee::write(text);
+                return;
+            }
+            OutputEvent::ThinkingStart
+            | OutputEvent::Waiting
+            | OutputEvent::WorkingProgress { .. } => return,
+            #[cfg(feature = "images")]
+            OutputEvent::ImagePreview { .. } => return,
+            _ => {}
+        }
+
+        Self::flush(&mut buffers);
+
+        let json = match event {
+            OutputEvent::ThinkingEnd | OutputEvent::TextEnd => return,
+            OutputEvent::ToolCall { description } => JsonEvent::ToolCall { description },
+            OutputEvent::ToolResult {
+                tool_name,
+                is_error,
+                error_preview,
+                exit_code,
+                summary,
+            } => JsonEvent::ToolResult {
+                tool_name,
+                is_error: *is_error,
+                error: error_preview.as_deref(),
+                exit_code: *exit_code,
+                summary: summary.as_deref(),
+            },
+            OutputEvent::ToolOutput { text } => JsonEvent::ToolOutput { text },
+            OutputEvent::FileReadOutput { filename, text } => {
+                JsonEvent::FileRead { filename, text }
+            }
+            OutputEvent::FileDiff { diff, summary, .. } => JsonEvent::FileDiff {
+                diff,
+                summary: summary.as_deref(),
+            },
+            OutputEvent::UsageUpdate {
+                input_tokens,
+                output_tokens,
+                cache_read_tokens,
+                cache_write_tokens,
+            } => JsonEvent::Usage {
+                input_tokens: *input_tokens,
+                output_tokens: *output_tokens,
+                cache_read_tokens: *cache_read_tokens,
+                cache_write_tokens: *cache_write_tokens,
+            },
+            OutputEvent::ContextUpdate {
+                input_tokens,
+                context_limit,
+            } => JsonEvent::Context {
+                input_tokens: *input_tokens,
+                context_limit: *context_limit,
+            },
+            OutputEvent::AutoCompactStarting {
+                current_usage,
+                limit,
+            } => JsonEvent::AutoCompactStarted {
+                current_usage: *current_usage,
+                limit: *limit,
+            },
+            OutputEvent::AutoCompactCompleted { messages_compacted } => {
+                JsonEvent::AutoCompactCompleted {
+                    messages_compacted: *messages_compacted,
+                }
+            }
+            OutputEvent::Info(message) => JsonEvent::Info { message },
+            OutputEvent::Warning(message) => JsonEvent::Warning { message },
+            OutputEvent::RateLimited { message, wait } => JsonEvent::RateLimited {
+                message,
+                retry_after_secs: wait.as_secs_f64(),
+            },
+            OutputEvent::Error(message) => JsonEvent::Error { message },
+            OutputEvent::Done => JsonEvent::Done,
+            OutputEvent::Interrupted => JsonEvent::Interrupted,
+            OutputEvent::Thinking(_)
+            | OutputEvent::Text(_)
+            | OutputEvent::ThinkingStart
+            | OutputEvent::Waiting
+            | OutputEvent::WorkingProgress { .. } => return,
+            #[cfg(feature = "images")]
+            OutputEvent::ImagePreview { .. } => return,
+        };
+        Self::write(&json);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_serialization() {
+        let json = serde_json::to_string(&JsonEvent::ToolResult {
+            tool_name: "bash",
+            is_error: true,
+            error: Some("boom"),
+            exit_code: Some(1),
+            summary: None,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"tool_result","tool_name":"bash","is_error":true,"error":"boom","exit_code":1}"#
+        );
+
+        let json = serde_json::to_string(&JsonEvent::Done).unwrap();
+        assert_eq!(json, r#"{"type":"done"}"#);
+    }
+}
@@ -7,20 +7,25 @@ use std::io::{self, Write};
 use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering};
 use std::sync::{Mutex, OnceLock};
 
+#[cfg(feature = "images")]
 use base64::Engine;
 use colored::{Color, Colorize};
 use tokio::sync::watch;
 use unicode_width::UnicodeWidthChar;
 
+use super::escapes;
 use super::history::{self, HistoryEvent};
 use super::markdown::{
-    render_markdown_inlines, render_markdown_inlines_with_style, render_markdown_line,
+    is_list_marker, render_markdown_inlines, render_markdown_inlines_with_style,
+    render_markdown_line, style_list_marker,
 };
+use super::math::MathStream;
 use super::render::{
     BG_DARK_GREEN, BG_DARK_RED, file_read_scroll_summary, format_summary_suffix,
-    style_file_read_line,
+    highlight_code_lines, style_file_read_line,
 };
 use super::spacing::{LastBlock, needs_blank_line_before};
+use super::tee;
 use super::terminal;
 use crate::output::{OutputEvent, OutputListener};
 use crate::syntax;
@@ -28,12 +33,17 @@ use crate::syntax;
 static ACTIVE_LISTENER: OnceLock<&'static CliListener> = OnceLock::new();
 
 // Global spinner state - completely decoupled from mutex-protected state
-static SPINNER_STATE: AtomicU8 = AtomicU8::new(0); // 0=Ready, 1=Working, 2=Thinking
+static SPINNER_STATE: AtomicU8 = AtomicU8::new(0); // 0=Ready, 1=Working, 2=Thinking, 3=Rate limited
 static SPINNER_TX: OnceLock<watch::Sender<u8>> = OnceLock::new();
+// When a rate-limited request is resent, for the countdown on the status line
+static RATE_LIMIT_UNTIL: Mutex<Option<std::time::Instant>> = Mutex::new(None);
 
 // Whether to show image previews (loaded from config)
 static SHOW_IMAGE_PREVIEWS: AtomicBool = AtomicBool::new(true);
 
+// Whether to print text blocks only once complete (loaded from config)
+static INSTANT_PAINT: AtomicBool = AtomicBool::new(false);
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 enum ToolOutputDisplayMode {
@@ -83,15 +93,35 @@ pub(crate) fn reload_show_image_previews() {
     let enabled = crate::config::ConfigFile::load()
         .map(|c| c.show_image_previews)
         .unwrap_or(true);
+    set_show_image_previews(enabled);
+}
+
+pub(crate) fn set_show_image_previews(enabled: bool) {
     SHOW_IMAGE_PREVIEWS.store(enabled, Ordering::Relaxed);
 }
 
+/// Reload the instant_paint setting from config
+pub(crate) fn reload_instant_paint() {
+    let enabled = crate::config::ConfigFile::load()
+        .map(|c| c.instant_paint)
+        .unwrap_or(false);
+    set_instant_paint(enabled);
+}
+
+pub(crate) fn set_instant_paint(enabled: bool) {
+    INSTANT_PAINT.store(enabled, Ordering::Relaxed);
+}
+
 /// Reload the hide_tool_output setting from config
 pub(crate) fn reload_hide_tool_output() {
     let enabled = crate::config::ConfigFile::load()
         .map(|c| c.hide_tool_output)
         .unwrap_or(false);
-    if enabled {
+    set_hide_tool_output(enabled);
+}
+
+pub(crate) fn set_hide_tool_output(hidden: bool) {
+    if hidden {
         hide_tool_output();
     } else {
         show_tool_output();
@@ -278,7 +308,7 @@ pub(crate) fn force_tool_output_rerender() {
         return;
     };
 
-    let (buffer, line_count, total_lines, old_reserved_lines, file_read_mode) = {
+    let (buffer, line_count, total_lines, old_reserved_lines, file_read_mode, scroll_offset) = {
         let Ok(state) = listener.state.lock() else {
             return;
         };
@@ -291,6 +321,7 @@ pub(crate) fn force_tool_output_rerender() {
             state.tool_output.total_lines,
             state.tool_output.reserved_lines,
             state.tool_output.is_file_read_mode(),
+            state.tool_output.scroll_offset,
         )
     };
 
@@ -310,6 +341,19 @@ pub(crate) fn force_tool_output_rerender() {
         );
 
         (lines, file_read_scroll_summary(total_lines, visible_count))
+    } else if scroll_offset > 0 {
+        // Scrolled back with the mouse wheel: show a window ending above the tail.
+        let wrapped = crate::cli::render::wrap_text(&buffer, width);
+        let end = wrapped.len().saturating_sub(scroll_offset);
+        let start = end.saturating_sub(max_lines);
+
+        let mut lines: Vec<String> = Vec::with_capacity(end - start);
+        lines.extend(
+            wrapped[start..end]
+                .iter()
+                .map(|line| crate::cli::render::style_tool_output_line(line)),
+        );
+        (lines, None)
     } else {
         // Tool viewport keeps showing the tail.
         let (tail_lines, _) =
@@ -358,6 +402,44 @@ pub(crate) fn force_tool_output_rerender() {
     super::terminal::render_tool_viewport(&visible, viewport_height, spacer);
 }
 
+/// Scroll the live tool output viewport by `delta` lines (positive = back in time).
+///
+/// Only applies to streaming tool output shown as a tail; file_read previews and
+/// expanded or hidden output are left alone.
+pub(crate) fn scroll_tool_output(delta: isize) {
+    if !is_tool_output_viewport_active() {
+        return;
+    }
+
+    let Some(listener) = ACTIVE_LISTENER.get() else {
+        return;
+    };
+
+    {
+        let Ok(mut state) = listener.state.lock() else {
+            return;
+        };
+        if state.tool_output.is_file_read_mode() {
+            return;
+        }
+
+        let width = CliListener::terminal_width();
+        let wrapped_count = crate::cli::render::wrap_text(&state.tool_output.buffer, width).len();
+        let max_offset = wrapped_count.saturating_sub(tool_output_viewport_lines());
+        let offset = state
+            .tool_output
+            .scroll_offset
+            .saturating_add_signed(delta)
+            .min(max_offset);
+        if offset == state.tool_output.scroll_offset {
+            return;
+        }
+        state.tool_output.scroll_offset = offset;
+    }
+
+    force_tool_output_rerender();
+}
+
 /// Get the current tool output viewport line count
 pub(crate) fn tool_output_viewport_lines() -> usize {
     // Cap to available terminal space (leave room for prompt + status + spacer)
@@ -564,6 +646,26 @@ fn write_status_line_for_spinner_state(state: u8, frame: Option<&str>) {
             let line = format_status_line(&left, stats.as_deref());
             terminal::write_status_line(&line);
         }
+        3 => {
+            let remaining = RATE_LIMIT_UNTIL
+                .lock()
+                .ok()
+                .and_then(|until| *until)
+                .map(|until| until.saturating_duration_since(std::time::Instant::now()))
+                .unwrap_or_default();
+            let spinner = frame.unwrap_or("⠿");
+            let left = format!(
+                "{} {}",
+                spinner.yellow(),
+                format!(
+                    "Rate limited, resuming in {}",
+                    format_countdown(remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0))
+                )
+                .yellow()
+            );
+            let line = format_status_line(&left, stats.as_deref());
+            terminal::write_status_line(&line);
+        }
         _ => {}
     }
 }
@@ -595,6 +697,30 @@ pub(crate) fn spinner_working() {
     }
 }
 
+/// Set spinner to the rate-limit countdown until `wait` has passed
+fn spinner_rate_limited(wait: std::time::Duration) {
+    if let Ok(mut until) = RATE_LIMIT_UNTIL.lock() {
+        *until = Some(std::time::Instant::now() + wait);
+    }
+    let prev = SPINNER_STATE.swap(3, Ordering::AcqRel);
+    if prev != 3
+        && let Some(tx) = SPINNER_TX.get()
+    {
+        terminal::set_streaming_status_line_active(true);
+
+        let _ = tx.send(3);
+    }
+}
+
+/// Format a countdown as `42s` or `3m 05s`.
+fn format_countdown(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    }
+}
+
 /// Set spinner to "Thinking" state
 pub(crate) fn spinner_thinking() {
     let prev = SPINNER_STATE.swap(2, Ordering::AcqRel);
@@ -779,7 +905,7 @@ fn build_stats_string() -> Option<String> {
 ///
 /// - Thousands: whole-number "k" suffix (e.g. 308k)
 /// - Millions: one decimal "M" suffix when useful (e.g. 8.1M)
-fn format_tokens(tokens: u64) -> String {
+pub(super) fn format_tokens(tokens: u64) -> String {
     if tokens >= 1_000_000 {
         let millions = tokens as f64 / 1_000_000.0;
         if millions >= 10.0 {
@@ -946,6 +1072,9 @@ struct WordWrapper {
     in_code_block: bool,
     /// Language for current code block (for syntax highlighting)
     code_language: Option<String>,
+    /// Lines of the current code block, highlighted one at a time as they
+    /// arrive and again as a whole once the closing fence does
+    code_block_lines: Vec<String>,
     /// Whether we're inside an inline code span
     in_inline_code: bool,
     /// Current bold delimiter if inside a bold span ('*' or '_')
@@ -982,6 +1111,7 @@ impl WordWrapper {
             line_buffer: String::new(),
             in_code_block: false,
             code_language: None,
+            code_block_lines: Vec::new(),
             in_inline_code: false,
             in_bold_span: None,
             pending_bold_marker: None,
@@ -1002,6 +1132,7 @@ impl WordWrapper {
         self.line_buffer.clear();
         self.in_code_block = false;
         self.code_language = None;
+        self.code_block_lines.clear();
         self.in_inline_code = false;
         self.in_bold_span = None;
         self.pending_bold_marker = None;
@@ -1065,7 +1196,8 @@ impl WordWrapper {
         if self.in_code_block && !self.line_buffer.is_empty() {
             let highlighted = self.highlight_line(&self.line_buffer.clone());
             self.emit_println(&highlighted);
-            self.line_buffer.clear();
+            self.code_block_lines
+                .push(std::mem::take(&mut self.line_buffer));
             self.word_buffer.clear();
             self.column = 0;
             self.maybe_table_row = false;
@@ -1116,7 +1248,12 @@ impl WordWrapper {
         }
 
         // Print the word (with style if set)
-        if let Some(style) = self.style {
+        if self.style.is_none()
+            && self.line_buffer.trim() == self.word_buffer
+            && is_list_marker(&self.word_buffer)
+        {
+            self.emit_print(&style_list_marker(&self.word_buffer));
+        } else if let Some(style) = self.style {
             let rendered = render_markdown_inlines_with_style(&self.word_buffer, Some(style));
             self.emit_print(&format!("{}{}\x1b[0m", style, rendered));
         } else {
@@ -1229,7 +1366,9 @@ impl WordWrapper {
 
                 if is_fence {
                     if self.in_code_block {
-                        // Closing fence - just print it and reset state
+                        // Closing fence - restyle the block now that all of it
+                        // is known, print the fence and reset state
+                        self.rehighlight_code_block();
                         self.in_code_block = false;
                         self.code_language = None;
                         self.emit_println("```");
@@ -1238,6 +1377,7 @@ impl WordWrapper {
                         // Opening fence - extract language and enter code block mode
                         self.word_buffer.clear();
                         self.in_code_block = true;
+                        self.code_block_lines.clear();
                         let lang = self
                             .line_buffer
                             .trim()
@@ -1263,7 +1403,8 @@ impl WordWrapper {
                     // Inside code block - print line with syntax highlighting
                     let highlighted = self.highlight_line(&self.line_buffer.clone());
                     self.emit_println(&highlighted);
-                    self.line_buffer.clear();
+                    self.code_block_lines
+                        .push(std::mem::take(&mut self.line_buffer));
                 } else if self.maybe_heading {
                     if self.in_table {
                         self.flush_table();
@@ -1429,6 +1570,25 @@ impl WordWrapper {
     }
 
     /// Highlight a single line of code using the current code block language
+    /// Re-highlight the finished code block as a whole and redraw it if that
+    /// differs from highlighting it a line at a time, as it does for block
+    /// comments and strings spanning several lines.
+    fn rehighlight_code_block(&mut self) {
+        let lines = std::mem::take(&mut self.code_block_lines);
+        if lines.is_empty() {
+            return;
+        }
+        let whole = highlight_code_lines(&lines.join("\n"), self.code_language.as_deref());
+        let changed = whole.len() == lines.len()
+            && whole
+                .iter()
+                .zip(&lines)
+                .any(|(highlighted, line)| *highlighted != self.highlight_line(line));
+        if changed {
+            terminal::rewrite_output_lines(&whole);
+        }
+    }
+
     fn highlight_line(&self, line: &str) -> String {
         let lang = self.code_language.as_deref();
         let spans = syntax::highlight_code(line, lang);
@@ -1549,6 +1709,8 @@ struct StreamState {
     thinking: WordWrapper,
     /// Word wrapper for response text (no style)
     response: WordWrapper,
+    /// Renders math in response text before it reaches the word wrapper
+    math: MathStream,
     /// Last logical block that emitted visible output.
     last_block: Option<LastBlock>,
     /// Trailing newlines seen in thinking deltas that we haven't rendered yet.
@@ -1572,6 +1734,8 @@ struct StreamState {
     last_tool_call_open: bool,
     /// Whether we're inside a <Tool>...</Tool> block
     in_tool_block: bool,
+    /// Thinking or text collected in instant paint mode, rendered when the block ends
+    held_text: Option<OutputEvent>,
     /// Tool output viewport state
     tool_output: ToolOutputState,
     /// Whether an info message was printed inside a tool block since the last tool call banner.
@@ -1587,6 +1751,7 @@ impl StreamState {
         Self {
             thinking: WordWrapper::new(Some("\x1b[3;90m"), 0),
             response: WordWrapper::new(None, 0),
+            math: MathStream::default(),
             last_block: None,
             thinking_pending_newlines: 0,
             text_output_written: false,
@@ -1595,6 +1760,7 @@ impl StreamState {
             diff_shown: false,
             last_tool_call_open: false,
             in_tool_block: false,
+            held_text: None,
             tool_output: ToolOutputState::new(),
             info_since_last_tool_call: false,
         }
@@ -1603,6 +1769,7 @@ impl StreamState {
     fn reset(&mut self) {
         self.thinking.reset();
         self.response.reset();
+        self.math = MathStream::default();
         self.thinking_pending_newlines = 0;
         self.text_output_written = false;
         self.output_state = OutputState::Idle;
@@ -1630,6 +1797,8 @@ struct ToolOutputState {
     total_lines: usize,
     /// Lines currently in buffer (may be less than total_lines after truncation)
     line_count: usize,
+    /// Wrapped lines scrolled back from the tail (0 = following the tail)
+    scroll_offset: usize,
 }
 
 impl ToolOutputState {
@@ -1643,6 +1812,7 @@ impl ToolOutputState {
             reserved_lines: 0,
             total_lines: 0,
             line_count: 0,
+            scroll_offset: 0,
         }
     }
 
@@ -1655,6 +1825,7 @@ impl ToolOutputState {
         self.reserved_lines = 0;
         self.total_lines = 0;
         self.line_count = 0;
+        self.scroll_offset = 0;
     }
 
     /// Mark the tool as completed - late-arriving output will be ignored
@@ -1857,6 +2028,36 @@ impl CliListener {
         self.close_tool_block_inner(false);
     }
 
+    /// In instant paint mode, collect streamed thinking and text so each block is
+    /// rendered in one go when it completes, avoiding per-token redraws.
+    ///
+    /// Returns true if the event was held back.
+    fn hold_for_instant_paint(&self, event: &OutputEvent) -> bool {
+        let instant = INSTANT_PAINT.load(Ordering::Relaxed);
+        let (flush, held) = {
+            let Ok(mut state) = self.state.lock() else {
+                return false;
+            };
+            match (event, state.held_text.as_mut()) {
+                (OutputEvent::Thinking(text), Some(OutputEvent::Thinking(held)))
+                | (OutputEvent::Text(text), Some(OutputEvent::Text(held)))
+                    if instant =>
+                {
+                    held.push_str(text);
+                    return true;
+                }
+                (OutputEvent::Thinking(_) | OutputEvent::Text(_), _) if instant => {
+                    (state.held_text.replace(event.clone()), true)
+                }
+                _ => (state.held_text.take(), false),
+            }
+        };
+        if let Some(flush) = flush {
+            self.handle_event(&flush);
+        }
+        held
+    }
+
     fn handle_event(&self, event: &OutputEvent) {
         match event {
             OutputEvent::ThinkingStart => {
@@ -2051,13 +2252,15 @@ impl CliListener {
                         .set_pending_spacing_before_output(min_newlines);
                 }
 
-                state.response.process_text(text, width);
+                let rendered = state.math.push(text);
+                state.response.process_text(&rendered, width);
                 let did_output = state.response.take_emitted_output();
                 if did_output {
                     state.text_output_written = true;
                     state.last_block = Some(LastBlock::Text);
                 }
                 history::append_assistant_text(text);
+                tee::write(text);
                 state.output_state.start_text();
                 if did_output {
                     state.output_state.mark_text_output();
@@ -2090,8 +2293,12 @@ impl CliListener {
                     state.last_block = Some(LastBlock::Text);
                 }
 
-                // Flush any remaining word buffer
+                // Flush any held-back math and the remaining word buffer
                 let width = Self::terminal_width();
+                let rest = state.math.finish();
+                if !rest.is_empty() {
+                    state.response.process_text(&rest, width);
+                }
                 state.response.flush_word(width);
 
                 // If we were buffering a markdown table, flush it. When the model ends mid-table
@@ -2111,6 +2318,7 @@ impl CliListener {
                     history::push(HistoryEvent::ResponseEnd);
                 }
                 history::finish_assistant_text();
+                tee::end_response();
                 state.reset();
             }
 
@@ -2438,8 +2646,14 @@ impl CliListener {
                         .truncate_to_last_lines(crate::cli::TOOL_OUTPUT_MAX_BUFFER_LINES);
                 }
 
-                // Only render viewport on complete lines
-                if has_trailing_newline {
+                // Only render viewport on complete lines, and leave it alone while the user
+                // has scrolled back so new output doesn't yank the view to the tail.
+                let scrolled_back = self
+                    .state
+                    .lock()
+                    .map(|state| state.tool_output.scroll_offset > 0)
+                    .unwrap_or(false);
+                if has_trailing_newline && !scrolled_back {
                     let (reserve_delta, visible_lines, viewport_height, spacer_lines) = {
                         let Ok(mut state) = self.state.lock() else {
                             return;
@@ -2888,6 +3102,7 @@ impl CliListener {
                 }
             }
 
+            #[cfg(feature = "images")]
             OutputEvent::ImagePreview { data, mime_type } => {
                 // Display image preview for terminals that support it (e.g., Kitty)
                 if SHOW_IMAGE_PREVIEWS.load(Ordering::Relaxed)
@@ -2938,6 +3153,26 @@ impl CliListener {
                 history::push(HistoryEvent::AutoCompact { message: msg });
             }
 
+            OutputEvent::RateLimited { message, wait } => {
+                let last_block = self.state.lock().map(|s| s.last_block).unwrap_or(None);
+                if needs_blank_line_before(last_block, LastBlock::Info) {
+                    terminal::ensure_trailing_newlines(2);
+                } else {
+                    terminal::ensure_line_break();
+                }
+                let msg = format!(
+                    "{} (waiting {} before resending)",
+                    message,
+                    format_countdown(wait.as_secs().max(1))
+                );
+                terminal::println_above(&msg.yellow().to_string());
+                history::push(HistoryEvent::Warning(msg));
+                if let Ok(mut state) = self.state.lock() {
+                    state.last_block = Some(LastBlock::Info);
+                }
+                spinner_rate_limited(*wait);
+            }
+
             OutputEvent::Waiting => {
                 // Latch current stats into accumulated values (for multi-API-call turns)
                 latch_streaming_stats();
@@ -3009,10 +3244,16 @@ impl CliListener {
 
 impl OutputListener for CliListener {
     fn on_event(&self, event: &OutputEvent) {
+        let event = escapes::sanitize_event(event);
+        let event = event.as_ref();
+        super::mirror::publish_output(event);
         if terminal::is_output_buffering() {
             Self::buffer_event(event);
             return;
         }
+        if self.hold_for_instant_paint(event) {
+            return;
+        }
         self.handle_event(event);
     }
 }
@@ -41,9 +41,37 @@ pub(crate) fn render_markdown_line(line: &str) -> String {
         return out;
     }
 
+    // List items: color the bullet or number.
+    if let Some((marker, rest)) = trimmed.split_once(' ')
+        && is_list_marker(marker)
+    {
+        return format!(
+            "{}{} {}",
+            &line[..leading_len],
+            style_list_marker(marker),
+            render_markdown_inlines(rest)
+        );
+    }
+
     render_markdown_inlines(line)
 }
 
+/// Whether `word` starts a list item: `-`, `*`, `+`, `1.` or `1)`.
+pub(crate) fn is_list_marker(word: &str) -> bool {
+    match word {
+        "-" | "*" | "+" => true,
+        _ => word.strip_suffix(['.', ')']).is_some_and(|n| {
+            !n.is_empty() && n.len() <= 9 && n.chars().all(|c| c.is_ascii_digit())
+        }),
+    }
+}
+
+pub(crate) fn style_list_marker(marker: &str) -> String {
+    marker
+        .truecolor(HEADING_FG.0, HEADING_FG.1, HEADING_FG.2)
+        .to_string()
+}
+
 pub(crate) fn is_heading_line(line: &str) -> bool {
     let trimmed = line.trim_start_matches([' ', '\t']);
     if let Some((hashes, _rest)) = trimmed.split_once(' ') {
@@ -445,6 +473,19 @@ mod tests {
         assert!(rendered.contains("\x1b[1;"));
     }
 
+    #[test]
+    fn test_render_markdown_list_marker() {
+        enable_colors();
+        assert!(is_list_marker("-"));
+        assert!(is_list_marker("12."));
+        assert!(!is_list_marker("1.5"));
+        assert!(!is_list_marker("**"));
+        let out = render_markdown_line("  - item `x`");
+        assert!(out.starts_with("  \x1b["));
+        assert!(out.contains("item"));
+        assert_eq!(render_markdown_line("-1 degrees"), "-1 degrees");
+    }
+
     #[test]
     fn test_is_heading_line() {
         assert!(is_heading_line("# Heading"));
@@ -0,0 +1,662 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Unicode approximation of LaTeX math in assistant output.
+//!
+//! `$...$` and `$$...$$` spans are rewritten to plain unicode (`\alpha^2` becomes
+//! `α²`). Spans using unsupported commands, or that never close, are left as raw
+//! text. Math inside code blocks and inline code is not touched.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Longest inline span held back while waiting for the closing `$`.
+const MAX_INLINE_LEN: usize = 200;
+
+/// Longest display span held back while waiting for the closing `$$`.
+const MAX_DISPLAY_LEN: usize = 4000;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    #[default]
+    Text,
+    /// Saw a single `$`; waiting to see if it opens inline or display math.
+    Dollar,
+    Inline,
+    /// Saw a candidate closing `$`; a following digit means it was a literal.
+    InlineClosing,
+    Display,
+    /// Saw one `$` of a candidate closing `$$`.
+    DisplayClosing,
+}
+
+/// Streaming math renderer.
+///
+/// Text is pushed as it arrives and returned once it is safe to display; math
+/// spans are held back until they close.
+#[derive(Debug, Default)]
+pub(crate) struct MathStream {
+    mode: Mode,
+    /// Raw text of the open math span, including the opening delimiter.
+    span: String,
+    /// Text of the current line (outside math), for code fence detection.
+    line: String,
+    in_code_block: bool,
+    in_inline_code: bool,
+}
+
+impl MathStream {
+    /// Process a chunk of text, returning the text ready to display.
+    pub(crate) fn push(&mut self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for ch in text.chars() {
+            self.push_char(ch, &mut out);
+        }
+        out
+    }
+
+    /// Flush any held-back text at the end of a response.
+    pub(crate) fn finish(&mut self) -> String {
+        let mut out = String::new();
+        // Falling back reprocesses the span, which may open another one.
+        loop {
+            match self.mode {
+                Mode::Text => break,
+                Mode::Dollar => {
+                    out.push('$');
+                    self.span.clear();
+                    self.mode = Mode::Text;
+                }
+                Mode::InlineClosing => {
+                    self.span.push('$');
+                    out.push_str(&render_inline(&self.span));
+                    self.span.clear();
+                    self.mode = Mode::Text;
+                }
+                Mode::Inline | Mode::Display => self.fallback(&mut out),
+                Mode::DisplayClosing => {
+                    self.span.push('$');
+                    self.fallback(&mut out);
+                }
+            }
+        }
+        *self = Self::default();
+        out
+    }
+
+    fn push_char(&mut self, ch: char, out: &mut String) {
+        match self.mode {
+            Mode::Text => {
+                if ch == '\n' {
+                    if self.line.trim_start().starts_with("```") {
+                        self.in_code_block = !self.in_code_block;
+                    }
+                    self.line.clear();
+                    self.in_inline_code = false;
+                    out.push(ch);
+                    return;
+                }
+                if !self.in_code_block {
+                    if ch == '`' {
+                        self.in_inline_code = !self.in_inline_code;
+                    } else if ch == '$' && !self.in_inline_code && !self.line.ends_with('\\') {
+                        self.mode = Mode::Dollar;
+                        self.span.push(ch);
+                        return;
+                    }
+                }
+                self.line.push(ch);
+                out.push(ch);
+            }
+            Mode::Dollar => {
+                if ch == '$' {
+                    self.mode = Mode::Display;
+                    self.span.push(ch);
+                } else if ch.is_whitespace() {
+                    // "$ " is a literal dollar sign.
+                    self.span.clear();
+                    self.mode = Mode::Text;
+                    self.line.push('$');
+                    out.push('$');
+                    self.push_char(ch, out);
+                } else {
+                    self.mode = Mode::Inline;
+                    self.span.push(ch);
+                }
+            }
+            Mode::Inline => {
+                if ch == '\n' {
+                    self.fallback(out);
+                    self.push_char(ch, out);
+                } else if ch == '$'
+                    && !self.span.ends_with(char::is_whitespace)
+                    && !self.span.ends_with('\\')
+                {
+                    self.mode = Mode::InlineClosing;
+                } else {
+                    self.span.push(ch);
+                    if self.span.len() > MAX_INLINE_LEN {
+                        self.fallback(out);
+                    }
+                }
+            }
+            Mode::InlineClosing => {
+                if ch.is_ascii_digit() {
+                    // "$5 and $10": not a closing delimiter.
+                    self.span.push('$');
+                    self.mode = Mode::Inline;
+                    self.push_char(ch, out);
+                } else {
+                    self.span.push('$');
+                    out.push_str(&render_inline(&self.span));
+                    self.span.clear();
+                    self.mode = Mode::Text;
+                    self.push_char(ch, out);
+                }
+            }
+            Mode::Display => {
+                if ch == '$' {
+                    self.mode = Mode::DisplayClosing;
+                } else {
+                    self.span.push(ch);
+                    // A blank line ends the paragraph, so the span is not math.
+                    if self.span.len() > MAX_DISPLAY_LEN || self.span.ends_with("\n\n") {
+                        self.fallback(out);
+                    }
+                }
+            }
+            Mode::DisplayClosing => {
+                if ch == '$' {
+                    out.push_str(&render_display(&self.span));
+                    self.span.clear();
+                    self.mode = Mode::Text;
+                } else {
+                    self.span.push('$');
+                    self.mode = Mode::Display;
+                    self.push_char(ch, out);
+                }
+            }
+        }
+    }
+
+    /// Give up on the open span: emit its opening delimiter literally and
+    /// reprocess the rest as normal text.
+    fn fallback(&mut self, out: &mut String) {
+        let raw = std::mem::take(&mut self.span);
+        self.mode = Mode::Text;
+        let delimiter = if raw.starts_with("$$") { "$$" } else { "$" };
+        self.line.push_str(delimiter);
+        out.push_str(delimiter);
+        for ch in raw[delimiter.len()..].chars() {
+            self.push_char(ch, out);
+        }
+    }
+}
+
+/// Render all math spans in a complete piece of text.
+pub(crate) fn render_math(text: &str) -> String {
+    let mut stream = MathStream::default();
+    let mut out = stream.push(text);
+    out.push_str(&stream.finish());
+    out
+}
+
+/// Render a closed `$...$` span, or return it unchanged.
+fn render_inline(span: &str) -> String {
+    let expr = &span[1..span.len() - 1];
+    latex_to_unicode(expr).unwrap_or_else(|| span.to_string())
+}
+
+/// Render a `$$...` span (closing delimiter not included), or return it raw.
+fn render_display(span: &str) -> String {
+    let expr = &span[2..];
+    match latex_to_unicode(expr) {
+        Some(rendered) => rendered
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => format!("{}$$", span),
+    }
+}
+
+/// Convert a LaTeX math expression to a unicode approximation.
+///
+/// Returns `None` if the expression uses anything that can't be represented.
+pub(crate) fn latex_to_unicode(expr: &str) -> Option<String> {
+    let mut chars = expr.chars().peekable();
+    let out = parse_group(&mut chars, false)?;
+    Some(out.trim().to_string())
+}
+
+/// Parse until the end of input, or the closing `}` if `in_braces`.
+fn parse_group(chars: &mut Peekable<Chars>, in_braces: bool) -> Option<String> {
+    let mut out = String::new();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '}' if in_braces => return Some(out),
+            '}' => return None,
+            '{' => out.push_str(&parse_group(chars, true)?),
+            '\\' => out.push_str(&parse_command(chars)?),
+            '^' => out.push_str(&superscript(&parse_arg(chars)?)),
+            '_' => out.push_str(&subscript(&parse_arg(chars)?)),
+            '&' | '~' => out.push(' '),
+            _ => out.push(ch),
+        }
+    }
+    // Unbalanced braces.
+    if in_braces { None } else { Some(out) }
+}
+
+/// Parse a single argument: a braced group, a command, or one character.
+fn parse_arg(chars: &mut Peekable<Chars>) -> Option<String> {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    match chars.next()? {
+        '{' => parse_group(chars, true),
+        '\\' => parse_command(chars),
+        ch => Some(ch.to_string()),
+    }
+}
+
+fn parse_command(chars: &mut Peekable<Chars>) -> Option<String> {
+    let mut name = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+        name.push(c);
+    }
+    if name.is_empty() {
+        return match chars.next()? {
+            c @ ('{' | '}' | '$' | '%' | '&' | '_' | '#') => Some(c.to_string()),
+            ',' | ';' | ':' | ' ' => Some(" ".to_string()),
+            '!' => Some(String::new()),
+            '|' => Some("‖".to_string()),
+            '\\' => Some("  ".to_string()),
+            _ => None,
+        };
+    }
+
+    if let Some(symbol) = symbol(&name) {
+        return Some(symbol.to_string());
+    }
+
+    match name.as_str() {
+        "frac" | "dfrac" | "tfrac" => {
+            let num = parse_arg(chars)?;
+            let den = parse_arg(chars)?;
+            Some(format!("{}/{}", wrap(&num), wrap(&den)))
+        }
+        "sqrt" => {
+            let mut index = String::new();
+            if chars.next_if_eq(&'[').is_some() {
+                index = parse_until_bracket(chars)?;
+            }
+            let radicand = parse_arg(chars)?;
+            let root = match index.as_str() {
+                "" | "2" => "√",
+                "3" => "∛",
+                "4" => "∜",
+                _ => return Some(format!("{}√{}", superscript(&index), wrap(&radicand))),
+            };
+            Some(format!("{}{}", root, wrap(&radicand)))
+        }
+        "binom" => {
+            let n = parse_arg(chars)?;
+            let k = parse_arg(chars)?;
+            Some(format!("C({}, {})", n, k))
+        }
+        "mathbb" => parse_arg(chars)?.chars().map(blackboard).collect(),
+        "text" | "textrm" | "textbf" | "textit" | "mathrm" | "mathbf" | "mathit" | "mathsf"
+        | "mathtt" | "mathcal" | "boldsymbol" | "operatorname" => parse_arg(chars),
+        "hat" | "widehat" => accent(chars, '\u{0302}'),
+        "bar" | "overline" => accent(chars, '\u{0304}'),
+        "tilde" | "widetilde" => accent(chars, '\u{0303}'),
+        "vec" => accent(chars, '\u{20D7}'),
+        "dot" => accent(chars, '\u{0307}'),
+        "left" | "right" => {
+            // `\left.` is an invisible delimiter.
+            chars.next_if_eq(&'.');
+            Some(String::new())
+        }
+        "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "Bigl" | "Bigr" | "displaystyle"
+        | "textstyle" | "limits" | "nolimits" => Some(String::new()),
+        _ => None,
+    }
+}
+
+fn parse_until_bracket(chars: &mut Peekable<Chars>) -> Option<String> {
+    let mut out = String::new();
+    for c in chars.by_ref() {
+        if c == ']' {
+            return Some(out);
+        }
+        out.push(c);
+    }
+    None
+}
+
+/// Apply a combining accent to a single-character argument.
+fn accent(chars: &mut Peekable<Chars>, mark: char) -> Option<String> {
+    let arg = parse_arg(chars)?;
+    if arg.chars().count() == 1 {
+        Some(format!("{}{}", arg, mark))
+    } else {
+        Some(arg)
+    }
+}
+
+/// Parenthesize compound fraction and root operands.
+fn wrap(s: &str) -> String {
+    if s.chars().any(|c| " +-−±·×/=<>".contains(c)) {
+        format!("({})", s)
+    } else {
+        s.to_string()
+    }
+}
+
+fn superscript(s: &str) -> String {
+    match s.chars().map(superscript_char).collect::<Option<String>>() {
+        Some(mapped) => mapped,
+        None if s.chars().count() == 1 => format!("^{}", s),
+        None => format!("^({})", s),
+    }
+}
+
+fn subscript(s: &str) -> String {
+    match s.chars().map(subscript_char).collect::<Option<String>>() {
+        Some(mapped) => mapped,
+        None if s.chars().count() == 1 => format!("_{}", s),
+        None => format!("_({})", s),
+    }
+}
+
+fn superscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'a' => 'ᵃ',
+        'b' => 'ᵇ',
+        'c' => 'ᶜ',
+        'd' => 'ᵈ',
+        'e' => 'ᵉ',
+        'f' => 'ᶠ',
+        'g' => 'ᵍ',
+        'h' => 'ʰ',
+        'i' => 'ⁱ',
+        'j' => 'ʲ',
+        'k' => 'ᵏ',
+        'l' => 'ˡ',
+        'm' => 'ᵐ',
+        'n' => 'ⁿ',
+        'o' => 'ᵒ',
+        'p' => 'ᵖ',
+        'r' => 'ʳ',
+        's' => 'ˢ',
+        't' => 'ᵗ',
+        'u' => 'ᵘ',
+        'v' => 'ᵛ',
+        'w' => 'ʷ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'z' => 'ᶻ',
+        'T' => 'ᵀ',
+        '′' => '′',
+        '*' => '*',
+        ' ' => ' ',
+        _ => return None,
+    })
+}
+
+fn subscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        ' ' => ' ',
+        _ => return None,
+    })
+}
+
+fn blackboard(c: char) -> Option<char> {
+    Some(match c {
+        'N' => 'ℕ',
+        'Z' => 'ℤ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'C' => 'ℂ',
+        'P' => 'ℙ',
+        'E' => '𝔼',
+        _ => return None,
+    })
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        // Greek
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" | "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" | "vartheta" => "θ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "rho" | "varrho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" | "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        // Operators and relations
+        "times" => "×",
+        "cdot" => "·",
+        "div" => "÷",
+        "pm" => "±",
+        "mp" => "∓",
+        "le" | "leq" => "≤",
+        "ge" | "geq" => "≥",
+        "ne" | "neq" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "ll" => "≪",
+        "gg" => "≫",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "emptyset" | "varnothing" => "∅",
+        "forall" => "∀",
+        "exists" => "∃",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "circ" => "∘",
+        "ast" => "∗",
+        "star" => "⋆",
+        "mid" => "|",
+        "parallel" => "∥",
+        "perp" => "⊥",
+        "angle" => "∠",
+        "prime" => "′",
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "sum" => "∑",
+        "prod" => "∏",
+        "int" => "∫",
+        "iint" => "∬",
+        "oint" => "∮",
+        // Arrows
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        // Delimiters and dots
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lbrace" => "{",
+        "rbrace" => "}",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        // Named functions
+        "log" => "log",
+        "ln" => "ln",
+        "lg" => "lg",
+        "exp" => "exp",
+        "sin" => "sin",
+        "cos" => "cos",
+        "tan" => "tan",
+        "lim" => "lim",
+        "max" => "max",
+        "min" => "min",
+        "sup" => "sup",
+        "inf" => "inf",
+        "arg" => "arg",
+        "det" => "det",
+        "gcd" => "gcd",
+        "deg" => "deg",
+        "mod" | "bmod" => "mod",
+        // Spacing
+        "quad" => "  ",
+        "qquad" => "    ",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latex_to_unicode_symbols_and_scripts() {
+        assert_eq!(latex_to_unicode(r"\alpha^2 + \beta_i").unwrap(), "α² + βᵢ");
+        assert_eq!(latex_to_unicode(r"O(n \log n)").unwrap(), "O(n log n)");
+        assert_eq!(latex_to_unicode(r"\sum_{i=1}^{n} i").unwrap(), "∑ᵢ₌₁ⁿ i");
+        assert_eq!(latex_to_unicode(r"x^{q}").unwrap(), "x^q");
+        assert_eq!(latex_to_unicode(r"\frac{n(n+1)}{2}").unwrap(), "(n(n+1))/2");
+        assert_eq!(latex_to_unicode(r"\sqrt{x}").unwrap(), "√x");
+        assert_eq!(latex_to_unicode(r"x \in \mathbb{R}").unwrap(), "x ∈ ℝ");
+    }
+
+    #[test]
+    fn test_latex_to_unicode_unsupported() {
+        assert!(latex_to_unicode(r"\begin{matrix}a\end{matrix}").is_none());
+        assert!(latex_to_unicode(r"\frac{a}{b").is_none());
+    }
+
+    #[test]
+    fn test_render_math_spans() {
+        assert_eq!(render_math("runs in $O(n^2)$ time"), "runs in O(n²) time");
+        assert_eq!(render_math("$$\n\\pi \\approx 3.14\n$$"), "π ≈ 3.14");
+        assert_eq!(
+            render_math(r"keep $\unknown{x}$ raw"),
+            r"keep $\unknown{x}$ raw"
+        );
+    }
+
+    #[test]
+    fn test_render_math_leaves_prices_and_code() {
+        assert_eq!(render_math("costs $5 and $10"), "costs $5 and $10");
+        assert_eq!(render_math("a $ b $ c"), "a $ b $ c");
+        assert_eq!(render_math("`$x^2$`"), "`$x^2$`");
+        assert_eq!(
+            render_math("```sh\necho $HOME$x\n```\n"),
+            "```sh\necho $HOME$x\n```\n"
+        );
+        assert_eq!(render_math("unclosed $x^2\nnext"), "unclosed $x^2\nnext");
+    }
+
+    #[test]
+    fn test_math_stream_split_chunks() {
+        let mut stream = MathStream::default();
+        let mut out = String::new();
+        for chunk in ["value $\\al", "pha$", " and $", "$x_1$", "$ done"] {
+            out.push_str(&stream.push(chunk));
+        }
+        out.push_str(&stream.finish());
+        assert_eq!(out, "value α and x₁ done");
+    }
+}
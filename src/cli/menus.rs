@@ -15,7 +15,8 @@ use crossterm::style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor
 use crossterm::terminal::{self, ClearType};
 use unicode_width::UnicodeWidthStr;
 
-use super::style::{MENU_BG_NORMAL, MENU_BG_SELECTED, SOFTWARE_CURSOR_OFF, SOFTWARE_CURSOR_ON};
+use super::capabilities;
+use super::style::{SOFTWARE_CURSOR_OFF, SOFTWARE_CURSOR_ON, menu_bg_normal, menu_bg_selected};
 
 use crate::config::{ConfigFile, DefaultModel};
 use crate::providers::{ModelChoice, build_model_choices};
@@ -76,7 +77,7 @@ fn render_filter_header(
         stdout,
         cursor::MoveTo(0, start_row),
         terminal::Clear(ClearType::CurrentLine),
-        SetBackgroundColor(MENU_BG_NORMAL),
+        SetBackgroundColor(menu_bg_normal()),
         SetForegroundColor(Color::Yellow)
     )?;
 
@@ -183,7 +184,7 @@ impl ModelMenuState {
                 })
                 .collect();
 
-            matches.sort_by(|(_, score_a), (_, score_b)| score_a.cmp(score_b));
+            matches.sort_by_key(|(_, score)| *score);
             matches.into_iter().map(|(c, _)| c).collect()
         }
     }
@@ -342,7 +343,7 @@ impl ModelMenuState {
                 stdout,
                 cursor::MoveTo(0, start_row + 1),
                 terminal::Clear(ClearType::CurrentLine),
-                SetBackgroundColor(MENU_BG_NORMAL),
+                SetBackgroundColor(menu_bg_normal()),
                 SetForegroundColor(Color::DarkGrey)
             )?;
             let msg = "  No matching models";
@@ -382,31 +383,15 @@ impl ModelMenuState {
             // Colors matching slash_menu.rs
             let (bg_color, name_color, desc_color) = if is_selected {
                 (
-                    MENU_BG_SELECTED,
-                    Color::Rgb {
-                        r: 137,
-                        g: 180,
-                        b: 250,
-                    },
-                    Color::Rgb {
-                        r: 200,
-                        g: 200,
-                        b: 200,
-                    },
+                    menu_bg_selected(),
+                    capabilities::rgb(137, 180, 250),
+                    capabilities::rgb(200, 200, 200),
                 )
             } else {
                 (
-                    MENU_BG_NORMAL,
-                    Color::Rgb {
-                        r: 120,
-                        g: 120,
-                        b: 120,
-                    },
-                    Color::Rgb {
-                        r: 150,
-                        g: 150,
-                        b: 150,
-                    },
+                    menu_bg_normal(),
+                    capabilities::rgb(120, 120, 120),
+                    capabilities::rgb(150, 150, 150),
                 )
             };
 
@@ -452,7 +437,7 @@ impl ModelMenuState {
                 stdout,
                 cursor::MoveTo(0, indicator_row),
                 terminal::Clear(ClearType::CurrentLine),
-                SetBackgroundColor(MENU_BG_NORMAL),
+                SetBackgroundColor(menu_bg_normal()),
                 SetForegroundColor(Color::DarkGrey)
             )?;
             let msg = format!(
@@ -470,68 +455,6 @@ impl ModelMenuState {
     }
 }
 
-/// A setting option in the settings menu
-#[derive(Clone, Debug)]
-pub(super) enum SettingOption {
-    ShowImagePreviews(bool),
-    LspEnabled(bool),
-    HideToolOutput(bool),
-    CompactMode(bool),
-}
-
-impl SettingOption {
-    fn label(&self) -> &'static str {
-        match self {
-            SettingOption::ShowImagePreviews(_) => "Image Previews",
-            SettingOption::LspEnabled(_) => "LSP Integration",
-            SettingOption::HideToolOutput(_) => "Hide Tool Output",
-            SettingOption::CompactMode(_) => "Compact Mode",
-        }
-    }
-
-    fn value_display(&self) -> String {
-        match self {
-            SettingOption::ShowImagePreviews(enabled)
-            | SettingOption::LspEnabled(enabled)
-            | SettingOption::HideToolOutput(enabled)
-            | SettingOption::CompactMode(enabled) => {
-                if *enabled { "Enabled" } else { "Disabled" }.to_string()
-            }
-        }
-    }
-
-    fn toggle(&mut self) {
-        match self {
-            SettingOption::ShowImagePreviews(enabled)
-            | SettingOption::LspEnabled(enabled)
-            | SettingOption::HideToolOutput(enabled)
-            | SettingOption::CompactMode(enabled) => {
-                *enabled = !*enabled;
-            }
-        }
-    }
-
-    fn save(&self) {
-        if let Ok(mut config) = ConfigFile::load() {
-            match self {
-                SettingOption::ShowImagePreviews(enabled) => {
-                    config.show_image_previews = *enabled;
-                }
-                SettingOption::LspEnabled(enabled) => {
-                    config.lsp_enabled = *enabled;
-                }
-                SettingOption::HideToolOutput(enabled) => {
-                    config.hide_tool_output = *enabled;
-                }
-                SettingOption::CompactMode(enabled) => {
-                    config.compact_mode = *enabled;
-                }
-            }
-            let _ = config.save();
-        }
-    }
-}
-
 /// A choice for the default model selection
 #[derive(Clone, Debug)]
 pub(super) enum DefaultModelChoice {
@@ -575,8 +498,8 @@ pub(super) enum SettingsMenuAction {
 
 /// State for the settings menu
 pub(super) struct SettingsMenuState {
-    /// Setting options
-    options: Vec<SettingOption>,
+    /// The on/off settings, changed from the menu by toggling
+    options: Vec<&'static crate::settings::Setting>,
     /// Current selection index
     selected_index: usize,
     /// Default model submenu state (when open)
@@ -632,14 +555,11 @@ impl DefaultModelSubmenuState {
 impl SettingsMenuState {
     /// Create a new settings menu state
     pub fn new() -> Self {
-        let config = ConfigFile::load().unwrap_or_default();
         Self {
-            options: vec![
-                SettingOption::CompactMode(config.compact_mode),
-                SettingOption::ShowImagePreviews(config.show_image_previews),
-                SettingOption::LspEnabled(config.lsp_enabled),
-                SettingOption::HideToolOutput(config.hide_tool_output),
-            ],
+            options: crate::settings::SETTINGS
+                .iter()
+                .filter(|s| s.ty == crate::settings::SettingType::Bool)
+                .collect(),
             selected_index: 0,
             default_model_submenu: None,
         }
@@ -779,9 +699,10 @@ impl SettingsMenuState {
             return;
         }
 
-        if let Some(option) = self.options.get_mut(self.selected_index) {
-            option.toggle();
-            option.save();
+        if let Some(setting) = self.options.get(self.selected_index) {
+            let mut ctx = crate::settings::Context::load(None);
+            let enabled = setting.get(&ctx) == crate::settings::SettingValue::Bool(true);
+            let _ = setting.set(&mut ctx, crate::settings::SettingValue::Bool(!enabled));
         }
     }
 
@@ -821,16 +742,8 @@ impl SettingsMenuState {
     fn render_main_menu(&self, stdout: &mut io::Stdout, start_row: u16) -> io::Result<()> {
         let term_width = terminal::size().map(|(w, _)| w as usize).unwrap_or(80);
 
-        let bg_normal = Color::Rgb {
-            r: 20,
-            g: 20,
-            b: 20,
-        };
-        let bg_selected = Color::Rgb {
-            r: 30,
-            g: 30,
-            b: 30,
-        };
+        let bg_normal = capabilities::rgb(20, 20, 20);
+        let bg_selected = capabilities::rgb(30, 30, 30);
 
         // Header line
         queue!(
@@ -852,22 +765,28 @@ impl SettingsMenuState {
         let max_label_width = self
             .options
             .iter()
-            .map(|o| o.label().width())
+            .map(|o| o.label.width())
             .max()
             .unwrap_or(0)
             .max("Default Model".width());
 
         // Render setting options
+        let ctx = crate::settings::Context::load(None);
         for (i, option) in self.options.iter().enumerate() {
             let row = start_row + 1 + i as u16;
             let is_selected = i == self.selected_index;
+            let value = if option.get(&ctx) == crate::settings::SettingValue::Bool(true) {
+                "Enabled"
+            } else {
+                "Disabled"
+            };
 
             self.render_option_line(
                 stdout,
                 row,
                 is_selected,
-                option.label(),
-                &option.value_display(),
+                option.label,
+                value,
                 max_label_width,
                 term_width,
                 bg_normal,
@@ -922,30 +841,14 @@ impl SettingsMenuState {
         let (bg_color, label_color, value_color) = if is_selected {
             (
                 bg_selected,
-                Color::Rgb {
-                    r: 137,
-                    g: 180,
-                    b: 250,
-                },
-                Color::Rgb {
-                    r: 200,
-                    g: 200,
-                    b: 200,
-                },
+                capabilities::rgb(137, 180, 250),
+                capabilities::rgb(200, 200, 200),
             )
         } else {
             (
                 bg_normal,
-                Color::Rgb {
-                    r: 120,
-                    g: 120,
-                    b: 120,
-                },
-                Color::Rgb {
-                    r: 150,
-                    g: 150,
-                    b: 150,
-                },
+                capabilities::rgb(120, 120, 120),
+                capabilities::rgb(150, 150, 150),
             )
         };
 
@@ -1005,7 +908,7 @@ impl SettingsMenuState {
                 stdout,
                 cursor::MoveTo(0, start_row + 1),
                 terminal::Clear(ClearType::CurrentLine),
-                SetBackgroundColor(MENU_BG_NORMAL),
+                SetBackgroundColor(menu_bg_normal()),
                 SetForegroundColor(Color::DarkGrey)
             )?;
             let msg = "  No matching models";
@@ -1047,31 +950,15 @@ impl SettingsMenuState {
 
             let (bg_color, name_color, desc_color) = if is_selected {
                 (
-                    MENU_BG_SELECTED,
-                    Color::Rgb {
-                        r: 137,
-                        g: 180,
-                        b: 250,
-                    },
-                    Color::Rgb {
-                        r: 200,
-                        g: 200,
-                        b: 200,
-                    },
+                    menu_bg_selected(),
+                    capabilities::rgb(137, 180, 250),
+                    capabilities::rgb(200, 200, 200),
                 )
             } else {
                 (
-                    MENU_BG_NORMAL,
-                    Color::Rgb {
-                        r: 120,
-                        g: 120,
-                        b: 120,
-                    },
-                    Color::Rgb {
-                        r: 150,
-                        g: 150,
-                        b: 150,
-                    },
+                    menu_bg_normal(),
+                    capabilities::rgb(120, 120, 120),
+                    capabilities::rgb(150, 150, 150),
                 )
             };
 
@@ -1124,7 +1011,7 @@ impl SettingsMenuState {
                 stdout,
                 cursor::MoveTo(0, indicator_row),
                 terminal::Clear(ClearType::CurrentLine),
-                SetBackgroundColor(MENU_BG_NORMAL),
+                SetBackgroundColor(menu_bg_normal()),
                 SetForegroundColor(Color::DarkGrey)
             )?;
             let msg = format!(
@@ -1161,6 +1048,9 @@ pub(super) struct SessionMenuState {
     selected_index: usize,
     /// Current session ID (to highlight)
     current_session_id: Option<String>,
+    working_dir: std::path::PathBuf,
+    /// Listing the sessions of every working directory (toggled with Tab)
+    all_projects: bool,
 }
 
 impl SessionMenuState {
@@ -1171,11 +1061,29 @@ impl SessionMenuState {
             sessions,
             selected_index: 0,
             current_session_id: current_session_id.map(|s| s.to_string()),
+            working_dir: working_dir.to_path_buf(),
+            all_projects: false,
         }
     }
 
+    /// Switch between this directory's sessions and every directory's.
+    fn toggle_all_projects(&mut self) {
+        self.all_projects = !self.all_projects;
+        self.sessions = if self.all_projects {
+            session::list_all_sessions()
+        } else {
+            session::list_sessions(&self.working_dir)
+        };
+        self.selected_index = 0;
+    }
+
     /// Handle a key event, returning the action to take
     pub fn handle_key(&mut self, key: KeyEvent) -> SessionMenuAction {
+        if key.code == KeyCode::Tab {
+            self.toggle_all_projects();
+            return SessionMenuAction::Redraw;
+        }
+
         let total = self.sessions.len();
         if total == 0 {
             // No sessions, Escape or Enter closes
@@ -1257,7 +1165,12 @@ impl SessionMenuState {
         )?;
         write!(
             stdout,
-            "Select session (↑↓ to navigate, Enter to select, Esc to cancel):"
+            "Select session (↑↓ to navigate, Tab for {}, Enter to select, Esc to cancel):",
+            if self.all_projects {
+                "this directory"
+            } else {
+                "all directories"
+            }
         )?;
         queue!(stdout, ResetColor)?;
 
@@ -1268,7 +1181,11 @@ impl SessionMenuState {
                 terminal::Clear(ClearType::CurrentLine),
                 SetForegroundColor(Color::DarkGrey)
             )?;
-            write!(stdout, "  No sessions found for this directory")?;
+            if self.all_projects {
+                write!(stdout, "  No sessions found")?;
+            } else {
+                write!(stdout, "  No sessions found for this directory")?;
+            }
             queue!(stdout, ResetColor)?;
             return Ok(());
         }
@@ -1295,14 +1212,22 @@ impl SessionMenuState {
                 .is_some_and(|id| id == &session_info.id);
 
             let age = session::format_age(&session_info.saved_at);
+            // The title when it has one, otherwise the first prompt
             let preview = session_info
-                .preview
+                .title
                 .as_deref()
+                .or(session_info.preview.as_deref())
                 .unwrap_or("(no preview)")
                 .chars()
                 .take(50)
                 .collect::<String>();
             let current_marker = if is_current { " (current)" } else { "" };
+            let pinned_marker = if session_info.pinned { " (pinned)" } else { "" };
+            let fork_marker = if session_info.forked_from.is_some() {
+                " (fork)"
+            } else {
+                ""
+            };
 
             queue!(
                 stdout,
@@ -1323,17 +1248,33 @@ impl SessionMenuState {
             queue!(stdout, SetForegroundColor(Color::DarkGrey))?;
             write!(stdout, "[{}]", age)?;
 
+            // Where the session is from and its size, when listing every directory
+            let mut location = String::new();
+            if self.all_projects {
+                location = format!(
+                    " {} ({} msgs)",
+                    super::shorten_path(&session_info.working_directory),
+                    session_info.message_count
+                );
+                write!(stdout, "{}", location)?;
+            }
+
             // Preview text
             if is_selected {
                 queue!(stdout, SetForegroundColor(Color::Cyan))?;
             } else if is_current {
                 queue!(stdout, SetForegroundColor(Color::Green))?;
+            } else if session_info.pinned {
+                queue!(stdout, SetForegroundColor(Color::Yellow))?;
             } else {
                 queue!(stdout, ResetColor)?;
             }
 
-            let line = format!(" {}{}", preview, current_marker);
-            let max_len = term_width.saturating_sub(15 + age.len()); // Account for prefix
+            let line = format!(
+                " {}{}{}{}",
+                preview, current_marker, pinned_marker, fork_marker
+            );
+            let max_len = term_width.saturating_sub(15 + age.len() + location.chars().count()); // Account for prefix
             let display_line: String = line.chars().take(max_len).collect();
             write!(stdout, "{}", display_line)?;
 
@@ -1389,6 +1330,23 @@ pub(super) struct McpServerOption {
     pub name: String,
     pub state: McpServerState,
     pub tool_count: usize,
+    /// Transport and command or URL
+    pub endpoint: String,
+    /// Why the server failed to start, or that its connection closed
+    pub error: Option<String>,
+}
+
+impl McpServerOption {
+    /// Status label and color, accounting for failures
+    fn status_label(&self) -> (&'static str, Color) {
+        match (self.state, &self.error) {
+            (McpServerState::Starting, _) => ("[Starting]", Color::Yellow),
+            (McpServerState::Disabled, Some(_)) => ("[Failed]  ", Color::Red),
+            (McpServerState::Disabled, None) => ("[Disabled]", Color::DarkGrey),
+            (McpServerState::Enabled, Some(_)) => ("[Closed]  ", Color::Red),
+            (McpServerState::Enabled, None) => ("[Enabled] ", Color::Green),
+        }
+    }
 }
 
 /// State for the MCP server selection menu
@@ -1402,7 +1360,17 @@ pub(super) struct McpMenuState {
 impl McpMenuState {
     /// Create a new MCP menu state from the current server statuses
     pub fn new(statuses: Vec<crate::mcp::McpServerStatus>) -> Self {
-        let servers = statuses
+        let mut menu = Self {
+            servers: Vec::new(),
+            selected_index: 0,
+        };
+        menu.update_statuses(statuses);
+        menu
+    }
+
+    /// Replace the server list with fresh statuses, keeping the selection
+    pub fn update_statuses(&mut self, statuses: Vec<crate::mcp::McpServerStatus>) {
+        self.servers = statuses
             .into_iter()
             .map(|s| McpServerOption {
                 name: s.name,
@@ -1412,12 +1380,13 @@ impl McpMenuState {
                     McpServerState::Disabled
                 },
                 tool_count: s.tool_count,
+                endpoint: s.endpoint,
+                error: s.error,
             })
             .collect();
-        Self {
-            servers,
-            selected_index: 0,
-        }
+        self.selected_index = self
+            .selected_index
+            .min(self.servers.len().saturating_sub(1));
     }
 
     /// Get the name of the selected server
@@ -1442,18 +1411,6 @@ impl McpMenuState {
         }
     }
 
-    /// Update server status after toggle completes
-    pub fn update_server_status(&mut self, name: &str, is_running: bool, tool_count: usize) {
-        if let Some(server) = self.servers.iter_mut().find(|s| s.name == name) {
-            server.state = if is_running {
-                McpServerState::Enabled
-            } else {
-                McpServerState::Disabled
-            };
-            server.tool_count = tool_count;
-        }
-    }
-
     /// Handle a key event, returning the action to take
     pub fn handle_key(&mut self, key: KeyEvent) -> McpMenuAction {
         let total = self.servers.len();
@@ -1519,16 +1476,8 @@ impl McpMenuState {
     pub fn render(&self, stdout: &mut io::Stdout, start_row: u16) -> io::Result<()> {
         let term_width = terminal::size().map(|(w, _)| w as usize).unwrap_or(80);
 
-        let bg_normal = Color::Rgb {
-            r: 20,
-            g: 20,
-            b: 20,
-        };
-        let bg_selected = Color::Rgb {
-            r: 30,
-            g: 30,
-            b: 30,
-        };
+        let bg_normal = capabilities::rgb(20, 20, 20);
+        let bg_selected = capabilities::rgb(30, 30, 30);
 
         // Header line
         queue!(
@@ -1584,30 +1533,14 @@ impl McpMenuState {
             let (bg_color, name_color, status_color) = if is_selected {
                 (
                     bg_selected,
-                    Color::Rgb {
-                        r: 137,
-                        g: 180,
-                        b: 250,
-                    },
-                    Color::Rgb {
-                        r: 200,
-                        g: 200,
-                        b: 200,
-                    },
+                    capabilities::rgb(137, 180, 250),
+                    capabilities::rgb(200, 200, 200),
                 )
             } else {
                 (
                     bg_normal,
-                    Color::Rgb {
-                        r: 120,
-                        g: 120,
-                        b: 120,
-                    },
-                    Color::Rgb {
-                        r: 150,
-                        g: 150,
-                        b: 150,
-                    },
+                    capabilities::rgb(120, 120, 120),
+                    capabilities::rgb(150, 150, 150),
                 )
             };
 
@@ -1624,11 +1557,7 @@ impl McpMenuState {
             write!(stdout, " ")?;
 
             // Status label with color
-            let (status_label, label_color) = match server.state {
-                McpServerState::Disabled => ("[Disabled]", Color::DarkGrey),
-                McpServerState::Starting => ("[Starting]", Color::Yellow),
-                McpServerState::Enabled => ("[Enabled] ", Color::Green),
-            };
+            let (status_label, label_color) = server.status_label();
             queue!(stdout, SetForegroundColor(label_color))?;
             write!(stdout, "{}", status_label)?;
             write!(stdout, " ")?;
@@ -1650,7 +1579,26 @@ impl McpMenuState {
             } else {
                 String::new()
             };
-            let content_width = 3 + status_label.width() + 1 + max_name_width + tool_info.width();
+            let mut content_width =
+                3 + status_label.width() + 1 + max_name_width + tool_info.width();
+
+            // The error if there is one, otherwise where the server is
+            let (detail, detail_color) = match &server.error {
+                Some(error) => (error.as_str(), Color::Red),
+                None => (server.endpoint.as_str(), Color::DarkGrey),
+            };
+            let available = term_width.saturating_sub(content_width + 2);
+            if available > 1 {
+                let detail = detail.lines().next().unwrap_or_default();
+                let detail = if detail.width() > available {
+                    super::prompt::truncate_to_width(detail, available - 1)
+                } else {
+                    detail.to_string()
+                };
+                queue!(stdout, SetForegroundColor(detail_color))?;
+                write!(stdout, "  {}", detail)?;
+                content_width += 2 + detail.width();
+            }
             let remaining = term_width.saturating_sub(content_width);
             write!(stdout, "{:width$}", "", width = remaining)?;
 
@@ -1716,7 +1664,7 @@ impl HistorySearchState {
                 })
                 .collect();
 
-            matches.sort_by(|(_, score_a), (_, score_b)| score_a.cmp(score_b));
+            matches.sort_by_key(|(_, score)| *score);
             matches.into_iter().map(|(entry, _)| entry).collect()
         }
     }
@@ -1819,16 +1767,8 @@ impl HistorySearchState {
         let term_width = terminal::size().map(|(w, _)| w as usize).unwrap_or(80);
 
         // Background colors matching model menu style
-        let bg_normal = Color::Rgb {
-            r: 20,
-            g: 20,
-            b: 20,
-        };
-        let bg_selected = Color::Rgb {
-            r: 30,
-            g: 30,
-            b: 30,
-        };
+        let bg_normal = capabilities::rgb(20, 20, 20);
+        let bg_selected = capabilities::rgb(30, 30, 30);
 
         // Draw header/search line with popup background
         queue!(
@@ -1898,24 +1838,10 @@ impl HistorySearchState {
 
             // Selection indicator
             if is_selected {
-                queue!(
-                    stdout,
-                    SetForegroundColor(Color::Rgb {
-                        r: 137,
-                        g: 180,
-                        b: 250
-                    })
-                )?;
+                queue!(stdout, SetForegroundColor(capabilities::rgb(137, 180, 250)))?;
                 write!(stdout, " > ")?;
             } else {
-                queue!(
-                    stdout,
-                    SetForegroundColor(Color::Rgb {
-                        r: 120,
-                        g: 120,
-                        b: 120
-                    })
-                )?;
+                queue!(stdout, SetForegroundColor(capabilities::rgb(120, 120, 120)))?;
                 write!(stdout, "   ")?;
             }
 
@@ -2101,16 +2027,8 @@ impl ToolsMenuState {
     pub fn render(&self, stdout: &mut io::Stdout, start_row: u16) -> io::Result<()> {
         let term_width = terminal::size().map(|(w, _)| w as usize).unwrap_or(80);
 
-        let bg_normal = Color::Rgb {
-            r: 20,
-            g: 20,
-            b: 20,
-        };
-        let bg_selected = Color::Rgb {
-            r: 30,
-            g: 30,
-            b: 30,
-        };
+        let bg_normal = capabilities::rgb(20, 20, 20);
+        let bg_selected = capabilities::rgb(30, 30, 30);
 
         // Header line
         queue!(
@@ -2190,17 +2108,9 @@ impl ToolsMenuState {
             let name_color = if tool.is_read_only_locked {
                 Color::DarkGrey
             } else if is_selected {
-                Color::Rgb {
-                    r: 137,
-                    g: 180,
-                    b: 250,
-                }
+                capabilities::rgb(137, 180, 250)
             } else {
-                Color::Rgb {
-                    r: 120,
-                    g: 120,
-                    b: 120,
-                }
+                capabilities::rgb(120, 120, 120)
             };
             queue!(stdout, SetForegroundColor(name_color))?;
             write!(stdout, "{:width$}", tool.name, width = max_name_width)?;
@@ -2209,11 +2119,7 @@ impl ToolsMenuState {
             let desc_color = if tool.is_read_only_locked {
                 Color::DarkGrey
             } else {
-                Color::Rgb {
-                    r: 128,
-                    g: 128,
-                    b: 128,
-                }
+                capabilities::rgb(128, 128, 128)
             };
             queue!(stdout, SetForegroundColor(desc_color))?;
             write!(stdout, "  {}", tool.description)?;
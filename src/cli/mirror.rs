@@ -0,0 +1,403 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Read-only mirrors of a running interactive session.
+//!
+//! `henri mirror --listen <addr>` watches the session in the current
+//! directory through its remote control socket and serves what it shows over
+//! HTTP: a page for browsers at `/`, and the events themselves as server-sent
+//! events at `/events`. `henri mirror --follow <addr>` prints those events in
+//! a terminal. Viewers can't send anything to the session.
+
+use std::io::Write;
+use std::sync::OnceLock;
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+
+use super::escapes::sanitize;
+use crate::output::OutputEvent;
+
+/// Events buffered for a viewer that falls behind before it misses some.
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// What a viewer is shown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum MirrorEvent {
+    Prompt {
+        text: String,
+    },
+    Thinking {
+        text: String,
+    },
+    Text {
+        text: String,
+    },
+    TextEnd,
+    ToolCall {
+        description: String,
+    },
+    ToolResult {
+        tool_name: String,
+        is_error: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        summary: Option<String>,
+    },
+    ToolOutput {
+        text: String,
+    },
+    FileDiff {
+        diff: String,
+    },
+    Info {
+        message: String,
+    },
+    Warning {
+        message: String,
+    },
+    Error {
+        message: String,
+    },
+    Done,
+}
+
+impl MirrorEvent {
+    /// The event shown for an output event, if it is shown at all.
+    fn from_output(event: &OutputEvent) -> Option<Self> {
+        Some(match event {
+            OutputEvent::Thinking(text) => MirrorEvent::Thinking { text: text.clone() },
+            OutputEvent::Text(text) => MirrorEvent::Text { text: text.clone() },
+            OutputEvent::TextEnd => MirrorEvent::TextEnd,
+            OutputEvent::ToolCall { description } => MirrorEvent::ToolCall {
+                description: description.clone(),
+            },
+            OutputEvent::ToolResult {
+                tool_name,
+                is_error,
+                summary,
+                ..
+            } => MirrorEvent::ToolResult {
+                tool_name: tool_name.clone(),
+                is_error: *is_error,
+                summary: summary.clone(),
+            },
+            OutputEvent::ToolOutput { text } | OutputEvent::FileReadOutput { text, .. } => {
+                MirrorEvent::ToolOutput { text: text.clone() }
+            }
+            OutputEvent::FileDiff { diff, .. } => MirrorEvent::FileDiff { diff: diff.clone() },
+            OutputEvent::Info(message) => MirrorEvent::Info {
+                message: message.clone(),
+            },
+            OutputEvent::Warning(message) => MirrorEvent::Warning {
+                message: message.clone(),
+            },
+            OutputEvent::RateLimited { message, .. } => MirrorEvent::Warning {
+                message: message.clone(),
+            },
+            OutputEvent::Error(message) => MirrorEvent::Error {
+                message: message.clone(),
+            },
+            OutputEvent::Done => MirrorEvent::Done,
+            _ => return None,
+        })
+    }
+
+    /// Render the event for a terminal, as text to print without a newline.
+    /// The text comes from a network peer, so it is sanitized.
+    fn render(&self) -> String {
+        match self {
+            MirrorEvent::Prompt { text } => {
+                format!("\n{} {}\n\n", ">".cyan().bold(), sanitize(text))
+            }
+            MirrorEvent::Thinking { text } => sanitize(text).bright_black().to_string(),
+            MirrorEvent::Text { text } => sanitize(text).into_owned(),
+            MirrorEvent::TextEnd => "\n".to_string(),
+            MirrorEvent::ToolCall { description } => {
+                format!("\n{} {}\n", "▶".cyan(), sanitize(description))
+            }
+            MirrorEvent::ToolResult {
+                is_error, summary, ..
+            } => {
+                let status = if *is_error {
+                    "✗".red()
+                } else {
+                    "✓".green()
+                };
+                match summary {
+                    Some(summary) => {
+                        format!("{} {}\n", status, sanitize(summary).bright_black())
+                    }
+                    None => format!("{}\n", status),
+                }
+            }
+            MirrorEvent::ToolOutput { text } => sanitize(text).bright_black().to_string(),
+            MirrorEvent::FileDiff { diff } => format!("{}\n", sanitize(diff)),
+            MirrorEvent::Info { message } => format!("{}\n", sanitize(message).bright_black()),
+            MirrorEvent::Warning { message } => format!("{}\n", sanitize(message).yellow()),
+            MirrorEvent::Error { message } => format!("{}\n", sanitize(message).red()),
+            MirrorEvent::Done => String::new(),
+        }
+    }
+}
+
+/// Watchers of this session, connected through the remote control socket.
+fn watchers() -> &'static broadcast::Sender<MirrorEvent> {
+    static WATCHERS: OnceLock<broadcast::Sender<MirrorEvent>> = OnceLock::new();
+    WATCHERS.get_or_init(|| broadcast::channel(CHANNEL_CAPACITY).0)
+}
+
+/// Start receiving what this session shows.
+pub(crate) fn watch() -> broadcast::Receiver<MirrorEvent> {
+    watchers().subscribe()
+}
+
+/// Send an output event to any watchers.
+pub(super) fn publish_output(event: &OutputEvent) {
+    if watchers().receiver_count() > 0
+        && let Some(event) = MirrorEvent::from_output(event)
+    {
+        let _ = watchers().send(event);
+    }
+}
+
+/// Send a prompt typed in this session to any watchers.
+pub(super) fn publish_prompt(text: &str) {
+    if watchers().receiver_count() > 0 {
+        let _ = watchers().send(MirrorEvent::Prompt {
+            text: text.to_string(),
+        });
+    }
+}
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>henri mirror</title>
+<style>
+body { background: #1e1e1e; color: #ddd; font: 14px/1.4 monospace; margin: 1em 2em; }
+pre { white-space: pre-wrap; margin: 0; }
+.prompt { color: #5fd7ff; margin-top: 1em; }
+.thinking, .tool-output, .info { color: #888; }
+.tool { color: #5fd7ff; margin-top: .5em; }
+.warning { color: #d7af00; }
+.error { color: #ff5f5f; }
+</style>
+</head>
+<body>
+<pre id="out"></pre>
+<script>
+const out = document.getElementById("out");
+let block = null;
+function append(cls, text) {
+  if (!block || block.className !== cls) {
+    block = document.createElement("div");
+    block.className = cls;
+    out.appendChild(block);
+  }
+  block.textContent += text;
+  window.scrollTo(0, document.body.scrollHeight);
+}
+function line(cls, text) {
+  block = null;
+  append(cls, text);
+  block = null;
+}
+const source = new EventSource("/events");
+source.onmessage = (message) => {
+  const e = JSON.parse(message.data);
+  switch (e.type) {
+    case "prompt": line("prompt", "> " + e.text); break;
+    case "thinking": append("thinking", e.text); break;
+    case "text": append("text", e.text); break;
+    case "text_end": block = null; break;
+    case "tool_call": line("tool", "▶ " + e.description); break;
+    case "tool_result": line(e.is_error ? "error" : "info", (e.is_error ? "✗ " : "✓ ") + (e.summary || "")); break;
+    case "tool_output": append("tool-output", e.text); break;
+    case "file_diff": line("tool-output", e.diff); break;
+    case "info": line("info", e.message); break;
+    case "warning": line("warning", e.message); break;
+    case "error": line("error", e.message); break;
+  }
+};
+source.onerror = () => line("warning", "Disconnected, retrying...");
+</script>
+</body>
+</html>
+"#;
+
+/// Serve the events on `events` to viewers connecting to `listener`.
+async fn serve_viewers(listener: TcpListener, events: broadcast::Sender<MirrorEvent>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(serve_viewer(stream, events.subscribe()));
+    }
+}
+
+async fn serve_viewer(stream: TcpStream, mut events: broadcast::Receiver<MirrorEvent>) {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    let Ok(Some(request)) = lines.next_line().await else {
+        return;
+    };
+    // Skip the headers
+    while let Ok(Some(header)) = lines.next_line().await {
+        if header.is_empty() {
+            break;
+        }
+    }
+
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+    match path {
+        "/" => {
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                PAGE.len(),
+                PAGE
+            );
+            let _ = write.write_all(response.as_bytes()).await;
+        }
+        "/events" => {
+            let header = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+                          Cache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n";
+            if write.write_all(header.as_bytes()).await.is_err() {
+                return;
+            }
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => MirrorEvent::Warning {
+                        message: format!("[{} events missed]", missed),
+                    },
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let Ok(json) = serde_json::to_string(&event) else {
+                    continue;
+                };
+                if write
+                    .write_all(format!("data: {}\n\n", json).as_bytes())
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        }
+        _ => {
+            let _ = write
+                .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                .await;
+        }
+    }
+}
+
+/// `henri mirror --listen <addr>`: serve the session running here to
+/// read-only viewers until it exits. Viewers aren't authenticated, so only
+/// loopback addresses are served unless `allow_remote` is set.
+pub(crate) async fn listen(
+    addr: &str,
+    pid: Option<u32>,
+    allow_remote: bool,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    let local = listener.local_addr()?;
+    if !local.ip().is_loopback() && !allow_remote {
+        return Err(std::io::Error::other(format!(
+            "{} is not a loopback address; pass --allow-remote to let anyone who can reach it read the session",
+            local
+        )));
+    }
+    let mut session = super::remote::watch(pid).await?;
+    eprintln!("Mirroring the session to http://{}/", local);
+    eprintln!(
+        "Follow it in a terminal with: henri mirror --follow {}",
+        local
+    );
+    if !local.ip().is_loopback() {
+        eprintln!(
+            "{}",
+            "Anyone who can reach this address can read the session.".yellow()
+        );
+    }
+
+    let (events, _) = broadcast::channel(CHANNEL_CAPACITY);
+    tokio::spawn(serve_viewers(listener, events.clone()));
+    while let Some(event) = session.recv().await {
+        let _ = events.send(event);
+    }
+    eprintln!("The session ended.");
+    Ok(())
+}
+
+/// `henri mirror --follow <addr>`: print a mirrored session.
+pub(crate) async fn follow(addr: &str) -> std::io::Result<()> {
+    let stream = TcpStream::connect(addr).await?;
+    let (read, mut write) = stream.into_split();
+    write
+        .write_all(format!("GET /events HTTP/1.1\r\nHost: {}\r\n\r\n", addr).as_bytes())
+        .await?;
+
+    let mut lines = BufReader::new(read).lines();
+    let mut stdout = std::io::stdout();
+    while let Some(line) = lines.next_line().await? {
+        let Some(json) = line.strip_prefix("data: ") else {
+            continue;
+        };
+        if let Ok(event) = serde_json::from_str::<MirrorEvent>(json) {
+            write!(stdout, "{}", event.render())?;
+            stdout.flush()?;
+        }
+    }
+    eprintln!("\nThe mirror closed.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_viewer_receives_events() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (events, _) = broadcast::channel(CHANNEL_CAPACITY);
+        tokio::spawn(serve_viewers(listener, events.clone()));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let (read, mut write) = stream.into_split();
+        write
+            .write_all(b"GET /events HTTP/1.1\r\nHost: test\r\n\r\n")
+            .await
+            .unwrap();
+        let mut lines = BufReader::new(read).lines();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "HTTP/1.1 200 OK");
+        while !lines.next_line().await.unwrap().unwrap().is_empty() {}
+
+        let event = MirrorEvent::from_output(&OutputEvent::ToolCall {
+            description: "Read src/main.rs".to_string(),
+        })
+        .unwrap();
+        // The viewer subscribes once its request is read
+        while events.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        events.send(event.clone()).unwrap();
+
+        let line = lines.next_line().await.unwrap().unwrap();
+        let json = line.strip_prefix("data: ").unwrap();
+        assert_eq!(serde_json::from_str::<MirrorEvent>(json).unwrap(), event);
+        assert!(MirrorEvent::from_output(&OutputEvent::Waiting).is_none());
+    }
+
+    #[test]
+    fn test_render_strips_escapes() {
+        let event = MirrorEvent::Text {
+            text: "\x1b]52;c;cm0gLXJmIH4=\x07\x1b]0;title\x07hello".to_string(),
+        };
+        assert_eq!(event.render(), "hello");
+    }
+}
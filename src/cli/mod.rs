@@ -6,26 +6,41 @@
 //! Uses an event-driven architecture with a unified event loop that handles
 //! keyboard input, resize events, and chat streaming concurrently.
 
+mod capabilities;
 mod clipboard;
 mod completion_menu;
+mod copy;
 mod editor;
+pub(crate) mod escapes;
 pub(crate) mod history;
+#[cfg(feature = "images")]
 pub(crate) mod image_preview;
 mod input;
+mod json_output;
 pub(crate) mod listener;
 mod markdown;
+mod math;
 mod menus;
+pub(crate) mod mirror;
+mod paste;
 mod prompt;
+pub(crate) mod remote;
 pub(crate) mod render;
+mod search;
+mod shell;
 mod slash_menu;
 pub(crate) mod spacing;
+mod statusline;
 mod style;
+pub(crate) mod tee;
 pub(crate) mod terminal;
 
 pub(crate) const TOOL_OUTPUT_VIEWPORT_LINES: usize = 5;
 pub(crate) const TOOL_OUTPUT_VIEWPORT_SPACER_LINES: u16 = 1;
 /// Maximum lines to keep in buffer (matches model context limit)
 pub(crate) const TOOL_OUTPUT_MAX_BUFFER_LINES: usize = 2000;
+/// Lines scrolled per mouse wheel notch in the tool output viewport
+const MOUSE_SCROLL_LINES: isize = 3;
 
 // Shared UI prefixes/symbols (kept here to avoid drift between renderer/listener).
 const TOOL_USE_PREFIX: &str = "● ";
@@ -41,8 +56,8 @@ use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
 use colored::Colorize;
 use crossterm::event::{
-    DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEventKind, KeyModifiers,
-    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+    MouseEventKind,
 };
 use crossterm::execute;
 use crossterm::terminal as crossterm_terminal;
@@ -58,7 +73,7 @@ pub(crate) struct PastedImage {
 }
 
 use crate::commands::Command;
-use crate::config::Config;
+use crate::config::{Config, SandboxMode};
 use crate::custom_commands::{self, CustomCommand};
 use crate::history::FileHistory;
 use crate::output::{self, OutputContext};
@@ -112,6 +127,7 @@ fn echo_user_prompt_to_output(prompt: &str, pasted_images: &[PastedImage]) {
         .collect();
 
     history::push_user_prompt(prompt, image_metas.clone());
+    mirror::publish_prompt(prompt);
 
     let rendered = render::render_event(
         &history::HistoryEvent::UserPrompt {
@@ -169,6 +185,55 @@ struct PendingPrompt {
     input: String,
     /// Images pasted with this prompt
     images: Vec<PastedImage>,
+    /// Model to use for this prompt only (from a trailing `@provider/model`)
+    model: Option<ModelChoice>,
+}
+
+impl PendingPrompt {
+    fn new(content: String, images: Vec<PastedImage>) -> Self {
+        let (input, model) =
+            split_model_override(&content, &crate::providers::build_model_choices());
+        Self {
+            input,
+            images,
+            model,
+        }
+    }
+
+    /// The prompt as the user typed it, including any model override.
+    fn editable_text(&self) -> String {
+        match &self.model {
+            Some(model) => format!("{} @{}", self.input, model.short_display()),
+            None => self.input.clone(),
+        }
+    }
+}
+
+/// Images referenced with `@path` in what the user typed, to send with the prompt.
+fn referenced_images(typed: &str, working_dir: &std::path::Path) -> Vec<PastedImage> {
+    crate::file_refs::image_references(typed, working_dir)
+        .into_iter()
+        .map(|(marker, image)| PastedImage {
+            marker,
+            mime_type: image.mime_type,
+            data: image.data,
+        })
+        .collect()
+}
+
+/// Split a trailing `@provider/model` off a queued prompt.
+///
+/// Only known models are recognized, so other `@` words are left in the prompt.
+fn split_model_override(content: &str, choices: &[ModelChoice]) -> (String, Option<ModelChoice>) {
+    let trimmed = content.trim_end();
+    if let Some((text, spec)) = trimmed.rsplit_once(|c: char| c.is_whitespace())
+        && let Some(spec) = spec.strip_prefix('@')
+        && !text.trim().is_empty()
+        && let Some(choice) = choices.iter().find(|c| c.short_display() == spec)
+    {
+        return (text.trim_end().to_string(), Some(choice.clone()));
+    }
+    (content.to_string(), None)
 }
 
 #[derive(Debug)]
@@ -215,8 +280,8 @@ struct ShellTask {
     line_rx: tokio::sync::mpsc::Receiver<ShellOutput>,
     /// Output lines collected for history
     output_lines: Vec<String>,
-    /// Exit status when complete
-    exit_status: Option<Option<i32>>,
+    /// Exit status and run time when complete
+    exit_status: Option<(Option<i32>, std::time::Duration)>,
 }
 
 /// Output from a shell command task
@@ -224,7 +289,10 @@ enum ShellOutput {
     /// A line of output (stdout or stderr)
     Line(String),
     /// Command completed with optional exit code
-    Done(Option<i32>),
+    Done {
+        exit_code: Option<i32>,
+        elapsed: std::time::Duration,
+    },
     /// Command failed to start
     Error(String),
 }
@@ -241,6 +309,16 @@ pub(crate) struct CliArgs {
     pub read_only: bool,
     /// Exit after processing the prompt (batch mode)
     pub batch: bool,
+    /// Reasoning level override (off, low, medium, high)
+    pub reasoning: Option<String>,
+    /// Thinking token budget override
+    pub thinking_budget: Option<u32>,
+    /// Sampling seed override
+    pub seed: Option<u64>,
+    /// Append streamed assistant text to this file
+    pub tee: Option<PathBuf>,
+    /// Emit output events as JSON lines instead of rendered text (implies batch mode)
+    pub json: bool,
 }
 
 /// Events from chat completion
@@ -287,6 +365,9 @@ fn supports_thinking(provider: ModelProvider, model: &str) -> bool {
         ModelProvider::Claude => uses_model_variants(provider, model),
         ModelProvider::OpenAiCompat => false, // Thinking is config-only, not UI toggleable
         ModelProvider::OpenRouter => true,
+        ModelProvider::Ollama => true,
+        ModelProvider::Vertex => false,
+        ModelProvider::Mistral => false,
     }
 }
 
@@ -311,6 +392,19 @@ async fn get_mcp_server_count(services: &Services) -> usize {
         .count()
 }
 
+/// Session cost for the prompt box, once anything has been spent
+fn cost_status() -> Option<(f64, bool)> {
+    let cost = crate::usage::session_cost();
+    if cost <= 0.0 {
+        return None;
+    }
+    let config = crate::config::ConfigFile::load().unwrap_or_default();
+    Some((
+        cost,
+        crate::usage::cost_limit_reached(&config.cost).is_some(),
+    ))
+}
+
 /// Update the prompt box with current provider/model, cwd, and thinking info
 async fn update_prompt_status(
     prompt_box: &mut PromptBox,
@@ -340,6 +434,46 @@ async fn update_prompt_status(
     let lsp_server_count = get_lsp_server_count().await;
     let mcp_server_count = get_mcp_server_count(services).await;
 
+    let custom_status = match statusline::command() {
+        Some(command) => {
+            let snapshot = statusline::Snapshot {
+                provider: provider.clone(),
+                model: model.clone(),
+                thinking: thinking.available.then(|| {
+                    if thinking.enabled {
+                        thinking.mode.clone().unwrap_or_else(|| "on".to_string())
+                    } else {
+                        "off".to_string()
+                    }
+                }),
+                cwd: working_dir.display().to_string(),
+                // Looked up in the background
+                git_branch: None,
+                read_only: security.read_only,
+                sandbox: security.sandbox_enabled,
+                context: statusline::ContextSnapshot::new(
+                    provider_manager.get_last_context_usage(),
+                    provider_manager.get_context_limit(),
+                ),
+                tokens: provider_manager
+                    .get_total_usage()
+                    .map(|(input, output)| statusline::TokenSnapshot { input, output }),
+                cost: statusline::CostSnapshot {
+                    session: crate::usage::session_cost(),
+                    turn: crate::usage::turn_cost(),
+                    limit: crate::config::ConfigFile::load()
+                        .ok()
+                        .and_then(|c| c.cost.max_session_cost),
+                },
+            };
+            statusline::request(command, snapshot, working_dir);
+            statusline::current()
+        }
+        None => None,
+    };
+    prompt_box.set_custom_status(custom_status);
+    prompt_box.set_cost(cost_status());
+
     prompt_box.set_status(
         provider,
         model,
@@ -375,6 +509,7 @@ async fn update_prompt_status_from_task(
     };
     let lsp_server_count = get_lsp_server_count().await;
     let mcp_server_count = get_mcp_server_count(services).await;
+    prompt_box.set_cost(cost_status());
     prompt_box.set_status(
         provider_name,
         task.model_id.clone(),
@@ -443,7 +578,11 @@ fn build_ephemeral_config_for_model(model: &str) -> crate::config::Config {
 /// Main entry point for the CLI interface
 pub(crate) async fn run(args: CliArgs) -> std::io::Result<()> {
     // Create output context for CLI
-    let output = {
+    let output = if args.json {
+        // Keep stdout for JSON events; anything else printed goes to stderr.
+        terminal::set_output_to_stderr(true);
+        OutputContext::new_cli(Arc::new(json_output::JsonListener::new()))
+    } else {
         let listener = Box::leak(Box::new(listener::CliListener::new()));
         listener.register_active();
         let proxy: Arc<dyn output::OutputListener> =
@@ -453,13 +592,24 @@ pub(crate) async fn run(args: CliArgs) -> std::io::Result<()> {
 
     let services = Services::new();
 
-    // Enable read-only mode if --read-only was passed
+    // Start in the configured sandbox mode; --read-only takes precedence
+    match crate::config::ConfigFile::load()
+        .map(|c| c.sandbox_mode)
+        .unwrap_or_default()
+    {
+        SandboxMode::ReadWrite => {}
+        SandboxMode::ReadOnly => services.set_read_only(true),
+        SandboxMode::Yolo => services.set_sandbox_enabled(false),
+    }
     if args.read_only {
+        services.set_sandbox_enabled(true);
         services.set_read_only(true);
     }
+    crate::startup::mark("config");
 
-    // Initialize MCP and LSP servers
+    // Register MCP and LSP servers; they are started when first used
     crate::config::initialize_servers(&args.working_dir, args.lsp_override).await;
+    crate::startup::mark("servers");
 
     // If no model specified on CLI, try to use the one from the restored session
     let model = args.model.clone().or_else(|| {
@@ -468,7 +618,7 @@ pub(crate) async fn run(args: CliArgs) -> std::io::Result<()> {
             .map(|s| format!("{}/{}", s.provider, s.model_id))
     });
 
-    let (provider_manager, thinking_state, welcome_message) = match Config::load(model) {
+    let (mut provider_manager, thinking_state, welcome_message) = match Config::load(model) {
         Ok(config) => {
             let provider_manager = ProviderManager::new(&config, services.clone());
             let thinking_state = provider_manager.default_thinking();
@@ -484,21 +634,74 @@ pub(crate) async fn run(args: CliArgs) -> std::io::Result<()> {
             std::process::exit(1);
         }
     };
+    crate::startup::mark("providers");
 
     let mut messages: Vec<Message> = Vec::new();
 
     // NOTE: When modifying current_session_id, also call services.set_session_id()
     // to keep the provider's cache key in sync.
     let mut current_session_id: Option<String> = None;
-    let read_only = args.read_only;
+    let read_only = services.is_read_only();
     let working_dir = args.working_dir;
 
     // Apply restored session if provided
     let mut thinking_state = thinking_state;
+    let mut seed = args.seed;
+    crate::sliding_window::reset();
     if let Some(restored) = args.restored_session {
         messages = restored.messages;
         thinking_state.enabled = restored.thinking_enabled;
+        crate::sliding_window::set_enabled(restored.sliding_window);
+        crate::usage::restore_session_cost(restored.cost);
         current_session_id = Some(restored.session_id);
+        seed = seed.or(restored.seed);
+    }
+    if let Some(pm) = provider_manager.as_mut() {
+        pm.set_seed(seed);
+        pm.set_working_dir(&working_dir);
+    }
+
+    if let Some(path) = args.tee.as_deref()
+        && let Err(e) = tee::start(path)
+    {
+        terminal::println_above(
+            &format!("Failed to open tee file {}: {}", path.display(), e)
+                .red()
+                .to_string(),
+        );
+    }
+
+    // Apply --reasoning / --thinking-budget overrides
+    if let Some(level) = args.reasoning.as_deref()
+        && let Some(pm) = provider_manager.as_mut()
+    {
+        let provider = pm.current_provider();
+        match crate::providers::resolve_reasoning_level(provider, pm.current_model_id(), level) {
+            Ok((model_id, state)) => {
+                let custom_provider = pm.current_custom_provider().map(|s| s.to_string());
+                pm.set_model(provider, model_id, custom_provider);
+                thinking_state = state;
+            }
+            Err(msg) => {
+                terminal::println_above(&format!("Error: {}", msg).red().to_string());
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(budget) = args.thinking_budget
+        && let Some(pm) = provider_manager.as_ref()
+        && let Err(msg) = crate::providers::validate_thinking_budget(pm.current_provider(), budget)
+    {
+        terminal::println_above(&format!("Error: {}", msg).red().to_string());
+        std::process::exit(1);
+    }
+    thinking_state.budget_tokens = args.thinking_budget;
+    if args.thinking_budget.is_some() && !thinking_state.enabled {
+        terminal::println_above(
+            &"Warning: --thinking-budget has no effect while thinking is off; use --reasoning to turn it on"
+                .yellow()
+                .to_string(),
+        );
     }
 
     if current_session_id.is_none() {
@@ -517,10 +720,20 @@ pub(crate) async fn run(args: CliArgs) -> std::io::Result<()> {
     let custom_commands = custom_commands::load_custom_commands().unwrap_or_default();
 
     // Load prompt history
-    let mut prompt_history = FileHistory::new();
+    let history_config = crate::config::ConfigFile::load()
+        .unwrap_or_default()
+        .history;
+    if let Err(e) = crate::history::excluded_patterns(&history_config) {
+        eprintln!(
+            "Warning: not saving prompt history, invalid excluded pattern {}",
+            e
+        );
+    }
+    let mut prompt_history = FileHistory::new(&history_config, &working_dir);
+    crate::startup::mark("history");
 
     // Run the event-driven main loop
-    run_event_loop(
+    let result = run_event_loop(
         &output,
         provider_manager,
         messages,
@@ -533,9 +746,11 @@ pub(crate) async fn run(args: CliArgs) -> std::io::Result<()> {
         &mut prompt_history,
         initial_prompt,
         welcome_message,
-        args.batch,
+        args.batch || args.json,
     )
-    .await
+    .await;
+    crate::hooks::session_end(&services).await;
+    result
 }
 
 /// Run the main event loop
@@ -557,6 +772,9 @@ async fn run_event_loop(
 ) -> std::io::Result<()> {
     let mut prompt_box = PromptBox::new();
     let mut input_state = InputState::new(working_dir.to_path_buf());
+    crate::walk::warm(working_dir);
+    crate::index::warm(working_dir);
+    crate::index::embeddings::warm(working_dir);
 
     // Wrap in Option for ownership transfer during chat
     let mut provider_manager = provider_manager;
@@ -573,6 +791,9 @@ async fn run_event_loop(
     // Active shell command task state
     let mut shell_task: Option<ShellTask> = None;
 
+    // Large paste waiting for the user to choose between attaching and inlining it
+    let mut held_paste: Option<paste::Paste> = None;
+
     // Model menu state (active when Some)
     let mut model_menu: Option<ModelMenuState> = None;
 
@@ -599,6 +820,28 @@ async fn run_event_loop(
     // Only support editing/deleting the most recently queued prompt.
     // When editing, we pop it out of the queue and put it back into the input buffer.
     let mut editing_pending_prompt: Option<PendingPrompt> = None;
+    // Compaction awaiting user review; the summary is loaded into the input for editing
+    let mut compaction_review: Option<CompactionState> = None;
+    // Whether the user has already been warned about nearly exhausted rate limits
+    let mut rate_limit_alerted = false;
+    let rate_limit_fallback = crate::config::ConfigFile::load()
+        .map(|c| c.rate_limit_fallback)
+        .unwrap_or_default();
+    // Model and thinking state to return to after a queued prompt's model override
+    let mut model_override_restore: Option<(ModelChoice, crate::providers::ThinkingState)> = None;
+
+    // Prompts from `henri send`, submitted when the input is free
+    let mut remote = if batch {
+        None
+    } else {
+        remote::RemoteControl::start(working_dir).ok()
+    };
+    let mut remote_prompts: VecDeque<String> = VecDeque::new();
+    let mut remote_submit = false;
+
+    // Session from another directory picked in the sessions menu, opened by
+    // relaunching there once the terminal is restored
+    let mut switch_session: Option<session::SessionInfo> = None;
 
     // Exit prompt state
     let mut exit_prompt: Option<std::time::Instant> = None;
@@ -610,26 +853,51 @@ async fn run_event_loop(
     // Track LSP generation to detect when servers start during streaming
     let mut last_lsp_generation = crate::lsp::generation();
 
+    // Optional mouse capture (wheel scrolls the tool output viewport)
+    let mouse_capture = !batch
+        && crate::config::ConfigFile::load()
+            .map(|c| c.mouse_capture)
+            .unwrap_or(false);
+
+    // Prompt without borders or terminal title
+    let minimal_ui = crate::config::ConfigFile::load()
+        .map(|c| c.minimal_ui)
+        .unwrap_or(false);
+    prompt_box.set_minimal(minimal_ui);
+
     // Enable raw mode for the entire session (skip in batch mode)
     if !batch {
-        let cwd_for_title = shorten_path(working_dir);
-        update_terminal_title(&format!("🐕 {}", cwd_for_title));
+        if !minimal_ui {
+            let cwd_for_title = shorten_path(working_dir);
+            update_terminal_title(&format!("🐕 {}", cwd_for_title));
+        }
 
         crossterm_terminal::enable_raw_mode()?;
         // Enable keyboard enhancement for Ctrl+M support (to distinguish from Enter)
-        // Enable bracketed paste to handle multi-line paste properly
-        execute!(
-            std::io::stdout(),
-            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES),
-            EnableBracketedPaste
-        )?;
+        // and bracketed paste to handle multi-line paste properly, where the
+        // terminal supports them
+        capabilities::detect();
+        capabilities::enable_input_modes()?;
+        set_mouse_capture(mouse_capture);
     }
+    crate::startup::mark("terminal");
 
     // Load settings (needed for both interactive and batch mode).
     // Do this before any history replay so startup rendering matches live/redraw spacing.
     listener::reload_show_image_previews();
     listener::reload_hide_tool_output();
+    listener::reload_instant_paint();
+    escapes::reload_config();
     spacing::reload_compact_mode();
+    statusline::reload_command();
+    shell::reload_config();
+    paste::reload_threshold();
+    crate::watchdog::set_interactive(!batch);
+    if !batch {
+        // Discover installed Ollama models for the model menu in the background.
+        tokio::spawn(crate::provider::ollama::refresh_models());
+    }
+    crate::startup::mark("settings");
 
     // Initial draw (skip in batch mode - no interactive prompt needed)
     if !batch {
@@ -662,6 +930,14 @@ async fn run_event_loop(
 
         // Start spinner updates.
         listener::init_spinner();
+
+        // Load the highlighting assets off the startup path, before the first
+        // code block needs them.
+        tokio::task::spawn_blocking(crate::syntax::warm);
+    }
+    crate::startup::mark("prompt");
+    if let Some(report) = crate::startup::report() {
+        terminal::println_above(&report);
     }
 
     // Submit initial prompt if provided
@@ -685,6 +961,7 @@ async fn run_event_loop(
                 custom_commands,
                 &mut provider_manager,
                 thinking_state,
+                &mut model_override_restore,
             )
             .await;
 
@@ -694,10 +971,24 @@ async fn run_event_loop(
                         return Ok(());
                     }
                 }
+                ProcessResult::AttachImage(image) => {
+                    if batch {
+                        return Ok(());
+                    }
+                    input_state.add_pasted_image(image.mime_type, image.data);
+                    input_state.insert_str(" ");
+                }
+                ProcessResult::ClearHistory(scope) => {
+                    clear_prompt_history(prompt_history, scope);
+                    if batch {
+                        return Ok(());
+                    }
+                }
                 ProcessResult::Quit => return Ok(()),
                 ProcessResult::StartChat(prompt, history_entry) => {
                     let history_text = history_entry.as_ref().unwrap_or(&prompt);
                     let _ = prompt_history.add_with_images(history_text, vec![]);
+                    let images = referenced_images(history_text, working_dir);
 
                     processing_initial_prompt = batch;
 
@@ -712,12 +1003,13 @@ async fn run_event_loop(
                             model_id: pm.current_model_id().to_string(),
                             thinking_enabled: thinking_state.enabled,
                             read_only,
+                            seed: pm.seed(),
                             // Safe to unwrap: we just ensured it's Some above
                             session_id: current_session_id.clone().unwrap(),
                         };
                         chat_task = Some(spawn_chat_task(
                             prompt,
-                            vec![],
+                            images,
                             &mut messages,
                             pm,
                             thinking_state,
@@ -842,11 +1134,8 @@ async fn run_event_loop(
                     // - run inquire prompts
                     // - restore terminal modes
                     let _ = crossterm_terminal::disable_raw_mode();
-                    let _ = execute!(
-                        std::io::stdout(),
-                        PopKeyboardEnhancementFlags,
-                        DisableBracketedPaste
-                    );
+                    let _ = capabilities::disable_input_modes();
+                    set_mouse_capture(false);
                     let _ = prompt_box.hide_and_clear();
 
                     // Run the provider management flow
@@ -869,13 +1158,8 @@ async fn run_event_loop(
 
                     // Restore terminal state without full redraw
                     println!();
-                    let _ = execute!(
-                        std::io::stdout(),
-                        PushKeyboardEnhancementFlags(
-                            KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
-                        ),
-                        EnableBracketedPaste
-                    );
+                    let _ = capabilities::enable_input_modes();
+                    set_mouse_capture(mouse_capture);
                     let _ = crossterm_terminal::enable_raw_mode();
 
                     // Refresh prompt status with potentially new provider
@@ -919,45 +1203,60 @@ async fn run_event_loop(
                                 }
                             });
                             pending_prompts.clear();
+                        } else if batch {
+                            // Nobody to review the summary in batch mode; accept it as-is.
+                            let summary = compaction_summary(&task_result.messages);
+                            archive_compacted_transcript(
+                                working_dir,
+                                current_session_id.as_deref(),
+                                &compaction_state.original,
+                            );
+                            messages = finalize_compaction(summary, compaction_state);
+                            print_compaction_done(messages.len());
                         } else {
-                            // Finalize compaction
-                            messages = finalize_compaction(task_result.messages, compaction_state);
+                            // Keep the original history until the user accepts the summary.
+                            let summary = compaction_summary(&task_result.messages);
+                            messages = compaction_state.original.clone();
+                            compaction_review = Some(compaction_state);
+                            input_state.set_content(summary.trim_end());
                             terminal::ensure_line_break();
                             terminal::println_above("");
-                            // Avoid printing a trailing newline so we don't leave an extra blank
-                            // row above the reserved streaming status line.
-                            terminal::print_above(
-                                &format!("[Compacted {} messages into summary.]", messages.len())
-                                    .green()
+                            terminal::println_above(
+                                &"Review summary (Enter to accept, Ctrl+G to edit in $EDITOR, Esc to discard)"
+                                    .bright_black()
                                     .to_string(),
                             );
+                            prompt_box.draw(&input_state, true)?;
                         }
                         chat_task = None;
                     } else {
                         // Normal chat completion
                         messages = task_result.messages;
 
-                        // Apply pending model change if any
+                        // Apply pending model change if any, otherwise undo a
+                        // per-prompt model override
+                        let restore = model_override_restore.take();
                         if let Some(choice) = pending_model_change.take()
                             && let Some(ref mut pm) = provider_manager
                         {
-                            let provider_changed = pm.set_model(
-                                choice.provider,
-                                choice.model_id.clone(),
-                                choice.custom_provider.clone(),
+                            apply_model_choice(
+                                pm,
+                                &choice,
+                                &mut messages,
+                                thinking_state,
+                                &mut input_state,
                             );
-                            if provider_changed {
-                                crate::provider::transform_thinking_for_provider_switch(
-                                    &mut messages,
-                                );
-                            }
-                            // Update thinking state to new model's default
-                            let new_thinking =
-                                default_thinking_state(choice.provider, &choice.model_id);
-                            thinking_state.enabled = new_thinking.enabled;
-                            thinking_state.mode = new_thinking.mode;
-                            // Update is_claude for slash menu filtering
-                            input_state.set_is_claude(choice.provider == ModelProvider::Claude);
+                        } else if let Some((choice, thinking)) = restore
+                            && let Some(ref mut pm) = provider_manager
+                        {
+                            apply_model_choice(
+                                pm,
+                                &choice,
+                                &mut messages,
+                                thinking_state,
+                                &mut input_state,
+                            );
+                            *thinking_state = thinking;
                         }
 
                         chat_task = None;
@@ -972,7 +1271,7 @@ async fn run_event_loop(
                                     ChatOutcome::Complete
                                 };
 
-                                if let Some(ref pm) = provider_manager {
+                                if let Some(ref mut pm) = provider_manager {
                                     handle_chat_outcome(
                                         outcome,
                                         &mut messages,
@@ -984,6 +1283,29 @@ async fn run_event_loop(
                                         &mut prompt_box,
                                     )?;
 
+                                    if !batch && !was_interrupted {
+                                        spawn_session_title(
+                                            pm,
+                                            &messages,
+                                            working_dir,
+                                            current_session_id.as_deref(),
+                                        );
+                                    }
+
+                                    if let Some(choice) = check_rate_limit_fallback(
+                                        pm,
+                                        &rate_limit_fallback,
+                                        &mut rate_limit_alerted,
+                                    ) {
+                                        apply_model_choice(
+                                            pm,
+                                            &choice,
+                                            &mut messages,
+                                            thinking_state,
+                                            &mut input_state,
+                                        );
+                                    }
+
                                     update_prompt_status(
                                         &mut prompt_box,
                                         pm,
@@ -994,6 +1316,14 @@ async fn run_event_loop(
                                     .await;
                                 }
 
+                                if !batch
+                                    && !was_interrupted
+                                    && let Some(hint) = crate::lsp::quickfix::take_hint("Ctrl+F")
+                                {
+                                    terminal::println_above(&hint.yellow().to_string());
+                                    history::push(history::HistoryEvent::Info(hint));
+                                }
+
                                 // Exit in batch mode after initial prompt completes
                                 if processing_initial_prompt {
                                     break;
@@ -1016,6 +1346,7 @@ async fn run_event_loop(
                                         pm.current_model_id(),
                                         thinking_state.enabled,
                                         read_only,
+                                        pm.seed(),
                                         current_session_id.as_deref(),
                                     ) {
                                         Ok(id) => *current_session_id = Some(id),
@@ -1064,10 +1395,20 @@ async fn run_event_loop(
 
                     // Pop next prompt from queue and start it
                     if let Some(next) = pending_prompts.pop_front()
-                        && let Some(pm) = provider_manager.take()
+                        && let Some(mut pm) = provider_manager.take()
                     {
                         // Note: prompt was already added to history when queued
 
+                        if let Some(ref choice) = next.model {
+                            model_override_restore = apply_model_override(
+                                &mut pm,
+                                choice,
+                                &mut messages,
+                                thinking_state,
+                                &mut input_state,
+                            );
+                        }
+
                         // Expand custom commands at execution time (important for queued prompts).
                         let prompt_to_send =
                             expand_custom_command_if_needed(&next.input, custom_commands)
@@ -1086,6 +1427,7 @@ async fn run_event_loop(
                             model_id: pm.current_model_id().to_string(),
                             thinking_enabled: thinking_state.enabled,
                             read_only,
+                            seed: pm.seed(),
                             // Safe to unwrap: we just ensured it's Some above
                             session_id: current_session_id.clone().unwrap(),
                         };
@@ -1133,8 +1475,8 @@ async fn run_event_loop(
                         task.output_lines.push(line.clone());
                         terminal::println_above(&render::style_tool_output_line(&line));
                     }
-                    Ok(ShellOutput::Done(exit_code)) => {
-                        task.exit_status = Some(exit_code);
+                    Ok(ShellOutput::Done { exit_code, elapsed }) => {
+                        task.exit_status = Some((exit_code, elapsed));
                         break;
                     }
                     Ok(ShellOutput::Error(msg)) => {
@@ -1166,28 +1508,36 @@ async fn run_event_loop(
             if let Some(ref task) = shell_task
                 && task.exit_status.is_some()
             {
-                // Save output to history
+                // Record the output and result the same way as the bash tool.
+                history::push(history::HistoryEvent::ToolStart);
                 if !task.output_lines.is_empty() {
-                    let combined = task.output_lines.join("\n");
-                    history::push(history::HistoryEvent::Info(combined));
-                }
-
-                // Show exit status if non-zero
-                if let Some(Some(code)) = task.exit_status
-                    && code != 0
-                {
-                    terminal::println_above(
-                        &format!("[Command exited with status {}]", code)
-                            .bright_black()
-                            .to_string(),
-                    );
+                    let mut combined = task.output_lines.join("\n");
+                    combined.push('\n');
+                    history::append_tool_output(&combined);
                 }
+                let (exit_code, elapsed) = task.exit_status.unwrap_or_default();
+                let (is_error, summary) =
+                    shell_result_summary(&task.output_lines, exit_code, elapsed);
+                terminal::println_above(
+                    render::render_tool_result_with_context(is_error, "", Some(&summary), false)
+                        .trim_end(),
+                );
+                history::push(history::HistoryEvent::ToolResult {
+                    output: String::new(),
+                    is_error,
+                    summary: Some(summary),
+                });
+                history::push(history::HistoryEvent::ToolEnd);
 
                 shell_task = None;
 
                 // Process any pending prompts that were queued while shell was running
                 if let Some(next) = pending_prompts.pop_front() {
-                    let PendingPrompt { input, images } = next;
+                    let PendingPrompt {
+                        input,
+                        images,
+                        model,
+                    } = next;
 
                     // Expand custom commands before processing so the model receives the expanded prompt.
                     let expanded_input =
@@ -1205,6 +1555,7 @@ async fn run_event_loop(
                         custom_commands,
                         &mut provider_manager,
                         thinking_state,
+                        &mut model_override_restore,
                     )
                     .await;
 
@@ -1222,11 +1573,25 @@ async fn run_event_loop(
                         ProcessResult::Quit => {
                             break;
                         }
-                        ProcessResult::StartChat(prompt, _history_entry) => {
+                        ProcessResult::StartChat(prompt, history_entry) => {
                             // spawn_chat_task echoes the prompt itself
                             // Note: queued prompts were already added to prompt_history when queued.
+                            let mut images = images;
+                            images.extend(referenced_images(
+                                history_entry.as_ref().unwrap_or(&prompt),
+                                working_dir,
+                            ));
 
-                            if let Some(pm) = provider_manager.take() {
+                            if let Some(mut pm) = provider_manager.take() {
+                                if let Some(ref choice) = model {
+                                    model_override_restore = apply_model_override(
+                                        &mut pm,
+                                        choice,
+                                        &mut messages,
+                                        thinking_state,
+                                        &mut input_state,
+                                    );
+                                }
                                 if current_session_id.is_none() {
                                     *current_session_id = Some(session::generate_session_id());
                                 }
@@ -1236,6 +1601,7 @@ async fn run_event_loop(
                                     model_id: pm.current_model_id().to_string(),
                                     thinking_enabled: thinking_state.enabled,
                                     read_only,
+                                    seed: pm.seed(),
                                     session_id: current_session_id.clone().unwrap(),
                                 };
                                 chat_task = Some(spawn_chat_task(
@@ -1254,6 +1620,15 @@ async fn run_event_loop(
                             shell_task = Some(spawn_shell_task(cmd));
                             prompt_box.draw_with_pending(&input_state, &pending_prompts)?;
                         }
+                        ProcessResult::AttachImage(image) => {
+                            echo_user_prompt_to_output(&expanded_input, &images);
+                            input_state.add_pasted_image(image.mime_type, image.data);
+                            prompt_box.draw_with_pending(&input_state, &pending_prompts)?;
+                        }
+                        ProcessResult::ClearHistory(scope) => {
+                            clear_prompt_history(prompt_history, scope);
+                            prompt_box.draw(&input_state, false)?;
+                        }
                         // Other results (menus, etc.) - just continue, menus will open
                         _ => {
                             prompt_box.draw(&input_state, false)?;
@@ -1302,30 +1677,121 @@ async fn run_event_loop(
             }
         }
 
-        let event = if crossterm::event::poll(Duration::from_millis(50))? {
-            Some(crossterm::event::read()?)
+        // Show a status line rendered in the background, unless a menu is over the prompt.
+        if model_menu.is_none()
+            && session_menu.is_none()
+            && settings_menu.is_none()
+            && mcp_menu.is_none()
+            && tools_menu.is_none()
+            && history_search.is_none()
+            && statusline::take_updated()
+        {
+            prompt_box.set_custom_status(statusline::current());
+            prompt_box.draw_with_pending(&input_state, &pending_prompts)?;
+        }
+
+        if let Some(ref mut remote) = remote {
+            while let Some(command) = remote.try_recv() {
+                match command {
+                    remote::RemoteCommand::Send(text) => remote_prompts.push_back(text),
+                    remote::RemoteCommand::Interrupt => {
+                        if let Some(ref task) = chat_task {
+                            task.interrupted.store(true, Ordering::SeqCst);
+                        }
+                    }
+                }
+            }
+        }
+        // Don't take over input the user is typing or a menu or question they're answering
+        let input_free = input_state.is_empty()
+            && held_paste.is_none()
+            && compaction_review.is_none()
+            && model_menu.is_none()
+            && session_menu.is_none()
+            && settings_menu.is_none()
+            && mcp_menu.is_none()
+            && tools_menu.is_none()
+            && history_search.is_none()
+            && !crate::permissions::awaiting_decision()
+            && !crate::watchdog::awaiting_answer();
+
+        let event = if input_free && let Some(text) = remote_prompts.pop_front() {
+            // Submit the prompt as if it was typed; the Null key stands in for Enter
+            input_state.set_content(&text);
+            remote_submit = true;
+            Some(Event::Key(KeyEvent::new(KeyCode::Null, KeyModifiers::NONE)))
+        } else if crossterm::event::poll(Duration::from_millis(50))? {
+            Some(capabilities::paste_fallback(crossterm::event::read()?))
         } else {
             None
         };
 
         if let Some(evt) = event {
             match evt {
-                Event::Resize(cols, rows) => {
-                    // In batch mode the prompt is never shown; ignore resize events.
-                    // Handling resizes here can force a prompt redraw and make it visible.
-                    if !batch {
-                        prompt_box
-                            .handle_resize(&input_state, &pending_prompts, cols, rows)
-                            .await?;
-                    }
+                // In batch mode the prompt is never shown; ignore resize events.
+                // Handling resizes here can force a prompt redraw and make it visible.
+                Event::Resize(cols, rows) if !batch => {
+                    prompt_box
+                        .handle_resize(&input_state, &pending_prompts, cols, rows)
+                        .await?;
                 }
+                Event::Mouse(mouse) => match mouse.kind {
+                    MouseEventKind::ScrollUp => listener::scroll_tool_output(MOUSE_SCROLL_LINES),
+                    MouseEventKind::ScrollDown => listener::scroll_tool_output(-MOUSE_SCROLL_LINES),
+                    _ => {}
+                },
                 Event::Paste(text) => {
                     // Handle bracketed paste - insert the full text with newlines
-                    let normalized = text.replace("\r\n", "\n").replace('\r', "\n");
-                    input_state.insert_str(&normalized);
+                    let pasted = paste::prepare(&text);
+                    if paste::should_hold(&pasted) {
+                        terminal::println_above(&paste::preview(&pasted));
+                        held_paste = Some(pasted);
+                        continue;
+                    }
+                    input_state.insert_str(&pasted.text);
+                    prompt_box.draw(&input_state, false)?;
+                }
+                Event::Key(key) if key.kind == KeyEventKind::Press && held_paste.is_some() => {
+                    let terminal_output = held_paste.as_ref().is_some_and(|p| p.terminal);
+                    let save: fn(&str) -> std::io::Result<String> = match key.code {
+                        KeyCode::Enter if terminal_output => paste::fold,
+                        KeyCode::Char('f' | 'F') if terminal_output => paste::fold,
+                        KeyCode::Enter | KeyCode::Char('a' | 'A') => paste::attach,
+                        KeyCode::Char('i' | 'I') => |text| Ok(text.to_string()),
+                        KeyCode::Esc => {
+                            held_paste = None;
+                            terminal::println_above("Paste discarded.");
+                            continue;
+                        }
+                        _ => continue,
+                    };
+                    let text = held_paste.take().map(|p| p.text).unwrap_or_default();
+                    let text = match save(&text) {
+                        Ok(text) => text,
+                        Err(e) => {
+                            terminal::println_above(
+                                &format!("Failed to save paste ({}), inserting inline.", e)
+                                    .red()
+                                    .to_string(),
+                            );
+                            text
+                        }
+                    };
+                    input_state.insert_str(&text);
                     prompt_box.draw(&input_state, false)?;
                 }
                 Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    // Ctrl+Esc cancels only the running tool; the model continues the turn.
+                    if chat_task.is_some()
+                        && key.code == KeyCode::Esc
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        if !crate::chat::cancel_current_tool() {
+                            terminal::println_above("No tool is running.");
+                        }
+                        continue;
+                    }
+
                     // Handle ESC during chat to interrupt (cancel agent loop)
                     // But if a menu is open, just close the menu instead
                     if chat_task.is_some() && key.code == KeyCode::Esc {
@@ -1368,6 +1834,41 @@ async fn run_event_loop(
                         continue;
                     }
 
+                    // Answer the loop watchdog's continue prompt with y/n.
+                    if chat_task.is_some()
+                        && crate::watchdog::awaiting_answer()
+                        && input_state.is_empty()
+                        && !key.modifiers.contains(KeyModifiers::CONTROL)
+                        && let KeyCode::Char(c @ ('y' | 'Y' | 'n' | 'N')) = key.code
+                    {
+                        let continue_loop = c.eq_ignore_ascii_case(&'y');
+                        terminal::println_above(if continue_loop {
+                            "Continuing."
+                        } else {
+                            "Stopping."
+                        });
+                        crate::watchdog::answer(continue_loop);
+                        continue;
+                    }
+
+                    // Answer a tool approval prompt with y/a/n.
+                    if chat_task.is_some()
+                        && crate::permissions::awaiting_decision()
+                        && input_state.is_empty()
+                        && !key.modifiers.contains(KeyModifiers::CONTROL)
+                        && let KeyCode::Char(c @ ('y' | 'Y' | 'a' | 'A' | 'n' | 'N')) = key.code
+                    {
+                        use crate::permissions::Decision;
+                        let (decision, message) = match c.to_ascii_lowercase() {
+                            'y' => (Decision::Once, "Allowed once."),
+                            'a' => (Decision::Always, "Allowed always."),
+                            _ => (Decision::Deny, "Denied."),
+                        };
+                        terminal::println_above(message);
+                        crate::permissions::decide(decision);
+                        continue;
+                    }
+
                     // Handle Ctrl+T during chat using cached provider info
                     if let Some(ref task) = chat_task
                         && key.code == KeyCode::Char('t')
@@ -1544,7 +2045,7 @@ async fn run_event_loop(
                     {
                         // Pop most recent queued prompt into input for editing.
                         if let Some(pending) = pending_prompts.pop_back() {
-                            let prompt_text = pending.input.clone();
+                            let prompt_text = pending.editable_text();
                             let images = pending.images.clone();
 
                             editing_pending_prompt = Some(pending);
@@ -1729,6 +2230,15 @@ async fn run_event_loop(
                                 input_state.clear();
                                 prompt_box.draw(&input_state, false)?;
                             }
+                            SessionMenuAction::Select(selected_session)
+                                if !same_directory(
+                                    &selected_session.working_directory,
+                                    working_dir,
+                                ) =>
+                            {
+                                switch_session = Some(selected_session);
+                                break;
+                            }
                             SessionMenuAction::Select(selected_session) => {
                                 session_menu = None;
                                 input_state.clear();
@@ -1741,6 +2251,8 @@ async fn run_event_loop(
                                     messages = restored.messages;
                                     thinking_state.enabled = restored.thinking_enabled;
                                     services.set_read_only(restored.read_only);
+                                    crate::sliding_window::set_enabled(restored.sliding_window);
+                                    crate::usage::restore_session_cost(restored.cost);
                                     // Use the ID we loaded by
                                     *current_session_id = Some(selected_session.id.clone());
                                     services.set_session_id(current_session_id.clone());
@@ -1764,6 +2276,7 @@ async fn run_event_loop(
                                             );
                                         }
                                         pm.set_thinking_enabled(thinking_state.enabled);
+                                        pm.set_seed(restored.seed);
 
                                         // Update is_claude for slash menu filtering
                                         input_state
@@ -1808,10 +2321,6 @@ async fn run_event_loop(
                             SettingsMenuAction::Close => {
                                 settings_menu = None;
                                 input_state.clear();
-                                // Reload settings after changes
-                                listener::reload_show_image_previews();
-                                listener::reload_hide_tool_output();
-                                spacing::reload_compact_mode();
                                 refresh_prompt_status(
                                     &mut prompt_box,
                                     &provider_manager,
@@ -1851,22 +2360,15 @@ async fn run_event_loop(
 
                                     // Toggle the server
                                     let result = services.mcp.toggle_server(&name).await;
-                                    match result {
-                                        Ok((is_running, tool_count)) => {
-                                            menu.update_server_status(
-                                                &name, is_running, tool_count,
-                                            );
-                                        }
-                                        Err(e) => {
-                                            // On error, reset to disabled
-                                            menu.update_server_status(&name, false, 0);
-                                            terminal::println_above(
-                                                &format!("Failed to toggle MCP server: {}", e)
-                                                    .red()
-                                                    .to_string(),
-                                            );
-                                        }
+                                    if let Err(e) = result {
+                                        terminal::println_above(
+                                            &format!("Failed to toggle MCP server: {}", e)
+                                                .red()
+                                                .to_string(),
+                                        );
                                     }
+                                    // Refresh all statuses, including the error if it failed
+                                    menu.update_statuses(services.mcp.server_statuses().await);
 
                                     // Update prompt status to reflect new MCP count
                                     refresh_prompt_status(
@@ -1924,7 +2426,11 @@ async fn run_event_loop(
                         continue;
                     }
 
-                    let action = input_state.handle_key(key);
+                    let action = if std::mem::take(&mut remote_submit) {
+                        InputAction::Submit
+                    } else {
+                        input_state.handle_key(key)
+                    };
 
                     // Clear exit prompt if action is not ClearOrExit
                     if !matches!(action, InputAction::ClearOrExit) {
@@ -2010,9 +2516,14 @@ async fn run_event_loop(
                             if let Ok((bytes, mime)) = clipboard::paste_image() {
                                 input_state.add_pasted_image(mime, bytes);
                             } else if let Ok(text) = clipboard::paste_text() {
-                                // Normalize newlines and insert
-                                let normalized = text.replace("\r\n", "\n").replace('\r', "\n");
-                                input_state.insert_str(&normalized);
+                                // Strip escapes, normalize newlines and insert
+                                let pasted = paste::prepare(&text);
+                                if paste::should_hold(&pasted) {
+                                    terminal::println_above(&paste::preview(&pasted));
+                                    held_paste = Some(pasted);
+                                } else {
+                                    input_state.insert_str(&pasted.text);
+                                }
                             }
                             if pending_prompts.is_empty() {
                                 prompt_box.draw(&input_state, false)?;
@@ -2037,11 +2548,13 @@ async fn run_event_loop(
                             let initial = input_state.content();
 
                             let _ = crossterm_terminal::disable_raw_mode();
+                            set_mouse_capture(false);
                             let _ = prompt_box.hide_and_clear();
 
                             let edited = editor::edit_text_in_external_editor(&initial);
 
                             let _ = crossterm_terminal::enable_raw_mode();
+                            set_mouse_capture(mouse_capture);
 
                             match edited {
                                 Ok(Some(text)) => {
@@ -2060,6 +2573,39 @@ async fn run_event_loop(
                                 }
                             }
                         }
+                        InputAction::CopyMode => {
+                            if chatting {
+                                continue;
+                            }
+
+                            let width = terminal::term_width() as usize;
+                            let rendered = render::render_all(&history::snapshot(), width);
+                            let result = {
+                                let _guard = terminal::lock_output();
+                                copy::run(&rendered)
+                            };
+                            let message = match result {
+                                Ok(Some(text)) => match clipboard::copy_text(&text) {
+                                    Ok(()) => Some(format!(
+                                        "Copied {} lines.",
+                                        text.lines().count().max(1)
+                                    )),
+                                    Err(e) => {
+                                        Some(format!("Failed to copy: {}", e).red().to_string())
+                                    }
+                                },
+                                Ok(None) => None,
+                                Err(e) => Some(
+                                    format!("Failed to enter copy mode: {}", e)
+                                        .red()
+                                        .to_string(),
+                                ),
+                            };
+                            if let Some(message) = message {
+                                terminal::println_above(&message);
+                            }
+                            prompt_box.draw(&input_state, true)?;
+                        }
                         InputAction::OpenModelMenu => {
                             // Open model menu - works during streaming too
                             if model_menu.is_none() {
@@ -2201,6 +2747,8 @@ async fn run_event_loop(
                             *current_session_id = Some(session::generate_session_id());
                             services.set_session_id(current_session_id.clone());
                             crate::usage::reset_last_context_usage();
+                            crate::usage::reset_session_cost();
+                            crate::lsp::quickfix::clear();
                             history::clear();
                             terminal::set_streaming_status_line_active(false);
                             terminal::redraw_from_history(prompt_box.height());
@@ -2211,6 +2759,20 @@ async fn run_event_loop(
                                 continue;
                             }
 
+                            // Reviewing a compaction summary: hitting Enter accepts it.
+                            if let Some(state) = compaction_review.take() {
+                                archive_compacted_transcript(
+                                    working_dir,
+                                    current_session_id.as_deref(),
+                                    &state.original,
+                                );
+                                messages = finalize_compaction(content, state);
+                                input_state.clear();
+                                print_compaction_done(messages.len());
+                                prompt_box.draw(&input_state, true)?;
+                                continue;
+                            }
+
                             // Editing a queued prompt: hitting Enter re-queues it.
                             if chatting && editing_pending_prompt.is_some() {
                                 // Replace the original with the edited version.
@@ -2223,10 +2785,8 @@ async fn run_event_loop(
                                     pasted_images_to_history_images(&pasted_images);
                                 let _ = prompt_history.add_with_images(&content, history_images);
 
-                                pending_prompts.push_back(PendingPrompt {
-                                    input: content,
-                                    images: pasted_images,
-                                });
+                                pending_prompts
+                                    .push_back(PendingPrompt::new(content, pasted_images));
                                 input_state.clear();
                                 prompt_box.draw_with_pending(&input_state, &pending_prompts)?;
                                 continue;
@@ -2245,7 +2805,7 @@ async fn run_event_loop(
                                     continue;
                                 }
 
-                                // Allow transaction logging commands to run immediately while the model is working.
+                                // Allow logging commands to run immediately while the model is working.
                                 if let Some(cmd_input) = content.trim().strip_prefix('/')
                                     && let Some(command) =
                                         crate::commands::parse(cmd_input, custom_commands)
@@ -2253,9 +2813,11 @@ async fn run_event_loop(
                                         command,
                                         Command::StartTransactionLogging
                                             | Command::StopTransactionLogging
+                                            | Command::Tee { .. }
                                     )
                                 {
                                     match command {
+                                        Command::Tee { args } => tee_command(&args),
                                         Command::StartTransactionLogging => {
                                             let path =
                                                 crate::provider::transaction_log::start(None);
@@ -2288,10 +2850,8 @@ async fn run_event_loop(
                                     pasted_images_to_history_images(&pasted_images);
                                 let _ = prompt_history.add_with_images(&content, history_images);
 
-                                pending_prompts.push_back(PendingPrompt {
-                                    input: content,
-                                    images: pasted_images,
-                                });
+                                pending_prompts
+                                    .push_back(PendingPrompt::new(content, pasted_images));
 
                                 input_state.clear();
 
@@ -2330,6 +2890,7 @@ async fn run_event_loop(
                                     custom_commands,
                                     &mut provider_manager,
                                     thinking_state,
+                                    &mut model_override_restore,
                                 )
                                 .await;
 
@@ -2353,7 +2914,7 @@ async fn run_event_loop(
                                     }
                                     ProcessResult::StartChat(prompt, history_entry) => {
                                         // Get active images before clearing
-                                        let pasted_images = input_state.active_images();
+                                        let mut pasted_images = input_state.active_images();
 
                                         // Save to history before clearing.
                                         // Use history_entry if provided (for custom commands),
@@ -2362,6 +2923,8 @@ async fn run_event_loop(
                                             history_entry.as_ref().unwrap_or(&prompt);
                                         let _ =
                                             prompt_history.add_with_images(history_text, vec![]);
+                                        pasted_images
+                                            .extend(referenced_images(history_text, working_dir));
 
                                         // Clear input and redraw prompt BEFORE spawning chat task.
                                         // This ensures the prompt box is at the correct position
@@ -2383,6 +2946,7 @@ async fn run_event_loop(
                                                 model_id: pm.current_model_id().to_string(),
                                                 thinking_enabled: thinking_state.enabled,
                                                 read_only,
+                                                seed: pm.seed(),
                                                 // Safe to unwrap: we just ensured it's Some above
                                                 session_id: current_session_id.clone().unwrap(),
                                             };
@@ -2538,11 +3102,8 @@ async fn run_event_loop(
                                         // - run inquire prompts
                                         // - restore terminal modes
                                         let _ = crossterm_terminal::disable_raw_mode();
-                                        let _ = execute!(
-                                            std::io::stdout(),
-                                            PopKeyboardEnhancementFlags,
-                                            DisableBracketedPaste
-                                        );
+                                        let _ = capabilities::disable_input_modes();
+                                        set_mouse_capture(false);
                                         let _ = prompt_box.hide_and_clear();
 
                                         // Run the provider management flow
@@ -2568,13 +3129,8 @@ async fn run_event_loop(
 
                                         // Restore terminal state without full redraw
                                         println!();
-                                        let _ = execute!(
-                                            std::io::stdout(),
-                                            PushKeyboardEnhancementFlags(
-                                                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
-                                            ),
-                                            EnableBracketedPaste
-                                        );
+                                        let _ = capabilities::enable_input_modes();
+                                        set_mouse_capture(mouse_capture);
                                         let _ = crossterm_terminal::enable_raw_mode();
 
                                         // Refresh prompt status with potentially new provider
@@ -2594,10 +3150,32 @@ async fn run_event_loop(
                                         prompt_box.draw(&input_state, false)?;
                                         shell_task = Some(spawn_shell_task(cmd));
                                     }
+                                    ProcessResult::AttachImage(image) => {
+                                        // The image marker replaces the command, ready for
+                                        // the prompt to be typed after it
+                                        input_state.clear();
+                                        input_state.add_pasted_image(image.mime_type, image.data);
+                                        input_state.insert_str(" ");
+                                        prompt_box.draw(&input_state, false)?;
+                                    }
+                                    ProcessResult::ClearHistory(scope) => {
+                                        clear_prompt_history(prompt_history, scope);
+                                        input_state.clear();
+                                        prompt_box.draw(&input_state, true)?;
+                                    }
                                 }
                             }
                         }
                         InputAction::CancelAgentLoop => {
+                            if let Some(state) = compaction_review.take() {
+                                messages = state.original;
+                                input_state.clear();
+                                terminal::println_above(
+                                    &"Compaction summary discarded.".yellow().to_string(),
+                                );
+                                prompt_box.draw(&input_state, true)?;
+                                continue;
+                            }
                             if chatting && editing_pending_prompt.is_some() {
                                 if let Some(pending) = editing_pending_prompt.take() {
                                     pending_prompts.push_back(pending);
@@ -2794,18 +3372,42 @@ async fn run_event_loop(
 
     // Restore terminal state (skip in batch mode - we never enabled raw mode)
     if !batch {
-        execute!(
-            std::io::stdout(),
-            PopKeyboardEnhancementFlags,
-            DisableBracketedPaste
-        )?;
+        set_mouse_capture(false);
+        capabilities::disable_input_modes()?;
         crossterm_terminal::disable_raw_mode()?;
         prompt_box.hide_and_exit()?;
     }
 
+    if let Some(session) = switch_session {
+        return reopen_in_session_directory(&session);
+    }
+
     Ok(())
 }
 
+fn same_directory(a: &std::path::Path, b: &std::path::Path) -> bool {
+    a == b
+        || a.canonicalize()
+            .ok()
+            .is_some_and(|a| b.canonicalize().ok() == Some(a))
+}
+
+/// Replace this process with `henri sessions <id>`, which opens the session in
+/// its own working directory with that directory's config, LSP and MCP servers.
+fn reopen_in_session_directory(session: &session::SessionInfo) -> std::io::Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    println!(
+        "Opening session in {}",
+        shorten_path(&session.working_directory)
+    );
+    let error = std::process::Command::new(std::env::current_exe()?)
+        .args(["sessions", &session.id])
+        .current_dir(&session.working_directory)
+        .exec();
+    Err(error)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn handle_chat_outcome(
     outcome: ChatOutcome,
@@ -2819,6 +3421,7 @@ fn handle_chat_outcome(
 ) -> std::io::Result<()> {
     match outcome {
         ChatOutcome::Complete => {
+            print_sources_footer(messages);
             match session::save_session(
                 working_dir,
                 messages,
@@ -2826,6 +3429,7 @@ fn handle_chat_outcome(
                 provider_manager.current_model_id(),
                 thinking_state.enabled,
                 read_only,
+                provider_manager.seed(),
                 current_session_id.as_deref(),
             ) {
                 Ok(id) => *current_session_id = Some(id),
@@ -2847,6 +3451,7 @@ fn handle_chat_outcome(
                     provider_manager.current_model_id(),
                     thinking_state.enabled,
                     read_only,
+                    provider_manager.seed(),
                     current_session_id.as_deref(),
                 ) {
                     Ok(id) => *current_session_id = Some(id),
@@ -2862,30 +3467,333 @@ fn handle_chat_outcome(
     Ok(())
 }
 
-async fn handle_global_shortcuts(
-    key: crossterm::event::KeyEvent,
-    thinking_state: &mut crate::providers::ThinkingState,
-    provider_manager: &mut ProviderManager,
-    services: &Services,
-    prompt_box: &mut PromptBox,
-    input_state: &InputState,
+/// Title the session after its first exchange. The model is asked in the
+/// background so the prompt stays responsive; if it fails, the sessions menu
+/// shows the first prompt as before.
+fn spawn_session_title(
+    provider_manager: &ProviderManager,
+    messages: &[Message],
     working_dir: &std::path::Path,
-) -> std::io::Result<bool> {
-    match (key.code, key.modifiers) {
-        (KeyCode::Char('t'), KeyModifiers::CONTROL) => {
-            let provider = provider_manager.current_provider();
-            let model = provider_manager.current_model_id();
+    session_id: Option<&str>,
+) {
+    let prompts = messages
+        .iter()
+        .filter(|m| m.role == Role::User && !m.is_tool_result_only())
+        .count();
+    let Some(session_id) = session_id else {
+        return;
+    };
+    if prompts != 1 || session::session_title(working_dir, session_id).is_some() {
+        return;
+    }
+    let Ok(mut titler) = provider_manager.detached() else {
+        return;
+    };
+    let messages = messages.to_vec();
+    let working_dir = working_dir.to_path_buf();
+    let session_id = session_id.to_string();
+    tokio::spawn(async move {
+        if let Ok(Some(title)) = titler.session_title(&messages).await {
+            let _ = session::set_session_title(&working_dir, &session_id, &title);
+        }
+    });
+}
 
-            // Only cycle if thinking is available for this model
-            if supports_thinking(provider, model) {
-                // Check if this provider uses model variants for reasoning levels
-                if uses_model_variants(provider, model) {
-                    // Cycle to next model variant
-                    let new_model = cycle_model_variant(provider, model);
-                    let custom_provider = provider_manager
-                        .current_custom_provider()
-                        .map(|s| s.to_string());
-                    provider_manager.set_model(provider, new_model.clone(), custom_provider);
+/// Remove prompts from the prompt history for `/history clear`.
+fn clear_prompt_history(prompt_history: &mut FileHistory, scope: crate::history::ClearScope) {
+    match prompt_history.clear(scope) {
+        Ok(removed) => terminal::println_above(&format!(
+            "Removed {} prompt{} from history.",
+            removed,
+            if removed == 1 { "" } else { "s" }
+        )),
+        Err(e) => {
+            terminal::println_above(&format!("Failed to clear history: {}", e).red().to_string())
+        }
+    }
+}
+
+/// Show whether prompts are being saved, for `/history`.
+fn history_status() {
+    let config = crate::config::ConfigFile::load()
+        .unwrap_or_default()
+        .history;
+    let status = match crate::history::excluded_patterns(&config) {
+        Err(e) => format!("not saved (invalid excluded pattern {})", e),
+        Ok(_) if !config.record => "not saved".to_string(),
+        Ok(patterns) if patterns.is_empty() => "saved".to_string(),
+        Ok(patterns) => format!(
+            "saved, except prompts matching {} excluded pattern{}",
+            patterns.len(),
+            if patterns.len() == 1 { "" } else { "s" }
+        ),
+    };
+    terminal::println_above(&format!("Prompt history: {}", status));
+    terminal::println_above(
+        &"Usage: /history clear <all|project|last [n]>"
+            .bright_black()
+            .to_string(),
+    );
+}
+
+/// Handle `/tee [file|off]`.
+fn tee_command(args: &str) {
+    match args.trim() {
+        "" => match tee::path() {
+            Some(path) => terminal::println_above(&format!("Tee: {}", path.display())),
+            None => terminal::println_above(&"Usage: /tee <file|off>".yellow().to_string()),
+        },
+        "off" => match tee::stop() {
+            Some(path) => {
+                terminal::println_above(&format!("Tee stopped: {}", path.display()));
+            }
+            None => terminal::println_above("Tee is not active."),
+        },
+        file => {
+            let path = PathBuf::from(file);
+            match tee::start(&path) {
+                Ok(()) => terminal::println_above(&format!("Tee started: {}", path.display())),
+                Err(e) => terminal::println_above(
+                    &format!("Failed to open {}: {}", path.display(), e)
+                        .red()
+                        .to_string(),
+                ),
+            }
+        }
+    }
+}
+
+/// Handle `/set [<setting> [value]]`.
+fn set_option(args: &str, provider_manager: Option<&mut ProviderManager>) {
+    let mut parts = args.split_whitespace();
+    let mut ctx = crate::settings::Context::load(provider_manager);
+
+    let Some(key) = parts.next() else {
+        // List every setting with its current value.
+        let width = crate::settings::SETTINGS
+            .iter()
+            .map(|s| s.key.len())
+            .max()
+            .unwrap_or(0);
+        for setting in crate::settings::SETTINGS {
+            terminal::println_above(&format!(
+                "{:width$} = {:<4} {}",
+                setting.key,
+                setting.get(&ctx).to_string(),
+                setting.description.bright_black(),
+                width = width
+            ));
+        }
+        return;
+    };
+    let Some(setting) = crate::settings::find(key) else {
+        terminal::println_above(
+            &format!("Unknown setting: {} (run /set to list them)", key)
+                .red()
+                .to_string(),
+        );
+        return;
+    };
+
+    if let Some(value) = parts.next() {
+        let result = setting
+            .parse(value)
+            .and_then(|value| setting.set(&mut ctx, value));
+        if let Err(e) = result {
+            terminal::println_above(&e.red().to_string());
+            return;
+        }
+    }
+    let value = setting.get(&ctx);
+    terminal::println_above(&format!("{} = {}", setting.key, value));
+
+    if setting.key == "seed"
+        && value != crate::settings::SettingValue::Number(None)
+        && ctx
+            .provider_manager
+            .as_ref()
+            .is_some_and(|pm| !pm.supports_seed())
+    {
+        terminal::println_above(
+            &"The current provider does not support a seed; it is only sent to OpenAI-compatible and OpenRouter models."
+                .yellow()
+                .to_string(),
+        );
+    }
+}
+
+/// Switch to a model while no chat is running.
+fn apply_model_choice(
+    provider_manager: &mut ProviderManager,
+    choice: &ModelChoice,
+    messages: &mut [Message],
+    thinking_state: &mut crate::providers::ThinkingState,
+    input_state: &mut InputState,
+) {
+    let provider_changed = provider_manager.set_model(
+        choice.provider,
+        choice.model_id.clone(),
+        choice.custom_provider.clone(),
+    );
+    if provider_changed {
+        crate::provider::transform_thinking_for_provider_switch(messages);
+    }
+    // Update thinking state to new model's default
+    let new_thinking = default_thinking_state(choice.provider, &choice.model_id);
+    thinking_state.enabled = new_thinking.enabled;
+    thinking_state.mode = new_thinking.mode;
+    // Update is_claude for slash menu filtering
+    input_state.set_is_claude(choice.provider == ModelProvider::Claude);
+}
+
+/// Switch to a queued prompt's model for the duration of that prompt.
+///
+/// Returns the model and thinking state to restore once the prompt completes,
+/// or `None` if the prompt already uses the current model.
+fn apply_model_override(
+    provider_manager: &mut ProviderManager,
+    choice: &ModelChoice,
+    messages: &mut [Message],
+    thinking_state: &mut crate::providers::ThinkingState,
+    input_state: &mut InputState,
+) -> Option<(ModelChoice, crate::providers::ThinkingState)> {
+    if provider_manager.current_provider() == choice.provider
+        && provider_manager.current_model_id() == choice.model_id
+        && provider_manager.current_custom_provider() == choice.custom_provider.as_deref()
+    {
+        return None;
+    }
+
+    let previous = ModelChoice {
+        provider: provider_manager.current_provider(),
+        model_id: provider_manager.current_model_id().to_string(),
+        custom_provider: provider_manager
+            .current_custom_provider()
+            .map(str::to_string),
+        is_favorite: false,
+    };
+    let previous_thinking = thinking_state.clone();
+    terminal::println_above(
+        &format!("Using {} for this prompt.", choice.short_display())
+            .bright_black()
+            .to_string(),
+    );
+    apply_model_choice(
+        provider_manager,
+        choice,
+        messages,
+        thinking_state,
+        input_state,
+    );
+    Some((previous, previous_thinking))
+}
+
+/// Warn when Claude subscription limits are nearly used up, once until
+/// usage drops back below the threshold.
+///
+/// Returns the configured fallback model when `rate-limit-fallback.auto` is set,
+/// so the caller can switch to it.
+fn check_rate_limit_fallback(
+    provider_manager: &ProviderManager,
+    fallback: &crate::config::RateLimitFallbackConfig,
+    alerted: &mut bool,
+) -> Option<ModelChoice> {
+    if provider_manager.current_provider() != ModelProvider::Claude {
+        return None;
+    }
+
+    let model = fallback.model.as_deref()?;
+    let (window, utilization) = crate::usage::latest_anthropic_rate_limits()?.max_utilization()?;
+    if utilization < fallback.threshold {
+        // The window has reset, so warn again when it fills up
+        *alerted = false;
+        return None;
+    }
+    if *alerted {
+        return None;
+    }
+
+    let (provider, model_id, custom_provider) = crate::providers::parse_model_spec(model);
+    if provider == provider_manager.current_provider()
+        && model_id == provider_manager.current_model_id()
+    {
+        return None;
+    }
+    *alerted = true;
+
+    let usage = format!("Claude {} usage is at {:.0}%.", window, utilization * 100.0);
+    if fallback.auto {
+        terminal::println_above(
+            &format!("{} Switching to fallback model {}.", usage, model)
+                .yellow()
+                .to_string(),
+        );
+        Some(ModelChoice {
+            provider,
+            model_id,
+            custom_provider,
+            is_favorite: false,
+        })
+    } else {
+        terminal::println_above(
+            &format!(
+                "{} Consider switching to {} ({}) to save the remaining budget.",
+                usage,
+                model,
+                capabilities::model_menu_key()
+            )
+            .yellow()
+            .to_string(),
+        );
+        None
+    }
+}
+
+/// List the web pages the model fetched this turn so claims can be checked.
+///
+/// The footer is recorded in the CLI history so it is saved with the session.
+fn print_sources_footer(messages: &[Message]) {
+    let sources = crate::tools::last_turn_sources(messages);
+    if sources.is_empty() {
+        return;
+    }
+
+    let mut lines = vec!["Sources:".to_string()];
+    lines.extend(sources.iter().map(|url| format!("  - {}", url)));
+    let footer = lines.join("\n");
+
+    terminal::ensure_line_break();
+    terminal::println_above("");
+    for line in &lines {
+        terminal::println_above(&line.dimmed().to_string());
+    }
+    history::push(history::HistoryEvent::Info(footer));
+}
+
+async fn handle_global_shortcuts(
+    key: crossterm::event::KeyEvent,
+    thinking_state: &mut crate::providers::ThinkingState,
+    provider_manager: &mut ProviderManager,
+    services: &Services,
+    prompt_box: &mut PromptBox,
+    input_state: &InputState,
+    working_dir: &std::path::Path,
+) -> std::io::Result<bool> {
+    match (key.code, key.modifiers) {
+        (KeyCode::Char('t'), KeyModifiers::CONTROL) => {
+            let provider = provider_manager.current_provider();
+            let model = provider_manager.current_model_id();
+
+            // Only cycle if thinking is available for this model
+            if supports_thinking(provider, model) {
+                // Check if this provider uses model variants for reasoning levels
+                if uses_model_variants(provider, model) {
+                    // Cycle to next model variant
+                    let new_model = cycle_model_variant(provider, model);
+                    let custom_provider = provider_manager
+                        .current_custom_provider()
+                        .map(|s| s.to_string());
+                    provider_manager.set_model(provider, new_model.clone(), custom_provider);
 
                     // Update thinking state mode to reflect the variant
                     let variant = get_model_variant(&new_model).map(|s| s.to_string());
@@ -2934,6 +3842,7 @@ struct SessionSaveContext {
     model_id: String,
     thinking_enabled: bool,
     read_only: bool,
+    seed: Option<u64>,
     /// Session ID is always set (generated at start of session or on /reset)
     session_id: String,
 }
@@ -2957,7 +3866,14 @@ fn spawn_chat_task(
     // Prime the status line immediately so the newly-reserved rows aren't blank.
     listener::spinner_working();
 
-    // Set up session save callback for incremental saves after each tool iteration
+    crate::tool_metrics::set_session(&session_save_ctx.session_id);
+
+    // Messages are journaled as they arrive, with full saves at the start of
+    // the turn and whenever the context is rewritten.
+    provider_manager
+        .services()
+        .journal
+        .open(&session_save_ctx.working_dir, &session_save_ctx.session_id);
     let save_callback: crate::providers::SessionSaveCallback =
         Box::new(move |msgs: &[Message]| {
             // Save session silently - don't print errors during streaming
@@ -2968,6 +3884,7 @@ fn spawn_chat_task(
                 &session_save_ctx.model_id,
                 session_save_ctx.thinking_enabled,
                 session_save_ctx.read_only,
+                session_save_ctx.seed,
                 Some(&session_save_ctx.session_id),
             );
         });
@@ -3031,15 +3948,15 @@ fn spawn_chat_task(
             let prefix = prompt_prefix;
             // Colorize image markers, restoring grey background after each marker.
             let styled_line =
-                render::colorize_image_markers(wrapped_line, Some(render::BG_GREY_ANSI));
+                render::colorize_image_markers(wrapped_line, Some(render::bg_grey_ansi()));
 
             // Full-width grey background without printing trailing spaces.
             let line = format!(
                 "{}{}{}{}\x1b[K\x1b[0m",
-                render::BG_GREY_ANSI,
+                render::bg_grey_ansi(),
                 prefix,
                 styled_line,
-                render::BG_GREY_ANSI,
+                render::bg_grey_ansi(),
             );
             prompt_lines.push(line);
         }
@@ -3049,9 +3966,9 @@ fn spawn_chat_task(
     if prompt_lines.is_empty() {
         let line = format!(
             "{}{}{}\x1b[K\x1b[0m",
-            render::BG_GREY_ANSI,
+            render::bg_grey_ansi(),
             prompt_prefix,
-            render::BG_GREY_ANSI,
+            render::bg_grey_ansi(),
         );
         prompt_lines.push(line);
     }
@@ -3109,6 +4026,7 @@ fn spawn_chat_task(
     // Set up thinking state
     provider_manager.set_thinking_enabled(thinking_state.enabled);
     provider_manager.set_thinking_mode(thinking_state.mode.clone());
+    provider_manager.set_thinking_budget(thinking_state.budget_tokens);
 
     // Create interrupt flag and result channel
     let interrupted = Arc::new(AtomicBool::new(false));
@@ -3269,10 +4187,9 @@ fn spawn_compaction_chat(
     }
 }
 
-/// Finalize compaction: extract summary from response and rebuild messages
-fn finalize_compaction(chat_messages: Vec<Message>, state: CompactionState) -> Vec<Message> {
-    // Extract summary from the last assistant message
-    let summary = chat_messages
+/// Extract the generated summary from the last assistant message of a compaction chat
+fn compaction_summary(chat_messages: &[Message]) -> String {
+    chat_messages
         .iter()
         .rev()
         .find(|m| m.role == Role::Assistant)
@@ -3290,8 +4207,11 @@ fn finalize_compaction(chat_messages: Vec<Message>, state: CompactionState) -> V
                 .collect::<Vec<_>>()
                 .join("\n"),
         })
-        .unwrap_or_default();
+        .unwrap_or_default()
+}
 
+/// Finalize compaction: rebuild messages from the (possibly edited) summary
+fn finalize_compaction(summary: String, state: CompactionState) -> Vec<Message> {
     // Build new messages: summary block + preserved messages
     let summary_message = Message {
         role: Role::User,
@@ -3306,21 +4226,77 @@ fn finalize_compaction(chat_messages: Vec<Message>, state: CompactionState) -> V
     new_messages
 }
 
+/// Keep the full pre-compaction history so `/expand` can show what was summarized
+fn archive_compacted_transcript(
+    working_dir: &std::path::Path,
+    session_id: Option<&str>,
+    messages: &[Message],
+) {
+    let Some(session_id) = session_id else {
+        return;
+    };
+    if let Err(e) = session::archive_compacted_transcript(working_dir, session_id, messages) {
+        terminal::println_above(
+            &format!("Failed to save pre-compaction transcript: {}", e)
+                .red()
+                .to_string(),
+        );
+    }
+}
+
+/// Turn terminal mouse reporting on or off.
+///
+/// Errors are ignored: terminals without mouse support simply never send mouse
+/// events, and keyboard handling is unaffected.
+fn set_mouse_capture(enabled: bool) {
+    if enabled {
+        let _ = execute!(std::io::stdout(), EnableMouseCapture);
+    } else {
+        let _ = execute!(std::io::stdout(), DisableMouseCapture);
+    }
+}
+
+/// Report a finished compaction
+fn print_compaction_done(message_count: usize) {
+    terminal::ensure_line_break();
+    terminal::println_above("");
+    // Avoid printing a trailing newline so we don't leave an extra blank
+    // row above the reserved streaming status line.
+    terminal::print_above(
+        &format!("[Compacted {} messages into summary.]", message_count)
+            .green()
+            .to_string(),
+    );
+}
+
+/// Summary for a finished `!command`, in the bash tool's format plus the run time.
+fn shell_result_summary(
+    lines: &[String],
+    exit_code: Option<i32>,
+    elapsed: std::time::Duration,
+) -> (bool, String) {
+    let total_bytes: usize = lines.iter().map(|l| l.len() + 1).sum();
+    let mut summary = format!("[Read {} lines, {} bytes", lines.len(), total_bytes);
+    let is_error = exit_code != Some(0);
+    if is_error {
+        match exit_code {
+            Some(code) => summary.push_str(&format!(", exit code {}", code)),
+            None => summary.push_str(", killed by signal"),
+        }
+    }
+    summary.push_str(&format!(", {:.1}s]", elapsed.as_secs_f64()));
+    (is_error, summary)
+}
+
 /// Spawn an async shell command task
 fn spawn_shell_task(cmd: String) -> ShellTask {
-    use tokio::process::Command;
-
     let (tx, rx) = tokio::sync::mpsc::channel::<ShellOutput>(100);
 
     tokio::spawn(async move {
         use tokio::io::AsyncBufReadExt;
 
-        let result = Command::new("sh")
-            .arg("-c")
-            .arg(&cmd)
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .spawn();
+        let started = std::time::Instant::now();
+        let result = shell::command(&cmd).spawn();
 
         match result {
             Ok(mut child) => {
@@ -3345,6 +4321,9 @@ fn spawn_shell_task(cmd: String) -> ShellTask {
                         let reader = tokio::io::BufReader::new(stderr);
                         let mut lines = reader.lines();
                         while let Ok(Some(line)) = lines.next_line().await {
+                            if shell::is_startup_noise(&line) {
+                                continue;
+                            }
                             let _ = tx_stderr.send(ShellOutput::Line(line)).await;
                         }
                     }
@@ -3357,7 +4336,12 @@ fn spawn_shell_task(cmd: String) -> ShellTask {
                 // Wait for the process to finish
                 let status = child.wait().await;
                 let exit_code = status.ok().and_then(|s| s.code());
-                let _ = tx.send(ShellOutput::Done(exit_code)).await;
+                let _ = tx
+                    .send(ShellOutput::Done {
+                        exit_code,
+                        elapsed: started.elapsed(),
+                    })
+                    .await;
             }
             Err(e) => {
                 let _ = tx.send(ShellOutput::Error(e.to_string())).await;
@@ -3399,6 +4383,10 @@ enum ProcessResult {
     StartCompaction(CompactionData),
     /// Start an async shell command
     StartShellCommand(String),
+    /// Add an image to the next prompt
+    AttachImage(crate::file_refs::ImageFile),
+    /// Remove prompts from the prompt history
+    ClearHistory(crate::history::ClearScope),
 }
 
 /// Process user input and return what to do next
@@ -3413,7 +4401,8 @@ async fn process_input(
     services: &Services,
     custom_commands: &[CustomCommand],
     provider_manager: &mut Option<ProviderManager>,
-    _thinking_state: &mut crate::providers::ThinkingState,
+    thinking_state: &mut crate::providers::ThinkingState,
+    model_override_restore: &mut Option<(ModelChoice, crate::providers::ThinkingState)>,
 ) -> ProcessResult {
     let input = input.trim();
 
@@ -3431,17 +4420,34 @@ async fn process_input(
     if input.starts_with('/') {
         let cmd_input = input.strip_prefix('/').unwrap_or("");
         if let Some(command) = crate::commands::parse(cmd_input, custom_commands) {
-            // Track if this is a custom command for history
-            let is_custom = matches!(command, Command::Custom { .. });
+            // Track if this command expands to a prompt, to keep what was typed in history
+            let is_custom = matches!(
+                command,
+                Command::Custom { .. }
+                    | Command::Explain { .. }
+                    | Command::Fix { .. }
+                    | Command::McpPrompt { .. }
+                    | Command::SplitCommits { .. }
+            );
 
             if provider_manager.is_none()
                 && !matches!(
                     command,
                     Command::Help
+                        | Command::Context
+                        | Command::Cost
+                        | Command::Copy { .. }
+                        | Command::Memory { .. }
+                        | Command::Doctor { .. }
                         | Command::Echo { .. }
+                        | Command::Fork { .. }
+                        | Command::History { .. }
                         | Command::Quit
                         | Command::ReadOnly
                         | Command::ReadWrite
+                        | Command::Revert
+                        | Command::Rollback { .. }
+                        | Command::Search { .. }
                         | Command::Yolo
                         | Command::Model
                         | Command::Provider
@@ -3467,6 +4473,8 @@ async fn process_input(
                 services,
                 custom_commands,
                 provider_manager,
+                thinking_state,
+                model_override_restore,
             )
             .await
             {
@@ -3478,7 +4486,7 @@ async fn process_input(
                 CommandResult::Continue => return ProcessResult::Continue,
                 CommandResult::Quit => return ProcessResult::Quit,
                 CommandResult::SendToModel(prompt) => {
-                    // For custom commands, save original command to history instead of expanded prompt
+                    // For custom commands, MCP prompts and /explain, save original command to history instead of expanded prompt
                     // (so Ctrl+R shows what the user typed).
                     let history_entry = if is_custom {
                         Some(input.to_string())
@@ -3497,6 +4505,8 @@ async fn process_input(
                 CommandResult::StartCompaction(data) => {
                     return ProcessResult::StartCompaction(data);
                 }
+                CommandResult::AttachImage(image) => return ProcessResult::AttachImage(image),
+                CommandResult::ClearHistory(scope) => return ProcessResult::ClearHistory(scope),
             }
         } else {
             // Unknown command
@@ -3506,7 +4516,21 @@ async fn process_input(
         }
     }
 
-    // Regular user message - spawn_chat_task will display the prompt
+    // Regular user message - spawn_chat_task will display the prompt. Any
+    // @server:uri resources, @path files and folded terminal output are read
+    // and appended, keeping what was typed for history.
+    let (mut expanded, mut errors) = services.mcp.expand_resources(input).await;
+    let (with_files, file_errors) =
+        crate::file_refs::expand_file_references(input, working_dir, &services.seen_files);
+    expanded.push_str(&with_files[input.len()..]);
+    expanded.push_str(&paste::folded_context(input));
+    errors.extend(file_errors);
+    for error in errors {
+        terminal::println_above(&error.yellow().to_string());
+    }
+    if expanded != input {
+        return ProcessResult::StartChat(expanded, Some(input.to_string()));
+    }
     ProcessResult::StartChat(input.to_string(), None)
 }
 
@@ -3534,6 +4558,10 @@ enum CommandResult {
     RunProviderFlow,
     /// Start compaction with the given data
     StartCompaction(CompactionData),
+    /// Add an image to the next prompt
+    AttachImage(crate::file_refs::ImageFile),
+    /// Remove prompts from the prompt history
+    ClearHistory(crate::history::ClearScope),
 }
 
 /// Data needed to perform compaction
@@ -3548,18 +4576,46 @@ struct CompactionData {
     request_text: String,
 }
 
+/// Remove the last turn from the conversation and the on-screen history.
+///
+/// Returns false if there was no turn to remove.
+fn undo_last_turn(messages: &mut Vec<Message>, prompt_box: &mut PromptBox) -> bool {
+    if crate::provider::remove_last_turn(messages) == 0 {
+        return false;
+    }
+    // Remove the last turn from the on-screen history as well.
+    // If the history is unexpectedly missing a `UserPrompt` marker, fall back
+    // to rebuilding from the remaining messages so the display stays in sync.
+    if !history::undo_last_turn() {
+        history::clear();
+        for msg in messages.iter() {
+            history::push_message(msg);
+        }
+    }
+
+    // Add an info event so the user can see what happened.
+    history::push(history::HistoryEvent::Info(
+        "Removed the most recent turn.".to_string(),
+    ));
+
+    prompt_box.redraw_history().ok();
+    true
+}
+
 /// Handle a parsed slash command.
 #[allow(clippy::too_many_arguments)]
 async fn handle_command(
     command: Command,
     messages: &mut Vec<Message>,
     current_session_id: &mut Option<String>,
-    _working_dir: &std::path::Path,
+    working_dir: &std::path::Path,
     prompt_box: &mut PromptBox,
     input_state: &mut InputState,
     services: &Services,
     custom_commands: &[CustomCommand],
     provider_manager: &mut Option<ProviderManager>,
+    thinking_state: &mut crate::providers::ThinkingState,
+    model_override_restore: &mut Option<(ModelChoice, crate::providers::ThinkingState)>,
 ) -> Option<CommandResult> {
     Some(match command {
         Command::Quit => CommandResult::Quit,
@@ -3571,6 +4627,9 @@ async fn handle_command(
             *current_session_id = Some(session::generate_session_id());
             services.set_session_id(current_session_id.clone());
             crate::usage::reset_last_context_usage();
+            crate::usage::reset_session_cost();
+            crate::lsp::quickfix::clear();
+            crate::sliding_window::reset();
             history::clear();
             terminal::set_streaming_status_line_active(false);
             terminal::redraw_from_history(prompt_box.height());
@@ -3587,6 +4646,53 @@ async fn handle_command(
             CommandResult::Continue
         }
 
+        Command::Status => {
+            show_status(
+                provider_manager.as_ref(),
+                thinking_state,
+                current_session_id.as_deref(),
+                working_dir,
+                messages,
+                services,
+            )
+            .await;
+            CommandResult::Continue
+        }
+
+        Command::Context => {
+            show_context(provider_manager.as_ref(), messages, services).await;
+            CommandResult::Continue
+        }
+
+        Command::Memory { args } => {
+            show_memory(&args, working_dir);
+            CommandResult::Continue
+        }
+
+        Command::Attach { path } => {
+            if path.is_empty() {
+                terminal::println_above(&"Usage: /attach <image file>".yellow().to_string());
+                return Some(CommandResult::Continue);
+            }
+            let full_path = working_dir.join(crate::tools::expand_tilde(&path));
+            match crate::file_refs::load_image(&full_path) {
+                Ok(image) => CommandResult::AttachImage(image),
+                Err(e) => {
+                    terminal::println_above(
+                        &format!("Failed to attach {}: {}", path, e)
+                            .red()
+                            .to_string(),
+                    );
+                    CommandResult::Continue
+                }
+            }
+        }
+
+        Command::Cost => {
+            show_cost();
+            CommandResult::Continue
+        }
+
         Command::Lsp => {
             // Keep /lsp as a menu-style action so it can run while streaming.
             CommandResult::OpenLspMenu
@@ -3613,26 +4719,145 @@ async fn handle_command(
         }
 
         Command::Undo => {
-            let removed = crate::provider::remove_last_turn(messages);
-            if removed > 0 {
-                // Remove the last turn from the on-screen history as well.
-                // If the history is unexpectedly missing a `UserPrompt` marker, fall back
-                // to rebuilding from the remaining messages so the display stays in sync.
-                if !history::undo_last_turn() {
-                    history::clear();
-                    for msg in messages.iter() {
-                        history::push_message(msg);
+            if !undo_last_turn(messages, prompt_box) {
+                terminal::println_above("No turns to undo.");
+            }
+            CommandResult::Continue
+        }
+
+        Command::Retry { model } => {
+            let Some(prompt) = crate::provider::last_user_prompt(messages) else {
+                terminal::println_above("No turns to retry.");
+                return Some(CommandResult::Continue);
+            };
+            undo_last_turn(messages, prompt_box);
+            if !model.is_empty()
+                && let Some(pm) = provider_manager.as_mut()
+            {
+                let (provider, model_id, custom_provider) =
+                    crate::providers::parse_model_spec(&model);
+                let choice = ModelChoice {
+                    provider,
+                    model_id,
+                    custom_provider,
+                    is_favorite: false,
+                };
+                *model_override_restore =
+                    apply_model_override(pm, &choice, messages, thinking_state, input_state);
+            }
+            CommandResult::SendToModel(prompt)
+        }
+
+        Command::Revert => {
+            match services.checkpoints.revert() {
+                Ok(restored) if restored.is_empty() => {
+                    terminal::println_above("No file changes to revert.");
+                }
+                Ok(restored) => {
+                    let root = crate::tools::paths::workspace_root();
+                    terminal::println_above(&format!(
+                        "Restored {} file{}:",
+                        restored.len(),
+                        if restored.len() == 1 { "" } else { "s" }
+                    ));
+                    for path in &restored {
+                        terminal::println_above(&format!(
+                            "  {}",
+                            crate::tools::paths::display(path, &root)
+                        ));
                     }
                 }
+                Err(e) => {
+                    terminal::println_above(
+                        &format!("Failed to revert files: {}", e).red().to_string(),
+                    );
+                }
+            }
+            CommandResult::Continue
+        }
 
-                // Add an info event so the user can see what happened.
-                history::push(history::HistoryEvent::Info(
-                    "Removed the most recent turn.".to_string(),
-                ));
-
-                prompt_box.redraw_history().ok();
+        Command::Rollback { args } => {
+            let mut words = args.split_whitespace();
+            let confirm = words.next() == Some("confirm");
+            let include_git = words.next() == Some("git");
+            let plan = crate::rollback::plan(messages, &services.checkpoints, working_dir);
+            let root = crate::tools::paths::workspace_root();
+            if plan.is_empty() {
+                terminal::println_above("Nothing to roll back.");
+            } else if !confirm {
+                terminal::println_above("Rolling back the last turn will:");
+                if let Some(prompt) = &plan.prompt {
+                    let preview: String = prompt.chars().take(60).collect();
+                    let ellipsis = if preview.len() < prompt.len() {
+                        "…"
+                    } else {
+                        ""
+                    };
+                    terminal::println_above(&format!(
+                        "  [x] Remove the turn \"{}{}\" from the conversation",
+                        preview.replace('\n', " "),
+                        ellipsis
+                    ));
+                }
+                for path in &plan.files {
+                    terminal::println_above(&format!(
+                        "  [x] Restore {}",
+                        crate::tools::paths::display(path, &root)
+                    ));
+                }
+                for path in &plan.git_files {
+                    terminal::println_above(&format!(
+                        "  [ ] git restore {}",
+                        crate::tools::paths::display(path, &root)
+                    ));
+                }
+                if !plan.git_files.is_empty() {
+                    terminal::println_above(
+                        &"git restore discards all unstaged changes in these files, including ones made before the turn."
+                            .yellow()
+                            .to_string(),
+                    );
+                }
+                let hint = if plan.git_files.is_empty() {
+                    "Run /rollback confirm to proceed."
+                } else {
+                    "Run /rollback confirm to proceed, or /rollback confirm git to also restore the unchecked files with git."
+                };
+                terminal::println_above(&hint.dimmed().to_string());
             } else {
-                terminal::println_above("No turns to undo.");
+                undo_last_turn(messages, prompt_box);
+                match services.checkpoints.revert_latest() {
+                    Ok(restored) => {
+                        for path in &restored {
+                            terminal::println_above(&format!(
+                                "Restored {}",
+                                crate::tools::paths::display(path, &root)
+                            ));
+                        }
+                    }
+                    Err(e) => {
+                        terminal::println_above(
+                            &format!("Failed to revert files: {}", e).red().to_string(),
+                        );
+                    }
+                }
+                if include_git && !plan.git_files.is_empty() {
+                    match crate::rollback::git_restore(working_dir, &plan.git_files) {
+                        Ok(()) => {
+                            for path in &plan.git_files {
+                                terminal::println_above(&format!(
+                                    "Restored {} with git",
+                                    crate::tools::paths::display(path, &root)
+                                ));
+                            }
+                        }
+                        Err(e) => {
+                            terminal::println_above(
+                                &format!("git restore failed: {}", e).red().to_string(),
+                            );
+                        }
+                    }
+                }
             }
             CommandResult::Continue
         }
@@ -3679,6 +4904,14 @@ async fn handle_command(
             }
         }
 
+        Command::McpPrompt { name, args } => match services.mcp.get_prompt(&name, &args).await {
+            Ok(prompt) => CommandResult::SendToModel(prompt),
+            Err(e) => {
+                terminal::println_above(&e.to_string().red().to_string());
+                return None;
+            }
+        },
+
         Command::Model => {
             // Return to event loop to open the model menu
             CommandResult::OpenModelMenu
@@ -3727,26 +4960,481 @@ async fn handle_command(
             CommandResult::OpenSessionsMenu
         }
 
-        Command::BuildAgentsMd => {
-            // Send the build-agents-md prompt to the model
-            let prompt = crate::prompts::BUILD_AGENTS_MD_PROMPT.to_string();
-            CommandResult::SendToModel(prompt)
+        Command::Set { args } => {
+            set_option(&args, provider_manager.as_mut());
+            CommandResult::Continue
         }
 
-        Command::StartTransactionLogging => {
-            let path = crate::provider::transaction_log::start(None);
-            terminal::println_above(&format!("Transaction logging started: {}", path.display()));
+        Command::Tee { args } => {
+            tee_command(&args);
             CommandResult::Continue
         }
 
-        Command::StopTransactionLogging => {
-            crate::provider::transaction_log::stop();
-            terminal::println_above("Transaction logging stopped.");
+        Command::Timeline { args } => {
+            let timeline = match args.trim() {
+                "" => services.timeline.render_turn(),
+                "session" => services.timeline.render_session(),
+                _ => {
+                    terminal::println_above(&"Usage: /timeline [session]".yellow().to_string());
+                    return Some(CommandResult::Continue);
+                }
+            };
+            match timeline {
+                Some(timeline) => {
+                    for line in timeline.lines() {
+                        terminal::println_above(line);
+                    }
+                }
+                None => terminal::println_above("No turns yet."),
+            }
             CommandResult::Continue
         }
 
-        Command::DumpPrompt => {
-            let Some(provider_manager) = provider_manager.as_mut() else {
+        Command::Doctor { topic } => {
+            match topic.as_str() {
+                "" | "terminal" => {
+                    terminal::println_above(&"Terminal capabilities:".cyan().bold().to_string());
+                    for line in capabilities::report() {
+                        terminal::println_above(&line);
+                    }
+                }
+                _ => {
+                    terminal::println_above(&"Usage: /doctor [terminal]".yellow().to_string());
+                }
+            }
+            CommandResult::Continue
+        }
+
+        Command::PinSession => {
+            let Some(session_id) = current_session_id.as_deref() else {
+                terminal::println_above("No session to pin yet.");
+                return None;
+            };
+            let pinned = session::load_session_by_id(working_dir, session_id)
+                .is_some_and(|state| state.meta.pinned);
+            match session::set_session_pinned(working_dir, session_id, !pinned) {
+                Ok(()) if pinned => terminal::println_above("Unpinned the session."),
+                Ok(()) => terminal::println_above("Pinned the session."),
+                Err(e) => terminal::println_above(
+                    &format!("Failed to pin the session: {}", e)
+                        .red()
+                        .to_string(),
+                ),
+            }
+            CommandResult::Continue
+        }
+
+        Command::Fork { turns } => {
+            let prompts = messages
+                .iter()
+                .filter(|m| m.role == Role::User && !m.is_tool_result_only())
+                .count();
+            let turns = match turns.parse::<usize>() {
+                _ if turns.is_empty() => None,
+                Ok(n) if (1..=prompts).contains(&n) => Some(n),
+                _ => {
+                    terminal::println_above(
+                        &format!("Usage: /fork [turn], where turn is 1 to {}", prompts)
+                            .yellow()
+                            .to_string(),
+                    );
+                    return None;
+                }
+            };
+            let Some(session_id) = current_session_id.clone() else {
+                terminal::println_above("No session to fork yet.");
+                return None;
+            };
+            let fork_id = match session::fork_session(working_dir, &session_id, turns) {
+                Ok(id) => id,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    terminal::println_above("No session to fork yet.");
+                    return None;
+                }
+                Err(e) => {
+                    terminal::println_above(
+                        &format!("Failed to fork the session: {}", e)
+                            .red()
+                            .to_string(),
+                    );
+                    return None;
+                }
+            };
+            let Some(state) = session::load_session_by_id(working_dir, &fork_id) else {
+                terminal::println_above(&"Failed to load the forked session".red().to_string());
+                return None;
+            };
+
+            *messages = session::RestoredSession::from_state(&state).messages;
+            *current_session_id = Some(fork_id);
+            services.set_session_id(current_session_id.clone());
+            if turns.is_some_and(|n| n < prompts) {
+                session::replay_session_into_output(&state);
+                prompt_box.redraw_history().ok();
+            }
+            terminal::println_above(&match turns {
+                Some(n) if n < prompts => format!(
+                    "Forked the session after turn {}. The original is still in /sessions.",
+                    n
+                ),
+                _ => "Forked the session. The original is still in /sessions.".to_string(),
+            });
+            CommandResult::Continue
+        }
+
+        Command::History { args } => match args.strip_prefix("clear") {
+            None if args.is_empty() => {
+                history_status();
+                CommandResult::Continue
+            }
+            Some(scope) => match crate::history::ClearScope::parse(scope) {
+                Some(scope) => CommandResult::ClearHistory(scope),
+                None => {
+                    terminal::println_above(
+                        &"Usage: /history clear <all|project|last [n]>"
+                            .yellow()
+                            .to_string(),
+                    );
+                    return None;
+                }
+            },
+            None => {
+                terminal::println_above(
+                    &"Usage: /history [clear <all|project|last [n]>]"
+                        .yellow()
+                        .to_string(),
+                );
+                return None;
+            }
+        },
+
+        Command::Rename { title } => {
+            let Some(title) = session::clean_title(&title) else {
+                terminal::println_above(&"Usage: /rename <title>".yellow().to_string());
+                return None;
+            };
+            let Some(session_id) = current_session_id.as_deref() else {
+                terminal::println_above("No session to rename yet.");
+                return None;
+            };
+            match session::set_session_title(working_dir, session_id, &title) {
+                Ok(()) => terminal::println_above(&format!("Renamed the session to {}.", title)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    terminal::println_above("No session to rename yet.");
+                }
+                Err(e) => terminal::println_above(
+                    &format!("Failed to rename the session: {}", e)
+                        .red()
+                        .to_string(),
+                ),
+            }
+            CommandResult::Continue
+        }
+
+        Command::Copy { args } => {
+            let Some(target) = copy::CopyTarget::parse(&args) else {
+                terminal::println_above(&"Usage: /copy [code [n]]".yellow().to_string());
+                return None;
+            };
+            let Some(response) = copy::last_response(messages) else {
+                terminal::println_above("No response to copy.");
+                return None;
+            };
+            let (text, what) = match target {
+                copy::CopyTarget::Response => (response, "the last response"),
+                copy::CopyTarget::CodeBlock(n) => {
+                    let blocks = copy::code_blocks(&response);
+                    let Some(block) = blocks.len().checked_sub(n).map(|i| blocks[i].clone()) else {
+                        terminal::println_above(&format!(
+                            "The last response has {} code block{}.",
+                            blocks.len(),
+                            if blocks.len() == 1 { "" } else { "s" }
+                        ));
+                        return None;
+                    };
+                    (block, "the code block")
+                }
+            };
+            match clipboard::copy_text(&text) {
+                Ok(()) => terminal::println_above(&format!(
+                    "Copied {} ({} lines).",
+                    what,
+                    text.lines().count()
+                )),
+                Err(e) => {
+                    terminal::println_above(&format!("Failed to copy: {}", e).red().to_string())
+                }
+            }
+            CommandResult::Continue
+        }
+
+        Command::Search { query } => {
+            if query.is_empty() {
+                terminal::println_above(&"Usage: /search <text>".yellow().to_string());
+                return None;
+            }
+            let matches = search::search(messages, &query);
+            if matches.is_empty() {
+                terminal::println_above(&format!("No matches for \"{}\".", query));
+                return None;
+            }
+            terminal::println_above(&format!(
+                "{} match{} for \"{}\":",
+                matches.len(),
+                if matches.len() == 1 { "" } else { "es" },
+                query
+            ));
+            for m in matches.iter().take(search::MAX_LISTED) {
+                terminal::println_above(&m.render());
+            }
+            if matches.len() > search::MAX_LISTED {
+                terminal::println_above(
+                    &format!("  … and {} more", matches.len() - search::MAX_LISTED)
+                        .dimmed()
+                        .to_string(),
+                );
+            }
+            CommandResult::Continue
+        }
+
+        Command::Note { text } => {
+            if text.is_empty() {
+                terminal::println_above(&"Usage: /note <text>".yellow().to_string());
+                return None;
+            }
+            history::push(history::HistoryEvent::Note(text));
+            prompt_box.redraw_history().ok();
+
+            // Save right away so the note is kept even if no further turns happen.
+            if !messages.is_empty()
+                && let Some(pm) = provider_manager.as_ref()
+            {
+                match session::save_session(
+                    working_dir,
+                    messages,
+                    &pm.current_provider(),
+                    pm.current_model_id(),
+                    thinking_state.enabled,
+                    services.is_read_only(),
+                    pm.seed(),
+                    current_session_id.as_deref(),
+                ) {
+                    Ok(id) => *current_session_id = Some(id),
+                    Err(e) => {
+                        terminal::println_above(&format!("Warning: Failed to save session: {}", e));
+                    }
+                }
+            }
+            CommandResult::Continue
+        }
+
+        Command::Recap { args } => {
+            let save = match args.trim() {
+                "" => false,
+                "save" => true,
+                _ => {
+                    terminal::println_above(&"Usage: /recap [save]".yellow().to_string());
+                    return None;
+                }
+            };
+            if !messages.iter().any(|m| m.role == Role::Assistant) {
+                terminal::println_above(&"Nothing to recap yet.".yellow().to_string());
+                return None;
+            }
+            let pm = provider_manager.as_mut()?;
+
+            input_state.clear();
+            prompt_box.draw(input_state, true).ok();
+            terminal::println_above(&"Writing recap...".cyan().to_string());
+
+            let model = crate::config::ConfigFile::load()
+                .ok()
+                .and_then(|config| config.recap.model);
+            match pm.recap(messages, model.as_deref()).await {
+                Ok(recap) => {
+                    terminal::println_above("");
+                    for line in recap.lines() {
+                        terminal::println_above(line);
+                    }
+                    if save {
+                        match crate::recap::append_worklog(working_dir, &recap) {
+                            Ok(path) => terminal::println_above(
+                                &format!("Appended to {}", path.display()).cyan().to_string(),
+                            ),
+                            Err(e) => terminal::println_above(
+                                &format!("Failed to write {}: {}", crate::recap::WORKLOG_FILE, e)
+                                    .red()
+                                    .to_string(),
+                            ),
+                        }
+                    }
+                }
+                Err(e) => {
+                    terminal::println_above(
+                        &format!("Error: {}", e.display_message()).red().to_string(),
+                    );
+                }
+            }
+            CommandResult::Continue
+        }
+
+        Command::Explain { args } => match crate::explain::prompt(&args, working_dir) {
+            Ok(prompt) => CommandResult::SendToModel(prompt),
+            Err(msg) => {
+                terminal::println_above(&msg.yellow().to_string());
+                return None;
+            }
+        },
+
+        Command::Translate { language } => {
+            if language.is_empty() {
+                terminal::println_above(&"Usage: /translate <language>".yellow().to_string());
+                return None;
+            }
+            if !messages.iter().any(|m| m.role == Role::Assistant) {
+                terminal::println_above(&"No answer to translate yet.".yellow().to_string());
+                return None;
+            }
+            CommandResult::SendToModel(crate::prompts::translate_prompt(&language))
+        }
+
+        Command::SplitCommits { instructions } => {
+            if services.is_read_only() {
+                terminal::println_above(
+                    &"/split-commits needs write access; leave read-only mode first."
+                        .yellow()
+                        .to_string(),
+                );
+                return None;
+            }
+            match crate::prompts::split_commits_prompt(working_dir, &instructions) {
+                Ok(prompt) => CommandResult::SendToModel(prompt),
+                Err(msg) => {
+                    terminal::println_above(&msg.yellow().to_string());
+                    return None;
+                }
+            }
+        }
+
+        Command::Feedback { args } => {
+            let (rating, note) = args.split_once(' ').unwrap_or((args.as_str(), ""));
+            let Some(rating) = crate::feedback::Rating::parse(rating) else {
+                terminal::println_above(&"Usage: /feedback up|down [note]".yellow().to_string());
+                return None;
+            };
+            let Some((turn, prompt)) = crate::feedback::last_answered_turn(messages) else {
+                terminal::println_above(&"No answer to rate yet.".yellow().to_string());
+                return None;
+            };
+            let pm = provider_manager.as_ref()?;
+            let provider = pm
+                .current_custom_provider()
+                .unwrap_or_else(|| pm.current_provider().id())
+                .to_string();
+            let note = note.trim();
+            let record = crate::feedback::FeedbackRecord {
+                timestamp: chrono::Utc::now(),
+                session_id: current_session_id.clone(),
+                turn,
+                provider,
+                model: pm.current_model_id().to_string(),
+                rating,
+                prompt,
+                note: (!note.is_empty()).then(|| note.to_string()),
+            };
+            match crate::feedback::record(&record) {
+                Ok(()) => {
+                    let label = match rating {
+                        crate::feedback::Rating::Up => "up",
+                        crate::feedback::Rating::Down => "down",
+                    };
+                    terminal::println_above(&format!(
+                        "Rated the last answer of {}/{} {}.",
+                        record.provider, record.model, label
+                    ));
+                }
+                Err(e) => {
+                    terminal::println_above(
+                        &format!("Failed to save feedback: {}", e).red().to_string(),
+                    );
+                }
+            }
+            CommandResult::Continue
+        }
+
+        Command::Fix { instructions } => {
+            let diagnostics = crate::lsp::quickfix::pending();
+            if diagnostics.is_empty() {
+                terminal::println_above(
+                    &"No diagnostics in recently edited files."
+                        .yellow()
+                        .to_string(),
+                );
+                return None;
+            }
+            CommandResult::SendToModel(crate::lsp::quickfix::prompt(
+                &diagnostics,
+                working_dir,
+                &instructions,
+            ))
+        }
+
+        Command::BuildAgentsMd => {
+            // Send the build-agents-md prompt to the model
+            let prompt = crate::prompts::BUILD_AGENTS_MD_PROMPT.to_string();
+            CommandResult::SendToModel(prompt)
+        }
+
+        Command::StartTransactionLogging => {
+            let path = crate::provider::transaction_log::start(None);
+            terminal::println_above(&format!("Transaction logging started: {}", path.display()));
+            CommandResult::Continue
+        }
+
+        Command::StopTransactionLogging => {
+            crate::provider::transaction_log::stop();
+            terminal::println_above("Transaction logging stopped.");
+            CommandResult::Continue
+        }
+
+        Command::Expand { args } => {
+            use crate::compaction;
+
+            if !matches!(args.trim(), "" | "summary") {
+                terminal::println_above(&"Usage: /expand [summary]".yellow().to_string());
+                return Some(CommandResult::Continue);
+            }
+            let transcripts = current_session_id
+                .as_deref()
+                .map(|id| session::load_compacted_transcripts(working_dir, id))
+                .unwrap_or_default();
+            let Some(transcript) = transcripts.last() else {
+                terminal::println_above(
+                    &"No compacted history for this session."
+                        .yellow()
+                        .to_string(),
+                );
+                return None;
+            };
+
+            let messages: Vec<Message> = transcript.messages.iter().map(Message::from).collect();
+            terminal::println_above(
+                &format!(
+                    "[Pre-compaction history: {} messages, compacted {}]",
+                    messages.len(),
+                    session::format_age(&transcript.compacted_at)
+                )
+                .cyan()
+                .to_string(),
+            );
+            for line in compaction::format_transcript(&messages) {
+                terminal::println_above(&escapes::sanitize(&line));
+            }
+            CommandResult::Continue
+        }
+
+        Command::DumpPrompt => {
+            let Some(provider_manager) = provider_manager.as_mut() else {
                 print_no_providers_or_model_configured();
                 return None;
             };
@@ -3823,6 +5511,14 @@ async fn handle_command(
                         terminal::println_above(&format!("Error: {}", e).red().to_string());
                     }
                 }
+                let cache = crate::usage::anthropic().cache_stats();
+                if cache.hits + cache.misses > 0 {
+                    terminal::println_above(&format!(
+                        "{} {}",
+                        "Prompt cache:".green(),
+                        cache.format()
+                    ));
+                }
             } else {
                 terminal::println_above(
                     &"/claude-usage requires a Claude provider with OAuth."
@@ -3830,6 +5526,14 @@ async fn handle_command(
                         .to_string(),
                 );
             }
+            let exploration = services.exploration.stats();
+            if exploration.hits + exploration.misses > 0 {
+                terminal::println_above(&format!(
+                    "{} {}",
+                    "Task read cache:".green(),
+                    exploration.format()
+                ));
+            }
             CommandResult::Continue
         }
 
@@ -3887,6 +5591,387 @@ async fn handle_command(
     })
 }
 
+/// Print an aligned overview of the current session state
+async fn show_status(
+    provider_manager: Option<&ProviderManager>,
+    thinking_state: &crate::providers::ThinkingState,
+    session_id: Option<&str>,
+    working_dir: &std::path::Path,
+    messages: &[Message],
+    services: &Services,
+) {
+    let mut rows: Vec<(&str, String)> = Vec::new();
+
+    if let Some(pm) = provider_manager {
+        let provider = pm.current_provider();
+        let model = pm.current_model_id();
+        let provider_str = match pm.current_custom_provider() {
+            Some(name) => format!("{} ({})", name, provider.display_name()),
+            None => provider.display_name().to_string(),
+        };
+        rows.push(("Provider", provider_str));
+        rows.push(("Model", model.to_string()));
+
+        let thinking = if !supports_thinking(provider, model) {
+            "n/a".to_string()
+        } else if !thinking_state.enabled {
+            "off".to_string()
+        } else {
+            thinking_state
+                .mode
+                .clone()
+                .unwrap_or_else(|| "on".to_string())
+        };
+        rows.push(("Thinking", thinking));
+
+        let context = match (pm.get_last_context_usage(), pm.get_context_limit()) {
+            (Some(used), Some(limit)) => format!(
+                "{} / {} tokens ({:.0}%)",
+                used,
+                limit,
+                used as f64 / limit as f64 * 100.0
+            ),
+            (Some(used), None) => format!("{} tokens", used),
+            _ => "unknown".to_string(),
+        };
+        rows.push(("Context", context));
+
+        if let Some((input, output)) = pm.get_total_usage() {
+            rows.push(("Tokens", format!("{} in / {} out", input, output)));
+        }
+        rows.push((
+            "Cost",
+            crate::usage::format_cost(crate::usage::session_cost()),
+        ));
+    } else {
+        rows.push(("Model", "not configured".to_string()));
+    }
+
+    let mode = if services.is_read_only() {
+        "Read-Only"
+    } else if services.is_sandbox_enabled() {
+        "Read-Write (Sandbox enabled)"
+    } else {
+        "YOLO (Sandbox disabled)"
+    };
+    rows.push(("Mode", mode.to_string()));
+
+    rows.push(("Session", session_id.unwrap_or("none").to_string()));
+    if let Some(title) = session_id.and_then(|id| session::session_title(working_dir, id)) {
+        rows.push(("Title", title));
+    }
+    rows.push(("Messages", messages.len().to_string()));
+
+    let mcp_servers: Vec<String> = services
+        .mcp
+        .server_statuses()
+        .await
+        .into_iter()
+        .filter(|s| s.is_running)
+        .map(|s| format!("{} ({} tools)", s.name, s.tool_count))
+        .collect();
+    rows.push((
+        "MCP",
+        if mcp_servers.is_empty() {
+            "none".to_string()
+        } else {
+            mcp_servers.join(", ")
+        },
+    ));
+
+    let config = crate::config::ConfigFile::load().unwrap_or_default();
+    #[cfg(feature = "plugins")]
+    if let Some(plugins) = crate::tools::plugin::status(&config) {
+        rows.push(("Plugins", plugins));
+    }
+
+    let lsp_enabled = config.lsp_enabled;
+    let lsp_servers: Vec<String> = if lsp_enabled {
+        crate::lsp::manager()
+            .server_info()
+            .await
+            .into_iter()
+            .map(|s| s.name)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    rows.push((
+        "LSP",
+        if !lsp_enabled {
+            "disabled".to_string()
+        } else if lsp_servers.is_empty() {
+            "none".to_string()
+        } else {
+            lsp_servers.join(", ")
+        },
+    ));
+
+    let instructions: Vec<String> = crate::prompts::instruction_files()
+        .iter()
+        .map(|file| match file.path.strip_prefix(working_dir) {
+            Ok(relative) => relative.display().to_string(),
+            Err(_) => shorten_path(&file.path),
+        })
+        .collect();
+    rows.push((
+        "Memory",
+        if instructions.is_empty() {
+            "none".to_string()
+        } else {
+            instructions.join(", ")
+        },
+    ));
+
+    terminal::println_above(&"Status:".cyan().bold().to_string());
+    for (label, value) in rows {
+        let label = format!("{:<10}", label);
+        terminal::println_above(&format!("  {} {}", label.green(), value));
+    }
+}
+
+/// Largest messages listed by `/context`.
+const CONTEXT_LARGEST_MESSAGES: usize = 5;
+
+/// Print an estimate of what takes up the context window
+async fn show_context(
+    provider_manager: Option<&ProviderManager>,
+    messages: &[Message],
+    services: &Services,
+) {
+    use crate::usage::{estimate_message_tokens, estimate_tokens};
+
+    let model = provider_manager
+        .map(|pm| pm.current_model_spec())
+        .unwrap_or_default();
+    let system = estimate_tokens(
+        &crate::prompts::system_prompt_with_services(Some(services), &model).join("\n\n"),
+    );
+    let tools = crate::tools::all_definitions(services).await;
+    let tool_tokens = estimate_tokens(&serde_json::to_string(&tools).unwrap_or_default());
+    let message_tokens: Vec<u64> = messages.iter().map(estimate_message_tokens).collect();
+    let conversation: u64 = message_tokens.iter().sum();
+    let estimated = system + tool_tokens + conversation;
+
+    let mut rows: Vec<(&str, String)> = vec![
+        ("System", format!("{} tokens", system)),
+        (
+            "Tools",
+            format!("{} tokens ({} tools)", tool_tokens, tools.len()),
+        ),
+        (
+            "Messages",
+            format!("{} tokens ({} messages)", conversation, messages.len()),
+        ),
+        ("Estimated", format!("{} tokens", estimated)),
+    ];
+
+    let mut used = estimated;
+    let mut limit = None;
+    if let Some(pm) = provider_manager {
+        if let Some(last) = pm.get_last_usage()
+            && last.input > 0
+        {
+            let prompt =
+                crate::usage::prompt_tokens(pm.current_provider(), pm.current_model_id(), &last);
+            let uncached = prompt.saturating_sub(last.cache_read + last.cache_creation);
+            rows.push((
+                "Last call",
+                format!(
+                    "{} tokens ({} cached, {} cache write, {} uncached)",
+                    prompt, last.cache_read, last.cache_creation, uncached
+                ),
+            ));
+            used = prompt;
+        }
+        limit = pm.get_context_limit();
+    }
+    match limit {
+        Some(limit) => rows.push((
+            "Window",
+            format!(
+                "{} tokens, {} remaining ({:.0}% used)",
+                limit,
+                limit.saturating_sub(used),
+                used as f64 / limit as f64 * 100.0
+            ),
+        )),
+        None => rows.push(("Window", "unknown".to_string())),
+    }
+
+    terminal::println_above(&"Context:".cyan().bold().to_string());
+    for (label, value) in rows {
+        let label = format!("{:<10}", label);
+        terminal::println_above(&format!("  {} {}", label.green(), value));
+    }
+    if let Some(limit) = limit {
+        let width = 40;
+        let filled = ((used as f64 / limit as f64 * width as f64).round() as usize).min(width);
+        terminal::println_above(&format!(
+            "  {:<10} {}{}",
+            "",
+            "█".repeat(filled),
+            "░".repeat(width - filled).bright_black()
+        ));
+    }
+
+    let mut largest: Vec<(usize, u64)> = message_tokens.into_iter().enumerate().collect();
+    largest.sort_by_key(|&(_, tokens)| std::cmp::Reverse(tokens));
+    largest.truncate(CONTEXT_LARGEST_MESSAGES);
+    if !largest.is_empty() {
+        terminal::println_above(&"Largest messages:".cyan().bold().to_string());
+        for (index, tokens) in largest {
+            let message = &messages[index];
+            terminal::println_above(&format!(
+                "  {:>4} {:<12} {:>6}  {}",
+                format!("#{}", index + 1),
+                message_kind(message),
+                listener::format_tokens(tokens),
+                message_preview(message).bright_black()
+            ));
+        }
+    }
+}
+
+fn message_kind(message: &Message) -> &'static str {
+    match message.role {
+        Role::System => "system",
+        Role::Assistant => "assistant",
+        Role::User if message.is_tool_result_only() => "tool results",
+        Role::User => "user",
+    }
+}
+
+/// First line of a message's text, or the tools it calls
+fn message_preview(message: &Message) -> String {
+    let text = match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Blocks(blocks) => blocks
+            .iter()
+            .find_map(|block| match block {
+                ContentBlock::Text { text } if !text.trim().is_empty() => Some(text.clone()),
+                ContentBlock::ToolUse { name, .. } => Some(format!("[{}]", name)),
+                ContentBlock::ToolResult { content, .. } => Some(content.clone()),
+                ContentBlock::Summary { .. } => Some("[summary]".to_string()),
+                _ => None,
+            })
+            .unwrap_or_default(),
+    };
+    let line = text
+        .lines()
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or("")
+        .trim();
+    prompt::truncate_to_width(line, 50)
+}
+
+/// Print the session cost, the budget and a breakdown by model
+/// List the instruction files added to the system prompt, or with `show`,
+/// print their contents.
+fn show_memory(args: &str, working_dir: &std::path::Path) {
+    let show = match args.trim() {
+        "" => false,
+        "show" => true,
+        _ => {
+            terminal::println_above(&"Usage: /memory [show]".red().to_string());
+            return;
+        }
+    };
+
+    let files = crate::prompts::instruction_files();
+    if files.is_empty() {
+        terminal::println_above(
+            "No AGENTS.md, CLAUDE.md or README found. /build-agents-md creates an AGENTS.md.",
+        );
+        return;
+    }
+
+    let name = |path: &std::path::Path| match path.strip_prefix(working_dir) {
+        Ok(relative) => relative.display().to_string(),
+        Err(_) => shorten_path(path),
+    };
+    let names: Vec<String> = files.iter().map(|file| name(&file.path)).collect();
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+
+    terminal::println_above(
+        &"Instructions in the system prompt:"
+            .cyan()
+            .bold()
+            .to_string(),
+    );
+    for (file, name) in files.iter().zip(&names) {
+        let tokens = crate::usage::estimate_tokens(&file.contents);
+        let name = format!("{:<width$}", name);
+        terminal::println_above(&format!(
+            "  {}  {:>5} lines  ~{} tokens",
+            name.green(),
+            file.contents.lines().count(),
+            listener::format_tokens(tokens),
+        ));
+    }
+
+    if show {
+        for (file, name) in files.iter().zip(&names) {
+            terminal::println_above("");
+            terminal::println_above(&format!("── {} ──", name).bright_black().to_string());
+            for line in file.contents.lines() {
+                terminal::println_above(line);
+            }
+        }
+    } else {
+        terminal::println_above(
+            &"/memory show prints their contents."
+                .bright_black()
+                .to_string(),
+        );
+    }
+}
+
+fn show_cost() {
+    let config = crate::config::ConfigFile::load().unwrap_or_default();
+    let mut rows: Vec<(String, String)> = vec![
+        (
+            "Session".to_string(),
+            crate::usage::format_cost(crate::usage::session_cost()),
+        ),
+        (
+            "Last turn".to_string(),
+            crate::usage::format_cost(crate::usage::turn_cost()),
+        ),
+    ];
+    if let Some(limit) = config.cost.max_session_cost {
+        let action = match config.cost.on_limit {
+            crate::config::CostLimitAction::Warn => "warn",
+            crate::config::CostLimitAction::Stop => "stop",
+        };
+        rows.push((
+            "Limit".to_string(),
+            format!("{} ({})", crate::usage::format_cost(limit), action),
+        ));
+    }
+    for (label, model) in crate::usage::session_cost_by_model() {
+        let cost = model
+            .cost
+            .map(crate::usage::format_cost)
+            .unwrap_or_else(|| "no pricing".to_string());
+        let mut tokens = format!("{} in / {} out", model.tokens.input, model.tokens.output);
+        if model.tokens.cache_read > 0 || model.tokens.cache_creation > 0 {
+            tokens.push_str(&format!(
+                " / {} cache read / {} cache write",
+                model.tokens.cache_read, model.tokens.cache_creation
+            ));
+        }
+        rows.push((label, format!("{}  {}", cost, tokens)));
+    }
+
+    let width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    terminal::println_above(&"Cost:".cyan().bold().to_string());
+    for (label, value) in rows {
+        let label = format!("{:<width$}", label);
+        terminal::println_above(&format!("  {} {}", label.green(), value));
+    }
+}
+
 /// Show help with available commands.
 fn show_help(_custom_commands: &[CustomCommand]) {
     let has_claude_oauth = crate::commands::has_claude_oauth_provider();
@@ -3912,7 +5997,7 @@ fn show_help(_custom_commands: &[CustomCommand]) {
     ));
 
     terminal::println_above(&"Keyboard shortcuts:".cyan().bold().to_string());
-    let shortcut = format!("{:<21}", "Ctrl+M");
+    let shortcut = format!("{:<21}", capabilities::model_menu_key());
     terminal::println_above(&format!("  {} Switch model", shortcut.yellow()));
     let shortcut = format!("{:<21}", "Ctrl+T");
     terminal::println_above(&format!("  {} Toggle thinking", shortcut.yellow()));
@@ -3943,6 +6028,21 @@ fn show_help(_custom_commands: &[CustomCommand]) {
         "  {} Edit prompt in $VISUAL/$EDITOR",
         shortcut.yellow()
     ));
+    let shortcut = format!("{:<21}", "Alt+C");
+    terminal::println_above(&format!(
+        "  {} Copy mode: select output with the keyboard (v select, y copy)",
+        shortcut.yellow()
+    ));
+    let shortcut = format!("{:<21}", "Alt+Up / Alt+Down");
+    terminal::println_above(&format!(
+        "  {} Rate the last answer up or down (/feedback)",
+        shortcut.yellow()
+    ));
+    let shortcut = format!("{:<21}", "Ctrl+F");
+    terminal::println_above(&format!(
+        "  {} Ask to fix diagnostics in edited files (/fix)",
+        shortcut.yellow()
+    ));
     let shortcut = format!("{:<21}", "Ctrl+H");
     terminal::println_above(&format!("  {} Toggle hide tool output", shortcut.yellow()));
     let shortcut = format!("{:<21}", "Ctrl+N");
@@ -3952,11 +6052,34 @@ fn show_help(_custom_commands: &[CustomCommand]) {
         "  {} Force redraw (repaint screen)",
         shortcut.yellow()
     ));
+    let shortcut = format!("{:<21}", "Ctrl+Esc");
+    terminal::println_above(&format!(
+        "  {} Cancel only the running tool (during response)",
+        shortcut.yellow()
+    ));
 }
 
 #[cfg(test)]
 mod tests {
-    use super::panic_payload_to_string;
+    use super::{panic_payload_to_string, shell_result_summary, split_model_override};
+    use crate::providers::{ModelChoice, ModelProvider};
+
+    #[test]
+    fn test_shell_result_summary() {
+        let lines = vec!["ok".to_string(), "done".to_string()];
+        let elapsed = std::time::Duration::from_millis(1250);
+        assert_eq!(
+            shell_result_summary(&lines, Some(0), elapsed),
+            (false, "[Read 2 lines, 8 bytes, 1.2s]".to_string())
+        );
+        assert_eq!(
+            shell_result_summary(&[], Some(2), elapsed),
+            (
+                true,
+                "[Read 0 lines, 0 bytes, exit code 2, 1.2s]".to_string()
+            )
+        );
+    }
 
     #[test]
     fn test_panic_payload_to_string_static_str() {
@@ -3969,4 +6092,29 @@ mod tests {
         let payload: Box<dyn std::any::Any + Send> = Box::new("boom".to_string());
         assert_eq!(panic_payload_to_string(payload), "boom");
     }
+
+    #[test]
+    fn test_split_model_override() {
+        let choices = vec![ModelChoice {
+            provider: ModelProvider::Claude,
+            model_id: "claude-haiku-4-5".to_string(),
+            custom_provider: None,
+            is_favorite: false,
+        }];
+
+        let (input, model) =
+            split_model_override("review the diff @claude/claude-haiku-4-5", &choices);
+        assert_eq!(input, "review the diff");
+        assert_eq!(model.unwrap().model_id, "claude-haiku-4-5");
+
+        // Unknown models and file references stay in the prompt
+        let (input, model) = split_model_override("look at @src/main.rs", &choices);
+        assert_eq!(input, "look at @src/main.rs");
+        assert!(model.is_none());
+
+        // A bare override with no prompt is not stripped
+        let (input, model) = split_model_override("@claude/claude-haiku-4-5", &choices);
+        assert_eq!(input, "@claude/claude-haiku-4-5");
+        assert!(model.is_none());
+    }
 }
@@ -0,0 +1,330 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Handling of large text pastes.
+//!
+//! Pasting a long log or file into the prompt box makes the editor sluggish and the
+//! message hard to read. Pastes longer than `paste-attach-lines` are held back and a
+//! preview is shown; the user can attach the text as a file (saved under the cache
+//! directory and referenced by path in the prompt) or insert it inline anyway.
+//!
+//! Escape sequences are stripped from every paste. A paste that looks like
+//! output copied from a terminal, such as a failed build, is labeled as such
+//! and can be folded into a context block: the prompt box shows a one-line
+//! marker and the output is appended to the prompt when it is sent.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+use colored::Colorize;
+
+use crate::config::ConfigFile;
+
+/// Number of lines shown in the preview.
+const PREVIEW_LINES: usize = 5;
+
+/// Lines of terminal output from which folding it is offered.
+const MIN_FOLD_LINES: usize = 5;
+
+/// Saved pastes older than this are removed when another is saved.
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Start of the marker that stands for folded terminal output in a prompt.
+const FOLD_MARKER_PREFIX: &str = "[Terminal output (";
+
+/// Start of lines that show a paste was copied from a terminal: shell
+/// prompts, compiler diagnostics, build tools and test runners.
+const TERMINAL_LINE_PREFIXES: &[&str] = &[
+    "$ ",
+    "% ",
+    "❯ ",
+    "➜ ",
+    "error[E",
+    "error: ",
+    "warning: ",
+    "Compiling ",
+    "Finished ",
+    "Traceback (most recent call last):",
+    "npm ERR!",
+    "make: ***",
+    "--- FAIL:",
+    "FAILED ",
+    "thread '",
+];
+
+static ATTACH_LINES: AtomicUsize = AtomicUsize::new(0);
+
+/// Reload the paste threshold from config.
+pub(crate) fn reload_threshold() {
+    let lines = ConfigFile::load()
+        .map(|c| c.paste_attach_lines)
+        .unwrap_or_else(|_| ConfigFile::default().paste_attach_lines);
+    ATTACH_LINES.store(lines, Ordering::Relaxed);
+}
+
+/// Pasted text, cleaned up for the prompt box.
+pub(crate) struct Paste {
+    pub text: String,
+    /// Looks like output copied from a terminal
+    pub terminal: bool,
+}
+
+/// Normalize line endings of pasted text.
+fn normalize(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Strip escape sequences from pasted text and normalize its line endings.
+///
+/// Line endings are resolved first, since stripping drops lone carriage
+/// returns.
+pub(crate) fn prepare(raw: &str) -> Paste {
+    let strip = super::escapes::strip;
+    let colored = raw.contains(['\x1b', '\u{9b}']);
+    let text = if colored {
+        // Output with escape codes uses a lone carriage return to redraw a
+        // line, as progress bars do; keep what was left on screen.
+        raw.replace("\r\n", "\n")
+            .split('\n')
+            .map(|line| {
+                line.rsplit('\r')
+                    .map(strip)
+                    .find(|s| !s.is_empty())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        strip(&normalize(raw)).into_owned()
+    };
+    let terminal = colored || looks_like_terminal_output(&text);
+    Paste { text, terminal }
+}
+
+fn looks_like_terminal_output(text: &str) -> bool {
+    text.lines().any(|line| {
+        let line = line.trim_start();
+        TERMINAL_LINE_PREFIXES
+            .iter()
+            .any(|prefix| line.starts_with(prefix))
+    })
+}
+
+/// Whether `text` is long enough to offer attaching it as a file.
+pub(crate) fn should_offer_attach(text: &str) -> bool {
+    let threshold = ATTACH_LINES.load(Ordering::Relaxed);
+    threshold > 0 && text.lines().count() > threshold
+}
+
+/// Whether to hold `paste` back and ask what to do with it rather than
+/// inserting it.
+pub(crate) fn should_hold(paste: &Paste) -> bool {
+    should_offer_attach(&paste.text)
+        || paste.terminal && paste.text.lines().count() >= MIN_FOLD_LINES
+}
+
+/// Preview of a held-back paste with the available choices.
+pub(crate) fn preview(paste: &Paste) -> String {
+    let text = &paste.text;
+    let total = text.lines().count();
+    let what = if paste.terminal {
+        "terminal output"
+    } else {
+        "text"
+    };
+    let mut out = format!("Pasted {}, {} lines ({} bytes):\n", what, total, text.len())
+        .yellow()
+        .to_string();
+    for line in text.lines().take(PREVIEW_LINES) {
+        out.push_str(&format!("  {}\n", line).bright_black().to_string());
+    }
+    if total > PREVIEW_LINES {
+        out.push_str(&"  …\n".bright_black().to_string());
+    }
+    if paste.terminal {
+        out.push_str(&format!(
+            "{} fold into context block  {} attach as file  {} insert inline  {} discard",
+            "[f/Enter]".cyan(),
+            "[a]".cyan(),
+            "[i]".cyan(),
+            "[Esc]".cyan()
+        ));
+    } else {
+        out.push_str(&format!(
+            "{} attach as file  {} insert inline  {} discard",
+            "[a/Enter]".cyan(),
+            "[i]".cyan(),
+            "[Esc]".cyan()
+        ));
+    }
+    out
+}
+
+fn pastes_dir() -> PathBuf {
+    dirs::home_dir()
+        .map(|home| home.join(".cache").join("henri").join("pastes"))
+        .unwrap_or_else(|| std::env::temp_dir().join("henri").join("pastes"))
+}
+
+/// Remove the files in `dir` last modified before `cutoff`.
+fn prune(dir: &Path, cutoff: SystemTime) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| modified < cutoff)
+        {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn save(prefix: &str, text: &str) -> std::io::Result<PathBuf> {
+    let dir = pastes_dir();
+    std::fs::create_dir_all(&dir)?;
+    if let Some(cutoff) = SystemTime::now().checked_sub(MAX_AGE) {
+        prune(&dir, cutoff);
+    }
+    let name = format!(
+        "{}-{}.txt",
+        prefix,
+        chrono::Local::now().format("%Y%m%d-%H%M%S-%3f")
+    );
+    let path = dir.join(name);
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// Save the paste to a file and return the text to insert into the prompt in its place.
+pub(crate) fn attach(text: &str) -> std::io::Result<String> {
+    let path = save("paste", text)?;
+    Ok(reference(&path.display().to_string(), text.lines().count()))
+}
+
+fn reference(path: &str, lines: usize) -> String {
+    format!("[Pasted text ({} lines) attached as file: {}]", lines, path)
+}
+
+/// Fold terminal output into a context block. The output is saved like an
+/// attached paste and the returned marker is inserted into the prompt in its
+/// place; [`folded_context`] brings it back when the prompt is sent.
+pub(crate) fn fold(text: &str) -> std::io::Result<String> {
+    let path = save("output", text)?;
+    Ok(format!(
+        "{}{} lines): {}]",
+        FOLD_MARKER_PREFIX,
+        text.lines().count(),
+        path.display()
+    ))
+}
+
+/// Context blocks for the terminal output folded into `input`, to append to
+/// the prompt.
+pub(crate) fn folded_context(input: &str) -> String {
+    let mut context = String::new();
+    let mut rest = input;
+    while let Some(start) = rest.find(FOLD_MARKER_PREFIX) {
+        rest = &rest[start + FOLD_MARKER_PREFIX.len()..];
+        let Some((_, path)) = rest
+            .split_once(']')
+            .and_then(|(marker, _)| marker.split_once(" lines): "))
+        else {
+            continue;
+        };
+        if let Ok(output) = std::fs::read_to_string(path) {
+            context.push_str(&format!(
+                "\n\n<terminal-output>\n{}\n</terminal-output>",
+                output.trim_end()
+            ));
+        }
+    }
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_offer_attach() {
+        ATTACH_LINES.store(3, Ordering::Relaxed);
+        assert!(!should_offer_attach("a\nb\nc"));
+        assert!(should_offer_attach("a\nb\nc\nd"));
+        ATTACH_LINES.store(0, Ordering::Relaxed);
+        assert!(!should_offer_attach("a\nb\nc\nd"));
+    }
+
+    #[test]
+    fn test_preview_truncates() {
+        let text = (1..=8).map(|i| format!("line {}", i)).collect::<Vec<_>>();
+        let preview = preview(&prepare(&text.join("\n")));
+        assert!(preview.contains("Pasted text, 8 lines"));
+        assert!(preview.contains("line 5"));
+        assert!(!preview.contains("line 6"));
+    }
+
+    #[test]
+    fn test_prepare_terminal_output() {
+        let paste = prepare(
+            "\x1b[1m\x1b[32m   Compiling\x1b[0m henri\r\n\x1b[1m\x1b[31merror[E0308]\x1b[0m: mismatched types\r\nBuilding [=>  ] 1/9\rBuilding [===] 9/9\n",
+        );
+        assert!(paste.terminal);
+        assert_eq!(
+            paste.text,
+            "   Compiling henri\nerror[E0308]: mismatched types\nBuilding [===] 9/9\n"
+        );
+
+        let paste = prepare("line one\r\nline two");
+        assert!(!paste.terminal);
+        assert_eq!(paste.text, "line one\nline two");
+        assert_eq!(prepare("line one\rline two").text, "line one\nline two");
+        assert!(prepare("$ cargo test\nok").terminal);
+    }
+
+    #[test]
+    fn test_folded_context() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("output.txt");
+        std::fs::write(&path, "error: could not compile `henri`\n").unwrap();
+
+        let marker = format!("[Terminal output (1 lines): {}]", path.display());
+        let context = folded_context(&format!("why does this fail? {}", marker));
+        assert_eq!(
+            context,
+            "\n\n<terminal-output>\nerror: could not compile `henri`\n</terminal-output>"
+        );
+        assert_eq!(folded_context("no output here"), "");
+    }
+
+    #[test]
+    fn test_reference() {
+        assert_eq!(
+            reference("/tmp/p.txt", 240),
+            "[Pasted text (240 lines) attached as file: /tmp/p.txt]"
+        );
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("paste-old.txt");
+        let new = dir.path().join("paste-new.txt");
+        std::fs::write(&old, "old").unwrap();
+        std::fs::write(&new, "new").unwrap();
+        let now = SystemTime::now();
+        std::fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(now - MAX_AGE * 2)
+            .unwrap();
+
+        prune(dir.path(), now - MAX_AGE);
+        assert!(!old.exists());
+        assert!(new.exists());
+    }
+}
@@ -18,6 +18,7 @@ use crossterm::terminal::{self, ClearType};
 use crossterm::{SynchronizedUpdate, cursor, execute, queue};
 use unicode_width::UnicodeWidthChar;
 
+use super::capabilities;
 use super::completion_menu;
 use super::input::{InputState, display_width};
 use super::menus::{
@@ -28,17 +29,16 @@ use super::render::colorize_image_markers;
 use super::style::{SOFTWARE_CURSOR_OFF, SOFTWARE_CURSOR_ON};
 use super::terminal as cli_terminal;
 
-const BORDER_COLOR: Color = Color::Rgb {
-    r: 68,
-    g: 68,
-    b: 68,
-};
+fn border_color() -> Color {
+    capabilities::rgb(68, 68, 68)
+}
 
 /// Background color for user prompts (ANSI 256-color 236 = dark grey)
 const PROMPT_BG_COLOR: Color = Color::AnsiValue(236);
 
 const EXIT_HINT_TEXT: &str = "Press Ctrl+C again within 2s to exit";
 const WELCOME_HINT_TEXT: &str = "Welcome to Henri 🐕, type /help for more info";
+const MINIMAL_WELCOME_HINT_TEXT: &str = "Welcome to Henri, type /help for more info";
 
 const SGR_DIM_ON: &str = "\x1b[2m";
 const SGR_DIM_OFF: &str = "\x1b[22m";
@@ -177,8 +177,14 @@ pub(super) struct PromptBox {
     last_start_row: Option<u16>,
     last_height: u16,
     status: StatusInfo,
+    /// Output of the user's statusline command, shown instead of the built-in status line
+    custom_status: Option<String>,
+    /// Session cost in dollars, and whether it has reached the limit
+    cost: Option<(f64, bool)>,
     exit_hint_until: Option<Instant>,
     welcome_hint_active: bool,
+    /// Leave out the borders and their indicators
+    minimal: bool,
 }
 
 impl PromptBox {
@@ -190,11 +196,33 @@ impl PromptBox {
             last_start_row: None,
             last_height: 0,
             status: StatusInfo::default(),
+            custom_status: None,
+            cost: None,
             exit_hint_until: None,
             welcome_hint_active: false,
+            minimal: false,
         }
     }
 
+    /// Draw without borders, for the `minimal-ui` setting.
+    pub(super) fn set_minimal(&mut self, minimal: bool) {
+        self.minimal = minimal;
+    }
+
+    /// Rows above the input: the top border, unless minimal.
+    fn top_rows(&self) -> u16 {
+        if self.minimal { 0 } else { 1 }
+    }
+
+    /// Rows between the input and the status line: the bottom border, unless minimal.
+    fn bottom_rows(&self) -> u16 {
+        self.top_rows()
+    }
+
+    fn border_rows(&self) -> u16 {
+        self.top_rows() + self.bottom_rows()
+    }
+
     /// Get the last known height of the prompt box.
     pub(super) fn height(&self) -> u16 {
         self.last_height
@@ -249,6 +277,16 @@ impl PromptBox {
         };
     }
 
+    /// Set the line rendered by the user's statusline command.
+    pub(super) fn set_custom_status(&mut self, line: Option<String>) {
+        self.custom_status = line;
+    }
+
+    /// Set the session cost shown on the top border.
+    pub(super) fn set_cost(&mut self, cost: Option<(f64, bool)>) {
+        self.cost = cost;
+    }
+
     /// Draw the prompt box with the given input state.
     /// If `inline` is true, positions relative to cursor; otherwise uses last known position.
     pub(super) fn draw(&mut self, state: &InputState, inline: bool) -> io::Result<()> {
@@ -279,7 +317,7 @@ impl PromptBox {
                 self.viewport_start_for_cursor(wrapped_rows.len(), rows_to_display, cursor_pos.row);
 
             let display_cursor_row = cursor_pos.row.saturating_sub(viewport_start);
-            let cursor_row = start_row + 1 + display_cursor_row as u16;
+            let cursor_row = start_row + self.top_rows() + display_cursor_row as u16;
             let prefix_width = state.display_prefix_width();
             let cursor_col = prefix_width + cursor_pos.col;
             cli_terminal::set_prompt_cursor(
@@ -327,7 +365,7 @@ impl PromptBox {
         let border_indicator = self.input_scroll_indicator(hidden_above, hidden_below);
 
         // Calculate final heights based on capped content display.
-        let display_height = (rows_to_display + 2) as u16; // top border + visible rows + bottom border
+        let display_height = rows_to_display as u16 + self.border_rows(); // borders + visible rows
         let status_row_offset = display_height;
         let actual_total_height = display_height + menu_height + 1;
 
@@ -374,7 +412,7 @@ impl PromptBox {
                 .take(rows_to_display)
                 .enumerate()
             {
-                let term_row = start_row + 1 + display_idx as u16;
+                let term_row = start_row + self.top_rows() + display_idx as u16;
                 queue!(
                     stdout,
                     cursor::MoveTo(0, term_row),
@@ -390,16 +428,20 @@ impl PromptBox {
                 write!(stdout, "{}", text)?;
             }
 
-            let input_bottom_row = start_row + 1 + rows_to_display as u16;
+            let input_bottom_row = start_row + self.top_rows() + rows_to_display as u16;
             let hint_text = if self.should_show_exit_hint(state, show_exit_hint) {
                 Some(EXIT_HINT_TEXT)
             } else if self.should_show_welcome_hint(state) {
-                Some(WELCOME_HINT_TEXT)
+                Some(if self.minimal {
+                    MINIMAL_WELCOME_HINT_TEXT
+                } else {
+                    WELCOME_HINT_TEXT
+                })
             } else {
                 None
             };
             if let Some(hint_text) = hint_text {
-                let hint_row = start_row + 1;
+                let hint_row = start_row + self.top_rows();
                 queue!(
                     stdout,
                     cursor::MoveTo(0, hint_row),
@@ -419,7 +461,7 @@ impl PromptBox {
             self.draw_bottom_border_line(stdout, input_bottom_row, border_indicator.as_deref())?;
 
             // Draw prompt status line below the bottom border
-            let status_row = input_bottom_row + 1;
+            let status_row = input_bottom_row + self.bottom_rows();
             self.draw_prompt_status_line(stdout, status_row)?;
 
             // Draw active menu (slash or completion) BELOW the status line.
@@ -429,7 +471,7 @@ impl PromptBox {
             // prompt buffer so it remains visible even when the user scrolls
             // terminal scrollback.
             let display_cursor_row = cursor_pos.row.saturating_sub(viewport_start);
-            let cursor_row = start_row + 1 + display_cursor_row as u16;
+            let cursor_row = start_row + self.top_rows() + display_cursor_row as u16;
             let prefix_width = state.display_prefix_width();
             let cursor_col = prefix_width + cursor_pos.col;
             queue!(
@@ -474,7 +516,7 @@ impl PromptBox {
         let rows_to_display = total_rows.min(max_content_rows);
         let border_indicator = self.input_scroll_indicator(total_rows - rows_to_display, 0);
 
-        let input_height = (rows_to_display + 2) as u16; // top border + visible rows + bottom border
+        let input_height = rows_to_display as u16 + self.border_rows(); // borders + visible rows
         let total_height = input_height + menu_height + 1;
         let status_row_offset = input_height;
 
@@ -501,7 +543,7 @@ impl PromptBox {
 
             // Draw wrapped rows (capped)
             for (display_idx, row) in wrapped_rows.iter().take(rows_to_display).enumerate() {
-                let term_row = start_row + 1 + display_idx as u16;
+                let term_row = start_row + self.top_rows() + display_idx as u16;
                 queue!(
                     stdout,
                     cursor::MoveTo(0, term_row),
@@ -516,11 +558,11 @@ impl PromptBox {
                 write!(stdout, "{}", text)?;
             }
 
-            let input_bottom_row = start_row + 1 + rows_to_display as u16;
+            let input_bottom_row = start_row + self.top_rows() + rows_to_display as u16;
             self.draw_bottom_border_line(stdout, input_bottom_row, border_indicator.as_deref())?;
 
             // Draw prompt status line below the bottom border
-            let status_row = input_bottom_row + 1;
+            let status_row = input_bottom_row + self.bottom_rows();
             self.draw_prompt_status_line(stdout, status_row)?;
 
             // Draw model menu BELOW the status line
@@ -569,7 +611,7 @@ impl PromptBox {
         let rows_to_display = total_rows.min(max_content_rows);
         let border_indicator = self.input_scroll_indicator(total_rows - rows_to_display, 0);
 
-        let input_height = (rows_to_display + 2) as u16; // top border + visible rows + bottom border
+        let input_height = rows_to_display as u16 + self.border_rows(); // borders + visible rows
         let total_height = input_height + menu_height + 1;
         let status_row_offset = input_height;
 
@@ -596,7 +638,7 @@ impl PromptBox {
 
             // Draw wrapped rows (capped)
             for (display_idx, row) in wrapped_rows.iter().take(rows_to_display).enumerate() {
-                let term_row = start_row + 1 + display_idx as u16;
+                let term_row = start_row + self.top_rows() + display_idx as u16;
                 queue!(
                     stdout,
                     cursor::MoveTo(0, term_row),
@@ -605,11 +647,11 @@ impl PromptBox {
                 write!(stdout, "{}", row.text)?;
             }
 
-            let input_bottom_row = start_row + 1 + rows_to_display as u16;
+            let input_bottom_row = start_row + self.top_rows() + rows_to_display as u16;
             self.draw_bottom_border_line(stdout, input_bottom_row, border_indicator.as_deref())?;
 
             // Draw prompt status line below the bottom border
-            let status_row = input_bottom_row + 1;
+            let status_row = input_bottom_row + self.bottom_rows();
             self.draw_prompt_status_line(stdout, status_row)?;
 
             // Draw history search menu BELOW the status line
@@ -657,7 +699,7 @@ impl PromptBox {
         let rows_to_display = total_rows.min(max_content_rows);
         let border_indicator = self.input_scroll_indicator(total_rows - rows_to_display, 0);
 
-        let input_height = (rows_to_display + 2) as u16; // top border + visible rows + bottom border
+        let input_height = rows_to_display as u16 + self.border_rows(); // borders + visible rows
         let total_height = input_height + menu_height + 1;
         let status_row_offset = input_height;
 
@@ -683,7 +725,7 @@ impl PromptBox {
             self.draw_border_line(stdout, start_row, true)?;
 
             for (i, row) in wrapped_rows.iter().enumerate() {
-                let term_row = start_row + 1 + i as u16;
+                let term_row = start_row + self.top_rows() + i as u16;
                 queue!(
                     stdout,
                     cursor::MoveTo(0, term_row),
@@ -692,11 +734,11 @@ impl PromptBox {
                 write!(stdout, "{}", row.text)?;
             }
 
-            let input_bottom_row = start_row + 1 + wrapped_rows.len() as u16;
+            let input_bottom_row = start_row + self.top_rows() + wrapped_rows.len() as u16;
             self.draw_bottom_border_line(stdout, input_bottom_row, border_indicator.as_deref())?;
 
             // Draw prompt status line below the bottom border
-            let status_row = input_bottom_row + 1;
+            let status_row = input_bottom_row + self.bottom_rows();
             self.draw_prompt_status_line(stdout, status_row)?;
 
             // Draw the sessions menu BELOW the status line
@@ -743,7 +785,7 @@ impl PromptBox {
         let rows_to_display = total_rows.min(max_content_rows);
         let border_indicator = self.input_scroll_indicator(total_rows - rows_to_display, 0);
 
-        let input_height = (rows_to_display + 2) as u16; // top border + visible rows + bottom border
+        let input_height = rows_to_display as u16 + self.border_rows(); // borders + visible rows
         let total_height = input_height + menu_height + 1;
         let status_row_offset = input_height;
 
@@ -770,7 +812,7 @@ impl PromptBox {
 
             // Draw wrapped rows (capped)
             for (display_idx, row) in wrapped_rows.iter().take(rows_to_display).enumerate() {
-                let term_row = start_row + 1 + display_idx as u16;
+                let term_row = start_row + self.top_rows() + display_idx as u16;
                 queue!(
                     stdout,
                     cursor::MoveTo(0, term_row),
@@ -779,11 +821,11 @@ impl PromptBox {
                 write!(stdout, "{}", row.text)?;
             }
 
-            let input_bottom_row = start_row + 1 + rows_to_display as u16;
+            let input_bottom_row = start_row + self.top_rows() + rows_to_display as u16;
             self.draw_bottom_border_line(stdout, input_bottom_row, border_indicator.as_deref())?;
 
             // Draw prompt status line below the bottom border
-            let status_row = input_bottom_row + 1;
+            let status_row = input_bottom_row + self.bottom_rows();
             self.draw_prompt_status_line(stdout, status_row)?;
 
             // Draw settings menu BELOW the status line
@@ -832,7 +874,7 @@ impl PromptBox {
         let rows_to_display = total_rows.min(max_content_rows);
         let border_indicator = self.input_scroll_indicator(total_rows - rows_to_display, 0);
 
-        let input_height = (rows_to_display + 2) as u16; // top border + visible rows + bottom border
+        let input_height = rows_to_display as u16 + self.border_rows(); // borders + visible rows
         let total_height = input_height + menu_height + 1;
         let status_row_offset = input_height;
 
@@ -859,7 +901,7 @@ impl PromptBox {
 
             // Draw wrapped rows (capped)
             for (display_idx, row) in wrapped_rows.iter().take(rows_to_display).enumerate() {
-                let term_row = start_row + 1 + display_idx as u16;
+                let term_row = start_row + self.top_rows() + display_idx as u16;
                 queue!(
                     stdout,
                     cursor::MoveTo(0, term_row),
@@ -868,11 +910,11 @@ impl PromptBox {
                 write!(stdout, "{}", row.text)?;
             }
 
-            let input_bottom_row = start_row + 1 + rows_to_display as u16;
+            let input_bottom_row = start_row + self.top_rows() + rows_to_display as u16;
             self.draw_bottom_border_line(stdout, input_bottom_row, border_indicator.as_deref())?;
 
             // Draw prompt status line below the bottom border
-            let status_row = input_bottom_row + 1;
+            let status_row = input_bottom_row + self.bottom_rows();
             self.draw_prompt_status_line(stdout, status_row)?;
 
             // Draw MCP menu BELOW the status line
@@ -921,7 +963,7 @@ impl PromptBox {
         let rows_to_display = total_rows.min(max_content_rows);
         let border_indicator = self.input_scroll_indicator(total_rows - rows_to_display, 0);
 
-        let input_height = (rows_to_display + 2) as u16; // top border + visible rows + bottom border
+        let input_height = rows_to_display as u16 + self.border_rows(); // borders + visible rows
         let total_height = input_height + menu_height + 1;
         let status_row_offset = input_height;
 
@@ -948,7 +990,7 @@ impl PromptBox {
 
             // Draw wrapped rows (capped)
             for (display_idx, row) in wrapped_rows.iter().take(rows_to_display).enumerate() {
-                let term_row = start_row + 1 + display_idx as u16;
+                let term_row = start_row + self.top_rows() + display_idx as u16;
                 queue!(
                     stdout,
                     cursor::MoveTo(0, term_row),
@@ -957,11 +999,11 @@ impl PromptBox {
                 write!(stdout, "{}", row.text)?;
             }
 
-            let input_bottom_row = start_row + 1 + rows_to_display as u16;
+            let input_bottom_row = start_row + self.top_rows() + rows_to_display as u16;
             self.draw_bottom_border_line(stdout, input_bottom_row, border_indicator.as_deref())?;
 
             // Draw prompt status line below the bottom border
-            let status_row = input_bottom_row + 1;
+            let status_row = input_bottom_row + self.bottom_rows();
             self.draw_prompt_status_line(stdout, status_row)?;
 
             // Draw tools menu BELOW the status line
@@ -1017,7 +1059,7 @@ impl PromptBox {
         let hidden_below = total_rows.saturating_sub(viewport_start + rows_to_display);
         let border_indicator = self.input_scroll_indicator(hidden_above, hidden_below);
 
-        let display_height = (rows_to_display + 2) as u16; // top border + visible rows + bottom border
+        let display_height = rows_to_display as u16 + self.border_rows(); // borders + visible rows
         let status_row_offset = pending_extra + display_height;
         let total_height = pending_extra + display_height + menu_height + 1;
 
@@ -1060,9 +1102,18 @@ impl PromptBox {
                     queue!(stdout, SetForegroundColor(Color::DarkYellow))?;
                     write!(stdout, "⧖ ")?;
 
+                    // Per-prompt model override in cyan
+                    let model_label = prompt
+                        .model
+                        .as_ref()
+                        .map(|m| format!("@{} ", m.short_display()))
+                        .unwrap_or_default();
+                    queue!(stdout, SetForegroundColor(Color::Cyan))?;
+                    write!(stdout, "{}", model_label)?;
+
                     // Text in white on grey background (truncated to fit)
                     queue!(stdout, SetForegroundColor(Color::White))?;
-                    let max_text_width = self.width.saturating_sub(2);
+                    let max_text_width = self.width.saturating_sub(2 + display_width(&model_label));
                     let text = prompt.input.replace('\n', " ⏎ ");
                     let display_text = if display_width(&text) > max_text_width {
                         truncate_to_width(&text, max_text_width.saturating_sub(1))
@@ -1070,7 +1121,9 @@ impl PromptBox {
                         text
                     };
                     // Calculate padding to fill the rest of the line with background
-                    let content_width = 2 + display_width(&display_text); // "⧖ " + text
+                    // "⧖ " + model + text
+                    let content_width =
+                        2 + display_width(&model_label) + display_width(&display_text);
                     let padding = self.width.saturating_sub(content_width);
                     write!(stdout, "{}{:padding$}", display_text, "", padding = padding)?;
                     queue!(stdout, ResetColor)?;
@@ -1092,7 +1145,7 @@ impl PromptBox {
                 .take(rows_to_display)
                 .enumerate()
             {
-                let term_row = current_row + 1 + display_idx as u16;
+                let term_row = current_row + self.top_rows() + display_idx as u16;
                 queue!(
                     stdout,
                     cursor::MoveTo(0, term_row),
@@ -1108,11 +1161,11 @@ impl PromptBox {
                 write!(stdout, "{}", text)?;
             }
 
-            let input_bottom_row = current_row + 1 + rows_to_display as u16;
+            let input_bottom_row = current_row + self.top_rows() + rows_to_display as u16;
             self.draw_bottom_border_line(stdout, input_bottom_row, border_indicator.as_deref())?;
 
             // Draw prompt status line below the bottom border
-            let status_row = input_bottom_row + 1;
+            let status_row = input_bottom_row + self.bottom_rows();
             self.draw_prompt_status_line(stdout, status_row)?;
 
             // Draw active menu (slash or completion) BELOW the status line.
@@ -1122,7 +1175,7 @@ impl PromptBox {
             // prompt buffer so it remains visible even when the user scrolls
             // terminal scrollback.
             let display_cursor_row = cursor_pos.row.saturating_sub(viewport_start);
-            let cursor_row = current_row + 1 + display_cursor_row as u16;
+            let cursor_row = current_row + self.top_rows() + display_cursor_row as u16;
             let prefix_width = state.display_prefix_width();
             let cursor_col = prefix_width + cursor_pos.col;
             queue!(
@@ -1211,7 +1264,7 @@ impl PromptBox {
             let _hidden_above = viewport_start;
             let _hidden_below = total_rows.saturating_sub(viewport_start + rows_to_display);
 
-            let display_height = (rows_to_display + 2) as u16;
+            let display_height = rows_to_display as u16 + self.border_rows();
             let input_height = display_height;
 
             input_height
@@ -1341,8 +1394,8 @@ impl PromptBox {
     /// Redraw history from scratch (for the /redraw command).
     /// Clears the output area and redraws from saved history, keeping the prompt intact.
     pub(super) fn redraw_history(&mut self) -> io::Result<()> {
-        // Minimum prompt height: top border + 1 input row + bottom border + status line = 4
-        let prompt_height: u16 = 4;
+        // Minimum prompt height: borders + 1 input row + status line
+        let prompt_height: u16 = self.border_rows() + 2;
 
         // Hide the prompt temporarily
         self.hide()?;
@@ -1399,7 +1452,9 @@ impl PromptBox {
         // - bottom border (1)
         // - status line (1)
         // - menu_height rows (optional)
-        let non_content_rows: u16 = pending_extra.saturating_add(menu_height).saturating_add(3);
+        let non_content_rows: u16 = pending_extra
+            .saturating_add(menu_height)
+            .saturating_add(1 + self.border_rows());
 
         self.max_prompt_height()
             .saturating_sub(non_content_rows)
@@ -1573,15 +1628,18 @@ impl PromptBox {
     }
 
     fn draw_border_line(&self, stdout: &mut io::Stdout, row: u16, is_top: bool) -> io::Result<()> {
+        if self.minimal {
+            return Ok(());
+        }
         queue!(
             stdout,
             cursor::MoveTo(0, row),
             terminal::Clear(ClearType::CurrentLine),
-            SetForegroundColor(BORDER_COLOR)
+            SetForegroundColor(border_color())
         )?;
 
         if is_top {
-            // Build top-right indicators in order: security, MCP, LSP.
+            // Build top-right indicators in order: cost, security, MCP, LSP.
             // Security is intentionally before MCP/LSP so it appears to their left.
             let (security_text, security_color) = if self.status.security.read_only {
                 ("RO".to_string(), Color::Yellow)
@@ -1591,7 +1649,16 @@ impl PromptBox {
                 ("YOLO".to_string(), Color::Red)
             };
 
-            let mut indicators: Vec<(String, Color)> = vec![(security_text, security_color)];
+            let mut indicators: Vec<(String, Color)> = Vec::new();
+            if let Some((cost, over_limit)) = self.cost {
+                let color = if over_limit {
+                    Color::Red
+                } else {
+                    Color::Yellow
+                };
+                indicators.push((crate::usage::format_cost(cost), color));
+            }
+            indicators.push((security_text, security_color));
             if self.status.mcp_server_count > 0 {
                 indicators.push((
                     format!("MCP: {}", self.status.mcp_server_count),
@@ -1618,7 +1685,7 @@ impl PromptBox {
                 write!(stdout, "[")?;
                 queue!(stdout, SetForegroundColor(color))?;
                 write!(stdout, "{}", text)?;
-                queue!(stdout, SetForegroundColor(BORDER_COLOR))?;
+                queue!(stdout, SetForegroundColor(border_color()))?;
                 write!(stdout, "]─")?;
             }
         } else {
@@ -1635,11 +1702,14 @@ impl PromptBox {
         row: u16,
         indicator: Option<&str>,
     ) -> io::Result<()> {
+        if self.minimal {
+            return Ok(());
+        }
         queue!(
             stdout,
             cursor::MoveTo(0, row),
             terminal::Clear(ClearType::CurrentLine),
-            SetForegroundColor(BORDER_COLOR)
+            SetForegroundColor(border_color())
         )?;
 
         let Some(indicator) = indicator else {
@@ -1688,6 +1758,15 @@ impl PromptBox {
             terminal::Clear(ClearType::CurrentLine),
         )?;
 
+        if let Some(line) = &self.custom_status {
+            write!(
+                stdout,
+                "{}",
+                super::statusline::fit_to_width(line, self.width)
+            )?;
+            return Ok(());
+        }
+
         let width = self.width;
         let provider = &self.status.provider;
         let model = &self.status.model;
@@ -1728,8 +1807,21 @@ impl PromptBox {
             .map(|(s, _)| display_width(s))
             .unwrap_or(0);
 
+        // Without the top border, show a security mode other than the default here
+        let security = if !self.minimal {
+            None
+        } else if self.status.security.read_only {
+            Some(("RO", Color::Yellow))
+        } else if !self.status.security.sandbox_enabled {
+            Some(("YOLO", Color::Red))
+        } else {
+            None
+        };
+        let security_width = security.map(|(text, _)| text.len() + 1).unwrap_or(0);
+
         // Fixed: "provider/model#suffix " (the slash and trailing space)
-        let fixed_left = provider_width + 1 + model_width + thinking_suffix_width + 1;
+        let fixed_left =
+            provider_width + 1 + model_width + thinking_suffix_width + 1 + security_width;
 
         // Truncate cwd if needed.
         let cwd_display = if fixed_left + cwd_width > width {
@@ -1758,6 +1850,11 @@ impl PromptBox {
         queue!(stdout, ResetColor)?;
         write!(stdout, " ")?;
 
+        if let Some((text, color)) = security {
+            queue!(stdout, SetForegroundColor(color))?;
+            write!(stdout, "{} ", text)?;
+        }
+
         queue!(stdout, SetForegroundColor(Color::Blue))?;
         write!(stdout, "{}", cwd_display)?;
 
@@ -1788,7 +1885,7 @@ fn draw_active_menu(
 }
 
 /// Truncate a string to fit within a display width, adding ellipsis.
-fn truncate_to_width(s: &str, max_width: usize) -> String {
+pub(super) fn truncate_to_width(s: &str, max_width: usize) -> String {
     use unicode_width::UnicodeWidthChar;
 
     let mut result = String::new();
@@ -0,0 +1,299 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Remote control of a running interactive session.
+//!
+//! Each interactive henri listens on `~/.cache/henri/instances/<pid>.sock`.
+//! `henri send <prompt>` submits a prompt as if it was typed, and `henri
+//! interrupt` stops the running turn, so editors and scripts can drive the
+//! session the user has open. `henri mirror` watches what the session shows.
+//! The protocol is one JSON object per line.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Request {
+    Hello,
+    Send {
+        text: String,
+    },
+    Interrupt,
+    /// Stream what the session shows, as `Event` responses, until the
+    /// connection closes
+    Watch,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Response {
+    Hello { pid: u32, cwd: String },
+    Ok { message: String },
+    Error { message: String },
+    Event { event: super::mirror::MirrorEvent },
+}
+
+/// A command for the event loop.
+#[derive(Debug)]
+pub(crate) enum RemoteCommand {
+    /// Submit a prompt once the input is free
+    Send(String),
+    /// Stop the running turn
+    Interrupt,
+}
+
+fn instances_dir() -> PathBuf {
+    dirs::home_dir()
+        .map(|home| home.join(".cache").join("henri").join("instances"))
+        .unwrap_or_else(|| PathBuf::from(".cache/henri/instances"))
+}
+
+/// The socket of the running session. Removed when dropped.
+pub(crate) struct RemoteControl {
+    path: PathBuf,
+    commands: mpsc::UnboundedReceiver<RemoteCommand>,
+}
+
+impl RemoteControl {
+    /// Listen for commands for the session in `working_dir`.
+    pub(crate) fn start(working_dir: &Path) -> std::io::Result<Self> {
+        let dir = instances_dir();
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.sock", std::process::id()));
+        // Left behind by an earlier process with the same pid
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)?;
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+        }
+
+        let (tx, commands) = mpsc::unbounded_channel();
+        let cwd = working_dir.display().to_string();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, tx.clone(), cwd.clone()));
+            }
+        });
+        Ok(Self { path, commands })
+    }
+
+    pub(crate) fn try_recv(&mut self) -> Option<RemoteCommand> {
+        self.commands.try_recv().ok()
+    }
+}
+
+impl Drop for RemoteControl {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+async fn serve(stream: UnixStream, commands: mpsc::UnboundedSender<RemoteCommand>, cwd: String) {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Hello) => Response::Hello {
+                pid: std::process::id(),
+                cwd: cwd.clone(),
+            },
+            Ok(Request::Send { text }) if text.trim().is_empty() => Response::Error {
+                message: "Empty prompt".to_string(),
+            },
+            Ok(Request::Send { text }) => {
+                let _ = commands.send(RemoteCommand::Send(text));
+                Response::Ok {
+                    message: "Prompt sent.".to_string(),
+                }
+            }
+            Ok(Request::Interrupt) => {
+                let _ = commands.send(RemoteCommand::Interrupt);
+                Response::Ok {
+                    message: "Interrupt sent.".to_string(),
+                }
+            }
+            Ok(Request::Watch) => {
+                stream_events(&mut write).await;
+                break;
+            }
+            Err(e) => Response::Error {
+                message: format!("Invalid request: {}", e),
+            },
+        };
+        let Ok(mut json) = serde_json::to_string(&response) else {
+            break;
+        };
+        json.push('\n');
+        if write.write_all(json.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Write what the session shows to a watcher until it disconnects.
+async fn stream_events(write: &mut tokio::net::unix::OwnedWriteHalf) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut events = super::mirror::watch();
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => super::mirror::MirrorEvent::Warning {
+                message: format!("[{} events missed]", missed),
+            },
+            Err(RecvError::Closed) => break,
+        };
+        let Ok(mut json) = serde_json::to_string(&Response::Event { event }) else {
+            continue;
+        };
+        json.push('\n');
+        if write.write_all(json.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Send one request to the socket at `path` and wait for the response.
+async fn round_trip(path: &Path, request: &Request) -> std::io::Result<Response> {
+    let stream = UnixStream::connect(path).await?;
+    let (read, mut write) = stream.into_split();
+    let mut json = serde_json::to_string(request).map_err(std::io::Error::other)?;
+    json.push('\n');
+    write.write_all(json.as_bytes()).await?;
+    let line = BufReader::new(read)
+        .lines()
+        .next_line()
+        .await?
+        .ok_or_else(|| std::io::Error::other("Connection closed without a response"))?;
+    serde_json::from_str(&line).map_err(std::io::Error::other)
+}
+
+/// Find the session to control: the one with `pid`, else the one running in
+/// `working_dir`, else the only one running.
+async fn find_instance(working_dir: &Path, pid: Option<u32>) -> std::io::Result<PathBuf> {
+    let mut instances = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(instances_dir()) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "sock") {
+                continue;
+            }
+            match round_trip(&path, &Request::Hello).await {
+                Ok(Response::Hello { pid, cwd }) => instances.push((path, pid, cwd)),
+                Ok(_) => {}
+                // No one is listening: a session that didn't exit cleanly
+                Err(_) => {
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+        }
+    }
+
+    let working_dir = working_dir.display().to_string();
+    let found = match pid {
+        Some(pid) => instances.iter().find(|(_, p, _)| *p == pid),
+        None => {
+            let here: Vec<_> = instances
+                .iter()
+                .filter(|(_, _, cwd)| *cwd == working_dir)
+                .collect();
+            match (here.as_slice(), instances.as_slice()) {
+                ([one], _) => Some(*one),
+                ([], [only]) => Some(only),
+                _ => None,
+            }
+        }
+    };
+    if let Some((path, _, _)) = found {
+        return Ok(path.clone());
+    }
+
+    let message = if instances.is_empty() {
+        "No interactive henri session is running.".to_string()
+    } else {
+        let list: Vec<String> = instances
+            .iter()
+            .map(|(_, pid, cwd)| format!("  {}  {}", pid, cwd))
+            .collect();
+        format!("Choose a session with --pid:\n{}", list.join("\n"))
+    };
+    Err(std::io::Error::new(std::io::ErrorKind::NotFound, message))
+}
+
+/// Watch the running session, receiving what it shows until it exits.
+pub(crate) async fn watch(
+    pid: Option<u32>,
+) -> std::io::Result<mpsc::Receiver<super::mirror::MirrorEvent>> {
+    let working_dir = std::env::current_dir()?;
+    let path = find_instance(&working_dir, pid).await?;
+    let stream = UnixStream::connect(path).await?;
+    let (read, mut write) = stream.into_split();
+    let mut json = serde_json::to_string(&Request::Watch).map_err(std::io::Error::other)?;
+    json.push('\n');
+    write.write_all(json.as_bytes()).await?;
+
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        // Closing the write half would end the watch
+        let _write = write;
+        let mut lines = BufReader::new(read).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Ok(Response::Event { event }) = serde_json::from_str(&line)
+                && tx.send(event).await.is_err()
+            {
+                break;
+            }
+        }
+    });
+    Ok(rx)
+}
+
+/// Send `request` to a running session and print its reply.
+pub(crate) async fn send(request: Request, pid: Option<u32>) -> std::io::Result<()> {
+    let working_dir = std::env::current_dir()?;
+    let path = find_instance(&working_dir, pid).await?;
+    match round_trip(&path, &request).await? {
+        Response::Ok { message } => {
+            eprintln!("{}", message);
+            Ok(())
+        }
+        Response::Error { message } => Err(std::io::Error::other(message)),
+        Response::Hello { .. } | Response::Event { .. } => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_serve() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            serve(stream, tx, "/work".to_string()).await;
+        });
+
+        let response = round_trip(
+            &path,
+            &Request::Send {
+                text: "run the tests".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        assert!(matches!(response, Response::Ok { .. }));
+        assert!(
+            matches!(rx.recv().await, Some(RemoteCommand::Send(text)) if text == "run the tests")
+        );
+    }
+}
@@ -11,63 +11,84 @@ use std::borrow::Cow;
 use colored::{Color, Colorize};
 use unicode_width::UnicodeWidthChar;
 
+use super::capabilities::{self, ColorDepth};
+use super::escapes::sanitize;
 use super::history::{HistoryEvent, ImageMeta};
 use super::markdown::{align_markdown_tables, render_markdown_line};
+use super::math::render_math;
+#[cfg(feature = "images")]
 use crate::cli::image_preview;
 use crate::cli::spacing::{LastBlock, block_for_event, needs_blank_line_before};
 use crate::syntax;
 
 // Shared color constants for consistent styling
 
-// ANSI background for user prompt lines (dark grey) using truecolor.
+// ANSI background for user prompt lines (dark grey).
 //
 // We use this to fill the remainder of a prompt line using `\x1b[K`
 // (erase-to-end-of-line) without printing trailing spaces. This avoids terminals
-// auto-wrapping when the last column is filled.
-pub(super) const BG_GREY_ANSI: &str = "\x1b[48;2;48;48;48m";
+// auto-wrapping when the last column is filled. Terminals with only the basic
+// colors get no background, as none of them is a dark enough grey.
+pub(super) fn bg_grey_ansi() -> &'static str {
+    match capabilities::get().color_depth {
+        ColorDepth::TrueColor => "\x1b[48;2;48;48;48m",
+        ColorDepth::Ansi256 => "\x1b[48;5;236m",
+        ColorDepth::Basic | ColorDepth::None => "",
+    }
+}
 pub(super) const BG_DARK_GREEN: Color = Color::TrueColor { r: 0, g: 20, b: 0 };
 pub(super) const BG_DARK_RED: Color = Color::TrueColor { r: 20, g: 0, b: 0 };
 
 /// Render a single history event to styled text.
 ///
 /// This renderer intentionally avoids inserting blank lines between events.
+/// Text from the model and tools is passed through [`sanitize`] first.
 pub(crate) fn render_event(event: &HistoryEvent, width: usize) -> String {
     match event {
         HistoryEvent::UserPrompt { text, images } => render_user_prompt(text, images, width),
         HistoryEvent::AssistantText { text, is_streaming } => {
-            render_assistant_text(text, *is_streaming, width)
+            render_assistant_text(&sanitize(text), *is_streaming, width)
         }
         HistoryEvent::Thinking { text, is_streaming } => {
-            render_thinking(text, *is_streaming, width)
+            render_thinking(&sanitize(text), *is_streaming, width)
         }
         // No output for block boundaries.
         HistoryEvent::ThinkingEnd | HistoryEvent::ResponseEnd => String::new(),
         HistoryEvent::ToolStart | HistoryEvent::ToolEnd => String::new(),
-        HistoryEvent::ToolUse { description } => render_tool_use(description),
+        HistoryEvent::ToolUse { description } => render_tool_use(&sanitize(description)),
         HistoryEvent::ToolResult {
             is_error,
             output,
             summary,
-        } => render_tool_result(*is_error, output, summary.as_deref()),
+        } => render_tool_result(
+            *is_error,
+            output,
+            summary.as_deref().map(sanitize).as_deref(),
+        ),
         HistoryEvent::ToolOutput {
             text, total_lines, ..
-        } => render_tool_output(text, *total_lines, width),
+        } => render_tool_output(&sanitize(text), *total_lines, width),
         HistoryEvent::FileReadOutput {
             filename,
             text,
             total_lines,
             ..
-        } => render_file_read_output(filename, text, *total_lines, width),
+        } => render_file_read_output(&sanitize(filename), &sanitize(text), *total_lines, width),
         HistoryEvent::ImagePreview { data, mime_type } => render_image_preview(data, mime_type),
-        HistoryEvent::Error(msg) => render_error(msg),
-        HistoryEvent::Warning(msg) => render_warning(msg),
-        HistoryEvent::Info(msg) => render_info(msg),
+        HistoryEvent::Error(msg) => render_error(&sanitize(msg)),
+        HistoryEvent::Warning(msg) => render_warning(&sanitize(msg)),
+        HistoryEvent::Info(msg) => render_info(&sanitize(msg)),
         HistoryEvent::FileDiff {
             diff,
             language,
             summary,
-        } => render_file_diff(diff, language.as_deref(), summary.as_deref()),
+        } => render_file_diff(
+            &sanitize(diff),
+            language.as_deref(),
+            summary.as_deref().map(sanitize).as_deref(),
+        ),
         HistoryEvent::AutoCompact { message } => render_auto_compact(message),
+        HistoryEvent::Note(text) => render_note(text),
     }
 }
 
@@ -206,7 +227,7 @@ fn render_user_prompt(text: &str, _images: &[ImageMeta], width: usize) -> String
 
     if text.is_empty() {
         // Empty prompt: still render a single prompt row.
-        output.push_str(BG_GREY_ANSI);
+        output.push_str(bg_grey_ansi());
         output.push_str("\x1b[K\x1b[0m\n");
         return output;
     }
@@ -215,12 +236,12 @@ fn render_user_prompt(text: &str, _images: &[ImageMeta], width: usize) -> String
         let wrapped = wrap_text(line, content_width);
         for wrapped_line in &wrapped {
             // Colorize image markers in the line, restoring grey background after each marker.
-            let styled_line = colorize_image_markers(wrapped_line, Some(BG_GREY_ANSI));
+            let styled_line = colorize_image_markers(wrapped_line, Some(bg_grey_ansi()));
 
             // Full-width grey background without printing trailing spaces.
-            output.push_str(BG_GREY_ANSI);
+            output.push_str(bg_grey_ansi());
             output.push_str(&styled_line);
-            output.push_str(BG_GREY_ANSI);
+            output.push_str(bg_grey_ansi());
             output.push_str("\x1b[K\x1b[0m\n");
         }
     }
@@ -298,13 +319,14 @@ fn render_assistant_text(text: &str, _is_streaming: bool, width: usize) -> Strin
     // Streaming output ignores carriage returns; mirror that here so history
     // redraw/replay matches what was shown live.
     let text = strip_carriage_returns(text);
+    let text = render_math(&text);
 
     // Match live streaming width (`CliListener::terminal_width`) so replay/redraw wraps
     // assistant markdown identically.
     let content_width = width.saturating_sub(2).max(1);
 
     // Align markdown tables if they fit within the width
-    let aligned_text = align_markdown_tables(text.as_ref(), Some(content_width));
+    let aligned_text = align_markdown_tables(&text, Some(content_width));
 
     let mut output = String::new();
     let mut pos = 0;
@@ -443,6 +465,44 @@ fn highlight_code_block(code: &str, language: Option<&str>) -> String {
     result
 }
 
+/// Highlight a code block as a whole and split it into lines, closing the
+/// colors at the end of each line so the lines can be printed separately.
+pub(super) fn highlight_code_lines(code: &str, language: Option<&str>) -> Vec<String> {
+    let spans = syntax::highlight_code(code, language);
+    let mut lines = vec![String::new()];
+    let mut push = |text: &str, color: Option<(u8, u8, u8)>| {
+        for (idx, part) in text.split('\n').enumerate() {
+            if idx > 0 {
+                lines.push(String::new());
+            }
+            if part.is_empty() {
+                continue;
+            }
+            let line = lines.last_mut().unwrap();
+            match color {
+                Some((r, g, b)) => line.push_str(&part.truecolor(r, g, b).to_string()),
+                None => line.push_str(part),
+            }
+        }
+    };
+
+    let mut last_end = 0;
+    for span in spans {
+        if span.start > last_end {
+            push(&code[last_end..span.start], None);
+        }
+        push(
+            &code[span.start..span.end],
+            Some((span.color.r, span.color.g, span.color.b)),
+        );
+        last_end = span.end;
+    }
+    if last_end < code.len() {
+        push(&code[last_end..], None);
+    }
+    lines
+}
+
 /// Render thinking text - dimmed and italic
 fn render_thinking(text: &str, _is_streaming: bool, width: usize) -> String {
     let wrapped = wrap_text(text, width);
@@ -493,7 +553,7 @@ fn render_tool_result(is_error: bool, _output: &str, summary: Option<&str>) -> S
 }
 
 /// Render tool result with context about whether it follows a ToolUse inline.
-fn render_tool_result_with_context(
+pub(super) fn render_tool_result_with_context(
     is_error: bool,
     _output: &str,
     summary: Option<&str>,
@@ -513,6 +573,7 @@ fn render_tool_result_with_context(
 }
 
 /// Render image preview using Kitty placeholders.
+#[cfg(feature = "images")]
 fn render_image_preview(data: &[u8], mime_type: &str) -> String {
     let Some(preview) = image_preview::get_image_preview(data, mime_type) else {
         return String::new();
@@ -532,6 +593,11 @@ fn render_image_preview(data: &[u8], mime_type: &str) -> String {
     output
 }
 
+#[cfg(not(feature = "images"))]
+fn render_image_preview(_data: &[u8], _mime_type: &str) -> String {
+    String::new()
+}
+
 /// Render error message - red styled
 fn render_error(msg: &str) -> String {
     format!("{}\n", msg.red())
@@ -1123,6 +1189,16 @@ fn render_auto_compact(message: &str) -> String {
     format!("{}\n", message.yellow())
 }
 
+/// Render a user note, marked so it stands apart from model output.
+fn render_note(text: &str) -> String {
+    let mut output = String::new();
+    for (i, line) in text.lines().enumerate() {
+        let marker = if i == 0 { "✎ Note:" } else { "       " };
+        output.push_str(&format!("{} {}\n", marker.magenta().bold(), line.magenta()));
+    }
+    output
+}
+
 // ============================================================================
 // Text wrapping utilities
 // ============================================================================
@@ -1514,6 +1590,21 @@ mod tests {
         assert_eq!(result, vec!["a  b"]);
     }
 
+    #[test]
+    #[cfg(feature = "highlight")]
+    fn test_highlight_code_lines_closes_each_line() {
+        enable_colors();
+        let code = "/* a\nb */\nlet x = 1;";
+        let lines = highlight_code_lines(code, Some("rust"));
+        assert_eq!(lines.len(), 3);
+        // The comment spans two lines; each is colored on its own.
+        assert!(lines[1].starts_with("\x1b["));
+        assert!(lines.iter().all(|line| line.ends_with("\x1b[0m")));
+        let lines = highlight_code_lines("plain\n\ntext", None);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "");
+    }
+
     #[test]
     fn test_wrap_text_long_word() {
         let result = wrap_text("supercalifragilisticexpialidocious", 10);
@@ -1572,6 +1663,18 @@ mod tests {
         assert!(result.ends_with('\n'));
     }
 
+    #[test]
+    fn test_render_note() {
+        enable_colors();
+        let result = render_event(
+            &HistoryEvent::Note("went wrong here\nretry later".to_string()),
+            80,
+        );
+        assert!(result.contains("✎ Note:"));
+        assert!(result.contains("went wrong here"));
+        assert_eq!(result.lines().count(), 2);
+    }
+
     #[test]
     fn test_render_tool_result_success() {
         enable_colors();
@@ -1600,6 +1703,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "highlight")]
     fn test_code_block_highlighting() {
         enable_colors();
         let text = r#"Here is some code:
@@ -1627,6 +1731,7 @@ And more text."#;
     }
 
     #[test]
+    #[cfg(feature = "highlight")]
     fn test_multiple_code_blocks() {
         let text = r#"First block:
 ```python
@@ -1671,9 +1776,9 @@ const x = 42;
         let result = render_assistant_text(text, false, 80);
 
         assert!(!result.contains('\r'));
-        assert!(result.contains("-   Markdown table with features"));
-        assert!(result.contains("-   Rust function with proper formatting"));
-        assert!(result.contains("-   Go function with proper formatting"));
+        assert!(result.contains("   Markdown table with features"));
+        assert!(result.contains("   Rust function with proper formatting"));
+        assert!(result.contains("   Go function with proper formatting"));
     }
 
     #[test]
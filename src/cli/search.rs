@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! `/search`: find text in the conversation.
+//!
+//! Output goes to the terminal's own scrollback, which henri can't scroll, so
+//! each match is listed with the turn it's in and the line around it.
+
+use colored::Colorize;
+
+use crate::provider::{ContentBlock, Message, MessageContent, Role};
+
+/// Characters of context shown on each side of a match.
+const CONTEXT_CHARS: usize = 40;
+
+/// Matches listed before the rest are only counted.
+pub(super) const MAX_LISTED: usize = 50;
+
+pub(super) struct SearchMatch {
+    /// 1-based turn number, counting the prompts typed by the user
+    pub turn: usize,
+    pub source: &'static str,
+    /// The line containing the match, shortened around it
+    pub before: String,
+    pub matched: String,
+    pub after: String,
+}
+
+impl SearchMatch {
+    pub(super) fn render(&self) -> String {
+        format!(
+            "  {} {}: {}{}{}",
+            format!("turn {}", self.turn).dimmed(),
+            self.source.cyan(),
+            self.before,
+            self.matched.black().on_yellow(),
+            self.after
+        )
+    }
+}
+
+/// Find every line in the conversation containing `query`, ignoring case.
+pub(super) fn search(messages: &[Message], query: &str) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    if query.is_empty() {
+        return matches;
+    }
+    let mut turn = 0;
+    for message in messages {
+        if message.role == Role::User && !message.is_tool_result_only() {
+            turn += 1;
+        }
+        let source = match message.role {
+            Role::User => "you",
+            Role::Assistant => "assistant",
+            Role::System => continue,
+        };
+        let texts: Vec<(&'static str, &str)> = match &message.content {
+            MessageContent::Text(text) => vec![(source, text.as_str())],
+            MessageContent::Blocks(blocks) => blocks
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::Text { text } => Some((source, text.as_str())),
+                    ContentBlock::ToolResult { content, .. } => Some(("tool", content.as_str())),
+                    _ => None,
+                })
+                .collect(),
+        };
+        for (source, text) in texts {
+            for line in text.lines() {
+                if let Some((start, end)) = find_ignore_case(line, query) {
+                    matches.push(SearchMatch {
+                        turn: turn.max(1),
+                        source,
+                        before: shorten_start(&line[..start]),
+                        matched: line[start..end].to_string(),
+                        after: shorten_end(&line[end..]),
+                    });
+                }
+            }
+        }
+    }
+    matches
+}
+
+/// Byte range of the first case-insensitive occurrence of `needle`.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<(usize, usize)> {
+    for (start, _) in haystack.char_indices() {
+        let mut rest = haystack[start..].char_indices();
+        let mut end = start;
+        let mut found = true;
+        for expected in needle.chars() {
+            match rest.next() {
+                Some((offset, ch)) if ch.to_lowercase().eq(expected.to_lowercase()) => {
+                    end = start + offset + ch.len_utf8();
+                }
+                _ => {
+                    found = false;
+                    break;
+                }
+            }
+        }
+        if found {
+            return Some((start, end));
+        }
+    }
+    None
+}
+
+fn shorten_start(text: &str) -> String {
+    let text = text.trim_start();
+    let count = text.chars().count();
+    if count <= CONTEXT_CHARS {
+        return text.to_string();
+    }
+    format!(
+        "…{}",
+        text.chars().skip(count - CONTEXT_CHARS).collect::<String>()
+    )
+}
+
+fn shorten_end(text: &str) -> String {
+    let text = text.trim_end();
+    if text.chars().count() <= CONTEXT_CHARS {
+        return text.to_string();
+    }
+    format!("{}…", text.chars().take(CONTEXT_CHARS).collect::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        let messages = vec![
+            Message::user("Where is the Parser defined?"),
+            Message::assistant_blocks(vec![ContentBlock::Text {
+                text: "Let me look.\nThe parser lives in src/parser.rs".to_string(),
+            }]),
+            Message::user("thanks"),
+        ];
+        let matches = search(&messages, "PARSER");
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].turn, 1);
+        assert_eq!(matches[0].source, "you");
+        assert_eq!(matches[0].before, "Where is the ");
+        assert_eq!(matches[0].matched, "Parser");
+        assert_eq!(matches[1].source, "assistant");
+        assert_eq!(matches[1].after, " lives in src/parser.rs");
+        assert!(search(&messages, "missing").is_empty());
+
+        let long = format!("{}needle", "x".repeat(100));
+        let matches = search(&[Message::user(long)], "needle");
+        assert!(matches[0].before.starts_with('…'));
+        assert_eq!(matches[0].before.chars().count(), CONTEXT_CHARS + 1);
+    }
+}
@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Shell selection for inline `!command` input.
+//!
+//! Commands run through the shell from the `[shell]` config section, falling back to
+//! `$SHELL` and then `sh`. With `interactive = true` the shell is started with `-i` so
+//! aliases and functions from the user's rc files are available.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Mutex;
+
+use crate::config::{ConfigFile, ShellConfig};
+
+static CONFIG: Mutex<Option<ShellConfig>> = Mutex::new(None);
+
+/// Job control warnings printed by `bash -i` when it has no terminal.
+const BASH_JOB_CONTROL_WARNINGS: &[&str] = &[
+    "bash: cannot set terminal process group",
+    "bash: no job control in this shell",
+];
+
+/// Reload the shell settings from config.
+pub(crate) fn reload_config() {
+    let config = ConfigFile::load().map(|c| c.shell).unwrap_or_default();
+    *CONFIG.lock().unwrap() = Some(config);
+}
+
+fn config() -> ShellConfig {
+    CONFIG.lock().unwrap().clone().unwrap_or_default()
+}
+
+/// Pick the shell program: the configured one, then `$SHELL`, then `sh`.
+fn resolve_program(configured: Option<&str>, env_shell: Option<&str>) -> String {
+    [configured, env_shell]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|s| !s.is_empty())
+        .unwrap_or("sh")
+        .to_string()
+}
+
+fn shell_name(program: &str) -> &str {
+    Path::new(program)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(program)
+}
+
+/// Arguments placed before `-c <command>`.
+fn shell_args(program: &str, interactive: bool) -> Vec<&'static str> {
+    match shell_name(program) {
+        // fish loads its config for `-c` already, and other shells may not know `-i`.
+        "bash" | "zsh" | "ksh" | "mksh" | "sh" | "dash" if interactive => vec!["-i"],
+        _ => Vec::new(),
+    }
+}
+
+/// Build the command that runs `cmd` through the user's shell.
+pub(crate) fn command(cmd: &str) -> tokio::process::Command {
+    let config = config();
+    let env_shell = std::env::var("SHELL").ok();
+    let program = resolve_program(config.program.as_deref(), env_shell.as_deref());
+
+    let mut command = tokio::process::Command::new(&program);
+    command
+        .args(shell_args(&program, config.interactive))
+        .arg("-c")
+        .arg(cmd)
+        // Keep the shell off the terminal; Henri owns it while the command runs.
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    // A ZDOTDIR left behind by another tool (editor terminals often point it at a
+    // temporary directory) would make zsh skip the user's own startup files.
+    if shell_name(&program) == "zsh"
+        && let Some(zdotdir) = std::env::var_os("ZDOTDIR")
+        && !Path::new(&zdotdir).is_dir()
+    {
+        command.env_remove("ZDOTDIR");
+    }
+
+    command
+}
+
+/// Whether a stderr line is noise from starting an interactive shell without a terminal.
+pub(crate) fn is_startup_noise(line: &str) -> bool {
+    BASH_JOB_CONTROL_WARNINGS
+        .iter()
+        .any(|warning| line.starts_with(warning))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_program() {
+        assert_eq!(
+            resolve_program(Some("/bin/zsh"), Some("/bin/bash")),
+            "/bin/zsh"
+        );
+        assert_eq!(resolve_program(Some(" "), Some("/bin/bash")), "/bin/bash");
+        assert_eq!(resolve_program(None, None), "sh");
+    }
+
+    #[test]
+    fn test_shell_args() {
+        assert_eq!(shell_args("/usr/bin/zsh", true), vec!["-i"]);
+        assert!(shell_args("/usr/bin/zsh", false).is_empty());
+        assert!(shell_args("/usr/bin/fish", true).is_empty());
+    }
+
+    #[test]
+    fn test_is_startup_noise() {
+        assert!(is_startup_noise(
+            "bash: cannot set terminal process group (-1): Inappropriate ioctl for device"
+        ));
+        assert!(!is_startup_noise("bash: foo: command not found"));
+    }
+}
@@ -16,7 +16,8 @@ use crossterm::style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor
 use crossterm::terminal::{self, ClearType};
 use unicode_width::UnicodeWidthStr;
 
-use super::style::{MENU_BG_NORMAL, MENU_BG_SELECTED, SOFTWARE_CURSOR_OFF, SOFTWARE_CURSOR_ON};
+use super::capabilities;
+use super::style::{SOFTWARE_CURSOR_OFF, SOFTWARE_CURSOR_ON, menu_bg_normal, menu_bg_selected};
 
 use crate::commands::{DynamicSlashCommand, filter_commands};
 use crate::custom_commands::{CustomCommand, load_custom_commands};
@@ -142,7 +143,7 @@ impl SlashMenuState {
             stdout,
             cursor::MoveTo(0, start_row),
             terminal::Clear(ClearType::CurrentLine),
-            SetBackgroundColor(MENU_BG_NORMAL),
+            SetBackgroundColor(menu_bg_normal()),
             SetForegroundColor(Color::Yellow)
         )?;
         let header_text = format!(" Filter: /{}", self.search_query);
@@ -166,31 +167,15 @@ impl SlashMenuState {
             // Colors matching cli/input.rs menu rendering
             let (bg_color, name_color, desc_color) = if is_selected {
                 (
-                    MENU_BG_SELECTED,
-                    Color::Rgb {
-                        r: 137,
-                        g: 180,
-                        b: 250,
-                    },
-                    Color::Rgb {
-                        r: 200,
-                        g: 200,
-                        b: 200,
-                    },
+                    menu_bg_selected(),
+                    capabilities::rgb(137, 180, 250),
+                    capabilities::rgb(200, 200, 200),
                 )
             } else {
                 (
-                    MENU_BG_NORMAL,
-                    Color::Rgb {
-                        r: 120,
-                        g: 120,
-                        b: 120,
-                    },
-                    Color::Rgb {
-                        r: 150,
-                        g: 150,
-                        b: 150,
-                    },
+                    menu_bg_normal(),
+                    capabilities::rgb(120, 120, 120),
+                    capabilities::rgb(150, 150, 150),
                 )
             };
 
@@ -245,7 +230,7 @@ impl SlashMenuState {
                 stdout,
                 cursor::MoveTo(0, indicator_row),
                 terminal::Clear(ClearType::CurrentLine),
-                SetBackgroundColor(MENU_BG_NORMAL),
+                SetBackgroundColor(menu_bg_normal()),
                 SetForegroundColor(Color::DarkGrey)
             )?;
             write!(
@@ -25,10 +25,10 @@ pub(crate) fn toggle_compact_mode() -> bool {
     enabled
 }
 
-/// Reload the compact-mode setting from config.
+/// Reload the compact-mode setting from config. The minimal UI is always compact.
 pub(crate) fn reload_compact_mode() {
     let enabled = crate::config::ConfigFile::load()
-        .map(|c| c.compact_mode)
+        .map(|c| c.compact_mode || c.minimal_ui)
         .unwrap_or(false);
     set_compact_mode(enabled);
 }
@@ -53,9 +53,10 @@ pub(crate) fn block_for_event(event: &HistoryEvent) -> Option<LastBlock> {
         HistoryEvent::UserPrompt { .. } => Some(LastBlock::UserPrompt),
         HistoryEvent::AssistantText { .. } => Some(LastBlock::Text),
         HistoryEvent::Thinking { .. } => Some(LastBlock::Thinking),
-        HistoryEvent::Info(_) | HistoryEvent::Error(_) | HistoryEvent::Warning(_) => {
-            Some(LastBlock::Info)
-        }
+        HistoryEvent::Info(_)
+        | HistoryEvent::Error(_)
+        | HistoryEvent::Warning(_)
+        | HistoryEvent::Note(_) => Some(LastBlock::Info),
         HistoryEvent::ToolUse { .. } => Some(LastBlock::ToolCall),
         HistoryEvent::ToolResult { .. }
         | HistoryEvent::ToolOutput { .. }
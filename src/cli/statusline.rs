@@ -0,0 +1,316 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! User-defined status line.
+//!
+//! When `statusline-command` is configured, the command is run through the shell with a
+//! JSON snapshot of the current state on stdin. The first line it prints replaces the
+//! built-in status line below the prompt box. SGR color sequences are kept; any other
+//! control characters are dropped so the output can't move the cursor.
+//!
+//! The command runs in the background so a slow script or repository never holds up
+//! input: the prompt box shows the last line rendered and is redrawn when a new one
+//! arrives.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use unicode_width::UnicodeWidthChar;
+
+use crate::config::ConfigFile;
+
+/// How long the command may run before the built-in status line is used instead.
+const COMMAND_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long the git branch is reused before it is looked up again.
+const BRANCH_TTL: Duration = Duration::from_secs(5);
+
+static COMMAND: Mutex<Option<String>> = Mutex::new(None);
+
+/// The last line rendered by the command.
+static LINE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Set when `LINE` changed and the prompt box hasn't picked it up yet.
+static UPDATED: AtomicBool = AtomicBool::new(false);
+
+/// The latest render asked for, run once the one in progress finishes.
+static PENDING: Mutex<Option<(String, Snapshot, PathBuf)>> = Mutex::new(None);
+
+/// Whether a background task is rendering.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// The git branch of a directory, and when it was looked up.
+static BRANCH: Mutex<Option<(PathBuf, Option<String>, Instant)>> = Mutex::new(None);
+
+/// Reload the statusline command from config.
+pub(crate) fn reload_command() {
+    let command = ConfigFile::load()
+        .ok()
+        .and_then(|c| c.statusline_command)
+        .filter(|c| !c.trim().is_empty());
+    *COMMAND.lock().unwrap() = command;
+}
+
+pub(crate) fn command() -> Option<String> {
+    COMMAND.lock().unwrap().clone()
+}
+
+/// State passed to the statusline command as JSON.
+#[derive(Debug, Serialize)]
+pub(crate) struct Snapshot {
+    pub provider: String,
+    pub model: String,
+    /// Thinking mode ("off", "high", ...) when the model supports thinking
+    pub thinking: Option<String>,
+    pub cwd: String,
+    pub git_branch: Option<String>,
+    pub read_only: bool,
+    pub sandbox: bool,
+    pub context: ContextSnapshot,
+    pub tokens: Option<TokenSnapshot>,
+    pub cost: CostSnapshot,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ContextSnapshot {
+    pub tokens: Option<u64>,
+    pub limit: Option<u64>,
+    /// Percentage of the context window used
+    pub percent: Option<f64>,
+}
+
+impl ContextSnapshot {
+    pub(crate) fn new(tokens: Option<u64>, limit: Option<u64>) -> Self {
+        let percent = match (tokens, limit) {
+            (Some(tokens), Some(limit)) if limit > 0 => {
+                Some((tokens as f64 / limit as f64 * 1000.0).round() / 10.0)
+            }
+            _ => None,
+        };
+        Self {
+            tokens,
+            limit,
+            percent,
+        }
+    }
+}
+
+/// Total tokens used with the current provider since startup.
+#[derive(Debug, Serialize)]
+pub(crate) struct TokenSnapshot {
+    pub input: u64,
+    pub output: u64,
+}
+
+/// Dollar cost of the session, from the built-in or configured prices.
+#[derive(Debug, Serialize)]
+pub(crate) struct CostSnapshot {
+    pub session: f64,
+    pub turn: f64,
+    /// The configured `max-session-cost`
+    pub limit: Option<f64>,
+}
+
+/// Render the status line for `snapshot` in the background. Renders asked for while
+/// one is running are coalesced into the latest.
+pub(crate) fn request(command: String, snapshot: Snapshot, cwd: &Path) {
+    *PENDING.lock().unwrap() = Some((command, snapshot, cwd.to_path_buf()));
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::spawn(async {
+        loop {
+            let next = PENDING.lock().unwrap().take();
+            let Some((command, mut snapshot, cwd)) = next else {
+                RUNNING.store(false, Ordering::SeqCst);
+                // A request may have arrived after the check above.
+                if PENDING.lock().unwrap().is_none() || RUNNING.swap(true, Ordering::SeqCst) {
+                    break;
+                }
+                continue;
+            };
+            snapshot.git_branch = cached_git_branch(&cwd).await;
+            let line = render(&command, &snapshot, &cwd).await;
+            let mut last = LINE.lock().unwrap();
+            if *last != line {
+                *last = line;
+                UPDATED.store(true, Ordering::SeqCst);
+            }
+        }
+    });
+}
+
+/// The last line rendered, if any.
+pub(crate) fn current() -> Option<String> {
+    LINE.lock().unwrap().clone()
+}
+
+/// Whether a new line was rendered since the last call.
+pub(crate) fn take_updated() -> bool {
+    UPDATED.swap(false, Ordering::SeqCst)
+}
+
+/// The git branch of `dir`, looked up at most every `BRANCH_TTL`.
+async fn cached_git_branch(dir: &Path) -> Option<String> {
+    if let Some((cached_dir, branch, at)) = BRANCH.lock().unwrap().as_ref()
+        && cached_dir == dir
+        && at.elapsed() < BRANCH_TTL
+    {
+        return branch.clone();
+    }
+    let branch = git_branch(dir).await;
+    *BRANCH.lock().unwrap() = Some((dir.to_path_buf(), branch.clone(), Instant::now()));
+    branch
+}
+
+/// Current git branch of `dir`, if it is inside a work tree.
+async fn git_branch(dir: &Path) -> Option<String> {
+    let output = tokio::process::Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(COMMAND_TIMEOUT, output)
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!branch.is_empty()).then_some(branch)
+}
+
+/// Run `command` with `snapshot` on stdin and return the first line of its output.
+pub(crate) async fn render(command: &str, snapshot: &Snapshot, cwd: &Path) -> Option<String> {
+    let json = serde_json::to_string(snapshot).ok()?;
+    let mut child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .ok()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The command may not read its input; ignore a closed pipe.
+        let _ = stdin.write_all(json.as_bytes()).await;
+    }
+
+    let output = tokio::time::timeout(COMMAND_TIMEOUT, child.wait_with_output())
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().find(|l| !l.trim().is_empty())?;
+    Some(super::escapes::sanitize_keeping_colors(line).replace('\t', " "))
+}
+
+/// Truncate a sanitized line to `max_width` visible columns, keeping SGR sequences.
+pub(crate) fn fit_to_width(line: &str, max_width: usize) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut width = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            out.push(c);
+            for c in chars.by_ref() {
+                out.push(c);
+                if c == 'm' {
+                    break;
+                }
+            }
+            continue;
+        }
+        let cw = c.width().unwrap_or(0);
+        if width + cw > max_width {
+            break;
+        }
+        out.push(c);
+        width += cw;
+    }
+    out.push_str("\x1b[0m");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_to_width() {
+        assert_eq!(
+            fit_to_width("\x1b[31mabcdef\x1b[0m", 3),
+            "\x1b[31mabc\x1b[0m"
+        );
+        assert_eq!(fit_to_width("ab", 10), "ab\x1b[0m");
+    }
+
+    #[test]
+    fn test_context_percent() {
+        let context = ContextSnapshot::new(Some(50_000), Some(200_000));
+        assert_eq!(context.percent, Some(25.0));
+        assert_eq!(ContextSnapshot::new(Some(1), None).percent, None);
+    }
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            provider: "claude".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            thinking: None,
+            cwd: "/tmp".to_string(),
+            git_branch: Some("main".to_string()),
+            read_only: false,
+            sandbox: true,
+            context: ContextSnapshot::new(None, None),
+            tokens: None,
+            cost: CostSnapshot {
+                session: 0.0,
+                turn: 0.0,
+                limit: None,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_render_reads_snapshot() {
+        let snapshot = snapshot();
+        let dir = tempfile::tempdir().unwrap();
+        let line = render(
+            r#"grep -o '"git_branch":"[^"]*"'; echo second"#,
+            &snapshot,
+            dir.path(),
+        )
+        .await;
+        assert_eq!(line.as_deref(), Some(r#""git_branch":"main""#));
+
+        assert_eq!(render("exit 1", &snapshot, dir.path()).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_request_renders_in_background() {
+        let dir = tempfile::tempdir().unwrap();
+        request("echo ready".to_string(), snapshot(), dir.path());
+        for _ in 0..200 {
+            if take_updated() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(current().as_deref(), Some("ready"));
+    }
+}
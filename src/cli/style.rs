@@ -3,17 +3,15 @@
 
 use crossterm::style::Color;
 
+use super::capabilities;
+
 pub(super) const SOFTWARE_CURSOR_ON: &str = "\x1b[7m";
 pub(super) const SOFTWARE_CURSOR_OFF: &str = "\x1b[27m";
 
-pub(super) const MENU_BG_NORMAL: Color = Color::Rgb {
-    r: 20,
-    g: 20,
-    b: 20,
-};
+pub(super) fn menu_bg_normal() -> Color {
+    capabilities::rgb(20, 20, 20)
+}
 
-pub(super) const MENU_BG_SELECTED: Color = Color::Rgb {
-    r: 30,
-    g: 30,
-    b: 30,
-};
+pub(super) fn menu_bg_selected() -> Color {
+    capabilities::rgb(30, 30, 30)
+}
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Plain-text copy of assistant output, appended to a file as it streams.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+struct Tee {
+    path: PathBuf,
+    file: File,
+    /// Whether text has been written since the last response separator.
+    in_response: bool,
+    /// Whether the last text written ended with a newline.
+    at_line_start: bool,
+}
+
+static TEE: Mutex<Option<Tee>> = Mutex::new(None);
+
+/// Start appending assistant text to `path`, replacing any active tee.
+pub(crate) fn start(path: &Path) -> io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    *TEE.lock().unwrap() = Some(Tee {
+        path: path.to_path_buf(),
+        file,
+        in_response: false,
+        at_line_start: true,
+    });
+    Ok(())
+}
+
+/// Stop the tee, returning the file it was writing to.
+pub(crate) fn stop() -> Option<PathBuf> {
+    TEE.lock().unwrap().take().map(|tee| tee.path)
+}
+
+/// The file currently being written to, if any.
+pub(crate) fn path() -> Option<PathBuf> {
+    TEE.lock().unwrap().as_ref().map(|tee| tee.path.clone())
+}
+
+/// Append streamed assistant text.
+pub(crate) fn write(text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    let mut guard = TEE.lock().unwrap();
+    if let Some(tee) = guard.as_mut() {
+        let _ = tee.file.write_all(text.as_bytes());
+        tee.in_response = true;
+        tee.at_line_start = text.ends_with('\n');
+    }
+}
+
+/// Mark the end of an assistant response with a blank line.
+pub(crate) fn end_response() {
+    let mut guard = TEE.lock().unwrap();
+    if let Some(tee) = guard.as_mut()
+        && tee.in_response
+    {
+        let separator = if tee.at_line_start { "\n" } else { "\n\n" };
+        let _ = tee.file.write_all(separator.as_bytes());
+        tee.in_response = false;
+        tee.at_line_start = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tee_appends_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tee.txt");
+        std::fs::write(&path, "earlier\n").unwrap();
+
+        start(&path).unwrap();
+        assert_eq!(self::path(), Some(path.clone()));
+        write("Hello, ");
+        write("world");
+        end_response();
+        // An empty response adds no extra separator.
+        end_response();
+        write("Second\n");
+        end_response();
+        assert_eq!(stop(), Some(path.clone()));
+
+        // Nothing is written once stopped.
+        write("ignored");
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "earlier\nHello, world\n\nSecond\n\n"
+        );
+    }
+}
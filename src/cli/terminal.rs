@@ -77,6 +77,8 @@ impl OutputCursor {
 static OUTPUT_CURSOR: Mutex<OutputCursor> = Mutex::new(OutputCursor::new());
 static OUTPUT_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
 static OUTPUT_BUFFERING: AtomicBool = AtomicBool::new(false);
+/// Send text printed above the prompt to stderr, keeping stdout for JSON output.
+static OUTPUT_TO_STDERR: AtomicBool = AtomicBool::new(false);
 
 fn output_lock() -> &'static Mutex<()> {
     OUTPUT_LOCK.get_or_init(|| Mutex::new(()))
@@ -103,6 +105,10 @@ pub(crate) fn is_output_buffering() -> bool {
     OUTPUT_BUFFERING.load(Ordering::SeqCst)
 }
 
+pub(crate) fn set_output_to_stderr(enabled: bool) {
+    OUTPUT_TO_STDERR.store(enabled, Ordering::SeqCst);
+}
+
 fn reset_output_cursor() {
     if let Ok(mut state) = OUTPUT_CURSOR.lock() {
         state.col = 0;
@@ -878,6 +884,73 @@ pub(crate) fn render_tool_viewport(lines: &[String], height: u16, spacer_lines:
     });
 }
 
+/// Replace the last `lines.len()` lines of output above the prompt.
+///
+/// Used to restyle a block once all of it has arrived. Only possible when the
+/// output ends with a newline, every line fits on one row and none of the lines
+/// have scrolled off screen; returns false otherwise, leaving the output as is.
+pub(crate) fn rewrite_output_lines(lines: &[String]) -> bool {
+    use crossterm::SynchronizedUpdate;
+
+    let _guard = lock_output();
+
+    let (start_row, status_active, cursor_pos, visible) = {
+        let state = PROMPT_STATE.lock().unwrap();
+        (
+            state.start_row,
+            state.status_line_active,
+            state.cursor_pos,
+            state.visible,
+        )
+    };
+    if !visible || lines.is_empty() || output_cursor_col() != 0 {
+        return false;
+    }
+
+    let term_width = term_width();
+    if lines
+        .iter()
+        .any(|line| calculate_output_size(0, line, term_width).0 > 0)
+    {
+        return false;
+    }
+
+    let reserved_rows = if status_active {
+        STREAMING_STATUS_LINE_ROWS
+    } else {
+        0
+    };
+    // The row the next output goes to; the lines being replaced are above it.
+    let output_row = start_row.saturating_sub(reserved_rows).saturating_sub(1);
+    let Ok(count) = u16::try_from(lines.len()) else {
+        return false;
+    };
+    if count > output_row {
+        return false;
+    }
+    let top = output_row - count;
+
+    let mut stdout = io::stdout();
+    let _ = stdout.sync_update(|stdout| {
+        use crossterm::queue;
+
+        queue!(stdout, Hide)?;
+        for (idx, line) in lines.iter().enumerate() {
+            queue!(
+                stdout,
+                MoveTo(0, top + idx as u16),
+                Clear(ClearType::CurrentLine)
+            )?;
+            write!(stdout, "{}", line)?;
+        }
+        if let Some((off, col)) = cursor_pos {
+            queue!(stdout, MoveTo(col, start_row + off))?;
+        }
+        io::Result::Ok(())
+    });
+    true
+}
+
 /// Write a complete line, handling prompt visibility.
 /// If prompt is visible, writes above it. Otherwise writes normally.
 pub(crate) fn println_above(text: &str) {
@@ -914,6 +987,13 @@ fn print_above_locked(text: &str) {
         return;
     }
 
+    if OUTPUT_TO_STDERR.load(Ordering::SeqCst) {
+        let mut stderr = io::stderr();
+        let _ = stderr.write_all(text.as_bytes());
+        let _ = stderr.flush();
+        return;
+    }
+
     // When the terminal is in raw mode, `\n` does not reliably return to column 0.
     // Normalize to CRLF so multi-line output renders correctly and our cursor tracking
     // matches what the terminal actually does.
@@ -21,28 +21,106 @@ pub(crate) fn has_claude_oauth_provider() -> bool {
 /// Command identifier for dispatch.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum Command {
+    Attach {
+        path: String,
+    },
     BuildAgentsMd,
     ClaudeCountTokens,
     Clear,
     Compact,
-    Custom { name: String, args: String },
+    Context,
+    Copy {
+        args: String,
+    },
+    Cost,
+    Doctor {
+        topic: String,
+    },
+    Custom {
+        name: String,
+        args: String,
+    },
     DumpPrompt,
-    Echo { text: String },
+    Echo {
+        text: String,
+    },
+    Expand {
+        args: String,
+    },
+    Explain {
+        args: String,
+    },
+    Feedback {
+        args: String,
+    },
+    Fix {
+        instructions: String,
+    },
     Help,
     Lsp,
     Mcp,
+    /// A prompt from an MCP server, named `server:prompt`
+    McpPrompt {
+        name: String,
+        args: String,
+    },
+    Fork {
+        turns: String,
+    },
+    History {
+        args: String,
+    },
+    Memory {
+        args: String,
+    },
     Model,
     Provider,
     Quit,
+    Note {
+        text: String,
+    },
+    PinSession,
     ReadOnly,
+    Recap {
+        args: String,
+    },
+    Rename {
+        title: String,
+    },
+    SplitCommits {
+        instructions: String,
+    },
     ReadWrite,
+    Revert,
+    Retry {
+        model: String,
+    },
+    Rollback {
+        args: String,
+    },
     Yolo,
+    Search {
+        query: String,
+    },
     Sessions,
+    Set {
+        args: String,
+    },
     Settings,
     Skills,
+    Status,
     StartTransactionLogging,
     StopTransactionLogging,
+    Tee {
+        args: String,
+    },
+    Timeline {
+        args: String,
+    },
     Tools,
+    Translate {
+        language: String,
+    },
     Truncate,
     Undo,
     Forget,
@@ -60,12 +138,49 @@ pub(crate) enum Availability {
     ClaudeOAuthConfigured,
 }
 
+/// How Tab completes the arguments of a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ArgCompleter {
+    /// No completion (no arguments, or free text)
+    None,
+    /// One of a fixed set of words, for the first argument
+    Words(&'static [&'static str]),
+    /// File paths, plus a fixed set of words for the first argument
+    Files(&'static [&'static str]),
+    /// A runtime setting, then one of its values
+    Settings,
+}
+
+impl ArgCompleter {
+    /// Fixed words matching `prefix` for the argument following `previous`.
+    pub(crate) fn words(&self, previous: &[&str], prefix: &str) -> Vec<String> {
+        let words = match self {
+            ArgCompleter::Settings => return crate::settings::complete(previous, prefix),
+            ArgCompleter::Words(words) | ArgCompleter::Files(words) if previous.is_empty() => {
+                *words
+            }
+            _ => return Vec::new(),
+        };
+        words
+            .iter()
+            .filter(|w| w.starts_with(prefix))
+            .map(|w| w.to_string())
+            .collect()
+    }
+
+    /// Whether arguments are completed as file paths.
+    pub(crate) fn completes_files(&self) -> bool {
+        matches!(self, ArgCompleter::Files(_))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct SlashCommand {
     pub command: Command,
     pub name: &'static str,
     pub description: &'static str,
     pub availability: Availability,
+    pub args: ArgCompleter,
 }
 
 /// Owned version of SlashCommand for dynamic commands.
@@ -77,41 +192,88 @@ pub(crate) struct DynamicSlashCommand {
 }
 
 pub(crate) const COMMANDS: &[SlashCommand] = &[
+    SlashCommand {
+        command: Command::Attach {
+            path: String::new(),
+        },
+        name: "attach",
+        description: "Attach an image file to the next prompt",
+        availability: Availability::Always,
+        args: ArgCompleter::Files(&[]),
+    },
     SlashCommand {
         command: Command::BuildAgentsMd,
         name: "build-agents-md",
         description: "Generate/update AGENTS.md file for this project",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::ClaudeCountTokens,
         name: "claude-count-tokens",
         description: "Count tokens in current prompt",
         availability: Availability::ClaudeOnly,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Clear,
         name: "clear",
         description: "Clear conversation history",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Clear,
         name: "new",
         description: "Start a new conversation (alias for /clear)",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Compact,
         name: "compact",
         description: "Summarize older messages to reduce context",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Context,
+        name: "context",
+        description: "Show what takes up the context window",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Copy {
+            args: String::new(),
+        },
+        name: "copy",
+        description: "Copy the last response, or a code block from it, to the clipboard (/copy [code [n]])",
+        availability: Availability::Always,
+        args: ArgCompleter::Words(&["code"]),
+    },
+    SlashCommand {
+        command: Command::Cost,
+        name: "cost",
+        description: "Show the token usage and cost of this session",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::DumpPrompt,
         name: "dump-prompt",
         description: "Dump the full API request",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Doctor {
+            topic: String::new(),
+        },
+        name: "doctor",
+        description: "Show detected terminal capabilities and fallbacks",
+        availability: Availability::Always,
+        args: ArgCompleter::Words(&["terminal"]),
     },
     SlashCommand {
         command: Command::Echo {
@@ -120,123 +282,334 @@ pub(crate) const COMMANDS: &[SlashCommand] = &[
         name: "echo",
         description: "Echo text to the output area",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Expand {
+            args: String::new(),
+        },
+        name: "expand",
+        description: "Show the history replaced by the last compaction (/expand summary)",
+        availability: Availability::Always,
+        args: ArgCompleter::Words(&["summary"]),
+    },
+    SlashCommand {
+        command: Command::Explain {
+            args: String::new(),
+        },
+        name: "explain",
+        description: "Explain a file or line range (/explain [depth] <file[:start-end]>)",
+        availability: Availability::Always,
+        args: ArgCompleter::Files(crate::explain::DEPTHS),
+    },
+    SlashCommand {
+        command: Command::Feedback {
+            args: String::new(),
+        },
+        name: "feedback",
+        description: "Rate the last answer (/feedback up|down [note])",
+        availability: Availability::Always,
+        args: ArgCompleter::Words(&["up", "down"]),
+    },
+    SlashCommand {
+        command: Command::Fix {
+            instructions: String::new(),
+        },
+        name: "fix",
+        description: "Ask the model to fix diagnostics in recently edited files",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Fork {
+            turns: String::new(),
+        },
+        name: "fork",
+        description: "Continue in a copy of this session, optionally from an earlier turn (/fork [turn])",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Help,
         name: "help",
         description: "Show available commands",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::History {
+            args: String::new(),
+        },
+        name: "history",
+        description: "Show whether prompts are saved, or clear them (/history clear <all|project|last [n]>)",
+        availability: Availability::Always,
+        args: ArgCompleter::Words(&["clear"]),
     },
     SlashCommand {
         command: Command::Lsp,
         name: "lsp",
         description: "Show LSP server status",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Mcp,
         name: "mcp",
         description: "Manage MCP server connections",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Memory {
+            args: String::new(),
+        },
+        name: "memory",
+        description: "Show the AGENTS.md and other instruction files loaded (/memory [show])",
+        availability: Availability::Always,
+        args: ArgCompleter::Words(&["show"]),
     },
     SlashCommand {
         command: Command::Model,
         name: "model",
         description: "Select a model",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Note {
+            text: String::new(),
+        },
+        name: "note",
+        description: "Add a note to the transcript (not sent to the model)",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::PinSession,
+        name: "pin-session",
+        description: "Pin the current session, or unpin it if pinned",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Provider,
         name: "provider",
         description: "Manage AI providers (add/remove)",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Quit,
         name: "quit",
         description: "Exit the application",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::ReadOnly,
         name: "read-only",
         description: "Switch to Read-Only mode",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::ReadWrite,
         name: "read-write",
         description: "Switch to Read-Write mode (Sandbox enabled)",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Recap {
+            args: String::new(),
+        },
+        name: "recap",
+        description: "Summarize what was done this session (/recap [save] to append to WORKLOG.md)",
+        availability: Availability::Always,
+        args: ArgCompleter::Words(&["save"]),
+    },
+    SlashCommand {
+        command: Command::Rename {
+            title: String::new(),
+        },
+        name: "rename",
+        description: "Set the title of the current session (/rename <title>)",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Retry {
+            model: String::new(),
+        },
+        name: "retry",
+        description: "Resend the last prompt, optionally with another model (/retry [model])",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Revert,
+        name: "revert",
+        description: "Restore files changed by the last turn",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Rollback {
+            args: String::new(),
+        },
+        name: "rollback",
+        description: "Undo the last turn and its file changes (/rollback confirm [git])",
+        availability: Availability::Always,
+        args: ArgCompleter::Words(&["confirm"]),
     },
     SlashCommand {
         command: Command::Yolo,
         name: "yolo",
         description: "Switch to YOLO mode (Sandbox disabled)",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Search {
+            query: String::new(),
+        },
+        name: "search",
+        description: "Find text in the conversation (/search <text>)",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Sessions,
         name: "sessions",
         description: "List and select previous sessions",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Set {
+            args: String::new(),
+        },
+        name: "set",
+        description: "Show or change a setting (e.g. /set seed 42)",
+        availability: Availability::Always,
+        args: ArgCompleter::Settings,
     },
     SlashCommand {
         command: Command::Settings,
         name: "settings",
         description: "Configure application settings",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Skills,
         name: "skills",
         description: "List available skills",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::SplitCommits {
+            instructions: String::new(),
+        },
+        name: "split-commits",
+        description: "Split uncommitted changes into a series of commits",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Status,
+        name: "status",
+        description: "Show model, session, context and server status",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::StartTransactionLogging,
         name: "start-transaction-logging",
         description: "Enable transaction logging to NDJSON file",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::StopTransactionLogging,
         name: "stop-transaction-logging",
         description: "Disable transaction logging",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Tee {
+            args: String::new(),
+        },
+        name: "tee",
+        description: "Append assistant text to a file (/tee <file> or /tee off)",
+        availability: Availability::Always,
+        args: ArgCompleter::Files(&["off"]),
+    },
+    SlashCommand {
+        command: Command::Timeline {
+            args: String::new(),
+        },
+        name: "timeline",
+        description: "Show where the time of the last turn went (/timeline session for every turn)",
+        availability: Availability::Always,
+        args: ArgCompleter::Words(&["session"]),
     },
     SlashCommand {
         command: Command::Tools,
         name: "tools",
         description: "Enable/disable built-in tools",
         availability: Availability::Always,
+        args: ArgCompleter::None,
+    },
+    SlashCommand {
+        command: Command::Translate {
+            language: String::new(),
+        },
+        name: "translate",
+        description: "Translate the last answer into another language",
+        availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Truncate,
         name: "truncate",
         description: "Keep only the last message and clear the rest",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Undo,
         name: "undo",
         description: "Remove the most recent turn (user message and response)",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Forget,
         name: "forget",
         description: "Remove the oldest turn from conversation history",
         availability: Availability::Always,
+        args: ArgCompleter::None,
     },
     SlashCommand {
         command: Command::Usage,
         name: "claude-usage",
-        description: "Show Anthropic rate limits",
+        description: "Show Anthropic rate limits and cache hits",
         availability: Availability::ClaudeOAuthConfigured,
+        args: ArgCompleter::None,
     },
 ];
 
+/// The argument completer of a built-in command.
+pub(crate) fn arg_completer(name: &str) -> ArgCompleter {
+    COMMANDS
+        .iter()
+        .find(|cmd| cmd.name.eq_ignore_ascii_case(name))
+        .map_or(ArgCompleter::None, |cmd| cmd.args)
+}
+
 /// Filter commands based on context.
 pub(crate) fn filter_commands(
     query: &str,
@@ -284,6 +657,19 @@ pub(crate) fn filter_commands(
         }
     }
 
+    for prompt in crate::mcp::manager().prompt_commands() {
+        if prompt.name.to_lowercase().contains(&query) {
+            results.push(DynamicSlashCommand {
+                command: Command::McpPrompt {
+                    name: prompt.name.clone(),
+                    args: String::new(),
+                },
+                name: prompt.name,
+                description: prompt.description,
+            });
+        }
+    }
+
     results
 }
 
@@ -303,10 +689,77 @@ pub(crate) fn parse(
         (input.to_lowercase(), String::new())
     };
 
-    // Handle echo specially since it takes arguments
+    // Handle attach, copy, doctor, echo, expand, explain, feedback, fix, fork, history, memory, note, recap, rename, retry, rollback, search, set, split-commits, tee, timeline and translate specially since they take arguments
+    if cmd_name == "attach" {
+        return Some(Command::Attach { path: args });
+    }
+    if cmd_name == "copy" {
+        return Some(Command::Copy { args });
+    }
+    if cmd_name == "doctor" {
+        return Some(Command::Doctor {
+            topic: args.to_lowercase(),
+        });
+    }
     if cmd_name == "echo" {
         return Some(Command::Echo { text: args });
     }
+    if cmd_name == "expand" {
+        return Some(Command::Expand { args });
+    }
+    if cmd_name == "explain" {
+        return Some(Command::Explain { args });
+    }
+    if cmd_name == "feedback" {
+        return Some(Command::Feedback { args });
+    }
+    if cmd_name == "fix" {
+        return Some(Command::Fix { instructions: args });
+    }
+    if cmd_name == "fork" {
+        return Some(Command::Fork { turns: args });
+    }
+    if cmd_name == "history" {
+        return Some(Command::History { args });
+    }
+    if cmd_name == "memory" {
+        return Some(Command::Memory { args });
+    }
+    if cmd_name == "note" {
+        return Some(Command::Note { text: args });
+    }
+    if cmd_name == "recap" {
+        return Some(Command::Recap { args });
+    }
+    if cmd_name == "rename" {
+        return Some(Command::Rename { title: args });
+    }
+    if cmd_name == "retry" {
+        return Some(Command::Retry { model: args });
+    }
+    if cmd_name == "rollback" {
+        return Some(Command::Rollback {
+            args: args.to_lowercase(),
+        });
+    }
+    if cmd_name == "search" {
+        return Some(Command::Search { query: args });
+    }
+    if cmd_name == "set" {
+        return Some(Command::Set { args });
+    }
+    if cmd_name == "split-commits" {
+        return Some(Command::SplitCommits { instructions: args });
+    }
+    if cmd_name == "tee" {
+        return Some(Command::Tee { args });
+    }
+    if cmd_name == "timeline" {
+        return Some(Command::Timeline { args });
+    }
+    if cmd_name == "translate" {
+        return Some(Command::Translate { language: args });
+    }
 
     // Check built-in commands (they don't accept arguments currently)
     if let Some(cmd) = COMMANDS.iter().find(|cmd| cmd.name == cmd_name) {
@@ -321,5 +774,17 @@ pub(crate) fn parse(
         });
     }
 
+    // Check prompts from running MCP servers
+    if let Some(prompt) = crate::mcp::manager()
+        .prompt_commands()
+        .into_iter()
+        .find(|prompt| prompt.name.to_lowercase() == cmd_name)
+    {
+        return Some(Command::McpPrompt {
+            name: prompt.name,
+            args,
+        });
+    }
+
     None
 }
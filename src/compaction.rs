@@ -282,6 +282,61 @@ pub(crate) fn build_summarization_request_text(messages_to_summarize: &[Message]
     )
 }
 
+/// Render a transcript as plain text lines for display (e.g. `/expand`).
+///
+/// Thinking and binary content are omitted; tool calls and results are shown
+/// as one-line markers so the conversation flow stays readable.
+pub(crate) fn format_transcript(messages: &[Message]) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for msg in messages {
+        let role_str = match msg.role {
+            Role::User if msg.is_tool_result_only() => continue,
+            Role::User => "User",
+            Role::Assistant => "Assistant",
+            Role::System => continue,
+        };
+
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!("{}:", role_str));
+
+        match &msg.content {
+            MessageContent::Text(text) => lines.extend(text.lines().map(str::to_string)),
+            MessageContent::Blocks(blocks) => {
+                for block in blocks {
+                    match block {
+                        ContentBlock::Text { text } => {
+                            lines.extend(text.lines().map(str::to_string));
+                        }
+                        ContentBlock::ToolUse { name, input, .. } => {
+                            let input_str = serde_json::to_string(input).unwrap_or_default();
+                            lines.push(format!("[tool: {} {}]", name, input_str));
+                        }
+                        ContentBlock::Summary {
+                            summary,
+                            messages_compacted,
+                        } => {
+                            lines.push(format!(
+                                "[summary of {} earlier messages]",
+                                messages_compacted
+                            ));
+                            lines.extend(summary.lines().map(str::to_string));
+                        }
+                        ContentBlock::Image { mime_type, .. } => {
+                            lines.push(format!("[image: {}]", mime_type));
+                        }
+                        ContentBlock::Thinking { .. } | ContentBlock::ToolResult { .. } => {}
+                    }
+                }
+            }
+        }
+    }
+
+    lines
+}
+
 /// Build the user message that asks for summarization
 pub(crate) fn build_summarization_request(messages_to_summarize: &[Message]) -> Message {
     Message::user(build_summarization_request_text(messages_to_summarize))
@@ -599,4 +654,50 @@ mod tests {
         let user_message_count = xml.matches("<message role=\"user\">").count();
         assert_eq!(user_message_count, 1);
     }
+
+    #[test]
+    fn test_format_transcript() {
+        let messages = vec![
+            Message::system("system prompt"),
+            Message::user("Fix the bug"),
+            Message::assistant_blocks(vec![
+                ContentBlock::Thinking {
+                    thinking: "hmm".to_string(),
+                    provider_data: None,
+                },
+                ContentBlock::Text {
+                    text: "Looking".to_string(),
+                },
+                ContentBlock::ToolUse {
+                    id: "t1".to_string(),
+                    name: "file_read".to_string(),
+                    input: serde_json::json!({"path": "a.rs"}),
+                    thought_signature: None,
+                },
+            ]),
+            Message {
+                role: Role::User,
+                content: MessageContent::Blocks(vec![ContentBlock::ToolResult {
+                    tool_use_id: "t1".to_string(),
+                    content: "fn main() {}".to_string(),
+                    is_error: false,
+                    data: None,
+                    mime_type: None,
+                }]),
+            },
+        ];
+
+        let lines = format_transcript(&messages);
+        assert_eq!(
+            lines,
+            vec![
+                "User:",
+                "Fix the bug",
+                "",
+                "Assistant:",
+                "Looking",
+                "[tool: file_read {\"path\":\"a.rs\"}]",
+            ]
+        );
+    }
 }
@@ -5,6 +5,9 @@
 
 use std::path::{Path, PathBuf};
 
+/// Most matches offered when a bare `@name` is looked up across the tree.
+const MAX_TREE_MATCHES: usize = 50;
+
 /// File path completer with match state
 pub(crate) struct FileCompleter {
     /// Current completion matches
@@ -42,6 +45,12 @@ impl FileCompleter {
         self.index = 0;
     }
 
+    /// Initialize completion with matches computed by the caller
+    pub(crate) fn init_with(&mut self, matches: Vec<String>) {
+        self.matches = matches;
+        self.index = 0;
+    }
+
     /// Get the currently selected match, if any
     pub(crate) fn current(&self) -> Option<&str> {
         self.matches.get(self.index).map(|s| s.as_str())
@@ -59,10 +68,23 @@ impl FileCompleter {
 
     /// Get file completion matches for a prefix
     pub(crate) fn get_matches(&self, prefix: &str) -> Vec<String> {
+        // @path file references complete like the path itself
+        if let Some(path) = prefix.strip_prefix('@') {
+            let mut matches = self.get_matches(path);
+            // A bare name with no match here is looked up anywhere in the tree.
+            if matches.is_empty() && !path.is_empty() && !path.contains('/') {
+                matches = crate::walk::find(&self.working_dir, path, MAX_TREE_MATCHES);
+            }
+            return matches.into_iter().map(|m| format!("@{}", m)).collect();
+        }
+
         let working_dir = &self.working_dir;
 
         // Determine the prefix to prepend to results (preserve what the user typed)
-        let (search_dir, partial_name, result_prefix) = if prefix.starts_with('/') {
+        let (search_dir, partial_name, result_prefix) = if prefix.is_empty() {
+            // Nothing typed yet - list the working directory
+            (working_dir.clone(), String::new(), String::new())
+        } else if prefix.starts_with('/') {
             // Absolute path
             let base_path = PathBuf::from(prefix);
             if base_path.as_os_str() == "/" {
@@ -179,32 +201,17 @@ impl FileCompleter {
             resolve_relative_path(working_dir, prefix)
         };
 
-        // Try to read directory
-        let mut matches = Vec::new();
-        if let Ok(entries) = std::fs::read_dir(&search_dir) {
-            for entry in entries.flatten() {
-                let name = entry.file_name();
-                let name_str = name.to_string_lossy();
-
-                // Skip hidden files unless the partial starts with '.'
-                if name_str.starts_with('.') && !partial_name.starts_with('.') {
-                    continue;
-                }
-
-                // Check if name matches partial
-                if name_str.starts_with(&partial_name) {
-                    let is_dir = entry.path().is_dir();
-
-                    // Build the display path with the original prefix
-                    let display_path = if is_dir {
-                        format!("{}{}/", result_prefix, name_str)
-                    } else {
-                        format!("{}{}", result_prefix, name_str)
-                    };
-
-                    matches.push(display_path);
-                }
-            }
+        // List the directory, leaving out ignored files unless nothing else
+        // matches. Hidden files are only listed when the partial starts with '.'.
+        let mut options = crate::walk::WalkOptions {
+            include_hidden: partial_name.starts_with('.'),
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let mut matches = list_matches(&search_dir, &partial_name, &result_prefix, options);
+        if matches.is_empty() && !partial_name.is_empty() {
+            options.include_ignored = true;
+            matches = list_matches(&search_dir, &partial_name, &result_prefix, options);
         }
 
         // Sort: directories first, then alphabetically
@@ -222,6 +229,31 @@ impl FileCompleter {
     }
 }
 
+/// Entries of `dir` starting with `partial_name`, prefixed with
+/// `result_prefix`, with a trailing `/` for directories.
+fn list_matches(
+    dir: &Path,
+    partial_name: &str,
+    result_prefix: &str,
+    options: crate::walk::WalkOptions,
+) -> Vec<String> {
+    crate::walk::walk(dir, options)
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.depth() == 1)
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with(partial_name) {
+                return None;
+            }
+            Some(if entry.path().is_dir() {
+                format!("{}{}/", result_prefix, name)
+            } else {
+                format!("{}{}", result_prefix, name)
+            })
+        })
+        .collect()
+}
+
 /// Resolve a relative path prefix (including `../`) into the (search_dir,
 /// partial_name, result_prefix) tuple used by `get_matches`.
 fn resolve_relative_path(working_dir: &Path, prefix: &str) -> (PathBuf, String, String) {
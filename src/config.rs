@@ -1,8 +1,9 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Jason Ish
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{OnceLock, RwLock};
 
@@ -12,7 +13,27 @@ use serde::{Deserialize, Serialize};
 use crate::error::{self, Result};
 
 const CONFIG_FILE: &str = "config.toml";
+const BACKUP_DIR: &str = "backups";
+/// Number of timestamped config backups to keep.
+const MAX_BACKUPS: usize = 10;
 const CONFIG_DIR: &str = ".config/henri";
+/// Project-local config, relative to the working directory.
+const PROJECT_CONFIG_FILE: &str = ".henri/config.toml";
+/// Top-level keys a project config may set. Everything else, such as provider
+/// credentials, only comes from the global config.
+const PROJECT_CONFIG_KEYS: &[&str] = &[
+    "default-model",
+    "model",
+    "sandbox-mode",
+    "lsp-enabled",
+    "disabled-tools",
+    "auto-compact",
+    "loop-watchdog",
+    "max-tokens-continuation",
+    "overflow-recovery",
+    "prompts",
+    "history",
+];
 
 static CONFIG_DIR_OVERRIDE: OnceLock<RwLock<Option<PathBuf>>> = OnceLock::new();
 
@@ -44,6 +65,99 @@ pub(crate) fn config_dir() -> PathBuf {
     resolve_config_dir(override_dir.as_deref())
 }
 
+/// Directory holding timestamped backups of the config file.
+fn backup_dir() -> PathBuf {
+    config_dir().join(BACKUP_DIR)
+}
+
+/// Backups of the config file, newest first.
+pub(crate) fn list_config_backups() -> Vec<PathBuf> {
+    list_backups(&backup_dir())
+}
+
+fn list_backups(dir: &Path) -> Vec<PathBuf> {
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("config-") && name.ends_with(".toml"))
+        })
+        .collect();
+    // Timestamps sort lexically.
+    backups.sort_unstable_by(|a, b| b.cmp(a));
+    backups
+}
+
+/// Save `content`, the current config, into `backup_dir` with a timestamp,
+/// pruning the oldest backups. Nothing is saved when the newest backup
+/// already holds it. Backups hold credentials, so only the owner can read
+/// them.
+fn backup_file(content: &str, backup_dir: &Path) -> io::Result<()> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let backups = list_backups(backup_dir);
+    if backups
+        .first()
+        .is_some_and(|newest| fs::read_to_string(newest).is_ok_and(|c| c == content))
+    {
+        return Ok(());
+    }
+    fs::create_dir_all(backup_dir)?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S%.6f");
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(backup_dir.join(format!("config-{}.toml", stamp)))?
+        .write_all(content.as_bytes())?;
+    for old in list_backups(backup_dir).into_iter().skip(MAX_BACKUPS) {
+        let _ = fs::remove_file(old);
+    }
+    Ok(())
+}
+
+/// Replace `path` with `content` via a temp file and rename, so a crash never
+/// leaves a partially written file. The previous version is backed up first
+/// when `backup_dir` is given.
+fn write_atomic(path: &Path, content: &str, backup_dir: Option<&Path>) -> io::Result<()> {
+    if let Ok(existing) = fs::read_to_string(path) {
+        if existing == content {
+            return Ok(());
+        }
+        if let Some(backup_dir) = backup_dir {
+            backup_file(&existing, backup_dir)?;
+        }
+    }
+
+    let tmp = path.with_extension("toml.tmp");
+    {
+        let mut file = fs::File::create(&tmp)?;
+        // The config holds credentials; keep the existing permissions.
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)
+}
+
+/// Restore the config file from a backup. The current version is backed up
+/// first, so a rollback can itself be rolled back.
+pub(crate) fn rollback_config(backup: &Path) -> Result<()> {
+    let content = fs::read_to_string(backup).map_err(|e| error::Error::Config(e.to_string()))?;
+    toml::from_str::<toml::Value>(&content).map_err(|e| {
+        error::Error::Config(format!("{} is not valid TOML: {}", backup.display(), e))
+    })?;
+    let path = config_dir().join(CONFIG_FILE);
+    write_atomic(&path, &content, Some(&backup_dir()))
+        .map_err(|e| error::Error::Config(e.to_string()))
+}
+
 pub(crate) fn persist_last_used_model(model: &str) {
     if model.trim().is_empty() {
         return;
@@ -54,10 +168,13 @@ pub(crate) fn persist_last_used_model(model: &str) {
     };
 
     let mut state = config.state.unwrap_or_default();
+    if state.last_model.as_deref() == Some(model) {
+        return;
+    }
     state.last_model = Some(model.to_string());
     config.state = Some(state);
 
-    let _ = config.save();
+    let _ = config.save_without_backup();
 }
 
 /// Default model selection strategy on startup.
@@ -91,6 +208,20 @@ pub(crate) struct AutoCompactConfig {
     /// Defaults to 2.
     #[serde(default = "default_auto_compact_preserve_turns")]
     pub preserve_turns: usize,
+    /// How new sessions make room: summarize old turns or drop them.
+    #[serde(default)]
+    pub strategy: ContextStrategy,
+}
+
+/// How to make room when the context nears the model's limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ContextStrategy {
+    /// Replace old turns with a summary written by the model
+    #[default]
+    Summarize,
+    /// Drop old turns, keeping a preamble of key facts
+    SlidingWindow,
 }
 
 impl Default for AutoCompactConfig {
@@ -99,8 +230,562 @@ impl Default for AutoCompactConfig {
             enabled: default_auto_compact_enabled(),
             threshold: default_auto_compact_threshold(),
             preserve_turns: default_auto_compact_preserve_turns(),
+            strategy: ContextStrategy::default(),
+        }
+    }
+}
+
+/// Fallback model to use when subscription rate limits are nearly exhausted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct RateLimitFallbackConfig {
+    /// Model to switch to (e.g., "claude/claude-haiku-4-5"). Disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Utilization (0.0-1.0) of the 5-hour or weekly window that triggers the fallback.
+    /// Defaults to 0.9 (90%).
+    #[serde(default = "default_rate_limit_fallback_threshold")]
+    pub threshold: f64,
+    /// Switch automatically instead of only suggesting the fallback. Defaults to false.
+    #[serde(default)]
+    pub auto: bool,
+}
+
+impl Default for RateLimitFallbackConfig {
+    fn default() -> Self {
+        Self {
+            model: None,
+            threshold: default_rate_limit_fallback_threshold(),
+            auto: false,
+        }
+    }
+}
+
+fn default_rate_limit_fallback_threshold() -> f64 {
+    0.9
+}
+
+/// Runaway agent loop detection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct LoopWatchdogConfig {
+    /// Whether loop detection is enabled. Defaults to true.
+    #[serde(default = "default_loop_watchdog_enabled")]
+    pub enabled: bool,
+    /// Pause when a tool is called this many times with identical arguments in one turn.
+    /// Defaults to 3; 0 disables the check.
+    #[serde(default = "default_loop_watchdog_repeat_limit")]
+    pub repeat_limit: usize,
+    /// Pause after this many edits that undo an earlier edit. Defaults to 2; 0 disables.
+    #[serde(default = "default_loop_watchdog_revert_limit")]
+    pub revert_limit: usize,
+    /// Pause after this many tool iterations without a file change. Defaults to 40; 0 disables.
+    #[serde(default = "default_loop_watchdog_idle_iterations")]
+    pub idle_iterations: usize,
+}
+
+impl Default for LoopWatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_loop_watchdog_enabled(),
+            repeat_limit: default_loop_watchdog_repeat_limit(),
+            revert_limit: default_loop_watchdog_revert_limit(),
+            idle_iterations: default_loop_watchdog_idle_iterations(),
+        }
+    }
+}
+
+fn default_loop_watchdog_enabled() -> bool {
+    true
+}
+
+fn default_loop_watchdog_repeat_limit() -> usize {
+    3
+}
+
+fn default_loop_watchdog_revert_limit() -> usize {
+    2
+}
+
+fn default_loop_watchdog_idle_iterations() -> usize {
+    40
+}
+
+/// Cost tracking and the per-session budget.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct CostConfig {
+    /// Session cost in US dollars at which to warn or stop (default: no limit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_session_cost: Option<f64>,
+    /// What to do when the session cost reaches the limit (default: warn)
+    #[serde(default)]
+    pub on_limit: CostLimitAction,
+    /// Prices for models missing from the built-in table, or overrides, keyed
+    /// by model ID or `provider/model`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pricing: BTreeMap<String, ModelPricing>,
+}
+
+impl CostConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// System prompt overrides. Both are templates that may use `{cwd}`, `{os}`,
+/// `{git_branch}` and `{date}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PromptsConfig {
+    /// Replaces the built-in instructions. Project files, skills and the
+    /// other context are still added after it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    /// Added after the instructions and project context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub append: Option<String>,
+    /// Read CLAUDE.md in directories without an AGENTS.md (default: true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude_md: Option<bool>,
+    /// Changes for particular providers or models, applied in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adapters: Vec<PromptAdapterConfig>,
+}
+
+/// System prompt changes for the models matching `model`. Both are templates
+/// like the ones in [`PromptsConfig`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PromptAdapterConfig {
+    /// `provider/model-id` pattern in which `*` matches any text
+    pub model: String,
+    /// Replaces the instructions for these models
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    /// Added after the `append` of `[prompts]` for these models
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub append: Option<String>,
+}
+
+impl PromptAdapterConfig {
+    /// Whether the adapter applies to `model`, given as `provider/model-id`.
+    pub(crate) fn matches(&self, model: &str) -> bool {
+        wildcard_match(&self.model, model)
+    }
+}
+
+/// Match `text` against `pattern`, in which `*` matches any text.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut text) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match text.find(part) {
+            Some(idx) => text = &text[idx + part.len()..],
+            None => return false,
         }
     }
+    text.len() >= last.len() && text.ends_with(last)
+}
+
+impl PromptsConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub(crate) fn claude_md(&self) -> bool {
+        self.claude_md.unwrap_or(true)
+    }
+}
+
+/// Action taken when the session cost reaches `max-session-cost`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CostLimitAction {
+    /// Warn once and keep going
+    #[default]
+    Warn,
+    /// Stop the agent loop and refuse new requests
+    Stop,
+}
+
+/// Price of a model in US dollars per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    /// Price of tokens written to the prompt cache (default: the input price)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
+    /// Price of tokens read from the prompt cache (default: the input price)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+}
+
+/// What to do when a response is cut off by the output token limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ContinuationMode {
+    /// Continue without asking
+    #[default]
+    Auto,
+    /// Ask before continuing
+    Ask,
+    /// Leave the response truncated
+    Off,
+}
+
+/// Continuation of responses cut off by the output token limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct MaxTokensContinuationConfig {
+    #[serde(default)]
+    pub mode: ContinuationMode,
+    /// Maximum continuations for a single response. Defaults to 3.
+    #[serde(default = "default_max_continuations")]
+    pub max_continuations: usize,
+}
+
+impl Default for MaxTokensContinuationConfig {
+    fn default() -> Self {
+        Self {
+            mode: ContinuationMode::default(),
+            max_continuations: default_max_continuations(),
+        }
+    }
+}
+
+fn default_max_continuations() -> usize {
+    3
+}
+
+/// Recovery from a request that overflows the context window because of a
+/// large tool result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct OverflowRecoveryConfig {
+    /// Summarize the largest tool result and retry once. Defaults to true.
+    #[serde(default = "default_overflow_recovery_enabled")]
+    pub enabled: bool,
+    /// Model that writes the summary (e.g., "claude/claude-haiku-4-5").
+    /// Defaults to the current model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl Default for OverflowRecoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_overflow_recovery_enabled(),
+            model: None,
+        }
+    }
+}
+
+fn default_overflow_recovery_enabled() -> bool {
+    true
+}
+
+/// Settings for `/recap`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct RecapConfig {
+    /// Model that writes the recap (e.g., "claude/claude-haiku-4-5").
+    /// Defaults to the current model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// Network timeouts in seconds, with overrides keyed by provider id.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TimeoutsConfig {
+    #[serde(flatten)]
+    pub defaults: TimeoutSettings,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub providers: HashMap<String, TimeoutSettings>,
+}
+
+impl TimeoutsConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Settings for a provider, with its overrides applied over the defaults.
+    pub(crate) fn for_provider(&self, provider_id: &str) -> TimeoutSettings {
+        let mut settings = self.defaults;
+        if let Some(overrides) = self.providers.get(provider_id) {
+            settings.connect = overrides.connect.or(settings.connect);
+            settings.read = overrides.read.or(settings.read);
+            settings.stall = overrides.stall.or(settings.stall);
+        }
+        settings
+    }
+}
+
+/// Timeout settings; unset fields fall back to the defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct TimeoutSettings {
+    /// Time allowed to establish a connection (default: 30)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect: Option<u64>,
+    /// Time a single read may block, including the wait for the response
+    /// to start; 0 disables (default: disabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read: Option<u64>,
+    /// Time without streamed data before the response is abandoned and
+    /// retried; 0 disables (default: 120)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stall: Option<u64>,
+}
+
+/// Whether bash commands need approval.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum BashApproval {
+    /// Run commands without asking
+    #[default]
+    Allow,
+    /// Ask before running commands that are not allowed
+    Ask,
+}
+
+/// Approvals for tool actions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PermissionsConfig {
+    /// Whether bash commands need approval (default: allow)
+    #[serde(default)]
+    pub bash: BashApproval,
+    /// Command prefixes that run without asking (e.g., "cargo test")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_commands: Vec<String>,
+    /// Directories outside the working directory that tools may write to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_paths: Vec<PathBuf>,
+}
+
+impl PermissionsConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Prompt history settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct HistoryConfig {
+    /// Save prompts to the history file (default: true)
+    #[serde(default = "default_history_record")]
+    pub record: bool,
+    /// Prompts matching any of these regexes are not saved
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_patterns: Vec<String>,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            record: default_history_record(),
+            excluded_patterns: Vec::new(),
+        }
+    }
+}
+
+impl HistoryConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Apply a project's history settings over the global ones. A project may
+    /// only record less: it can turn recording off and add patterns, but not
+    /// turn recording back on or drop a global pattern.
+    fn restrict(&mut self, global: &HistoryConfig) {
+        self.record &= global.record;
+        for pattern in &global.excluded_patterns {
+            if !self.excluded_patterns.contains(pattern) {
+                self.excluded_patterns.push(pattern.clone());
+            }
+        }
+    }
+}
+
+fn default_history_record() -> bool {
+    true
+}
+
+/// Tool settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ToolsConfig {
+    /// Local executables offered to the model as tools, keyed by tool name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub external: BTreeMap<String, ExternalToolConfig>,
+    /// Maximum bytes of page content returned by the fetch tool
+    #[serde(
+        default,
+        rename = "fetch-max-bytes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fetch_max_bytes: Option<usize>,
+}
+
+impl ToolsConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A local executable registered as a tool.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct ExternalToolConfig {
+    /// Description shown to the model
+    pub description: String,
+    /// Shell command to run; `{name}` is replaced by the quoted argument `name`
+    pub command: String,
+    /// JSON schema of the arguments (default: no arguments)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<serde_json::Value>,
+    /// Timeout in seconds (default: 120)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+/// A formatter or linter run on files changed by the edit and write tools.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PostEditCheckConfig {
+    /// Shell command to run; `{file}` is replaced by the quoted path, which
+    /// is appended when there is no placeholder
+    pub command: String,
+    /// File extensions the check applies to, without the dot
+    pub extensions: Vec<String>,
+    /// Timeout in seconds (default: 60)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+/// Endpoint that computes the embeddings used by `semantic_search`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct EmbeddingsConfig {
+    /// Base URL of an OpenAI-compatible API, e.g. `http://localhost:11434/v1`
+    pub url: String,
+    /// Embedding model, e.g. `nomic-embed-text`
+    pub model: String,
+    /// API key, sent as a bearer token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+/// Commands run on lifecycle events, keyed by event.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct HooksConfig {
+    /// Before a tool runs; exiting with status 2 blocks the call
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_tool_use: Vec<HookConfig>,
+    /// After a tool has run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_tool_use: Vec<HookConfig>,
+    /// When a prompt is sent to the model
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub user_prompt_submit: Vec<HookConfig>,
+    /// When Henri exits
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub session_end: Vec<HookConfig>,
+}
+
+impl HooksConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A shell command run on a hook event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct HookConfig {
+    /// Shell command to run; the event is written to its stdin as JSON
+    pub command: String,
+    /// Tool names the hook applies to, for tool events (default: all tools)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+    /// Timeout in seconds (default: 30)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+/// Experimental WASM tool plugins, loaded from the `plugins` directory of
+/// the config directory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PluginsConfig {
+    /// Load plugins (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Instruction budget for a single call (default: 1000000000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u64>,
+    /// Maximum memory of a plugin instance in MiB (default: 64)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<usize>,
+}
+
+impl PluginsConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Initial sandbox mode for tool execution.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum SandboxMode {
+    /// Writes are limited to the working directory
+    #[default]
+    ReadWrite,
+    /// No file modifications
+    ReadOnly,
+    /// No restrictions
+    Yolo,
+}
+
+impl SandboxMode {
+    fn strictness(self) -> u8 {
+        match self {
+            SandboxMode::Yolo => 0,
+            SandboxMode::ReadWrite => 1,
+            SandboxMode::ReadOnly => 2,
+        }
+    }
+
+    /// Apply a project's sandbox mode over the global one. A project may only
+    /// make the sandbox stricter.
+    fn restrict(&mut self, global: SandboxMode) {
+        if self.strictness() < global.strictness() {
+            *self = global;
+        }
+    }
+}
+
+/// Shell used for `!command` input.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct ShellConfig {
+    /// Shell program. Defaults to `$SHELL`, then `sh`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program: Option<String>,
+    /// Start the shell as an interactive shell so rc-file aliases are loaded (default: false)
+    #[serde(default)]
+    pub interactive: bool,
 }
 
 fn default_auto_compact_enabled() -> bool {
@@ -142,6 +827,28 @@ pub(crate) struct ConfigFile {
     /// Remove blank lines between output blocks for a more compact display (default: false)
     #[serde(default = "default_compact_mode", rename = "compact-mode")]
     pub compact_mode: bool,
+    /// Prompt without borders, emoji or terminal title, and compact spacing (default: false)
+    #[serde(default = "default_minimal_ui", rename = "minimal-ui")]
+    pub minimal_ui: bool,
+    /// Capture mouse events so the wheel scrolls the tool output viewport (default: false)
+    #[serde(default = "default_mouse_capture", rename = "mouse-capture")]
+    pub mouse_capture: bool,
+    /// Print each response block once it is complete instead of streaming it (default: false)
+    #[serde(default = "default_instant_paint", rename = "instant-paint")]
+    pub instant_paint: bool,
+    /// Pass color sequences in model and tool output through to the terminal (default: false)
+    #[serde(default = "default_ansi_colors", rename = "ansi-colors")]
+    pub ansi_colors: bool,
+    /// Offer to attach pastes longer than this many lines as a file; 0 disables (default: 100)
+    #[serde(default = "default_paste_attach_lines", rename = "paste-attach-lines")]
+    pub paste_attach_lines: usize,
+    /// Command whose output replaces the status line below the prompt box
+    #[serde(
+        default,
+        rename = "statusline-command",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub statusline_command: Option<String>,
     /// List of favorite model identifiers (e.g., "claude/claude-sonnet-4-5")
     #[serde(
         default,
@@ -149,9 +856,71 @@ pub(crate) struct ConfigFile {
         skip_serializing_if = "Vec::is_empty"
     )]
     pub favorite_models: Vec<String>,
+    /// Models to fail over to, in order, when the current one keeps failing
+    #[serde(
+        default,
+        rename = "fallback-models",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub fallback_models: Vec<String>,
     /// Auto-compaction settings
     #[serde(default, rename = "auto-compact")]
     pub auto_compact: AutoCompactConfig,
+    /// Rate-limit fallback settings
+    #[serde(default, rename = "rate-limit-fallback")]
+    pub rate_limit_fallback: RateLimitFallbackConfig,
+    /// Runaway agent loop detection settings
+    #[serde(default, rename = "loop-watchdog")]
+    pub loop_watchdog: LoopWatchdogConfig,
+    /// Continuation of responses cut off by the output token limit
+    #[serde(default, rename = "max-tokens-continuation")]
+    pub max_tokens_continuation: MaxTokensContinuationConfig,
+    /// Summarizing oversized tool results when the context overflows
+    #[serde(default, rename = "overflow-recovery")]
+    pub overflow_recovery: OverflowRecoveryConfig,
+    /// Settings for `/recap`
+    #[serde(default)]
+    pub recap: RecapConfig,
+    /// Pricing and the per-session cost limit
+    #[serde(default, skip_serializing_if = "CostConfig::is_default")]
+    pub cost: CostConfig,
+    /// System prompt overrides
+    #[serde(default, skip_serializing_if = "PromptsConfig::is_default")]
+    pub prompts: PromptsConfig,
+    /// Shell settings for `!command` input
+    #[serde(default)]
+    pub shell: ShellConfig,
+    /// Network timeouts for provider requests
+    #[serde(default, skip_serializing_if = "TimeoutsConfig::is_default")]
+    pub timeouts: TimeoutsConfig,
+    /// Approvals for bash commands and writes outside the working directory
+    #[serde(default, skip_serializing_if = "PermissionsConfig::is_default")]
+    pub permissions: PermissionsConfig,
+    /// Prompt history recording
+    #[serde(default, skip_serializing_if = "HistoryConfig::is_default")]
+    pub history: HistoryConfig,
+    /// External tools
+    #[serde(default, skip_serializing_if = "ToolsConfig::is_default")]
+    pub tools: ToolsConfig,
+    /// Experimental WASM tool plugins
+    #[serde(default, skip_serializing_if = "PluginsConfig::is_default")]
+    pub plugins: PluginsConfig,
+    /// Commands run on lifecycle events
+    #[serde(default, skip_serializing_if = "HooksConfig::is_default")]
+    pub hooks: HooksConfig,
+    /// Formatters and linters run after the edit and write tools change a file
+    #[serde(
+        default,
+        rename = "post-edit-checks",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub post_edit_checks: Vec<PostEditCheckConfig>,
+    /// Embeddings for `semantic_search`; the tool is only offered when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embeddings: Option<EmbeddingsConfig>,
+    /// Sandbox mode on startup (default: read-write)
+    #[serde(default, rename = "sandbox-mode")]
+    pub sandbox_mode: SandboxMode,
     /// List of disabled tool names
     #[serde(
         default,
@@ -159,6 +928,10 @@ pub(crate) struct ConfigFile {
         skip_serializing_if = "Vec::is_empty"
     )]
     pub disabled_tools: Vec<String>,
+    /// Effective values of the keys set by a project config when loaded, so
+    /// `save` can keep them out of the global config.
+    #[serde(skip)]
+    project_overlay: Option<Vec<(String, Option<toml::Value>)>>,
 }
 
 impl Default for ConfigFile {
@@ -174,13 +947,42 @@ impl Default for ConfigFile {
             lsp_enabled: default_lsp_enabled(),
             hide_tool_output: default_hide_tool_output(),
             compact_mode: default_compact_mode(),
+            minimal_ui: default_minimal_ui(),
+            mouse_capture: default_mouse_capture(),
+            instant_paint: default_instant_paint(),
+            ansi_colors: default_ansi_colors(),
+            paste_attach_lines: default_paste_attach_lines(),
+            statusline_command: None,
             favorite_models: Vec::new(),
+            fallback_models: Vec::new(),
             auto_compact: AutoCompactConfig::default(),
+            rate_limit_fallback: RateLimitFallbackConfig::default(),
+            loop_watchdog: LoopWatchdogConfig::default(),
+            max_tokens_continuation: MaxTokensContinuationConfig::default(),
+            overflow_recovery: OverflowRecoveryConfig::default(),
+            recap: RecapConfig::default(),
+            cost: CostConfig::default(),
+            prompts: PromptsConfig::default(),
+            shell: ShellConfig::default(),
+            timeouts: TimeoutsConfig::default(),
+            permissions: PermissionsConfig::default(),
+            history: HistoryConfig::default(),
+            tools: ToolsConfig::default(),
+            plugins: PluginsConfig::default(),
+            hooks: HooksConfig::default(),
+            post_edit_checks: Vec::new(),
+            embeddings: None,
+            sandbox_mode: SandboxMode::default(),
             disabled_tools: Vec::new(),
+            project_overlay: None,
         }
     }
 }
 
+fn default_paste_attach_lines() -> usize {
+    100
+}
+
 fn default_show_image_previews() -> bool {
     true
 }
@@ -197,6 +999,22 @@ fn default_compact_mode() -> bool {
     false
 }
 
+fn default_minimal_ui() -> bool {
+    false
+}
+
+fn default_mouse_capture() -> bool {
+    false
+}
+
+fn default_instant_paint() -> bool {
+    false
+}
+
+fn default_ansi_colors() -> bool {
+    false
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub(crate) struct McpConfig {
     #[serde(default)]
@@ -226,15 +1044,45 @@ pub(crate) struct LspServerConfig {
 #[serde(rename_all = "kebab-case")]
 pub(crate) struct McpServerConfig {
     pub name: String,
+    /// Command for a local (stdio) server
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub command: String,
     #[serde(default)]
     pub args: Vec<String>,
     #[serde(default)]
     pub env: std::collections::HashMap<String, String>,
+    /// URL of a remote server, used instead of `command`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "McpRemoteTransport::is_default")]
+    pub transport: McpRemoteTransport,
+    /// Extra HTTP headers for a remote server
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub headers: std::collections::HashMap<String, String>,
+    /// Sent as `Authorization: Bearer <token>` to a remote server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bearer_token: Option<String>,
     #[serde(default = "default_enabled")]
     pub enabled: bool,
 }
 
+/// Transport used to reach a remote MCP server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum McpRemoteTransport {
+    /// Streamable HTTP
+    #[default]
+    Http,
+    /// The older HTTP+SSE transport
+    Sse,
+}
+
+impl McpRemoteTransport {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 fn default_enabled() -> bool {
     true
 }
@@ -250,6 +1098,9 @@ pub(crate) enum ProviderType {
     Zen,
     OpenaiCompat,
     OpenRouter,
+    Ollama,
+    Vertex,
+    Mistral,
 }
 
 impl ProviderType {
@@ -263,6 +1114,9 @@ impl ProviderType {
             ProviderType::Zen => "zen",
             ProviderType::OpenaiCompat => "openai-compat",
             ProviderType::OpenRouter => "openrouter",
+            ProviderType::Ollama => "ollama",
+            ProviderType::Vertex => "vertex",
+            ProviderType::Mistral => "mistral",
         }
     }
 
@@ -276,6 +1130,9 @@ impl ProviderType {
             ProviderType::Zen => "OpenCode Zen",
             ProviderType::OpenaiCompat => "OpenAI Compatible",
             ProviderType::OpenRouter => "OpenRouter",
+            ProviderType::Ollama => "Ollama",
+            ProviderType::Vertex => "Vertex AI",
+            ProviderType::Mistral => "Mistral",
         }
     }
 }
@@ -291,6 +1148,9 @@ pub(crate) enum ProviderConfig {
     Zen(ZenProviderConfig),
     OpenaiCompat(OpenAiCompatProviderConfig),
     Openrouter(OpenRouterConfig),
+    Ollama(OllamaProviderConfig),
+    Vertex(VertexProviderConfig),
+    Mistral(MistralProviderConfig),
 }
 
 impl ProviderConfig {
@@ -303,6 +1163,9 @@ impl ProviderConfig {
             ProviderConfig::Zen(c) => c.enabled,
             ProviderConfig::OpenaiCompat(c) => c.enabled,
             ProviderConfig::Openrouter(c) => c.enabled,
+            ProviderConfig::Ollama(c) => c.enabled,
+            ProviderConfig::Vertex(c) => c.enabled,
+            ProviderConfig::Mistral(c) => c.enabled,
         }
     }
 
@@ -315,6 +1178,9 @@ impl ProviderConfig {
             ProviderConfig::Zen(_) => ProviderType::Zen,
             ProviderConfig::OpenaiCompat(_) => ProviderType::OpenaiCompat,
             ProviderConfig::Openrouter(_) => ProviderType::OpenRouter,
+            ProviderConfig::Ollama(_) => ProviderType::Ollama,
+            ProviderConfig::Vertex(_) => ProviderType::Vertex,
+            ProviderConfig::Mistral(_) => ProviderType::Mistral,
         }
     }
 
@@ -334,6 +1200,30 @@ impl ProviderConfig {
         }
     }
 
+    /// Get the Ollama config if this is an Ollama provider
+    pub(crate) fn as_ollama(&self) -> Option<&OllamaProviderConfig> {
+        match self {
+            ProviderConfig::Ollama(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Get the Vertex AI config if this is a Vertex AI provider
+    pub(crate) fn as_vertex(&self) -> Option<&VertexProviderConfig> {
+        match self {
+            ProviderConfig::Vertex(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Get the Mistral config if this is a Mistral provider
+    pub(crate) fn as_mistral(&self) -> Option<&MistralProviderConfig> {
+        match self {
+            ProviderConfig::Mistral(c) => Some(c),
+            _ => None,
+        }
+    }
+
     /// Get the Claude config if this is a Claude provider
     pub(crate) fn as_claude(&self) -> Option<&ClaudeProviderConfig> {
         match self {
@@ -425,10 +1315,60 @@ pub(crate) struct AntigravityProviderConfig {
 pub(crate) struct ClaudeProviderConfig {
     #[serde(default = "default_enabled")]
     pub enabled: bool,
+    /// API endpoint, for regional routing (default: https://api.anthropic.com)
+    #[serde(default, rename = "base-url", skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Beta features, optionally per model
+    #[serde(default, skip_serializing_if = "AnthropicBetas::is_default")]
+    pub betas: AnthropicBetas,
     #[serde(flatten)]
     pub auth: ClaudeAuth,
 }
 
+/// Anthropic beta feature settings, with per-model overrides.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct AnthropicBetas {
+    #[serde(flatten)]
+    pub defaults: AnthropicBetaSettings,
+    /// Overrides keyed by model name (without the `#variant` suffix)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub models: HashMap<String, AnthropicBetaSettings>,
+}
+
+impl AnthropicBetas {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Beta feature settings; unset fields fall back to the next level.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct AnthropicBetaSettings {
+    /// Mark the system prompt and conversation for prompt caching (default: true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_caching: Option<bool>,
+    /// How long cached prompts live (default: 5m)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<CacheTtl>,
+    /// Let the API clear old tool results as the context fills (default: false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_management: Option<bool>,
+    /// Additional beta flags, sent as-is
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<String>,
+}
+
+/// Prompt cache lifetime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) enum CacheTtl {
+    #[default]
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct ClaudeAuth {
     #[serde(rename = "refresh-token")]
@@ -453,42 +1393,141 @@ pub(crate) struct CopilotProviderConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
-pub(crate) struct OpenAiProviderConfig {
+pub(crate) struct OpenAiProviderConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_openai_client_id")]
+    pub client_id: String,
+    #[serde(default = "default_openai_audience")]
+    pub audience: String,
+    pub refresh_token: String,
+    pub access_token: String,
+    pub expires_at: u64,
+    #[serde(default)]
+    pub project_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct ZenProviderConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub api_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct OpenAiCompatProviderConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub api_key: String,
+    pub base_url: String,
+    #[serde(default, rename = "model", skip_serializing_if = "Vec::is_empty")]
+    pub model_configs: Vec<ModelConfig>,
+}
+
+impl OpenAiCompatProviderConfig {
+    /// Get all available model names for UI display
+    pub(crate) fn all_models(&self) -> Vec<String> {
+        self.model_configs
+            .iter()
+            .map(|m| m.display_name().to_string())
+            .collect()
+    }
+
+    /// Get configuration for a specific model by display name
+    pub(crate) fn get_model_config(&self, display_name: &str) -> Option<&ModelConfig> {
+        self.model_configs
+            .iter()
+            .find(|m| m.display_name() == display_name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct OpenRouterConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub api_key: String,
+    #[serde(default, rename = "model", skip_serializing_if = "Vec::is_empty")]
+    pub model_configs: Vec<ModelConfig>,
+}
+
+impl OpenRouterConfig {
+    /// Get all available model names for UI display
+    pub(crate) fn all_models(&self) -> Vec<String> {
+        self.model_configs
+            .iter()
+            .map(|m| m.display_name().to_string())
+            .collect()
+    }
+
+    /// Get configuration for a specific model by display name
+    pub(crate) fn get_model_config(&self, display_name: &str) -> Option<&ModelConfig> {
+        self.model_configs
+            .iter()
+            .find(|m| m.display_name() == display_name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct OllamaProviderConfig {
     #[serde(default = "default_enabled")]
     pub enabled: bool,
-    #[serde(default = "default_openai_client_id")]
-    pub client_id: String,
-    #[serde(default = "default_openai_audience")]
-    pub audience: String,
-    pub refresh_token: String,
-    pub access_token: String,
-    pub expires_at: u64,
-    #[serde(default)]
-    pub project_id: Option<String>,
+    #[serde(default = "default_ollama_base_url")]
+    pub base_url: String,
+    /// Per-model settings; installed models are discovered without being listed here
+    #[serde(default, rename = "model", skip_serializing_if = "Vec::is_empty")]
+    pub model_configs: Vec<ModelConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub(crate) struct ZenProviderConfig {
-    #[serde(default = "default_enabled")]
-    pub enabled: bool,
-    pub api_key: String,
+fn default_ollama_base_url() -> String {
+    crate::provider::ollama::DEFAULT_BASE_URL.to_string()
+}
+
+impl OllamaProviderConfig {
+    /// Get all configured model names for UI display
+    pub(crate) fn all_models(&self) -> Vec<String> {
+        self.model_configs
+            .iter()
+            .map(|m| m.display_name().to_string())
+            .collect()
+    }
+
+    /// Get configuration for a specific model by display name
+    pub(crate) fn get_model_config(&self, display_name: &str) -> Option<&ModelConfig> {
+        self.model_configs
+            .iter()
+            .find(|m| m.display_name() == display_name)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
-pub(crate) struct OpenAiCompatProviderConfig {
+pub(crate) struct VertexProviderConfig {
     #[serde(default = "default_enabled")]
     pub enabled: bool,
-    #[serde(default)]
-    pub api_key: String,
-    pub base_url: String,
+    /// Google Cloud project billed for requests
+    pub project: String,
+    /// Region the models are served from, or `global`
+    #[serde(default = "default_vertex_region")]
+    pub region: String,
+    /// Credentials file; Application Default Credentials are used if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<PathBuf>,
     #[serde(default, rename = "model", skip_serializing_if = "Vec::is_empty")]
     pub model_configs: Vec<ModelConfig>,
 }
 
-impl OpenAiCompatProviderConfig {
-    /// Get all available model names for UI display
+fn default_vertex_region() -> String {
+    crate::provider::vertex::DEFAULT_REGION.to_string()
+}
+
+impl VertexProviderConfig {
+    /// Get all configured model names for UI display
     pub(crate) fn all_models(&self) -> Vec<String> {
         self.model_configs
             .iter()
@@ -506,17 +1545,31 @@ impl OpenAiCompatProviderConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
-pub(crate) struct OpenRouterConfig {
+pub(crate) struct MistralProviderConfig {
     #[serde(default = "default_enabled")]
     pub enabled: bool,
     #[serde(default)]
     pub api_key: String,
+    /// API endpoint, e.g. `https://codestral.mistral.ai/v1` for a Codestral key
+    #[serde(default = "default_mistral_base_url")]
+    pub base_url: String,
+    /// Model used by the edit tool to fill in code; empty disables it
+    #[serde(default = "default_mistral_fim_model")]
+    pub fim_model: String,
     #[serde(default, rename = "model", skip_serializing_if = "Vec::is_empty")]
     pub model_configs: Vec<ModelConfig>,
 }
 
-impl OpenRouterConfig {
-    /// Get all available model names for UI display
+fn default_mistral_base_url() -> String {
+    crate::provider::mistral::DEFAULT_BASE_URL.to_string()
+}
+
+fn default_mistral_fim_model() -> String {
+    crate::provider::mistral::DEFAULT_FIM_MODEL.to_string()
+}
+
+impl MistralProviderConfig {
+    /// Get all configured model names for UI display
     pub(crate) fn all_models(&self) -> Vec<String> {
         self.model_configs
             .iter()
@@ -552,6 +1605,9 @@ pub(crate) struct ModelConfig {
     /// Maximum tokens to generate
     #[serde(default)]
     pub max_tokens: Option<u32>,
+    /// Context window in tokens, for models henri doesn't know the size of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
 }
 
 impl ModelConfig {
@@ -582,6 +1638,73 @@ fn default_openai_audience() -> String {
     "https://api.openai.com/v1".to_string()
 }
 
+/// Read the project config from the working directory, keeping only the keys a
+/// project may set. Unreadable or invalid project configs are ignored.
+fn load_project_table() -> Option<toml::Table> {
+    let path = std::env::current_dir().ok()?.join(PROJECT_CONFIG_FILE);
+    let content = fs::read_to_string(path).ok()?;
+    let mut table = toml::from_str::<toml::Table>(&content).ok()?;
+    table.retain(|key, _| PROJECT_CONFIG_KEYS.contains(&key));
+    (!table.is_empty()).then_some(table)
+}
+
+/// Merge `overlay` into `base`. Tables are merged recursively and arrays of named
+/// tables (like `[[mcp.servers]]`) are merged by name; other values are replaced.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(value)) => {
+                merge_tables(existing, value);
+            }
+            (Some(toml::Value::Array(existing)), toml::Value::Array(value))
+                if existing
+                    .iter()
+                    .chain(&value)
+                    .all(|v| entry_name(v).is_some()) =>
+            {
+                for entry in value {
+                    match existing
+                        .iter_mut()
+                        .find(|e| entry_name(e) == entry_name(&entry))
+                    {
+                        Some(slot) => *slot = entry,
+                        None => existing.push(entry),
+                    }
+                }
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn entry_name(value: &toml::Value) -> Option<&str> {
+    value.get("name").and_then(|name| name.as_str())
+}
+
+/// Restore the global values of keys that came from a project config and were not
+/// changed since loading, so saving doesn't copy project settings into the global config.
+fn strip_project_overlay(
+    table: &mut toml::Table,
+    overlay: &[(String, Option<toml::Value>)],
+    global: &toml::Table,
+) {
+    for (key, loaded) in overlay {
+        if table.get(key) != loaded.as_ref() {
+            continue;
+        }
+        match global.get(key) {
+            Some(value) => {
+                table.insert(key.clone(), value.clone());
+            }
+            None => {
+                table.remove(key);
+            }
+        }
+    }
+}
+
 impl ConfigFile {
     fn config_dir() -> PathBuf {
         crate::config::config_dir()
@@ -591,31 +1714,67 @@ impl ConfigFile {
         Self::config_dir().join(CONFIG_FILE)
     }
 
+    /// Load the global config with the project config from the working directory
+    /// (`.henri/config.toml`), if any, merged over it.
     pub(crate) fn load() -> Result<Self> {
         let path = Self::config_file_path();
 
-        if !path.exists() {
-            return Ok(Self::default());
+        let mut raw = if path.exists() {
+            let content =
+                fs::read_to_string(&path).map_err(|e| error::Error::Config(e.to_string()))?;
+            toml::from_str::<toml::Table>(&content)
+                .map_err(|e| error::Error::Config(e.to_string()))?
+        } else {
+            toml::Table::new()
+        };
+
+        let global_history: HistoryConfig = raw
+            .get("history")
+            .and_then(|value| value.clone().try_into().ok())
+            .unwrap_or_default();
+        let global_sandbox_mode: SandboxMode = raw
+            .get("sandbox-mode")
+            .and_then(|value| value.clone().try_into().ok())
+            .unwrap_or_default();
+        let project = load_project_table();
+        if let Some(project) = &project {
+            merge_tables(&mut raw, project.clone());
         }
 
-        let content = fs::read_to_string(&path).map_err(|e| error::Error::Config(e.to_string()))?;
+        let raw = toml::Value::Table(raw);
+        let mut config = match raw.clone().try_into::<Self>() {
+            Ok(config) => config,
+            // Get what we can out of a config with invalid fields
+            Err(_) => Self::load_with_fallback(&raw)?,
+        };
 
-        match toml::from_str(&content) {
-            Ok(config) => Ok(config),
-            Err(_) => {
-                // Try to load with toml::Value first to get what we can
-                Self::load_with_fallback(&content)
-            }
+        if project.as_ref().is_some_and(|p| p.contains_key("history")) {
+            config.history.restrict(&global_history);
+        }
+        if project
+            .as_ref()
+            .is_some_and(|p| p.contains_key("sandbox-mode"))
+        {
+            config.sandbox_mode.restrict(global_sandbox_mode);
         }
+
+        if let Some(project) = project {
+            let effective = toml::Value::try_from(&config).unwrap_or(toml::Value::Boolean(false));
+            config.project_overlay = Some(
+                project
+                    .keys()
+                    .map(|key| (key.clone(), effective.get(key).cloned()))
+                    .collect(),
+            );
+        }
+
+        Ok(config)
     }
 
     /// Attempt to load config with fallback for invalid fields.
-    /// This parses the TOML as a raw Value first, then selectively
+    /// This takes the TOML as a raw Value, then selectively
     /// deserializes fields that work, using defaults for the rest.
-    fn load_with_fallback(content: &str) -> Result<Self> {
-        let raw: toml::Value =
-            toml::from_str(content).map_err(|e| error::Error::Config(e.to_string()))?;
-
+    fn load_with_fallback(raw: &toml::Value) -> Result<Self> {
         let mut config = Self::default();
 
         // Try to extract each field, falling back to default on failure
@@ -690,6 +1849,48 @@ impl ConfigFile {
                 config.compact_mode = b;
             }
 
+            // minimal-ui
+            if let Some(val) = table.get("minimal-ui")
+                && let Some(b) = val.as_bool()
+            {
+                config.minimal_ui = b;
+            }
+
+            // mouse-capture
+            if let Some(val) = table.get("mouse-capture")
+                && let Some(b) = val.as_bool()
+            {
+                config.mouse_capture = b;
+            }
+
+            // instant-paint
+            if let Some(val) = table.get("instant-paint")
+                && let Some(b) = val.as_bool()
+            {
+                config.instant_paint = b;
+            }
+
+            // ansi-colors
+            if let Some(val) = table.get("ansi-colors")
+                && let Some(b) = val.as_bool()
+            {
+                config.ansi_colors = b;
+            }
+
+            // paste-attach-lines
+            if let Some(val) = table.get("paste-attach-lines")
+                && let Some(n) = val.as_integer()
+            {
+                config.paste_attach_lines = n.max(0) as usize;
+            }
+
+            // statusline-command
+            if let Some(val) = table.get("statusline-command")
+                && let Some(cmd) = val.as_str()
+            {
+                config.statusline_command = Some(cmd.to_string());
+            }
+
             // favorite-models
             if let Some(val) = table.get("favorite-models")
                 && let Ok(fav) = val.clone().try_into()
@@ -697,6 +1898,13 @@ impl ConfigFile {
                 config.favorite_models = fav;
             }
 
+            // fallback-models
+            if let Some(val) = table.get("fallback-models")
+                && let Ok(models) = val.clone().try_into()
+            {
+                config.fallback_models = models;
+            }
+
             // auto-compact
             if let Some(val) = table.get("auto-compact")
                 && let Ok(ac) = val.clone().try_into()
@@ -704,6 +1912,124 @@ impl ConfigFile {
                 config.auto_compact = ac;
             }
 
+            // rate-limit-fallback
+            if let Some(val) = table.get("rate-limit-fallback")
+                && let Ok(rl) = val.clone().try_into()
+            {
+                config.rate_limit_fallback = rl;
+            }
+
+            // loop-watchdog
+            if let Some(val) = table.get("loop-watchdog")
+                && let Ok(lw) = val.clone().try_into()
+            {
+                config.loop_watchdog = lw;
+            }
+
+            // max-tokens-continuation
+            if let Some(val) = table.get("max-tokens-continuation")
+                && let Ok(mc) = val.clone().try_into()
+            {
+                config.max_tokens_continuation = mc;
+            }
+
+            // overflow-recovery
+            if let Some(val) = table.get("overflow-recovery")
+                && let Ok(or) = val.clone().try_into()
+            {
+                config.overflow_recovery = or;
+            }
+
+            // recap
+            if let Some(val) = table.get("recap")
+                && let Ok(r) = val.clone().try_into()
+            {
+                config.recap = r;
+            }
+
+            // cost
+            if let Some(val) = table.get("cost")
+                && let Ok(c) = val.clone().try_into()
+            {
+                config.cost = c;
+            }
+
+            if let Some(val) = table.get("prompts")
+                && let Ok(p) = val.clone().try_into()
+            {
+                config.prompts = p;
+            }
+
+            // sandbox-mode
+            if let Some(val) = table.get("sandbox-mode")
+                && let Ok(mode) = val.clone().try_into()
+            {
+                config.sandbox_mode = mode;
+            }
+
+            // shell
+            if let Some(val) = table.get("shell")
+                && let Ok(sh) = val.clone().try_into()
+            {
+                config.shell = sh;
+            }
+
+            // timeouts
+            if let Some(val) = table.get("timeouts")
+                && let Ok(t) = val.clone().try_into()
+            {
+                config.timeouts = t;
+            }
+
+            // permissions
+            if let Some(val) = table.get("permissions")
+                && let Ok(p) = val.clone().try_into()
+            {
+                config.permissions = p;
+            }
+
+            // history
+            if let Some(val) = table.get("history")
+                && let Ok(h) = val.clone().try_into()
+            {
+                config.history = h;
+            }
+
+            // tools
+            if let Some(val) = table.get("tools")
+                && let Ok(t) = val.clone().try_into()
+            {
+                config.tools = t;
+            }
+
+            // plugins
+            if let Some(val) = table.get("plugins")
+                && let Ok(p) = val.clone().try_into()
+            {
+                config.plugins = p;
+            }
+
+            // hooks
+            if let Some(val) = table.get("hooks")
+                && let Ok(h) = val.clone().try_into()
+            {
+                config.hooks = h;
+            }
+
+            // post-edit-checks
+            if let Some(val) = table.get("post-edit-checks")
+                && let Ok(checks) = val.clone().try_into()
+            {
+                config.post_edit_checks = checks;
+            }
+
+            // embeddings
+            if let Some(val) = table.get("embeddings")
+                && let Ok(e) = val.clone().try_into()
+            {
+                config.embeddings = Some(e);
+            }
+
             // disabled-tools
             if let Some(val) = table.get("disabled-tools")
                 && let Ok(dt) = val.clone().try_into()
@@ -715,16 +2041,59 @@ impl ConfigFile {
         Ok(config)
     }
 
+    /// Save the config, backing up the previous version first.
     pub(crate) fn save(&self) -> Result<()> {
+        self.write_file(true)
+    }
+
+    /// Save a change Henri makes on its own, such as a refreshed token, the
+    /// last used model or a remembered permission, without backing up the
+    /// previous config. Backing those up would soon push every useful
+    /// backup out.
+    pub(crate) fn save_without_backup(&self) -> Result<()> {
+        self.write_file(false)
+    }
+
+    fn write_file(&self, backup: bool) -> Result<()> {
         let path = Self::config_file_path();
 
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).map_err(|e| error::Error::Config(e.to_string()))?;
         }
 
+        let mut value =
+            toml::Value::try_from(self).map_err(|e| error::Error::Config(e.to_string()))?;
+        if let Some(overlay) = &self.project_overlay
+            && let Some(table) = value.as_table_mut()
+        {
+            let global = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| toml::from_str::<toml::Table>(&content).ok())
+                .unwrap_or_default();
+            strip_project_overlay(table, overlay, &global);
+        }
+
         let content =
-            toml::to_string_pretty(self).map_err(|e| error::Error::Config(e.to_string()))?;
-        fs::write(path, content).map_err(|e| error::Error::Config(e.to_string()))
+            toml::to_string_pretty(&value).map_err(|e| error::Error::Config(e.to_string()))?;
+        let backup_dir = backup.then(backup_dir);
+        write_atomic(&path, &content, backup_dir.as_deref())
+            .map_err(|e| error::Error::Config(e.to_string()))
+    }
+
+    /// The context window configured for a model of an OpenAI-compatible,
+    /// OpenRouter, Ollama, Vertex AI or Mistral provider.
+    pub(crate) fn model_context_window(&self, model: &str) -> Option<u64> {
+        self.providers.entries.values().find_map(|provider| {
+            let model_config = match provider {
+                ProviderConfig::OpenaiCompat(c) => c.get_model_config(model),
+                ProviderConfig::Openrouter(c) => c.get_model_config(model),
+                ProviderConfig::Ollama(c) => c.get_model_config(model),
+                ProviderConfig::Vertex(c) => c.get_model_config(model),
+                ProviderConfig::Mistral(c) => c.get_model_config(model),
+                _ => None,
+            };
+            model_config.and_then(|m| m.context_window)
+        })
     }
 
     /// Get a provider by local identifier
@@ -848,12 +2217,7 @@ pub async fn initialize_servers(working_dir: &Path, lsp_override: Option<bool>)
             .servers
             .iter()
             .filter(|s| s.enabled)
-            .map(|s| crate::mcp::McpServerConfig {
-                name: s.name.clone(),
-                _command: s.command.clone(),
-                _args: s.args.clone(),
-                _env: s.env.clone(),
-            })
+            .map(crate::mcp::McpServerConfig::from_config)
             .collect();
         crate::mcp::register_servers(servers).await;
     }
@@ -883,6 +2247,225 @@ pub async fn initialize_servers(working_dir: &Path, lsp_override: Option<bool>)
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_claude_provider_betas_round_trip() {
+        let toml_str = r#"
+[providers.claude]
+type = "claude"
+base-url = "https://claude.example.eu"
+refresh-token = "r"
+access-token = "a"
+expires-at = 1
+
+[providers.claude.betas]
+cache-ttl = "1h"
+
+[providers.claude.betas.models.claude-opus-4-6]
+context-management = true
+"#;
+        let config: ConfigFile = toml::from_str(toml_str).unwrap();
+        let claude = config.get_provider("claude").unwrap().as_claude().unwrap();
+        assert_eq!(
+            claude.base_url.as_deref(),
+            Some("https://claude.example.eu")
+        );
+        assert_eq!(claude.betas.defaults.cache_ttl, Some(CacheTtl::OneHour));
+        assert_eq!(
+            claude.betas.models["claude-opus-4-6"].context_management,
+            Some(true)
+        );
+        assert_eq!(claude.auth.access_token, "a");
+
+        let saved = toml::to_string(&config).unwrap();
+        let reloaded: ConfigFile = toml::from_str(&saved).unwrap();
+        let reloaded = reloaded
+            .get_provider("claude")
+            .unwrap()
+            .as_claude()
+            .unwrap();
+        assert_eq!(reloaded.betas, claude.betas);
+    }
+
+    #[test]
+    fn test_timeouts_provider_overrides() {
+        let toml_str = r#"
+[timeouts]
+connect = 10
+stall = 90
+
+[timeouts.providers.ollama]
+stall = 600
+"#;
+        let config: ConfigFile = toml::from_str(toml_str).unwrap();
+        let ollama = config.timeouts.for_provider("ollama");
+        assert_eq!(ollama.connect, Some(10));
+        assert_eq!(ollama.stall, Some(600));
+        assert_eq!(ollama.read, None);
+        assert_eq!(config.timeouts.for_provider("claude").stall, Some(90));
+
+        let saved = toml::to_string(&config).unwrap();
+        let reloaded: ConfigFile = toml::from_str(&saved).unwrap();
+        assert_eq!(reloaded.timeouts, config.timeouts);
+        assert!(
+            !toml::to_string(&ConfigFile::default())
+                .unwrap()
+                .contains("timeouts")
+        );
+    }
+
+    #[test]
+    fn test_external_tools() {
+        let toml_str = r#"
+[tools.external.lint]
+description = "Run the linter"
+command = "cargo clippy -p {package}"
+timeout = 300
+
+[tools.external.lint.input-schema]
+type = "object"
+required = ["package"]
+
+[tools.external.lint.input-schema.properties.package]
+type = "string"
+"#;
+        let config: ConfigFile = toml::from_str(toml_str).unwrap();
+        let lint = &config.tools.external["lint"];
+        assert_eq!(lint.command, "cargo clippy -p {package}");
+        assert_eq!(lint.timeout, Some(300));
+        assert_eq!(
+            lint.input_schema.as_ref().unwrap()["properties"]["package"]["type"],
+            "string"
+        );
+
+        let saved = toml::to_string(&config).unwrap();
+        let reloaded: ConfigFile = toml::from_str(&saved).unwrap();
+        assert_eq!(reloaded.tools, config.tools);
+    }
+
+    #[test]
+    fn test_prompt_adapters() {
+        let toml_str = r#"
+[[prompts.adapters]]
+model = "*/qwen*"
+append = "Call one tool at a time."
+
+[[prompts.adapters]]
+model = "copilot/gpt-5*-mini"
+system = "Be brief."
+"#;
+        let config: ConfigFile = toml::from_str(toml_str).unwrap();
+        let [qwen, mini] = &config.prompts.adapters[..] else {
+            panic!("expected two adapters");
+        };
+        assert!(qwen.matches("local/qwen3-coder"));
+        assert!(!qwen.matches("local/llama3.2"));
+        assert!(mini.matches("copilot/gpt-5-mini"));
+        assert!(mini.matches("copilot/gpt-5.1-codex-mini"));
+        assert!(!mini.matches("copilot/gpt-5-mini-high"));
+        assert!(!mini.matches("openai/gpt-5-mini"));
+        assert_eq!(mini.system.as_deref(), Some("Be brief."));
+    }
+
+    #[test]
+    fn test_project_history_only_records_less() {
+        let global = HistoryConfig {
+            record: false,
+            excluded_patterns: vec!["(?i)password".to_string()],
+        };
+        let mut project = HistoryConfig {
+            record: true,
+            excluded_patterns: vec!["token".to_string()],
+        };
+        project.restrict(&global);
+        assert!(!project.record);
+        assert_eq!(project.excluded_patterns, vec!["token", "(?i)password"]);
+    }
+
+    #[test]
+    fn test_project_sandbox_mode_only_stricter() {
+        let mut project = SandboxMode::Yolo;
+        project.restrict(SandboxMode::ReadWrite);
+        assert_eq!(project, SandboxMode::ReadWrite);
+
+        let mut project = SandboxMode::ReadOnly;
+        project.restrict(SandboxMode::ReadWrite);
+        assert_eq!(project, SandboxMode::ReadOnly);
+    }
+
+    #[test]
+    fn test_merge_project_tables() {
+        let mut base: toml::Table = toml::from_str(
+            r#"
+default-model = "claude/claude-sonnet-4-5"
+[auto-compact]
+enabled = true
+threshold = 0.75
+[[mcp.servers]]
+name = "fs"
+command = ["mcp-fs"]
+[[mcp.servers]]
+name = "web"
+command = ["mcp-web"]
+"#,
+        )
+        .unwrap();
+        let overlay: toml::Table = toml::from_str(
+            r#"
+default-model = "zen/big-pickle"
+[auto-compact]
+threshold = 0.5
+[[mcp.servers]]
+name = "web"
+command = ["mcp-web", "--project"]
+[[mcp.servers]]
+name = "db"
+command = ["mcp-db"]
+"#,
+        )
+        .unwrap();
+        merge_tables(&mut base, overlay);
+
+        assert_eq!(base["default-model"].as_str(), Some("zen/big-pickle"));
+        assert_eq!(base["auto-compact"]["enabled"].as_bool(), Some(true));
+        assert_eq!(base["auto-compact"]["threshold"].as_float(), Some(0.5));
+        let servers = base["mcp"]["servers"].as_array().unwrap();
+        let names: Vec<_> = servers.iter().filter_map(entry_name).collect();
+        assert_eq!(names, vec!["fs", "web", "db"]);
+        assert_eq!(servers[1]["command"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_strip_project_overlay() {
+        let global: toml::Table = toml::from_str(r#"default-model = "claude/a""#).unwrap();
+        let mut saved: toml::Table = toml::from_str(
+            r#"
+default-model = "zen/b"
+disabled-tools = ["bash"]
+"#,
+        )
+        .unwrap();
+        let overlay = vec![
+            (
+                "default-model".to_string(),
+                Some(toml::Value::String("zen/b".to_string())),
+            ),
+            (
+                "disabled-tools".to_string(),
+                Some(toml::Value::Array(vec![toml::Value::String(
+                    "bash".to_string(),
+                )])),
+            ),
+        ];
+        strip_project_overlay(&mut saved, &overlay, &global);
+        assert_eq!(saved["default-model"].as_str(), Some("claude/a"));
+        assert!(!saved.contains_key("disabled-tools"));
+
+        // A value changed after loading is kept.
+        let mut saved: toml::Table = toml::from_str(r#"default-model = "zen/c""#).unwrap();
+        strip_project_overlay(&mut saved, &overlay, &global);
+        assert_eq!(saved["default-model"].as_str(), Some("zen/c"));
+    }
+
     #[test]
     fn test_config_serialization_uses_hyphens() {
         // Test that ZenProviderConfig serializes with hyphens
@@ -939,6 +2522,7 @@ mod tests {
             temperature: Some(0.7),
             top_p: None,
             max_tokens: Some(1000),
+            context_window: None,
         };
         let toml = toml::to_string(&model_config).unwrap();
         assert!(toml.contains("id = "), "Expected 'id'");
@@ -957,6 +2541,7 @@ mod tests {
             temperature: None,
             top_p: None,
             max_tokens: None,
+            context_window: None,
         };
         assert_eq!(model_config_no_name.display_name(), "fallback-id");
         assert_eq!(model_config.display_name(), "Test Model");
@@ -1015,6 +2600,17 @@ expires-at = 12345
         assert!(matches!(config.default_model, DefaultModel::LastUsed));
     }
 
+    #[test]
+    fn test_fallback_models() {
+        let toml_str = r#"fallback-models = ["openai/gpt-5", "zen/big-pickle"]"#;
+        let config: ConfigFile = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.fallback_models,
+            vec!["openai/gpt-5", "zen/big-pickle"]
+        );
+        assert!(ConfigFile::default().fallback_models.is_empty());
+    }
+
     #[test]
     fn test_default_model_specific() {
         // Test parsing a specific model string
@@ -1046,6 +2642,26 @@ expires-at = 12345
         assert!(!config.show_image_previews);
     }
 
+    #[test]
+    fn test_rate_limit_fallback_parsing() {
+        let config: ConfigFile = toml::from_str("").unwrap();
+        assert!(config.rate_limit_fallback.model.is_none());
+        assert_eq!(config.rate_limit_fallback.threshold, 0.9);
+
+        let toml_str = r#"
+[rate-limit-fallback]
+model = "claude/claude-haiku-4-5"
+auto = true
+"#;
+        let config: ConfigFile = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.rate_limit_fallback.model.as_deref(),
+            Some("claude/claude-haiku-4-5")
+        );
+        assert!(config.rate_limit_fallback.auto);
+        assert_eq!(config.rate_limit_fallback.threshold, 0.9);
+    }
+
     #[test]
     fn test_default_model_serialization() {
         // Test that LastUsed serializes correctly
@@ -1088,6 +2704,7 @@ expires-at = 12345
                     temperature: None,
                     top_p: None,
                     max_tokens: None,
+                    context_window: None,
                 },
                 ModelConfig {
                     id: "claude-opus-4-5-thinking".to_string(),
@@ -1096,6 +2713,7 @@ expires-at = 12345
                     temperature: None,
                     top_p: None,
                     max_tokens: None,
+                    context_window: None,
                 },
             ],
         };
@@ -1123,4 +2741,47 @@ expires-at = 12345
         let by_id = config.get_model_config("claude-opus-4-5-thinking");
         assert!(by_id.is_none());
     }
+
+    #[test]
+    fn test_write_atomic_keeps_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        let backups = dir.path().join(BACKUP_DIR);
+
+        write_atomic(&path, "a = 1\n", Some(&backups)).unwrap();
+        assert!(list_backups(&backups).is_empty());
+
+        // Unchanged content doesn't create a backup.
+        write_atomic(&path, "a = 1\n", Some(&backups)).unwrap();
+        assert!(list_backups(&backups).is_empty());
+
+        write_atomic(&path, "a = 2\n", Some(&backups)).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a = 2\n");
+        assert!(!path.with_extension("toml.tmp").exists());
+        let listed = list_backups(&backups);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(fs::read_to_string(&listed[0]).unwrap(), "a = 1\n");
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&listed[0]).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Automatic saves skip the backup, and a backup identical to the
+        // newest one isn't made again.
+        write_atomic(&path, "a = 1\n", None).unwrap();
+        assert_eq!(list_backups(&backups).len(), 1);
+        write_atomic(&path, "a = 2\n", Some(&backups)).unwrap();
+        assert_eq!(list_backups(&backups).len(), 1);
+
+        for i in 3..(MAX_BACKUPS + 5) {
+            write_atomic(&path, &format!("a = {}\n", i), Some(&backups)).unwrap();
+        }
+        let listed = list_backups(&backups);
+        assert_eq!(listed.len(), MAX_BACKUPS);
+        assert_eq!(
+            fs::read_to_string(&listed[0]).unwrap(),
+            format!("a = {}\n", MAX_BACKUPS + 3)
+        );
+    }
 }
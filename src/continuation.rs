@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Continuation of responses cut off by the output token limit.
+//!
+//! When a response stops with `StopReason::MaxTokens`, the chat loop asks the model to
+//! pick up where it stopped. Once the continuation arrives it is stitched onto the
+//! truncated response so the conversation holds a single assistant message, as if the
+//! response had never been interrupted.
+
+use crate::provider::{ContentBlock, Message, MessageContent, Role};
+
+/// Prompt sent to ask the model to continue a truncated response.
+pub(crate) const PROMPT: &str = "Your previous response was cut off by the output token limit. \
+Continue exactly where it stopped, without repeating any of it and without preamble. \
+If it stopped inside a code block, continue inside that code block.";
+
+/// Longest overlap checked when the continuation repeats the end of the response.
+const MAX_OVERLAP: usize = 500;
+
+/// Shortest repeated text treated as overlap, so common short words aren't dropped.
+const MIN_OVERLAP: usize = 8;
+
+/// Merge the continuation following the prompt at `prompt_index` into the truncated
+/// assistant message before it, removing the prompt.
+///
+/// Messages after the continuation (such as tool results) are kept.
+pub(crate) fn stitch(messages: &mut Vec<Message>, prompt_index: usize) {
+    let valid = prompt_index > 0
+        && prompt_index + 1 < messages.len()
+        && messages[prompt_index - 1].role == Role::Assistant
+        && messages[prompt_index].role == Role::User
+        && messages[prompt_index + 1].role == Role::Assistant;
+    if !valid {
+        return;
+    }
+
+    messages.remove(prompt_index);
+    let continuation = messages.remove(prompt_index);
+    let truncated = &mut messages[prompt_index - 1];
+
+    let mut blocks = into_blocks(std::mem::replace(
+        &mut truncated.content,
+        MessageContent::Blocks(Vec::new()),
+    ));
+    for block in into_blocks(continuation.content) {
+        match (blocks.last_mut(), block) {
+            // The model's reasoning about how to continue isn't part of the response.
+            (_, ContentBlock::Thinking { .. }) => {}
+            (Some(ContentBlock::Text { text }), ContentBlock::Text { text: more }) => {
+                join_text(text, &more);
+            }
+            (_, block) => blocks.push(block),
+        }
+    }
+    truncated.content = MessageContent::Blocks(blocks);
+}
+
+fn into_blocks(content: MessageContent) -> Vec<ContentBlock> {
+    match content {
+        MessageContent::Blocks(blocks) => blocks,
+        MessageContent::Text(text) => vec![ContentBlock::Text { text }],
+    }
+}
+
+/// Append `more` to `text`, dropping a re-opened code fence or repeated overlap.
+fn join_text(text: &mut String, more: &str) {
+    let mut more = more;
+
+    // Models often restart the code block they were in; the fence is already open.
+    if in_code_block(text) {
+        let trimmed = more.trim_start_matches('\n');
+        if trimmed.starts_with("```")
+            && let Some((_, rest)) = trimmed.split_once('\n')
+        {
+            more = rest;
+        }
+    }
+
+    let overlap = (1..=MAX_OVERLAP.min(text.len()).min(more.len()))
+        .rev()
+        .filter(|&n| text.is_char_boundary(text.len() - n) && more.is_char_boundary(n))
+        .find(|&n| text.ends_with(&more[..n]) && more[..n].trim().len() >= MIN_OVERLAP);
+    if let Some(n) = overlap {
+        more = &more[n..];
+    }
+
+    text.push_str(more);
+}
+
+/// Whether `text` ends inside an unclosed fenced code block.
+fn in_code_block(text: &str) -> bool {
+    text.lines()
+        .filter(|line| line.trim_start().starts_with("```"))
+        .count()
+        % 2
+        == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(message: &Message) -> &str {
+        match &message.content {
+            MessageContent::Blocks(blocks) => match blocks.as_slice() {
+                [ContentBlock::Text { text }] => text,
+                _ => panic!("expected a single text block"),
+            },
+            MessageContent::Text(text) => text,
+        }
+    }
+
+    #[test]
+    fn test_stitch_merges_text() {
+        let mut messages = vec![
+            Message::user("write it"),
+            Message::assistant_blocks(vec![ContentBlock::Text {
+                text: "fn main() {\n    println!(\"hel".to_string(),
+            }]),
+            Message::user(PROMPT),
+            Message::assistant_blocks(vec![
+                ContentBlock::Thinking {
+                    thinking: "continuing".to_string(),
+                    provider_data: None,
+                },
+                ContentBlock::Text {
+                    text: "lo\");\n}\n".to_string(),
+                },
+            ]),
+        ];
+        stitch(&mut messages, 2);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(
+            text(&messages[1]),
+            "fn main() {\n    println!(\"hello\");\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_stitch_ignores_mismatched_roles() {
+        let mut messages = vec![Message::user("a"), Message::user(PROMPT)];
+        stitch(&mut messages, 1);
+        assert_eq!(messages.len(), 2);
+    }
+
+    #[test]
+    fn test_join_text_drops_reopened_fence() {
+        let mut text = "Here:\n```rust\nlet a = 1;\n".to_string();
+        join_text(&mut text, "```rust\nlet b = 2;\n```\n");
+        assert_eq!(text, "Here:\n```rust\nlet a = 1;\nlet b = 2;\n```\n");
+    }
+
+    #[test]
+    fn test_join_text_drops_overlap() {
+        let mut text = "The quick brown fox".to_string();
+        join_text(&mut text, "brown fox jumps");
+        assert_eq!(text, "The quick brown fox jumps");
+
+        let mut text = "print the ".to_string();
+        join_text(&mut text, "the result");
+        assert_eq!(text, "print the the result");
+    }
+}
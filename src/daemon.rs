@@ -0,0 +1,538 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Long-running agent daemon.
+//!
+//! `henri daemon` starts the MCP and LSP servers for its working directory once
+//! and then serves named conversations over a unix socket, keeping each one's
+//! provider connection and history in memory. `henri attach <name>` is a thin
+//! client that sends prompts to a conversation and prints the streamed events,
+//! so quick questions don't wait for the servers to start.
+//!
+//! The protocol is one JSON object per line in each direction. A connection may
+//! send any number of requests; the events of a prompt end with `done` or
+//! `failed`. `interrupt` may be sent while a prompt is running.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{Mutex, mpsc};
+
+use crate::agent::{Agent, Event};
+use crate::cli::escapes::sanitize;
+
+/// Request from a client.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Request {
+    /// Ask for the daemon's working directory and model
+    Hello,
+    /// Send a prompt to a conversation, creating it if needed
+    Prompt { conversation: String, text: String },
+    /// Stop the prompt running in a conversation
+    Interrupt { conversation: String },
+    /// Forget the history of a conversation
+    Clear { conversation: String },
+    /// List the conversations
+    List,
+}
+
+/// Response or event sent to a client.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Response {
+    Hello {
+        cwd: String,
+        model: Option<String>,
+    },
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+    },
+    ToolCall {
+        description: String,
+    },
+    ToolOutput {
+        text: String,
+    },
+    ToolResult {
+        tool_name: String,
+        is_error: bool,
+        summary: Option<String>,
+    },
+    Info {
+        message: String,
+    },
+    Warning {
+        message: String,
+    },
+    Error {
+        message: String,
+    },
+    /// The prompt finished
+    Done {
+        answer: String,
+    },
+    /// The prompt or request failed
+    Failed {
+        message: String,
+    },
+    Cleared,
+    Conversations {
+        names: Vec<String>,
+    },
+}
+
+impl Response {
+    fn from_event(event: &Event) -> Self {
+        match event {
+            Event::Text(text) => Response::Text { text: text.clone() },
+            Event::Thinking(text) => Response::Thinking { text: text.clone() },
+            Event::ToolCall { description } => Response::ToolCall {
+                description: description.clone(),
+            },
+            Event::ToolOutput(text) => Response::ToolOutput { text: text.clone() },
+            Event::ToolResult {
+                tool_name,
+                is_error,
+                summary,
+            } => Response::ToolResult {
+                tool_name: tool_name.clone(),
+                is_error: *is_error,
+                summary: summary.clone(),
+            },
+            Event::Info(message) => Response::Info {
+                message: message.clone(),
+            },
+            Event::Warning(message) => Response::Warning {
+                message: message.clone(),
+            },
+            Event::Error(message) => Response::Error {
+                message: message.clone(),
+            },
+        }
+    }
+}
+
+/// Path of the daemon's socket.
+pub(crate) fn socket_path() -> PathBuf {
+    dirs::home_dir()
+        .map(|home| home.join(".cache").join("henri").join("daemon.sock"))
+        .unwrap_or_else(|| PathBuf::from(".cache/henri/daemon.sock"))
+}
+
+struct Conversation {
+    agent: Mutex<Agent>,
+    interrupted: Arc<AtomicBool>,
+}
+
+struct Daemon {
+    cwd: PathBuf,
+    model: Option<String>,
+    conversations: Mutex<HashMap<String, Arc<Conversation>>>,
+}
+
+impl Daemon {
+    async fn conversation(&self, name: &str) -> Result<Arc<Conversation>, String> {
+        let mut conversations = self.conversations.lock().await;
+        if let Some(conversation) = conversations.get(name) {
+            return Ok(Arc::clone(conversation));
+        }
+        let agent = Agent::new(self.model.as_deref()).map_err(|e| e.to_string())?;
+        let conversation = Arc::new(Conversation {
+            interrupted: agent.interrupt_handle(),
+            agent: Mutex::new(agent),
+        });
+        conversations.insert(name.to_string(), Arc::clone(&conversation));
+        Ok(conversation)
+    }
+
+    async fn handle(&self, request: Request, tx: &mpsc::UnboundedSender<Response>) {
+        match request {
+            Request::Hello => {
+                let _ = tx.send(Response::Hello {
+                    cwd: self.cwd.display().to_string(),
+                    model: self.model.clone(),
+                });
+            }
+            Request::Prompt { conversation, text } => {
+                let conversation = match self.conversation(&conversation).await {
+                    Ok(conversation) => conversation,
+                    Err(message) => {
+                        let _ = tx.send(Response::Failed { message });
+                        return;
+                    }
+                };
+                let mut agent = conversation.agent.lock().await;
+                let events = tx.clone();
+                agent.on_event(move |event| {
+                    let _ = events.send(Response::from_event(event));
+                });
+                let response = match agent.send(&text).await {
+                    Ok(answer) => Response::Done { answer },
+                    Err(e) => Response::Failed {
+                        message: e.to_string(),
+                    },
+                };
+                let _ = tx.send(response);
+            }
+            Request::Interrupt { conversation } => {
+                if let Some(conversation) = self.conversations.lock().await.get(&conversation) {
+                    conversation.interrupted.store(true, Ordering::SeqCst);
+                }
+            }
+            Request::Clear { conversation } => {
+                if let Some(conversation) = self.conversations.lock().await.get(&conversation) {
+                    conversation.agent.lock().await.clear();
+                }
+                let _ = tx.send(Response::Cleared);
+            }
+            Request::List => {
+                let mut names: Vec<String> =
+                    self.conversations.lock().await.keys().cloned().collect();
+                names.sort();
+                let _ = tx.send(Response::Conversations { names });
+            }
+        }
+    }
+}
+
+/// Serve a client until it disconnects. Interrupts are handled as soon as they
+/// arrive; other requests run in order.
+async fn serve_client(daemon: Arc<Daemon>, stream: UnixStream) {
+    let (read, mut write) = stream.into_split();
+    let (tx, mut rx) = mpsc::unbounded_channel::<Response>();
+    let (requests_tx, mut requests) = mpsc::unbounded_channel::<Request>();
+
+    let writer = tokio::spawn(async move {
+        while let Some(response) = rx.recv().await {
+            let Ok(mut line) = serde_json::to_string(&response) else {
+                continue;
+            };
+            line.push('\n');
+            if write.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let reader_daemon = Arc::clone(&daemon);
+    let reader_tx = tx.clone();
+    let reader = tokio::spawn(async move {
+        let mut lines = BufReader::new(read).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            match serde_json::from_str::<Request>(&line) {
+                Ok(request @ Request::Interrupt { .. }) => {
+                    reader_daemon.handle(request, &reader_tx).await;
+                }
+                Ok(request) => {
+                    if requests_tx.send(request).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    let _ = reader_tx.send(Response::Failed {
+                        message: format!("Invalid request: {}", e),
+                    });
+                }
+            }
+        }
+    });
+
+    while let Some(request) = requests.recv().await {
+        daemon.handle(request, &tx).await;
+    }
+    drop(tx);
+    reader.abort();
+    let _ = writer.await;
+}
+
+/// Run the daemon in the foreground until interrupted.
+pub(crate) async fn run(model: Option<String>) -> std::io::Result<()> {
+    let path = socket_path();
+    if UnixStream::connect(&path).await.is_ok() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AddrInUse,
+            format!("A henri daemon is already listening on {}", path.display()),
+        ));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // A socket left behind by a daemon that didn't exit cleanly
+    let _ = std::fs::remove_file(&path);
+
+    // Fail early on a bad model or missing provider rather than on the first prompt
+    let agent = Agent::new(model.as_deref()).map_err(|e| std::io::Error::other(e.to_string()))?;
+    let cwd = std::env::current_dir()?;
+    agent.start_servers(&cwd).await;
+
+    let listener = UnixListener::bind(&path)?;
+    restrict_permissions(&path)?;
+    eprintln!(
+        "henri daemon listening on {} (working directory {})",
+        path.display(),
+        cwd.display()
+    );
+
+    let daemon = Arc::new(Daemon {
+        cwd,
+        model,
+        conversations: Mutex::new(HashMap::new()),
+    });
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve_client(Arc::clone(&daemon), stream));
+                }
+                Err(e) => eprintln!("Failed to accept connection: {}", e),
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+
+/// Only the user may connect; the daemon runs tools on their behalf.
+fn restrict_permissions(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+}
+
+/// A connection to a running daemon.
+pub(crate) struct Client {
+    lines: tokio::io::Lines<BufReader<tokio::net::unix::OwnedReadHalf>>,
+    write: tokio::net::unix::OwnedWriteHalf,
+}
+
+impl Client {
+    pub(crate) async fn connect() -> std::io::Result<Self> {
+        let path = socket_path();
+        let stream = UnixStream::connect(&path).await.map_err(|e| {
+            std::io::Error::new(
+                e.kind(),
+                format!(
+                    "No henri daemon is listening on {} ({}). Start one with `henri daemon`.",
+                    path.display(),
+                    e
+                ),
+            )
+        })?;
+        let (read, write) = stream.into_split();
+        Ok(Self {
+            lines: BufReader::new(read).lines(),
+            write,
+        })
+    }
+
+    pub(crate) async fn send(&mut self, request: &Request) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(request).map_err(std::io::Error::other)?;
+        line.push('\n');
+        self.write.write_all(line.as_bytes()).await
+    }
+
+    /// The next response, or None when the daemon closed the connection.
+    pub(crate) async fn receive(&mut self) -> std::io::Result<Option<Response>> {
+        loop {
+            let Some(line) = self.lines.next_line().await? else {
+                return Ok(None);
+            };
+            if let Ok(response) = serde_json::from_str(&line) {
+                return Ok(Some(response));
+            }
+        }
+    }
+}
+
+/// Print a response from the daemon. Returns true when it ends a request.
+///
+/// Everything the daemon sends can carry model or tool text, so it is
+/// sanitized before printing like any other output.
+fn print_response(response: Response) -> bool {
+    match response {
+        Response::Hello { cwd, model } => {
+            let model = model.unwrap_or_else(|| "default model".to_string());
+            println!(
+                "{}",
+                sanitize(&format!("Attached to henri daemon in {} ({})", cwd, model)).dimmed()
+            );
+            true
+        }
+        Response::Text { text } => {
+            print!("{}", sanitize(&text));
+            let _ = std::io::stdout().flush();
+            false
+        }
+        Response::Thinking { text } => {
+            print!("{}", sanitize(&text).dimmed());
+            let _ = std::io::stdout().flush();
+            false
+        }
+        Response::ToolCall { description } => {
+            println!("\n{}", format!("▶ {}", sanitize(&description)).cyan());
+            false
+        }
+        Response::ToolOutput { .. } => false,
+        Response::ToolResult {
+            tool_name,
+            is_error,
+            summary,
+        } => {
+            if is_error {
+                let summary = summary.unwrap_or_default();
+                println!(
+                    "{}",
+                    sanitize(&format!("✗ {} {}", tool_name, summary)).red()
+                );
+            } else if let Some(summary) = summary {
+                println!("{}", format!("✓ {}", sanitize(&summary)).dimmed());
+            }
+            false
+        }
+        Response::Info { message } => {
+            println!("{}", sanitize(&message).dimmed());
+            false
+        }
+        Response::Warning { message } => {
+            println!("{}", sanitize(&message).yellow());
+            false
+        }
+        Response::Error { message } => {
+            eprintln!("{}", sanitize(&message).red());
+            false
+        }
+        Response::Done { .. } => {
+            println!();
+            true
+        }
+        Response::Failed { message } => {
+            eprintln!("{}", format!("Error: {}", sanitize(&message)).red());
+            true
+        }
+        Response::Cleared => {
+            println!("{}", "Conversation cleared.".dimmed());
+            true
+        }
+        Response::Conversations { names } => {
+            println!("{}", sanitize(&names.join("\n")));
+            true
+        }
+    }
+}
+
+/// Send a request and print responses until it completes. Ctrl+C interrupts
+/// the prompt instead of exiting.
+async fn round_trip(
+    client: &mut Client,
+    conversation: &str,
+    request: Request,
+) -> std::io::Result<()> {
+    client.send(&request).await?;
+    loop {
+        tokio::select! {
+            response = client.receive() => match response? {
+                Some(response) => {
+                    if print_response(response) {
+                        return Ok(());
+                    }
+                }
+                None => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionAborted,
+                        "The henri daemon closed the connection",
+                    ));
+                }
+            },
+            _ = tokio::signal::ctrl_c() => {
+                client
+                    .send(&Request::Interrupt {
+                        conversation: conversation.to_string(),
+                    })
+                    .await?;
+            }
+        }
+    }
+}
+
+/// Attach to conversation `name` on the running daemon. With a prompt, send it
+/// and exit; otherwise read prompts from stdin until `/quit` or end of input.
+pub(crate) async fn attach(name: &str, prompt: Option<String>) -> std::io::Result<()> {
+    let mut client = Client::connect().await?;
+
+    if let Some(text) = prompt {
+        let request = Request::Prompt {
+            conversation: name.to_string(),
+            text,
+        };
+        return round_trip(&mut client, name, request).await;
+    }
+
+    round_trip(&mut client, name, Request::Hello).await?;
+    println!(
+        "{}",
+        format!(
+            "Conversation '{}'. /clear to start over, /quit to detach.",
+            name
+        )
+        .dimmed()
+    );
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        print!("{} ", ">".green());
+        std::io::stdout().flush()?;
+        let Some(line) = lines.next_line().await? else {
+            println!();
+            return Ok(());
+        };
+        let request = match line.trim() {
+            "" => continue,
+            "/quit" | "/exit" => return Ok(()),
+            "/clear" => Request::Clear {
+                conversation: name.to_string(),
+            },
+            "/list" => Request::List,
+            text => Request::Prompt {
+                conversation: name.to_string(),
+                text: text.to_string(),
+            },
+        };
+        round_trip(&mut client, name, request).await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_format() {
+        let request: Request =
+            serde_json::from_str(r#"{"type":"prompt","conversation":"notes","text":"hi"}"#)
+                .unwrap();
+        assert!(matches!(
+            request,
+            Request::Prompt { ref conversation, ref text } if conversation == "notes" && text == "hi"
+        ));
+
+        let response = Response::from_event(&Event::ToolCall {
+            description: "bash: ls".to_string(),
+        });
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"type":"tool_call","description":"bash: ls"}"#
+        );
+    }
+}
@@ -42,8 +42,19 @@ fn extract_error_message(json: &serde_json::Value) -> Option<String> {
     None
 }
 
+/// Fragments of the errors providers return when the context window overflows.
+const CONTEXT_OVERFLOW_MESSAGES: &[&str] = &[
+    "prompt is too long",
+    "context_length_exceeded",
+    "maximum context length",
+    "exceeds the context window",
+    "model_max_prompt_tokens_exceeded",
+    "input token count",
+];
+
+/// Errors returned by henri.
 #[derive(Error, Debug)]
-pub(crate) enum Error {
+pub enum Error {
     #[error("Authentication error: {0}")]
     Auth(String),
 
@@ -74,6 +85,16 @@ pub(crate) enum Error {
     #[error("Retryable API error: {status} - {message}")]
     Retryable { status: u16, message: String },
 
+    #[error("Rate limited: {status} - {message} (retry after {}s)", retry_after.as_secs())]
+    RateLimited {
+        status: u16,
+        message: String,
+        retry_after: std::time::Duration,
+    },
+
+    #[error("Stream stalled: no data received for {0}s")]
+    Stalled(u64),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -95,7 +116,9 @@ impl Error {
     /// For API errors, extracts a human-readable message from JSON error responses.
     pub(crate) fn display_message(&self) -> String {
         match self {
-            Error::Api { message, .. } | Error::Retryable { message, .. } => {
+            Error::Api { message, .. }
+            | Error::Retryable { message, .. }
+            | Error::RateLimited { message, .. } => {
                 // Try to extract a concise message from JSON error responses
                 if let Ok(json) = serde_json::from_str::<serde_json::Value>(message) {
                     extract_error_message(&json).unwrap_or_else(|| message.clone())
@@ -107,9 +130,42 @@ impl Error {
         }
     }
 
+    /// Check if the request was rejected for not fitting in the model's context window.
+    pub(crate) fn is_context_overflow(&self) -> bool {
+        let Error::Api { status, message } = self else {
+            return false;
+        };
+        if *status == 413 {
+            return true;
+        }
+        let message = message.to_lowercase();
+        CONTEXT_OVERFLOW_MESSAGES
+            .iter()
+            .any(|pattern| message.contains(pattern))
+    }
+
     /// Check if this error is retryable (server overloaded, timeout, etc.)
     pub(crate) fn is_retryable(&self) -> bool {
-        matches!(self, Error::Retryable { .. } | Error::Http(_))
+        matches!(
+            self,
+            Error::Retryable { .. }
+                | Error::RateLimited { .. }
+                | Error::Http(_)
+                | Error::Stalled(_)
+        )
+    }
+
+    /// Attach the wait a rate-limited response asked for, turning a
+    /// retryable error into [`Error::RateLimited`].
+    pub(crate) fn with_retry_after(self, retry_after: Option<std::time::Duration>) -> Self {
+        match (self, retry_after) {
+            (Error::Retryable { status, message }, Some(retry_after)) => Error::RateLimited {
+                status,
+                message,
+                retry_after,
+            },
+            (error, _) => error,
+        }
     }
 }
 
@@ -119,6 +175,25 @@ pub(crate) type Result<T> = std::result::Result<T, Error>;
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_context_overflow() {
+        let err = Error::Api {
+            status: 400,
+            message: r#"{"type":"error","error":{"type":"invalid_request_error","message":"prompt is too long: 215000 tokens > 200000 maximum"}}"#.to_string(),
+        };
+        assert!(err.is_context_overflow());
+        let err = Error::Api {
+            status: 400,
+            message: r#"{"error":{"code":"context_length_exceeded"}}"#.to_string(),
+        };
+        assert!(err.is_context_overflow());
+        let err = Error::Api {
+            status: 400,
+            message: "invalid tool schema".to_string(),
+        };
+        assert!(!err.is_context_overflow());
+    }
+
     #[test]
     fn test_display_message_google_api_error() {
         // Google API error format with status and message
@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! The `/explain` command.
+//!
+//! `/explain [depth] <file[:range]>` reads the file, or the given lines of it,
+//! and sends it to the model with a request for an explanation. The depth is
+//! one of `summary`, `detailed` (the default) or `line-by-line`; a range is
+//! `START-END`, `START-` or a single line.
+
+use std::path::Path;
+
+/// Depth keywords, in the order offered for completion.
+pub(crate) const DEPTHS: &[&str] = &["summary", "detailed", "line-by-line"];
+
+/// Largest excerpt sent, to keep the prompt within reason.
+const MAX_LINES: usize = 2000;
+
+/// How much detail to ask for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Depth {
+    Summary,
+    Detailed,
+    LineByLine,
+}
+
+impl Depth {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "summary" => Some(Depth::Summary),
+            "detailed" => Some(Depth::Detailed),
+            "line-by-line" => Some(Depth::LineByLine),
+            _ => None,
+        }
+    }
+
+    fn instruction(self) -> &'static str {
+        match self {
+            Depth::Summary => {
+                "Give a short summary of what this code does and how it fits into the project, \
+                 in a few sentences."
+            }
+            Depth::Detailed => {
+                "Explain what this code does, how it works and why it is written this way. \
+                 Point out the main data structures, control flow and anything surprising."
+            }
+            Depth::LineByLine => {
+                "Walk through this code line by line (grouping trivial lines), citing line \
+                 numbers, and explain what each part does."
+            }
+        }
+    }
+}
+
+/// Parsed `/explain` arguments.
+#[derive(Debug, PartialEq, Eq)]
+struct Request {
+    path: String,
+    /// First line and optional last line, 1-based and inclusive
+    range: Option<(usize, Option<usize>)>,
+    depth: Depth,
+}
+
+/// Split a `START-END`, `START-` or `LINE` suffix off `target`.
+fn split_range(target: &str) -> (&str, Option<(usize, Option<usize>)>) {
+    let Some((path, range)) = target.rsplit_once(':') else {
+        return (target, None);
+    };
+    let parsed = match range.split_once('-') {
+        Some((start, "")) => start.parse().ok().map(|s| (s, None)),
+        Some((start, end)) => start
+            .parse()
+            .ok()
+            .zip(end.parse().ok())
+            .map(|(s, e)| (s, Some(e))),
+        None => range.parse().ok().map(|l| (l, Some(l))),
+    };
+    match parsed {
+        Some(range) if !path.is_empty() => (path, Some(range)),
+        _ => (target, None),
+    }
+}
+
+fn parse(args: &str) -> Result<Request, String> {
+    let mut depth = None;
+    let mut target = None;
+    for word in args.split_whitespace() {
+        match Depth::from_name(word) {
+            Some(d) if depth.is_none() => depth = Some(d),
+            _ if target.is_none() => target = Some(word),
+            _ => return Err(usage()),
+        }
+    }
+    let target = target.ok_or_else(usage)?;
+    let (path, range) = split_range(target);
+    if let Some((start, end)) = range
+        && (start == 0 || end.is_some_and(|end| end < start))
+    {
+        return Err(format!("Invalid line range in {}", target));
+    }
+    Ok(Request {
+        path: path.to_string(),
+        range,
+        depth: depth.unwrap_or(Depth::Detailed),
+    })
+}
+
+fn usage() -> String {
+    format!("Usage: /explain [{}] <file[:start-end]>", DEPTHS.join("|"))
+}
+
+/// Number the lines of the requested range of `content`.
+fn excerpt(content: &str, range: Option<(usize, Option<usize>)>) -> Result<String, String> {
+    let total = content.lines().count();
+    let (start, end) = match range {
+        Some((start, end)) => (start, end.unwrap_or(total).min(total)),
+        None => (1, total),
+    };
+    if start > total.max(1) {
+        return Err(format!("Line {} is beyond the end of the file", start));
+    }
+    if end + 1 - start > MAX_LINES {
+        return Err(format!(
+            "Too many lines ({}); give a range of at most {} lines",
+            end + 1 - start,
+            MAX_LINES
+        ));
+    }
+    let width = end.to_string().len();
+    Ok(content
+        .lines()
+        .enumerate()
+        .skip(start - 1)
+        .take(end + 1 - start)
+        .map(|(i, line)| format!("{:>width$}  {}\n", i + 1, line))
+        .collect())
+}
+
+/// Build the prompt for `/explain <args>`, reading paths relative to `cwd`.
+///
+/// Returns a message for the user if the arguments or the file are invalid.
+pub(crate) fn prompt(args: &str, cwd: &Path) -> Result<String, String> {
+    let request = parse(args)?;
+    let path = cwd.join(&request.path);
+    let content =
+        std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", request.path, e))?;
+    let content =
+        String::from_utf8(content).map_err(|_| format!("{} is not a text file", request.path))?;
+    let lines = excerpt(&content, request.range)?;
+
+    let location = match request.range {
+        Some((start, Some(end))) if start == end => format!("line {} of {}", start, request.path),
+        Some((start, Some(end))) => format!("lines {}-{} of {}", start, end, request.path),
+        Some((start, None)) => format!("{} from line {}", request.path, start),
+        None => request.path.clone(),
+    };
+    Ok(format!(
+        "Explain {}. {}\n\n```\n{}```",
+        location,
+        request.depth.instruction(),
+        lines
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("src/main.rs:10-20").unwrap(),
+            Request {
+                path: "src/main.rs".to_string(),
+                range: Some((10, Some(20))),
+                depth: Depth::Detailed,
+            }
+        );
+        assert_eq!(
+            parse("summary src/lib.rs:5-").unwrap().range,
+            Some((5, None))
+        );
+        assert_eq!(
+            parse("src/lib.rs:7 line-by-line").unwrap().depth,
+            Depth::LineByLine
+        );
+        // A colon that isn't followed by a range is part of the path
+        assert_eq!(parse("notes:draft.md").unwrap().path, "notes:draft.md");
+        assert!(parse("").is_err());
+        assert!(parse("summary").is_err());
+        assert!(parse("src/lib.rs:9-3").is_err());
+    }
+
+    #[test]
+    fn test_prompt_numbers_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let text: String = (1..=12).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(dir.path().join("a.txt"), text).unwrap();
+
+        let text = prompt("summary a.txt:9-10", dir.path()).unwrap();
+        assert!(text.starts_with("Explain lines 9-10 of a.txt. Give a short summary"));
+        assert!(text.ends_with("```\n 9  line 9\n10  line 10\n```"));
+
+        assert!(prompt("a.txt:20", dir.path()).is_err());
+        assert!(prompt("missing.txt", dir.path()).is_err());
+    }
+}
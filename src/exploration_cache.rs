@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Results of file reads made by delegated tasks, shared between them.
+//!
+//! Sub-agents exploring a codebase tend to read the same files. A read is
+//! keyed by the file's path, a hash of its content and the requested range,
+//! so a file that changed is never served from the cache. A task reading
+//! something again that it already got is told to look at its earlier result
+//! instead of being sent the same content twice.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::tools::ToolResult;
+
+/// Cached results kept before the cache is emptied.
+const MAX_ENTRIES: usize = 256;
+
+/// A read of part of a file with a given content.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Key {
+    path: PathBuf,
+    hash: u64,
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+impl Key {
+    pub(crate) fn new(
+        path: &Path,
+        content: &[u8],
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            hash: crate::seen_files::hash(content),
+            offset,
+            limit,
+        }
+    }
+}
+
+/// What the cache holds for a read.
+pub(crate) enum Lookup {
+    /// Not cached; read the file and [`ExplorationCache::insert`] the result.
+    Miss,
+    /// Read before by another task, with the same result.
+    Hit(ToolResult),
+    /// This task already got the result.
+    Repeat,
+}
+
+/// Counts shown by `/claude-usage`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+    pub repeats: u64,
+    /// Estimated tokens not sent again thanks to repeats
+    pub tokens_saved: u64,
+}
+
+impl Stats {
+    pub(crate) fn format(&self) -> String {
+        format!(
+            "{} hits, {} misses; {} repeated reads answered by reference (~{} tokens saved)",
+            self.hits, self.misses, self.repeats, self.tokens_saved
+        )
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    results: HashMap<Key, ToolResult>,
+    /// Keys already returned to each task
+    served: HashSet<(u64, Key)>,
+    stats: Stats,
+}
+
+/// Read results shared by the tasks of a session, through
+/// [`crate::services::Services`].
+#[derive(Default)]
+pub(crate) struct ExplorationCache {
+    inner: Mutex<Inner>,
+}
+
+impl ExplorationCache {
+    /// Look up a read of `key` by `task`.
+    pub(crate) fn lookup(&self, task: u64, key: &Key) -> Lookup {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(result) = inner.results.get(key).cloned() else {
+            inner.stats.misses += 1;
+            return Lookup::Miss;
+        };
+        if inner.served.insert((task, key.clone())) {
+            inner.stats.hits += 1;
+            Lookup::Hit(result)
+        } else {
+            inner.stats.repeats += 1;
+            inner.stats.tokens_saved += crate::usage::estimate_tokens(&result.content);
+            Lookup::Repeat
+        }
+    }
+
+    /// Remember the result of a read of `key` by `task`. Only successful text
+    /// results are kept.
+    pub(crate) fn insert(&self, task: u64, key: Key, result: &ToolResult) {
+        if result.is_error || result.data.is_some() {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner.results.len() >= MAX_ENTRIES {
+            inner.results.clear();
+            inner.served.clear();
+        }
+        inner.served.insert((task, key.clone()));
+        inner.results.insert(key, result.clone());
+    }
+
+    pub(crate) fn stats(&self) -> Stats {
+        self.inner.lock().unwrap().stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let cache = ExplorationCache::default();
+        let path = Path::new("/src/main.rs");
+        let key = Key::new(path, b"fn main() {}\n", None, None);
+
+        assert!(matches!(cache.lookup(1, &key), Lookup::Miss));
+        cache.insert(1, key.clone(), &ToolResult::success("a", "1: fn main() {}"));
+        assert!(matches!(cache.lookup(1, &key), Lookup::Repeat));
+        assert!(matches!(
+            cache.lookup(2, &key),
+            Lookup::Hit(result) if result.content == "1: fn main() {}"
+        ));
+
+        // Changed content or another range is a different read.
+        let changed = Key::new(path, b"fn main() { run() }\n", None, None);
+        assert!(matches!(cache.lookup(2, &changed), Lookup::Miss));
+        let range = Key::new(path, b"fn main() {}\n", Some(10), None);
+        assert!(matches!(cache.lookup(2, &range), Lookup::Miss));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.repeats), (1, 3, 1));
+    }
+}
@@ -0,0 +1,240 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Ratings of assistant answers.
+//!
+//! `/feedback up|down [note]` rates the last answer, as do Alt+Up and Alt+Down on
+//! an empty prompt. Ratings are appended to `~/.cache/henri/feedback.jsonl` with the
+//! model and the start of the prompt, and `henri stats feedback` summarizes them per
+//! model. Rating the same answer again replaces the earlier rating.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::provider::{ContentBlock, Message, MessageContent, Role};
+
+/// Characters of the prompt kept with a rating.
+const PROMPT_EXCERPT_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Rating {
+    Up,
+    Down,
+}
+
+impl Rating {
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "up" | "+" | "+1" | "good" | "yes" => Some(Rating::Up),
+            "down" | "-" | "-1" | "bad" | "no" => Some(Rating::Down),
+            _ => None,
+        }
+    }
+}
+
+/// One rating in the feedback log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct FeedbackRecord {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: Option<String>,
+    /// Number of the rated turn within the session, starting at 1
+    pub turn: usize,
+    pub provider: String,
+    pub model: String,
+    pub rating: Rating,
+    /// Start of the prompt that was answered
+    pub prompt: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Ratings of a single model.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct ModelFeedback {
+    pub up: usize,
+    pub down: usize,
+}
+
+impl ModelFeedback {
+    pub(crate) fn total(&self) -> usize {
+        self.up + self.down
+    }
+
+    /// Fraction of ratings that are thumbs-up.
+    pub(crate) fn satisfaction(&self) -> f64 {
+        if self.total() == 0 {
+            0.0
+        } else {
+            self.up as f64 / self.total() as f64
+        }
+    }
+}
+
+fn feedback_path() -> PathBuf {
+    dirs::home_dir()
+        .map(|home| home.join(".cache").join("henri").join("feedback.jsonl"))
+        .unwrap_or_else(|| PathBuf::from(".cache/henri/feedback.jsonl"))
+}
+
+/// The turn number and prompt of the last answered turn in `messages`.
+pub(crate) fn last_answered_turn(messages: &[Message]) -> Option<(usize, String)> {
+    let is_prompt = |m: &Message| m.role == Role::User && !m.is_tool_result_only();
+    let index = messages.iter().rposition(is_prompt)?;
+    if !messages[index..].iter().any(|m| m.role == Role::Assistant) {
+        return None;
+    }
+    let turn = messages[..=index].iter().filter(|m| is_prompt(m)).count();
+    let text = match &messages[index].content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Blocks(blocks) => blocks
+            .iter()
+            .filter_map(|b| match b {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some((turn, text.chars().take(PROMPT_EXCERPT_CHARS).collect()))
+}
+
+/// Append a rating to the feedback log.
+pub(crate) fn record(record: &FeedbackRecord) -> std::io::Result<()> {
+    let path = feedback_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string(record)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", json)
+}
+
+/// Load all ratings from the feedback log, oldest first.
+pub(crate) fn load() -> Vec<FeedbackRecord> {
+    let Ok(file) = File::open(feedback_path()) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// Aggregate ratings per `provider/model`, counting only the latest rating of
+/// each answer.
+pub(crate) fn aggregate(records: &[FeedbackRecord]) -> BTreeMap<String, ModelFeedback> {
+    let mut latest: BTreeMap<(Option<&str>, usize), &FeedbackRecord> = BTreeMap::new();
+    let mut unsessioned = Vec::new();
+    for record in records {
+        match &record.session_id {
+            Some(id) => {
+                latest.insert((Some(id.as_str()), record.turn), record);
+            }
+            None => unsessioned.push(record),
+        }
+    }
+
+    let mut stats: BTreeMap<String, ModelFeedback> = BTreeMap::new();
+    for record in latest.into_values().chain(unsessioned) {
+        let entry = stats
+            .entry(format!("{}/{}", record.provider, record.model))
+            .or_default();
+        match record.rating {
+            Rating::Up => entry.up += 1,
+            Rating::Down => entry.down += 1,
+        }
+    }
+    stats
+}
+
+/// Format aggregated ratings as a table, most satisfying models first.
+pub(crate) fn format_table(stats: &BTreeMap<String, ModelFeedback>) -> String {
+    let mut rows: Vec<(&String, &ModelFeedback)> = stats.iter().collect();
+    rows.sort_by(|a, b| {
+        b.1.satisfaction()
+            .total_cmp(&a.1.satisfaction())
+            .then(b.1.total().cmp(&a.1.total()))
+    });
+
+    let name_width = rows
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Model".len());
+    let mut out = format!(
+        "{:<name_width$}  {:>6}  {:>4}  {:>4}  {:>9}\n",
+        "Model", "Rated", "Up", "Down", "Satisfied"
+    );
+    for (name, s) in rows {
+        out.push_str(&format!(
+            "{:<name_width$}  {:>6}  {:>4}  {:>4}  {:>8.1}%\n",
+            name,
+            s.total(),
+            s.up,
+            s.down,
+            s.satisfaction() * 100.0,
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(session: &str, turn: usize, model: &str, rating: Rating) -> FeedbackRecord {
+        FeedbackRecord {
+            timestamp: Utc::now(),
+            session_id: Some(session.to_string()),
+            turn,
+            provider: "claude".to_string(),
+            model: model.to_string(),
+            rating,
+            prompt: String::new(),
+            note: None,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_keeps_latest_rating() {
+        let records = vec![
+            record("s1", 1, "opus", Rating::Down),
+            record("s1", 1, "opus", Rating::Up),
+            record("s1", 2, "opus", Rating::Down),
+            record("s2", 1, "haiku", Rating::Up),
+        ];
+        let stats = aggregate(&records);
+        assert_eq!(stats["claude/opus"], ModelFeedback { up: 1, down: 1 });
+        assert_eq!(stats["claude/haiku"].satisfaction(), 1.0);
+
+        let table = format_table(&stats);
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[1].starts_with("claude/haiku"));
+        assert!(lines[2].ends_with("50.0%"));
+    }
+
+    #[test]
+    fn test_last_answered_turn() {
+        let mut messages = vec![
+            Message::user("first"),
+            Message::assistant_blocks(vec![]),
+            Message::user("second\n  question"),
+        ];
+        assert_eq!(last_answered_turn(&messages), None);
+        messages.push(Message::assistant_blocks(vec![]));
+        assert_eq!(
+            last_answered_turn(&messages),
+            Some((2, "second question".to_string()))
+        );
+    }
+}
@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! `@path` file references in prompts.
+//!
+//! Each `@path` that names a file is read and appended to the prompt with line
+//! numbers, so the model has the contents without a `file_read` call. Images
+//! are sent as image blocks instead. Words that aren't files, such as
+//! `@provider/model` or `@server:uri`, are left to their own handlers.
+
+use std::path::Path;
+
+use crate::seen_files::SeenFiles;
+
+/// Lines included from one file before it is truncated.
+const MAX_LINES: usize = 2000;
+
+/// Bytes included from one file before it is truncated.
+const MAX_BYTES: usize = 50 * 1024;
+
+/// Find the `@path` words in `input`, without trailing punctuation.
+fn file_references(input: &str) -> Vec<&str> {
+    let mut refs: Vec<&str> = Vec::new();
+    for word in input.split_whitespace() {
+        let Some(path) = word.strip_prefix('@') else {
+            continue;
+        };
+        let path = path.trim_end_matches([',', '.', ';', ':', '!', '?', ')']);
+        if !path.is_empty() && !refs.contains(&path) {
+            refs.push(path);
+        }
+    }
+    refs
+}
+
+/// An image referenced with `@path` or attached with `/attach`.
+pub(crate) struct ImageFile {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Load the image at `path`. Fails if it isn't an image.
+pub(crate) fn load_image(path: &Path) -> std::io::Result<ImageFile> {
+    let data = std::fs::read(path)?;
+    match crate::tools::detect_image_mime(&data, path) {
+        Some(mime_type) => Ok(ImageFile {
+            mime_type: mime_type.to_string(),
+            data,
+        }),
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "not a supported image",
+        )),
+    }
+}
+
+/// Load the images referenced with `@path` in `input`, with the reference as
+/// typed.
+pub(crate) fn image_references(input: &str, working_dir: &Path) -> Vec<(String, ImageFile)> {
+    file_references(input)
+        .into_iter()
+        .filter_map(|reference| {
+            let path = working_dir.join(crate::tools::expand_tilde(reference));
+            if !path.is_file() {
+                return None;
+            }
+            let image = load_image(&path).ok()?;
+            Some((format!("@{}", reference), image))
+        })
+        .collect()
+}
+
+/// Append the contents of each file referenced with `@path` to `input`.
+///
+/// Returns the expanded prompt and a message for each file that couldn't be
+/// included.
+pub(crate) fn expand_file_references(
+    input: &str,
+    working_dir: &Path,
+    seen: &SeenFiles,
+) -> (String, Vec<String>) {
+    let mut expanded = input.to_string();
+    let mut errors = Vec::new();
+    for reference in file_references(input) {
+        let path = working_dir.join(crate::tools::expand_tilde(reference));
+        if !path.is_file() {
+            continue;
+        }
+        match std::fs::read(&path) {
+            // Sent as an image block by `image_references`
+            Ok(bytes) if crate::tools::detect_image_mime(&bytes, &path).is_some() => {}
+            Ok(bytes) if bytes.contains(&0) => {
+                errors.push(format!("Not including @{}: binary file", reference));
+            }
+            Ok(bytes) => {
+                seen.record(&path);
+                expanded.push_str("\n\n");
+                expanded.push_str(&render_file(reference, &String::from_utf8_lossy(&bytes)));
+            }
+            Err(e) => errors.push(format!("Failed to read @{}: {}", reference, e)),
+        }
+    }
+    (expanded, errors)
+}
+
+/// Render a file with numbered lines, truncated at `MAX_LINES` or `MAX_BYTES`.
+fn render_file(path: &str, contents: &str) -> String {
+    let mut out = format!("<file path=\"{}\">\n", path);
+    let total = contents.lines().count();
+    let mut size = 0;
+    let mut included = 0;
+    for (idx, line) in contents.lines().enumerate().take(MAX_LINES) {
+        size += line.len() + 1;
+        if size > MAX_BYTES {
+            break;
+        }
+        out.push_str(&format!("{:>6}\t{}\n", idx + 1, line));
+        included += 1;
+    }
+    if included < total {
+        out.push_str(&format!(
+            "[Truncated at line {} of {}; use file_read for the rest]\n",
+            included, total
+        ));
+    }
+    out.push_str("</file>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_file_references() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/foo.rs"), "fn main() {}\n").unwrap();
+
+        let seen = SeenFiles::default();
+        let (expanded, errors) = expand_file_references(
+            "Explain @src/foo.rs, ask @claude/opus and @src/foo.rs again",
+            dir.path(),
+            &seen,
+        );
+        assert!(errors.is_empty());
+        assert_eq!(
+            expanded,
+            "Explain @src/foo.rs, ask @claude/opus and @src/foo.rs again\n\n\
+             <file path=\"src/foo.rs\">\n     1\tfn main() {}\n</file>"
+        );
+
+        std::fs::write(dir.path().join("shot.png"), b"\x89PNG\r\n\x1a\n....").unwrap();
+        let (expanded, _) = expand_file_references("What is in @shot.png?", dir.path(), &seen);
+        assert_eq!(expanded, "What is in @shot.png?");
+        let images = image_references("What is in @shot.png?", dir.path());
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].0, "@shot.png");
+        assert_eq!(images[0].1.mime_type, "image/png");
+
+        let long = "x\n".repeat(MAX_LINES + 5);
+        assert!(render_file("long.txt", &long).contains("[Truncated at line 2000 of 2005;"));
+    }
+}
@@ -2,11 +2,14 @@
 // Copyright (c) 2025 Jason Ish
 
 use dirs::home_dir;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
 
+use crate::config::HistoryConfig;
+
 const HISTORY_FILE: &str = "history.json";
 const MAX_HISTORY: usize = 5000;
 
@@ -14,6 +17,9 @@ const MAX_HISTORY: usize = 5000;
 struct HistoryEntry {
     prompt: String,
     images: Vec<HistoryImage>,
+    /// Working directory the prompt was typed in, for `/history clear project`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,18 +29,71 @@ pub(crate) struct HistoryImage {
     pub data: String, // Base64 encoded image data
 }
 
+/// Which prompts `/history clear` removes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ClearScope {
+    All,
+    /// Prompts typed in the current working directory
+    Project,
+    /// The most recent prompts
+    Last(usize),
+}
+
+impl ClearScope {
+    /// Parse the scope of `/history clear`: `all`, `project` or `last [n]`.
+    pub(crate) fn parse(args: &str) -> Option<Self> {
+        let mut words = args.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (Some("all"), None, _) => Some(ClearScope::All),
+            (Some("project"), None, _) => Some(ClearScope::Project),
+            (Some("last"), None, _) => Some(ClearScope::Last(1)),
+            (Some("last"), Some(n), None) => {
+                n.parse().ok().filter(|n| *n > 0).map(ClearScope::Last)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Compile the excluded patterns from the config, or return the first one
+/// that isn't a valid regex.
+pub(crate) fn excluded_patterns(config: &HistoryConfig) -> Result<Vec<Regex>, String> {
+    config
+        .excluded_patterns
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(|e| format!("{}: {}", pattern, e)))
+        .collect()
+}
+
 pub(crate) struct FileHistory {
     entries: Vec<String>,
     path: PathBuf,
     max_len: usize,
     ignore_dups: bool,
     ignore_space: bool,
+    /// Whether prompts are saved to the history file. Prompts that aren't are
+    /// still kept in memory for the rest of the session.
+    record: bool,
+    excluded: Vec<Regex>,
+    working_dir: Option<PathBuf>,
 }
 
 impl FileHistory {
-    pub(crate) fn new() -> Self {
+    /// Load the history file, recording new prompts as `config` allows. An
+    /// invalid excluded pattern turns recording off rather than letting
+    /// prompts through that it was meant to catch.
+    pub(crate) fn new(config: &HistoryConfig, working_dir: &Path) -> Self {
         let path = Self::history_path();
-        Self::new_with_path(path)
+        let mut history = Self::new_with_path(path);
+        match excluded_patterns(config) {
+            Ok(excluded) => {
+                history.record = config.record;
+                history.excluded = excluded;
+            }
+            Err(_) => history.record = false,
+        }
+        history.working_dir = Some(working_dir.to_path_buf());
+        history
     }
 
     fn new_with_path(path: PathBuf) -> Self {
@@ -51,6 +110,9 @@ impl FileHistory {
             max_len: MAX_HISTORY,
             ignore_dups: true,
             ignore_space: true,
+            record: true,
+            excluded: Vec::new(),
+            working_dir: None,
         }
     }
 
@@ -62,6 +124,13 @@ impl FileHistory {
     }
 
     fn load_from_file(path: &Path) -> Vec<String> {
+        Self::load_entries(path)
+            .into_iter()
+            .map(|entry| entry.prompt)
+            .collect()
+    }
+
+    fn load_entries(path: &Path) -> Vec<HistoryEntry> {
         let Ok(file) = File::open(path) else {
             return Vec::new();
         };
@@ -74,7 +143,7 @@ impl FileHistory {
                 continue;
             };
             if let Ok(entry) = serde_json::from_str::<HistoryEntry>(&line) {
-                entries.push(entry.prompt);
+                entries.push(entry);
             }
         }
 
@@ -89,6 +158,7 @@ impl FileHistory {
         let entry = HistoryEntry {
             prompt: prompt.to_string(),
             images,
+            dir: self.working_dir.clone(),
         };
 
         if let Ok(mut file) = OpenOptions::new()
@@ -116,7 +186,9 @@ impl FileHistory {
         }
 
         self.entries.push(line.to_string());
-        self.append_to_file_with_images(line, images);
+        if self.records(line) {
+            self.append_to_file_with_images(line, images);
+        }
 
         // Just trim in-memory; file compaction happens on next load
         if self.entries.len() > self.max_len {
@@ -126,6 +198,43 @@ impl FileHistory {
         true
     }
 
+    /// Whether `prompt` is saved to the history file.
+    fn records(&self, prompt: &str) -> bool {
+        self.record && !self.excluded.iter().any(|re| re.is_match(prompt))
+    }
+
+    /// Remove prompts from the history file and from memory. Returns the
+    /// number of saved prompts removed.
+    pub(crate) fn clear(&mut self, scope: ClearScope) -> std::io::Result<usize> {
+        let mut entries = Self::load_entries(&self.path);
+        let before = entries.len();
+        match scope {
+            ClearScope::All => entries.clear(),
+            ClearScope::Project => {
+                entries.retain(|entry| entry.dir.is_none() || entry.dir != self.working_dir)
+            }
+            ClearScope::Last(n) => entries.truncate(entries.len().saturating_sub(n)),
+        }
+        let removed = before - entries.len();
+
+        let mut contents = String::new();
+        for entry in &entries {
+            let json = serde_json::to_string(entry)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            contents.push_str(&json);
+            contents.push('\n');
+        }
+        if self.path.exists() {
+            fs::write(&self.path, contents)?;
+        }
+
+        self.entries = entries.into_iter().map(|entry| entry.prompt).collect();
+        if self.entries.len() > self.max_len {
+            self.entries = self.entries.split_off(self.entries.len() - self.max_len);
+        }
+        Ok(removed)
+    }
+
     pub(crate) fn len(&self) -> usize {
         self.entries.len()
     }
@@ -143,12 +252,6 @@ impl FileHistory {
     }
 }
 
-impl Default for FileHistory {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,4 +309,47 @@ mod tests {
             history.entries
         );
     }
+
+    #[test]
+    fn test_excluded_prompts_and_clear() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        let config = HistoryConfig {
+            record: true,
+            excluded_patterns: vec!["(?i)password".to_string()],
+        };
+
+        let mut history = FileHistory::new_with_path(path.clone());
+        history.excluded = excluded_patterns(&config).unwrap();
+        history.working_dir = Some(PathBuf::from("/work/a"));
+        history.add_with_images("first", vec![]);
+        history.add_with_images("my Password is hunter2", vec![]);
+        history.working_dir = Some(PathBuf::from("/work/b"));
+        history.add_with_images("second", vec![]);
+        history.add_with_images("third", vec![]);
+        // Kept in memory for this session, but never saved
+        assert_eq!(history.len(), 4);
+        assert_eq!(
+            FileHistory::load_from_file(&path),
+            vec!["first", "second", "third"]
+        );
+
+        assert_eq!(history.clear(ClearScope::Last(1)).unwrap(), 1);
+        assert_eq!(history.entries(), ["first", "second"]);
+        assert_eq!(history.clear(ClearScope::Project).unwrap(), 1);
+        assert_eq!(FileHistory::load_from_file(&path), vec!["first"]);
+        assert_eq!(history.clear(ClearScope::All).unwrap(), 1);
+        assert!(history.is_empty());
+
+        assert_eq!(ClearScope::parse("last 3"), Some(ClearScope::Last(3)));
+        assert_eq!(ClearScope::parse("last 0"), None);
+        assert_eq!(ClearScope::parse(""), None);
+        assert!(
+            excluded_patterns(&HistoryConfig {
+                record: true,
+                excluded_patterns: vec!["(".to_string()],
+            })
+            .is_err()
+        );
+    }
 }
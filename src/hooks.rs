@@ -0,0 +1,295 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Commands run on lifecycle events, configured under `[hooks]`.
+//!
+//! Each hook is a shell command that gets the event as JSON on stdin. Hooks
+//! for `pre-tool-use` can stop a tool call by exiting with status 2; what they
+//! print to stderr is returned to the model as the tool's error. Any other
+//! failure is only reported as a warning.
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::config::{HookConfig, HooksConfig};
+use crate::output::{self, OutputContext};
+use crate::services::Services;
+use crate::tools::ToolResult;
+
+/// Default time a hook may run before it is killed.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Exit status with which a `pre-tool-use` hook blocks the tool call.
+const BLOCK_STATUS: i32 = 2;
+
+/// The event written to a hook's stdin.
+#[derive(Debug, Default, Serialize)]
+struct Event<'a> {
+    event: &'static str,
+    session_id: Option<String>,
+    cwd: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_input: Option<&'a serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_result: Option<ToolResultEvent<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolResultEvent<'a> {
+    content: &'a str,
+    is_error: bool,
+}
+
+impl<'a> Event<'a> {
+    fn new(event: &'static str, services: &Services) -> Self {
+        Self {
+            event,
+            session_id: services.session_id(),
+            cwd: std::env::current_dir()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+/// How a hook finished.
+enum Outcome {
+    Success,
+    Failed { status: Option<i32>, stderr: String },
+    TimedOut,
+}
+
+/// Load the configured hooks, or none if the config can't be read.
+pub(crate) fn load() -> HooksConfig {
+    crate::config::ConfigFile::load()
+        .map(|config| config.hooks)
+        .unwrap_or_default()
+}
+
+/// Whether `hook` applies to the tool `name`.
+fn matches_tool(hook: &HookConfig, name: &str) -> bool {
+    let canonical = crate::tools::canonicalize_builtin_tool_name(name).unwrap_or(name);
+    hook.tools.is_empty() || hook.tools.iter().any(|t| t == name || t == canonical)
+}
+
+async fn run(hook: &HookConfig, event: &Event<'_>) -> std::io::Result<Outcome> {
+    let json = serde_json::to_string(event)?;
+    let mut child = Command::new("bash")
+        .arg("-c")
+        .arg(&hook.command)
+        .env("HENRI_HOOK_EVENT", event.event)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // Written from a task so a hook that doesn't read its input can't
+        // block on a full pipe.
+        tokio::spawn(async move {
+            let _ = stdin.write_all(json.as_bytes()).await;
+        });
+    }
+
+    let timeout = hook
+        .timeout
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_TIMEOUT);
+    let Ok(result) = tokio::time::timeout(timeout, child.wait_with_output()).await else {
+        return Ok(Outcome::TimedOut);
+    };
+    let result = result?;
+    if result.status.success() {
+        Ok(Outcome::Success)
+    } else {
+        Ok(Outcome::Failed {
+            status: result.status.code(),
+            stderr: String::from_utf8_lossy(&result.stderr).trim().to_string(),
+        })
+    }
+}
+
+fn warn(output: Option<&OutputContext>, hook: &HookConfig, event: &str, problem: String) {
+    let message = format!("{} hook `{}` {}", event, hook.command, problem);
+    match output {
+        Some(output) => output::emit_warning(output, &message),
+        None => eprintln!("Warning: {}", message),
+    }
+}
+
+/// Run `hooks` in order. Returns the stderr of the first hook that exited
+/// with [`BLOCK_STATUS`] when `can_block` is set.
+async fn run_all(
+    hooks: &[&HookConfig],
+    event: &Event<'_>,
+    can_block: bool,
+    output: Option<&OutputContext>,
+) -> Option<String> {
+    for hook in hooks {
+        match run(hook, event).await {
+            Ok(Outcome::Success) => {}
+            Ok(Outcome::Failed {
+                status: Some(BLOCK_STATUS),
+                stderr,
+            }) if can_block => {
+                return Some(if stderr.is_empty() {
+                    format!("Blocked by hook `{}`", hook.command)
+                } else {
+                    stderr
+                });
+            }
+            Ok(Outcome::Failed { status, stderr }) => {
+                let status = status.map_or("a signal".to_string(), |s| format!("status {}", s));
+                let problem = if stderr.is_empty() {
+                    format!("exited with {}", status)
+                } else {
+                    format!("exited with {}: {}", status, stderr)
+                };
+                warn(output, hook, event.event, problem);
+            }
+            Ok(Outcome::TimedOut) => warn(output, hook, event.event, "timed out".to_string()),
+            Err(e) => warn(output, hook, event.event, format!("failed to run: {}", e)),
+        }
+    }
+    None
+}
+
+/// Run the `pre-tool-use` hooks for a tool call. Returns the reason when a
+/// hook blocks the call.
+pub(crate) async fn pre_tool_use(
+    config: &HooksConfig,
+    tool_name: &str,
+    input: &serde_json::Value,
+    output: &OutputContext,
+    services: &Services,
+) -> Option<String> {
+    let hooks: Vec<_> = config
+        .pre_tool_use
+        .iter()
+        .filter(|hook| matches_tool(hook, tool_name))
+        .collect();
+    if hooks.is_empty() {
+        return None;
+    }
+    let event = Event {
+        tool_name: Some(tool_name),
+        tool_input: Some(input),
+        ..Event::new("pre_tool_use", services)
+    };
+    run_all(&hooks, &event, true, Some(output)).await
+}
+
+/// Run the `post-tool-use` hooks after a tool call has finished.
+pub(crate) async fn post_tool_use(
+    config: &HooksConfig,
+    tool_name: &str,
+    input: &serde_json::Value,
+    result: &ToolResult,
+    output: &OutputContext,
+    services: &Services,
+) {
+    let hooks: Vec<_> = config
+        .post_tool_use
+        .iter()
+        .filter(|hook| matches_tool(hook, tool_name))
+        .collect();
+    if hooks.is_empty() {
+        return;
+    }
+    let event = Event {
+        tool_name: Some(tool_name),
+        tool_input: Some(input),
+        tool_result: Some(ToolResultEvent {
+            content: &result.content,
+            is_error: result.is_error,
+        }),
+        ..Event::new("post_tool_use", services)
+    };
+    run_all(&hooks, &event, false, Some(output)).await;
+}
+
+/// Run the `user-prompt-submit` hooks for a prompt about to be sent.
+pub(crate) async fn user_prompt_submit(
+    config: &HooksConfig,
+    prompt: &str,
+    output: &OutputContext,
+    services: &Services,
+) {
+    if config.user_prompt_submit.is_empty() {
+        return;
+    }
+    let hooks: Vec<_> = config.user_prompt_submit.iter().collect();
+    let event = Event {
+        prompt: Some(prompt),
+        ..Event::new("user_prompt_submit", services)
+    };
+    run_all(&hooks, &event, false, Some(output)).await;
+}
+
+/// Run the `session-end` hooks. Called once the interface has exited, so
+/// problems are printed to stderr.
+pub(crate) async fn session_end(services: &Services) {
+    let config = load();
+    if config.session_end.is_empty() {
+        return;
+    }
+    let hooks: Vec<_> = config.session_end.iter().collect();
+    run_all(&hooks, &Event::new("session_end", services), false, None).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(command: &str) -> HookConfig {
+        HookConfig {
+            command: command.to_string(),
+            tools: Vec::new(),
+            timeout: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pre_tool_use_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let seen = dir.path().join("event.json");
+        let config = HooksConfig {
+            pre_tool_use: vec![
+                hook(&format!("cat > '{}'", seen.display())),
+                HookConfig {
+                    tools: vec!["file_write".to_string()],
+                    ..hook("echo 'no writes today' >&2; exit 2")
+                },
+            ],
+            ..Default::default()
+        };
+        let output = OutputContext::null();
+        let services = Services::null();
+        let input = serde_json::json!({"command": "ls"});
+
+        assert_eq!(
+            pre_tool_use(&config, "bash", &input, &output, &services).await,
+            None
+        );
+        let event: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&seen).unwrap()).unwrap();
+        assert_eq!(event["event"], "pre_tool_use");
+        assert_eq!(event["tool_name"], "bash");
+        assert_eq!(event["tool_input"]["command"], "ls");
+
+        assert_eq!(
+            pre_tool_use(&config, "file_write", &input, &output, &services).await,
+            Some("no writes today".to_string())
+        );
+    }
+}
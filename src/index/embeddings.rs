@@ -0,0 +1,551 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Embeddings of the working directory's source files, for the
+//! `semantic_search` tool.
+//!
+//! Only used when `[embeddings]` is configured. Files are split into
+//! overlapping chunks of lines, and each chunk is embedded through the
+//! configured OpenAI-compatible `/embeddings` endpoint. Vectors are kept in
+//! `~/.cache/henri/embeddings`, one store per directory and model, so only
+//! files that changed since the last run are embedded again. A search embeds
+//! the query and compares it with every chunk; a repository's worth of
+//! vectors is small enough that no approximate index is needed.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::EmbeddingsConfig;
+use crate::error::{Error, Result};
+
+/// Lines in a chunk.
+const CHUNK_LINES: usize = 60;
+
+/// Lines a chunk shares with the next, so code near a boundary is whole in
+/// one of them.
+const CHUNK_OVERLAP: usize = 15;
+
+/// Characters of a chunk sent to be embedded.
+const MAX_CHUNK_CHARS: usize = 4000;
+
+/// Files larger than this are left out.
+const MAX_FILE_BYTES: u64 = 256 * 1024;
+
+/// Most files embedded under one root.
+const MAX_FILES: usize = 20_000;
+
+/// Chunks sent in one request.
+const BATCH_SIZE: usize = 64;
+
+/// Time allowed for one request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Results returned when the caller doesn't ask for a number.
+pub(crate) const DEFAULT_LIMIT: usize = 8;
+
+/// Files embedded besides source files.
+const DOC_EXTENSIONS: &[&str] = &["md", "txt", "rst"];
+
+/// Stores by root, kept between calls.
+static STORES: tokio::sync::Mutex<Option<HashMap<PathBuf, Store>>> =
+    tokio::sync::Mutex::const_new(None);
+
+/// A range of lines of a file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Chunk {
+    path: String,
+    /// 1-based first line
+    start: usize,
+    /// 1-based last line
+    end: usize,
+}
+
+/// What a file's chunks were computed from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FileStamp {
+    mtime: Option<u128>,
+    hash: String,
+}
+
+/// Chunks and their vectors; `vectors` holds `dims` values per chunk, in
+/// the order of `chunks`, normalized to unit length.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Store {
+    model: String,
+    dims: usize,
+    files: HashMap<String, FileStamp>,
+    chunks: Vec<Chunk>,
+    #[serde(skip)]
+    vectors: Vec<f32>,
+}
+
+/// A search result.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Hit {
+    pub path: String,
+    pub start: usize,
+    pub end: usize,
+    /// Cosine similarity with the query
+    pub score: f32,
+}
+
+fn stamp_mtime(path: &Path) -> Option<u128> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis())
+}
+
+fn content_hash(content: &[u8]) -> String {
+    let digest = Sha256::digest(content);
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Whether files with `extension` are embedded.
+fn embeddable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| super::symbols::supported(e) || DOC_EXTENSIONS.contains(&e))
+}
+
+/// Chunks of a file, with the text to embed for each.
+type Chunks = Vec<(Chunk, String)>;
+
+/// Split `text` into overlapping chunks of lines. Blank chunks are left out.
+fn chunk(path: &str, text: &str) -> Chunks {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let end = (start + CHUNK_LINES).min(lines.len());
+        let body = lines[start..end].join("\n");
+        if !body.trim().is_empty() {
+            let mut embed = format!("{}\n{}", path, body);
+            if embed.len() > MAX_CHUNK_CHARS {
+                let cut = (0..=MAX_CHUNK_CHARS)
+                    .rev()
+                    .find(|i| embed.is_char_boundary(*i))
+                    .unwrap_or(0);
+                embed.truncate(cut);
+            }
+            chunks.push((
+                Chunk {
+                    path: path.to_string(),
+                    start: start + 1,
+                    end,
+                },
+                embed,
+            ));
+        }
+        if end == lines.len() {
+            break;
+        }
+        start = end - CHUNK_OVERLAP;
+    }
+    chunks
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    #[serde(default)]
+    index: Option<usize>,
+}
+
+/// Embed `inputs` with the configured endpoint, returning one normalized
+/// vector per input.
+async fn embed(config: &EmbeddingsConfig, inputs: &[String]) -> Result<Vec<Vec<f32>>> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let url = format!("{}/embeddings", config.url.trim_end_matches('/'));
+    let mut request = client.post(&url).json(&serde_json::json!({
+        "model": config.model,
+        "input": inputs,
+    }));
+    if let Some(api_key) = &config.api_key {
+        request = request.bearer_auth(api_key);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| Error::Other(format!("Embedding request to {} failed: {}", url, e)))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(Error::Other(format!(
+            "Embedding request failed with status {}: {}",
+            status,
+            crate::session::truncate_str(body.trim(), 300)
+        )));
+    }
+    let mut response: EmbeddingResponse = response
+        .json()
+        .await
+        .map_err(|e| Error::Other(format!("Invalid embedding response: {}", e)))?;
+    if response.data.len() != inputs.len() {
+        return Err(Error::Other(format!(
+            "Expected {} embeddings, got {}",
+            inputs.len(),
+            response.data.len()
+        )));
+    }
+    response.data.sort_by_key(|d| d.index);
+    Ok(response
+        .data
+        .into_iter()
+        .map(|d| {
+            let mut vector = d.embedding;
+            normalize(&mut vector);
+            vector
+        })
+        .collect())
+}
+
+/// Where the store of `root` for `model` is kept.
+fn store_path(root: &Path, model: &str) -> Option<PathBuf> {
+    let key = content_hash(format!("{}\n{}", root.display(), model).as_bytes());
+    Some(
+        dirs::home_dir()?
+            .join(".cache")
+            .join("henri")
+            .join("embeddings")
+            .join(key),
+    )
+}
+
+impl Store {
+    fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Load the store saved at `path`, if it was made with `model`.
+    fn load(path: &Path, model: &str) -> Option<Self> {
+        let meta = std::fs::read_to_string(path.with_extension("json")).ok()?;
+        let mut store: Store = serde_json::from_str(&meta).ok()?;
+        let bytes = std::fs::read(path.with_extension("bin")).ok()?;
+        store.vectors = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        (store.model == model && store.vectors.len() == store.chunks.len() * store.dims)
+            .then_some(store)
+    }
+
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let bytes: Vec<u8> = self.vectors.iter().flat_map(|v| v.to_le_bytes()).collect();
+        std::fs::write(path.with_extension("bin"), bytes)?;
+        std::fs::write(path.with_extension("json"), serde_json::to_string(self)?)
+    }
+
+    /// Drop the chunks of `path`.
+    fn remove(&mut self, path: &str) {
+        let dims = self.dims;
+        let mut kept_chunks = Vec::with_capacity(self.chunks.len());
+        let mut kept_vectors = Vec::with_capacity(self.vectors.len());
+        for (i, chunk) in self.chunks.drain(..).enumerate() {
+            if chunk.path != path {
+                kept_vectors.extend_from_slice(&self.vectors[i * dims..(i + 1) * dims]);
+                kept_chunks.push(chunk);
+            }
+        }
+        self.chunks = kept_chunks;
+        self.vectors = kept_vectors;
+        self.files.remove(path);
+    }
+
+    /// Bring the store up to date with `files`, the files under `root`,
+    /// embedding the ones that changed. Returns whether anything changed.
+    async fn update(
+        &mut self,
+        config: &EmbeddingsConfig,
+        root: &Path,
+        files: Vec<PathBuf>,
+    ) -> Result<bool> {
+        let mut files: Vec<(String, PathBuf)> = files
+            .into_iter()
+            .filter(|path| embeddable(path))
+            .filter_map(|path| {
+                let relative = path.strip_prefix(root).ok()?.to_str()?.to_string();
+                Some((relative, path))
+            })
+            .collect();
+        files.sort();
+        files.truncate(MAX_FILES);
+
+        let mut changed = false;
+        let present: std::collections::HashSet<&str> = files
+            .iter()
+            .map(|(relative, _)| relative.as_str())
+            .collect();
+        let deleted: Vec<String> = self
+            .files
+            .keys()
+            .filter(|path| !present.contains(path.as_str()))
+            .cloned()
+            .collect();
+        for path in deleted {
+            self.remove(&path);
+            changed = true;
+        }
+
+        // Files whose content changed, with their chunks.
+        let mut pending: Vec<(String, FileStamp, Chunks)> = Vec::new();
+        for (relative, path) in &files {
+            let mtime = stamp_mtime(path);
+            let stamp = self.files.get(relative);
+            if mtime.is_some() && stamp.is_some_and(|s| s.mtime == mtime) {
+                continue;
+            }
+            let text = std::fs::metadata(path)
+                .ok()
+                .filter(|m| m.len() <= MAX_FILE_BYTES)
+                .and_then(|_| std::fs::read_to_string(path).ok());
+            let Some(text) = text else {
+                // Too large or not text (any more).
+                if self.files.contains_key(relative) {
+                    self.remove(relative);
+                    changed = true;
+                }
+                continue;
+            };
+            let hash = content_hash(text.as_bytes());
+            if let Some(existing) = self.files.get_mut(relative)
+                && existing.hash == hash
+            {
+                existing.mtime = mtime;
+                changed = true;
+                continue;
+            }
+            pending.push((
+                relative.clone(),
+                FileStamp { mtime, hash },
+                chunk(relative, &text),
+            ));
+        }
+
+        // Embed a batch of files at a time, so what was embedded is kept if
+        // a later request fails.
+        let mut pending = pending.into_iter().peekable();
+        while pending.peek().is_some() {
+            let mut batch = Vec::new();
+            let mut count = 0;
+            while let Some((_, _, chunks)) = pending.peek()
+                && (batch.is_empty() || count + chunks.len() <= BATCH_SIZE)
+            {
+                count += chunks.len();
+                batch.push(pending.next().expect("peeked"));
+            }
+            let inputs: Vec<String> = batch
+                .iter()
+                .flat_map(|(_, _, chunks)| chunks.iter().map(|(_, text)| text.clone()))
+                .collect();
+            let mut vectors = Vec::new();
+            for inputs in inputs.chunks(BATCH_SIZE) {
+                vectors.extend(embed(config, inputs).await?);
+            }
+            if let Some(first) = vectors.first() {
+                if self.dims == 0 {
+                    self.dims = first.len();
+                }
+                if vectors.iter().any(|v| v.len() != self.dims) {
+                    return Err(Error::Other(format!(
+                        "The embedding model returned vectors of a different size than the {} stored",
+                        self.dims
+                    )));
+                }
+            }
+            let mut vectors = vectors.into_iter();
+            for (relative, stamp, chunks) in batch {
+                self.remove(&relative);
+                for (chunk, _) in chunks {
+                    let vector = vectors.next().expect("one vector per chunk");
+                    self.chunks.push(chunk);
+                    self.vectors.extend(vector);
+                }
+                self.files.insert(relative, stamp);
+            }
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    /// The chunks most similar to `query`, best first, leaving out chunks
+    /// that overlap a better one.
+    fn search(&self, query: &[f32], limit: usize) -> Vec<Hit> {
+        if self.dims == 0 || query.len() != self.dims {
+            return Vec::new();
+        }
+        let mut scored: Vec<(f32, &Chunk)> = self
+            .chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                let vector = &self.vectors[i * self.dims..(i + 1) * self.dims];
+                let score = vector.iter().zip(query).map(|(a, b)| a * b).sum();
+                (score, chunk)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut hits: Vec<Hit> = Vec::new();
+        for (score, chunk) in scored {
+            if hits.len() >= limit {
+                break;
+            }
+            let overlaps = hits
+                .iter()
+                .any(|h| h.path == chunk.path && h.start <= chunk.end && chunk.start <= h.end);
+            if !overlaps {
+                hits.push(Hit {
+                    path: chunk.path.clone(),
+                    start: chunk.start,
+                    end: chunk.end,
+                    score,
+                });
+            }
+        }
+        hits
+    }
+}
+
+/// Run `f` on the up-to-date store of `root`, loading it from disk or
+/// building it first.
+async fn with_store<T>(
+    config: &EmbeddingsConfig,
+    root: &Path,
+    f: impl FnOnce(&Store) -> T,
+) -> Result<T> {
+    let files = {
+        let root = root.to_path_buf();
+        tokio::task::spawn_blocking(move || crate::walk::files(&root))
+            .await
+            .unwrap_or_default()
+    };
+    let path = store_path(root, &config.model);
+    let mut stores = STORES.lock().await;
+    let stores = stores.get_or_insert_with(HashMap::new);
+    let store = stores.entry(root.to_path_buf()).or_insert_with(|| {
+        path.as_deref()
+            .and_then(|p| Store::load(p, &config.model))
+            .unwrap_or_else(|| Store::new(&config.model))
+    });
+    if store.model != config.model {
+        *store = Store::new(&config.model);
+    }
+    let result = store.update(config, root, files).await;
+    // Keep what was embedded before a failed request. A store that can't be
+    // saved is built again next time.
+    if let Some(path) = &path
+        && !matches!(result, Ok(false))
+    {
+        let _ = store.save(path);
+    }
+    result?;
+    Ok(f(store))
+}
+
+/// Embed the files of `root` in the background when embeddings are
+/// configured, so the first search doesn't wait for them.
+pub(crate) fn warm(root: &Path) {
+    let config = crate::config::ConfigFile::load().unwrap_or_default();
+    let Some(embeddings) = config.embeddings else {
+        return;
+    };
+    if config.disabled_tools.iter().any(|t| t == "semantic_search") {
+        return;
+    }
+    let root = root.to_path_buf();
+    tokio::spawn(async move {
+        let _ = with_store(&embeddings, &root, |_| ()).await;
+    });
+}
+
+/// The chunks of the files under `root` most similar in meaning to `query`.
+pub(crate) async fn search(
+    config: &EmbeddingsConfig,
+    root: &Path,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<Hit>> {
+    let query = embed(config, &[query.to_string()]).await?.remove(0);
+    with_store(config, root, |store| store.search(&query, limit)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk() {
+        let text: String = (1..=120).map(|i| format!("line {}\n", i)).collect();
+        let chunks: Vec<(usize, usize)> = chunk("src/a.rs", &text)
+            .into_iter()
+            .map(|(c, _)| (c.start, c.end))
+            .collect();
+        assert_eq!(chunks, vec![(1, 60), (46, 105), (91, 120)]);
+        let (_, embed) = &chunk("src/a.rs", "fn main() {}\n")[0];
+        assert_eq!(embed, "src/a.rs\nfn main() {}");
+        assert!(chunk("src/a.rs", "\n\n").is_empty());
+    }
+
+    #[test]
+    fn test_search() {
+        let mut store = Store::new("test");
+        store.dims = 2;
+        for (path, start, end, vector) in [
+            ("src/auth.rs", 1, 60, [1.0, 0.0]),
+            ("src/auth.rs", 46, 105, [0.9, 0.1]),
+            ("src/ui.rs", 1, 60, [0.0, 1.0]),
+            ("src/db.rs", 1, 60, [0.6, 0.4]),
+        ] {
+            store.chunks.push(Chunk {
+                path: path.to_string(),
+                start,
+                end,
+            });
+            let mut vector = vector.to_vec();
+            normalize(&mut vector);
+            store.vectors.extend(vector);
+        }
+
+        let hits: Vec<(String, usize)> = store
+            .search(&[1.0, 0.0], 2)
+            .into_iter()
+            .map(|h| (h.path, h.start))
+            .collect();
+        // The overlapping second chunk of src/auth.rs is left out.
+        assert_eq!(
+            hits,
+            vec![("src/auth.rs".to_string(), 1), ("src/db.rs".to_string(), 1)]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store");
+        store.save(&path).unwrap();
+        let loaded = Store::load(&path, "test").unwrap();
+        assert_eq!(loaded.vectors, store.vectors);
+        assert!(Store::load(&path, "other").is_none());
+    }
+}
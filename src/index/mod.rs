@@ -0,0 +1,374 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Index of the definitions and file summaries of the working directory, for
+//! the `codebase_search` tool.
+//!
+//! The index is built in the background when the CLI starts and kept for the
+//! life of the process. Files come from the cached [`crate::walk`] index, so
+//! ignored files are left out; before each search, files whose modification
+//! time changed are read again and deleted ones dropped.
+
+pub(crate) mod embeddings;
+pub(crate) mod symbols;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use symbols::Symbol;
+
+/// Files larger than this are left out; they are mostly generated.
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+
+/// Most files indexed under one root.
+const MAX_FILES: usize = 50_000;
+
+/// Results returned when the caller doesn't ask for a number.
+pub(crate) const DEFAULT_LIMIT: usize = 20;
+
+/// Indexes by root, kept between calls.
+static INDEXES: Mutex<Option<HashMap<PathBuf, SymbolIndex>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct FileEntry {
+    mtime: Option<SystemTime>,
+    summary: Option<String>,
+    symbols: Vec<Symbol>,
+}
+
+/// Definitions and summaries of the files under a root, keyed by path
+/// relative to the root.
+#[derive(Debug, Default)]
+struct SymbolIndex {
+    files: HashMap<String, FileEntry>,
+}
+
+/// A search result.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Match {
+    Symbol {
+        path: String,
+        symbol: Symbol,
+    },
+    File {
+        path: String,
+        summary: Option<String>,
+    },
+}
+
+fn mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Read `path` and find its definitions, or `None` when it is too large or
+/// not text.
+fn index_file(path: &Path, extension: &str) -> Option<FileEntry> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if metadata.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    let text = std::fs::read_to_string(path).ok()?;
+    Some(FileEntry {
+        mtime: metadata.modified().ok(),
+        summary: symbols::summary(&text),
+        symbols: symbols::extract(extension, &text),
+    })
+}
+
+impl SymbolIndex {
+    /// Bring the index of `root` up to date with `files`, the files under it.
+    fn update(&mut self, root: &Path, files: Vec<PathBuf>) {
+        let mut files: Vec<(String, PathBuf, String)> = files
+            .into_iter()
+            .filter_map(|path| {
+                let extension = path.extension()?.to_str()?.to_string();
+                if !symbols::supported(&extension) {
+                    return None;
+                }
+                let relative = path.strip_prefix(root).ok()?.to_str()?.to_string();
+                Some((relative, path, extension))
+            })
+            .collect();
+        files.sort();
+        files.truncate(MAX_FILES);
+
+        let stale: Vec<&(String, PathBuf, String)> = files
+            .iter()
+            .filter(|(relative, path, _)| {
+                self.files
+                    .get(relative)
+                    .is_none_or(|entry| entry.mtime.is_none() || entry.mtime != mtime(path))
+            })
+            .collect();
+        let indexed = index_files(&stale);
+
+        let present: std::collections::HashSet<&str> = files
+            .iter()
+            .map(|(relative, _, _)| relative.as_str())
+            .collect();
+        self.files
+            .retain(|relative, _| present.contains(relative.as_str()));
+        for ((relative, _, _), entry) in stale.into_iter().zip(indexed) {
+            match entry {
+                Some(entry) => {
+                    self.files.insert(relative.clone(), entry);
+                }
+                None => {
+                    self.files.remove(relative);
+                }
+            }
+        }
+    }
+
+    /// The best matches for `query`, best first.
+    fn search(&self, query: &str, limit: usize) -> Vec<Match> {
+        let terms = terms(query);
+        if terms.is_empty() {
+            return Vec::new();
+        }
+        let mut scored: Vec<(u32, Match)> = Vec::new();
+        for (path, entry) in &self.files {
+            let path_lower = path.to_lowercase();
+            let summary_lower = entry.summary.as_deref().map(str::to_lowercase);
+            let file_score: u32 = terms
+                .iter()
+                .map(|term| {
+                    let file_name = path_lower.rsplit('/').next().unwrap_or(&path_lower);
+                    let mut score = 0;
+                    if file_name.contains(term.as_str()) {
+                        score += 20;
+                    } else if path_lower.contains(term.as_str()) {
+                        score += 10;
+                    }
+                    if summary_lower
+                        .as_deref()
+                        .is_some_and(|s| s.contains(term.as_str()))
+                    {
+                        score += 8;
+                    }
+                    score
+                })
+                .sum();
+            if file_score > 0 {
+                scored.push((
+                    file_score,
+                    Match::File {
+                        path: path.clone(),
+                        summary: entry.summary.clone(),
+                    },
+                ));
+            }
+            for symbol in &entry.symbols {
+                let score = symbol_score(&symbol.name, &terms);
+                if score > 0 {
+                    // Break ties towards symbols in files that also match.
+                    scored.push((
+                        score + file_score / 4,
+                        Match::Symbol {
+                            path: path.clone(),
+                            symbol: symbol.clone(),
+                        },
+                    ));
+                }
+            }
+        }
+        scored.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .cmp(a_score)
+                .then_with(|| sort_key(a).cmp(&sort_key(b)))
+        });
+        scored.truncate(limit);
+        scored.into_iter().map(|(_, m)| m).collect()
+    }
+}
+
+fn sort_key(m: &Match) -> (&str, usize) {
+    match m {
+        Match::Symbol { path, symbol } => (path, symbol.line),
+        Match::File { path, .. } => (path, 0),
+    }
+}
+
+/// Read `files` using a few threads, in order.
+fn index_files(files: &[&(String, PathBuf, String)]) -> Vec<Option<FileEntry>> {
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let chunk = files.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = files
+            .chunks(chunk)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(_, path, extension)| index_file(path, extension))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Lowercase words of `query`, with identifiers split into their parts so
+/// `FileIndex`, `file_index` and "file index" find the same things.
+fn terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in query.split(|c: char| !c.is_alphanumeric() && c != '_') {
+        let word = word.trim_matches('_');
+        if word.is_empty() {
+            continue;
+        }
+        terms.push(word.to_lowercase());
+        let parts = split_identifier(word);
+        if parts.len() > 1 {
+            terms.extend(parts);
+        }
+    }
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// Lowercase parts of a snake_case or camelCase identifier.
+fn split_identifier(word: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    for piece in word.split('_').filter(|p| !p.is_empty()) {
+        let mut current = String::new();
+        let mut prev_lower = false;
+        for c in piece.chars() {
+            if c.is_uppercase() && prev_lower && !current.is_empty() {
+                parts.push(current.to_lowercase());
+                current.clear();
+            }
+            prev_lower = c.is_lowercase() || c.is_ascii_digit();
+            current.push(c);
+        }
+        if !current.is_empty() {
+            parts.push(current.to_lowercase());
+        }
+    }
+    parts
+}
+
+/// How well a definition named `name` matches `terms`.
+fn symbol_score(name: &str, terms: &[String]) -> u32 {
+    let lower = name.to_lowercase();
+    let parts = split_identifier(name);
+    terms
+        .iter()
+        .map(|term| {
+            if lower == *term {
+                100
+            } else if parts.contains(term) {
+                40
+            } else if lower.starts_with(term.as_str()) {
+                30
+            } else if lower.contains(term.as_str()) {
+                15
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+/// Run `f` on the index of `root`, building it or bringing it up to date
+/// first.
+fn with_index<T>(root: &Path, f: impl FnOnce(&SymbolIndex) -> T) -> Option<T> {
+    let files = crate::walk::files(root);
+    let mut indexes = INDEXES.lock().ok()?;
+    let index = indexes
+        .get_or_insert_with(HashMap::new)
+        .entry(root.to_path_buf())
+        .or_default();
+    index.update(root, files);
+    Some(f(index))
+}
+
+/// Build the index of `root` in the background, so the first search is fast.
+pub(crate) fn warm(root: &Path) {
+    let root = root.to_path_buf();
+    std::thread::spawn(move || with_index(&root, |_| ()));
+}
+
+/// The best `limit` matches for `query` among the files under `root`.
+pub(crate) fn search(root: &Path, query: &str, limit: usize) -> Vec<Match> {
+    with_index(root, |index| index.search(query, limit)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terms() {
+        assert_eq!(
+            terms("FileIndex refresh"),
+            vec!["file", "fileindex", "index", "refresh"]
+        );
+        assert_eq!(terms("load_config"), vec!["config", "load", "load_config"]);
+    }
+
+    #[test]
+    fn test_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("src/config.rs"),
+            "//! Loading the configuration file.\n\npub fn load_config() {}\nfn parse() {}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() { load_config(); }\n").unwrap();
+        std::fs::write(root.join("README.md"), "config\n").unwrap();
+        let files = vec![
+            root.join("src/config.rs"),
+            root.join("src/main.rs"),
+            root.join("README.md"),
+        ];
+
+        let mut index = SymbolIndex::default();
+        index.update(root, files.clone());
+        assert_eq!(index.files.len(), 2);
+        let found = index.search("load config", 3);
+        assert_eq!(
+            found[0],
+            Match::Symbol {
+                path: "src/config.rs".to_string(),
+                symbol: Symbol {
+                    name: "load_config".to_string(),
+                    kind: "fn".to_string(),
+                    line: 3,
+                },
+            }
+        );
+        assert!(matches!(
+            &found[1],
+            Match::File { path, summary: Some(summary) }
+                if path == "src/config.rs" && summary == "Loading the configuration file."
+        ));
+
+        // Changed and deleted files are picked up by the next update.
+        std::fs::write(
+            root.join("src/main.rs"),
+            "fn main() {}\nfn load_settings() {}\n",
+        )
+        .unwrap();
+        index.files.get_mut("src/main.rs").unwrap().mtime = None;
+        std::fs::remove_file(root.join("src/config.rs")).unwrap();
+        index.update(root, vec![root.join("src/main.rs")]);
+        let found = index.search("load", 5);
+        assert_eq!(found.len(), 1);
+        assert!(matches!(
+            &found[0],
+            Match::Symbol { path, symbol } if path == "src/main.rs" && symbol.name == "load_settings"
+        ));
+    }
+}
@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Definitions and a one-line summary found in a source file.
+//!
+//! Definitions are matched line by line with a few patterns per language, so
+//! they are found quickly without a parser for each language, at the cost of
+//! missing some (a signature split over lines) and finding a few that aren't
+//! (a function declared in a C macro).
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// A definition in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Symbol {
+    pub name: String,
+    /// Keyword it was defined with, e.g. `fn` or `class`
+    pub kind: String,
+    /// 1-based line number
+    pub line: usize,
+}
+
+/// A pattern matching a definition on one line.
+struct Pattern {
+    /// Kind of every match, or `None` when the first group is the kind
+    kind: Option<&'static str>,
+    regex: Regex,
+}
+
+impl Pattern {
+    fn new(kind: Option<&'static str>, regex: &str) -> Self {
+        Self {
+            kind,
+            regex: Regex::new(regex).expect("invalid symbol pattern"),
+        }
+    }
+}
+
+/// Patterns by the file extensions they apply to.
+static LANGUAGES: LazyLock<Vec<(&[&str], Vec<Pattern>)>> = LazyLock::new(|| {
+    vec![
+        (
+            &["rs"][..],
+            vec![Pattern::new(
+                None,
+                r#"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe|extern(?:\s+"[^"]*")?)\s+)*(fn|struct|enum|trait|type|mod|const|static|macro_rules!)\s*([A-Za-z_][A-Za-z0-9_]*)"#,
+            )],
+        ),
+        (
+            &["py"][..],
+            vec![Pattern::new(
+                None,
+                r"^\s*(?:async\s+)?(def|class)\s+([A-Za-z_]\w*)",
+            )],
+        ),
+        (
+            &["js", "jsx", "mjs", "cjs", "ts", "tsx"][..],
+            vec![
+                Pattern::new(
+                    None,
+                    r"^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?(function|class|interface|type|enum)\*?\s+([A-Za-z_$][\w$]*)",
+                ),
+                Pattern::new(None, r"^(?:export\s+)?(const|let|var)\s+([A-Za-z_$][\w$]*)"),
+            ],
+        ),
+        (
+            &["go"][..],
+            vec![Pattern::new(
+                None,
+                r"^(func|type)\s+(?:\([^)]*\)\s*)?([A-Za-z_]\w*)",
+            )],
+        ),
+        (
+            &["java", "kt", "kts", "cs", "scala", "swift"][..],
+            vec![Pattern::new(
+                None,
+                r"^\s*(?:(?:public|private|protected|internal|static|final|abstract|sealed|open|data|partial)\s+)*(class|interface|enum|record|object|struct|trait|fun|func)\s+([A-Za-z_]\w*)",
+            )],
+        ),
+        (
+            &["c", "h", "cc", "cpp", "cxx", "hpp", "hh"][..],
+            vec![
+                Pattern::new(
+                    None,
+                    r"^\s*(?:typedef\s+)?(struct|union|enum|class|namespace)\s+([A-Za-z_]\w*)",
+                ),
+                Pattern::new(None, r"^#\s*(define)\s+([A-Za-z_]\w*)"),
+                Pattern::new(
+                    Some("function"),
+                    r"^[A-Za-z_][\w\s\*&:<>,]*?[\s\*&]([A-Za-z_][\w:~]*)\s*\([^;]*$",
+                ),
+            ],
+        ),
+        (
+            &["rb"][..],
+            vec![Pattern::new(
+                None,
+                r"^\s*(def|class|module)\s+(?:self\.)?([A-Za-z_][\w:]*[?!]?)",
+            )],
+        ),
+    ]
+});
+
+/// Words that look like a C function name in front of `(`.
+const NOT_FUNCTIONS: &[&str] = &["if", "for", "while", "switch", "return", "sizeof", "else"];
+
+/// Whether definitions can be found in files with `extension`.
+pub(crate) fn supported(extension: &str) -> bool {
+    patterns(extension).is_some()
+}
+
+fn patterns(extension: &str) -> Option<&'static [Pattern]> {
+    LANGUAGES
+        .iter()
+        .find(|(extensions, _)| extensions.contains(&extension))
+        .map(|(_, patterns)| patterns.as_slice())
+}
+
+/// The definitions in `text`, the content of a file with `extension`.
+pub(crate) fn extract(extension: &str, text: &str) -> Vec<Symbol> {
+    let Some(patterns) = patterns(extension) else {
+        return Vec::new();
+    };
+    let mut symbols = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        for pattern in patterns {
+            let Some(captures) = pattern.regex.captures(line) else {
+                continue;
+            };
+            let (kind, name) = match pattern.kind {
+                Some(kind) => (kind, &captures[1]),
+                None => (captures.get(1).map_or("", |m| m.as_str()), &captures[2]),
+            };
+            if NOT_FUNCTIONS.contains(&name) {
+                continue;
+            }
+            symbols.push(Symbol {
+                name: name.to_string(),
+                kind: kind.trim_end_matches('!').to_string(),
+                line: i + 1,
+            });
+            break;
+        }
+    }
+    symbols
+}
+
+/// The first comment near the top of `text` that reads like a description,
+/// skipping license and copyright lines.
+pub(crate) fn summary(text: &str) -> Option<String> {
+    const MARKERS: &[&str] = &["//!", "///", "//", "/**", "/*", "*", "#", "\"\"\"", "--"];
+    for line in text.lines().take(30) {
+        let line = line.trim();
+        if line.starts_with("#!") || line.starts_with("#include") || line.starts_with("#define") {
+            continue;
+        }
+        let Some(marker) = MARKERS.iter().find(|m| line.starts_with(**m)) else {
+            continue;
+        };
+        let comment = line[marker.len()..]
+            .trim()
+            .trim_end_matches("*/")
+            .trim_end_matches("\"\"\"")
+            .trim();
+        let lower = comment.to_ascii_lowercase();
+        if comment.split_whitespace().count() < 3
+            || lower.starts_with("spdx-")
+            || lower.starts_with("copyright")
+            || lower.contains("license")
+        {
+            continue;
+        }
+        return Some(comment.to_string());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(extension: &str, text: &str) -> Vec<(String, String, usize)> {
+        extract(extension, text)
+            .into_iter()
+            .map(|s| (s.kind, s.name, s.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract() {
+        let rust = "pub(crate) struct FileIndex {\n}\n\nimpl FileIndex {\n    pub(crate) async fn refresh(&mut self) {}\n}\nmacro_rules! log {}\n";
+        assert_eq!(
+            names("rs", rust),
+            vec![
+                ("struct".to_string(), "FileIndex".to_string(), 1),
+                ("fn".to_string(), "refresh".to_string(), 5),
+                ("macro_rules".to_string(), "log".to_string(), 7),
+            ]
+        );
+
+        let c = "#define MAX 10\nstatic int parse_line(const char *s)\n{\n    if (x) {\n    return foo(1);\n}\nint decl(void);\n";
+        assert_eq!(
+            names("c", c),
+            vec![
+                ("define".to_string(), "MAX".to_string(), 1),
+                ("function".to_string(), "parse_line".to_string(), 2),
+            ]
+        );
+
+        let ts = "export default async function load() {}\nexport const API_URL = '';\n  const local = 1;\n";
+        assert_eq!(
+            names("ts", ts),
+            vec![
+                ("function".to_string(), "load".to_string(), 1),
+                ("const".to_string(), "API_URL".to_string(), 2),
+            ]
+        );
+        assert!(!supported("md"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let rust = "// SPDX-License-Identifier: MIT\n// Copyright (c) 2026 Jason Ish\n\n//! Walking the working directory with the user's ignore rules.\n";
+        assert_eq!(
+            summary(rust).as_deref(),
+            Some("Walking the working directory with the user's ignore rules.")
+        );
+        let python = "#!/usr/bin/env python3\n\"\"\"Convert session logs to CSV.\"\"\"\n";
+        assert_eq!(
+            summary(python).as_deref(),
+            Some("Convert session logs to CSV.")
+        );
+        assert_eq!(summary("fn main() {}\n"), None);
+    }
+}
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Henri as a library.
+//!
+//! The `henri` binary is a thin wrapper around this crate. Other Rust
+//! programs can embed the same agent loop through [`Agent`]: it talks to the
+//! configured providers, runs the built-in, MCP and external tools, and
+//! reports progress as [`Event`]s, using the same config file and
+//! credentials as the CLI.
+//!
+//! The conversation is made of [`provider::Message`]s, the tools offered to
+//! the model are listed as [`tools::ToolDefinition`]s, saved sessions are
+//! found with [`session::list_sessions`], and the security mode is kept in
+//! [`services::Services`].
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), henri_core::Error> {
+//! let mut agent = henri_core::Agent::new(None)?;
+//! agent.on_event(|event| {
+//!     if let henri_core::Event::ToolCall { description } = event {
+//!         eprintln!("-> {description}");
+//!     }
+//! });
+//! let answer = agent.send("What does src/lib.rs export?").await?;
+//! println!("{answer}");
+//! # Ok(())
+//! # }
+//! ```
+
+mod agent;
+mod app;
+mod auth;
+mod chat;
+mod checkpoints;
+mod cli;
+mod commands;
+mod compaction;
+mod completion;
+mod config;
+mod continuation;
+mod custom_commands;
+mod daemon;
+mod diff;
+mod error;
+mod explain;
+mod exploration_cache;
+mod feedback;
+mod file_refs;
+mod history;
+mod hooks;
+mod index;
+mod lsp;
+mod mcp;
+mod output;
+mod permissions;
+mod prompts;
+pub mod provider;
+mod provider_test;
+mod providers;
+mod recap;
+mod rollback;
+mod seen_files;
+pub mod services;
+pub mod session;
+mod session_diff;
+mod settings;
+mod skills;
+mod sliding_window;
+mod sse;
+mod startup;
+mod syntax;
+mod timeline;
+mod tool_metrics;
+mod tool_summary;
+pub mod tools;
+mod upgrade;
+mod usage;
+mod version;
+mod walk;
+mod watchdog;
+
+pub use agent::{Agent, Event};
+pub use error::Error;
+
+/// Run the `henri` command-line interface with the process arguments.
+#[doc(hidden)]
+pub fn run_cli() -> std::io::Result<()> {
+    startup::start();
+    app::run()
+}
@@ -26,11 +26,16 @@ use tokio::task::JoinHandle;
 
 use crate::error::{Error, Result};
 
+pub(crate) mod quickfix;
+
 // LSP request timeout in seconds
 const REQUEST_TIMEOUT_SECS: u64 = 30;
 
-// Default wait time for diagnostics in milliseconds
-const DIAGNOSTIC_WAIT_MS: u64 = 500;
+// Longest wait for a server to publish diagnostics after a change, in milliseconds
+const DIAGNOSTIC_WAIT_MS: u64 = 3000;
+
+// Interval at which fresh diagnostics are checked for, in milliseconds
+const DIAGNOSTIC_POLL_MS: u64 = 50;
 
 // Maximum diagnostics to display
 const MAX_ERRORS_DISPLAYED: usize = 10;
@@ -63,6 +68,32 @@ pub(crate) struct LspServerConfig {
     pub root_path: PathBuf,
 }
 
+/// Diagnostics of a file after a change, with the ones it had before.
+#[derive(Debug, Default)]
+pub(crate) struct ChangeDiagnostics {
+    /// Servers started to handle the file
+    pub started: Vec<LspServerInfo>,
+    pub current: Vec<FileDiagnostic>,
+    pub previous: Vec<FileDiagnostic>,
+}
+
+impl ChangeDiagnostics {
+    /// Whether `diagnostic` wasn't reported before the change. Line numbers
+    /// move with edits, so diagnostics are matched by severity and message.
+    fn is_new(&self, diagnostic: &FileDiagnostic) -> bool {
+        let same = |d: &&FileDiagnostic| {
+            d.severity == diagnostic.severity && d.message == diagnostic.message
+        };
+        let index = self
+            .current
+            .iter()
+            .filter(same)
+            .position(|d| std::ptr::eq(d, diagnostic))
+            .unwrap_or(0);
+        self.previous.iter().filter(same).count() <= index
+    }
+}
+
 /// A diagnostic with file context
 #[derive(Debug, Clone)]
 pub(crate) struct FileDiagnostic {
@@ -146,6 +177,8 @@ struct LspClient {
     next_id: AtomicI64,
     pending_requests: Arc<RwLock<HashMap<i64, tokio::sync::oneshot::Sender<Value>>>>,
     diagnostics: Arc<RwLock<HashMap<PathBuf, Vec<Diagnostic>>>>,
+    /// Number of times diagnostics were published for each file
+    publish_counts: Arc<RwLock<HashMap<PathBuf, u64>>>,
     file_extensions: Vec<String>,
     _root_path: PathBuf,
     _reader_handle: JoinHandle<()>,
@@ -189,12 +222,21 @@ impl LspClient {
             Arc::new(RwLock::new(HashMap::new()));
         let diagnostics: Arc<RwLock<HashMap<PathBuf, Vec<Diagnostic>>>> =
             Arc::new(RwLock::new(HashMap::new()));
+        let publish_counts: Arc<RwLock<HashMap<PathBuf, u64>>> =
+            Arc::new(RwLock::new(HashMap::new()));
 
         // Spawn reader task
         let pending_clone = pending_requests.clone();
         let diagnostics_clone = diagnostics.clone();
+        let publish_counts_clone = publish_counts.clone();
         let reader_handle = tokio::spawn(async move {
-            Self::reader_loop(stdout, pending_clone, diagnostics_clone).await;
+            Self::reader_loop(
+                stdout,
+                pending_clone,
+                diagnostics_clone,
+                publish_counts_clone,
+            )
+            .await;
         });
 
         let mut client = Self {
@@ -204,6 +246,7 @@ impl LspClient {
             next_id: AtomicI64::new(1),
             pending_requests,
             diagnostics,
+            publish_counts,
             file_extensions: config.file_extensions.clone(),
             _root_path: config.root_path.clone(),
             _reader_handle: reader_handle,
@@ -221,6 +264,7 @@ impl LspClient {
         stdout: tokio::process::ChildStdout,
         pending_requests: Arc<RwLock<HashMap<i64, tokio::sync::oneshot::Sender<Value>>>>,
         diagnostics: Arc<RwLock<HashMap<PathBuf, Vec<Diagnostic>>>>,
+        publish_counts: Arc<RwLock<HashMap<PathBuf, u64>>>,
     ) {
         use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader as TokioBufReader};
 
@@ -271,6 +315,11 @@ impl LspClient {
                     && let Some(path) = uri_to_path(&diag_params.uri)
                 {
                     let mut diags = diagnostics.write().await;
+                    *publish_counts
+                        .write()
+                        .await
+                        .entry(path.clone())
+                        .or_default() += 1;
                     diags.insert(path, diag_params.diagnostics);
                 }
                 continue;
@@ -487,6 +536,16 @@ impl LspClient {
             .unwrap_or_default()
     }
 
+    /// Number of times diagnostics were published for a file
+    async fn publish_count(&self, path: &Path) -> u64 {
+        self.publish_counts
+            .read()
+            .await
+            .get(path)
+            .copied()
+            .unwrap_or(0)
+    }
+
     /// Check if this client handles files with the given extension
     fn handles_extension(&self, ext: &str) -> bool {
         self.file_extensions.iter().any(|e| e == ext)
@@ -592,32 +651,60 @@ impl LspManager {
         Ok(started)
     }
 
-    /// Get diagnostics for a file with default wait time
-    pub async fn get_diagnostics_with_wait(&self, path: &Path) -> Vec<FileDiagnostic> {
-        self.get_diagnostics_with_custom_wait(path, DIAGNOSTIC_WAIT_MS)
-            .await
+    /// The client handling the file at `path`, already canonicalized.
+    fn client_for<'a>(clients: &'a [LspClient], path: &Path) -> Option<&'a LspClient> {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        clients.iter().find(|c| c.handles_extension(ext))
     }
 
-    /// Get diagnostics for a file with custom wait time
-    async fn get_diagnostics_with_custom_wait(
-        &self,
-        path: &Path,
-        wait_ms: u64,
-    ) -> Vec<FileDiagnostic> {
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    /// Notify the server of a changed file and wait for the diagnostics it
+    /// publishes in response, or until [`DIAGNOSTIC_WAIT_MS`] have passed.
+    /// Returns the diagnostics from before the change along with them.
+    pub async fn diagnose_change(&self, path: &Path, content: &str) -> ChangeDiagnostics {
+        let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let (previous, published) = {
+            let clients = self.clients.read().await;
+            match Self::client_for(&clients, &canonical) {
+                Some(client) => (
+                    client.get_diagnostics(&canonical).await,
+                    client.publish_count(&canonical).await,
+                ),
+                None => (Vec::new(), 0),
+            }
+        };
 
-        // Wait for LSP to process
-        tokio::time::sleep(std::time::Duration::from_millis(wait_ms)).await;
+        let started = match self.notify_file_changed(path, content).await {
+            Ok(started) => started,
+            Err(_) => {
+                return ChangeDiagnostics {
+                    previous,
+                    ..Default::default()
+                };
+            }
+        };
 
-        let clients = self.clients.read().await;
-        for client in clients.iter() {
-            if client.handles_extension(ext) {
-                return client.get_diagnostics(&path).await;
+        let deadline =
+            tokio::time::Instant::now() + std::time::Duration::from_millis(DIAGNOSTIC_WAIT_MS);
+        loop {
+            tokio::time::sleep(std::time::Duration::from_millis(DIAGNOSTIC_POLL_MS)).await;
+            let clients = self.clients.read().await;
+            let Some(client) = Self::client_for(&clients, &canonical) else {
+                return ChangeDiagnostics {
+                    started,
+                    previous,
+                    ..Default::default()
+                };
+            };
+            if client.publish_count(&canonical).await > published
+                || tokio::time::Instant::now() >= deadline
+            {
+                return ChangeDiagnostics {
+                    started,
+                    current: client.get_diagnostics(&canonical).await,
+                    previous,
+                };
             }
         }
-
-        Vec::new()
     }
 
     /// Check if any LSP server (running or pending) handles the given file extension
@@ -696,47 +783,91 @@ pub(crate) async fn register_configs(servers: Vec<LspServerConfig>) {
 }
 
 /// Format diagnostics for inclusion in tool results
-pub(crate) fn format_diagnostics(diagnostics: &[FileDiagnostic]) -> String {
-    if diagnostics.is_empty() {
-        return String::new();
-    }
-
-    let errors: Vec<_> = diagnostics
+pub(crate) fn format_diagnostics(change: &ChangeDiagnostics) -> String {
+    let count = |diagnostics: &[FileDiagnostic], severity| {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    };
+    let errors: Vec<_> = change
+        .current
         .iter()
         .filter(|d| d.severity == DiagnosticSeverity::ERROR)
         .collect();
-    let warnings: Vec<_> = diagnostics
+    let warnings: Vec<_> = change
+        .current
         .iter()
         .filter(|d| d.severity == DiagnosticSeverity::WARNING)
         .collect();
+    let previous_errors = count(&change.previous, DiagnosticSeverity::ERROR);
+
+    if errors.is_empty() && warnings.is_empty() {
+        if previous_errors > 0 {
+            return format!(
+                "\n\n--- LSP Diagnostics ---\nNo errors remain; {} fixed.\n",
+                if previous_errors == 1 {
+                    "1 error was".to_string()
+                } else {
+                    format!("{} errors were", previous_errors)
+                }
+            );
+        }
+        return String::new();
+    }
 
+    // Everything is new to a file that had no diagnostics, so only mark them
+    // when there were some.
+    let marked = !change.previous.is_empty();
     let mut result = String::new();
     result.push_str("\n\n--- LSP Diagnostics ---\n");
 
-    if !errors.is_empty() {
-        result.push_str(&format!("Errors ({}):\n", errors.len()));
-        for diag in errors.iter().take(MAX_ERRORS_DISPLAYED) {
-            result.push_str(&format!("  {}\n", diag.format()));
+    let mut push_section = |label: &str, diagnostics: &[&FileDiagnostic], max: usize| {
+        if diagnostics.is_empty() {
+            return;
         }
-        if errors.len() > MAX_ERRORS_DISPLAYED {
+        // New diagnostics are listed first, as those are the ones the change
+        // introduced.
+        let (new, old): (Vec<&FileDiagnostic>, Vec<&FileDiagnostic>) =
+            diagnostics.iter().partition(|d| change.is_new(d));
+        if !marked || new.is_empty() {
+            result.push_str(&format!("{} ({}):\n", label, diagnostics.len()));
+        } else {
             result.push_str(&format!(
-                "  ... and {} more errors\n",
-                errors.len() - MAX_ERRORS_DISPLAYED
+                "{} ({}, {} new):\n",
+                label,
+                diagnostics.len(),
+                new.len()
             ));
         }
-    }
-
-    if !warnings.is_empty() {
-        result.push_str(&format!("Warnings ({}):\n", warnings.len()));
-        for diag in warnings.iter().take(MAX_WARNINGS_DISPLAYED) {
-            result.push_str(&format!("  {}\n", diag.format()));
+        for diag in new.iter().chain(old.iter()).take(max) {
+            if marked && change.is_new(diag) {
+                result.push_str(&format!("  [new] {}\n", diag.format()));
+            } else {
+                result.push_str(&format!("  {}\n", diag.format()));
+            }
         }
-        if warnings.len() > MAX_WARNINGS_DISPLAYED {
+        if diagnostics.len() > max {
             result.push_str(&format!(
-                "  ... and {} more warnings\n",
-                warnings.len() - MAX_WARNINGS_DISPLAYED
+                "  ... and {} more {}\n",
+                diagnostics.len() - max,
+                label.to_lowercase()
             ));
         }
+    };
+    push_section("Errors", &errors, MAX_ERRORS_DISPLAYED);
+    push_section("Warnings", &warnings, MAX_WARNINGS_DISPLAYED);
+
+    if previous_errors > errors.len() {
+        result.push_str(&format!(
+            "{} fewer error{} than before this change.\n",
+            previous_errors - errors.len(),
+            if previous_errors - errors.len() == 1 {
+                ""
+            } else {
+                "s"
+            }
+        ));
     }
 
     result
@@ -774,3 +905,49 @@ pub(crate) fn diagnostic_summary(diagnostics: &[FileDiagnostic]) -> Option<Strin
     }
     Some(format!("[LSP diagnostics: {}]", parts.join(", ")))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(line: u32, message: &str, severity: DiagnosticSeverity) -> FileDiagnostic {
+        FileDiagnostic {
+            file_path: PathBuf::from("src/main.rs"),
+            line,
+            column: 0,
+            message: message.to_string(),
+            severity,
+        }
+    }
+
+    #[test]
+    fn test_format_diagnostics() {
+        let unused = diagnostic(2, "unused variable `x`", DiagnosticSeverity::WARNING);
+        let mismatch = diagnostic(5, "mismatched types", DiagnosticSeverity::ERROR);
+        let mut change = ChangeDiagnostics {
+            current: vec![
+                diagnostic(9, "mismatched types", DiagnosticSeverity::ERROR),
+                diagnostic(7, "cannot find value `y`", DiagnosticSeverity::ERROR),
+                diagnostic(3, "unused variable `x`", DiagnosticSeverity::WARNING),
+            ],
+            previous: vec![mismatch.clone(), unused.clone()],
+            ..Default::default()
+        };
+        // Diagnostics that only moved aren't new; new ones are listed first.
+        let out = format_diagnostics(&change);
+        assert!(out.contains(
+            "Errors (2, 1 new):\n  [new] src/main.rs:8:1: error: cannot find value `y`\n  src/main.rs:10:1"
+        ));
+        assert!(out.contains("Warnings (1):\n  src/main.rs:4:1"));
+
+        // Nothing marked when the file had no diagnostics before.
+        change.previous.clear();
+        assert!(format_diagnostics(&change).contains("Errors (2):\n  src/main.rs:10:1"));
+
+        change.current = vec![unused];
+        change.previous = vec![mismatch.clone(), mismatch];
+        assert!(format_diagnostics(&change).contains("2 fewer errors than before"));
+        change.current.clear();
+        assert!(format_diagnostics(&change).contains("No errors remain; 2 errors were fixed."));
+    }
+}
@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Quick-fix prompts from diagnostics.
+//!
+//! The latest errors and warnings reported for each file edited by a tool are
+//! kept here. After a turn the user is offered `/fix` (or Ctrl+F), which
+//! packages them with the surrounding code into a prompt, so errors don't have
+//! to be copied by hand.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use lsp_types::DiagnosticSeverity;
+
+use super::FileDiagnostic;
+
+/// Most diagnostics included in a prompt.
+const MAX_DIAGNOSTICS: usize = 20;
+
+/// Lines of code shown before and after a diagnostic.
+const CONTEXT_LINES: u32 = 3;
+
+#[derive(Default)]
+struct Pending {
+    files: BTreeMap<PathBuf, Vec<FileDiagnostic>>,
+    /// Whether the user has been told about the current diagnostics
+    notified: bool,
+}
+
+static PENDING: Mutex<Option<Pending>> = Mutex::new(None);
+
+fn with_pending<T>(f: impl FnOnce(&mut Pending) -> T) -> T {
+    let mut guard = PENDING.lock().unwrap();
+    f(guard.get_or_insert_with(Pending::default))
+}
+
+fn is_problem(diagnostic: &FileDiagnostic) -> bool {
+    diagnostic.severity == DiagnosticSeverity::ERROR
+        || diagnostic.severity == DiagnosticSeverity::WARNING
+}
+
+/// Replace the diagnostics of an edited file with the latest ones.
+pub(crate) fn record(path: &Path, diagnostics: &[FileDiagnostic]) {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let problems: Vec<FileDiagnostic> = diagnostics
+        .iter()
+        .filter(|d| is_problem(d))
+        .cloned()
+        .collect();
+    with_pending(|pending| {
+        let changed = if problems.is_empty() {
+            pending.files.remove(&path).is_some()
+        } else {
+            pending.files.insert(path, problems);
+            true
+        };
+        if changed {
+            pending.notified = false;
+        }
+    });
+}
+
+/// All pending diagnostics, errors first.
+pub(crate) fn pending() -> Vec<FileDiagnostic> {
+    let mut diagnostics: Vec<FileDiagnostic> =
+        with_pending(|pending| pending.files.values().flatten().cloned().collect());
+    diagnostics.sort_by_key(|d| d.severity != DiagnosticSeverity::ERROR);
+    diagnostics
+}
+
+pub(crate) fn has_pending() -> bool {
+    with_pending(|pending| !pending.files.is_empty())
+}
+
+/// Forget all pending diagnostics, such as when starting a new session.
+pub(crate) fn clear() {
+    *PENDING.lock().unwrap() = None;
+}
+
+/// A one-line offer to fix the pending diagnostics, returned once per change.
+pub(crate) fn take_hint(key: &str) -> Option<String> {
+    let diagnostics = pending();
+    let summary = super::diagnostic_summary(&diagnostics)?;
+    let notified = with_pending(|pending| std::mem::replace(&mut pending.notified, true));
+    if notified {
+        return None;
+    }
+    Some(format!(
+        "{} Press {} or type /fix to ask henri to fix them.",
+        summary, key
+    ))
+}
+
+/// Build a prompt asking the model to fix `diagnostics`, with the code
+/// around each one and any extra instructions from the user.
+pub(crate) fn prompt(
+    diagnostics: &[FileDiagnostic],
+    working_dir: &Path,
+    instructions: &str,
+) -> String {
+    let mut prompt = String::from(
+        "The language server reports these problems in files edited in this session. \
+         Fix them, addressing the cause rather than silencing the diagnostics, and keep \
+         the changes minimal.\n",
+    );
+
+    // Diagnostics carry canonical paths
+    let working_dir = std::fs::canonicalize(working_dir).unwrap_or_else(|_| working_dir.into());
+    let mut sources: BTreeMap<&Path, Option<Vec<String>>> = BTreeMap::new();
+    for diagnostic in diagnostics.iter().take(MAX_DIAGNOSTICS) {
+        let lines = sources.entry(&diagnostic.file_path).or_insert_with(|| {
+            std::fs::read_to_string(&diagnostic.file_path)
+                .ok()
+                .map(|s| s.lines().map(str::to_string).collect())
+        });
+        let path = diagnostic
+            .file_path
+            .strip_prefix(&working_dir)
+            .unwrap_or(&diagnostic.file_path);
+        let located = FileDiagnostic {
+            file_path: path.to_path_buf(),
+            ..diagnostic.clone()
+        };
+        prompt.push_str(&format!("\n{}\n", located.format()));
+        if let Some(lines) = lines {
+            prompt.push_str(&snippet(lines, diagnostic.line));
+        }
+    }
+    if diagnostics.len() > MAX_DIAGNOSTICS {
+        prompt.push_str(&format!(
+            "\n... and {} more\n",
+            diagnostics.len() - MAX_DIAGNOSTICS
+        ));
+    }
+
+    let instructions = instructions.trim();
+    if !instructions.is_empty() {
+        prompt.push_str(&format!(
+            "\nAdditional instructions from the user: {}\n",
+            instructions
+        ));
+    }
+    prompt
+}
+
+/// Numbered source lines around the 0-based `line`, marking that line.
+fn snippet(lines: &[String], line: u32) -> String {
+    let start = line.saturating_sub(CONTEXT_LINES) as usize;
+    let end = (line.saturating_add(CONTEXT_LINES) as usize + 1).min(lines.len());
+    if start >= end {
+        return String::new();
+    }
+    let mut out = String::from("```\n");
+    for (i, text) in lines[start..end].iter().enumerate() {
+        let number = start + i;
+        let marker = if number == line as usize { ">" } else { " " };
+        out.push_str(&format!("{}{:>5} | {}\n", marker, number + 1, text));
+    }
+    out.push_str("```\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("main.rs");
+        std::fs::write(&file, "fn main() {\n    let x: u32 = \"one\";\n}\n").unwrap();
+        let diagnostics = vec![FileDiagnostic {
+            file_path: file,
+            line: 1,
+            column: 17,
+            message: "mismatched types".to_string(),
+            severity: DiagnosticSeverity::ERROR,
+        }];
+
+        let prompt = prompt(&diagnostics, dir.path(), "don't change the type");
+        assert!(prompt.contains("\nmain.rs:2:18: error: mismatched types\n"));
+        assert!(prompt.contains(">    2 |     let x: u32 = \"one\";\n"));
+        assert!(prompt.contains("     3 | }\n"));
+        assert!(prompt.ends_with("Additional instructions from the user: don't change the type\n"));
+    }
+}
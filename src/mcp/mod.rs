@@ -1,28 +1,142 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Jason Ish
 
+mod prompts;
+mod sse;
+
+use std::collections::HashMap;
 use std::process::Stdio;
 use std::sync::Arc;
 
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use rmcp::RoleClient;
 use rmcp::ServiceExt;
 use rmcp::model::CallToolRequestParams;
+use rmcp::model::GetPromptRequestParams;
+use rmcp::model::ReadResourceRequestParams;
 use rmcp::model::Tool;
 use rmcp::service::RunningService;
-use rmcp::transport::TokioChildProcess;
+use rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig;
+use rmcp::transport::{StreamableHttpClientTransport, TokioChildProcess};
 use tokio::process::Command;
 use tokio::sync::RwLock;
 
-use crate::error::Result;
+use crate::config::McpRemoteTransport;
+use crate::error::{Error, Result};
 use crate::tools::{ToolDefinition, ToolResult};
 
+pub(crate) use prompts::PromptCommand;
+
 /// Configuration for an MCP server
 #[derive(Debug, Clone)]
 pub(crate) struct McpServerConfig {
     pub name: String,
-    pub _command: String,
-    pub _args: Vec<String>,
-    pub _env: std::collections::HashMap<String, String>,
+    pub transport: McpTransport,
+}
+
+/// How to reach an MCP server
+#[derive(Debug, Clone)]
+pub(crate) enum McpTransport {
+    /// A local process speaking over stdin/stdout
+    Stdio {
+        command: String,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+    },
+    /// A remote server, with the headers sent on every request
+    Remote {
+        url: String,
+        kind: McpRemoteTransport,
+        headers: Vec<(String, String)>,
+    },
+}
+
+impl McpServerConfig {
+    pub(crate) fn from_config(config: &crate::config::McpServerConfig) -> Self {
+        let transport = match &config.url {
+            Some(url) => {
+                let mut headers: Vec<(String, String)> = config
+                    .headers
+                    .iter()
+                    .map(|(name, value)| (name.clone(), expand_env(value)))
+                    .collect();
+                if let Some(token) = &config.bearer_token {
+                    headers.push((
+                        "Authorization".to_string(),
+                        format!("Bearer {}", expand_env(token)),
+                    ));
+                }
+                McpTransport::Remote {
+                    url: url.clone(),
+                    kind: config.transport,
+                    headers,
+                }
+            }
+            None => McpTransport::Stdio {
+                command: config.command.clone(),
+                args: config.args.clone(),
+                env: config.env.clone(),
+            },
+        };
+        Self {
+            name: config.name.clone(),
+            transport,
+        }
+    }
+}
+
+impl McpTransport {
+    /// Short description for the MCP menu
+    fn describe(&self) -> String {
+        match self {
+            McpTransport::Stdio { command, .. } => format!("stdio: {}", command),
+            McpTransport::Remote {
+                url,
+                kind: McpRemoteTransport::Http,
+                ..
+            } => format!("http: {}", url),
+            McpTransport::Remote {
+                url,
+                kind: McpRemoteTransport::Sse,
+                ..
+            } => format!("sse: {}", url),
+        }
+    }
+}
+
+/// Replace `${NAME}` with the value of the environment variable `NAME`, so
+/// tokens don't have to be stored in the config file.
+fn expand_env(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let name = &rest[start + 2..start + 2 + len];
+        out.push_str(&std::env::var(name).unwrap_or_default());
+        rest = &rest[start + 3 + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Build an HTTP client that sends `headers` with every request.
+fn http_client(headers: &[(String, String)]) -> Result<reqwest::Client> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| Error::Config(format!("Invalid MCP header name {}: {}", name, e)))?;
+        let mut value = HeaderValue::from_str(value)
+            .map_err(|e| Error::Config(format!("Invalid value for MCP header {}: {}", name, e)))?;
+        value.set_sensitive(true);
+        map.insert(name, value);
+    }
+    Ok(reqwest::Client::builder()
+        .user_agent(format!("henri/{}", crate::version::VERSION))
+        .default_headers(map)
+        .build()?)
 }
 
 /// A running MCP server client
@@ -30,6 +144,9 @@ struct McpClient {
     name: String,
     service: RunningService<RoleClient, ()>,
     tools: Vec<Tool>,
+    prompts: Vec<PromptCommand>,
+    /// Whether the server offers resources
+    has_resources: bool,
 }
 
 /// Status of an MCP server
@@ -38,6 +155,11 @@ pub(crate) struct McpServerStatus {
     pub name: String,
     pub is_running: bool,
     pub tool_count: usize,
+    /// Transport and command or URL
+    pub endpoint: String,
+    /// Why the server isn't usable: it failed to start or its connection
+    /// closed
+    pub error: Option<String>,
 }
 
 /// Manager for multiple MCP server connections
@@ -46,6 +168,11 @@ pub(crate) struct McpManager {
     clients: RwLock<Vec<McpClient>>,
     /// Configured servers (not necessarily running)
     configured_servers: RwLock<Vec<McpServerConfig>>,
+    /// Last error starting each server
+    errors: RwLock<HashMap<String, String>>,
+    /// Prompts of the running servers, readable without awaiting so the
+    /// slash command menu can list them
+    prompt_commands: std::sync::RwLock<Vec<PromptCommand>>,
 }
 
 impl McpManager {
@@ -53,6 +180,8 @@ impl McpManager {
         Self {
             clients: RwLock::new(Vec::new()),
             configured_servers: RwLock::new(Vec::new()),
+            errors: RwLock::new(HashMap::new()),
+            prompt_commands: std::sync::RwLock::new(Vec::new()),
         }
     }
 
@@ -66,68 +195,220 @@ impl McpManager {
     pub(crate) async fn server_statuses(&self) -> Vec<McpServerStatus> {
         let configured = self.configured_servers.read().await;
         let clients = self.clients.read().await;
+        let errors = self.errors.read().await;
 
         configured
             .iter()
             .map(|config| {
                 let client = clients.iter().find(|c| c.name == config.name);
+                let error = match client {
+                    Some(c) if c.service.is_transport_closed() => {
+                        Some("connection closed".to_string())
+                    }
+                    Some(_) => None,
+                    None => errors.get(&config.name).cloned(),
+                };
                 McpServerStatus {
                     name: config.name.clone(),
                     is_running: client.is_some(),
                     tool_count: client.map(|c| c.tools.len()).unwrap_or(0),
+                    endpoint: config.transport.describe(),
+                    error,
                 }
             })
             .collect()
     }
 
-    /// Start an MCP server and connect to it
+    /// Start an MCP server, recording the error if it fails
     async fn start_server(&self, config: &McpServerConfig) -> Result<usize> {
+        let result = self.connect(config).await;
+        let mut errors = self.errors.write().await;
+        match &result {
+            Ok(_) => errors.remove(&config.name),
+            Err(e) => errors.insert(config.name.clone(), e.to_string()),
+        };
+        result
+    }
+
+    /// Start or connect to an MCP server
+    async fn connect(&self, config: &McpServerConfig) -> Result<usize> {
         let mut clients = self.clients.write().await;
         if let Some(existing) = clients.iter().find(|c| c.name == config.name) {
             return Ok(existing.tools.len());
         }
 
-        let mut cmd = Command::new(&config._command);
-        for arg in &config._args {
-            cmd.arg(arg);
-        }
-        // Set environment variables for the MCP server
-        for (key, value) in &config._env {
-            cmd.env(key, value);
-        }
-
-        // Use builder to redirect stderr to null to avoid corrupting output
-        let (transport, _stderr) = TokioChildProcess::builder(cmd)
-            .stderr(Stdio::null())
-            .spawn()
-            .map_err(|e| {
-                crate::error::Error::Other(format!("Failed to spawn MCP server: {}", e))
-            })?;
+        let service = match &config.transport {
+            McpTransport::Stdio { command, args, env } => {
+                if command.is_empty() {
+                    return Err(Error::Config(format!(
+                        "MCP server '{}' has neither a command nor a url",
+                        config.name
+                    )));
+                }
+                let mut cmd = Command::new(command);
+                for arg in args {
+                    cmd.arg(arg);
+                }
+                // Set environment variables for the MCP server
+                for (key, value) in env {
+                    cmd.env(key, value);
+                }
 
-        let service = ().serve(transport).await.map_err(|e| {
-            crate::error::Error::Other(format!("Failed to initialize MCP client: {}", e))
-        })?;
+                // Use builder to redirect stderr to null to avoid corrupting output
+                let (transport, _stderr) = TokioChildProcess::builder(cmd)
+                    .stderr(Stdio::null())
+                    .spawn()
+                    .map_err(|e| Error::Other(format!("Failed to spawn MCP server: {}", e)))?;
+                ().serve(transport).await
+            }
+            McpTransport::Remote {
+                url,
+                kind: McpRemoteTransport::Http,
+                headers,
+            } => {
+                let transport = StreamableHttpClientTransport::with_client(
+                    http_client(headers)?,
+                    StreamableHttpClientTransportConfig::with_uri(url.as_str()),
+                );
+                ().serve(transport).await
+            }
+            McpTransport::Remote {
+                url,
+                kind: McpRemoteTransport::Sse,
+                headers,
+            } => {
+                let transport = sse::SseTransport::connect(http_client(headers)?, url).await?;
+                ().serve(transport).await
+            }
+        }
+        .map_err(|e| Error::Other(format!("Failed to initialize MCP client: {}", e)))?;
 
         // List available tools from this server
         let tools_result = service
             .list_tools(Default::default())
             .await
-            .map_err(|e| crate::error::Error::Other(format!("Failed to list tools: {}", e)))?;
+            .map_err(|e| Error::Other(format!("Failed to list tools: {}", e)))?;
 
         let tools = tools_result.tools;
         let tool_count = tools.len();
 
+        let capabilities = service.peer_info().map(|info| &info.capabilities);
+        let has_prompts = capabilities.is_some_and(|c| c.prompts.is_some());
+        let has_resources = capabilities.is_some_and(|c| c.resources.is_some());
+        let prompts = if has_prompts {
+            service
+                .list_all_prompts()
+                .await
+                .map_err(|e| Error::Other(format!("Failed to list prompts: {}", e)))?
+                .into_iter()
+                .map(|prompt| PromptCommand {
+                    name: format!("{}:{}", config.name, prompt.name),
+                    server: config.name.clone(),
+                    description: prompt
+                        .description
+                        .or(prompt.title)
+                        .unwrap_or_else(|| format!("Prompt from {}", config.name)),
+                    prompt: prompt.name,
+                    arguments: prompt.arguments.unwrap_or_default(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         let client = McpClient {
             name: config.name.clone(),
             service,
             tools,
+            prompts,
+            has_resources,
         };
 
         clients.push(client);
+        self.update_prompt_commands(&clients);
 
         Ok(tool_count)
     }
 
+    fn update_prompt_commands(&self, clients: &[McpClient]) {
+        let commands = clients.iter().flat_map(|c| c.prompts.clone()).collect();
+        if let Ok(mut prompt_commands) = self.prompt_commands.write() {
+            *prompt_commands = commands;
+        }
+    }
+
+    /// Prompts of the running servers, offered as slash commands
+    pub(crate) fn prompt_commands(&self) -> Vec<PromptCommand> {
+        self.prompt_commands
+            .read()
+            .map(|commands| commands.clone())
+            .unwrap_or_default()
+    }
+
+    /// Get a server prompt with the arguments typed after its command, as
+    /// the text to send to the model.
+    pub(crate) async fn get_prompt(&self, name: &str, args: &str) -> Result<String> {
+        let command = self
+            .prompt_commands()
+            .into_iter()
+            .find(|c| c.name == name)
+            .ok_or_else(|| Error::Other(format!("MCP prompt '{}' is not available", name)))?;
+        let arguments = command.parse_arguments(args).map_err(Error::Other)?;
+
+        let clients = self.clients.read().await;
+        let client = clients
+            .iter()
+            .find(|c| c.name == command.server)
+            .ok_or_else(|| {
+                Error::Other(format!("MCP server '{}' is not running", command.server))
+            })?;
+        let result = client
+            .service
+            .get_prompt(GetPromptRequestParams {
+                meta: None,
+                name: command.prompt.clone(),
+                arguments: (!arguments.is_empty()).then_some(arguments),
+            })
+            .await
+            .map_err(|e| Error::Other(format!("Failed to get MCP prompt: {}", e)))?;
+        Ok(prompts::render_messages(&result.messages))
+    }
+
+    /// Append the contents of the `@server:uri` resources referenced in
+    /// `input`. Returns the expanded input and a message for each resource
+    /// that couldn't be read.
+    pub(crate) async fn expand_resources(&self, input: &str) -> (String, Vec<String>) {
+        let clients = self.clients.read().await;
+        let servers: Vec<String> = clients
+            .iter()
+            .filter(|c| c.has_resources)
+            .map(|c| c.name.clone())
+            .collect();
+
+        let mut expanded = input.to_string();
+        let mut errors = Vec::new();
+        for (server, uri) in prompts::resource_references(input, &servers) {
+            let Some(client) = clients.iter().find(|c| c.name == server) else {
+                continue;
+            };
+            let result = client
+                .service
+                .read_resource(ReadResourceRequestParams {
+                    meta: None,
+                    uri: uri.to_string(),
+                })
+                .await;
+            match result {
+                Ok(result) => {
+                    expanded.push_str("\n\n");
+                    expanded.push_str(&prompts::render_contents(&result.contents));
+                }
+                Err(e) => errors.push(format!("Failed to read @{}:{}: {}", server, uri, e)),
+            }
+        }
+        (expanded, errors)
+    }
+
     /// Stop an MCP server
     async fn stop_server(&self, name: &str) {
         let mut clients = self.clients.write().await;
@@ -135,6 +416,7 @@ impl McpManager {
             let client = clients.remove(pos);
             // The service will be dropped here, which should close the connection
             drop(client);
+            self.update_prompt_commands(&clients);
         }
     }
 
@@ -163,7 +445,7 @@ impl McpManager {
                     let tool_count = self.start_server(&cfg).await?;
                     Ok((true, tool_count))
                 }
-                None => Err(crate::error::Error::Other(format!(
+                None => Err(Error::Other(format!(
                     "MCP server '{}' not found in configuration",
                     name
                 ))),
@@ -300,3 +582,47 @@ pub(crate) async fn register_servers(servers: Vec<McpServerConfig>) {
     let mgr = manager();
     mgr.register_servers(servers).await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_env() {
+        let home = std::env::var("HOME").unwrap_or_default();
+        assert_eq!(expand_env("a ${HOME} b"), format!("a {} b", home));
+        assert_eq!(expand_env("x${HENRI_TEST_UNSET_VAR}y"), "xy");
+        assert_eq!(expand_env("no ${closing"), "no ${closing");
+    }
+
+    #[test]
+    fn test_remote_config() {
+        let config: crate::config::McpServerConfig = toml::from_str(
+            r#"
+            name = "remote"
+            url = "https://example.com/sse"
+            transport = "sse"
+            bearer-token = "secret"
+            headers = { X-Team = "core" }
+            "#,
+        )
+        .unwrap();
+        let server = McpServerConfig::from_config(&config);
+        let McpTransport::Remote { kind, headers, .. } = &server.transport else {
+            panic!("expected a remote transport");
+        };
+        assert_eq!(*kind, McpRemoteTransport::Sse);
+        assert!(headers.contains(&("X-Team".to_string(), "core".to_string())));
+        assert!(headers.contains(&("Authorization".to_string(), "Bearer secret".to_string())));
+        assert_eq!(server.transport.describe(), "sse: https://example.com/sse");
+        assert!(http_client(headers).is_ok());
+
+        // Without a url it is a local server
+        let config: crate::config::McpServerConfig =
+            toml::from_str("name = \"local\"\ncommand = \"mcp-fs\"").unwrap();
+        assert!(matches!(
+            McpServerConfig::from_config(&config).transport,
+            McpTransport::Stdio { .. }
+        ));
+    }
+}
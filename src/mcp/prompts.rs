@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! MCP prompts and resources.
+//!
+//! A server's prompts are offered as slash commands named `server:prompt`.
+//! Arguments are given as `name=value` words, or positionally in the order the
+//! server declares them, with the last one taking the rest of the line. The
+//! messages the server returns are flattened into a single prompt.
+//!
+//! `@server:uri` in a message reads that resource from the server and appends
+//! its contents to the message.
+
+use rmcp::model::{
+    JsonObject, PromptArgument, PromptMessage, PromptMessageContent, PromptMessageRole,
+    ResourceContents,
+};
+
+/// A server prompt offered as a slash command.
+#[derive(Debug, Clone)]
+pub(crate) struct PromptCommand {
+    /// Command name, `server:prompt`
+    pub name: String,
+    pub server: String,
+    pub prompt: String,
+    pub description: String,
+    pub arguments: Vec<PromptArgument>,
+}
+
+impl PromptCommand {
+    fn usage(&self) -> String {
+        let args: Vec<String> = self
+            .arguments
+            .iter()
+            .map(|arg| {
+                if arg.required.unwrap_or(false) {
+                    format!("<{}>", arg.name)
+                } else {
+                    format!("[{}]", arg.name)
+                }
+            })
+            .collect();
+        format!("Usage: /{} {}", self.name, args.join(" "))
+            .trim_end()
+            .to_string()
+    }
+
+    /// Map the text after the command onto the declared arguments.
+    pub(super) fn parse_arguments(&self, args: &str) -> Result<JsonObject, String> {
+        let mut values = JsonObject::new();
+        let mut positional = Vec::new();
+        for word in args.split_whitespace() {
+            match word.split_once('=') {
+                Some((name, value)) if self.arguments.iter().any(|a| a.name == name) => {
+                    values.insert(name.to_string(), value.into());
+                }
+                _ => positional.push(word),
+            }
+        }
+
+        let unfilled: Vec<&PromptArgument> = self
+            .arguments
+            .iter()
+            .filter(|a| !values.contains_key(&a.name))
+            .collect();
+        if !positional.is_empty() && unfilled.is_empty() {
+            return Err(self.usage());
+        }
+        let mut words = positional.into_iter();
+        for (i, arg) in unfilled.iter().enumerate() {
+            let value = if i + 1 == unfilled.len() {
+                words.by_ref().collect::<Vec<_>>().join(" ")
+            } else {
+                words.next().unwrap_or_default().to_string()
+            };
+            if !value.is_empty() {
+                values.insert(arg.name.clone(), value.into());
+            }
+        }
+
+        let missing = self
+            .arguments
+            .iter()
+            .any(|a| a.required.unwrap_or(false) && !values.contains_key(&a.name));
+        if missing {
+            return Err(self.usage());
+        }
+        Ok(values)
+    }
+}
+
+/// Flatten the messages of a prompt into the text sent to the model.
+pub(super) fn render_messages(messages: &[PromptMessage]) -> String {
+    let parts: Vec<String> = messages
+        .iter()
+        .map(|message| {
+            let text = match &message.content {
+                PromptMessageContent::Text { text } => text.clone(),
+                PromptMessageContent::Resource { resource } => {
+                    render_contents(std::slice::from_ref(&resource.resource))
+                }
+                PromptMessageContent::ResourceLink { link } => {
+                    format!("Resource: {}", link.uri)
+                }
+                PromptMessageContent::Image { .. } => "[Image omitted]".to_string(),
+            };
+            match message.role {
+                PromptMessageRole::User => text,
+                PromptMessageRole::Assistant => format!("Assistant: {}", text),
+            }
+        })
+        .collect();
+    parts.join("\n\n")
+}
+
+/// Find the `@server:uri` references to the given servers in `input`.
+pub(super) fn resource_references<'a>(
+    input: &'a str,
+    servers: &[String],
+) -> Vec<(&'a str, &'a str)> {
+    input
+        .split_whitespace()
+        .filter_map(|word| {
+            let (server, uri) = word.strip_prefix('@')?.split_once(':')?;
+            let uri = uri.trim_end_matches([',', '.', ';', '!', '?']);
+            (!uri.is_empty() && servers.iter().any(|s| s == server)).then_some((server, uri))
+        })
+        .collect()
+}
+
+/// Render the contents of a resource for the model.
+pub(super) fn render_contents(contents: &[ResourceContents]) -> String {
+    contents
+        .iter()
+        .map(|content| match content {
+            ResourceContents::TextResourceContents { uri, text, .. } => {
+                format!(
+                    "<resource uri=\"{}\">\n{}\n</resource>",
+                    uri,
+                    text.trim_end()
+                )
+            }
+            ResourceContents::BlobResourceContents { uri, mime_type, .. } => format!(
+                "<resource uri=\"{}\">[Binary content ({}) omitted]</resource>",
+                uri,
+                mime_type.as_deref().unwrap_or("unknown type")
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argument(name: &str, required: bool) -> PromptArgument {
+        PromptArgument {
+            name: name.to_string(),
+            title: None,
+            description: None,
+            required: Some(required),
+        }
+    }
+
+    fn command(arguments: Vec<PromptArgument>) -> PromptCommand {
+        PromptCommand {
+            name: "git:review".to_string(),
+            server: "git".to_string(),
+            prompt: "review".to_string(),
+            description: String::new(),
+            arguments,
+        }
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let cmd = command(vec![argument("branch", true), argument("focus", false)]);
+        let values = cmd.parse_arguments("main error handling").unwrap();
+        assert_eq!(values["branch"], "main");
+        assert_eq!(values["focus"], "error handling");
+
+        let values = cmd.parse_arguments("focus=tests dev").unwrap();
+        assert_eq!(values["branch"], "dev");
+        assert_eq!(values["focus"], "tests");
+
+        assert_eq!(
+            cmd.parse_arguments("").unwrap_err(),
+            "Usage: /git:review <branch> [focus]"
+        );
+        assert!(command(vec![]).parse_arguments("extra").is_err());
+        assert!(command(vec![]).parse_arguments("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resource_references() {
+        let servers = vec!["docs".to_string()];
+        assert_eq!(
+            resource_references(
+                "Compare @docs:file:///api.md, with @other:x and me@docs.com",
+                &servers
+            ),
+            vec![("docs", "file:///api.md")]
+        );
+    }
+}
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Client side of the older HTTP+SSE MCP transport.
+//!
+//! The client opens an event stream with a GET request. The server's first
+//! event carries the endpoint that messages are POSTed to; its responses and
+//! notifications then arrive as further events on the stream. rmcp only
+//! implements Streamable HTTP, which replaced this transport, but some
+//! servers still only speak this one.
+
+use rmcp::RoleClient;
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+use rmcp::transport::Transport;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::error::{Error, Result};
+use crate::sse::SseStream;
+
+pub(super) struct SseTransport {
+    client: reqwest::Client,
+    endpoint: url::Url,
+    messages: mpsc::Receiver<ServerJsonRpcMessage>,
+    reader: JoinHandle<()>,
+}
+
+impl SseTransport {
+    /// Open the event stream at `url` and wait for the message endpoint.
+    pub(super) async fn connect(client: reqwest::Client, url: &str) -> Result<Self> {
+        let base = url::Url::parse(url)
+            .map_err(|e| Error::Config(format!("Invalid MCP server URL {}: {}", url, e)))?;
+        let response = client
+            .get(base.clone())
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .send()
+            .await?
+            .error_for_status()?;
+        let mut events = SseStream::new(Box::pin(response.bytes_stream()));
+
+        // The first event is the endpoint, relative to the stream URL
+        let endpoint = match events.next_event().await {
+            Some(Ok(data)) => base
+                .join(data.trim())
+                .map_err(|e| Error::Other(format!("Invalid endpoint from MCP server: {}", e)))?,
+            Some(Err(e)) => return Err(e.into()),
+            None => {
+                return Err(Error::Other(
+                    "MCP server closed the event stream before sending an endpoint".to_string(),
+                ));
+            }
+        };
+
+        let (tx, messages) = mpsc::channel(16);
+        let reader = tokio::spawn(async move {
+            while let Some(Ok(data)) = events.next_event().await {
+                // Skip anything that isn't a JSON-RPC message, such as pings
+                let Ok(message) = serde_json::from_str(&data) else {
+                    continue;
+                };
+                if tx.send(message).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            client,
+            endpoint,
+            messages,
+            reader,
+        })
+    }
+}
+
+impl Transport<RoleClient> for SseTransport {
+    type Error = reqwest::Error;
+
+    fn send(
+        &mut self,
+        item: ClientJsonRpcMessage,
+    ) -> impl Future<Output = std::result::Result<(), Self::Error>> + Send + 'static {
+        let request = self.client.post(self.endpoint.clone()).json(&item);
+        async move {
+            request.send().await?.error_for_status()?;
+            Ok(())
+        }
+    }
+
+    async fn receive(&mut self) -> Option<ServerJsonRpcMessage> {
+        self.messages.recv().await
+    }
+
+    async fn close(&mut self) -> std::result::Result<(), Self::Error> {
+        self.reader.abort();
+        Ok(())
+    }
+}
+
+impl Drop for SseTransport {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
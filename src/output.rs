@@ -38,6 +38,11 @@ pub(crate) enum OutputEvent {
     Warning(String),
     /// Waiting for model response
     Waiting,
+    /// Rate limited; the request is resent once `wait` has passed
+    RateLimited {
+        message: String,
+        wait: std::time::Duration,
+    },
     /// Model finished responding
     Done,
     /// Interaction was interrupted
@@ -65,6 +70,7 @@ pub(crate) enum OutputEvent {
         summary: Option<String>,
     },
     /// Image preview (for terminals that support inline images)
+    #[cfg(feature = "images")]
     ImagePreview {
         /// Base64-encoded image data
         data: String,
@@ -103,8 +109,7 @@ impl OutputContext {
         }
     }
 
-    /// Create a null output context that discards all events (for tests)
-    #[cfg(test)]
+    /// Create a null output context that discards all events
     pub(crate) fn null() -> Self {
         Self { listener: None }
     }
@@ -115,6 +120,32 @@ impl OutputContext {
             listener.on_event(&event);
         }
     }
+
+    /// A context that also shows every event to `observer`, before the
+    /// listener of this one.
+    pub(crate) fn with_observer(&self, observer: Arc<dyn OutputListener>) -> Self {
+        Self {
+            listener: Some(Arc::new(Observed {
+                observer,
+                listener: self.listener.clone(),
+            })),
+        }
+    }
+}
+
+/// Listener of [`OutputContext::with_observer`].
+struct Observed {
+    observer: Arc<dyn OutputListener>,
+    listener: Option<Arc<dyn OutputListener>>,
+}
+
+impl OutputListener for Observed {
+    fn on_event(&self, event: &OutputEvent) {
+        self.observer.on_event(event);
+        if let Some(listener) = &self.listener {
+            listener.on_event(event);
+        }
+    }
 }
 
 pub(crate) fn menu_page_size() -> usize {
@@ -282,6 +313,14 @@ pub(crate) fn emit_warning(ctx: &OutputContext, message: &str) {
     ctx.emit(OutputEvent::Warning(message.to_string()));
 }
 
+/// Emit a rate-limit wait before the request is resent
+pub(crate) fn emit_rate_limited(ctx: &OutputContext, message: &str, wait: std::time::Duration) {
+    ctx.emit(OutputEvent::RateLimited {
+        message: message.to_string(),
+        wait,
+    });
+}
+
 /// Emit auto-compaction starting
 pub(crate) fn emit_auto_compact_starting(ctx: &OutputContext, current_usage: u64, limit: u64) {
     ctx.emit(OutputEvent::AutoCompactStarting {
@@ -296,6 +335,7 @@ pub(crate) fn emit_auto_compact_completed(ctx: &OutputContext, messages_compacte
 }
 
 /// Emit image preview for terminals that support inline images
+#[cfg(feature = "images")]
 pub(crate) fn emit_image_preview(ctx: &OutputContext, data: String, mime_type: String) {
     ctx.emit(OutputEvent::ImagePreview { data, mime_type });
 }
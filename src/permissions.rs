@@ -0,0 +1,331 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Approval of tool actions.
+//!
+//! When `permissions.bash` is "ask", bash commands that don't match an
+//! allowed command prefix pause the chat loop until the user allows the
+//! command once, allows its prefix always, or denies it. Writes outside the
+//! working directory that the sandbox would block are handled the same way,
+//! with "always" allowing the containing directory. "Always" answers are
+//! saved to the config file.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use crate::config::{BashApproval, ConfigFile, PermissionsConfig};
+use crate::output;
+use crate::services::Services;
+
+/// The user's answer to an approval prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Decision {
+    Once,
+    Always,
+    Deny,
+}
+
+/// Sender for the answer to the approval prompt currently being shown.
+static PENDING_DECISION: Mutex<Option<oneshot::Sender<Decision>>> = Mutex::new(None);
+
+/// Whether the chat loop is paused waiting for an approval.
+pub(crate) fn awaiting_decision() -> bool {
+    PENDING_DECISION.lock().unwrap().is_some()
+}
+
+/// Answer the approval prompt.
+pub(crate) fn decide(decision: Decision) {
+    if let Some(tx) = PENDING_DECISION.lock().unwrap().take() {
+        let _ = tx.send(decision);
+    }
+}
+
+/// Split a shell command into the simple commands it runs.
+fn command_segments(command: &str) -> Vec<String> {
+    // Redirections such as `2>&1` and `&>` aren't command separators
+    let command = command
+        .replace(">&", ">")
+        .replace("<&", "<")
+        .replace("&>", ">");
+    command
+        .split(['\n', ';', '&', '|'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether a command runs other commands in ways prefixes can't describe.
+fn has_substitution(command: &str) -> bool {
+    command.contains("$(")
+        || command.contains('`')
+        || command.contains("<(")
+        || command.contains(">(")
+}
+
+/// The prefix saved when a command is allowed always: the program, plus its
+/// subcommand when the second word looks like one (e.g. "cargo test").
+pub(crate) fn command_prefix(segment: &str) -> String {
+    let mut words = segment.split_whitespace();
+    let Some(program) = words.next() else {
+        return String::new();
+    };
+    match words.next() {
+        Some(sub)
+            if !sub.starts_with('-')
+                && sub
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+        {
+            format!("{} {}", program, sub)
+        }
+        _ => program.to_string(),
+    }
+}
+
+fn matches_prefix(segment: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim();
+    !prefix.is_empty()
+        && segment
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+}
+
+/// Prefixes of the segments of `command` that no allowed prefix covers.
+fn unapproved_prefixes(command: &str, allowed: &[String]) -> Vec<String> {
+    let mut missing: Vec<String> = Vec::new();
+    for segment in command_segments(command) {
+        if allowed.iter().any(|p| matches_prefix(&segment, p)) {
+            continue;
+        }
+        let prefix = command_prefix(&segment);
+        if !missing.contains(&prefix) {
+            missing.push(prefix);
+        }
+    }
+    missing
+}
+
+/// Approval state, shared through [`Services`].
+#[derive(Default)]
+pub(crate) struct Permissions {
+    config: RwLock<PermissionsConfig>,
+}
+
+impl Permissions {
+    pub(crate) fn new(config: PermissionsConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+        }
+    }
+
+    /// Load the permissions from the config file.
+    pub(crate) fn load() -> Self {
+        Self::new(ConfigFile::load().unwrap_or_default().permissions)
+    }
+
+    /// Directories outside the working directory that tools may write to.
+    pub(crate) fn allowed_paths(&self) -> Vec<PathBuf> {
+        self.config.read().unwrap().allowed_paths.clone()
+    }
+
+    /// Check that a bash command may run, asking the user when needed.
+    ///
+    /// Returns the reason when the command must not run.
+    pub(crate) async fn approve_bash(
+        &self,
+        command: &str,
+        services: &Services,
+        output: &output::OutputContext,
+    ) -> Result<(), String> {
+        let missing = {
+            let config = self.config.read().unwrap();
+            if config.bash == BashApproval::Allow {
+                return Ok(());
+            }
+            unapproved_prefixes(command, &config.allowed_commands)
+        };
+        let substitution = has_substitution(command);
+        if missing.is_empty() && !substitution {
+            return Ok(());
+        }
+
+        if !crate::watchdog::is_interactive() {
+            return Err(
+                "Bash commands need approval and there is nobody to approve them. \
+                Add the command to permissions.allowed-commands."
+                    .to_string(),
+            );
+        }
+
+        let question = if substitution {
+            format!(
+                "Allow bash command `{}`? [y] once, [n] deny",
+                command.trim()
+            )
+        } else {
+            format!(
+                "Allow bash command `{}`? [y] once, [a] always allow `{}`, [n] deny",
+                command.trim(),
+                missing.join("`, `")
+            )
+        };
+
+        match ask(&question, services, output).await {
+            Decision::Once => Ok(()),
+            Decision::Always if substitution => Ok(()),
+            Decision::Always => {
+                self.persist(output, |config| {
+                    for prefix in &missing {
+                        if !config.allowed_commands.contains(prefix) {
+                            config.allowed_commands.push(prefix.clone());
+                        }
+                    }
+                });
+                Ok(())
+            }
+            Decision::Deny => Err("The user denied permission to run this command".to_string()),
+        }
+    }
+
+    /// Ask whether a write outside the working directory, which the sandbox
+    /// would block, may go ahead. Returns false without asking when nobody
+    /// can answer.
+    pub(crate) async fn approve_write(
+        &self,
+        path: &Path,
+        services: &Services,
+        output: &output::OutputContext,
+    ) -> bool {
+        if !crate::watchdog::is_interactive() {
+            return false;
+        }
+
+        let dir = path.parent().unwrap_or(path).to_path_buf();
+        let question = format!(
+            "Allow writing {} outside the working directory? [y] once, [a] always allow {}, [n] deny",
+            path.display(),
+            dir.display()
+        );
+
+        match ask(&question, services, output).await {
+            Decision::Once => true,
+            Decision::Always => {
+                self.persist(output, |config| {
+                    if !config.allowed_paths.contains(&dir) {
+                        config.allowed_paths.push(dir.clone());
+                    }
+                });
+                true
+            }
+            Decision::Deny => false,
+        }
+    }
+
+    /// Apply `update` to the in-memory settings and save it to the config file.
+    fn persist(&self, output: &output::OutputContext, update: impl Fn(&mut PermissionsConfig)) {
+        update(&mut self.config.write().unwrap());
+        let result = ConfigFile::load().and_then(|mut config| {
+            update(&mut config.permissions);
+            config.save_without_backup()
+        });
+        if let Err(e) = result {
+            output::emit_warning(output, &format!("Failed to save permissions: {}", e));
+        }
+    }
+}
+
+/// Show `question` and wait for the user's decision.
+///
+/// Returns `Deny` when the user interrupts.
+async fn ask(question: &str, services: &Services, output: &output::OutputContext) -> Decision {
+    let (tx, rx) = oneshot::channel();
+    *PENDING_DECISION.lock().unwrap() = Some(tx);
+    output::emit_warning(output, question);
+
+    let decision = tokio::select! {
+        biased;
+        _ = async {
+            while !services.is_interrupted() {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        } => Decision::Deny,
+        decision = rx => decision.unwrap_or(Decision::Deny),
+    };
+    PENDING_DECISION.lock().unwrap().take();
+    decision
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_prefix() {
+        assert_eq!(command_prefix("cargo test --all"), "cargo test");
+        assert_eq!(command_prefix("git status"), "git status");
+        assert_eq!(command_prefix("ls -la"), "ls");
+        assert_eq!(command_prefix("cat src/main.rs"), "cat");
+        assert_eq!(command_prefix("pwd"), "pwd");
+    }
+
+    #[test]
+    fn test_unapproved_prefixes_checks_every_segment() {
+        let allowed = vec!["cargo test".to_string(), "git status".to_string()];
+        assert!(unapproved_prefixes("cargo test -- --nocapture", &allowed).is_empty());
+        assert!(unapproved_prefixes("git status && cargo test", &allowed).is_empty());
+        assert!(unapproved_prefixes("cargo test 2>&1", &allowed).is_empty());
+        assert_eq!(
+            unapproved_prefixes("cargo test && rm -rf target", &allowed),
+            vec!["rm"]
+        );
+        assert_eq!(
+            unapproved_prefixes("cargo testing", &allowed),
+            vec!["cargo testing"]
+        );
+        assert_eq!(
+            unapproved_prefixes("cargo build | tee -a log; cargo build", &allowed),
+            vec!["cargo build", "tee"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_approve_bash_without_prompt() {
+        let services = Services::null();
+        let output = output::OutputContext::null();
+
+        let allow = Permissions::default();
+        assert!(
+            allow
+                .approve_bash("rm -rf target", &services, &output)
+                .await
+                .is_ok()
+        );
+
+        let ask = Permissions::new(PermissionsConfig {
+            bash: BashApproval::Ask,
+            allowed_commands: vec!["cargo test".to_string()],
+            allowed_paths: Vec::new(),
+        });
+        assert!(
+            ask.approve_bash("cargo test", &services, &output)
+                .await
+                .is_ok()
+        );
+        // Not interactive, so nobody can approve it
+        assert!(
+            ask.approve_bash("cargo test $(rm -rf /)", &services, &output)
+                .await
+                .is_err()
+        );
+        assert!(
+            ask.approve_bash("cargo test > >(sh -c 'rm -rf ~')", &services, &output)
+                .await
+                .is_err()
+        );
+        assert!(ask.approve_bash("make", &services, &output).await.is_err());
+    }
+}
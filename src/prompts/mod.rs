@@ -3,22 +3,78 @@
 
 //! System prompts and guidelines for Henri.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use chrono::Local;
 
+use crate::config::ConfigFile;
 use crate::services::Services;
 use crate::skills;
 
+mod template;
+
 /// Git guidelines embedded at compile time.
 const GIT_GUIDELINES: &str = include_str!("git.md");
 
 /// Prompt for building/updating AGENTS.md files.
 pub(crate) const BUILD_AGENTS_MD_PROMPT: &str = include_str!("build-agents-md.md");
 
+/// Instructions for `/split-commits`.
+const SPLIT_COMMITS_PROMPT: &str = include_str!("split-commits.md");
+
+/// Build the `/split-commits` prompt for the repository at `working_dir`,
+/// adding any extra instructions from the user.
+///
+/// Returns a message for the user if there is nothing to split.
+pub(crate) fn split_commits_prompt(
+    working_dir: &std::path::Path,
+    instructions: &str,
+) -> Result<String, String> {
+    let output = std::process::Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(working_dir)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err("Not a git repository.".to_string());
+    }
+    if output.stdout.iter().all(u8::is_ascii_whitespace) {
+        return Err("No uncommitted changes to split.".to_string());
+    }
+    Ok(with_instructions(instructions))
+}
+
+fn with_instructions(instructions: &str) -> String {
+    let instructions = instructions.trim();
+    if instructions.is_empty() {
+        SPLIT_COMMITS_PROMPT.to_string()
+    } else {
+        format!(
+            "{}\nAdditional instructions from the user: {}\n",
+            SPLIT_COMMITS_PROMPT, instructions
+        )
+    }
+}
+
+/// Prompt asking the model to re-render its last answer in another language.
+pub(crate) fn translate_prompt(language: &str) -> String {
+    format!(
+        "Translate your previous answer into {language}. Do not call any tools or redo any \
+         work; only re-render the previous answer. Start your reply with the line \
+         \"[Translation: {language}]\" and leave code blocks, commands, paths and \
+         identifiers untranslated."
+    )
+}
+
 /// Default system prompt for AI assistants.
 const DEFAULT_SYSTEM_PROMPT: &str = include_str!("system.md");
 
+/// System prompt for models with small context windows.
+const CONDENSED_SYSTEM_PROMPT: &str = include_str!("system-condensed.md");
+
+/// Models with a context window smaller than this get the condensed prompts.
+const SMALL_CONTEXT_TOKENS: u64 = 32_768;
+
 /// Maximum depth for project structure tree.
 const MAX_DEPTH: usize = 2;
 
@@ -76,24 +132,73 @@ fn is_git_directory() -> bool {
         .unwrap_or(false)
 }
 
-pub(crate) fn system_prompt_with_services(services: Option<&Services>) -> Vec<String> {
-    let mut prompt = vec![];
+/// How much of the standard prompts a model is given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PromptTier {
+    Full,
+    /// A shorter system prompt without the project structure, git guidelines
+    /// or skills, and shorter tool descriptions, so a small context window
+    /// isn't mostly used up before the first prompt.
+    Condensed,
+}
+
+impl PromptTier {
+    /// The tier for a model's context window, if it is known.
+    pub(crate) fn for_context_limit(limit: Option<u64>) -> Self {
+        match limit {
+            Some(limit) if limit < SMALL_CONTEXT_TOKENS => PromptTier::Condensed,
+            _ => PromptTier::Full,
+        }
+    }
+}
 
-    prompt.push(default_system_prompt().to_string());
+/// The system prompt for `model`, given as `provider/model-id` to pick the
+/// `[[prompts.adapters]]` that apply to it.
+pub(crate) fn system_prompt_with_services(services: Option<&Services>, model: &str) -> Vec<String> {
+    system_prompt_for_tier(services, model, PromptTier::Full)
+}
+
+pub(crate) fn system_prompt_for_tier(
+    services: Option<&Services>,
+    model: &str,
+    tier: PromptTier,
+) -> Vec<String> {
+    let mut prompt = vec![];
+    let prompts = ConfigFile::load()
+        .map(|config| config.prompts)
+        .unwrap_or_default();
+    let full = tier == PromptTier::Full;
+    let adapters: Vec<_> = prompts
+        .adapters
+        .iter()
+        .filter(|adapter| adapter.matches(model))
+        .collect();
+
+    // The last matching adapter that replaces the instructions wins
+    let system = adapters
+        .iter()
+        .rev()
+        .find_map(|adapter| adapter.system.as_ref())
+        .or(prompts.system.as_ref());
+    match system {
+        Some(system) => prompt.push(template::render(system)),
+        None if full => prompt.push(default_system_prompt().to_string()),
+        None => prompt.push(CONDENSED_SYSTEM_PROMPT.to_string()),
+    }
 
     if let Ok(cwd) = std::env::current_dir() {
         prompt.push(format!("Current working directory: {}", cwd.display()));
     }
 
-    if let Some(project_structure) = project_structure() {
+    if full && let Some(project_structure) = project_structure() {
         prompt.push(project_structure);
     }
 
-    if let Some(git_guidelines) = git_guidelines_if_in_repo() {
+    if full && let Some(git_guidelines) = git_guidelines_if_in_repo() {
         prompt.push(format!("<GitGuidelines>{}</GitGuidelines>", git_guidelines));
     }
 
-    for agent_file in discover_agent_files() {
+    for agent_file in discover_agent_files(prompts.claude_md()) {
         let instruction = format!(
             "<Instructions filename=\"{}\">{}</Instructions>",
             agent_file.path.display(),
@@ -103,10 +208,17 @@ pub(crate) fn system_prompt_with_services(services: Option<&Services>) -> Vec<St
     }
 
     // Add skill prompts (agentskills.io format)
-    if let Some(skills_block) = skills::get_skill_prompts() {
+    if full && let Some(skills_block) = skills::get_skill_prompts() {
         prompt.push(skills_block);
     }
 
+    let appends = adapters
+        .iter()
+        .filter_map(|adapter| adapter.append.as_ref());
+    for append in prompts.append.iter().chain(appends) {
+        prompt.push(template::render(append));
+    }
+
     // If provided, append read-only mode notice.
     if services.is_some_and(|s| s.is_read_only()) {
         prompt.push(READ_ONLY_NOTICE.to_string());
@@ -145,7 +257,7 @@ fn project_structure() -> Option<String> {
 }
 
 /// Gets project structure from git repository.
-fn project_structure_from_git(_cwd: &PathBuf) -> Option<String> {
+fn project_structure_from_git(cwd: &Path) -> Option<String> {
     let output = std::process::Command::new("git")
         .args(["ls-tree", "-r", "--name-only", "HEAD"])
         .output()
@@ -156,12 +268,14 @@ fn project_structure_from_git(_cwd: &PathBuf) -> Option<String> {
     }
 
     let stdout = String::from_utf8(output.stdout).ok()?;
+    let ignored = crate::walk::ignore_files(cwd);
     let mut tree = TreeBuilder::new(MAX_DEPTH, MAX_ENTRIES);
 
     for line in stdout.lines() {
         if let Some(depth) = path_depth(line)
             && depth <= MAX_DEPTH
             && !should_skip_path(line)
+            && !crate::walk::is_ignored(&ignored, line)
         {
             tree.add(line, depth);
         }
@@ -172,21 +286,22 @@ fn project_structure_from_git(_cwd: &PathBuf) -> Option<String> {
 
 /// Gets project structure from filesystem.
 fn project_structure_from_filesystem(cwd: &PathBuf) -> Option<String> {
-    use walkdir::WalkDir;
-
     let mut tree = TreeBuilder::new(MAX_DEPTH, MAX_ENTRIES);
 
-    let walker = WalkDir::new(cwd)
-        .min_depth(1)
-        .max_depth(MAX_DEPTH + 1)
-        .into_iter();
+    let walker = crate::walk::walk(
+        cwd,
+        crate::walk::WalkOptions {
+            max_depth: Some(MAX_DEPTH + 1),
+            ..Default::default()
+        },
+    );
 
     for entry in walker.filter_map(|e| e.ok()) {
         let path = entry.path();
         let relative = path.strip_prefix(cwd).ok()?;
         let path_str = relative.to_str()?;
 
-        if should_skip_path(path_str) {
+        if path_str.is_empty() || should_skip_path(path_str) {
             continue;
         }
 
@@ -295,13 +410,27 @@ pub(crate) struct AgentFile {
     pub contents: String,
 }
 
+/// The instruction files added to the system prompt, as `/memory` shows them.
+pub(crate) fn instruction_files() -> Vec<AgentFile> {
+    let prompts = ConfigFile::load()
+        .map(|config| config.prompts)
+        .unwrap_or_default();
+    discover_agent_files(prompts.claude_md())
+}
+
 /// Discover AGENTS.md and CLAUDE.md files from current directory up to home/root,
 /// plus README files from the current directory only.
 /// Returns files in order from closest (current dir) to farthest (home/root).
 /// Prefers AGENTS.md over CLAUDE.md - only uses CLAUDE.md as fallback if AGENTS.md
-/// doesn't exist in a given directory.
+/// doesn't exist in a given directory, and only if `include_claude_md` is set.
 /// This function reads files fresh each time to pick up any changes.
-pub(crate) fn discover_agent_files() -> Vec<AgentFile> {
+fn discover_agent_files(include_claude_md: bool) -> Vec<AgentFile> {
+    let filenames = if include_claude_md {
+        AGENT_FILENAMES
+    } else {
+        &AGENT_FILENAMES[..1]
+    };
+
     let cwd = match std::env::current_dir() {
         Ok(p) => p,
         Err(_) => return Vec::new(),
@@ -331,7 +460,7 @@ pub(crate) fn discover_agent_files() -> Vec<AgentFile> {
 
     while let Some(dir) = current {
         // Prefer AGENTS.md, fall back to CLAUDE.md if not found
-        for filename in AGENT_FILENAMES {
+        for filename in filenames {
             let file_path = dir.join(filename);
             if file_path.is_file()
                 && let Ok(contents) = std::fs::read_to_string(&file_path)
@@ -373,6 +502,43 @@ fn determine_stop_directory(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_prompt_tier_for_context_limit() {
+        assert_eq!(PromptTier::for_context_limit(None), PromptTier::Full);
+        assert_eq!(
+            PromptTier::for_context_limit(Some(8192)),
+            PromptTier::Condensed
+        );
+        assert_eq!(
+            PromptTier::for_context_limit(Some(200_000)),
+            PromptTier::Full
+        );
+    }
+
+    #[test]
+    fn test_split_commits_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(split_commits_prompt(dir.path(), "").is_err());
+
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap()
+        };
+        git(&["init", "--quiet"]);
+        assert_eq!(
+            split_commits_prompt(dir.path(), "").unwrap_err(),
+            "No uncommitted changes to split."
+        );
+
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        let prompt = split_commits_prompt(dir.path(), " keep docs separate ").unwrap();
+        assert!(prompt.starts_with("Split the uncommitted changes"));
+        assert!(prompt.ends_with("Additional instructions from the user: keep docs separate\n"));
+    }
+
     #[test]
     fn test_path_depth() {
         assert_eq!(path_depth(""), None);
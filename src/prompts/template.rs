@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Variables in system prompt templates from the `[prompts]` config.
+//!
+//! `{cwd}`, `{os}`, `{git_branch}` and `{date}` are replaced with their values.
+//! Anything else in braces, such as code in the prompt, is left alone.
+
+use chrono::Local;
+
+/// Render `template` with the values of the current environment.
+pub(crate) fn render(template: &str) -> String {
+    render_with(template, variable)
+}
+
+fn variable(name: &str) -> Option<String> {
+    match name {
+        "cwd" => std::env::current_dir()
+            .ok()
+            .map(|cwd| cwd.display().to_string()),
+        "os" => Some(std::env::consts::OS.to_string()),
+        // Empty outside a repository
+        "git_branch" => Some(git_branch().unwrap_or_default()),
+        "date" => Some(Local::now().format("%Y-%m-%d").to_string()),
+        _ => None,
+    }
+}
+
+fn git_branch() -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!branch.is_empty()).then_some(branch)
+}
+
+/// Replace each `{name}` for which `lookup` has a value, keeping the others as
+/// written.
+fn render_with(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let name_len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        let name = &after[..name_len];
+        if after[name_len..].starts_with('}')
+            && let Some(value) = lookup(name)
+        {
+            out.push_str(&value);
+            rest = &after[name_len + 1..];
+        } else {
+            out.push('{');
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_with() {
+        let lookup = |name: &str| match name {
+            "cwd" => Some("/src/app".to_string()),
+            "git_branch" => Some("main".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            render_with("In {cwd} on {git_branch}.", lookup),
+            "In /src/app on main."
+        );
+        assert_eq!(
+            render_with("fn f() { {unknown} {cwd", lookup),
+            "fn f() { {unknown} {cwd"
+        );
+    }
+}
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Jason Ish
 
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use base64::Engine;
@@ -8,10 +10,14 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
-use crate::config::{ClaudeAuth, ClaudeProviderConfig, ConfigFile, ProviderConfig, ProviderType};
+use crate::config::{
+    AnthropicBetas, CacheTtl, ClaudeAuth, ConfigFile, ProviderConfig, ProviderType,
+};
 use crate::error::{Error, Result};
 use crate::output;
 use crate::provider::model_utils;
+use crate::provider::timeouts::Timeouts;
+use crate::provider::transport::{self, HttpRequest, HttpResponse, HttpTransport};
 use crate::provider::{
     ChatResponse, ContentBlock, Message, MessageContent, Provider, Role, StopReason, ToolCall,
 };
@@ -20,11 +26,22 @@ use crate::sse;
 use crate::tools;
 use crate::usage;
 
-pub(crate) const API_URL: &str = "https://api.anthropic.com/v1/messages?beta=true";
+/// Default API endpoint; `base-url` in the provider config overrides it.
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
 const TOKEN_URL: &str = "https://console.anthropic.com/v1/oauth/token";
 const CLIENT_ID: &str = "9d1c250a-e61b-44d9-88ed-5944d1962f5e";
-pub(crate) const ANTHROPIC_VERSION: &str = "2023-06-01";
-pub(crate) const ANTHROPIC_BETA: &str = "claude-code-20250219,oauth-2025-04-20,fine-grained-tool-streaming-2025-05-14,interleaved-thinking-2025-05-14";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Betas sent with every request; OAuth access requires the first two.
+const BASE_BETAS: &[&str] = &[
+    "claude-code-20250219",
+    "oauth-2025-04-20",
+    "fine-grained-tool-streaming-2025-05-14",
+    "interleaved-thinking-2025-05-14",
+];
+/// Output tokens requested per response; a thinking budget must stay below it.
+pub(crate) const MAX_TOKENS: u32 = 16000;
+const EXTENDED_CACHE_TTL_BETA: &str = "extended-cache-ttl-2025-04-11";
+const CONTEXT_MANAGEMENT_BETA: &str = "context-management-2025-06-27";
 
 const CLAUDE_CODE_VERSION: &str = "2.1.2";
 
@@ -37,6 +54,7 @@ fn to_claude_code_name(name: &str) -> String {
         "file_edit" => "Edit".to_string(),
         "bash" => "Bash".to_string(),
         "fetch" => "Fetch".to_string(),
+        "glob" => "Glob".to_string(),
         other => other.to_string(),
     }
 }
@@ -49,6 +67,7 @@ fn from_claude_code_name(name: &str) -> String {
         "Edit" => "file_edit".to_string(),
         "Bash" => "bash".to_string(),
         "Fetch" => "fetch".to_string(),
+        "Glob" => "glob".to_string(),
         other => other.to_string(),
     }
 }
@@ -83,6 +102,73 @@ fn is_claude_opus_4_6(model: &str) -> bool {
     model_utils::base_model_name(model) == "claude-opus-4-6"
 }
 
+/// Beta features in effect for a model.
+#[derive(Debug, Clone, PartialEq)]
+struct BetaFeatures {
+    prompt_caching: bool,
+    cache_ttl: CacheTtl,
+    context_management: bool,
+    extra: Vec<String>,
+}
+
+impl BetaFeatures {
+    /// Resolve the settings for `model`: its override, then the provider defaults.
+    fn resolve(betas: &AnthropicBetas, model: &str) -> Self {
+        let defaults = &betas.defaults;
+        let model = betas
+            .models
+            .get(model_utils::base_model_name(model))
+            .cloned()
+            .unwrap_or_default();
+        Self {
+            prompt_caching: model
+                .prompt_caching
+                .or(defaults.prompt_caching)
+                .unwrap_or(true),
+            cache_ttl: model.cache_ttl.or(defaults.cache_ttl).unwrap_or_default(),
+            context_management: model
+                .context_management
+                .or(defaults.context_management)
+                .unwrap_or(false),
+            extra: defaults.extra.iter().chain(&model.extra).cloned().collect(),
+        }
+    }
+
+    /// Value of the `anthropic-beta` header.
+    fn header(&self) -> String {
+        let mut betas: Vec<&str> = BASE_BETAS.to_vec();
+        if self.prompt_caching && self.cache_ttl == CacheTtl::OneHour {
+            betas.push(EXTENDED_CACHE_TTL_BETA);
+        }
+        if self.context_management {
+            betas.push(CONTEXT_MANAGEMENT_BETA);
+        }
+        for beta in &self.extra {
+            if !betas.contains(&beta.as_str()) {
+                betas.push(beta);
+            }
+        }
+        betas.join(",")
+    }
+
+    /// The `cache_control` marker for cached prompt blocks, if caching is on.
+    fn cache_control(&self) -> Option<serde_json::Value> {
+        if !self.prompt_caching {
+            return None;
+        }
+        Some(match self.cache_ttl {
+            CacheTtl::FiveMinutes => serde_json::json!({"type": "ephemeral"}),
+            CacheTtl::OneHour => serde_json::json!({"type": "ephemeral", "ttl": "1h"}),
+        })
+    }
+
+    /// The `context_management` request field, if enabled.
+    fn context_management(&self) -> Option<serde_json::Value> {
+        self.context_management
+            .then(|| serde_json::json!({"edits": [{"type": "clear_tool_uses_20250919"}]}))
+    }
+}
+
 struct AuthState {
     local_id: String,
     access_token: String,
@@ -91,8 +177,13 @@ struct AuthState {
 }
 
 pub(crate) struct AnthropicClient {
+    /// Client for OAuth token refreshes
     client: Client,
+    /// Transport for API requests
+    transport: Arc<dyn HttpTransport>,
     state: Mutex<AuthState>,
+    base_url: String,
+    betas: AnthropicBetas,
 }
 
 impl AnthropicClient {
@@ -108,18 +199,88 @@ impl AnthropicClient {
             .ok_or_else(|| Error::Auth("Anthropic not configured. Run /login.".to_string()))?;
 
         Ok(Self {
-            client: Client::new(),
+            client: Timeouts::for_provider(&local_id).client(),
+            transport: transport::default_transport(&local_id),
             state: Mutex::new(AuthState {
                 local_id,
                 access_token: claude.auth.access_token,
                 refresh_token: claude.auth.refresh_token,
                 expires_at: claude.auth.expires_at,
             }),
+            base_url: claude
+                .base_url
+                .as_deref()
+                .map(|url| url.trim().trim_end_matches('/'))
+                .filter(|url| !url.is_empty())
+                .unwrap_or(DEFAULT_BASE_URL)
+                .to_string(),
+            betas: claude.betas,
         })
     }
 
-    pub(crate) fn http_client(&self) -> &Client {
-        &self.client
+    /// Headers for an API request to `model`, as sent and as written to the
+    /// transaction log. OAuth access requires looking like Claude Code.
+    pub(crate) fn request_headers(
+        &self,
+        model: &str,
+        access_token: &str,
+    ) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        headers.insert("content-type".to_string(), "application/json".to_string());
+        headers.insert("accept".to_string(), "application/json".to_string());
+        headers.insert(
+            "anthropic-version".to_string(),
+            ANTHROPIC_VERSION.to_string(),
+        );
+        headers.insert("anthropic-beta".to_string(), self.beta_header(model));
+        headers.insert(
+            "user-agent".to_string(),
+            format!("claude-cli/{} (external, cli)", CLAUDE_CODE_VERSION),
+        );
+        headers.insert("x-app".to_string(), "cli".to_string());
+        headers.insert(
+            "anthropic-dangerous-direct-browser-access".to_string(),
+            "true".to_string(),
+        );
+        headers.insert(
+            "authorization".to_string(),
+            format!("Bearer {}", access_token),
+        );
+        headers
+    }
+
+    /// POST an API request through the transport.
+    pub(crate) async fn post(
+        &self,
+        url: &str,
+        headers: &HashMap<String, String>,
+        body: Vec<u8>,
+    ) -> Result<HttpResponse> {
+        self.transport
+            .post(HttpRequest {
+                url: url.to_string(),
+                headers: transport::header_map(headers)?,
+                body,
+            })
+            .await
+    }
+
+    /// URL of the messages endpoint.
+    pub(crate) fn messages_url(&self) -> String {
+        format!("{}/v1/messages?beta=true", self.base_url)
+    }
+
+    fn count_tokens_url(&self) -> String {
+        format!("{}/v1/messages/count_tokens", self.base_url)
+    }
+
+    fn beta_features(&self, model: &str) -> BetaFeatures {
+        BetaFeatures::resolve(&self.betas, model)
+    }
+
+    /// Value of the `anthropic-beta` header for requests to `model`.
+    pub(crate) fn beta_header(&self, model: &str) -> String {
+        self.beta_features(model).header()
     }
 
     pub async fn get_access_token(&self) -> Result<String> {
@@ -225,24 +386,20 @@ impl AnthropicClient {
             state.refresh_token = new_refresh;
             state.expires_at = now + expires_in * 1000;
 
-            if let Ok(mut config) = ConfigFile::load() {
-                // Get the current enabled state from the existing provider
-                let enabled = config
+            if let Ok(mut config) = ConfigFile::load()
+                && let Some(claude) = config
                     .get_provider(&state.local_id)
-                    .map(|p| p.is_enabled())
-                    .unwrap_or(true);
-                config.set_provider(
-                    state.local_id.clone(),
-                    ProviderConfig::Claude(ClaudeProviderConfig {
-                        enabled,
-                        auth: ClaudeAuth {
-                            refresh_token: state.refresh_token.clone(),
-                            access_token: state.access_token.clone(),
-                            expires_at: state.expires_at,
-                        },
-                    }),
-                );
-                let _ = config.save();
+                    .and_then(|p| p.as_claude())
+            {
+                // Keep the rest of the provider's settings
+                let mut claude = claude.clone();
+                claude.auth = ClaudeAuth {
+                    refresh_token: state.refresh_token.clone(),
+                    access_token: state.access_token.clone(),
+                    expires_at: state.expires_at,
+                };
+                config.set_provider(state.local_id.clone(), ProviderConfig::Claude(claude));
+                let _ = config.save_without_backup();
             }
 
             return Ok(state.access_token.clone());
@@ -257,6 +414,53 @@ impl AnthropicClient {
     }
 }
 
+/// Most `cache_control` markers the API accepts in one request.
+const MAX_CACHE_BREAKPOINTS: usize = 4;
+
+/// Mark the prefixes of a request to cache. The prompt is cached in the order
+/// tools, system, messages, up to each marker:
+///
+/// - the last tool, so the tools stay cached when the system prompt changes
+/// - the last system block before the date, which goes last as it changes daily
+/// - the last two user messages: the newest caches the conversation for the next
+///   request, and the one before is where the previous request put its marker.
+///   The API only looks back 20 blocks for a cached prefix, which a turn with
+///   many tool calls exceeds.
+fn add_cache_breakpoints(
+    tools: &mut [AnthropicTool],
+    system: &mut [serde_json::Value],
+    messages: &mut [serde_json::Value],
+    cache_control: &serde_json::Value,
+) {
+    let mut remaining = MAX_CACHE_BREAKPOINTS;
+
+    if let Some(tool) = tools.last_mut() {
+        tool.cache_control = Some(cache_control.clone());
+        remaining -= 1;
+    }
+
+    // The last system block is the date, unless it's the only one
+    let stable = if system.len() > 1 {
+        system.len() - 1
+    } else {
+        system.len()
+    };
+    if let Some(block) = system[..stable].last_mut() {
+        block["cache_control"] = cache_control.clone();
+        remaining -= 1;
+    }
+
+    let user_blocks = messages
+        .iter_mut()
+        .rev()
+        .filter(|m| m["role"] == "user")
+        .filter_map(|m| m.get_mut("content")?.as_array_mut()?.last_mut())
+        .take(remaining);
+    for block in user_blocks {
+        block["cache_control"] = cache_control.clone();
+    }
+}
+
 #[derive(Serialize)]
 struct AnthropicRequest {
     model: String,
@@ -270,6 +474,8 @@ struct AnthropicRequest {
     thinking: Option<ThinkingConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     output_config: Option<OutputConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context_management: Option<serde_json::Value>,
 }
 
 #[derive(Serialize)]
@@ -293,6 +499,8 @@ struct AnthropicTool {
     name: String,
     description: String,
     input_schema: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -365,6 +573,8 @@ pub(crate) struct AnthropicProvider {
     client: AnthropicClient,
     model: String,
     thinking_mode: Option<String>,
+    /// Explicit thinking budget overriding the one implied by `thinking_mode`
+    thinking_budget: Option<u32>,
     services: Services,
 }
 
@@ -374,6 +584,7 @@ impl AnthropicProvider {
         Ok(Self {
             client: AnthropicClient::try_new()?,
             thinking_mode: thinking_level_from_model(&model).map(|s| s.to_string()),
+            thinking_budget: None,
             model,
             services: services.clone(),
         })
@@ -384,6 +595,10 @@ impl AnthropicProvider {
         self.thinking_mode = thinking_level_from_model(&self.model).map(|s| s.to_string());
     }
 
+    pub(crate) fn set_thinking_budget(&mut self, budget_tokens: Option<u32>) {
+        self.thinking_budget = budget_tokens;
+    }
+
     /// Returns the available thinking modes for Claude models.
     pub(crate) fn thinking_modes() -> &'static [&'static str] {
         &[]
@@ -684,13 +899,17 @@ impl AnthropicProvider {
 
     /// Build the request struct for the Anthropic API
     async fn build_request(&self, messages: &[Message]) -> AnthropicRequest {
-        let tools: Vec<AnthropicTool> = tools::all_definitions(&self.services)
+        let mut tools: Vec<AnthropicTool> = tools::all_definitions(&self.services)
             .await
             .into_iter()
             .map(|t| AnthropicTool {
                 name: to_claude_code_name(&t.name),
                 description: t.description,
-                input_schema: t.input_schema,
+                input_schema: tools::schema::adapt(
+                    &t.input_schema,
+                    tools::schema::Dialect::Anthropic,
+                ),
+                cache_control: None,
             })
             .collect();
 
@@ -745,51 +964,45 @@ impl AnthropicProvider {
                 }),
                 _ => None,
             };
+            let thinking = match (thinking, self.thinking_budget) {
+                (Some(ThinkingConfig::Enabled { .. }), Some(budget_tokens)) => {
+                    Some(ThinkingConfig::Enabled { budget_tokens })
+                }
+                (thinking, _) => thinking,
+            };
 
             (thinking, None)
         };
 
+        let features = self.client.beta_features(&self.model);
+
         // OAuth mode: MUST start with Claude Code identity
         let mut system = vec![serde_json::json!({
             "type": "text",
             "text": "You are Claude Code, Anthropic's official CLI for Claude.",
-            "cache_control": {"type": "ephemeral"}
         })];
 
         // Add Henri-specific system prompts
-        for part in crate::prompts::system_prompt_with_services(Some(&self.services)) {
+        let model = format!("claude/{}", self.model);
+        for part in crate::prompts::system_prompt_with_services(Some(&self.services), &model) {
             system.push(serde_json::json!({"type": "text", "text": part}));
         }
 
-        // Add cache control to the second-to-last system block to enable prompt caching.
-        // The last block is the timestamp which changes on every request, so we cache
-        // everything before it.
-        let len = system.len();
-        if len >= 2 {
-            system[len - 2]["cache_control"] = serde_json::json!({"type": "ephemeral"});
-        }
-
         let mut built_messages = self.build_messages(messages);
-
-        // Add cache control to the last user message to enable conversation history caching.
-        if let Some(last_msg) = built_messages.last_mut()
-            && last_msg["role"] == "user"
-            && let Some(content) = last_msg.get_mut("content")
-            && let Some(blocks) = content.as_array_mut()
-            && let Some(last_block) = blocks.last_mut()
-        {
-            last_block["cache_control"] = serde_json::json!({"type": "ephemeral"});
+        if let Some(cache_control) = features.cache_control() {
+            add_cache_breakpoints(&mut tools, &mut system, &mut built_messages, &cache_control);
         }
 
         AnthropicRequest {
             model: model.to_string(),
             messages: built_messages,
             system,
-            max_tokens: 16000,
+            max_tokens: MAX_TOKENS,
             stream: true,
             tools,
             thinking,
             output_config,
+            context_management: features.context_management(),
         }
     }
 
@@ -804,69 +1017,34 @@ impl AnthropicProvider {
 
         // Record TX bytes
         let body_bytes = serde_json::to_vec(&request)?;
+        let url = self.client.messages_url();
+        let req_headers = self.client.request_headers(&self.model, &access_token);
 
-        // Build headers for transaction logging
-        let mut req_headers = std::collections::HashMap::new();
-        req_headers.insert("content-type".to_string(), "application/json".to_string());
-        req_headers.insert("accept".to_string(), "application/json".to_string());
-        req_headers.insert(
-            "anthropic-version".to_string(),
-            ANTHROPIC_VERSION.to_string(),
-        );
-        req_headers.insert("anthropic-beta".to_string(), ANTHROPIC_BETA.to_string());
-
-        // OAuth mode: Add headers to mimic Claude Code exactly
-        req_headers.insert(
-            "user-agent".to_string(),
-            format!("claude-cli/{} (external, cli)", CLAUDE_CODE_VERSION),
-        );
-        req_headers.insert("x-app".to_string(), "cli".to_string());
-        req_headers.insert(
-            "anthropic-dangerous-direct-browser-access".to_string(),
-            "true".to_string(),
-        );
-
-        req_headers.insert(
-            "authorization".to_string(),
-            format!("Bearer {}", access_token),
-        );
-
-        // Build HTTP request with the same headers
         let response = self
             .client
-            .http_client()
-            .post(API_URL)
-            .header("content-type", "application/json")
-            .header("accept", "application/json")
-            .header("anthropic-version", ANTHROPIC_VERSION)
-            .header("anthropic-beta", ANTHROPIC_BETA)
-            .header(
-                "user-agent",
-                format!("claude-cli/{} (external, cli)", CLAUDE_CODE_VERSION),
-            )
-            .header("x-app", "cli")
-            .header("anthropic-dangerous-direct-browser-access", "true")
-            .header("authorization", format!("Bearer {}", access_token))
-            .body(body_bytes)
-            .send()
+            .post(&url, &req_headers, body_bytes)
             .await
-            .map_err(|e| {
-                Error::Other(format!(
+            .map_err(|e| match e {
+                Error::Config(_) => e,
+                e => Error::Other(format!(
                     "Failed to connect to Anthropic API ({}): {}",
-                    API_URL, e
-                ))
+                    url, e
+                )),
             })?;
 
         let resp_headers =
-            crate::provider::transaction_log::header_map_to_hash_map(response.headers());
+            crate::provider::transaction_log::header_map_to_hash_map(&response.headers);
+        crate::usage::record_anthropic_rate_limits(&crate::usage::RateLimits::from_headers(
+            &response.headers,
+        ));
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let status_code = status.as_u16();
-            let text = response.text().await.unwrap_or_default();
+        if !response.is_success() {
+            let status_code = response.status;
+            let text = response.text().await;
 
+            let retry_after = super::retry_after(&resp_headers);
             crate::provider::transaction_log::log(
-                API_URL,
+                &url,
                 req_headers.clone(),
                 serde_json::to_value(&request).unwrap_or_default(),
                 resp_headers,
@@ -882,14 +1060,14 @@ impl AnthropicProvider {
             if text.contains("tool_use") && text.contains("tool_result") {
                 return Err(Error::SessionCorrupted(format!(
                     "Anthropic chat failed: {} - {}",
-                    status, text
+                    status_code, text
                 )));
             }
 
             if status_code == 401 {
                 return Err(Error::Unauthorized(format!(
                     "Anthropic chat failed: {} - {}",
-                    status, text
+                    status_code, text
                 )));
             }
 
@@ -898,12 +1076,13 @@ impl AnthropicProvider {
                 return Err(Error::Retryable {
                     status: status_code,
                     message: text,
-                });
+                }
+                .with_retry_after(retry_after));
             }
 
             return Err(Error::Auth(format!(
                 "Anthropic chat failed: {} - {}",
-                status, text
+                status_code, text
             )));
         }
 
@@ -917,9 +1096,9 @@ impl AnthropicProvider {
         let mut thinking = output::ThinkingState::new(output);
         let mut streaming_start: Option<Instant> = None;
 
-        let mut sse = sse::SseStream::new(response.bytes_stream());
+        let mut sse = sse::SseStream::new(response.body);
         while let Some(result) = sse.next_event().await {
-            let data = result.map_err(Error::Http)?;
+            let data = result?;
 
             let Ok(event) = serde_json::from_str::<StreamEvent>(&data) else {
                 continue;
@@ -950,12 +1129,7 @@ impl AnthropicProvider {
                             let limit = Self::context_limit(&self.model);
                             output::emit_context_update(output, context_tokens, limit);
                         }
-                        if cache_write_tokens > 0 {
-                            usage::anthropic().add_cache_creation(cache_write_tokens);
-                        }
-                        if cache_read_tokens > 0 {
-                            usage::anthropic().add_cache_read(cache_read_tokens);
-                        }
+                        usage::anthropic().record_cache(cache_write_tokens, cache_read_tokens);
 
                         if input_tokens > 0 || cache_read_tokens > 0 || cache_write_tokens > 0 {
                             output::emit_usage_update(
@@ -1117,7 +1291,7 @@ impl AnthropicProvider {
 
         if crate::provider::transaction_log::is_active() {
             crate::provider::transaction_log::log(
-                API_URL,
+                &url,
                 req_headers,
                 serde_json::to_value(&request).unwrap_or(serde_json::json!({})),
                 resp_headers,
@@ -1171,43 +1345,26 @@ impl AnthropicProvider {
             "tools": request.tools,
         });
 
-        let url = "https://api.anthropic.com/v1/messages/count_tokens";
+        let url = self.client.count_tokens_url();
+        let headers = self.client.request_headers(&self.model, &access_token);
 
         let response = self
             .client
-            .http_client()
-            .post(url)
-            .header("content-type", "application/json")
-            .header("accept", "application/json")
-            .header("anthropic-version", ANTHROPIC_VERSION)
-            .header("anthropic-beta", ANTHROPIC_BETA)
-            .header(
-                "user-agent",
-                format!("claude-cli/{} (external, cli)", CLAUDE_CODE_VERSION),
-            )
-            .header("x-app", "cli")
-            .header("anthropic-dangerous-direct-browser-access", "true")
-            .header("authorization", format!("Bearer {}", access_token))
-            .json(&count_request)
-            .send()
+            .post(&url, &headers, serde_json::to_vec(&count_request)?)
             .await
             .map_err(|e| Error::Auth(e.to_string()))?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
+        let status = response.status;
+        let success = response.is_success();
+        let text = response.text().await;
+        if !success {
             return Err(Error::Auth(format!(
                 "Token count failed: {} - {}",
                 status, text
             )));
         }
 
-        let json: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| Error::Auth(e.to_string()))?;
-
-        Ok(json)
+        serde_json::from_str(&text).map_err(|e| Error::Auth(e.to_string()))
     }
 
     /// Get the context limit for a given model name
@@ -1249,6 +1406,184 @@ impl Provider for AnthropicProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::provider::transport::mock::MockTransport;
+
+    fn mock_provider(transport: Arc<MockTransport>) -> AnthropicProvider {
+        let client = AnthropicClient {
+            client: Client::new(),
+            transport,
+            state: Mutex::new(AuthState {
+                local_id: "claude".to_string(),
+                access_token: "token".to_string(),
+                refresh_token: String::new(),
+                expires_at: 0,
+            }),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            betas: AnthropicBetas::default(),
+        };
+        AnthropicProvider {
+            client,
+            model: DEFAULT_MODEL.to_string(),
+            thinking_mode: None,
+            thinking_budget: None,
+            services: Services::null(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_streamed_tool_use() {
+        let fixture = include_str!("testdata/anthropic_tool_use.sse");
+        // Deliver the fixture in small chunks so events straddle chunk boundaries.
+        let chunks: Vec<&str> = fixture
+            .as_bytes()
+            .chunks(7)
+            .map(|c| std::str::from_utf8(c).unwrap())
+            .collect();
+        let transport = Arc::new(MockTransport::default());
+        transport.push_response(200, chunks);
+
+        let provider = mock_provider(transport.clone());
+        let response = provider
+            .chat(
+                vec![Message::user("list files")],
+                &crate::output::OutputContext::null(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].name, "bash");
+        assert_eq!(response.tool_calls[0].input["command"], "ls");
+        assert!(matches!(
+            &response.content_blocks[0],
+            ContentBlock::Thinking { thinking, provider_data: Some(data) }
+                if thinking == "List them." && data["signature"] == "sig"
+        ));
+        assert!(matches!(
+            &response.content_blocks[1],
+            ContentBlock::Text { text } if text == "Hello"
+        ));
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].url,
+            "https://api.anthropic.com/v1/messages?beta=true"
+        );
+        assert_eq!(requests[0].headers["authorization"], "Bearer token");
+        assert_eq!(requests[0].headers["anthropic-version"], ANTHROPIC_VERSION);
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn test_chat_error_status() {
+        let transport = Arc::new(MockTransport::default());
+        transport.push_response(529, vec![r#"{"error":{"type":"overloaded_error"}}"#]);
+        transport.push_response(400, vec!["bad request"]);
+
+        let provider = mock_provider(transport);
+        let output = crate::output::OutputContext::null();
+        let err = provider
+            .chat(vec![Message::user("hi")], &output)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Retryable { status: 529, .. }));
+
+        let err = provider
+            .chat(vec![Message::user("hi")], &output)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Auth(message) if message.contains("400")));
+    }
+
+    #[test]
+    fn test_beta_features_per_model() {
+        let betas: AnthropicBetas = toml::from_str(
+            r#"
+cache-ttl = "1h"
+extra = ["files-api-2025-04-14"]
+
+[models.claude-opus-4-6]
+context-management = true
+prompt-caching = false
+"#,
+        )
+        .unwrap();
+
+        let sonnet = BetaFeatures::resolve(&betas, "claude-sonnet-4-5#high");
+        assert!(sonnet.prompt_caching);
+        assert_eq!(sonnet.cache_ttl, CacheTtl::OneHour);
+        assert_eq!(
+            sonnet.cache_control(),
+            Some(serde_json::json!({"type": "ephemeral", "ttl": "1h"}))
+        );
+        let header = sonnet.header();
+        assert!(header.starts_with("claude-code-20250219,oauth-2025-04-20"));
+        assert!(header.contains(EXTENDED_CACHE_TTL_BETA));
+        assert!(header.ends_with("files-api-2025-04-14"));
+        assert!(!header.contains(CONTEXT_MANAGEMENT_BETA));
+
+        let opus = BetaFeatures::resolve(&betas, "claude-opus-4-6");
+        assert!(opus.cache_control().is_none());
+        assert!(opus.context_management().is_some());
+        let header = opus.header();
+        assert!(header.contains(CONTEXT_MANAGEMENT_BETA));
+        assert!(!header.contains(EXTENDED_CACHE_TTL_BETA));
+    }
+
+    #[test]
+    fn test_cache_breakpoints() {
+        let cache_control = serde_json::json!({"type": "ephemeral"});
+        let mut tools: Vec<AnthropicTool> = ["Read", "Bash"]
+            .into_iter()
+            .map(|name| AnthropicTool {
+                name: name.to_string(),
+                description: String::new(),
+                input_schema: serde_json::json!({}),
+                cache_control: None,
+            })
+            .collect();
+        let mut system = vec![
+            serde_json::json!({"type": "text", "text": "identity"}),
+            serde_json::json!({"type": "text", "text": "instructions"}),
+            serde_json::json!({"type": "text", "text": "Current date"}),
+        ];
+        let text = |role: &str| serde_json::json!({"role": role, "content": [{"type": "text", "text": "x"}]});
+        let mut messages = vec![
+            text("user"),
+            text("assistant"),
+            text("user"),
+            text("assistant"),
+            text("user"),
+        ];
+
+        add_cache_breakpoints(&mut tools, &mut system, &mut messages, &cache_control);
+
+        assert!(tools[0].cache_control.is_none());
+        assert_eq!(tools[1].cache_control.as_ref(), Some(&cache_control));
+        let marked = |blocks: &[serde_json::Value]| {
+            blocks
+                .iter()
+                .map(|b| b.get("cache_control").is_some())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(marked(&system), [false, true, false]);
+        let message_blocks: Vec<serde_json::Value> =
+            messages.iter().map(|m| m["content"][0].clone()).collect();
+        assert_eq!(marked(&message_blocks), [false, false, true, false, true]);
+    }
+
+    #[test]
+    fn test_beta_features_default() {
+        let features = BetaFeatures::resolve(&AnthropicBetas::default(), "claude-haiku-4-5");
+        assert_eq!(features.header(), BASE_BETAS.join(","));
+        assert_eq!(
+            features.cache_control(),
+            Some(serde_json::json!({"type": "ephemeral"}))
+        );
+    }
 
     #[test]
     fn test_thinking_config_enabled_serialization() {
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Jason Ish
 
+use std::sync::Arc;
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use base64::Engine;
@@ -12,6 +13,7 @@ use crate::config::{AntigravityProviderConfig, ConfigFile, ProviderConfig};
 use crate::error::{Error, Result};
 use crate::output;
 use crate::provider::model_utils;
+use crate::provider::transport::{self, HttpRequest, HttpTransport};
 use crate::provider::{
     ChatResponse, ContentBlock, Message, MessageContent, Provider, Role, StopReason, ToolCall,
 };
@@ -57,6 +59,9 @@ const INTERNAL_MAX_RETRIES: u32 = 3;
 /// Initial retry delay for internal loop (exponential: 1s, 2s, 4s)
 const INTERNAL_INITIAL_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
 
+/// Output tokens requested per response; a thinking budget must stay below it.
+pub(crate) const MAX_OUTPUT_TOKENS: u32 = 64000;
+
 struct AuthState {
     local_id: String,
     access_token: String,
@@ -68,39 +73,12 @@ struct AuthState {
 /// Antigravity system instruction prompt embedded at compile time.
 const ANTIGRAVITY_SYSTEM_INSTRUCTION: &str = include_str!("../prompts/antigravity.md");
 
-fn strip_unsupported_schema_fields(schema: serde_json::Value) -> serde_json::Value {
-    match schema {
-        serde_json::Value::Object(mut map) => {
-            map.remove("$schema");
-            map.remove("$id");
-            map.remove("$comment");
-            map.remove("$ref");
-            map.remove("$defs");
-            map.remove("definitions");
-            map.remove("const");
-            map.remove("additionalProperties");
-            map.remove("propertyNames");
-            map.remove("title");
-
-            let cleaned: serde_json::Map<String, serde_json::Value> = map
-                .into_iter()
-                .map(|(k, v)| (k, strip_unsupported_schema_fields(v)))
-                .collect();
-
-            serde_json::Value::Object(cleaned)
-        }
-        serde_json::Value::Array(arr) => serde_json::Value::Array(
-            arr.into_iter()
-                .map(strip_unsupported_schema_fields)
-                .collect(),
-        ),
-        other => other,
-    }
-}
-
 pub(crate) struct AntigravityProvider {
     state: Mutex<AuthState>,
     model: String,
+    /// Explicit thinking budget for Claude models, overriding the variant's budget
+    thinking_budget: Option<u32>,
+    transport: Arc<dyn HttpTransport>,
     services: Services,
 }
 
@@ -134,6 +112,8 @@ impl AntigravityProvider {
                 project_id: antigravity.project_id.clone(),
             }),
             model: "gemini-3-flash#medium".to_string(),
+            thinking_budget: None,
+            transport: transport::default_transport(provider_name),
             services,
         })
     }
@@ -142,6 +122,10 @@ impl AntigravityProvider {
         self.model = model;
     }
 
+    pub(crate) fn set_thinking_budget(&mut self, budget_tokens: Option<u32>) {
+        self.thinking_budget = budget_tokens;
+    }
+
     /// Get the available variants (thinking levels) for a given model.
     /// Returns the variant suffixes like "high", "medium", "low".
     pub(crate) fn model_variants(model: &str) -> Vec<&'static str> {
@@ -266,7 +250,7 @@ impl AntigravityProvider {
                     project_id: state.project_id.clone(),
                 }),
             );
-            let _ = config.save();
+            let _ = config.save_without_backup();
         }
 
         Ok(state.access_token.clone())
@@ -409,7 +393,7 @@ impl AntigravityProvider {
                 serde_json::json!({
                     "name": t.name,
                     "description": t.description,
-                    "parameters": strip_unsupported_schema_fields(t.input_schema)
+                    "parameters": tools::schema::adapt(&t.input_schema, tools::schema::Dialect::Gemini)
                 })
             })
             .collect();
@@ -419,7 +403,8 @@ impl AntigravityProvider {
             serde_json::json!({"text": "Your name is Henri."}),
         ];
 
-        for part in crate::prompts::system_prompt_with_services(Some(&self.services)) {
+        let model = format!("antigravity/{}", self.model);
+        for part in crate::prompts::system_prompt_with_services(Some(&self.services), &model) {
             system_parts.push(serde_json::json!({"text": part}));
         }
 
@@ -436,7 +421,7 @@ impl AntigravityProvider {
                 "functionDeclarations": tools
             }],
             "generationConfig": {
-                "maxOutputTokens": 64000,
+                "maxOutputTokens": MAX_OUTPUT_TOKENS,
                 "temperature": 1.0
             },
             "sessionId": session_id,
@@ -459,6 +444,10 @@ impl AntigravityProvider {
                 Some("xhigh") => 48000,
                 _ => 0,
             };
+            let budget = match self.thinking_budget {
+                Some(override_budget) if budget > 0 => override_budget,
+                _ => budget,
+            };
             if budget > 0 {
                 request["generationConfig"]["thinkingConfig"] = serde_json::json!({
                     "includeThoughts": true,
@@ -567,6 +556,7 @@ impl AntigravityProvider {
             "interleaved-thinking-2025-05-14".to_string(),
         );
 
+        let header_map = transport::header_map(&headers)?;
         let mut last_error = None;
         let mut final_response = None;
 
@@ -574,31 +564,27 @@ impl AntigravityProvider {
             // Build URL - model is in request body, not URL path
             let url = base_url.to_string();
 
-            // Create a fresh client for each request attempt
-            let client = Client::new();
-            let mut request_builder = client.post(&url);
-            for (key, value) in &headers {
-                request_builder = request_builder.header(key, value);
-            }
-
-            match request_builder.body(body_bytes.clone()).send().await {
+            let http_request = HttpRequest {
+                url: url.clone(),
+                headers: header_map.clone(),
+                body: body_bytes.clone(),
+            };
+            match self.transport.post(http_request).await {
                 Ok(response) => {
-                    if response.status().is_success() {
+                    if response.is_success() {
                         final_response = Some(response);
                         break;
                     }
 
-                    let status = response.status();
-                    let status_code = status.as_u16();
+                    let status_code = response.status;
 
                     // Capture headers before consuming the response body
-                    let error_headers = crate::provider::transaction_log::header_map_to_hash_map(
-                        response.headers(),
-                    );
+                    let error_headers =
+                        crate::provider::transaction_log::header_map_to_hash_map(&response.headers);
 
                     // If Unauthorized, fail immediately to trigger refresh in outer loop
                     if status_code == 401 {
-                        let text = response.text().await.unwrap_or_default();
+                        let text = response.text().await;
 
                         crate::provider::transaction_log::log(
                             &url,
@@ -614,13 +600,14 @@ impl AntigravityProvider {
 
                         return Err(Error::Unauthorized(format!(
                             "Antigravity chat failed: {} - {}",
-                            status, text
+                            status_code, text
                         )));
                     }
 
                     // For other errors, store and try next endpoint
-                    let text = response.text().await.unwrap_or_default();
+                    let text = response.text().await;
 
+                    let retry_after = super::retry_after(&error_headers);
                     crate::provider::transaction_log::log(
                         &url,
                         headers.clone(),
@@ -636,14 +623,17 @@ impl AntigravityProvider {
                     // Check for retryable errors (timeouts, overloaded, rate limits)
                     if super::is_retryable_status(status_code) || super::is_retryable_message(&text)
                     {
-                        last_error = Some(Error::Retryable {
-                            status: status_code,
-                            message: text,
-                        });
+                        last_error = Some(
+                            Error::Retryable {
+                                status: status_code,
+                                message: text,
+                            }
+                            .with_retry_after(retry_after),
+                        );
                     } else {
                         last_error = Some(Error::Auth(format!(
                             "Antigravity chat failed: {} - {}",
-                            status, text
+                            status_code, text
                         )));
                     }
                 }
@@ -663,7 +653,7 @@ impl AntigravityProvider {
         })?;
 
         let resp_headers =
-            crate::provider::transaction_log::header_map_to_hash_map(response.headers());
+            crate::provider::transaction_log::header_map_to_hash_map(&response.headers);
         // Use primary endpoint URL for logging (actual endpoint used is not tracked)
         let url = ANTIGRAVITY_ENDPOINTS[0].to_string();
 
@@ -684,10 +674,10 @@ impl AntigravityProvider {
         let mut final_cached_tokens: Option<u64> = None;
         let mut final_thought_tokens: Option<u64> = None;
 
-        let mut sse = sse::SseStream::new(response.bytes_stream());
+        let mut sse = sse::SseStream::new(response.body);
 
         while let Some(result) = sse.next_event().await {
-            let data = result.map_err(Error::Http)?;
+            let data = result?;
 
             let Ok(event): std::result::Result<serde_json::Value, _> = serde_json::from_str(&data)
             else {
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Jason Ish
 
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use reqwest::Client;
@@ -12,6 +13,8 @@ use crate::error::{Error, Result};
 use crate::output;
 use crate::prompts;
 use crate::provider::model_utils;
+use crate::provider::timeouts::Timeouts;
+use crate::provider::transport::{self, HttpRequest, HttpTransport};
 use crate::provider::{
     ChatResponse, ContentBlock, Message, MessageContent, Provider, Role, StopReason, ToolCall,
 };
@@ -59,7 +62,10 @@ struct CopilotState {
 }
 
 pub(crate) struct CopilotProvider {
+    /// Client for token exchanges
     client: Client,
+    /// Transport for API requests
+    transport: Arc<dyn HttpTransport>,
     state: Mutex<CopilotState>,
     model: String,
     thinking_enabled: bool,
@@ -177,7 +183,8 @@ impl CopilotProvider {
             .ok_or_else(|| Error::Auth("GitHub Copilot not configured. Run /login.".to_string()))?;
 
         Ok(Self {
-            client: Client::new(),
+            client: Timeouts::for_provider(&local_id).client(),
+            transport: transport::default_transport(&local_id),
             state: Mutex::new(CopilotState {
                 local_id,
                 access_token: github.access_token,
@@ -284,16 +291,21 @@ impl CopilotProvider {
                     copilot_expires_at: Some(copilot_response.expires_at),
                 }),
             );
-            let _ = config.save();
+            let _ = config.save_without_backup();
         }
 
         Ok(copilot_response.token)
     }
 
+    fn system_prompt(&self) -> String {
+        let model = format!("copilot/{}", self.model);
+        prompts::system_prompt_with_services(Some(&self.services), &model).join("\n\n")
+    }
+
     fn build_messages(&self, messages: Vec<Message>) -> Vec<CopilotMessage> {
         let mut payload = vec![CopilotMessage {
             role: "system".to_string(),
-            content: Some(prompts::system_prompt_with_services(Some(&self.services)).join("\n\n")),
+            content: Some(self.system_prompt()),
             tool_calls: None,
             tool_call_id: None,
         }];
@@ -414,7 +426,7 @@ impl CopilotProvider {
             role: "system".to_string(),
             content: vec![CopilotResponseContent {
                 kind: "input_text".to_string(),
-                text: prompts::system_prompt_with_services(Some(&self.services)).join("\n\n"),
+                text: self.system_prompt(),
             }],
         })];
 
@@ -513,7 +525,10 @@ impl CopilotProvider {
                 function: OpenAiFunction {
                     name: t.name,
                     description: t.description,
-                    parameters: t.input_schema,
+                    parameters: tools::schema::adapt(
+                        &t.input_schema,
+                        tools::schema::Dialect::OpenAi,
+                    ),
                 },
             })
             .collect();
@@ -534,7 +549,7 @@ impl CopilotProvider {
                 kind: "function".to_string(),
                 name: t.name,
                 description: t.description,
-                parameters: t.input_schema,
+                parameters: tools::schema::adapt(&t.input_schema, tools::schema::Dialect::OpenAi),
             })
             .collect();
 
@@ -612,17 +627,12 @@ impl Provider for CopilotProvider {
         req_headers.insert("X-GitHub-Api-Version".to_string(), "2023-07-07".to_string());
 
         let response = self
-            .client
-            .post(CHAT_URL)
-            .header("Authorization", format!("Bearer {copilot_token}"))
-            .header("User-Agent", "GitHubCopilotChat/1.0")
-            .header("Editor-Version", EDITOR_VERSION)
-            .header("Editor-Plugin-Version", "copilot-chat/0.26.7")
-            .header("Content-Type", "application/json")
-            .header("Accept", "text/event-stream")
-            .header("X-GitHub-Api-Version", "2023-07-07")
-            .body(body_bytes)
-            .send()
+            .transport
+            .post(HttpRequest {
+                url: CHAT_URL.to_string(),
+                headers: transport::header_map(&req_headers)?,
+                body: body_bytes,
+            })
             .await
             .map_err(|e| {
                 Error::Other(format!(
@@ -632,13 +642,13 @@ impl Provider for CopilotProvider {
             })?;
 
         let resp_headers =
-            crate::provider::transaction_log::header_map_to_hash_map(response.headers());
+            crate::provider::transaction_log::header_map_to_hash_map(&response.headers);
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let status_code = status.as_u16();
-            let text = response.text().await.unwrap_or_default();
+        if !response.is_success() {
+            let status_code = response.status;
+            let text = response.text().await;
 
+            let retry_after = super::retry_after(&resp_headers);
             crate::provider::transaction_log::log(
                 CHAT_URL,
                 req_headers.clone(),
@@ -656,12 +666,13 @@ impl Provider for CopilotProvider {
                 return Err(Error::Retryable {
                     status: status_code,
                     message: text,
-                });
+                }
+                .with_retry_after(retry_after));
             }
 
             return Err(Error::Auth(format!(
                 "Copilot chat failed: {} - {}",
-                status, text
+                status_code, text
             )));
         }
 
@@ -674,9 +685,9 @@ impl Provider for CopilotProvider {
         let mut thinking = output::ThinkingState::new(output);
         let mut raw_events: Vec<serde_json::Value> = Vec::new();
 
-        let mut sse = sse::SseStream::new(response.bytes_stream());
+        let mut sse = sse::SseStream::new(response.body);
         while let Some(result) = sse.next_event().await {
-            let data = result.map_err(Error::Http)?;
+            let data = result?;
 
             let Ok(chunk) = serde_json::from_str::<CopilotChunk>(&data) else {
                 continue;
@@ -904,28 +915,23 @@ impl CopilotProvider {
         req_headers.insert("X-GitHub-Api-Version".to_string(), "2023-07-07".to_string());
 
         let response = self
-            .client
-            .post(RESPONSES_URL)
-            .header("Authorization", format!("Bearer {copilot_token}"))
-            .header("User-Agent", "GitHubCopilotChat/1.0")
-            .header("Editor-Version", EDITOR_VERSION)
-            .header("Editor-Plugin-Version", "copilot-chat/0.26.7")
-            .header("Content-Type", "application/json")
-            .header("Accept", "text/event-stream")
-            .header("X-GitHub-Api-Version", "2023-07-07")
-            .body(body_bytes)
-            .send()
+            .transport
+            .post(HttpRequest {
+                url: RESPONSES_URL.to_string(),
+                headers: transport::header_map(&req_headers)?,
+                body: body_bytes,
+            })
             .await
             .map_err(|e| Error::Auth(e.to_string()))?;
 
         let resp_headers =
-            crate::provider::transaction_log::header_map_to_hash_map(response.headers());
+            crate::provider::transaction_log::header_map_to_hash_map(&response.headers);
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let status_code = status.as_u16();
-            let text = response.text().await.unwrap_or_default();
+        if !response.is_success() {
+            let status_code = response.status;
+            let text = response.text().await;
 
+            let retry_after = super::retry_after(&resp_headers);
             crate::provider::transaction_log::log(
                 RESPONSES_URL,
                 req_headers.clone(),
@@ -943,12 +949,13 @@ impl CopilotProvider {
                 return Err(Error::Retryable {
                     status: status_code,
                     message: text,
-                });
+                }
+                .with_retry_after(retry_after));
             }
 
             return Err(Error::Auth(format!(
                 "Copilot responses failed: {} - {}",
-                status, text
+                status_code, text
             )));
         }
 
@@ -963,9 +970,9 @@ impl CopilotProvider {
         let mut pending_functions: std::collections::HashMap<usize, (String, String, String)> =
             std::collections::HashMap::new();
 
-        let mut sse = sse::SseStream::new(response.bytes_stream());
+        let mut sse = sse::SseStream::new(response.body);
         while let Some(result) = sse.next_event().await {
-            let data = result.map_err(Error::Http)?;
+            let data = result?;
 
             if data.is_empty() {
                 continue;
@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Mistral provider.
+//!
+//! Chat uses Mistral's OpenAI-compatible chat completions API. Codestral's
+//! fill-in-the-middle endpoint is also exposed through [`fill_in_middle`], which
+//! the edit tool uses to generate code between a prefix and a suffix.
+
+use serde::Deserialize;
+
+use crate::config::{ConfigFile, MistralProviderConfig, ProviderType};
+use crate::error::{Error, Result};
+use crate::provider::openai_compat::{
+    ModelConfigProvider, OpenAiChatConfig, build_request, execute_chat,
+};
+use crate::provider::timeouts::Timeouts;
+use crate::provider::{ChatResponse, Message, Provider};
+use crate::services::Services;
+use crate::usage;
+
+pub(crate) const DEFAULT_BASE_URL: &str = "https://api.mistral.ai/v1";
+
+/// Model used for fill-in-the-middle completions unless configured otherwise.
+pub(crate) const DEFAULT_FIM_MODEL: &str = "codestral-latest";
+
+/// Upper bound on the code generated by a single FIM completion.
+const FIM_MAX_TOKENS: u32 = 2048;
+
+/// Models offered in the model menu in addition to the configured ones.
+const MISTRAL_MODELS: &[&str] = &[
+    "codestral-latest",
+    "devstral-medium-latest",
+    "devstral-small-latest",
+    "mistral-large-latest",
+    "mistral-medium-latest",
+];
+
+pub(crate) struct MistralProvider {
+    config: OpenAiChatConfig,
+    provider_config: MistralProviderConfig,
+}
+
+impl MistralProvider {
+    pub(crate) fn try_new(provider_name: &str, services: Services) -> Result<Self> {
+        let config = ConfigFile::load()?;
+        let mistral = config
+            .get_provider(provider_name)
+            .and_then(|p| p.as_mistral())
+            .ok_or_else(|| {
+                Error::Auth(format!(
+                    "Mistral provider '{}' not configured.",
+                    provider_name
+                ))
+            })?;
+
+        if !mistral.enabled {
+            return Err(Error::Auth(format!(
+                "Mistral provider '{}' is disabled.",
+                provider_name
+            )));
+        }
+
+        if mistral.api_key.is_empty() {
+            return Err(Error::Auth(format!(
+                "Mistral provider '{}' API key is not set.",
+                provider_name
+            )));
+        }
+
+        let chat_config = OpenAiChatConfig {
+            provider_name: provider_name.to_string(),
+            transport: crate::provider::transport::default_transport(provider_name),
+            api_key: mistral.api_key.clone(),
+            base_url: mistral.base_url.clone(),
+            model: "default".to_string(),
+            usage_tracker: usage::mistral(),
+            custom_headers: None,
+            services,
+            seed: None,
+        };
+
+        Ok(Self {
+            config: chat_config,
+            provider_config: mistral.clone(),
+        })
+    }
+
+    pub(crate) fn set_model(&mut self, model: String) {
+        self.config.model = model;
+    }
+
+    pub(crate) fn models() -> &'static [&'static str] {
+        MISTRAL_MODELS
+    }
+
+    /// Get the context limit for a given model name
+    pub(crate) fn context_limit(model: &str) -> Option<u64> {
+        let configured = ConfigFile::load()
+            .ok()
+            .and_then(|config| config.model_context_window(model));
+        if configured.is_some() {
+            configured
+        } else if model.starts_with("codestral-") || model.starts_with("devstral-") {
+            Some(256_000)
+        } else if model.starts_with("mistral-") {
+            Some(128_000)
+        } else {
+            None
+        }
+    }
+}
+
+impl Provider for MistralProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Message>,
+        output: &crate::output::OutputContext,
+    ) -> Result<ChatResponse> {
+        execute_chat(&self.config, &self.provider_config, &messages, output, None).await
+    }
+
+    async fn prepare_request(&self, messages: Vec<Message>) -> Result<serde_json::Value> {
+        let request = build_request(&self.config, &self.provider_config, &messages, None).await?;
+        Ok(serde_json::to_value(&request)?)
+    }
+
+    fn start_turn(&self) {
+        usage::mistral().start_turn();
+    }
+}
+
+impl ModelConfigProvider for MistralProviderConfig {
+    fn get_model_config(&self, model_id: &str) -> Option<&crate::config::ModelConfig> {
+        self.get_model_config(model_id)
+    }
+}
+
+/// The Mistral provider used for fill-in-the-middle completions: the first
+/// enabled one with an API key and a FIM model.
+pub(crate) fn fim_provider(config: &ConfigFile) -> Option<&MistralProviderConfig> {
+    let mut providers = config.providers_of_type(ProviderType::Mistral);
+    providers.sort_by_key(|(name, _)| name.as_str());
+    providers
+        .into_iter()
+        .filter_map(|(_, provider)| provider.as_mistral())
+        .find(|p| p.enabled && !p.api_key.is_empty() && !p.fim_model.is_empty())
+}
+
+#[derive(Deserialize)]
+struct FimResponse {
+    choices: Vec<FimChoice>,
+}
+
+#[derive(Deserialize)]
+struct FimChoice {
+    message: FimMessage,
+}
+
+#[derive(Deserialize)]
+struct FimMessage {
+    content: String,
+}
+
+fn fim_request(model: &str, prefix: &str, suffix: &str) -> serde_json::Value {
+    serde_json::json!({
+        "model": model,
+        "prompt": prefix,
+        "suffix": suffix,
+        "max_tokens": FIM_MAX_TOKENS,
+        "temperature": 0.0,
+        "stream": false,
+    })
+}
+
+/// Generate the code that goes between `prefix` and `suffix`.
+pub(crate) async fn fill_in_middle(prefix: &str, suffix: &str) -> Result<String> {
+    let config = ConfigFile::load()?;
+    let provider = fim_provider(&config).ok_or_else(|| {
+        Error::Auth("Fill-in-the-middle requires a configured Mistral provider.".to_string())
+    })?;
+
+    let response = Timeouts::for_provider("mistral")
+        .client()
+        .post(format!(
+            "{}/fim/completions",
+            provider.base_url.trim_end_matches('/')
+        ))
+        .bearer_auth(&provider.api_key)
+        .json(&fim_request(&provider.fim_model, prefix, suffix))
+        .send()
+        .await?;
+
+    let status = response.status().as_u16();
+    if !response.status().is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Err(super::api_error(status, message));
+    }
+
+    let response: FimResponse = response.json().await?;
+    response
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message.content)
+        .ok_or_else(|| Error::Other("Empty fill-in-the-middle response".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fim_response() {
+        let body = fim_request(
+            "codestral-latest",
+            "fn add(a: i32, b: i32) -> i32 {\n",
+            "}\n",
+        );
+        assert_eq!(body["prompt"], "fn add(a: i32, b: i32) -> i32 {\n");
+        assert_eq!(body["suffix"], "}\n");
+
+        let response: FimResponse = serde_json::from_str(
+            r#"{"id": "1", "model": "codestral-latest", "choices": [{"index": 0, "message": {"role": "assistant", "content": "    a + b\n"}, "finish_reason": "stop"}], "usage": {"prompt_tokens": 12, "completion_tokens": 4}}"#,
+        )
+        .unwrap();
+        assert_eq!(response.choices[0].message.content, "    a + b\n");
+    }
+}
@@ -1,35 +1,52 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Jason Ish
 
+//! Model providers and the messages of a conversation.
+//!
+//! Embedders only see the message types: a conversation is a list of
+//! [`Message`]s, each holding text or [`ContentBlock`]s.
+
 pub(crate) mod anthropic;
 pub(crate) mod antigravity;
 pub(crate) mod copilot;
+pub(crate) mod mistral;
 pub(crate) mod model_utils;
+pub(crate) mod ollama;
 pub(crate) mod openai;
 pub(crate) mod openai_compat;
 pub(crate) mod openrouter;
+pub(crate) mod timeouts;
 pub(crate) mod transaction_log;
+pub(crate) mod transport;
+pub(crate) mod vertex;
 pub(crate) mod zen;
 
+use std::collections::HashMap;
+
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
 
+/// A message of a conversation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub(crate) struct Message {
+pub struct Message {
     pub role: Role,
     pub content: MessageContent,
 }
 
+/// The content of a [`Message`]: plain text, or blocks of text, images,
+/// reasoning and tool calls.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
-pub(crate) enum MessageContent {
+pub enum MessageContent {
     Text(String),
     Blocks(Vec<ContentBlock>),
 }
 
+/// A part of a [`Message`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
-pub(crate) enum ContentBlock {
+#[non_exhaustive]
+pub enum ContentBlock {
     Text {
         text: String,
     },
@@ -68,16 +85,18 @@ pub(crate) enum ContentBlock {
     },
 }
 
+/// Who a [`Message`] is from.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
-pub(crate) enum Role {
+pub enum Role {
     System,
     User,
     Assistant,
 }
 
 impl Message {
-    pub(crate) fn user(content: impl Into<String>) -> Self {
+    /// A user message with the text `content`.
+    pub fn user(content: impl Into<String>) -> Self {
         Self {
             role: Role::User,
             content: MessageContent::Text(content.into()),
@@ -150,6 +169,27 @@ pub(crate) fn remove_last_turn(messages: &mut Vec<Message>) -> usize {
     removed
 }
 
+/// The text of the most recent user prompt, as it was sent to the model.
+///
+/// Images sent with the prompt are left out.
+pub(crate) fn last_user_prompt(messages: &[Message]) -> Option<String> {
+    let message = messages
+        .iter()
+        .rev()
+        .find(|m| m.role == Role::User && !m.is_tool_result_only())?;
+    Some(match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Blocks(blocks) => blocks
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    })
+}
+
 /// Remove assistant responses and tool-result-only user messages from the end,
 /// stopping once the most recent real user prompt is preserved.
 ///
@@ -252,6 +292,9 @@ pub(crate) fn context_limit(provider: crate::providers::ModelProvider, model: &s
         ModelProvider::OpenCodeZen => zen::ZenProvider::context_limit(model),
         ModelProvider::OpenAiCompat => openai_compat::OpenAiCompatProvider::context_limit(model),
         ModelProvider::OpenRouter => openrouter::OpenRouterProvider::context_limit(model),
+        ModelProvider::Ollama => ollama::OllamaProvider::context_limit(model),
+        ModelProvider::Vertex => vertex::VertexProvider::context_limit(model),
+        ModelProvider::Mistral => mistral::MistralProvider::context_limit(model),
     }
 }
 
@@ -302,6 +345,27 @@ pub(crate) fn is_retryable_message(message: &str) -> bool {
         || msg_lower.contains("server had an error")
 }
 
+/// How long a response asks us to wait before retrying, from its
+/// `retry-after-ms` or `retry-after` header (seconds or an HTTP date).
+pub(crate) fn retry_after(headers: &HashMap<String, String>) -> Option<std::time::Duration> {
+    if let Some(ms) = headers
+        .get("retry-after-ms")
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        && ms >= 0.0
+    {
+        return Some(std::time::Duration::from_millis(ms as u64));
+    }
+    let value = headers.get("retry-after")?.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        // Out of range values, like inf, aren't a usable hint
+        return std::time::Duration::try_from_secs_f64(secs).ok();
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
 /// Create the appropriate error for an API response based on status and message.
 /// Returns `Error::Retryable` if the error appears to be transient, otherwise `Error::Api`.
 pub(crate) fn api_error(status: u16, message: String) -> crate::error::Error {
@@ -316,6 +380,52 @@ pub(crate) fn api_error(status: u16, message: String) -> crate::error::Error {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_retry_after() {
+        let headers = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+        assert_eq!(
+            retry_after(&headers(&[("retry-after", "30")])),
+            Some(std::time::Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_after(&headers(&[
+                ("retry-after", "30"),
+                ("retry-after-ms", "1500")
+            ])),
+            Some(std::time::Duration::from_millis(1500))
+        );
+        assert_eq!(
+            retry_after(&headers(&[(
+                "retry-after",
+                "Wed, 21 Oct 2015 07:28:00 GMT"
+            )])),
+            None
+        );
+        let soon = (chrono::Utc::now() + chrono::Duration::seconds(120)).to_rfc2822();
+        let wait = retry_after(&headers(&[("retry-after", &soon)])).unwrap();
+        assert!(wait.as_secs() > 100 && wait.as_secs() <= 120);
+        assert_eq!(retry_after(&headers(&[])), None);
+        assert_eq!(retry_after(&headers(&[("retry-after", "-5")])), None);
+        assert_eq!(retry_after(&headers(&[("retry-after", "inf")])), None);
+        assert_eq!(retry_after(&headers(&[("retry-after", "1e30")])), None);
+
+        let error = api_error(429, "slow down".to_string())
+            .with_retry_after(Some(std::time::Duration::from_secs(30)));
+        assert!(matches!(
+            error,
+            crate::error::Error::RateLimited { status: 429, .. }
+        ));
+        assert!(error.is_retryable());
+        let error = api_error(400, "bad".to_string())
+            .with_retry_after(Some(std::time::Duration::from_secs(30)));
+        assert!(matches!(error, crate::error::Error::Api { .. }));
+    }
+
     #[test]
     fn test_is_tool_result_only() {
         // Text message - not tool result only
@@ -434,6 +544,34 @@ mod tests {
         assert_eq!(removed, 0);
     }
 
+    #[test]
+    fn test_last_user_prompt_skips_tool_results() {
+        let mut messages = vec![
+            Message::user("First"),
+            Message::assistant_text("Response 1"),
+            Message::user("Second"),
+            Message::assistant_blocks(vec![ContentBlock::ToolUse {
+                id: "1".into(),
+                name: "glob".into(),
+                input: serde_json::json!({}),
+                thought_signature: None,
+            }]),
+            Message {
+                role: Role::User,
+                content: MessageContent::Blocks(vec![ContentBlock::ToolResult {
+                    tool_use_id: "1".into(),
+                    content: "file.txt".into(),
+                    is_error: false,
+                    data: None,
+                    mime_type: None,
+                }]),
+            },
+        ];
+        assert_eq!(last_user_prompt(&messages).as_deref(), Some("Second"));
+        messages.clear();
+        assert_eq!(last_user_prompt(&messages), None);
+    }
+
     #[test]
     fn test_remove_first_turn_simple() {
         // Simple case: user + assistant
@@ -0,0 +1,741 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Native Ollama provider.
+//!
+//! Talks to Ollama's own `/api/chat` endpoint, which streams newline-delimited JSON,
+//! instead of its OpenAI-compatible `/v1` API. Installed models are discovered from
+//! `/api/tags`, so they show up in the model menu without being listed in the config.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ConfigFile, OllamaProviderConfig};
+use crate::error::{Error, Result};
+use crate::output;
+use crate::prompts;
+use crate::provider::transport::{self, HttpRequest, HttpTransport};
+use crate::provider::{
+    ChatResponse, ContentBlock, Message, MessageContent, Provider, Role, StopReason, ToolCall,
+};
+use crate::services::Services;
+use crate::tools;
+use crate::usage;
+
+/// Where Ollama listens by default.
+pub(crate) const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+/// How long to wait for `/api/tags`; a stopped server shouldn't hold anything up.
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Installed models discovered per provider name.
+static DISCOVERED: Mutex<BTreeMap<String, Vec<String>>> = Mutex::new(BTreeMap::new());
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<TagModel>,
+}
+
+#[derive(Deserialize)]
+struct TagModel {
+    name: String,
+}
+
+/// Fetch the names of the models installed on the Ollama server at `base_url`.
+async fn fetch_models(base_url: &str) -> Result<Vec<String>> {
+    let url = format!("{}/api/tags", base_url.trim_end_matches('/'));
+    let response = reqwest::Client::new()
+        .get(&url)
+        .timeout(DISCOVERY_TIMEOUT)
+        .send()
+        .await?;
+
+    let status = response.status().as_u16();
+    if !response.status().is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Err(super::api_error(status, message));
+    }
+
+    let tags: TagsResponse = response.json().await?;
+    let mut names: Vec<String> = tags.models.into_iter().map(|m| m.name).collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Discover the models of the provider named `provider_name`, remembering them for
+/// the model menu.
+pub(crate) async fn refresh_provider(provider_name: &str, base_url: &str) -> Result<Vec<String>> {
+    let models = fetch_models(base_url).await?;
+    DISCOVERED
+        .lock()
+        .unwrap()
+        .insert(provider_name.to_string(), models.clone());
+    Ok(models)
+}
+
+/// Refresh the discovered models of every enabled Ollama provider. Servers that
+/// can't be reached keep their previously discovered models.
+pub(crate) async fn refresh_models() {
+    let Ok(config) = ConfigFile::load() else {
+        return;
+    };
+    for (name, provider_config) in &config.providers.entries {
+        if let Some(ollama) = provider_config.as_ollama()
+            && ollama.enabled
+        {
+            let _ = refresh_provider(name, &ollama.base_url).await;
+        }
+    }
+}
+
+/// Models discovered for the provider named `provider_name`.
+pub(crate) fn discovered_models(provider_name: &str) -> Vec<String> {
+    DISCOVERED
+        .lock()
+        .unwrap()
+        .get(provider_name)
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[derive(Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<serde_json::Value>,
+    stream: bool,
+    /// Only sent to turn thinking off; models that can't think reject `true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    think: Option<bool>,
+    #[serde(skip_serializing_if = "ChatOptions::is_empty")]
+    options: ChatOptions,
+}
+
+#[derive(Serialize, Default)]
+struct ChatOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+}
+
+impl ChatOptions {
+    fn is_empty(&self) -> bool {
+        self.temperature.is_none()
+            && self.top_p.is_none()
+            && self.num_predict.is_none()
+            && self.seed.is_none()
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    message: Option<ChunkMessage>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    done_reason: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChunkMessage {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    thinking: String,
+    #[serde(default)]
+    tool_calls: Vec<ChunkToolCall>,
+}
+
+#[derive(Deserialize)]
+struct ChunkToolCall {
+    function: ChunkFunction,
+}
+
+#[derive(Deserialize)]
+struct ChunkFunction {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+/// Splits a streamed response body into lines.
+struct LineStream {
+    body: BoxStream<'static, Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+}
+
+impl LineStream {
+    fn new(body: BoxStream<'static, Result<Vec<u8>>>) -> Self {
+        Self {
+            body,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// The next non-empty line, including a final line without a newline.
+    async fn next_line(&mut self) -> Option<Result<String>> {
+        loop {
+            if let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+                let line = String::from_utf8_lossy(&line).trim().to_string();
+                if line.is_empty() {
+                    continue;
+                }
+                return Some(Ok(line));
+            }
+
+            match self.body.next().await {
+                Some(Ok(chunk)) => self.buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    let line = String::from_utf8_lossy(&self.buffer).trim().to_string();
+                    self.buffer.clear();
+                    return (!line.is_empty()).then_some(Ok(line));
+                }
+            }
+        }
+    }
+}
+
+pub(crate) struct OllamaProvider {
+    provider_name: String,
+    transport: Arc<dyn HttpTransport>,
+    provider_config: OllamaProviderConfig,
+    model: String,
+    thinking_enabled: bool,
+    /// Sampling seed, for deterministic sampling
+    seed: Option<u64>,
+    services: Services,
+}
+
+impl OllamaProvider {
+    pub(crate) fn try_new(provider_name: &str, services: Services) -> Result<Self> {
+        let config = ConfigFile::load()?;
+        let ollama = config
+            .get_provider(provider_name)
+            .and_then(|p| p.as_ollama())
+            .ok_or_else(|| {
+                Error::Auth(format!(
+                    "Ollama provider '{}' not configured.",
+                    provider_name
+                ))
+            })?;
+
+        if !ollama.enabled {
+            return Err(Error::Auth(format!(
+                "Ollama provider '{}' is disabled.",
+                provider_name
+            )));
+        }
+
+        Ok(Self::with_config(provider_name, ollama.clone(), services))
+    }
+
+    pub(crate) fn with_config(
+        provider_name: &str,
+        config: OllamaProviderConfig,
+        services: Services,
+    ) -> Self {
+        Self {
+            provider_name: provider_name.to_string(),
+            transport: transport::default_transport(provider_name),
+            provider_config: config,
+            model: String::new(),
+            thinking_enabled: true,
+            seed: None,
+            services,
+        }
+    }
+
+    pub(crate) fn set_model(&mut self, model: String) {
+        self.model = model;
+    }
+
+    pub(crate) fn set_thinking_enabled(&mut self, enabled: bool) {
+        self.thinking_enabled = enabled;
+    }
+
+    pub(crate) fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    /// Get context limit for a given model name from its configured `context-window`.
+    /// Returns None when none is set, since the window depends on how the model is run.
+    pub(crate) fn context_limit(model: &str) -> Option<u64> {
+        ConfigFile::load().ok()?.model_context_window(model)
+    }
+
+    async fn build_request(&self, messages: &[Message]) -> ChatRequest {
+        let model_params = self.provider_config.get_model_config(&self.model);
+        let tier =
+            prompts::PromptTier::for_context_limit(model_params.and_then(|c| c.context_window));
+
+        let mut all_messages = vec![Message::system(
+            prompts::system_prompt_for_tier(
+                Some(&self.services),
+                &format!("{}/{}", self.provider_name, self.model),
+                tier,
+            )
+            .join("\n\n"),
+        )];
+        all_messages.extend(messages.iter().cloned());
+
+        let tools = tools::all_definitions(&self.services)
+            .await
+            .into_iter()
+            .map(|t| match tier {
+                prompts::PromptTier::Full => t,
+                prompts::PromptTier::Condensed => t.condensed(),
+            })
+            .map(|t| {
+                serde_json::json!({
+                    "type": "function",
+                    "function": {
+                        "name": t.name,
+                        "description": t.description,
+                        "parameters": tools::schema::adapt(&t.input_schema, tools::schema::Dialect::Compat),
+                    }
+                })
+            })
+            .collect();
+        let model = model_params
+            .map(|c| c.id.clone())
+            .unwrap_or_else(|| self.model.clone());
+
+        ChatRequest {
+            model,
+            messages: build_messages(&all_messages),
+            tools,
+            stream: true,
+            think: (!self.thinking_enabled).then_some(false),
+            options: ChatOptions {
+                temperature: model_params.and_then(|c| c.temperature),
+                top_p: model_params.and_then(|c| c.top_p),
+                num_predict: model_params.and_then(|c| c.max_tokens),
+                seed: self.seed,
+            },
+        }
+    }
+}
+
+/// Convert provider messages to Ollama's chat format.
+///
+/// Ollama identifies tool results by tool name rather than call ID, and takes images
+/// as a list of base64 strings on the message.
+fn build_messages(messages: &[Message]) -> Vec<serde_json::Value> {
+    let tool_names: HashMap<&str, &str> = messages
+        .iter()
+        .filter_map(|m| match &m.content {
+            MessageContent::Blocks(blocks) => Some(blocks),
+            MessageContent::Text(_) => None,
+        })
+        .flatten()
+        .filter_map(|block| match block {
+            ContentBlock::ToolUse { id, name, .. } => Some((id.as_str(), name.as_str())),
+            _ => None,
+        })
+        .collect();
+
+    let mut out = Vec::new();
+    for message in messages {
+        let role = match message.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+        };
+
+        let blocks = match &message.content {
+            MessageContent::Text(text) => {
+                out.push(serde_json::json!({"role": role, "content": text}));
+                continue;
+            }
+            MessageContent::Blocks(blocks) => blocks,
+        };
+
+        let mut text = Vec::new();
+        let mut images = Vec::new();
+        let mut tool_calls = Vec::new();
+        for block in blocks {
+            match block {
+                ContentBlock::Text { text: t } => text.push(t.clone()),
+                ContentBlock::Image { data, .. } => images.push(STANDARD.encode(data)),
+                ContentBlock::Thinking { .. } => {}
+                ContentBlock::ToolUse { name, input, .. } => {
+                    tool_calls.push(serde_json::json!({
+                        "function": {"name": name, "arguments": input}
+                    }));
+                }
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    data,
+                    ..
+                } => {
+                    let mut result = serde_json::json!({
+                        "role": "tool",
+                        "content": content,
+                        "tool_name": tool_names.get(tool_use_id.as_str()).copied().unwrap_or(""),
+                    });
+                    if let Some(data) = data {
+                        result["images"] = serde_json::json!([data]);
+                    }
+                    out.push(result);
+                }
+                ContentBlock::Summary {
+                    summary,
+                    messages_compacted,
+                } => text.push(format!(
+                    "[Summary of {} previous messages]\n\n{}",
+                    messages_compacted, summary
+                )),
+            }
+        }
+
+        if text.is_empty() && images.is_empty() && tool_calls.is_empty() {
+            continue;
+        }
+        let mut value = serde_json::json!({"role": role, "content": text.join("\n\n")});
+        if !images.is_empty() {
+            value["images"] = serde_json::json!(images);
+        }
+        if !tool_calls.is_empty() {
+            value["tool_calls"] = serde_json::json!(tool_calls);
+        }
+        out.push(value);
+    }
+    out
+}
+
+impl Provider for OllamaProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Message>,
+        output: &crate::output::OutputContext,
+    ) -> Result<ChatResponse> {
+        let url = format!(
+            "{}/api/chat",
+            self.provider_config.base_url.trim_end_matches('/')
+        );
+        let request = self.build_request(&messages).await;
+
+        let mut req_headers = HashMap::new();
+        req_headers.insert("Content-Type".to_string(), "application/json".to_string());
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        let response = self
+            .transport
+            .post(HttpRequest {
+                url: url.clone(),
+                headers,
+                body: serde_json::to_vec(&request)?,
+            })
+            .await
+            .map_err(|e| {
+                Error::Other(format!(
+                    "Failed to connect to {} ({}): {}",
+                    self.provider_name, url, e
+                ))
+            })?;
+
+        let resp_headers =
+            crate::provider::transaction_log::header_map_to_hash_map(&response.headers);
+
+        if !response.is_success() {
+            let status = response.status;
+            let message = response.text().await;
+
+            let retry_after = super::retry_after(&resp_headers);
+            crate::provider::transaction_log::log(
+                &url,
+                req_headers,
+                serde_json::to_value(&request).unwrap_or_default(),
+                resp_headers,
+                serde_json::json!({
+                    "error": true,
+                    "status": status,
+                    "body": message
+                }),
+            );
+
+            return Err(super::api_error(status, message).with_retry_after(retry_after));
+        }
+
+        let mut full_text = String::new();
+        let mut tool_calls: Vec<ToolCall> = Vec::new();
+        let mut stop_reason = StopReason::Unknown;
+        let mut thinking = output::ThinkingState::new(output);
+        let streaming_start = std::time::Instant::now();
+        let mut raw_events: Vec<serde_json::Value> = Vec::new();
+
+        let mut lines = LineStream::new(response.body);
+        while let Some(line) = lines.next_line().await {
+            let line = line?;
+            raw_events
+                .push(serde_json::from_str(&line).unwrap_or(serde_json::json!({ "raw": line })));
+
+            let Ok(chunk) = serde_json::from_str::<ChatChunk>(&line) else {
+                continue;
+            };
+
+            if let Some(error) = chunk.error {
+                thinking.end();
+                return Err(super::api_error(500, error));
+            }
+
+            if let Some(message) = chunk.message {
+                if !message.thinking.is_empty() {
+                    thinking.emit(&message.thinking);
+                }
+                if !message.content.is_empty() {
+                    thinking.end();
+                    output::print_text(output, &message.content);
+                    full_text.push_str(&message.content);
+                }
+                for call in message.tool_calls {
+                    tool_calls.push(ToolCall {
+                        id: format!("call_{}", uuid::Uuid::new_v4().simple()),
+                        name: call.function.name,
+                        input: call.function.arguments,
+                        thought_signature: None,
+                    });
+                }
+            }
+
+            if chunk.done {
+                stop_reason = match chunk.done_reason.as_deref() {
+                    Some("stop") => StopReason::EndTurn,
+                    Some("length") => StopReason::MaxTokens,
+                    _ => StopReason::Unknown,
+                };
+
+                let input_tokens = chunk.prompt_eval_count.unwrap_or(0);
+                let output_tokens = chunk.eval_count.unwrap_or(0);
+                let usage_tracker = usage::ollama();
+                if input_tokens > 0 {
+                    usage_tracker.record_input(input_tokens);
+                    output::emit_context_update(output, input_tokens, None);
+                }
+                if output_tokens > 0 {
+                    usage_tracker.record_output(output_tokens);
+                    let duration = streaming_start.elapsed().as_secs_f64();
+                    if duration > 0.0 {
+                        output::emit_working_progress(
+                            output,
+                            usage_tracker.turn_total(),
+                            duration,
+                            output_tokens as f64 / duration,
+                        );
+                    }
+                }
+                if input_tokens > 0 || output_tokens > 0 {
+                    output::emit_usage_update(output, input_tokens, output_tokens, 0, 0);
+                }
+            }
+        }
+
+        thinking.end();
+
+        // Only end the text block if we actually streamed any text.
+        if !full_text.is_empty() {
+            output::print_text_end(output);
+        }
+
+        if crate::provider::transaction_log::is_active() {
+            crate::provider::transaction_log::log(
+                &url,
+                req_headers,
+                serde_json::to_value(&request).unwrap_or(serde_json::json!({})),
+                resp_headers,
+                serde_json::Value::Array(raw_events),
+            );
+        }
+
+        let mut content_blocks = Vec::new();
+        if !full_text.is_empty() {
+            content_blocks.push(ContentBlock::Text { text: full_text });
+        }
+        for call in &tool_calls {
+            content_blocks.push(ContentBlock::ToolUse {
+                id: call.id.clone(),
+                name: call.name.clone(),
+                input: call.input.clone(),
+                thought_signature: None,
+            });
+        }
+
+        if !tool_calls.is_empty() {
+            stop_reason = StopReason::ToolUse;
+        }
+
+        Ok(ChatResponse {
+            tool_calls,
+            content_blocks,
+            stop_reason,
+        })
+    }
+
+    async fn prepare_request(&self, messages: Vec<Message>) -> Result<serde_json::Value> {
+        let request = self.build_request(&messages).await;
+        Ok(serde_json::to_value(&request)?)
+    }
+
+    fn start_turn(&self) {
+        usage::ollama().start_turn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::transport::mock::MockTransport;
+
+    fn mock_provider(transport: Arc<MockTransport>) -> OllamaProvider {
+        let mut provider = OllamaProvider::with_config(
+            "ollama",
+            OllamaProviderConfig {
+                enabled: true,
+                base_url: "http://localhost:11434/".to_string(),
+                model_configs: Vec::new(),
+            },
+            Services::null(),
+        );
+        provider.transport = transport;
+        provider.set_model("qwen3:8b".to_string());
+        provider
+    }
+
+    #[tokio::test]
+    async fn test_chat_streamed_tool_call() {
+        let body = concat!(
+            r#"{"message":{"role":"assistant","content":"","thinking":"Let me look"},"done":false}"#,
+            "\n",
+            r#"{"message":{"role":"assistant","content":"Listing"},"done":false}"#,
+            "\n",
+            r#"{"message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"bash","arguments":{"command":"ls"}}}]},"done":false}"#,
+            "\n",
+            r#"{"message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":120,"eval_count":15}"#,
+        );
+        // Deliver the body in small chunks so lines straddle chunk boundaries.
+        let chunks: Vec<&str> = body
+            .as_bytes()
+            .chunks(7)
+            .map(|c| std::str::from_utf8(c).unwrap())
+            .collect();
+        let transport = Arc::new(MockTransport::default());
+        transport.push_response(200, chunks);
+
+        let provider = mock_provider(transport.clone());
+        let response = provider
+            .chat(
+                vec![Message::user("list files")],
+                &crate::output::OutputContext::null(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].name, "bash");
+        assert_eq!(response.tool_calls[0].input["command"], "ls");
+        assert!(matches!(
+            &response.content_blocks[0],
+            ContentBlock::Text { text } if text == "Listing"
+        ));
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].url, "http://localhost:11434/api/chat");
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["model"], "qwen3:8b");
+        assert!(body.get("think").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_error() {
+        let transport = Arc::new(MockTransport::default());
+        transport.push_response(200, vec![r#"{"error":"model 'nope' not found"}"#]);
+
+        let provider = mock_provider(transport);
+        let err = provider
+            .chat(
+                vec![Message::user("hi")],
+                &crate::output::OutputContext::null(),
+            )
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("not found"));
+    }
+
+    #[tokio::test]
+    async fn test_request_options() {
+        let mut provider = mock_provider(Arc::new(MockTransport::default()));
+        let request = provider
+            .prepare_request(vec![Message::user("hi")])
+            .await
+            .unwrap();
+        assert!(request.get("options").is_none());
+
+        provider.set_seed(Some(7));
+        provider.set_thinking_enabled(false);
+        let request = provider
+            .prepare_request(vec![Message::user("hi")])
+            .await
+            .unwrap();
+        assert_eq!(request["options"]["seed"], 7);
+        assert_eq!(request["think"], false);
+    }
+
+    #[test]
+    fn test_build_messages_tool_round_trip() {
+        let messages = vec![
+            Message::assistant_blocks(vec![ContentBlock::ToolUse {
+                id: "call_1".to_string(),
+                name: "bash".to_string(),
+                input: serde_json::json!({"command": "ls"}),
+                thought_signature: None,
+            }]),
+            Message {
+                role: Role::User,
+                content: MessageContent::Blocks(vec![ContentBlock::ToolResult {
+                    tool_use_id: "call_1".to_string(),
+                    content: "README.md".to_string(),
+                    is_error: false,
+                    data: None,
+                    mime_type: None,
+                }]),
+            },
+        ];
+        let result = build_messages(&messages);
+        assert_eq!(result.len(), 2);
+        assert_eq!(
+            result[0]["tool_calls"][0]["function"]["arguments"]["command"],
+            "ls"
+        );
+        assert_eq!(result[1]["role"], "tool");
+        assert_eq!(result[1]["tool_name"], "bash");
+        assert_eq!(result[1]["content"], "README.md");
+    }
+}
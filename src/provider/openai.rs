@@ -2,6 +2,7 @@
 // Copyright (c) 2025 Jason Ish
 
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use base64::Engine;
@@ -14,6 +15,8 @@ use crate::config::{ConfigFile, OpenAiProviderConfig, ProviderConfig, ProviderTy
 use crate::error::{Error, Result};
 use crate::output;
 use crate::provider::model_utils;
+use crate::provider::timeouts::Timeouts;
+use crate::provider::transport::{self, HttpRequest, HttpTransport};
 use crate::provider::{
     ChatResponse, ContentBlock, Message, MessageContent, Provider, Role, StopReason, ToolCall,
 };
@@ -65,7 +68,10 @@ struct OpenAiState {
 }
 
 pub(crate) struct OpenAiProvider {
+    /// Client for OAuth token refreshes
     client: Client,
+    /// Transport for API requests
+    transport: Arc<dyn HttpTransport>,
     state: Mutex<OpenAiState>,
     model: String,
     project_id: Option<String>,
@@ -83,6 +89,8 @@ struct OpenAiTool {
     name: String,
     description: String,
     parameters: serde_json::Value,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    strict: bool,
 }
 
 #[derive(Serialize, Default, Clone)]
@@ -142,7 +150,8 @@ impl OpenAiProvider {
             .ok_or_else(|| Error::Auth("OpenAI not configured. Run /login.".to_string()))?;
 
         Ok(Self {
-            client: Client::new(),
+            client: Timeouts::for_provider(&local_id).client(),
+            transport: transport::default_transport(&local_id),
             state: Mutex::new(OpenAiState {
                 local_id,
                 access_token: openai.access_token,
@@ -448,14 +457,17 @@ impl OpenAiProvider {
                     project_id: self.project_id.clone(),
                 }),
             );
-            let _ = config.save();
+            let _ = config.save_without_backup();
         }
 
         Ok(state.access_token.clone())
     }
 
     async fn build_request(&self, messages: &[Message]) -> CodexRequest {
-        let instructions = crate::prompts::system_prompt_with_services(Some(&self.services));
+        let instructions = crate::prompts::system_prompt_with_services(
+            Some(&self.services),
+            &format!("openai/{}", self.model),
+        );
         let instructions = instructions.join("\n\n");
 
         let input = self.build_codex_input(messages);
@@ -464,11 +476,17 @@ impl OpenAiProvider {
         let tools: Vec<OpenAiTool> = tools::all_definitions(&self.services)
             .await
             .into_iter()
-            .map(|t| OpenAiTool {
-                kind: "function",
-                name: t.name,
-                description: t.description,
-                parameters: t.input_schema,
+            .map(|t| {
+                let strict = tools::schema::strict(&t.input_schema);
+                OpenAiTool {
+                    kind: "function",
+                    name: t.name,
+                    description: t.description,
+                    strict: strict.is_some(),
+                    parameters: strict.unwrap_or_else(|| {
+                        tools::schema::adapt(&t.input_schema, tools::schema::Dialect::OpenAi)
+                    }),
+                }
             })
             .collect();
 
@@ -518,20 +536,16 @@ impl OpenAiProvider {
             "responses=experimental".to_string(),
         );
         req_headers.insert("originator".to_string(), "codex_cli_rs".to_string());
-        req_headers.insert("chatgpt-account-id".to_string(), account_id.clone());
+        req_headers.insert("chatgpt-account-id".to_string(), account_id);
         req_headers.insert("accept".to_string(), "text/event-stream".to_string());
 
         let response = self
-            .client
-            .post(OPENAI_CODEX_URL)
-            .header("Authorization", format!("Bearer {}", access_token))
-            .header("Content-Type", "application/json")
-            .header("OpenAI-Beta", "responses=experimental")
-            .header("originator", "codex_cli_rs")
-            .header("chatgpt-account-id", account_id)
-            .header("accept", "text/event-stream")
-            .body(body_bytes)
-            .send()
+            .transport
+            .post(HttpRequest {
+                url: OPENAI_CODEX_URL.to_string(),
+                headers: transport::header_map(&req_headers)?,
+                body: body_bytes,
+            })
             .await
             .map_err(|e| {
                 Error::Other(format!(
@@ -541,12 +555,13 @@ impl OpenAiProvider {
             })?;
 
         let resp_headers =
-            crate::provider::transaction_log::header_map_to_hash_map(response.headers());
+            crate::provider::transaction_log::header_map_to_hash_map(&response.headers);
 
-        if !response.status().is_success() {
-            let status = response.status().as_u16();
-            let message = response.text().await.unwrap_or_default();
+        if !response.is_success() {
+            let status = response.status;
+            let message = response.text().await;
 
+            let retry_after = super::retry_after(&resp_headers);
             crate::provider::transaction_log::log(
                 OPENAI_CODEX_URL,
                 req_headers.clone(),
@@ -559,7 +574,7 @@ impl OpenAiProvider {
                 }),
             );
 
-            return Err(super::api_error(status, message));
+            return Err(super::api_error(status, message).with_retry_after(retry_after));
         }
 
         let mut full_text = String::new();
@@ -573,9 +588,9 @@ impl OpenAiProvider {
         let mut reasoning_summary = String::new();
         let mut encrypted_content: Option<String> = None;
 
-        let mut sse = sse::SseStream::new(response.bytes_stream());
+        let mut sse = sse::SseStream::new(response.body);
         while let Some(result) = sse.next_event().await {
-            let data = result.map_err(Error::Http)?;
+            let data = result?;
 
             let Ok(event) = serde_json::from_str::<serde_json::Value>(&data) else {
                 continue;
@@ -950,6 +965,86 @@ fn chatgpt_account_id(token: &str) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::provider::transport::mock::MockTransport;
+
+    fn mock_provider(transport: Arc<MockTransport>) -> OpenAiProvider {
+        let claims = serde_json::json!({
+            "https://api.openai.com/auth": { "chatgpt_account_id": "acct" }
+        });
+        let token = format!("x.{}.y", URL_SAFE_NO_PAD.encode(claims.to_string()));
+        OpenAiProvider {
+            client: Client::new(),
+            transport,
+            state: Mutex::new(OpenAiState {
+                local_id: "openai".to_string(),
+                access_token: token,
+                refresh_token: String::new(),
+                expires_at: 0,
+            }),
+            model: OPENAI_DEFAULT_MODEL.to_string(),
+            project_id: None,
+            client_id: String::new(),
+            audience: String::new(),
+            thinking_enabled: true,
+            usage_tracker: usage::openai(),
+            services: Services::null(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_streamed_tool_call() {
+        let fixture = include_str!("testdata/openai_tool_call.sse");
+        // Deliver the fixture in small chunks so events straddle chunk boundaries.
+        let chunks: Vec<&str> = fixture
+            .as_bytes()
+            .chunks(7)
+            .map(|c| std::str::from_utf8(c).unwrap())
+            .collect();
+        let transport = Arc::new(MockTransport::default());
+        transport.push_response(200, chunks);
+
+        let provider = mock_provider(transport.clone());
+        let response = provider
+            .chat(
+                vec![Message::user("list files")],
+                &crate::output::OutputContext::null(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].id, "call_1");
+        assert_eq!(response.tool_calls[0].name, "bash");
+        assert_eq!(response.tool_calls[0].input["command"], "ls");
+        assert!(matches!(
+            &response.content_blocks[0],
+            ContentBlock::Text { text } if text == "Hello"
+        ));
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, OPENAI_CODEX_URL);
+        assert_eq!(requests[0].headers["chatgpt-account-id"], "acct");
+    }
+
+    #[tokio::test]
+    async fn test_chat_error_status() {
+        let transport = Arc::new(MockTransport::default());
+        transport.push_response(400, vec!["bad request"]);
+
+        let provider = mock_provider(transport.clone());
+        let err = provider
+            .chat(
+                vec![Message::user("hi")],
+                &crate::output::OutputContext::null(),
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Api { status: 400, .. }));
+        assert_eq!(transport.requests().len(), 1);
+    }
 
     #[test]
     fn test_model_variants() {
@@ -8,17 +8,18 @@
 //! (e.g., OpenRouterProvider).
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
-use reqwest::Client;
-use reqwest::header::HeaderMap;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::Serialize;
 
 use crate::config::ConfigFile;
 use crate::error::{Error, Result};
 use crate::output;
 use crate::prompts;
+use crate::provider::transport::{self, HttpRequest, HttpTransport};
 use crate::provider::{
     ChatResponse, ContentBlock, Message, MessageContent, Provider, Role, StopReason, ToolCall,
 };
@@ -31,13 +32,15 @@ const MAX_CONTINUATION_DEPTH: u8 = 255;
 /// Configuration for an OpenAI-compatible chat client.
 pub(crate) struct OpenAiChatConfig {
     pub provider_name: String,
-    pub client: Client,
+    pub transport: Arc<dyn HttpTransport>,
     pub api_key: String,
     pub base_url: String,
     pub model: String,
     pub usage_tracker: &'static usage::Usage,
     pub custom_headers: Option<HeaderMap>,
     pub services: Services,
+    /// Sampling seed, for backends that support deterministic sampling
+    pub seed: Option<u64>,
 }
 
 /// Trait for accessing model-specific configuration.
@@ -60,6 +63,8 @@ pub(crate) struct OpenAiRequest {
     max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     reasoning_effort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
 }
 
 #[derive(Serialize)]
@@ -168,36 +173,45 @@ async fn execute_chat_inner(
     );
     req_headers.insert("Content-Type".to_string(), "application/json".to_string());
 
-    let mut builder = config
-        .client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", config.api_key))
-        .header("Content-Type", "application/json");
+    let mut headers = HeaderMap::new();
+    let authorization = HeaderValue::from_str(&format!("Bearer {}", config.api_key))
+        .map_err(|_| Error::Config("The API key contains invalid characters".to_string()))?;
+    headers.insert(AUTHORIZATION, authorization);
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
     // Add custom headers if provided
     if let Some(ref custom_headers) = config.custom_headers {
         for (k, v) in custom_headers.iter() {
             req_headers.insert(k.to_string(), v.to_str().unwrap_or("<binary>").to_string());
         }
-        builder = builder.headers(custom_headers.clone());
+        headers.extend(custom_headers.clone());
     }
 
     // Record TX bytes
     let body_bytes = serde_json::to_vec(&request)?;
 
-    let response = builder.body(body_bytes).send().await.map_err(|e| {
-        Error::Other(format!(
-            "Failed to connect to {} ({}): {}",
-            config.provider_name, url, e
-        ))
-    })?;
+    let response = config
+        .transport
+        .post(HttpRequest {
+            url: url.clone(),
+            headers,
+            body: body_bytes,
+        })
+        .await
+        .map_err(|e| {
+            Error::Other(format!(
+                "Failed to connect to {} ({}): {}",
+                config.provider_name, url, e
+            ))
+        })?;
 
-    let resp_headers = crate::provider::transaction_log::header_map_to_hash_map(response.headers());
+    let resp_headers = crate::provider::transaction_log::header_map_to_hash_map(&response.headers);
 
-    if !response.status().is_success() {
-        let status = response.status().as_u16();
-        let message = response.text().await.unwrap_or_default();
+    if !response.is_success() {
+        let status = response.status;
+        let message = response.text().await;
 
+        let retry_after = super::retry_after(&resp_headers);
         crate::provider::transaction_log::log(
             &url,
             req_headers.clone(),
@@ -210,7 +224,7 @@ async fn execute_chat_inner(
             }),
         );
 
-        return Err(super::api_error(status, message));
+        return Err(super::api_error(status, message).with_retry_after(retry_after));
     }
 
     let mut full_text = String::new();
@@ -224,10 +238,10 @@ async fn execute_chat_inner(
     let mut usage_recorded = false;
     let mut raw_events: Vec<serde_json::Value> = Vec::new();
 
-    let mut sse = crate::sse::SseStream::new(response.bytes_stream());
+    let mut sse = crate::sse::SseStream::new(response.body);
 
     while let Some(result) = sse.next_event().await {
-        let data = result.map_err(Error::Http)?;
+        let data = result?;
         raw_events.push(serde_json::from_str(&data).unwrap_or(serde_json::json!({ "raw": data })));
 
         // Try structured parsing first
@@ -459,27 +473,37 @@ pub(crate) async fn build_request(
     messages: &[Message],
     reasoning_effort_override: Option<&str>,
 ) -> Result<OpenAiRequest> {
+    // Get model-specific config (lookup by display name)
+    let model_params = model_config.get_model_config(&config.model);
+    let tier = prompts::PromptTier::for_context_limit(model_params.and_then(|c| c.context_window));
+
     let mut all_messages = vec![Message::system(
-        prompts::system_prompt_with_services(Some(&config.services)).join("\n\n"),
+        prompts::system_prompt_for_tier(
+            Some(&config.services),
+            &format!("{}/{}", config.provider_name, config.model),
+            tier,
+        )
+        .join("\n\n"),
     )];
     all_messages.extend(messages.iter().cloned());
 
     let tools: Vec<OpenAiTool> = tools::all_definitions(&config.services)
         .await
         .into_iter()
+        .map(|t| match tier {
+            prompts::PromptTier::Full => t,
+            prompts::PromptTier::Condensed => t.condensed(),
+        })
         .map(|t| OpenAiTool {
             kind: "function",
             function: OpenAiFunction {
                 name: t.name,
                 description: t.description,
-                parameters: t.input_schema,
+                parameters: tools::schema::adapt(&t.input_schema, tools::schema::Dialect::Compat),
             },
         })
         .collect();
 
-    // Get model-specific config (lookup by display name)
-    let model_params = model_config.get_model_config(&config.model);
-
     // Get the API model id from the config
     let api_model = model_params
         .map(|c| c.id.clone())
@@ -499,6 +523,7 @@ pub(crate) async fn build_request(
         top_p: model_params.and_then(|c| c.top_p),
         max_tokens: model_params.and_then(|c| c.max_tokens),
         reasoning_effort,
+        seed: config.seed,
     })
 }
 
@@ -662,6 +687,8 @@ fn build_messages(messages: &[Message]) -> Vec<serde_json::Value> {
 pub(crate) struct OpenAiCompatProvider {
     config: OpenAiChatConfig,
     provider_config: crate::config::OpenAiCompatProviderConfig,
+    /// Reasoning effort overriding the per-model config (e.g. from `--reasoning`)
+    reasoning_effort: Option<String>,
 }
 
 impl OpenAiCompatProvider {
@@ -708,18 +735,20 @@ impl OpenAiCompatProvider {
 
         let chat_config = OpenAiChatConfig {
             provider_name: provider_name.to_string(),
-            client: Client::new(),
+            transport: transport::default_transport(provider_name),
             api_key: openai_compat.api_key.clone(),
             base_url: openai_compat.base_url.clone(),
             model: "default".to_string(),
             usage_tracker: usage::openai_compat(),
             custom_headers: None,
             services,
+            seed: None,
         };
 
         Ok(Self {
             config: chat_config,
             provider_config,
+            reasoning_effort: None,
         })
     }
 
@@ -731,18 +760,20 @@ impl OpenAiCompatProvider {
     ) -> Self {
         let chat_config = OpenAiChatConfig {
             provider_name: provider_name.to_string(),
-            client: Client::new(),
+            transport: transport::default_transport(provider_name),
             api_key: config.api_key.clone(),
             base_url: config.base_url.clone(),
             model: "default".to_string(),
             usage_tracker,
             custom_headers: None,
             services,
+            seed: None,
         };
 
         Self {
             config: chat_config,
             provider_config: config,
+            reasoning_effort: None,
         }
     }
 
@@ -750,10 +781,18 @@ impl OpenAiCompatProvider {
         self.config.model = model;
     }
 
+    pub(crate) fn set_reasoning_effort(&mut self, effort: Option<String>) {
+        self.reasoning_effort = effort;
+    }
+
+    pub(crate) fn set_seed(&mut self, seed: Option<u64>) {
+        self.config.seed = seed;
+    }
+
     /// Get context limit for a given model name
     /// Returns None since we don't know limits of arbitrary OpenAI-compatible providers
-    pub(crate) fn context_limit(_model: &str) -> Option<u64> {
-        None
+    pub(crate) fn context_limit(model: &str) -> Option<u64> {
+        ConfigFile::load().ok()?.model_context_window(model)
     }
 }
 
@@ -763,11 +802,24 @@ impl Provider for OpenAiCompatProvider {
         messages: Vec<Message>,
         output: &crate::output::OutputContext,
     ) -> Result<ChatResponse> {
-        execute_chat(&self.config, &self.provider_config, &messages, output, None).await
+        execute_chat(
+            &self.config,
+            &self.provider_config,
+            &messages,
+            output,
+            self.reasoning_effort.as_deref(),
+        )
+        .await
     }
 
     async fn prepare_request(&self, messages: Vec<Message>) -> Result<serde_json::Value> {
-        let request = build_request(&self.config, &self.provider_config, &messages, None).await?;
+        let request = build_request(
+            &self.config,
+            &self.provider_config,
+            &messages,
+            self.reasoning_effort.as_deref(),
+        )
+        .await?;
         Ok(serde_json::to_value(&request)?)
     }
 
@@ -786,6 +838,110 @@ impl ModelConfigProvider for crate::config::OpenAiCompatProviderConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::provider::transport::mock::MockTransport;
+
+    fn mock_provider(transport: Arc<MockTransport>) -> OpenAiCompatProvider {
+        let mut provider = OpenAiCompatProvider::with_config(
+            "test",
+            crate::config::OpenAiCompatProviderConfig {
+                enabled: true,
+                api_key: "secret".to_string(),
+                base_url: "http://localhost/v1/".to_string(),
+                model_configs: Vec::new(),
+            },
+            usage::openai_compat(),
+            Services::null(),
+        );
+        provider.config.transport = transport;
+        provider
+    }
+
+    #[tokio::test]
+    async fn test_execute_chat_streamed_tool_call() {
+        let fixture = include_str!("testdata/openai_compat_tool_call.sse");
+        // Deliver the fixture in small chunks so events straddle chunk boundaries.
+        let chunks: Vec<&str> = fixture
+            .as_bytes()
+            .chunks(7)
+            .map(|c| std::str::from_utf8(c).unwrap())
+            .collect();
+        let transport = Arc::new(MockTransport::default());
+        transport.push_response(200, chunks);
+
+        let provider = mock_provider(transport.clone());
+        let response = provider
+            .chat(
+                vec![Message::user("list files")],
+                &crate::output::OutputContext::null(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].name, "bash");
+        assert_eq!(response.tool_calls[0].input["command"], "ls");
+        assert!(matches!(
+            &response.content_blocks[0],
+            ContentBlock::Text { text } if text == "Hello"
+        ));
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "http://localhost/v1/chat/completions");
+        assert_eq!(requests[0].headers[AUTHORIZATION], "Bearer secret");
+    }
+
+    #[tokio::test]
+    async fn test_execute_chat_error_status() {
+        let transport = Arc::new(MockTransport::default());
+        transport.push_response(400, vec!["bad request"]);
+
+        let provider = mock_provider(transport);
+        let err = provider
+            .chat(
+                vec![Message::user("hi")],
+                &crate::output::OutputContext::null(),
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Api { status: 400, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_execute_chat_invalid_api_key() {
+        let transport = Arc::new(MockTransport::default());
+        let mut provider = mock_provider(transport.clone());
+        provider.config.api_key = "secret\n".to_string();
+        let err = provider
+            .chat(
+                vec![Message::user("hi")],
+                &crate::output::OutputContext::null(),
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Config(_)));
+        assert!(transport.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_request_includes_seed() {
+        let mut provider = mock_provider(Arc::new(MockTransport::default()));
+        let request = provider
+            .prepare_request(vec![Message::user("hi")])
+            .await
+            .unwrap();
+        assert!(request.get("seed").is_none());
+
+        provider.set_seed(Some(42));
+        let request = provider
+            .prepare_request(vec![Message::user("hi")])
+            .await
+            .unwrap();
+        assert_eq!(request["seed"], 42);
+    }
 
     #[test]
     fn test_build_messages_simple_text() {
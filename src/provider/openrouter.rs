@@ -13,6 +13,8 @@ use crate::usage;
 pub(crate) struct OpenRouterProvider {
     config: OpenAiChatConfig,
     provider_config: crate::config::OpenRouterConfig,
+    /// Reasoning effort overriding the per-model config (e.g. from `--reasoning`)
+    reasoning_effort: Option<String>,
 }
 
 impl OpenRouterProvider {
@@ -61,18 +63,20 @@ impl OpenRouterProvider {
 
         let chat_config = OpenAiChatConfig {
             provider_name: provider_name.to_string(),
-            client: reqwest::Client::new(),
+            transport: crate::provider::transport::default_transport(provider_name),
             api_key: openrouter_config.api_key.clone(),
             base_url,
             model: "default".to_string(),
             usage_tracker: usage::openrouter(),
             custom_headers: Some(custom_headers),
             services,
+            seed: None,
         };
 
         Ok(Self {
             config: chat_config,
             provider_config,
+            reasoning_effort: None,
         })
     }
 
@@ -80,10 +84,18 @@ impl OpenRouterProvider {
         self.config.model = model;
     }
 
+    pub(crate) fn set_reasoning_effort(&mut self, effort: Option<String>) {
+        self.reasoning_effort = effort;
+    }
+
+    pub(crate) fn set_seed(&mut self, seed: Option<u64>) {
+        self.config.seed = seed;
+    }
+
     /// Get context limit for a given model name
     /// Returns None since OpenRouter hosts many different models with varying limits
-    pub(crate) fn context_limit(_model: &str) -> Option<u64> {
-        None
+    pub(crate) fn context_limit(model: &str) -> Option<u64> {
+        ConfigFile::load().ok()?.model_context_window(model)
     }
 }
 
@@ -93,12 +105,25 @@ impl Provider for OpenRouterProvider {
         messages: Vec<Message>,
         output: &crate::output::OutputContext,
     ) -> Result<ChatResponse> {
-        execute_chat(&self.config, &self.provider_config, &messages, output, None).await
+        execute_chat(
+            &self.config,
+            &self.provider_config,
+            &messages,
+            output,
+            self.reasoning_effort.as_deref(),
+        )
+        .await
     }
 
     async fn prepare_request(&self, messages: Vec<Message>) -> Result<serde_json::Value> {
         use crate::provider::openai_compat::build_request;
-        let request = build_request(&self.config, &self.provider_config, &messages, None).await?;
+        let request = build_request(
+            &self.config,
+            &self.provider_config,
+            &messages,
+            self.reasoning_effort.as_deref(),
+        )
+        .await?;
         Ok(serde_json::to_value(&request)?)
     }
 
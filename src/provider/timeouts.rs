@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Connection, read and stall timeouts for provider requests.
+//!
+//! The timeouts are configured per provider. The stall timeout is applied by
+//! the transport, see [`crate::provider::transport`].
+
+use std::time::Duration;
+
+use crate::config::{ConfigFile, TimeoutSettings};
+
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_STALL_TIMEOUT_SECS: u64 = 120;
+
+/// Resolved timeouts for one provider.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Timeouts {
+    pub connect: Duration,
+    pub read: Option<Duration>,
+    pub stall: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self::from_settings(TimeoutSettings::default())
+    }
+}
+
+impl Timeouts {
+    /// Timeouts configured for the provider with the given local id.
+    pub(crate) fn for_provider(provider_id: &str) -> Self {
+        ConfigFile::load()
+            .map(|config| Self::from_settings(config.timeouts.for_provider(provider_id)))
+            .unwrap_or_default()
+    }
+
+    fn from_settings(settings: TimeoutSettings) -> Self {
+        let seconds = |value: Option<u64>| value.filter(|&s| s > 0).map(Duration::from_secs);
+        Self {
+            connect: Duration::from_secs(settings.connect.unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS)),
+            read: seconds(settings.read),
+            stall: seconds(Some(settings.stall.unwrap_or(DEFAULT_STALL_TIMEOUT_SECS))),
+        }
+    }
+
+    /// Build an HTTP client with the connect and read timeouts applied.
+    pub(crate) fn client(&self) -> reqwest::Client {
+        let mut builder = reqwest::Client::builder().connect_timeout(self.connect);
+        if let Some(read) = self.read {
+            builder = builder.read_timeout(read);
+        }
+        builder.build().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_settings_defaults() {
+        let t = Timeouts::default();
+        assert_eq!(t.connect, Duration::from_secs(30));
+        assert_eq!(t.read, None);
+        assert_eq!(t.stall, Some(Duration::from_secs(120)));
+        let disabled = Timeouts::from_settings(TimeoutSettings {
+            stall: Some(0),
+            ..Default::default()
+        });
+        assert_eq!(disabled.stall, None);
+    }
+}
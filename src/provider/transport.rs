@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! HTTP transport used by providers.
+//!
+//! Providers send requests through the [`HttpTransport`] trait instead of a
+//! concrete `reqwest::Client`, so streaming parsers can be exercised in unit
+//! tests against captured SSE fixtures, and cross-cutting behavior such as
+//! timeouts or rate-limit handling has a single place to live.
+//!
+//! A streamed response that stops delivering data without closing the
+//! connection would otherwise wait forever. The reqwest transport ends such a
+//! body with [`Error::Stalled`] when no data arrives for the stall timeout, so
+//! the request is retried like any other transient failure.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream};
+use futures::{Stream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::error::{Error, Result};
+use crate::provider::timeouts::Timeouts;
+
+/// An outgoing POST request.
+#[derive(Debug, Clone)]
+pub(crate) struct HttpRequest {
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// A response whose body is consumed as a stream of chunks.
+pub(crate) struct HttpResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: BoxStream<'static, Result<Vec<u8>>>,
+}
+
+impl HttpResponse {
+    pub(crate) fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Collect the remaining body as (lossy) UTF-8 text.
+    pub(crate) async fn text(mut self) -> String {
+        let mut bytes = Vec::new();
+        while let Some(Ok(chunk)) = self.body.next().await {
+            bytes.extend_from_slice(&chunk);
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+/// Build request headers from the map that is also written to the
+/// transaction log.
+pub(crate) fn header_map(headers: &HashMap<String, String>) -> Result<HeaderMap> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| Error::Config(format!("Invalid header name: {}", name)))?;
+        let value = HeaderValue::from_str(value).map_err(|_| {
+            Error::Config(format!("The {} header contains invalid characters", name))
+        })?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+/// Sends provider HTTP requests.
+pub(crate) trait HttpTransport: Send + Sync {
+    fn post(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse>>;
+}
+
+/// Transport backed by a real `reqwest::Client`.
+pub(crate) struct ReqwestTransport {
+    client: reqwest::Client,
+    timeouts: Timeouts,
+}
+
+impl ReqwestTransport {
+    pub(crate) fn new(timeouts: Timeouts) -> Self {
+        Self {
+            client: timeouts.client(),
+            timeouts,
+        }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn post(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse>> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .post(&request.url)
+                .headers(request.headers)
+                .body(request.body)
+                .send()
+                .await?;
+
+            Ok(HttpResponse {
+                status: response.status().as_u16(),
+                headers: response.headers().clone(),
+                body: watch(
+                    &self.timeouts,
+                    response
+                        .bytes_stream()
+                        .map(|chunk| chunk.map(|b| b.to_vec())),
+                ),
+            })
+        })
+    }
+}
+
+/// Wrap a response body so a gap longer than the stall timeout ends it with
+/// [`Error::Stalled`]. Read timeouts from the client are reported the same
+/// way. The stream ends after the first error.
+fn watch<S, B, E>(timeouts: &Timeouts, body: S) -> BoxStream<'static, Result<B>>
+where
+    S: Stream<Item = std::result::Result<B, E>> + Send + 'static,
+    B: Send + 'static,
+    E: Into<Error> + 'static,
+{
+    let stall = timeouts.stall;
+    let read = timeouts.read;
+    stream::unfold(Some(body.boxed()), move |body| async move {
+        let mut body = body?;
+        let next = match stall {
+            Some(limit) => match tokio::time::timeout(limit, body.next()).await {
+                Ok(next) => next,
+                Err(_) => return Some((Err(Error::Stalled(limit.as_secs())), None)),
+            },
+            None => body.next().await,
+        };
+        match next? {
+            Ok(chunk) => Some((Ok(chunk), Some(body))),
+            Err(e) => {
+                let e = match e.into() {
+                    Error::Http(e) if e.is_timeout() => {
+                        Error::Stalled(read.or(stall).unwrap_or_default().as_secs())
+                    }
+                    e => e,
+                };
+                Some((Err(e), None))
+            }
+        }
+    })
+    .boxed()
+}
+
+/// The default transport for a provider, using its configured timeouts.
+pub(crate) fn default_transport(provider_id: &str) -> Arc<dyn HttpTransport> {
+    Arc::new(ReqwestTransport::new(Timeouts::for_provider(provider_id)))
+}
+
+/// Test transport that replays canned responses and records requests.
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    pub(crate) struct MockTransport {
+        responses: Mutex<VecDeque<(u16, Vec<Vec<u8>>)>>,
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    impl MockTransport {
+        /// Queue a response whose body is delivered as the given chunks.
+        pub(crate) fn push_response(&self, status: u16, chunks: Vec<&str>) {
+            self.responses.lock().unwrap().push_back((
+                status,
+                chunks.into_iter().map(|c| c.as_bytes().to_vec()).collect(),
+            ));
+        }
+
+        pub(crate) fn requests(&self) -> Vec<HttpRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    impl HttpTransport for MockTransport {
+        fn post(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse>> {
+            self.requests.lock().unwrap().push(request);
+            let next = self.responses.lock().unwrap().pop_front();
+            Box::pin(async move {
+                let (status, chunks) =
+                    next.ok_or_else(|| Error::Other("no mock response queued".into()))?;
+                Ok(HttpResponse {
+                    status,
+                    headers: HeaderMap::new(),
+                    body: futures::stream::iter(chunks.into_iter().map(Ok)).boxed(),
+                })
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn timeouts(stall: Duration) -> Timeouts {
+        Timeouts {
+            stall: Some(stall),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_stall() {
+        let body =
+            stream::iter(vec![Ok::<_, Error>(b"data: 1\n".to_vec())]).chain(stream::pending());
+        let mut watched = watch(&timeouts(Duration::from_millis(20)), body);
+
+        assert_eq!(watched.next().await.unwrap().unwrap(), b"data: 1\n");
+        let err = watched.next().await.unwrap().unwrap_err();
+        assert!(matches!(err, Error::Stalled(_)));
+        assert!(err.is_retryable());
+        assert!(watched.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_watch_passes_through_slow_chunks() {
+        let body = stream::iter(vec![b"a".to_vec(), b"b".to_vec()]).then(|chunk| async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok::<_, Error>(chunk)
+        });
+        let chunks: Vec<_> = watch(&timeouts(Duration::from_millis(100)), body)
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks.iter().all(|c| c.is_ok()));
+    }
+}
@@ -0,0 +1,200 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Vertex AI provider.
+//!
+//! Serves Anthropic's Claude models and Google's Gemini models through a Google
+//! Cloud project, billed to that project and authenticated with Application
+//! Default Credentials. Vertex AI speaks the same Anthropic and Gemini APIs as
+//! OpenCode Zen, so Zen's backends are reused with a different URL and an OAuth
+//! access token.
+
+use std::sync::Arc;
+
+use crate::auth::adc::TokenSource;
+use crate::config::{ConfigFile, VertexProviderConfig};
+use crate::error::{Error, Result};
+use crate::provider::transport::{self, HttpTransport};
+use crate::provider::zen::{ChatContext, Service, anthropic, gemini};
+use crate::provider::{ChatResponse, Message, Provider};
+use crate::services::Services;
+use crate::usage;
+
+/// Region used when none is configured.
+pub(crate) const DEFAULT_REGION: &str = "us-central1";
+
+/// Models offered in the model menu in addition to the configured ones.
+const VERTEX_MODELS: &[&str] = &[
+    "claude-haiku-4-5@20251001",
+    "claude-opus-4-5@20251101",
+    "claude-sonnet-4-5@20250929",
+    "gemini-2.5-flash",
+    "gemini-2.5-pro",
+    "gemini-3-pro-preview",
+];
+
+/// The publisher serving `model` in the Model Garden.
+fn publisher(model: &str) -> &'static str {
+    if model.starts_with("claude") {
+        "anthropic"
+    } else {
+        "google"
+    }
+}
+
+/// URL of the models of `publisher` in the configured project and region.
+fn models_url(config: &VertexProviderConfig, publisher: &str) -> String {
+    let host = if config.region == "global" {
+        "aiplatform.googleapis.com".to_string()
+    } else {
+        format!("{}-aiplatform.googleapis.com", config.region)
+    };
+    format!(
+        "https://{}/v1/projects/{}/locations/{}/publishers/{}/models",
+        host, config.project, config.region, publisher
+    )
+}
+
+pub(crate) struct VertexProvider {
+    provider_name: String,
+    transport: Arc<dyn HttpTransport>,
+    provider_config: VertexProviderConfig,
+    tokens: TokenSource,
+    model: String,
+    services: Services,
+}
+
+impl VertexProvider {
+    pub(crate) fn try_new(provider_name: &str, services: Services) -> Result<Self> {
+        let config = ConfigFile::load()?;
+        let vertex = config
+            .get_provider(provider_name)
+            .and_then(|p| p.as_vertex())
+            .ok_or_else(|| {
+                Error::Auth(format!(
+                    "Vertex AI provider '{}' not configured.",
+                    provider_name
+                ))
+            })?;
+
+        if !vertex.enabled {
+            return Err(Error::Auth(format!(
+                "Vertex AI provider '{}' is disabled.",
+                provider_name
+            )));
+        }
+
+        let tokens = TokenSource::load(vertex.credentials.as_deref())?;
+        Ok(Self {
+            provider_name: provider_name.to_string(),
+            transport: transport::default_transport("vertex"),
+            provider_config: vertex.clone(),
+            tokens,
+            model: String::new(),
+            services,
+        })
+    }
+
+    pub(crate) fn set_model(&mut self, model: String) {
+        self.model = model;
+    }
+
+    pub(crate) fn models() -> &'static [&'static str] {
+        VERTEX_MODELS
+    }
+
+    /// Get the context limit for a given model name
+    pub(crate) fn context_limit(model: &str) -> Option<u64> {
+        let configured = ConfigFile::load()
+            .ok()
+            .and_then(|config| config.model_context_window(model));
+        if configured.is_some() {
+            configured
+        } else if model.starts_with("claude-") {
+            Some(200_000)
+        } else if model.starts_with("gemini-") {
+            Some(1_000_000)
+        } else {
+            None
+        }
+    }
+}
+
+impl Provider for VertexProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Message>,
+        output: &crate::output::OutputContext,
+    ) -> Result<ChatResponse> {
+        let token = self.tokens.token().await?;
+        let publisher = publisher(&self.model);
+        let models_url = models_url(&self.provider_config, publisher);
+
+        let ctx = ChatContext {
+            transport: self.transport.as_ref(),
+            api_key: &token,
+            provider: &self.provider_name,
+            model: &self.model,
+            services: &self.services,
+            service: Service::Vertex {
+                models_url: &models_url,
+            },
+        };
+
+        match publisher {
+            "anthropic" => anthropic::chat(&ctx, messages, output).await,
+            _ => gemini::chat(&ctx, messages, output).await,
+        }
+    }
+
+    async fn prepare_request(&self, messages: Vec<Message>) -> Result<serde_json::Value> {
+        match publisher(&self.model) {
+            "anthropic" => {
+                let request = anthropic::build_request(
+                    &self.provider_name,
+                    &self.model,
+                    &messages,
+                    &self.services,
+                )
+                .await
+                .for_vertex();
+                anthropic::prepare_request_value(&request)
+            }
+            _ => {
+                let request =
+                    gemini::build_request(&self.model, &messages, None, &self.services).await;
+                gemini::prepare_request_value(&request)
+            }
+        }
+    }
+
+    fn start_turn(&self) {
+        usage::vertex().start_turn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_models_url() {
+        let mut config = VertexProviderConfig {
+            enabled: true,
+            project: "acme".to_string(),
+            region: DEFAULT_REGION.to_string(),
+            credentials: None,
+            model_configs: Vec::new(),
+        };
+        assert_eq!(
+            models_url(&config, publisher("claude-sonnet-4-5@20250929")),
+            "https://us-central1-aiplatform.googleapis.com/v1/projects/acme/locations/us-central1/publishers/anthropic/models"
+        );
+
+        config.region = "global".to_string();
+        assert_eq!(
+            models_url(&config, publisher("gemini-2.5-pro")),
+            "https://aiplatform.googleapis.com/v1/projects/acme/locations/global/publishers/google/models"
+        );
+    }
+}
@@ -2,24 +2,33 @@
 // Copyright (c) 2025 Jason Ish
 
 use base64::Engine;
+use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, Result};
 use crate::output;
 use crate::prompts;
+use crate::provider::transport::HttpRequest;
 use crate::provider::{
     ChatResponse, ContentBlock, Message, MessageContent, Role, StopReason, ToolCall,
 };
 use crate::services::Services;
 use crate::sse;
 use crate::tools;
-use crate::usage;
 
-use super::{ChatContext, ZEN_BASE_URL};
+use super::{ChatContext, Service, ZEN_BASE_URL};
+
+/// Version of the Messages API requested from Vertex AI, which takes it in the
+/// body instead of a header.
+const VERTEX_ANTHROPIC_VERSION: &str = "vertex-2023-10-16";
 
 #[derive(Serialize)]
-pub(super) struct AnthropicRequest {
-    model: String,
+pub(crate) struct AnthropicRequest {
+    /// Vertex AI takes the model from the URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    anthropic_version: Option<&'static str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     system: Option<String>,
     messages: Vec<serde_json::Value>,
@@ -104,7 +113,7 @@ enum PendingBlock {
     ToolUse(PendingToolUse),
 }
 
-pub(super) fn build_messages(messages: &[Message]) -> Vec<serde_json::Value> {
+pub(crate) fn build_messages(messages: &[Message]) -> Vec<serde_json::Value> {
     messages
         .iter()
         .filter(|m| !matches!(m.role, Role::System))
@@ -219,7 +228,8 @@ pub(super) fn build_messages(messages: &[Message]) -> Vec<serde_json::Value> {
         .collect()
 }
 
-pub(super) async fn build_request(
+pub(crate) async fn build_request(
+    provider: &str,
     model: &str,
     messages: &[Message],
     services: &Services,
@@ -230,15 +240,22 @@ pub(super) async fn build_request(
         .map(|t| AnthropicTool {
             name: t.name,
             description: t.description,
-            input_schema: t.input_schema,
+            input_schema: tools::schema::adapt(&t.input_schema, tools::schema::Dialect::Anthropic),
         })
         .collect();
 
     let thinking = None;
 
     AnthropicRequest {
-        model: model.to_string(),
-        system: Some(prompts::system_prompt_with_services(Some(services)).join("\n\n")),
+        model: Some(model.to_string()),
+        anthropic_version: None,
+        system: Some(
+            prompts::system_prompt_with_services(
+                Some(services),
+                &format!("{}/{}", provider, model),
+            )
+            .join("\n\n"),
+        ),
         messages: build_messages(messages),
         max_tokens: 16000,
         stream: true,
@@ -247,41 +264,68 @@ pub(super) async fn build_request(
     }
 }
 
-pub(super) async fn chat(
+pub(crate) async fn chat(
     ctx: &ChatContext<'_>,
     messages: Vec<Message>,
     output: &crate::output::OutputContext,
 ) -> Result<ChatResponse> {
-    let url = format!("{}/messages", ZEN_BASE_URL);
-    let request = build_request(ctx.model, &messages, ctx.services).await;
-
-    let mut req_headers = std::collections::HashMap::new();
-    req_headers.insert("x-api-key".to_string(), ctx.api_key.to_string());
-    req_headers.insert("Content-Type".to_string(), "application/json".to_string());
-    req_headers.insert("anthropic-version".to_string(), "2023-06-01".to_string());
+    let mut request = build_request(ctx.provider, ctx.model, &messages, ctx.services).await;
+
+    let mut headers = HeaderMap::new();
+    let url = match ctx.service {
+        Service::Zen => {
+            headers.insert(
+                "x-api-key",
+                ctx.api_key.parse().expect("API key should be valid header"),
+            );
+            headers.insert(
+                "anthropic-version",
+                "2023-06-01".parse().expect("static header value"),
+            );
+            format!("{}/messages", ZEN_BASE_URL)
+        }
+        Service::Vertex { models_url } => {
+            request = request.for_vertex();
+            headers.insert(
+                "authorization",
+                format!("Bearer {}", ctx.api_key)
+                    .parse()
+                    .expect("access token should be valid header"),
+            );
+            format!("{}/{}:streamRawPredict", models_url, ctx.model)
+        }
+    };
+    headers.insert(
+        "content-type",
+        "application/json".parse().expect("static header value"),
+    );
+    let req_headers = crate::provider::transaction_log::header_map_to_hash_map(&headers);
 
     let response = ctx
-        .client
-        .post(&url)
-        .header("x-api-key", ctx.api_key)
-        .header("Content-Type", "application/json")
-        .header("anthropic-version", "2023-06-01")
-        .json(&request)
-        .send()
+        .transport
+        .post(HttpRequest {
+            url: url.clone(),
+            headers,
+            body: serde_json::to_vec(&request)?,
+        })
         .await
         .map_err(|e| {
             Error::Other(format!(
-                "Failed to connect to Zen (Anthropic API - {}) ({}): {}",
-                ctx.model, url, e
+                "Failed to connect to {} (Anthropic API - {}) ({}): {}",
+                ctx.service.name(),
+                ctx.model,
+                url,
+                e
             ))
         })?;
 
-    let resp_headers = crate::provider::transaction_log::header_map_to_hash_map(response.headers());
+    let resp_headers = crate::provider::transaction_log::header_map_to_hash_map(&response.headers);
 
-    if !response.status().is_success() {
-        let status = response.status().as_u16();
-        let message = response.text().await.unwrap_or_default();
+    if !response.is_success() {
+        let status = response.status;
+        let message = response.text().await;
 
+        let retry_after = crate::provider::retry_after(&resp_headers);
         crate::provider::transaction_log::log(
             &url,
             req_headers,
@@ -294,7 +338,7 @@ pub(super) async fn chat(
             }),
         );
 
-        return Err(crate::provider::api_error(status, message));
+        return Err(crate::provider::api_error(status, message).with_retry_after(retry_after));
     }
 
     let mut full_text = String::new();
@@ -304,9 +348,9 @@ pub(super) async fn chat(
     let mut pending_block: Option<PendingBlock> = None;
     let mut thinking = output::ThinkingState::new(output);
 
-    let mut sse = sse::SseStream::new(response.bytes_stream());
+    let mut sse = sse::SseStream::new(response.body);
     while let Some(result) = sse.next_event().await {
-        let data = result.map_err(Error::Http)?;
+        let data = result?;
 
         let Ok(event) = serde_json::from_str::<AnthropicEvent>(&data) else {
             continue;
@@ -318,13 +362,13 @@ pub(super) async fn chat(
                     && let Some(u) = &msg.usage
                 {
                     if let Some(input) = u.input_tokens {
-                        usage::zen().record_input(input);
+                        ctx.service.usage().record_input(input);
                     }
                     if let Some(cache_create) = u.cache_creation_input_tokens {
-                        usage::zen().add_cache_creation(cache_create);
+                        ctx.service.usage().add_cache_creation(cache_create);
                     }
                     if let Some(cache_read) = u.cache_read_input_tokens {
-                        usage::zen().add_cache_read(cache_read);
+                        ctx.service.usage().add_cache_read(cache_read);
                     }
                 }
             }
@@ -419,7 +463,7 @@ pub(super) async fn chat(
                 if let Some(u) = &event.usage
                     && let Some(output_tokens) = u.output_tokens
                 {
-                    usage::zen().record_output(output_tokens);
+                    ctx.service.usage().record_output(output_tokens);
                 }
             }
             _ => {}
@@ -467,6 +511,15 @@ pub(super) async fn chat(
     })
 }
 
-pub(super) fn prepare_request_value(request: &AnthropicRequest) -> Result<serde_json::Value> {
+impl AnthropicRequest {
+    /// Adapt the request for Vertex AI's `streamRawPredict`.
+    pub(crate) fn for_vertex(mut self) -> Self {
+        self.model = None;
+        self.anthropic_version = Some(VERTEX_ANTHROPIC_VERSION);
+        self
+    }
+}
+
+pub(crate) fn prepare_request_value(request: &AnthropicRequest) -> Result<serde_json::Value> {
     Ok(serde_json::to_value(request)?)
 }
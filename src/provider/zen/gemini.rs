@@ -10,19 +10,19 @@ use serde::{Deserialize, Serialize};
 use crate::error::{Error, Result};
 use crate::output;
 use crate::prompts;
+use crate::provider::transport::HttpRequest;
 use crate::provider::{
     ChatResponse, ContentBlock, Message, MessageContent, Role, StopReason, ToolCall,
 };
 use crate::services::Services;
 use crate::sse;
 use crate::tools;
-use crate::usage;
 
-use super::{ChatContext, ZEN_BASE_URL, strip_unsupported_schema_fields};
+use super::{ChatContext, Service, ZEN_BASE_URL};
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-pub(super) struct GeminiRequest {
+pub(crate) struct GeminiRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     system_instruction: Option<GeminiSystemInstruction>,
     contents: Vec<GeminiContent>,
@@ -236,7 +236,7 @@ fn build_contents(messages: &[Message]) -> Vec<GeminiContent> {
         .collect()
 }
 
-pub(super) async fn build_request(
+pub(crate) async fn build_request(
     _model: &str,
     messages: &[Message],
     _thinking_mode: Option<&str>,
@@ -249,7 +249,7 @@ pub(super) async fn build_request(
             .map(|t| GeminiFunctionDeclaration {
                 name: t.name,
                 description: t.description,
-                parameters: strip_unsupported_schema_fields(t.input_schema),
+                parameters: tools::schema::adapt(&t.input_schema, tools::schema::Dialect::Gemini),
             })
             .collect(),
     }];
@@ -289,51 +289,66 @@ pub(super) async fn build_request(
     }
 }
 
-pub(super) async fn chat(
+pub(crate) async fn chat(
     ctx: &ChatContext<'_>,
     messages: Vec<Message>,
     output: &crate::output::OutputContext,
 ) -> Result<ChatResponse> {
-    let url = format!(
-        "{}/models/{}:streamGenerateContent?alt=sse",
-        ZEN_BASE_URL, ctx.model
-    );
     let request = build_request(ctx.model, &messages, None, ctx.services).await;
 
     let mut headers = HeaderMap::new();
-    headers.insert(
-        "x-goog-api-key",
-        ctx.api_key.parse().expect("API key should be valid header"),
-    );
+    let url = match ctx.service {
+        Service::Zen => {
+            headers.insert(
+                "x-goog-api-key",
+                ctx.api_key.parse().expect("API key should be valid header"),
+            );
+            format!(
+                "{}/models/{}:streamGenerateContent?alt=sse",
+                ZEN_BASE_URL, ctx.model
+            )
+        }
+        Service::Vertex { models_url } => {
+            headers.insert(
+                "authorization",
+                format!("Bearer {}", ctx.api_key)
+                    .parse()
+                    .expect("access token should be valid header"),
+            );
+            format!("{}/{}:streamGenerateContent?alt=sse", models_url, ctx.model)
+        }
+    };
     headers.insert(
         "content-type",
         "application/json".parse().expect("static header value"),
     );
-
-    let mut req_headers = std::collections::HashMap::new();
-    req_headers.insert("x-goog-api-key".to_string(), ctx.api_key.to_string());
-    req_headers.insert("Content-Type".to_string(), "application/json".to_string());
+    let req_headers = crate::provider::transaction_log::header_map_to_hash_map(&headers);
 
     let response = ctx
-        .client
-        .post(&url)
-        .headers(headers)
-        .json(&request)
-        .send()
+        .transport
+        .post(HttpRequest {
+            url: url.clone(),
+            headers,
+            body: serde_json::to_vec(&request)?,
+        })
         .await
         .map_err(|e| {
             Error::Other(format!(
-                "Failed to connect to Zen (Gemini API - {}) ({}): {}",
-                ctx.model, url, e
+                "Failed to connect to {} (Gemini API - {}) ({}): {}",
+                ctx.service.name(),
+                ctx.model,
+                url,
+                e
             ))
         })?;
 
-    let resp_headers = crate::provider::transaction_log::header_map_to_hash_map(response.headers());
+    let resp_headers = crate::provider::transaction_log::header_map_to_hash_map(&response.headers);
 
-    if !response.status().is_success() {
-        let status = response.status().as_u16();
-        let message = response.text().await.unwrap_or_default();
+    if !response.is_success() {
+        let status = response.status;
+        let message = response.text().await;
 
+        let retry_after = crate::provider::retry_after(&resp_headers);
         crate::provider::transaction_log::log(
             &url,
             req_headers,
@@ -346,7 +361,7 @@ pub(super) async fn chat(
             }),
         );
 
-        return Err(crate::provider::api_error(status, message));
+        return Err(crate::provider::api_error(status, message).with_retry_after(retry_after));
     }
 
     let mut full_text = String::new();
@@ -356,9 +371,9 @@ pub(super) async fn chat(
     let mut thinking = output::ThinkingState::new(output);
     let mut streaming_start: Option<Instant> = None;
 
-    let mut sse = sse::SseStream::new(response.bytes_stream());
+    let mut sse = sse::SseStream::new(response.body);
     while let Some(result) = sse.next_event().await {
-        let data = result.map_err(Error::Http)?;
+        let data = result?;
 
         let Ok(resp) = serde_json::from_str::<GeminiResponse>(&data) else {
             continue;
@@ -416,15 +431,15 @@ pub(super) async fn chat(
         // Handle usage metadata
         if let Some(u) = &resp.usage_metadata {
             if let Some(prompt_tokens) = u.prompt_token_count {
-                usage::zen().record_input(prompt_tokens);
+                ctx.service.usage().record_input(prompt_tokens);
             }
             if let Some(completion_tokens) = u.candidates_token_count {
-                usage::zen().record_output(completion_tokens);
+                ctx.service.usage().record_output(completion_tokens);
 
                 if let Some(start) = streaming_start {
                     let duration = start.elapsed().as_secs_f64();
                     if duration > 0.0 {
-                        let turn_total = usage::zen().turn_total();
+                        let turn_total = ctx.service.usage().turn_total();
                         let tokens_per_sec = completion_tokens as f64 / duration;
                         output::emit_working_progress(output, turn_total, duration, tokens_per_sec);
                     }
@@ -458,6 +473,6 @@ pub(super) async fn chat(
     })
 }
 
-pub(super) fn prepare_request_value(request: &GeminiRequest) -> Result<serde_json::Value> {
+pub(crate) fn prepare_request_value(request: &GeminiRequest) -> Result<serde_json::Value> {
     Ok(serde_json::to_value(request)?)
 }
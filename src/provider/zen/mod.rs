@@ -1,17 +1,16 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Jason Ish
 
-mod anthropic;
-mod gemini;
+pub(crate) mod anthropic;
+pub(crate) mod gemini;
 mod responses;
 
-use std::sync::LazyLock;
-
-use reqwest::Client;
+use std::sync::{Arc, LazyLock};
 
 use crate::config::Config;
 use crate::error::{Error, Result};
 use crate::provider::openai_compat::OpenAiCompatProvider;
+use crate::provider::transport::{self, HttpTransport};
 use crate::provider::{ChatResponse, Message, Provider};
 use crate::services::Services;
 use crate::usage;
@@ -19,11 +18,42 @@ use crate::usage;
 const ZEN_BASE_URL: &str = "https://opencode.ai/zen/v1";
 
 /// Common context for chat operations across API backends
-pub(super) struct ChatContext<'a> {
-    pub client: &'a Client,
+pub(crate) struct ChatContext<'a> {
+    pub transport: &'a dyn HttpTransport,
+    /// API key, or the OAuth access token for Vertex AI
     pub api_key: &'a str,
+    /// Name of the provider in model specs, such as `zen`
+    pub provider: &'a str,
     pub model: &'a str,
     pub services: &'a Services,
+    pub service: Service<'a>,
+}
+
+/// The service the Anthropic and Gemini backends talk to. Vertex AI serves the
+/// same APIs as Zen behind a different URL and authentication.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Service<'a> {
+    Zen,
+    /// Vertex AI, with the URL of the publisher's models
+    Vertex {
+        models_url: &'a str,
+    },
+}
+
+impl Service<'_> {
+    fn name(&self) -> &'static str {
+        match self {
+            Service::Zen => "Zen",
+            Service::Vertex { .. } => "Vertex AI",
+        }
+    }
+
+    fn usage(&self) -> &'static usage::Usage {
+        match self {
+            Service::Zen => usage::zen(),
+            Service::Vertex { .. } => usage::vertex(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -74,28 +104,8 @@ fn get_model_spec(model: &str) -> Option<&'static ZenModelSpec> {
 static ZEN_MODEL_NAMES: LazyLock<Vec<&'static str>> =
     LazyLock::new(|| ZEN_MODELS.iter().map(|m| m.name).collect());
 
-fn strip_unsupported_schema_fields(schema: serde_json::Value) -> serde_json::Value {
-    match schema {
-        serde_json::Value::Object(mut map) => {
-            map.remove("$schema");
-            map.remove("additionalProperties");
-            let cleaned: serde_json::Map<String, serde_json::Value> = map
-                .into_iter()
-                .map(|(k, v)| (k, strip_unsupported_schema_fields(v)))
-                .collect();
-            serde_json::Value::Object(cleaned)
-        }
-        serde_json::Value::Array(arr) => serde_json::Value::Array(
-            arr.into_iter()
-                .map(strip_unsupported_schema_fields)
-                .collect(),
-        ),
-        other => other,
-    }
-}
-
 pub(crate) struct ZenProvider {
-    client: Client,
+    transport: Arc<dyn HttpTransport>,
     api_key: String,
     model: String,
     openai_compat_delegate: Option<OpenAiCompatProvider>,
@@ -107,7 +117,7 @@ impl ZenProvider {
         let openai_compat_delegate = Self::create_openai_compat_delegate(config, services.clone());
 
         Self {
-            client: Client::new(),
+            transport: transport::default_transport("zen"),
             api_key: config.api_key.clone(),
             model: config.model.clone(),
             openai_compat_delegate,
@@ -135,6 +145,7 @@ impl ZenProvider {
                 temperature: None,
                 top_p: None,
                 max_tokens: None,
+                context_window: None,
             });
 
         provider_config
@@ -146,6 +157,7 @@ impl ZenProvider {
                 temperature: None,
                 top_p: None,
                 max_tokens: None,
+                context_window: None,
             });
 
         provider_config
@@ -157,6 +169,7 @@ impl ZenProvider {
                 temperature: None,
                 top_p: None,
                 max_tokens: None,
+                context_window: None,
             });
 
         provider_config
@@ -168,6 +181,7 @@ impl ZenProvider {
                 temperature: Some(1.0),
                 top_p: Some(0.95),
                 max_tokens: Some(32000),
+                context_window: None,
             });
 
         provider_config
@@ -179,6 +193,7 @@ impl ZenProvider {
                 temperature: Some(1.0),
                 top_p: Some(0.95),
                 max_tokens: Some(32000),
+                context_window: None,
             });
 
         Some(OpenAiCompatProvider::with_config(
@@ -249,10 +264,12 @@ impl Provider for ZenProvider {
             .unwrap_or(ApiType::OpenAiCompatible);
 
         let ctx = ChatContext {
-            client: &self.client,
+            transport: self.transport.as_ref(),
             api_key: &self.api_key,
+            provider: "zen",
             model: &self.model,
             services: &self.services,
+            service: Service::Zen,
         };
 
         match api_type {
@@ -283,7 +300,7 @@ impl Provider for ZenProvider {
             }
             ApiType::Anthropic => {
                 let request =
-                    anthropic::build_request(&self.model, &messages, &self.services).await;
+                    anthropic::build_request("zen", &self.model, &messages, &self.services).await;
                 anthropic::prepare_request_value(&request)
             }
             ApiType::Gemini => {
@@ -9,6 +9,7 @@ use serde::Serialize;
 use crate::error::{Error, Result};
 use crate::output;
 
+use crate::provider::transport::{self, HttpRequest};
 use crate::provider::{
     ChatResponse, ContentBlock, Message, MessageContent, Role, StopReason, ToolCall,
 };
@@ -16,7 +17,7 @@ use crate::services::Services;
 use crate::tools;
 use crate::usage;
 
-use super::{ChatContext, ZEN_BASE_URL, strip_unsupported_schema_fields};
+use super::{ChatContext, ZEN_BASE_URL};
 
 #[derive(Serialize)]
 pub(super) struct OpenAiResponsesRequest {
@@ -168,13 +169,11 @@ fn build_input(messages: &[Message]) -> Vec<serde_json::Value> {
                 if let MessageContent::Blocks(blocks) = &m.content {
                     for block in blocks {
                         match block {
-                            ContentBlock::Text { text } => {
-                                if !text.is_empty() {
-                                    input.push(serde_json::json!({
-                                        "role": "assistant",
-                                        "content": [{"type": "output_text", "text": text}]
-                                    }));
-                                }
+                            ContentBlock::Text { text } if !text.is_empty() => {
+                                input.push(serde_json::json!({
+                                    "role": "assistant",
+                                    "content": [{"type": "output_text", "text": text}]
+                                }));
                             }
                             ContentBlock::ToolUse {
                                 id,
@@ -213,7 +212,7 @@ pub(super) async fn build_request(
             tool_type: "function".to_string(),
             name: t.name,
             description: t.description,
-            parameters: strip_unsupported_schema_fields(t.input_schema),
+            parameters: tools::schema::adapt(&t.input_schema, tools::schema::Dialect::Compat),
         })
         .collect();
 
@@ -248,12 +247,12 @@ pub(super) async fn chat(
     req_headers.insert("Content-Type".to_string(), "application/json".to_string());
 
     let response = ctx
-        .client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", ctx.api_key))
-        .header("Content-Type", "application/json")
-        .json(&request)
-        .send()
+        .transport
+        .post(HttpRequest {
+            url: url.clone(),
+            headers: transport::header_map(&req_headers)?,
+            body: serde_json::to_vec(&request)?,
+        })
         .await
         .map_err(|e| {
             Error::Other(format!(
@@ -262,12 +261,13 @@ pub(super) async fn chat(
             ))
         })?;
 
-    let resp_headers = crate::provider::transaction_log::header_map_to_hash_map(response.headers());
+    let resp_headers = crate::provider::transaction_log::header_map_to_hash_map(&response.headers);
 
-    if !response.status().is_success() {
-        let status = response.status().as_u16();
-        let message = response.text().await.unwrap_or_default();
+    if !response.is_success() {
+        let status = response.status;
+        let message = response.text().await;
 
+        let retry_after = crate::provider::retry_after(&resp_headers);
         crate::provider::transaction_log::log(
             &url,
             req_headers,
@@ -280,7 +280,7 @@ pub(super) async fn chat(
             }),
         );
 
-        return Err(crate::provider::api_error(status, message));
+        return Err(crate::provider::api_error(status, message).with_retry_after(retry_after));
     }
 
     let mut tool_calls = Vec::new();
@@ -292,9 +292,9 @@ pub(super) async fn chat(
     let mut streaming_start: Option<Instant> = None;
 
     // Use custom SSE parser for event: + data: format
-    let mut sse = ResponsesSseStream::new(response.bytes_stream());
+    let mut sse = ResponsesSseStream::new(response.body);
     while let Some(result) = sse.next_event().await {
-        let (event_type, data) = result.map_err(Error::Http)?;
+        let (event_type, data) = result?;
 
         match event_type.as_str() {
             // Handle reasoning summary events (various formats)
@@ -0,0 +1,302 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! `henri provider test`: a short suite of requests that checks what a
+//! provider and model actually support.
+//!
+//! Useful when wiring up an OpenAI-compatible endpoint of unknown quality.
+//! Each check sends a fresh conversation and the results are printed as a
+//! pass/fail table.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use colored::Colorize;
+
+use crate::config::Config;
+use crate::output::{OutputContext, OutputEvent, OutputListener};
+use crate::provider::{ContentBlock, Message, MessageContent, Role};
+use crate::providers::ProviderManager;
+use crate::services::Services;
+
+/// Time allowed for each check, including any tool calls.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Numbers requested by the long output check.
+const LONG_OUTPUT_COUNT: u32 = 1000;
+
+enum Outcome {
+    Pass(String),
+    Fail(String),
+    Skip(String),
+}
+
+/// Records what a check's request streamed back.
+#[derive(Default)]
+struct Recorder {
+    text_chunks: AtomicUsize,
+    tool_results: Mutex<Vec<(String, bool)>>,
+}
+
+impl OutputListener for Recorder {
+    fn on_event(&self, event: &OutputEvent) {
+        match event {
+            OutputEvent::Text(_) => {
+                self.text_chunks.fetch_add(1, Ordering::Relaxed);
+            }
+            OutputEvent::ToolResult {
+                tool_name,
+                is_error,
+                ..
+            } => {
+                if let Ok(mut results) = self.tool_results.lock() {
+                    results.push((tool_name.clone(), *is_error));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+struct Tester {
+    provider_manager: ProviderManager,
+}
+
+impl Tester {
+    /// Send `message` as a new conversation. Returns the final response text
+    /// and what was streamed.
+    async fn ask(&mut self, message: Message) -> Result<(String, Arc<Recorder>), String> {
+        let recorder = Arc::new(Recorder::default());
+        let output = OutputContext::new_cli(recorder.clone());
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let mut messages = vec![message];
+        let chat = self
+            .provider_manager
+            .chat(&mut messages, &interrupted, &output);
+        match tokio::time::timeout(CHECK_TIMEOUT, chat).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return Err(e.to_string()),
+            Err(_) => return Err(format!("no answer within {}s", CHECK_TIMEOUT.as_secs())),
+        }
+        let answer = crate::tools::task::final_answer(&messages).unwrap_or_default();
+        Ok((answer, recorder))
+    }
+
+    async fn streaming(&mut self) -> Outcome {
+        let prompt = "Count from 1 to 20, one number per line, with nothing else.";
+        let (answer, recorder) = match self.ask(Message::user(prompt)).await {
+            Ok(result) => result,
+            Err(e) => return Outcome::Fail(e),
+        };
+        let chunks = recorder.text_chunks.load(Ordering::Relaxed);
+        if !answer.contains("20") {
+            Outcome::Fail("incomplete response".to_string())
+        } else if chunks < 2 {
+            Outcome::Fail("response arrived in one piece".to_string())
+        } else {
+            Outcome::Pass(format!("{} chunks", chunks))
+        }
+    }
+
+    async fn tool_call(&mut self) -> Outcome {
+        let code = uuid::Uuid::new_v4().simple().to_string();
+        let file = match tempfile::NamedTempFile::new()
+            .and_then(|file| std::fs::write(file.path(), &code).map(|_| file))
+        {
+            Ok(file) => file,
+            Err(e) => return Outcome::Fail(format!("failed to create a file: {}", e)),
+        };
+        let prompt = format!(
+            "Use the file_read tool to read {}, then reply with the code it contains and nothing else.",
+            file.path().display()
+        );
+        let (answer, recorder) = match self.ask(Message::user(prompt)).await {
+            Ok(result) => result,
+            Err(e) => return Outcome::Fail(e),
+        };
+        let results = recorder
+            .tool_results
+            .lock()
+            .map(|results| results.clone())
+            .unwrap_or_default();
+        if results.is_empty() {
+            Outcome::Fail("no tool was called".to_string())
+        } else if !results.iter().any(|(name, _)| name == "file_read") {
+            Outcome::Fail(format!("called {} instead of file_read", results[0].0))
+        } else if results.iter().all(|(_, is_error)| *is_error) {
+            Outcome::Fail("file_read failed".to_string())
+        } else if !answer.contains(&code) {
+            Outcome::Fail("tool result not used in the answer".to_string())
+        } else {
+            Outcome::Pass(format!("{} tool call(s)", results.len()))
+        }
+    }
+
+    async fn image_input(&mut self, enabled: bool) -> Outcome {
+        if !enabled {
+            return Outcome::Skip("use --images to test".to_string());
+        }
+        let data = match red_png() {
+            Some(Ok(data)) => data,
+            Some(Err(e)) => return Outcome::Fail(format!("failed to encode the image: {}", e)),
+            None => return Outcome::Skip("built without image support".to_string()),
+        };
+        let message = Message {
+            role: Role::User,
+            content: MessageContent::Blocks(vec![
+                ContentBlock::Text {
+                    text: "What color is this image? Answer with one word.".to_string(),
+                },
+                ContentBlock::Image {
+                    mime_type: "image/png".to_string(),
+                    data,
+                },
+            ]),
+        };
+        match self.ask(message).await {
+            Ok((answer, _)) if answer.to_lowercase().contains("red") => {
+                Outcome::Pass("red".to_string())
+            }
+            Ok((answer, _)) => Outcome::Fail(format!("answered {:?}", truncate(&answer))),
+            Err(e) => Outcome::Fail(e),
+        }
+    }
+
+    async fn long_output(&mut self) -> Outcome {
+        let prompt = format!(
+            "Write the integers from 1 to {} in order, separated by single spaces, with nothing else.",
+            LONG_OUTPUT_COUNT
+        );
+        let answer = match self.ask(Message::user(prompt)).await {
+            Ok((answer, _)) => answer,
+            Err(e) => return Outcome::Fail(e),
+        };
+        let numbers: Vec<u32> = answer
+            .split_whitespace()
+            .filter_map(|word| {
+                word.trim_matches(|c: char| !c.is_ascii_digit())
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        match numbers.last() {
+            Some(&last) if last == LONG_OUTPUT_COUNT => {
+                Outcome::Pass(format!("{} numbers", numbers.len()))
+            }
+            Some(&last) => Outcome::Fail(format!("stopped at {}", last)),
+            None => Outcome::Fail("no numbers in the response".to_string()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Counts {
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+}
+
+impl Counts {
+    /// Print one row of the results table.
+    fn report(&mut self, label: &str, outcome: Outcome) {
+        let (status, detail) = match outcome {
+            Outcome::Pass(detail) => {
+                self.passed += 1;
+                ("pass".green(), detail)
+            }
+            Outcome::Fail(detail) => {
+                self.failed += 1;
+                ("fail".red(), detail)
+            }
+            Outcome::Skip(detail) => {
+                self.skipped += 1;
+                ("skip".dimmed(), detail)
+            }
+        };
+        println!("  {:<16}{}  {}", label, status, detail.dimmed());
+    }
+}
+
+/// A solid red PNG for the image check, or `None` without the `images` feature.
+fn red_png() -> Option<Result<Vec<u8>, String>> {
+    #[cfg(feature = "images")]
+    {
+        let image = image::RgbImage::from_pixel(64, 64, image::Rgb([220, 20, 20]));
+        let mut data = std::io::Cursor::new(Vec::new());
+        Some(
+            image
+                .write_to(&mut data, image::ImageFormat::Png)
+                .map(|_| data.into_inner())
+                .map_err(|e| e.to_string()),
+        )
+    }
+    #[cfg(not(feature = "images"))]
+    None
+}
+
+fn truncate(text: &str) -> String {
+    let first_line = text.lines().next().unwrap_or_default();
+    match first_line.char_indices().nth(40) {
+        Some((idx, _)) => format!("{}...", &first_line[..idx]),
+        None => first_line.to_string(),
+    }
+}
+
+/// Run the suite against `model` (`provider/model-id`, or the default model)
+/// and print the results. Fails if any check failed.
+pub(crate) async fn run(model: Option<String>, images: bool) -> std::io::Result<()> {
+    let config = Config::load(model).map_err(|e| std::io::Error::other(e.to_string()))?;
+    let services = Services::new();
+    // Nothing in the suite needs to write
+    services.set_read_only(true);
+
+    let mut provider_manager = ProviderManager::new(&config, services);
+    let thinking = provider_manager.default_thinking();
+    provider_manager.set_thinking_enabled(thinking.enabled);
+    provider_manager.set_thinking_mode(thinking.mode);
+    let name = provider_manager.current_model_spec();
+    println!("Testing {}\n", name.bold());
+
+    let mut tester = Tester { provider_manager };
+    let mut counts = Counts::default();
+    counts.report("streaming", tester.streaming().await);
+    counts.report("tool call", tester.tool_call().await);
+    counts.report("image input", tester.image_input(images).await);
+    counts.report("long output", tester.long_output().await);
+    counts.report(
+        "stop sequences",
+        Outcome::Skip("not sent by henri".to_string()),
+    );
+
+    println!(
+        "\n{} passed, {} failed, {} skipped",
+        counts.passed, counts.failed, counts.skipped
+    );
+    if counts.failed > 0 {
+        return Err(std::io::Error::other(format!(
+            "{} check(s) failed for {}",
+            counts.failed, name
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder() {
+        let recorder = Arc::new(Recorder::default());
+        let output = OutputContext::new_cli(recorder.clone());
+        output.emit(OutputEvent::Text("1\n".to_string()));
+        output.emit(OutputEvent::Text("2\n".to_string()));
+        crate::output::print_tool_result(&output, "file_read", false, None, None, None);
+        assert_eq!(recorder.text_chunks.load(Ordering::Relaxed), 2);
+        assert_eq!(
+            *recorder.tool_results.lock().unwrap(),
+            vec![("file_read".to_string(), false)]
+        );
+    }
+}
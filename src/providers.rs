@@ -4,22 +4,27 @@
 // Provider management for multiple AI backends.
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
 use crate::compaction;
-use crate::config::{Config, ConfigFile, ProviderType};
+use crate::config::{Config, ConfigFile, CostConfig, CostLimitAction, ProviderType};
 use crate::error::Result;
 use crate::output::OutputContext;
 use crate::provider::anthropic::AnthropicProvider;
 use crate::provider::antigravity::AntigravityProvider;
 use crate::provider::copilot::CopilotProvider;
+use crate::provider::mistral::MistralProvider;
+use crate::provider::ollama::OllamaProvider;
 use crate::provider::openai::OpenAiProvider;
 use crate::provider::openai_compat::OpenAiCompatProvider;
 use crate::provider::openrouter::OpenRouterProvider;
+use crate::provider::vertex::VertexProvider;
 use crate::provider::zen::ZenProvider;
 use crate::provider::{ContentBlock, Message, MessageContent, Role};
 use crate::services::Services;
+use crate::usage::TokenCounts;
 
 /// Callback type for saving session after tool iterations.
 /// Called with a reference to the current messages after each tool loop iteration.
@@ -34,6 +39,9 @@ pub(crate) enum ModelProvider {
     OpenAi,
     OpenAiCompat,
     OpenRouter,
+    Ollama,
+    Vertex,
+    Mistral,
 }
 
 impl ModelProvider {
@@ -46,6 +54,9 @@ impl ModelProvider {
             ModelProvider::OpenAi => "OpenAI",
             ModelProvider::OpenAiCompat => "OpenAI Compatible",
             ModelProvider::OpenRouter => "OpenRouter",
+            ModelProvider::Ollama => "Ollama",
+            ModelProvider::Vertex => "Vertex AI",
+            ModelProvider::Mistral => "Mistral",
         }
     }
 
@@ -58,6 +69,9 @@ impl ModelProvider {
             ModelProvider::OpenAi => "openai",
             ModelProvider::OpenAiCompat => "openai_compat",
             ModelProvider::OpenRouter => "openrouter",
+            ModelProvider::Ollama => "ollama",
+            ModelProvider::Vertex => "vertex",
+            ModelProvider::Mistral => "mistral",
         }
     }
 }
@@ -66,11 +80,18 @@ impl ModelProvider {
 pub(crate) struct ThinkingState {
     pub enabled: bool,
     pub mode: Option<String>,
+    /// Explicit thinking token budget (e.g. from `--thinking-budget`), overriding the
+    /// budget implied by the mode for providers that take a token budget.
+    pub budget_tokens: Option<u32>,
 }
 
 impl ThinkingState {
     pub(crate) fn new(enabled: bool, mode: Option<String>) -> Self {
-        Self { enabled, mode }
+        Self {
+            enabled,
+            mode,
+            budget_tokens: None,
+        }
     }
 
     pub(crate) fn label(&self) -> &str {
@@ -111,6 +132,7 @@ pub(crate) fn default_thinking_state(provider: ModelProvider, model_id: &str) ->
         ModelProvider::Antigravity => AntigravityProvider::default_thinking_state(model_id),
         ModelProvider::OpenCodeZen => ZenProvider::default_thinking_state(model_id),
         ModelProvider::Claude => AnthropicProvider::default_thinking_state(model_id),
+        ModelProvider::Vertex | ModelProvider::Mistral => ThinkingState::new(false, None),
         ModelProvider::GitHubCopilot => {
             if let Some(variant) = get_model_variant(model_id) {
                 ThinkingState::new(variant != "off", Some(variant.to_string()))
@@ -138,10 +160,12 @@ pub(crate) fn cycle_thinking_state(
     let modes: &[&str] = match provider {
         ModelProvider::OpenCodeZen => ZenProvider::thinking_modes(model_id),
         ModelProvider::Claude => AnthropicProvider::thinking_modes(),
+        ModelProvider::Vertex | ModelProvider::Mistral => &[],
         ModelProvider::GitHubCopilot
         | ModelProvider::OpenAi
         | ModelProvider::OpenAiCompat
         | ModelProvider::OpenRouter
+        | ModelProvider::Ollama
         | ModelProvider::Antigravity => &["off", "on"],
     };
 
@@ -184,6 +208,82 @@ pub(crate) fn cycle_model_variant(provider: ModelProvider, model_id: &str) -> St
     }
 }
 
+/// Get the available reasoning variants for a model, for providers that use model variants.
+fn model_variants(provider: ModelProvider, model_id: &str) -> Vec<&'static str> {
+    match provider {
+        ModelProvider::GitHubCopilot => CopilotProvider::model_variants(model_id),
+        ModelProvider::Claude => AnthropicProvider::model_variants(model_id),
+        ModelProvider::OpenAi => OpenAiProvider::model_variants(model_id),
+        ModelProvider::Antigravity => AntigravityProvider::model_variants(model_id),
+        _ => Vec::new(),
+    }
+}
+
+/// Resolve a reasoning level (e.g. from `--reasoning`) into the model ID and thinking
+/// state to use.
+///
+/// Providers that encode reasoning in the model variant (`model#high`) switch to the
+/// matching variant; all others carry the level in the thinking mode. Returns an error
+/// message listing the valid levels if the model has variants but none match.
+pub(crate) fn resolve_reasoning_level(
+    provider: ModelProvider,
+    model_id: &str,
+    level: &str,
+) -> std::result::Result<(String, ThinkingState), String> {
+    let enabled = level != "off";
+    let mode = if enabled {
+        Some(level.to_string())
+    } else {
+        None
+    };
+
+    if !uses_model_variants(provider, model_id) {
+        return Ok((model_id.to_string(), ThinkingState::new(enabled, mode)));
+    }
+
+    let variants = model_variants(provider, model_id);
+    if !variants.contains(&level) {
+        return Err(format!(
+            "Reasoning level '{}' is not available for {} (available: {})",
+            level,
+            model_id,
+            variants.join(", ")
+        ));
+    }
+
+    let base = model_id.split_once('#').map(|(b, _)| b).unwrap_or(model_id);
+    Ok((
+        format!("{}#{}", base, level),
+        ThinkingState::new(enabled, Some(level.to_string())),
+    ))
+}
+
+/// Smallest thinking budget the budget-based providers accept.
+pub(crate) const MIN_THINKING_BUDGET: u32 = 1024;
+
+/// Check a thinking budget (e.g. from `--thinking-budget`) against the output
+/// tokens `provider` requests, which the budget has to stay below. Providers
+/// that don't take a budget accept any.
+pub(crate) fn validate_thinking_budget(
+    provider: ModelProvider,
+    budget: u32,
+) -> std::result::Result<(), String> {
+    let max_output = match provider {
+        ModelProvider::Claude => crate::provider::anthropic::MAX_TOKENS,
+        ModelProvider::Antigravity => crate::provider::antigravity::MAX_OUTPUT_TOKENS,
+        _ => return Ok(()),
+    };
+    if budget >= max_output {
+        return Err(format!(
+            "Thinking budget {} must be below the {} output tokens requested from {}",
+            budget,
+            max_output,
+            provider.display_name()
+        ));
+    }
+    Ok(())
+}
+
 /// Get the current variant/reasoning level from a model ID.
 /// Returns the variant suffix (e.g., "high", "medium") or None if no variant.
 pub(crate) fn get_model_variant(model_id: &str) -> Option<&str> {
@@ -222,7 +322,10 @@ impl ModelChoice {
         match self.provider {
             ModelProvider::OpenAiCompat
             | ModelProvider::OpenCodeZen
-            | ModelProvider::OpenRouter => {
+            | ModelProvider::OpenRouter
+            | ModelProvider::Ollama
+            | ModelProvider::Vertex
+            | ModelProvider::Mistral => {
                 if let Some(custom) = &self.custom_provider {
                     format!("{}/{}", custom, self.model_id)
                 } else {
@@ -417,6 +520,72 @@ pub(crate) fn build_model_choices() -> Vec<ModelChoice> {
                     }
                 }
             }
+            ProviderType::Ollama => {
+                if let Some(ollama_config) = provider_config.as_ollama() {
+                    let mut models = ollama_config.all_models();
+                    for model in crate::provider::ollama::discovered_models(local_id) {
+                        if !models.contains(&model) {
+                            models.push(model);
+                        }
+                    }
+                    for model in models {
+                        let mut choice = ModelChoice {
+                            provider: ModelProvider::Ollama,
+                            model_id: model,
+                            custom_provider: Some(local_id.clone()),
+                            is_favorite: false,
+                        };
+                        choice.is_favorite = is_favorite(&choice);
+                        choices.push(choice);
+                    }
+                }
+            }
+            ProviderType::Vertex => {
+                if let Some(vertex_config) = provider_config.as_vertex() {
+                    let mut models: Vec<String> = VertexProvider::models()
+                        .iter()
+                        .map(|m| m.to_string())
+                        .collect();
+                    for model in vertex_config.all_models() {
+                        if !models.contains(&model) {
+                            models.push(model);
+                        }
+                    }
+                    for model in models {
+                        let mut choice = ModelChoice {
+                            provider: ModelProvider::Vertex,
+                            model_id: model,
+                            custom_provider: Some(local_id.clone()),
+                            is_favorite: false,
+                        };
+                        choice.is_favorite = is_favorite(&choice);
+                        choices.push(choice);
+                    }
+                }
+            }
+            ProviderType::Mistral => {
+                if let Some(mistral_config) = provider_config.as_mistral() {
+                    let mut models: Vec<String> = MistralProvider::models()
+                        .iter()
+                        .map(|m| m.to_string())
+                        .collect();
+                    for model in mistral_config.all_models() {
+                        if !models.contains(&model) {
+                            models.push(model);
+                        }
+                    }
+                    for model in models {
+                        let mut choice = ModelChoice {
+                            provider: ModelProvider::Mistral,
+                            model_id: model,
+                            custom_provider: Some(local_id.clone()),
+                            is_favorite: false,
+                        };
+                        choice.is_favorite = is_favorite(&choice);
+                        choices.push(choice);
+                    }
+                }
+            }
         }
     }
 
@@ -489,66 +658,129 @@ pub(crate) struct ProviderManager {
     openai_provider: Option<OpenAiProvider>,
     openai_compat_providers: HashMap<String, OpenAiCompatProvider>,
     openrouter_provider: Option<OpenRouterProvider>,
+    ollama_providers: HashMap<String, OllamaProvider>,
+    vertex_providers: HashMap<String, VertexProvider>,
+    mistral_providers: HashMap<String, MistralProvider>,
     current_provider: ModelProvider,
     current_model_id: String,
     current_custom_provider: Option<String>,
     services: Services,
-    /// Optional callback to save session after each tool iteration
+    /// Optional callback to save the whole session, called at the start of
+    /// each turn and whenever the context is rewritten
     session_save_callback: Option<SessionSaveCallback>,
+    /// Sampling seed for providers that support one
+    seed: Option<u64>,
+    /// Working directory of the session, where compacted history is archived
+    working_dir: PathBuf,
 }
 
 impl ProviderManager {
     pub(crate) fn new(config: &Config, services: Services) -> Self {
         let zen_provider = ZenProvider::new(config, services.clone());
-        let copilot_provider = CopilotProvider::try_new(services.clone()).ok();
-        let anthropic_provider = AnthropicProvider::try_new(services.clone()).ok();
-        let openai_provider = OpenAiProvider::try_new(services.clone()).ok();
-        let openrouter_provider = OpenRouterProvider::try_new("openrouter", services.clone()).ok();
-
-        // Load all configured OpenAI-compatible providers
-        let mut openai_compat_providers = HashMap::new();
-        let mut antigravity_providers = HashMap::new();
-        if let Ok(cfg) = crate::config::ConfigFile::load() {
-            for (name, provider_config) in &cfg.providers.entries {
-                if provider_config.as_openai_compat().is_some()
-                    && let Ok(provider) = OpenAiCompatProvider::try_new(name, services.clone())
-                {
-                    openai_compat_providers.insert(name.clone(), provider);
-                }
-                if provider_config.as_antigravity().is_some()
-                    && let Ok(provider) = AntigravityProvider::try_new(name, services.clone())
-                {
-                    antigravity_providers.insert(name.clone(), provider);
-                }
-            }
-        }
 
         let (current_provider, current_model_id, current_custom_provider) =
             parse_model_spec(&config.model);
 
         crate::config::persist_last_used_model(&config.model);
 
-        Self {
+        // Only the current provider is created here; the others are created
+        // when first selected, so startup doesn't load credentials it won't use.
+        let mut manager = Self {
             zen_provider,
-            antigravity_providers,
-            copilot_provider,
-            anthropic_provider,
-            openai_provider,
-            openai_compat_providers,
-            openrouter_provider,
+            antigravity_providers: HashMap::new(),
+            copilot_provider: None,
+            anthropic_provider: None,
+            openai_provider: None,
+            openai_compat_providers: HashMap::new(),
+            openrouter_provider: None,
+            ollama_providers: HashMap::new(),
+            vertex_providers: HashMap::new(),
+            mistral_providers: HashMap::new(),
             current_provider,
-            current_model_id,
-            current_custom_provider,
+            current_model_id: current_model_id.clone(),
+            current_custom_provider: current_custom_provider.clone(),
             services,
             session_save_callback: None,
-        }
+            seed: None,
+            working_dir: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        };
+        manager.set_model(current_provider, current_model_id, current_custom_provider);
+        manager
     }
 
-    /// Set a callback to be called after each tool iteration to save the session.
+    /// Set a callback to save the whole session. Messages added during a turn
+    /// are journaled through `services().journal` in between.
     pub(crate) fn set_session_save_callback(&mut self, callback: SessionSaveCallback) {
         self.session_save_callback = Some(callback);
     }
 
+    pub(crate) fn services(&self) -> &Services {
+        &self.services
+    }
+
+    /// Save the whole session if a callback is set.
+    fn save_session(&self, messages: &[Message]) {
+        if let Some(callback) = &self.session_save_callback {
+            callback(messages);
+        }
+    }
+
+    /// Switch to the next model from `fallbacks` that differs from the
+    /// current one and whose provider is configured, returning its spec.
+    fn fail_over(
+        &mut self,
+        fallbacks: &mut impl Iterator<Item = String>,
+        messages: &mut [Message],
+    ) -> Option<String> {
+        for spec in fallbacks {
+            let (provider, model_id, custom_provider) = parse_model_spec(&spec);
+            if provider == self.current_provider
+                && model_id == self.current_model_id
+                && custom_provider == self.current_custom_provider
+            {
+                continue;
+            }
+            let previous = (
+                self.current_provider,
+                self.current_model_id.clone(),
+                self.current_custom_provider.clone(),
+            );
+            let provider_changed = self.set_model(provider, model_id, custom_provider);
+            if !self.is_ready() {
+                let (provider, model_id, custom_provider) = previous;
+                self.set_model(provider, model_id, custom_provider);
+                continue;
+            }
+            if provider_changed {
+                crate::provider::transform_thinking_for_provider_switch(messages);
+            }
+            return Some(spec);
+        }
+        None
+    }
+
+    /// Whether the current provider is configured and can take requests.
+    fn is_ready(&self) -> bool {
+        let Some(name) = &self.current_custom_provider else {
+            return match self.current_provider {
+                ModelProvider::OpenCodeZen => true,
+                ModelProvider::GitHubCopilot => self.copilot_provider.is_some(),
+                ModelProvider::Claude => self.anthropic_provider.is_some(),
+                ModelProvider::OpenAi => self.openai_provider.is_some(),
+                ModelProvider::OpenRouter => self.openrouter_provider.is_some(),
+                _ => false,
+            };
+        };
+        match self.current_provider {
+            ModelProvider::Antigravity => self.antigravity_providers.contains_key(name),
+            ModelProvider::OpenAiCompat => self.openai_compat_providers.contains_key(name),
+            ModelProvider::Ollama => self.ollama_providers.contains_key(name),
+            ModelProvider::Vertex => self.vertex_providers.contains_key(name),
+            ModelProvider::Mistral => self.mistral_providers.contains_key(name),
+            _ => false,
+        }
+    }
+
     /// Set the current model.
     ///
     /// Returns `true` if the provider changed (not just the model within the same provider).
@@ -567,6 +799,7 @@ impl ProviderManager {
         self.current_provider = provider;
         self.current_model_id = model_id.clone();
         self.current_custom_provider = custom_provider.clone();
+        let seed = self.seed;
 
         match provider {
             ModelProvider::Antigravity => {
@@ -591,23 +824,38 @@ impl ProviderManager {
             }
             ModelProvider::OpenCodeZen => self.zen_provider.set_model(model_id),
             ModelProvider::GitHubCopilot => {
+                if self.copilot_provider.is_none() {
+                    self.copilot_provider = CopilotProvider::try_new(self.services.clone()).ok();
+                }
                 if let Some(ref mut p) = self.copilot_provider {
                     p.set_model(model_id);
                 }
             }
             ModelProvider::Claude => {
+                if self.anthropic_provider.is_none() {
+                    self.anthropic_provider =
+                        AnthropicProvider::try_new(self.services.clone()).ok();
+                }
                 if let Some(ref mut p) = self.anthropic_provider {
                     p.set_model(model_id);
                 }
             }
             ModelProvider::OpenAi => {
+                if self.openai_provider.is_none() {
+                    self.openai_provider = OpenAiProvider::try_new(self.services.clone()).ok();
+                }
                 if let Some(ref mut p) = self.openai_provider {
                     p.set_model(model_id);
                 }
             }
             ModelProvider::OpenRouter => {
+                if self.openrouter_provider.is_none() {
+                    self.openrouter_provider =
+                        OpenRouterProvider::try_new("openrouter", self.services.clone()).ok();
+                }
                 if let Some(ref mut p) = self.openrouter_provider {
                     p.set_model(model_id);
+                    p.set_seed(seed);
                 }
             }
             ModelProvider::OpenAiCompat => {
@@ -629,11 +877,72 @@ impl ProviderManager {
                     }
                     if let Some(p) = self.openai_compat_providers.get_mut(custom_name) {
                         p.set_model(model_id);
+                        p.set_seed(seed);
                     }
                 } else {
                     eprintln!("OpenAI Compatible provider requires a custom provider name.");
                 }
             }
+            ModelProvider::Ollama => {
+                if let Some(custom_name) = &custom_provider {
+                    if !self.ollama_providers.contains_key(custom_name) {
+                        if let Ok(provider) =
+                            OllamaProvider::try_new(custom_name, self.services.clone())
+                        {
+                            self.ollama_providers.insert(custom_name.clone(), provider);
+                        } else {
+                            eprintln!("Ollama provider '{}' not configured.", custom_name);
+                            return provider_changed;
+                        }
+                    }
+                    if let Some(p) = self.ollama_providers.get_mut(custom_name) {
+                        p.set_model(model_id);
+                        p.set_seed(seed);
+                    }
+                } else {
+                    eprintln!("Ollama provider requires a custom provider name.");
+                }
+            }
+            ModelProvider::Vertex => {
+                if let Some(custom_name) = &custom_provider {
+                    if !self.vertex_providers.contains_key(custom_name) {
+                        match VertexProvider::try_new(custom_name, self.services.clone()) {
+                            Ok(provider) => {
+                                self.vertex_providers.insert(custom_name.clone(), provider);
+                            }
+                            Err(e) => {
+                                eprintln!("{}", e);
+                                return provider_changed;
+                            }
+                        }
+                    }
+                    if let Some(p) = self.vertex_providers.get_mut(custom_name) {
+                        p.set_model(model_id);
+                    }
+                } else {
+                    eprintln!("Vertex AI provider requires a custom provider name.");
+                }
+            }
+            ModelProvider::Mistral => {
+                if let Some(custom_name) = &custom_provider {
+                    if !self.mistral_providers.contains_key(custom_name) {
+                        match MistralProvider::try_new(custom_name, self.services.clone()) {
+                            Ok(provider) => {
+                                self.mistral_providers.insert(custom_name.clone(), provider);
+                            }
+                            Err(e) => {
+                                eprintln!("{}", e);
+                                return provider_changed;
+                            }
+                        }
+                    }
+                    if let Some(p) = self.mistral_providers.get_mut(custom_name) {
+                        p.set_model(model_id);
+                    }
+                } else {
+                    eprintln!("Mistral provider requires a custom provider name.");
+                }
+            }
         }
         provider_changed
     }
@@ -644,8 +953,8 @@ impl ProviderManager {
 
     pub(crate) fn set_thinking_enabled(&mut self, enabled: bool) {
         match self.current_provider {
-            ModelProvider::OpenCodeZen => {
-                // Zen provider doesn't use thinking_enabled
+            ModelProvider::OpenCodeZen | ModelProvider::Vertex | ModelProvider::Mistral => {
+                // Zen, Vertex AI and Mistral providers don't use thinking_enabled
             }
             ModelProvider::GitHubCopilot => {
                 if let Some(ref mut p) = self.copilot_provider {
@@ -657,6 +966,13 @@ impl ProviderManager {
                     p.set_thinking_enabled(enabled);
                 }
             }
+            ModelProvider::Ollama => {
+                if let Some(name) = &self.current_custom_provider
+                    && let Some(p) = self.ollama_providers.get_mut(name)
+                {
+                    p.set_thinking_enabled(enabled);
+                }
+            }
             ModelProvider::Antigravity
             | ModelProvider::OpenRouter
             | ModelProvider::OpenAiCompat
@@ -680,10 +996,74 @@ impl ProviderManager {
                     self.current_model_id = format!("{}#{}", base, mode);
                 }
             }
+            ModelProvider::OpenRouter => {
+                if let Some(ref mut p) = self.openrouter_provider {
+                    p.set_reasoning_effort(mode);
+                }
+            }
+            ModelProvider::OpenAiCompat => {
+                if let Some(name) = &self.current_custom_provider
+                    && let Some(p) = self.openai_compat_providers.get_mut(name)
+                {
+                    p.set_reasoning_effort(mode);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Set an explicit thinking token budget for providers that take one.
+    /// `None` restores the budget implied by the thinking mode.
+    pub(crate) fn set_thinking_budget(&mut self, budget_tokens: Option<u32>) {
+        match self.current_provider {
+            ModelProvider::Claude => {
+                if let Some(ref mut p) = self.anthropic_provider {
+                    p.set_thinking_budget(budget_tokens);
+                }
+            }
+            ModelProvider::Antigravity => {
+                if let Some(name) = &self.current_custom_provider
+                    && let Some(p) = self.antigravity_providers.get_mut(name)
+                {
+                    p.set_thinking_budget(budget_tokens);
+                }
+            }
             _ => {}
         }
     }
 
+    /// Set the sampling seed. Only OpenAI-compatible backends (including
+    /// OpenRouter) and Ollama accept one; other providers ignore it.
+    pub(crate) fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+        if let Some(ref mut p) = self.openrouter_provider {
+            p.set_seed(seed);
+        }
+        for p in self.openai_compat_providers.values_mut() {
+            p.set_seed(seed);
+        }
+        for p in self.ollama_providers.values_mut() {
+            p.set_seed(seed);
+        }
+    }
+
+    pub(crate) fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Set the working directory of the session.
+    pub(crate) fn set_working_dir(&mut self, dir: &Path) {
+        self.working_dir = dir.to_path_buf();
+    }
+
+    /// Whether the current provider sends the sampling seed.
+    pub(crate) fn supports_seed(&self) -> bool {
+        matches!(
+            self.current_provider,
+            ModelProvider::OpenRouter | ModelProvider::OpenAiCompat | ModelProvider::Ollama
+        )
+    }
+
     /// Send a chat request, emitting events via output::emit()
     ///
     /// This method handles the full chat loop including:
@@ -701,6 +1081,19 @@ impl ProviderManager {
 
         let services = self.services.clone();
         let config = crate::config::ConfigFile::load().unwrap_or_default();
+        services.checkpoints.begin_turn();
+        let prompt = submitted_prompt(messages);
+        services
+            .timeline
+            .start_turn(prompt.as_deref().unwrap_or("(continued)"));
+        if let Some(prompt) = prompt {
+            crate::hooks::user_prompt_submit(&config.hooks, &prompt, output, &services).await;
+        }
+        if let Some(notice) = services.seen_files.take_changes() {
+            append_to_prompt(messages, notice);
+        }
+        // Save the prompt now; the rest of the turn is journaled.
+        self.save_session(messages);
 
         // Start turn for usage tracking (call once at the start)
         match self.current_provider {
@@ -739,33 +1132,89 @@ impl ProviderManager {
                     p.start_turn();
                 }
             }
+            ModelProvider::Ollama => {
+                if let Some(name) = &self.current_custom_provider
+                    && let Some(p) = self.ollama_providers.get(name)
+                {
+                    p.start_turn();
+                }
+            }
+            ModelProvider::Vertex => {
+                if let Some(name) = &self.current_custom_provider
+                    && let Some(p) = self.vertex_providers.get(name)
+                {
+                    p.start_turn();
+                }
+            }
+            ModelProvider::Mistral => {
+                if let Some(name) = &self.current_custom_provider
+                    && let Some(p) = self.mistral_providers.get(name)
+                {
+                    p.start_turn();
+                }
+            }
+        }
+
+        crate::usage::start_cost_turn();
+        if config.cost.on_limit == CostLimitAction::Stop
+            && let Some(msg) = crate::usage::cost_limit_reached(&config.cost)
+        {
+            let msg = format!("{}. Raise max-session-cost or /clear to continue.", msg);
+            crate::output::emit_error(output, &msg);
+            return Err(crate::error::Error::Other(msg));
         }
 
+        let mut watchdog = crate::watchdog::LoopWatchdog::new(config.loop_watchdog.clone());
+        // Index of the pending continuation prompt, and continuations of the current response.
+        let mut continuation_prompt: Option<usize> = None;
+        let mut continuations = 0;
+        // An oversized tool result is summarized at most once per turn.
+        let mut overflow_recovered = false;
+        // Fallback models not yet tried this turn.
+        let mut fallbacks = config.fallback_models.clone().into_iter();
+
         loop {
+            let tokens_before = self.current_usage().map(|usage| usage.counts());
+
             // Check for auto-compaction before each provider call
             if config.auto_compact.enabled
                 && let Some((usage, limit)) =
                     self.should_auto_compact(config.auto_compact.threshold)
             {
-                crate::output::emit_auto_compact_starting(output, usage, limit);
-                // Use quiet output for summarization so it doesn't stream to UI
-                let quiet_output = crate::output::OutputContext::new_quiet();
-                match self
-                    .compact_context(messages, config.auto_compact.preserve_turns, &quiet_output)
-                    .await
-                {
-                    Ok(result) => {
-                        crate::output::emit_auto_compact_completed(
-                            output,
-                            result.messages_compacted,
-                        );
-                    }
-                    Err(e) => {
-                        // Log error but continue - don't fail chat just because compaction failed
-                        crate::output::emit_error(
-                            output,
-                            &format!("Auto-compaction failed: {}", e),
-                        );
+                if crate::sliding_window::is_enabled() {
+                    self.slide_context(
+                        messages,
+                        config.auto_compact.preserve_turns,
+                        (usage, limit),
+                        output,
+                    );
+                    self.save_session(messages);
+                } else {
+                    crate::output::emit_auto_compact_starting(output, usage, limit);
+                    // Use quiet output for summarization so it doesn't stream to UI
+                    let quiet_output = crate::output::OutputContext::new_quiet();
+                    match self
+                        .compact_context(
+                            messages,
+                            config.auto_compact.preserve_turns,
+                            &quiet_output,
+                        )
+                        .await
+                    {
+                        Ok(result) => {
+                            crate::output::emit_auto_compact_completed(
+                                output,
+                                result.messages_compacted,
+                            );
+                            self.save_session(messages);
+                        }
+                        Err(e) => {
+                            // Log error but continue - don't fail chat just because compaction failed
+                            crate::output::emit_error(
+                                output,
+                                &format!("Auto-compaction failed: {}", e),
+                            );
+                        }
                     }
                 }
             }
@@ -778,7 +1227,7 @@ impl ProviderManager {
                             Some(p) => {
                                 p.set_model(self.current_model_id.clone());
                                 run_chat_iteration(p, messages, interrupted, output, &services)
-                                    .await?
+                                    .await
                             }
                             None => {
                                 let msg = format!(
@@ -798,12 +1247,12 @@ impl ProviderManager {
                 ModelProvider::OpenCodeZen => {
                     self.zen_provider.set_model(self.current_model_id.clone());
                     run_chat_iteration(&self.zen_provider, messages, interrupted, output, &services)
-                        .await?
+                        .await
                 }
                 ModelProvider::GitHubCopilot => match self.copilot_provider.as_mut() {
                     Some(p) => {
                         p.set_model(self.current_model_id.clone());
-                        run_chat_iteration(p, messages, interrupted, output, &services).await?
+                        run_chat_iteration(p, messages, interrupted, output, &services).await
                     }
                     None => {
                         let msg = "GitHub Copilot not configured";
@@ -814,7 +1263,7 @@ impl ProviderManager {
                 ModelProvider::Claude => match self.anthropic_provider.as_mut() {
                     Some(p) => {
                         p.set_model(self.current_model_id.clone());
-                        run_chat_iteration(p, messages, interrupted, output, &services).await?
+                        run_chat_iteration(p, messages, interrupted, output, &services).await
                     }
                     None => {
                         let msg = "Anthropic not configured";
@@ -825,7 +1274,7 @@ impl ProviderManager {
                 ModelProvider::OpenAi => match self.openai_provider.as_mut() {
                     Some(p) => {
                         p.set_model(self.current_model_id.clone());
-                        run_chat_iteration(p, messages, interrupted, output, &services).await?
+                        run_chat_iteration(p, messages, interrupted, output, &services).await
                     }
                     None => {
                         let msg = "OpenAI not configured";
@@ -836,7 +1285,7 @@ impl ProviderManager {
                 ModelProvider::OpenRouter => match self.openrouter_provider.as_mut() {
                     Some(p) => {
                         p.set_model(self.current_model_id.clone());
-                        run_chat_iteration(p, messages, interrupted, output, &services).await?
+                        run_chat_iteration(p, messages, interrupted, output, &services).await
                     }
                     None => {
                         let msg = "OpenRouter not configured";
@@ -850,7 +1299,7 @@ impl ProviderManager {
                             Some(p) => {
                                 p.set_model(self.current_model_id.clone());
                                 run_chat_iteration(p, messages, interrupted, output, &services)
-                                    .await?
+                                    .await
                             }
                             None => {
                                 let msg = format!(
@@ -867,14 +1316,182 @@ impl ProviderManager {
                         return Err(crate::error::Error::Auth(msg.to_string()));
                     }
                 }
+                ModelProvider::Ollama => {
+                    if let Some(custom_name) = &self.current_custom_provider.clone() {
+                        match self.ollama_providers.get_mut(custom_name) {
+                            Some(p) => {
+                                p.set_model(self.current_model_id.clone());
+                                run_chat_iteration(p, messages, interrupted, output, &services)
+                                    .await
+                            }
+                            None => {
+                                let msg =
+                                    format!("Ollama provider '{}' not configured", custom_name);
+                                crate::output::emit_error(output, &msg);
+                                return Err(crate::error::Error::Auth(msg));
+                            }
+                        }
+                    } else {
+                        let msg = "Ollama provider requires a custom provider name";
+                        crate::output::emit_error(output, msg);
+                        return Err(crate::error::Error::Auth(msg.to_string()));
+                    }
+                }
+                ModelProvider::Vertex => {
+                    if let Some(custom_name) = &self.current_custom_provider.clone() {
+                        match self.vertex_providers.get_mut(custom_name) {
+                            Some(p) => {
+                                p.set_model(self.current_model_id.clone());
+                                run_chat_iteration(p, messages, interrupted, output, &services)
+                                    .await
+                            }
+                            None => {
+                                let msg =
+                                    format!("Vertex AI provider '{}' not configured", custom_name);
+                                crate::output::emit_error(output, &msg);
+                                return Err(crate::error::Error::Auth(msg));
+                            }
+                        }
+                    } else {
+                        let msg = "Vertex AI provider requires a custom provider name";
+                        crate::output::emit_error(output, msg);
+                        return Err(crate::error::Error::Auth(msg.to_string()));
+                    }
+                }
+                ModelProvider::Mistral => {
+                    if let Some(custom_name) = &self.current_custom_provider.clone() {
+                        match self.mistral_providers.get_mut(custom_name) {
+                            Some(p) => {
+                                p.set_model(self.current_model_id.clone());
+                                run_chat_iteration(p, messages, interrupted, output, &services)
+                                    .await
+                            }
+                            None => {
+                                let msg =
+                                    format!("Mistral provider '{}' not configured", custom_name);
+                                crate::output::emit_error(output, &msg);
+                                return Err(crate::error::Error::Auth(msg));
+                            }
+                        }
+                    } else {
+                        let msg = "Mistral provider requires a custom provider name";
+                        crate::output::emit_error(output, msg);
+                        return Err(crate::error::Error::Auth(msg.to_string()));
+                    }
+                }
             };
 
+            // Count the tokens even when the iteration failed partway through
+            self.record_cost(tokens_before, &config.cost);
+            // Retries are exhausted by now, so move on to the next fallback model.
+            if let Err(e) = &result
+                && e.is_retryable()
+                && let Some(spec) = self.fail_over(&mut fallbacks, messages)
+            {
+                self.save_session(messages);
+                crate::output::emit_warning(
+                    output,
+                    &format!("{} Switching to {}.", e.display_message(), spec),
+                );
+                continue;
+            }
+            let result = match result {
+                Err(e)
+                    if e.is_context_overflow()
+                        && config.overflow_recovery.enabled
+                        && !overflow_recovered =>
+                {
+                    overflow_recovered = true;
+                    let summarized = self
+                        .summarize_tool_result(messages, config.overflow_recovery.model.as_deref())
+                        .await;
+                    match summarized {
+                        Ok(Some((tool_name, size))) => {
+                            self.save_session(messages);
+                            crate::output::emit_warning(
+                                output,
+                                &format!(
+                                    "Summarized the {} KB {} result to fit the context window. Retrying.",
+                                    size / 1024,
+                                    tool_name
+                                ),
+                            );
+                            continue;
+                        }
+                        Ok(None) => return Err(e),
+                        Err(summary_error) => {
+                            crate::output::emit_error(
+                                output,
+                                &format!(
+                                    "Failed to summarize the tool result: {}",
+                                    summary_error.display_message()
+                                ),
+                            );
+                            return Err(e);
+                        }
+                    }
+                }
+                result => result?,
+            };
+
+            if let Some(msg) = crate::usage::cost_limit_reached(&config.cost) {
+                match config.cost.on_limit {
+                    CostLimitAction::Warn => {
+                        if crate::usage::take_cost_limit_warning() {
+                            crate::output::emit_warning(output, &msg);
+                        }
+                    }
+                    CostLimitAction::Stop if matches!(result, ChatIterationResult::Done) => {
+                        crate::output::emit_warning(output, &msg);
+                    }
+                    CostLimitAction::Stop => {
+                        crate::output::emit_warning(output, &format!("{}. Stopping.", msg));
+                        crate::output::emit_interrupted(output);
+                        return Err(crate::error::Error::Interrupted);
+                    }
+                }
+            }
+
+            if let Some(index) = continuation_prompt.take() {
+                crate::continuation::stitch(messages, index);
+                self.save_session(messages);
+            }
+
             match result {
                 ChatIterationResult::Done => break,
+                ChatIterationResult::Truncated => {
+                    if !should_continue_truncated(
+                        &config.max_tokens_continuation,
+                        continuations,
+                        interrupted,
+                        output,
+                    )
+                    .await
+                    {
+                        if interrupted.load(std::sync::atomic::Ordering::SeqCst) {
+                            crate::output::emit_interrupted(output);
+                            return Err(crate::error::Error::Interrupted);
+                        }
+                        crate::output::emit_done(output);
+                        break;
+                    }
+                    continuations += 1;
+                    messages.push(Message::user(crate::continuation::PROMPT));
+                    continuation_prompt = Some(messages.len() - 1);
+                    continue;
+                }
                 ChatIterationResult::Continue => {
-                    // Save session after each tool iteration if callback is set
-                    if let Some(ref callback) = self.session_save_callback {
-                        callback(messages);
+                    continuations = 0;
+
+                    // The iteration ended with the assistant's tool calls and their results.
+                    if let [.., assistant, results] = messages.as_slice()
+                        && let Some(reason) = watchdog.observe(assistant, results)
+                    {
+                        if !crate::watchdog::ask_to_continue(&reason, interrupted, output).await {
+                            crate::output::emit_interrupted(output);
+                            return Err(crate::error::Error::Interrupted);
+                        }
+                        watchdog.reset();
                     }
                     continue;
                 }
@@ -949,6 +1566,60 @@ impl ProviderManager {
                     ))
                 }
             }
+            ModelProvider::Ollama => {
+                if let Some(custom_name) = &self.current_custom_provider {
+                    match self.ollama_providers.get_mut(custom_name) {
+                        Some(p) => {
+                            p.set_model(self.current_model_id.clone());
+                            p.prepare_request(messages).await
+                        }
+                        None => Err(crate::error::Error::Auth(format!(
+                            "Ollama provider '{}' not configured",
+                            custom_name
+                        ))),
+                    }
+                } else {
+                    Err(crate::error::Error::Auth(
+                        "Ollama provider requires a custom provider name".to_string(),
+                    ))
+                }
+            }
+            ModelProvider::Vertex => {
+                if let Some(custom_name) = &self.current_custom_provider {
+                    match self.vertex_providers.get_mut(custom_name) {
+                        Some(p) => {
+                            p.set_model(self.current_model_id.clone());
+                            p.prepare_request(messages).await
+                        }
+                        None => Err(crate::error::Error::Auth(format!(
+                            "Vertex AI provider '{}' not configured",
+                            custom_name
+                        ))),
+                    }
+                } else {
+                    Err(crate::error::Error::Auth(
+                        "Vertex AI provider requires a custom provider name".to_string(),
+                    ))
+                }
+            }
+            ModelProvider::Mistral => {
+                if let Some(custom_name) = &self.current_custom_provider {
+                    match self.mistral_providers.get_mut(custom_name) {
+                        Some(p) => {
+                            p.set_model(self.current_model_id.clone());
+                            p.prepare_request(messages).await
+                        }
+                        None => Err(crate::error::Error::Auth(format!(
+                            "Mistral provider '{}' not configured",
+                            custom_name
+                        ))),
+                    }
+                } else {
+                    Err(crate::error::Error::Auth(
+                        "Mistral provider requires a custom provider name".to_string(),
+                    ))
+                }
+            }
         }
     }
 
@@ -982,22 +1653,77 @@ impl ProviderManager {
         self.current_custom_provider.as_deref()
     }
 
+    /// The current model as `provider/model-id`
+    pub(crate) fn current_model_spec(&self) -> String {
+        format!(
+            "{}/{}",
+            self.current_custom_provider()
+                .unwrap_or_else(|| self.current_provider().id()),
+            self.current_model_id()
+        )
+    }
+
     /// Get the last context usage (input tokens) for the current provider.
     /// Returns None if usage tracking is not available for this provider.
     pub(crate) fn get_last_context_usage(&self) -> Option<u64> {
-        let input = match self.current_provider {
-            ModelProvider::Claude => crate::usage::anthropic().last_input(),
-            ModelProvider::OpenCodeZen => crate::usage::zen().last_input(),
-            ModelProvider::OpenAiCompat => crate::usage::openai_compat().last_input(),
-            ModelProvider::OpenAi => crate::usage::openai().last_input(),
-            ModelProvider::OpenRouter => crate::usage::openrouter().last_input(),
-            ModelProvider::Antigravity => crate::usage::antigravity().last_input(),
-            ModelProvider::GitHubCopilot => return None,
-        };
+        let input = self.current_usage()?.last_input();
         // Only return if we actually have usage data
         if input > 0 { Some(input) } else { None }
     }
 
+    /// Get the tokens reported for the last request to the current provider.
+    /// Returns None if usage tracking is not available for this provider.
+    pub(crate) fn get_last_usage(&self) -> Option<TokenCounts> {
+        self.current_usage().map(|usage| usage.last())
+    }
+
+    /// Get total (input, output) tokens used with the current provider.
+    /// Returns None if usage tracking is not available for this provider.
+    pub(crate) fn get_total_usage(&self) -> Option<(u64, u64)> {
+        self.current_usage().map(|usage| usage.totals())
+    }
+
+    /// Price the tokens used since `before` and add them to the session cost.
+    fn record_cost(&self, before: Option<TokenCounts>, config: &CostConfig) {
+        let (Some(usage), Some(before)) = (self.current_usage(), before) else {
+            return;
+        };
+        let tokens = usage.counts().since(&before);
+        if tokens.is_empty() {
+            return;
+        }
+        let label = format!(
+            "{}/{}",
+            self.current_custom_provider
+                .as_deref()
+                .unwrap_or_else(|| self.current_provider.id()),
+            self.current_model_id
+        );
+        let cost = crate::usage::price(
+            self.current_provider,
+            &label,
+            &self.current_model_id,
+            &tokens,
+            config,
+        );
+        crate::usage::record_cost(&label, &tokens, cost);
+    }
+
+    fn current_usage(&self) -> Option<&'static crate::usage::Usage> {
+        match self.current_provider {
+            ModelProvider::Claude => Some(crate::usage::anthropic()),
+            ModelProvider::OpenCodeZen => Some(crate::usage::zen()),
+            ModelProvider::OpenAiCompat => Some(crate::usage::openai_compat()),
+            ModelProvider::OpenAi => Some(crate::usage::openai()),
+            ModelProvider::OpenRouter => Some(crate::usage::openrouter()),
+            ModelProvider::Antigravity => Some(crate::usage::antigravity()),
+            ModelProvider::Ollama => Some(crate::usage::ollama()),
+            ModelProvider::Vertex => Some(crate::usage::vertex()),
+            ModelProvider::Mistral => Some(crate::usage::mistral()),
+            ModelProvider::GitHubCopilot => None,
+        }
+    }
+
     /// Get the context limit for the current provider and model.
     /// Returns None if the limit is unknown.
     pub(crate) fn get_context_limit(&self) -> Option<u64> {
@@ -1018,6 +1744,34 @@ impl ProviderManager {
         }
     }
 
+    /// Make room by dropping the oldest turns, after archiving them with the
+    /// session, and keeping a preamble of key facts from them.
+    fn slide_context(
+        &self,
+        messages: &mut Vec<Message>,
+        preserve_recent_turns: usize,
+        (usage, limit): (u64, u64),
+        output: &OutputContext,
+    ) {
+        let before = messages.clone();
+        let dropped = crate::sliding_window::slide(messages, preserve_recent_turns);
+        if dropped == 0 {
+            return;
+        }
+        if let Some(session_id) = self.services.session_id() {
+            let _ = crate::session::archive_compacted_transcript(
+                &self.working_dir,
+                &session_id,
+                &before,
+            );
+        }
+        let pct = usage as f64 / limit as f64 * 100.0;
+        output.emit(crate::output::OutputEvent::Info(format!(
+            "Context at {:.0}% ({}/{}) - dropped the {} oldest messages (sliding window).",
+            pct, usage, limit, dropped
+        )));
+    }
+
     /// Compact the message context by summarizing older messages
     pub async fn compact_context(
         &mut self,
@@ -1025,8 +1779,6 @@ impl ProviderManager {
         preserve_recent_turns: usize,
         output: &OutputContext,
     ) -> Result<compaction::CompactionResult> {
-        use crate::provider::Provider;
-
         let (to_compact, to_preserve) =
             compaction::segment_messages(messages, preserve_recent_turns);
 
@@ -1044,7 +1796,130 @@ impl ProviderManager {
         let request_messages = vec![system_msg, user_request];
 
         // Get summary from current provider
-        let response = match self.current_provider {
+        let response = self.complete(request_messages, output).await?;
+
+        // Extract summary text
+        let summary = response
+            .content_blocks
+            .iter()
+            .filter_map(|block| {
+                if let ContentBlock::Text { text } = block {
+                    Some(text.clone())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // Build new message list
+        let summary_message = Message {
+            role: Role::User,
+            content: MessageContent::Blocks(vec![ContentBlock::Summary {
+                summary,
+                messages_compacted,
+            }]),
+        };
+
+        // Keep the full pre-compaction history so it can be expanded later
+        if let Some(session_id) = self.services.session_id() {
+            let _ = crate::session::archive_compacted_transcript(
+                &self.working_dir,
+                &session_id,
+                messages,
+            );
+        }
+
+        let mut new_messages = vec![summary_message];
+        new_messages.extend(to_preserve);
+        *messages = new_messages;
+
+        Ok(compaction::CompactionResult { messages_compacted })
+    }
+
+    /// Replace the largest tool result in `messages` with a summary written by
+    /// `model` (`provider/model-id`), or the current model when `None`.
+    ///
+    /// Returns the name and size of the summarized result, or `None` when no
+    /// tool result is large enough to be worth summarizing.
+    async fn summarize_tool_result(
+        &mut self,
+        messages: &mut [Message],
+        model: Option<&str>,
+    ) -> Result<Option<(String, usize)>> {
+        let Some(result) = crate::tool_summary::largest_tool_result(messages) else {
+            return Ok(None);
+        };
+        let request = crate::tool_summary::summary_request(messages, &result);
+        let quiet_output = OutputContext::new_quiet();
+        let response = match model {
+            Some(model) => {
+                let config = Config::load(Some(model.to_string()))?;
+                let mut summarizer = ProviderManager::new(&config, self.services.clone());
+                summarizer.complete(request, &quiet_output).await?
+            }
+            None => self.complete(request, &quiet_output).await?,
+        };
+        let summary =
+            crate::tools::task::final_answer(&[Message::assistant_blocks(response.content_blocks)])
+                .ok_or_else(|| crate::error::Error::Other("The summary was empty".into()))?;
+        crate::tool_summary::replace(messages, &result, &summary);
+        Ok(Some((result.tool_name, result.size)))
+    }
+
+    /// Ask `model` (`provider/model-id`), or the current model when `None`,
+    /// for a recap of the session in `messages`. The conversation is not changed.
+    pub(crate) async fn recap(
+        &mut self,
+        messages: &[Message],
+        model: Option<&str>,
+    ) -> Result<String> {
+        let request = crate::recap::request(messages);
+        let quiet_output = OutputContext::new_quiet();
+        let response = match model {
+            Some(model) => {
+                let config = Config::load(Some(model.to_string()))?;
+                let mut recapper = ProviderManager::new(&config, self.services.clone());
+                recapper.complete(request, &quiet_output).await?
+            }
+            None => self.complete(request, &quiet_output).await?,
+        };
+        crate::tools::task::final_answer(&[Message::assistant_blocks(response.content_blocks)])
+            .ok_or_else(|| crate::error::Error::Other("The recap was empty".into()))
+    }
+
+    /// A new manager for the same model, for requests made alongside the
+    /// conversation, such as titling the session in the background.
+    pub(crate) fn detached(&self) -> Result<ProviderManager> {
+        let config = Config::load(None)?;
+        let mut manager = ProviderManager::new(&config, self.services.clone());
+        manager.set_model(
+            self.current_provider,
+            self.current_model_id.clone(),
+            self.current_custom_provider.clone(),
+        );
+        Ok(manager)
+    }
+
+    /// Ask the model for a short title for the conversation in `messages`.
+    pub(crate) async fn session_title(&mut self, messages: &[Message]) -> Result<Option<String>> {
+        let request = crate::session::title_request(messages);
+        let response = self.complete(request, &OutputContext::new_quiet()).await?;
+        Ok(
+            crate::tools::task::final_answer(&[Message::assistant_blocks(response.content_blocks)])
+                .and_then(|text| crate::session::clean_title(&text)),
+        )
+    }
+
+    /// Send a single request to the current model, without tools or retries.
+    async fn complete(
+        &mut self,
+        request_messages: Vec<Message>,
+        output: &OutputContext,
+    ) -> Result<crate::provider::ChatResponse> {
+        use crate::provider::Provider;
+
+        Ok(match self.current_provider {
             ModelProvider::Antigravity => {
                 if let Some(custom_name) = &self.current_custom_provider {
                     match self.antigravity_providers.get_mut(custom_name) {
@@ -1125,36 +2000,107 @@ impl ProviderManager {
                     ));
                 }
             }
-        };
-
-        // Extract summary text
-        let summary = response
-            .content_blocks
-            .iter()
-            .filter_map(|block| {
-                if let ContentBlock::Text { text } = block {
-                    Some(text.clone())
+            ModelProvider::Ollama => {
+                if let Some(custom_name) = &self.current_custom_provider {
+                    match self.ollama_providers.get_mut(custom_name) {
+                        Some(p) => {
+                            p.set_model(self.current_model_id.clone());
+                            p.chat(request_messages, output).await?
+                        }
+                        None => {
+                            return Err(crate::error::Error::Auth(format!(
+                                "Ollama provider '{}' not configured",
+                                custom_name
+                            )));
+                        }
+                    }
                 } else {
-                    None
+                    return Err(crate::error::Error::Auth(
+                        "Ollama provider requires a custom provider name".into(),
+                    ));
                 }
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        // Build new message list
-        let summary_message = Message {
-            role: Role::User,
-            content: MessageContent::Blocks(vec![ContentBlock::Summary {
-                summary,
-                messages_compacted,
-            }]),
-        };
+            }
+            ModelProvider::Vertex => {
+                if let Some(custom_name) = &self.current_custom_provider {
+                    match self.vertex_providers.get_mut(custom_name) {
+                        Some(p) => {
+                            p.set_model(self.current_model_id.clone());
+                            p.chat(request_messages, output).await?
+                        }
+                        None => {
+                            return Err(crate::error::Error::Auth(format!(
+                                "Vertex AI provider '{}' not configured",
+                                custom_name
+                            )));
+                        }
+                    }
+                } else {
+                    return Err(crate::error::Error::Auth(
+                        "Vertex AI provider requires a custom provider name".into(),
+                    ));
+                }
+            }
+            ModelProvider::Mistral => {
+                if let Some(custom_name) = &self.current_custom_provider {
+                    match self.mistral_providers.get_mut(custom_name) {
+                        Some(p) => {
+                            p.set_model(self.current_model_id.clone());
+                            p.chat(request_messages, output).await?
+                        }
+                        None => {
+                            return Err(crate::error::Error::Auth(format!(
+                                "Mistral provider '{}' not configured",
+                                custom_name
+                            )));
+                        }
+                    }
+                } else {
+                    return Err(crate::error::Error::Auth(
+                        "Mistral provider requires a custom provider name".into(),
+                    ));
+                }
+            }
+        })
+    }
+}
 
-        let mut new_messages = vec![summary_message];
-        new_messages.extend(to_preserve);
-        *messages = new_messages;
+/// The text of the prompt that starts this turn, or `None` when the last
+/// message isn't a prompt.
+fn submitted_prompt(messages: &[Message]) -> Option<String> {
+    let message = messages.last()?;
+    if message.role != Role::User || message.is_tool_result_only() {
+        return None;
+    }
+    match &message.content {
+        MessageContent::Text(prompt) => Some(prompt.clone()),
+        MessageContent::Blocks(blocks) => Some(
+            blocks
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        ),
+    }
+}
 
-        Ok(compaction::CompactionResult { messages_compacted })
+/// Append `text` to the prompt that starts this turn. Does nothing when the
+/// last message isn't a prompt, as when continuing after tool results.
+fn append_to_prompt(messages: &mut [Message], text: String) {
+    let Some(message) = messages.last_mut() else {
+        return;
+    };
+    if message.role != Role::User || message.is_tool_result_only() {
+        return;
+    }
+    match &mut message.content {
+        MessageContent::Text(prompt) => {
+            prompt.push_str("\n\n");
+            prompt.push_str(&text);
+        }
+        MessageContent::Blocks(blocks) => blocks.push(ContentBlock::Text { text }),
     }
 }
 
@@ -1195,7 +2141,7 @@ pub(crate) fn parse_model_spec(spec: &str) -> (ModelProvider, String, Option<Str
             "openrouter" => return (ModelProvider::OpenRouter, model.to_string(), None),
             "zen" => return (ModelProvider::OpenCodeZen, model.to_string(), None),
             _ => {
-                // Check if it's a custom OpenAI-compatible or Antigravity provider
+                // Check if it's a custom OpenAI-compatible, Antigravity, Ollama, Vertex AI or Mistral provider
                 if let Ok(config) = crate::config::ConfigFile::load() {
                     if config
                         .get_provider(prefix)
@@ -1217,6 +2163,36 @@ pub(crate) fn parse_model_spec(spec: &str) -> (ModelProvider, String, Option<Str
                             Some(prefix.to_string()),
                         );
                     }
+                    if config
+                        .get_provider(prefix)
+                        .is_some_and(|p| p.as_ollama().is_some())
+                    {
+                        return (
+                            ModelProvider::Ollama,
+                            model.to_string(),
+                            Some(prefix.to_string()),
+                        );
+                    }
+                    if config
+                        .get_provider(prefix)
+                        .is_some_and(|p| p.as_vertex().is_some())
+                    {
+                        return (
+                            ModelProvider::Vertex,
+                            model.to_string(),
+                            Some(prefix.to_string()),
+                        );
+                    }
+                    if config
+                        .get_provider(prefix)
+                        .is_some_and(|p| p.as_mistral().is_some())
+                    {
+                        return (
+                            ModelProvider::Mistral,
+                            model.to_string(),
+                            Some(prefix.to_string()),
+                        );
+                    }
                 }
                 // Default to Zen if not found
                 ModelProvider::OpenCodeZen
@@ -1228,3 +2204,82 @@ pub(crate) fn parse_model_spec(spec: &str) -> (ModelProvider, String, Option<Str
     // Default to Zen for unqualified model names
     (ModelProvider::OpenCodeZen, spec.to_string(), None)
 }
+
+/// Decide whether to continue a response cut off by the output token limit.
+async fn should_continue_truncated(
+    config: &crate::config::MaxTokensContinuationConfig,
+    continuations: usize,
+    interrupted: &Arc<AtomicBool>,
+    output: &crate::output::OutputContext,
+) -> bool {
+    use crate::config::ContinuationMode;
+
+    const CUT_OFF: &str = "Response was cut off by the output token limit";
+    match config.mode {
+        ContinuationMode::Off => {
+            crate::output::emit_warning(output, &format!("{}.", CUT_OFF));
+            false
+        }
+        _ if continuations >= config.max_continuations => {
+            crate::output::emit_warning(
+                output,
+                &format!(
+                    "{} and the continuation limit ({}) was reached.",
+                    CUT_OFF, config.max_continuations
+                ),
+            );
+            false
+        }
+        ContinuationMode::Auto => {
+            crate::output::emit_warning(output, &format!("{}, continuing.", CUT_OFF));
+            true
+        }
+        ContinuationMode::Ask if crate::watchdog::is_interactive() => {
+            crate::watchdog::confirm(&format!("{}. Continue it?", CUT_OFF), interrupted, output)
+                .await
+        }
+        ContinuationMode::Ask => {
+            crate::output::emit_warning(output, &format!("{}.", CUT_OFF));
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_reasoning_level() {
+        // Models with variants switch to the matching one
+        let (model, state) =
+            resolve_reasoning_level(ModelProvider::OpenAi, "gpt-5.2-codex#medium", "high").unwrap();
+        assert_eq!(model, "gpt-5.2-codex#high");
+        assert_eq!(state, ThinkingState::new(true, Some("high".to_string())));
+        let err = resolve_reasoning_level(ModelProvider::OpenAi, "gpt-5.2-codex#medium", "off")
+            .unwrap_err();
+        assert!(
+            err.contains("available: low, medium, high, xhigh"),
+            "{}",
+            err
+        );
+
+        // Others keep the model and carry the level in the thinking mode
+        let (model, state) =
+            resolve_reasoning_level(ModelProvider::OpenAiCompat, "qwen3", "low").unwrap();
+        assert_eq!(model, "qwen3");
+        assert_eq!(state, ThinkingState::new(true, Some("low".to_string())));
+        let (_, state) =
+            resolve_reasoning_level(ModelProvider::OpenAiCompat, "qwen3", "off").unwrap();
+        assert_eq!(state, ThinkingState::new(false, None));
+    }
+
+    #[test]
+    fn test_validate_thinking_budget() {
+        assert!(validate_thinking_budget(ModelProvider::Claude, 8000).is_ok());
+        assert!(validate_thinking_budget(ModelProvider::Claude, 16000).is_err());
+        assert!(validate_thinking_budget(ModelProvider::Antigravity, 32000).is_ok());
+        assert!(validate_thinking_budget(ModelProvider::Antigravity, 100_000).is_err());
+        assert!(validate_thinking_budget(ModelProvider::OpenAiCompat, 100_000).is_ok());
+    }
+}
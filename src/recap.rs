@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! The `/recap` command.
+//!
+//! `/recap` asks a (possibly cheaper) model for a short summary of what was
+//! done in the session, for a standup or a worklog. Unlike compaction, the
+//! conversation is left untouched. `/recap save` also appends the summary to
+//! `WORKLOG.md` in the working directory.
+
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::provider::{ContentBlock, Message, MessageContent, Role};
+use crate::session::truncate_str;
+
+/// File the recap is appended to by `/recap save`.
+pub(crate) const WORKLOG_FILE: &str = "WORKLOG.md";
+
+/// Longest excerpt of a single message included in the request.
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+const RECAP_SYSTEM_PROMPT: &str = r#"You are writing a short recap of a session between a user and a coding assistant, for the user to read out at a standup or keep in a worklog.
+
+Write in the first person plural, in Markdown, with these sections and nothing else:
+
+**Done** - a few bullets on what was accomplished.
+**Files changed** - the files that were changed, if any.
+**Commands run** - only the notable commands (builds, tests, migrations), if any.
+**Open** - unfinished work, open questions and TODOs, if any.
+
+Leave out empty sections. Keep it under 200 words and do not invent anything that is not in the transcript."#;
+
+/// Files written and commands run by the assistant's tool calls.
+#[derive(Debug, Default, PartialEq)]
+struct Activity {
+    files: BTreeSet<String>,
+    commands: Vec<String>,
+}
+
+fn activity(messages: &[Message]) -> Activity {
+    let mut activity = Activity::default();
+    for message in messages.iter().filter(|m| m.role == Role::Assistant) {
+        let MessageContent::Blocks(blocks) = &message.content else {
+            continue;
+        };
+        for block in blocks {
+            let ContentBlock::ToolUse { name, input, .. } = block else {
+                continue;
+            };
+            match name.as_str() {
+                "file_edit" | "file_write" => {
+                    if let Some(path) = input.get("filePath").and_then(|v| v.as_str()) {
+                        activity.files.insert(path.to_string());
+                    }
+                }
+                "apply_patch" => {
+                    activity.files.extend(crate::tools::patch_paths(input));
+                }
+                "bash" => {
+                    if let Some(command) = input.get("command").and_then(|v| v.as_str()) {
+                        activity.commands.push(command.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    activity
+}
+
+/// Build the request asking for a recap of `messages`.
+pub(crate) fn request(messages: &[Message]) -> Vec<Message> {
+    let activity = activity(messages);
+    let mut prompt = String::new();
+    if !activity.files.is_empty() {
+        prompt.push_str("Files changed:\n");
+        for file in &activity.files {
+            prompt.push_str(&format!("- {}\n", file));
+        }
+        prompt.push('\n');
+    }
+    if !activity.commands.is_empty() {
+        prompt.push_str("Commands run:\n");
+        for command in &activity.commands {
+            prompt.push_str(&format!("- {}\n", truncate_str(command, 200)));
+        }
+        prompt.push('\n');
+    }
+
+    prompt.push_str("Transcript:\n\n");
+    for message in messages {
+        let speaker = match message.role {
+            Role::User if message.is_tool_result_only() => continue,
+            Role::User => "User",
+            Role::Assistant => "Assistant",
+            Role::System => continue,
+        };
+        let text = match &message.content {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Blocks(blocks) => blocks
+                .iter()
+                .filter_map(|b| match b {
+                    ContentBlock::Text { text } => Some(text.as_str()),
+                    ContentBlock::Summary { summary, .. } => Some(summary.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+        if !text.trim().is_empty() {
+            prompt.push_str(&format!(
+                "{}: {}\n\n",
+                speaker,
+                truncate_str(&text, MAX_MESSAGE_CHARS)
+            ));
+        }
+    }
+
+    vec![Message::system(RECAP_SYSTEM_PROMPT), Message::user(prompt)]
+}
+
+/// Append `recap` to the worklog in `working_dir` under a dated heading.
+pub(crate) fn append_worklog(working_dir: &Path, recap: &str) -> std::io::Result<PathBuf> {
+    let path = working_dir.join(WORKLOG_FILE);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    let heading = chrono::Local::now().format("%Y-%m-%d %H:%M");
+    write!(file, "## {}\n\n{}\n\n", heading, recap.trim())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activity() {
+        let messages = vec![
+            Message::user("Fix the parser"),
+            Message::assistant_blocks(vec![
+                ContentBlock::ToolUse {
+                    id: "1".to_string(),
+                    name: "file_edit".to_string(),
+                    input: serde_json::json!({"filePath": "src/parser.rs"}),
+                    thought_signature: None,
+                },
+                ContentBlock::ToolUse {
+                    id: "2".to_string(),
+                    name: "bash".to_string(),
+                    input: serde_json::json!({"command": "cargo test"}),
+                    thought_signature: None,
+                },
+                ContentBlock::ToolUse {
+                    id: "3".to_string(),
+                    name: "file_write".to_string(),
+                    input: serde_json::json!({"filePath": "src/parser.rs"}),
+                    thought_signature: None,
+                },
+            ]),
+        ];
+        let activity = activity(&messages);
+        assert_eq!(
+            activity.files.into_iter().collect::<Vec<_>>(),
+            vec!["src/parser.rs"]
+        );
+        assert_eq!(activity.commands, vec!["cargo test"]);
+
+        let request = request(&messages);
+        assert_eq!(request.len(), 2);
+        assert_eq!(request[0].role, Role::System);
+    }
+
+    #[test]
+    fn test_append_worklog() {
+        let dir = tempfile::tempdir().unwrap();
+        append_worklog(dir.path(), "First").unwrap();
+        let path = append_worklog(dir.path(), "Second\n").unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert_eq!(content.matches("## ").count(), 2);
+        assert!(content.find("First").unwrap() < content.find("Second").unwrap());
+        assert!(content.ends_with("Second\n\n"));
+    }
+}
@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! `/rollback`: undo a bad turn in one step.
+//!
+//! Combines `/undo` (drop the turn from the conversation), `/revert` (restore
+//! the files the edit and write tools changed) and, optionally, `git restore`
+//! of tracked files modified since the turn started, which catches changes
+//! made through bash that checkpoints don't see.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::provider::{Message, MessageContent, Role};
+
+/// What `/rollback` would revert.
+pub(crate) struct RollbackPlan {
+    /// The prompt of the turn removed from the conversation
+    pub prompt: Option<String>,
+    /// Files restored from checkpoints
+    pub files: Vec<PathBuf>,
+    /// Tracked files restored with `git restore`, when asked for
+    pub git_files: Vec<PathBuf>,
+}
+
+impl RollbackPlan {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.prompt.is_none() && self.files.is_empty() && self.git_files.is_empty()
+    }
+}
+
+/// Work out what to revert for the most recent turn.
+pub(crate) fn plan(
+    messages: &[Message],
+    checkpoints: &crate::checkpoints::Checkpoints,
+    working_dir: &Path,
+) -> RollbackPlan {
+    let files = checkpoints.latest();
+    let git_files = match checkpoints.turn_started() {
+        Some(since) => modified_tracked_files(working_dir, since)
+            .into_iter()
+            .filter(|path| !files.contains(path))
+            .collect(),
+        None => Vec::new(),
+    };
+    RollbackPlan {
+        prompt: last_prompt(messages),
+        files,
+        git_files,
+    }
+}
+
+/// The text of the most recent prompt the user typed.
+fn last_prompt(messages: &[Message]) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .find(|m| m.role == Role::User && !m.is_tool_result_only())
+        .map(|m| match &m.content {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Blocks(blocks) => blocks
+                .iter()
+                .filter_map(|b| match b {
+                    crate::provider::ContentBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+        })
+}
+
+/// Slack for file systems that store modification times coarsely, which can
+/// put a write made just after the turn started slightly before it.
+const MTIME_SLACK: Duration = Duration::from_secs(1);
+
+/// Tracked files with unstaged changes, modified at or after `since`.
+fn modified_tracked_files(working_dir: &Path, since: SystemTime) -> Vec<PathBuf> {
+    let since = since - MTIME_SLACK;
+    let Ok(output) = std::process::Command::new("git")
+        .args(["diff", "--name-only", "-z"])
+        .current_dir(working_dir)
+        .output()
+    else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+    let Some(root) = git_root(working_dir) else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|name| !name.is_empty())
+        .map(|name| root.join(name))
+        .filter(|path| {
+            std::fs::metadata(path)
+                .and_then(|meta| meta.modified())
+                .is_ok_and(|modified| modified >= since)
+        })
+        .collect()
+}
+
+fn git_root(working_dir: &Path) -> Option<PathBuf> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .current_dir(working_dir)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Discard the working tree changes to `files` with `git restore`.
+pub(crate) fn git_restore(working_dir: &Path, files: &[PathBuf]) -> std::io::Result<()> {
+    let output = std::process::Command::new("git")
+        .arg("restore")
+        .arg("--")
+        .args(files)
+        .current_dir(working_dir)
+        .output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_plan_and_git_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        git(&root, &["init", "-q"]);
+        std::fs::write(root.join("old.txt"), "old").unwrap();
+        std::fs::write(root.join("bash.txt"), "one").unwrap();
+        git(&root, &["add", "."]);
+        git(
+            &root,
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-qm",
+                "init",
+            ],
+        );
+        // Changed before the turn: not part of the rollback
+        std::fs::write(root.join("old.txt"), "mine").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(root.join("old.txt"))
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(5))
+            .unwrap();
+
+        let checkpoints = crate::checkpoints::Checkpoints::default();
+        checkpoints.begin_turn();
+        std::fs::write(root.join("bash.txt"), "two").unwrap();
+
+        let messages = vec![
+            Message::user("delete everything"),
+            Message::assistant_blocks(vec![]),
+        ];
+        let plan = plan(&messages, &checkpoints, &root);
+        assert_eq!(plan.prompt.as_deref(), Some("delete everything"));
+        assert!(plan.files.is_empty());
+        assert_eq!(plan.git_files, vec![root.join("bash.txt")]);
+
+        git_restore(&root, &plan.git_files).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("bash.txt")).unwrap(),
+            "one"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("old.txt")).unwrap(),
+            "mine"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Files the model has seen, so changes made to them outside the conversation
+//! can be pointed out.
+//!
+//! The content of each file read, edited or written by a tool, or included
+//! with `@path`, is remembered. When the next prompt is sent, files that have
+//! changed on disk since are listed with a short diff, so a long session
+//! doesn't keep reasoning from an old copy after the user edits by hand.
+
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Files larger than this are compared by hash only, without a diff.
+const MAX_SNAPSHOT_BYTES: usize = 256 * 1024;
+
+/// Diff lines shown per file before the rest is left out.
+const MAX_DIFF_LINES: usize = 40;
+
+/// Changed files listed in one notice.
+const MAX_FILES: usize = 10;
+
+/// What a file looked like when the model last saw it.
+struct Snapshot {
+    hash: u64,
+    /// The text, if small enough to diff against
+    text: Option<String>,
+}
+
+impl Snapshot {
+    fn new(content: &[u8]) -> Self {
+        let text = (content.len() <= MAX_SNAPSHOT_BYTES)
+            .then(|| String::from_utf8(content.to_vec()).ok())
+            .flatten();
+        Self {
+            hash: hash(content),
+            text,
+        }
+    }
+}
+
+pub(crate) fn hash(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Snapshots of the files seen in this session, shared through
+/// [`crate::services::Services`].
+#[derive(Default)]
+pub(crate) struct SeenFiles {
+    files: Mutex<BTreeMap<PathBuf, Snapshot>>,
+}
+
+impl SeenFiles {
+    /// Remember the current content of `path`.
+    pub(crate) fn record(&self, path: &Path) {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if let Ok(content) = std::fs::read(&path) {
+            self.files
+                .lock()
+                .unwrap()
+                .insert(path, Snapshot::new(&content));
+        }
+    }
+
+    /// Describe the files that changed on disk since they were seen, and
+    /// remember their new content. `None` if nothing changed.
+    pub(crate) fn take_changes(&self) -> Option<String> {
+        let mut files = self.files.lock().unwrap();
+        let mut changes = Vec::new();
+        let mut deleted = Vec::new();
+
+        for (path, snapshot) in files.iter_mut() {
+            let Ok(content) = std::fs::read(path) else {
+                deleted.push(path.clone());
+                continue;
+            };
+            if hash(&content) == snapshot.hash {
+                continue;
+            }
+            let new = Snapshot::new(&content);
+            changes.push(describe_change(path, snapshot.text.as_deref(), &new));
+            *snapshot = new;
+        }
+        for path in &deleted {
+            files.remove(path);
+            changes.push(format!("{} was deleted.", display(path)));
+        }
+
+        if changes.is_empty() {
+            return None;
+        }
+        let mut notice = String::from(
+            "[These files changed on disk since you last saw them; your earlier copies are out of date.]",
+        );
+        let total = changes.len();
+        for change in changes.into_iter().take(MAX_FILES) {
+            notice.push_str("\n\n");
+            notice.push_str(&change);
+        }
+        if total > MAX_FILES {
+            notice.push_str(&format!("\n\n({} more files changed)", total - MAX_FILES));
+        }
+        Some(notice)
+    }
+}
+
+/// `path` relative to the working directory if it is inside it.
+fn display(path: &Path) -> String {
+    let root = crate::tools::paths::workspace_root();
+    path.strip_prefix(&root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+fn describe_change(path: &Path, old: Option<&str>, new: &Snapshot) -> String {
+    let (Some(old), Some(new)) = (old, new.text.as_deref()) else {
+        return format!("{} changed.", display(path));
+    };
+    let diff = crate::diff::unified_diff(path, old, new, 1);
+    let lines: Vec<&str> = diff.unified_diff.lines().collect();
+    let mut text = format!("{}:\n```diff\n", display(path));
+    for line in lines.iter().take(MAX_DIFF_LINES) {
+        text.push_str(line);
+        text.push('\n');
+    }
+    if lines.len() > MAX_DIFF_LINES {
+        text.push_str(&format!(
+            "... ({} more diff lines)\n",
+            lines.len() - MAX_DIFF_LINES
+        ));
+    }
+    text.push_str("```");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let edited = dir.path().join("edited.rs");
+        let removed = dir.path().join("removed.rs");
+        let same = dir.path().join("same.rs");
+        std::fs::write(&edited, "fn main() {\n    one();\n}\n").unwrap();
+        std::fs::write(&removed, "x\n").unwrap();
+        std::fs::write(&same, "y\n").unwrap();
+
+        let seen = SeenFiles::default();
+        seen.record(&edited);
+        seen.record(&removed);
+        seen.record(&same);
+        assert!(seen.take_changes().is_none());
+
+        std::fs::write(&edited, "fn main() {\n    two();\n}\n").unwrap();
+        std::fs::remove_file(&removed).unwrap();
+        let notice = seen.take_changes().unwrap();
+        assert!(notice.contains("-    one();\n+    two();"));
+        assert!(notice.contains("removed.rs was deleted."));
+        assert!(!notice.contains("same.rs"));
+
+        // Reported changes are not reported again.
+        assert!(seen.take_changes().is_none());
+    }
+}
@@ -2,19 +2,44 @@
 // Copyright (c) 2025 Jason Ish
 
 //! Service container for dependency injection.
+//!
+//! Embedders get the [`Services`] of an agent from
+//! [`Agent::services`](crate::Agent::services), to read and change its
+//! security mode.
 
 use std::sync::Arc;
 use std::sync::RwLock;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use crate::checkpoints::Checkpoints;
+use crate::exploration_cache::ExplorationCache;
 use crate::lsp::LspManager;
 use crate::mcp::McpManager;
+use crate::permissions::Permissions;
+use crate::seen_files::SeenFiles;
+use crate::session::Journal;
+use crate::timeline::Timeline;
+use crate::tools::post_edit::PostEditChecks;
 
 /// Container for shared services. Clone is cheap (uses Arc).
 #[derive(Clone)]
-pub(crate) struct Services {
-    pub mcp: Arc<McpManager>,
-    pub lsp: Arc<LspManager>,
+pub struct Services {
+    pub(crate) mcp: Arc<McpManager>,
+    pub(crate) lsp: Arc<LspManager>,
+    /// Approvals for bash commands and writes outside the working directory.
+    pub(crate) permissions: Arc<Permissions>,
+    /// Original content of files changed by tools, for /revert.
+    pub(crate) checkpoints: Arc<Checkpoints>,
+    /// Files the model has seen, to point out later changes to them.
+    pub(crate) seen_files: Arc<SeenFiles>,
+    /// File reads shared between delegated tasks.
+    pub(crate) exploration: Arc<ExplorationCache>,
+    /// Formatters and linters run after the edit and write tools.
+    pub(crate) post_edit_checks: Arc<PostEditChecks>,
+    /// Timing of the model requests and tool calls of each turn, for /timeline.
+    pub(crate) timeline: Arc<Timeline>,
+    /// Messages of the current turn, saved as they arrive.
+    pub(crate) journal: Arc<Journal>,
     /// Interrupt flag for cancellable operations (e.g., bash commands).
     interrupted: Option<Arc<AtomicBool>>,
     /// Current chat session identifier (used for provider request metadata).
@@ -30,6 +55,13 @@ impl Services {
         Self {
             mcp: crate::mcp::manager(),
             lsp: crate::lsp::manager(),
+            permissions: Arc::new(Permissions::load()),
+            checkpoints: Arc::new(Checkpoints::default()),
+            seen_files: Arc::new(SeenFiles::default()),
+            exploration: Arc::new(ExplorationCache::default()),
+            post_edit_checks: Arc::new(PostEditChecks::load()),
+            timeline: Arc::new(Timeline::default()),
+            journal: Arc::new(Journal::default()),
             interrupted: None,
             session_id: Arc::new(RwLock::new(None)),
             sandbox_enabled: Arc::new(AtomicBool::new(true)),
@@ -43,6 +75,13 @@ impl Services {
         Self {
             mcp: Arc::new(McpManager::new()),
             lsp: Arc::new(LspManager::new()),
+            permissions: Arc::new(Permissions::default()),
+            checkpoints: Arc::new(Checkpoints::default()),
+            seen_files: Arc::new(SeenFiles::default()),
+            exploration: Arc::new(ExplorationCache::default()),
+            post_edit_checks: Arc::new(PostEditChecks::default()),
+            timeline: Arc::new(Timeline::default()),
+            journal: Arc::new(Journal::default()),
             interrupted: None,
             session_id: Arc::new(RwLock::new(None)),
             sandbox_enabled: Arc::new(AtomicBool::new(true)),
@@ -55,6 +94,13 @@ impl Services {
         Self {
             mcp: self.mcp.clone(),
             lsp: self.lsp.clone(),
+            permissions: self.permissions.clone(),
+            checkpoints: self.checkpoints.clone(),
+            seen_files: self.seen_files.clone(),
+            exploration: self.exploration.clone(),
+            post_edit_checks: self.post_edit_checks.clone(),
+            timeline: self.timeline.clone(),
+            journal: self.journal.clone(),
             interrupted: Some(flag),
             session_id: self.session_id.clone(),
             sandbox_enabled: self.sandbox_enabled.clone(),
@@ -62,13 +108,22 @@ impl Services {
         }
     }
 
+    /// Return a clone whose read-only mode is on and independent of this one.
+    pub(crate) fn read_only_view(&self) -> Self {
+        Self {
+            read_only: Arc::new(AtomicBool::new(true)),
+            ..self.clone()
+        }
+    }
+
     pub(crate) fn set_session_id(&self, session_id: Option<String>) {
         if let Ok(mut guard) = self.session_id.write() {
             *guard = session_id;
         }
     }
 
-    pub(crate) fn session_id(&self) -> Option<String> {
+    /// Identifier of the current session, sent with provider requests.
+    pub fn session_id(&self) -> Option<String> {
         self.session_id.read().ok().and_then(|guard| guard.clone())
     }
 
@@ -80,22 +135,22 @@ impl Services {
     }
 
     /// Check if sandbox is enabled.
-    pub(crate) fn is_sandbox_enabled(&self) -> bool {
+    pub fn is_sandbox_enabled(&self) -> bool {
         self.sandbox_enabled.load(Ordering::SeqCst)
     }
 
     /// Set sandbox enabled/disabled.
-    pub(crate) fn set_sandbox_enabled(&self, enabled: bool) {
+    pub fn set_sandbox_enabled(&self, enabled: bool) {
         self.sandbox_enabled.store(enabled, Ordering::SeqCst);
     }
 
     /// Check if read-only mode is enabled.
-    pub(crate) fn is_read_only(&self) -> bool {
+    pub fn is_read_only(&self) -> bool {
         self.read_only.load(Ordering::SeqCst)
     }
 
     /// Set read-only mode enabled/disabled.
-    pub(crate) fn set_read_only(&self, enabled: bool) {
+    pub fn set_read_only(&self, enabled: bool) {
         self.read_only.store(enabled, Ordering::SeqCst);
     }
 
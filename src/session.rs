@@ -11,11 +11,22 @@
 //! ~/.cache/henri/sessions/
 //!   {dir_hash}/                    # Directory per working directory
 //!     {session_id}.json            # One file per session
+//!     {session_id}.compacted.jsonl # Pre-compaction transcripts (if compacted)
+//!     {session_id}.journal.jsonl   # Messages since the last save (mid-turn)
 //! ```
+//!
+//! The session file is rewritten at the start and end of each turn. In
+//! between, each assistant message and tool result is appended to the journal
+//! as it arrives, so a crash mid-turn loses at most the message in flight.
+//! Loading a session picks up whatever the journal holds.
+//!
+//! Embedders can find saved sessions with [`list_sessions`] and continue one
+//! with [`Agent::resume`](crate::Agent::resume).
 
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use base64::{Engine, engine::general_purpose::STANDARD};
 use chrono::{DateTime, Utc};
@@ -90,6 +101,9 @@ pub(crate) enum SerializableHistoryEvent {
     AutoCompact {
         message: String,
     },
+    Note {
+        text: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -192,6 +206,7 @@ impl From<&HistoryEvent> for SerializableHistoryEvent {
                 language: language.clone(),
                 summary: summary.clone(),
             },
+            HistoryEvent::Note(text) => SerializableHistoryEvent::Note { text: text.clone() },
             HistoryEvent::AutoCompact { message } => SerializableHistoryEvent::AutoCompact {
                 message: message.clone(),
             },
@@ -275,6 +290,7 @@ impl From<&SerializableHistoryEvent> for HistoryEvent {
                 language: language.clone(),
                 summary: summary.clone(),
             },
+            SerializableHistoryEvent::Note { text } => HistoryEvent::Note(text.clone()),
             SerializableHistoryEvent::AutoCompact { message } => HistoryEvent::AutoCompact {
                 message: message.clone(),
             },
@@ -314,17 +330,52 @@ pub(crate) struct SessionMeta {
     #[serde(default)]
     #[serde(skip_serializing_if = "std::ops::Not::not")]
     pub read_only: bool,
+
+    /// Sampling seed in effect, if one was set
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// Whether old turns are dropped instead of compacted
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub sliding_window: bool,
+
+    /// Pinned with `/pin-session`: kept by any cleanup of old sessions and
+    /// marked in the sessions menu
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+
+    /// Short title, generated after the first exchange or set with `/rename`
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Session this one was forked from with `/fork`
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<String>,
+
+    /// Dollar cost of the requests made in the session, when any were priced
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
 }
 
 /// Summary info for session listing (without loading full messages)
 #[derive(Debug, Clone)]
-pub(crate) struct SessionInfo {
+pub struct SessionInfo {
     pub id: String,
+    pub working_directory: PathBuf,
     pub saved_at: DateTime<Utc>,
-    pub _model_id: String,
-    pub _message_count: usize,
+    pub(crate) _model_id: String,
+    pub message_count: usize,
     /// First user message (truncated) for preview
     pub preview: Option<String>,
+    pub pinned: bool,
+    pub title: Option<String>,
+    pub forked_from: Option<String>,
 }
 
 /// Session state loaded from disk (metadata + messages).
@@ -349,6 +400,9 @@ pub(crate) struct RestoredSession {
     pub model_id: String,
     pub thinking_enabled: bool,
     pub read_only: bool,
+    pub seed: Option<u64>,
+    pub sliding_window: bool,
+    pub cost: f64,
     pub _state: SessionState, // Keep original state for replay
 }
 
@@ -362,6 +416,9 @@ impl RestoredSession {
             model_id: state.meta.model_id.clone(),
             thinking_enabled: state.meta.thinking_enabled,
             read_only: state.meta.read_only,
+            seed: state.meta.seed,
+            sliding_window: state.meta.sliding_window,
+            cost: state.meta.cost.unwrap_or_default(),
             _state: state.clone(),
         }
     }
@@ -611,6 +668,187 @@ fn get_session_path(dir: &Path, session_id: &str) -> PathBuf {
     sessions_dir_for_path(dir).join(format!("{}.json", session_id))
 }
 
+/// Get the path for a session's archive of pre-compaction transcripts.
+fn get_compacted_transcript_path(dir: &Path, session_id: &str) -> PathBuf {
+    sessions_dir_for_path(dir).join(format!("{}.compacted.jsonl", session_id))
+}
+
+/// Get the path of the journal kept next to a session file.
+fn get_journal_path(session_path: &Path) -> PathBuf {
+    session_path.with_extension("journal.jsonl")
+}
+
+/// Messages appended to the current session between full saves.
+#[derive(Default)]
+pub(crate) struct Journal {
+    path: Mutex<Option<PathBuf>>,
+}
+
+impl Journal {
+    /// Journal the messages of `session_id` from now on.
+    pub(crate) fn open(&self, working_directory: &Path, session_id: &str) {
+        let path = get_journal_path(&get_session_path(working_directory, session_id));
+        *self.path.lock().unwrap_or_else(|e| e.into_inner()) = Some(path);
+    }
+
+    /// Append a message and flush it to disk. Nothing is journaled until a
+    /// session is opened, and errors are ignored: the next full save still
+    /// has the message.
+    pub(crate) fn append(&self, message: &Message) {
+        let Some(path) = self.path.lock().unwrap_or_else(|e| e.into_inner()).clone() else {
+            return;
+        };
+        let _ = append_journal(&path, message);
+    }
+}
+
+fn append_journal(path: &Path, message: &Message) -> std::io::Result<()> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let line = serde_json::to_string(&SerializableMessage::from(message))
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", line)?;
+    file.sync_data()
+}
+
+/// Messages left in a journal by a turn that never finished.
+///
+/// Tool results are journaled one at a time, so consecutive ones are merged
+/// back into a single message, and tool calls still running when Henri
+/// stopped are given an error result so the conversation can continue.
+fn recover_journal(path: &Path) -> Vec<SerializableMessage> {
+    let Ok(file) = File::open(path) else {
+        return Vec::new();
+    };
+    let mut messages: Vec<Message> = Vec::new();
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(msg) = serde_json::from_str::<SerializableMessage>(&line) else {
+            continue;
+        };
+        let message: Message = (&msg).into();
+        if message.is_tool_result_only()
+            && let Some(last) = messages.last_mut()
+            && last.is_tool_result_only()
+            && let (MessageContent::Blocks(blocks), MessageContent::Blocks(more)) =
+                (&mut last.content, message.content.clone())
+        {
+            blocks.extend(more);
+            continue;
+        }
+        messages.push(message);
+    }
+
+    if let Some(call_index) = messages.iter().rposition(|m| m.role == Role::Assistant) {
+        let mut missing: Vec<String> = match &messages[call_index].content {
+            MessageContent::Blocks(blocks) => blocks
+                .iter()
+                .filter_map(|b| match b {
+                    ContentBlock::ToolUse { id, .. } => Some(id.clone()),
+                    _ => None,
+                })
+                .collect(),
+            MessageContent::Text(_) => Vec::new(),
+        };
+        for message in &messages[call_index + 1..] {
+            if let MessageContent::Blocks(blocks) = &message.content {
+                for block in blocks {
+                    if let ContentBlock::ToolResult { tool_use_id, .. } = block {
+                        missing.retain(|id| id != tool_use_id);
+                    }
+                }
+            }
+        }
+        let results = missing.into_iter().map(|id| ContentBlock::ToolResult {
+            tool_use_id: id,
+            content: "Henri exited before this tool finished".to_string(),
+            is_error: true,
+            data: None,
+            mime_type: None,
+        });
+        match messages.last_mut() {
+            Some(last) if last.is_tool_result_only() => {
+                if let MessageContent::Blocks(blocks) = &mut last.content {
+                    blocks.extend(results);
+                }
+            }
+            _ => {
+                let results: Vec<ContentBlock> = results.collect();
+                if !results.is_empty() {
+                    messages.push(Message {
+                        role: Role::User,
+                        content: MessageContent::Blocks(results),
+                    });
+                }
+            }
+        }
+    }
+
+    messages.iter().map(SerializableMessage::from).collect()
+}
+
+/// Full message history that was replaced by a compaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CompactedTranscript {
+    /// When the compaction happened
+    pub compacted_at: DateTime<Utc>,
+    /// Messages as they were before compaction
+    pub messages: Vec<SerializableMessage>,
+}
+
+/// Append the pre-compaction messages to the session's transcript archive.
+///
+/// One JSON line is written per compaction so repeated compactions keep every
+/// generation of history. Returns the archive path.
+pub(crate) fn archive_compacted_transcript(
+    working_directory: &Path,
+    session_id: &str,
+    messages: &[Message],
+) -> std::io::Result<PathBuf> {
+    let path = get_compacted_transcript_path(working_directory, session_id);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let transcript = CompactedTranscript {
+        compacted_at: Utc::now(),
+        messages: messages.iter().map(SerializableMessage::from).collect(),
+    };
+    let json = serde_json::to_string(&transcript)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    writeln!(file, "{}", json)?;
+
+    Ok(path)
+}
+
+/// Load all archived pre-compaction transcripts for a session, oldest first.
+pub(crate) fn load_compacted_transcripts(
+    working_directory: &Path,
+    session_id: &str,
+) -> Vec<CompactedTranscript> {
+    let path = get_compacted_transcript_path(working_directory, session_id);
+    let Ok(file) = File::open(&path) else {
+        return Vec::new();
+    };
+
+    BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
 /// Generate a new session ID.
 ///
 /// Uses UUIDv7 for time-sortable uniqueness.
@@ -624,6 +862,7 @@ pub(crate) fn generate_session_id() -> String {
 ///
 /// If `session_id` is None, generates a new session ID.
 /// Returns the session ID used.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn save_session(
     working_directory: &Path,
     messages: &[Message],
@@ -631,12 +870,14 @@ pub(crate) fn save_session(
     model_id: &str,
     thinking_enabled: bool,
     read_only: bool,
+    seed: Option<u64>,
     session_id: Option<&str>,
 ) -> std::io::Result<String> {
     let session_id = session_id
         .map(|s| s.to_string())
         .unwrap_or_else(generate_session_id);
     let session_path = get_session_path(working_directory, &session_id);
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
 
     // Ensure the sessions directory exists
     if let Some(parent) = session_path.parent() {
@@ -664,6 +905,22 @@ pub(crate) fn save_session(
         model_id: model_id.to_string(),
         thinking_enabled,
         read_only,
+        seed,
+        sliding_window: crate::sliding_window::is_enabled(),
+        pinned: false,
+        title: None,
+        forked_from: None,
+        cost: Some(crate::usage::session_cost()).filter(|cost| *cost > 0.0),
+    };
+    // Saving rewrites the whole file; keep the pin, title and parent.
+    let meta = match read_session_meta(&session_path) {
+        Some(old) => SessionMeta {
+            pinned: old.pinned,
+            title: old.title,
+            forked_from: old.forked_from,
+            ..meta
+        },
+        None => meta,
     };
 
     let state = SessionState {
@@ -672,7 +929,24 @@ pub(crate) fn save_session(
         cli_history,
     };
 
-    let mut file = File::create(&session_path)?;
+    write_session_file(&session_path, &state)?;
+
+    // The messages are all in the session file now.
+    match fs::remove_file(get_journal_path(&session_path)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+
+    Ok(session_id)
+}
+
+/// Write session state to `path` in JSONL format.
+///
+/// The file is written beside `path` and renamed over it, so a crash while
+/// saving leaves the previous save intact.
+fn write_session_file(path: &Path, state: &SessionState) -> std::io::Result<()> {
+    let tmp_path = path.with_extension("json.tmp");
+    let mut file = std::io::BufWriter::new(File::create(&tmp_path)?);
 
     // Write metadata as first line
     let meta_json = serde_json::to_string(&state.meta)
@@ -693,6 +967,139 @@ pub(crate) fn save_session(
         writeln!(file, "{}", history_json)?;
     }
 
+    file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(&tmp_path, path)
+}
+
+/// Value of the `format` field identifying an exported session file.
+const EXPORT_FORMAT: &str = "henri-session";
+
+/// A complete session in a single portable JSON document.
+///
+/// Sessions on disk are keyed by a hash of the working directory, so they can't simply
+/// be copied to another machine. An export carries everything needed to recreate the
+/// session under a different directory.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SessionExport {
+    pub format: String,
+    pub meta: SessionMeta,
+    pub messages: Vec<SerializableMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cli_history: Option<Vec<SerializableHistoryEvent>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compacted_transcripts: Vec<CompactedTranscript>,
+}
+
+/// Find a session of `dir` by ID or unique ID prefix, or the most recent one if no ID
+/// is given.
+fn resolve_session_id(dir: &Path, session_id: Option<&str>) -> std::io::Result<String> {
+    use std::io::{Error, ErrorKind};
+
+    let sessions = list_sessions(dir);
+    let Some(wanted) = session_id else {
+        return sessions
+            .first()
+            .map(|s| s.id.clone())
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "no saved sessions"));
+    };
+
+    if sessions.iter().any(|s| s.id == wanted) {
+        return Ok(wanted.to_string());
+    }
+    let matches: Vec<&SessionInfo> = sessions
+        .iter()
+        .filter(|s| s.id.starts_with(wanted))
+        .collect();
+    match matches.as_slice() {
+        [session] => Ok(session.id.clone()),
+        [] => Err(Error::new(
+            ErrorKind::NotFound,
+            format!("no session matching '{}'", wanted),
+        )),
+        _ => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("'{}' matches {} sessions", wanted, matches.len()),
+        )),
+    }
+}
+
+/// Export a session of `dir`, the most recent one if `session_id` is None.
+pub(crate) fn export_session(
+    dir: &Path,
+    session_id: Option<&str>,
+) -> std::io::Result<SessionExport> {
+    let session_id = resolve_session_id(dir, session_id)?;
+    let state = load_session_by_id(dir, &session_id).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("failed to load session {}", session_id),
+        )
+    })?;
+
+    Ok(SessionExport {
+        format: EXPORT_FORMAT.to_string(),
+        meta: state.meta,
+        messages: state.messages,
+        cli_history: state.cli_history,
+        compacted_transcripts: load_compacted_transcripts(dir, &session_id),
+    })
+}
+
+/// Import an exported session as the most recent session of `dir`.
+///
+/// The session keeps its ID unless `dir` already has a session with that ID.
+/// Returns the ID of the imported session.
+pub(crate) fn import_session(dir: &Path, export: SessionExport) -> std::io::Result<String> {
+    use std::io::{Error, ErrorKind};
+
+    if export.format != EXPORT_FORMAT {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "not an exported Henri session",
+        ));
+    }
+    if export.meta.version > SESSION_VERSION {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "session version {} is newer than this version of Henri supports",
+                export.meta.version
+            ),
+        ));
+    }
+
+    let mut session_id = export.meta.session_id.clone();
+    if session_id.is_empty() || get_session_path(dir, &session_id).exists() {
+        session_id = generate_session_id();
+    }
+
+    let session_path = get_session_path(dir, &session_id);
+    if let Some(parent) = session_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let state = SessionState {
+        meta: SessionMeta {
+            session_id: session_id.clone(),
+            working_directory: dir.to_path_buf(),
+            // Newest, so `henri --continue` picks it up.
+            saved_at: Utc::now(),
+            ..export.meta
+        },
+        messages: export.messages,
+        cli_history: export.cli_history,
+    };
+    write_session_file(&session_path, &state)?;
+
+    if !export.compacted_transcripts.is_empty() {
+        let mut file = File::create(get_compacted_transcript_path(dir, &session_id))?;
+        for transcript in &export.compacted_transcripts {
+            let json = serde_json::to_string(transcript)
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            writeln!(file, "{}", json)?;
+        }
+    }
+
     Ok(session_id)
 }
 
@@ -713,8 +1120,22 @@ pub(crate) fn load_session_by_id(dir: &Path, session_id: &str) -> Option<Session
     load_session_from_path(&session_path)
 }
 
-/// Load session state from a specific path.
+/// Load session state from a specific path, including any messages left in
+/// its journal.
 fn load_session_from_path(path: &Path) -> Option<SessionState> {
+    let mut state = read_session_file(path)?;
+    let recovered = recover_journal(&get_journal_path(path));
+    if !recovered.is_empty() {
+        state.messages.extend(recovered);
+        // The display history predates the recovered messages; replay
+        // rebuilds it from the messages instead.
+        state.cli_history = None;
+    }
+    Some(state)
+}
+
+/// Read a session file as last saved.
+fn read_session_file(path: &Path) -> Option<SessionState> {
     let file = File::open(path).ok()?;
     let reader = BufReader::new(file);
     let mut lines = reader.lines();
@@ -761,7 +1182,7 @@ fn load_session_from_path(path: &Path) -> Option<SessionState> {
 }
 
 /// List all sessions for a directory, sorted by recency (newest first).
-pub(crate) fn list_sessions(dir: &Path) -> Vec<SessionInfo> {
+pub fn list_sessions(dir: &Path) -> Vec<SessionInfo> {
     let sessions_dir = sessions_dir_for_path(dir);
     let mut sessions = Vec::new();
 
@@ -779,10 +1200,68 @@ pub(crate) fn list_sessions(dir: &Path) -> Vec<SessionInfo> {
     }
 
     // Sort by saved_at descending (newest first)
-    sessions.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.saved_at));
+    sessions
+}
+
+/// List the sessions of every working directory, sorted by recency (newest
+/// first).
+pub fn list_all_sessions() -> Vec<SessionInfo> {
+    let mut sessions = Vec::new();
+    let Ok(dirs) = fs::read_dir(sessions_base_dir()) else {
+        return sessions;
+    };
+
+    for dir in dirs.flatten() {
+        let Ok(entries) = fs::read_dir(dir.path()) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Some(info) = load_session_info(&path)
+            {
+                sessions.push(info);
+            }
+        }
+    }
+
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.saved_at));
     sessions
 }
 
+/// Find a session of any working directory by ID or unique ID prefix.
+pub(crate) fn find_session(wanted: &str) -> std::io::Result<SessionInfo> {
+    use std::io::{Error, ErrorKind};
+
+    let sessions = list_all_sessions();
+    if let Some(session) = sessions.iter().find(|s| s.id == wanted) {
+        return Ok(session.clone());
+    }
+    let matches: Vec<&SessionInfo> = sessions
+        .iter()
+        .filter(|s| s.id.starts_with(wanted))
+        .collect();
+    match matches.as_slice() {
+        [session] => Ok((*session).clone()),
+        [] => Err(Error::new(
+            ErrorKind::NotFound,
+            format!("no session matching '{}'", wanted),
+        )),
+        _ => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("'{}' matches {} sessions", wanted, matches.len()),
+        )),
+    }
+}
+
+/// Read only the metadata line of a session file.
+fn read_session_meta(path: &Path) -> Option<SessionMeta> {
+    let file = File::open(path).ok()?;
+    let meta_line = BufReader::new(file).lines().next()?.ok()?;
+    serde_json::from_str(&meta_line).ok()
+}
+
 /// Load just the session info (metadata + message count + preview) without loading all messages.
 fn load_session_info(path: &Path) -> Option<SessionInfo> {
     let file = File::open(path).ok()?;
@@ -823,10 +1302,14 @@ fn load_session_info(path: &Path) -> Option<SessionInfo> {
 
     Some(SessionInfo {
         id: session_id,
+        working_directory: meta.working_directory,
         saved_at: meta.saved_at,
         _model_id: meta.model_id,
-        _message_count: message_count,
+        message_count,
         preview,
+        pinned: meta.pinned,
+        title: meta.title,
+        forked_from: meta.forked_from,
     })
 }
 
@@ -864,6 +1347,147 @@ pub(crate) fn truncate_str(s: &str, max_chars: usize) -> String {
     }
 }
 
+/// Held while a session file is read and rewritten, so a title generated in
+/// the background doesn't race with the session being saved.
+static WRITE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Change the metadata of a saved session.
+fn update_session_meta(
+    dir: &Path,
+    session_id: &str,
+    update: impl FnOnce(&mut SessionMeta),
+) -> std::io::Result<()> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = get_session_path(dir, session_id);
+    // The journal stays as it is, so leave its messages out.
+    let mut state = read_session_file(&path)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "session not found"))?;
+    update(&mut state.meta);
+    write_session_file(&path, &state)
+}
+
+/// Pin or unpin a session.
+pub(crate) fn set_session_pinned(
+    dir: &Path,
+    session_id: &str,
+    pinned: bool,
+) -> std::io::Result<()> {
+    update_session_meta(dir, session_id, |meta| meta.pinned = pinned)
+}
+
+/// Set the title of a session.
+pub(crate) fn set_session_title(dir: &Path, session_id: &str, title: &str) -> std::io::Result<()> {
+    update_session_meta(dir, session_id, |meta| meta.title = Some(title.to_string()))
+}
+
+/// The title of a saved session, if it has one.
+pub(crate) fn session_title(dir: &Path, session_id: &str) -> Option<String> {
+    read_session_meta(&get_session_path(dir, session_id))?.title
+}
+
+/// Copy a saved session to a new session, keeping its first `turns` turns
+/// (all of them when None), so it can be continued without changing the
+/// original. Returns the new session's ID.
+pub(crate) fn fork_session(
+    dir: &Path,
+    session_id: &str,
+    turns: Option<usize>,
+) -> std::io::Result<String> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut state = load_session_from_path(&get_session_path(dir, session_id))
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "session not found"))?;
+
+    if let Some(turns) = turns {
+        let mut seen = 0;
+        let keep = state
+            .messages
+            .iter()
+            .position(|m| {
+                let message: Message = m.into();
+                if message.role == Role::User && !message.is_tool_result_only() {
+                    seen += 1;
+                }
+                seen > turns
+            })
+            .unwrap_or(state.messages.len());
+        if keep < state.messages.len() {
+            state.messages.truncate(keep);
+            // The saved display history covers the turns dropped; replay
+            // rebuilds it from the messages instead.
+            state.cli_history = None;
+        }
+    }
+
+    let fork_id = generate_session_id();
+    state.meta = SessionMeta {
+        session_id: fork_id.clone(),
+        saved_at: Utc::now(),
+        pinned: false,
+        forked_from: Some(session_id.to_string()),
+        ..state.meta
+    };
+    write_session_file(&get_session_path(dir, &fork_id), &state)?;
+
+    let compacted = get_compacted_transcript_path(dir, session_id);
+    if compacted.exists() {
+        fs::copy(&compacted, get_compacted_transcript_path(dir, &fork_id))?;
+    }
+
+    Ok(fork_id)
+}
+
+/// Longest title kept, in characters.
+const MAX_TITLE_CHARS: usize = 60;
+
+/// Messages asking the model for a title for the conversation that starts
+/// with `messages`.
+pub(crate) fn title_request(messages: &[Message]) -> Vec<Message> {
+    let mut transcript = String::new();
+    for message in messages {
+        let speaker = match message.role {
+            Role::User if message.is_tool_result_only() => continue,
+            Role::User => "User",
+            Role::Assistant => "Assistant",
+            Role::System => continue,
+        };
+        let text = match &message.content {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Blocks(blocks) => blocks
+                .iter()
+                .filter_map(|b| match b {
+                    ContentBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+        if !text.trim().is_empty() {
+            transcript.push_str(&format!("{}: {}\n\n", speaker, truncate_str(&text, 2000)));
+        }
+    }
+    vec![
+        Message::system(
+            "Write a title of at most 6 words for the conversation below between a \
+             user and a coding assistant. Reply with the title only, without quotes \
+             or a trailing period.",
+        ),
+        Message::user(transcript),
+    ]
+}
+
+/// Clean up a title written by the model: the first line, without quotes,
+/// markdown or a trailing period.
+pub(crate) fn clean_title(text: &str) -> Option<String> {
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = line
+        .trim_start_matches(['#', '*', ' '])
+        .trim_start_matches("Title:")
+        .trim_matches(['"', '\'', '`', '*', ' '])
+        .trim_end_matches('.')
+        .trim();
+    (!line.is_empty()).then(|| truncate_str(line, MAX_TITLE_CHARS))
+}
+
 /// Delete a specific session by ID.
 #[cfg(test)]
 pub(crate) fn delete_session(dir: &Path, session_id: &str) -> std::io::Result<()> {
@@ -871,6 +1495,14 @@ pub(crate) fn delete_session(dir: &Path, session_id: &str) -> std::io::Result<()
     if session_path.exists() {
         fs::remove_file(&session_path)?;
     }
+    let journal_path = get_journal_path(&session_path);
+    if journal_path.exists() {
+        fs::remove_file(&journal_path)?;
+    }
+    let compacted_path = get_compacted_transcript_path(dir, session_id);
+    if compacted_path.exists() {
+        fs::remove_file(&compacted_path)?;
+    }
     Ok(())
 }
 
@@ -1382,6 +2014,12 @@ mod tests {
                 model_id: "claude-sonnet-4".to_string(),
                 thinking_enabled: true,
                 read_only: false,
+                seed: None,
+                sliding_window: false,
+                pinned: false,
+                title: None,
+                forked_from: None,
+                cost: None,
             },
             messages: vec![
                 SerializableMessage {
@@ -1448,6 +2086,58 @@ mod tests {
         assert_eq!(path1, path2);
     }
 
+    #[test]
+    fn test_export_and_import_session() {
+        let _lock = SESSION_TEST_LOCK.lock().unwrap();
+        let _sessions_dir = TestSessionsDir::new();
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+
+        let messages = vec![
+            Message::user("Hello"),
+            Message::assistant_blocks(vec![ContentBlock::Text {
+                text: "Hi there!".to_string(),
+            }]),
+        ];
+        let session_id = save_session(
+            source.path(),
+            &messages,
+            &ModelProvider::Claude,
+            "claude-sonnet-4",
+            true,
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+        archive_compacted_transcript(source.path(), &session_id, &messages).unwrap();
+
+        let export = export_session(source.path(), Some(&session_id[..8])).unwrap();
+        let json = serde_json::to_string(&export).unwrap();
+        let export: SessionExport = serde_json::from_str(&json).unwrap();
+
+        let imported_id = import_session(target.path(), export).unwrap();
+        assert_eq!(imported_id, session_id);
+        let state = load_session(target.path()).unwrap();
+        assert_eq!(state.meta.working_directory, target.path());
+        assert_eq!(state.meta.model_id, "claude-sonnet-4");
+        assert!(state.meta.thinking_enabled);
+        assert_eq!(state.messages.len(), 2);
+        assert_eq!(
+            load_compacted_transcripts(target.path(), &imported_id).len(),
+            1
+        );
+
+        // Importing again doesn't overwrite the first import.
+        let export = export_session(source.path(), None).unwrap();
+        let second_id = import_session(target.path(), export).unwrap();
+        assert_ne!(second_id, session_id);
+
+        let mut bad = export_session(source.path(), None).unwrap();
+        bad.format = "other".to_string();
+        assert!(import_session(target.path(), bad).is_err());
+    }
+
     #[test]
     fn test_session_path_different_dirs() {
         let dir1 = Path::new("/tmp/project-a");
@@ -1479,6 +2169,7 @@ mod tests {
             true,
             false,
             None,
+            None,
         )
         .unwrap();
 
@@ -1509,6 +2200,7 @@ mod tests {
             "model1",
             true,
             false,
+            None,
             Some("20251231T100000"),
         )
         .unwrap();
@@ -1522,6 +2214,7 @@ mod tests {
             "model2",
             false,
             false,
+            None,
             Some("20251231T110000"),
         )
         .unwrap();
@@ -1550,6 +2243,41 @@ mod tests {
         delete_session(working_dir, &id2).unwrap();
     }
 
+    #[test]
+    fn test_archive_compacted_transcript() {
+        let _lock = SESSION_TEST_LOCK.lock().unwrap();
+        let _sessions_dir = TestSessionsDir::new();
+        let temp_dir = TempDir::new().unwrap();
+        let working_dir = temp_dir.path();
+        let session_id = "20251231T120000";
+
+        assert!(load_compacted_transcripts(working_dir, session_id).is_empty());
+
+        archive_compacted_transcript(working_dir, session_id, &[Message::user("first")]).unwrap();
+        archive_compacted_transcript(
+            working_dir,
+            session_id,
+            &[
+                Message::user("second"),
+                Message::assistant_blocks(vec![ContentBlock::Text {
+                    text: "reply".to_string(),
+                }]),
+            ],
+        )
+        .unwrap();
+
+        let transcripts = load_compacted_transcripts(working_dir, session_id);
+        assert_eq!(transcripts.len(), 2);
+        assert_eq!(transcripts[0].messages.len(), 1);
+        assert_eq!(transcripts[1].messages.len(), 2);
+
+        // The archive must not show up as a session of its own
+        assert!(list_sessions(working_dir).is_empty());
+
+        delete_session(working_dir, session_id).unwrap();
+        assert!(load_compacted_transcripts(working_dir, session_id).is_empty());
+    }
+
     #[test]
     fn test_save_and_load_session_read_only() {
         let _lock = SESSION_TEST_LOCK.lock().unwrap();
@@ -1566,6 +2294,7 @@ mod tests {
             "claude-opus-4-5",
             true,
             true,
+            Some(42),
             None,
         )
         .unwrap();
@@ -1573,10 +2302,134 @@ mod tests {
         let loaded = load_session(working_dir).unwrap();
         assert_eq!(loaded.meta.session_id, session_id);
         assert!(loaded.meta.read_only);
+        assert_eq!(loaded.meta.seed, Some(42));
 
         delete_session(working_dir, &session_id).unwrap();
     }
 
+    #[test]
+    fn test_pinned_session_stays_pinned_on_save() {
+        let _lock = SESSION_TEST_LOCK.lock().unwrap();
+        let _sessions_dir = TestSessionsDir::new();
+        let temp_dir = TempDir::new().unwrap();
+        let working_dir = temp_dir.path();
+
+        let save = |session_id: Option<&str>| {
+            save_session(
+                working_dir,
+                &[Message::user("Hello")],
+                &ModelProvider::Claude,
+                "claude-opus-4-5",
+                false,
+                false,
+                None,
+                session_id,
+            )
+            .unwrap()
+        };
+        let session_id = save(None);
+        assert!(!list_sessions(working_dir)[0].pinned);
+
+        set_session_pinned(working_dir, &session_id, true).unwrap();
+        save(Some(&session_id));
+        assert!(list_sessions(working_dir)[0].pinned);
+
+        set_session_pinned(working_dir, &session_id, false).unwrap();
+        assert!(!load_session(working_dir).unwrap().meta.pinned);
+
+        set_session_title(working_dir, &session_id, "Greeting the model").unwrap();
+        save(Some(&session_id));
+        assert_eq!(
+            list_sessions(working_dir)[0].title.as_deref(),
+            Some("Greeting the model")
+        );
+        assert_eq!(
+            session_title(working_dir, &session_id).as_deref(),
+            Some("Greeting the model")
+        );
+
+        delete_session(working_dir, &session_id).unwrap();
+    }
+
+    #[test]
+    fn test_fork_session() {
+        let _lock = SESSION_TEST_LOCK.lock().unwrap();
+        let _sessions_dir = TestSessionsDir::new();
+        let temp_dir = TempDir::new().unwrap();
+        let working_dir = temp_dir.path();
+
+        let messages = vec![
+            Message::user("First"),
+            Message::assistant_blocks(vec![ContentBlock::Text {
+                text: "One".to_string(),
+            }]),
+            Message::user("Second"),
+            Message::assistant_blocks(vec![ContentBlock::Text {
+                text: "Two".to_string(),
+            }]),
+        ];
+        let session_id = save_session(
+            working_dir,
+            &messages,
+            &ModelProvider::Claude,
+            "claude-opus-4-5",
+            false,
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let fork_id = fork_session(working_dir, &session_id, Some(1)).unwrap();
+        assert_ne!(fork_id, session_id);
+        let fork = load_session_by_id(working_dir, &fork_id).unwrap();
+        assert_eq!(fork.messages.len(), 2);
+        assert_eq!(fork.meta.forked_from.as_deref(), Some(session_id.as_str()));
+        assert_eq!(
+            load_session_by_id(working_dir, &session_id)
+                .unwrap()
+                .messages
+                .len(),
+            4
+        );
+
+        let full_id = fork_session(working_dir, &session_id, None).unwrap();
+        assert_eq!(
+            load_session_by_id(working_dir, &full_id)
+                .unwrap()
+                .messages
+                .len(),
+            4
+        );
+        assert!(fork_session(working_dir, "missing", None).is_err());
+    }
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(
+            clean_title("\"Fixing the parser crash.\"\n").as_deref(),
+            Some("Fixing the parser crash")
+        );
+        assert_eq!(
+            clean_title("\n**Title: Session titles**").as_deref(),
+            Some("Session titles")
+        );
+        assert_eq!(clean_title("  \n"), None);
+        assert_eq!(
+            clean_title(&"word ".repeat(30)).unwrap().chars().count(),
+            MAX_TITLE_CHARS
+        );
+
+        let request = title_request(&[
+            Message::user("Why does the parser crash?"),
+            Message::assistant_blocks(vec![ContentBlock::Text {
+                text: "It unwraps a None.".to_string(),
+            }]),
+        ]);
+        assert_eq!(request.len(), 2);
+        assert_eq!(request[0].role, Role::System);
+    }
+
     #[test]
     fn test_session_preview() {
         let _lock = SESSION_TEST_LOCK.lock().unwrap();
@@ -1599,12 +2452,33 @@ mod tests {
             true,
             false,
             None,
+            None,
         )
         .unwrap();
 
         let sessions = list_sessions(working_dir);
         assert_eq!(sessions.len(), 1);
-        assert_eq!(sessions[0]._message_count, 2);
+        assert_eq!(sessions[0].message_count, 2);
+
+        // Listed with the sessions of other directories too
+        let other_dir = TempDir::new().unwrap();
+        let other_id = save_session(
+            other_dir.path(),
+            &[Message::user("Elsewhere")],
+            &ModelProvider::Claude,
+            "test",
+            true,
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+        let all = list_all_sessions();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].id, other_id);
+        assert_eq!(all[0].working_directory, other_dir.path());
+        assert_eq!(find_session(&session_id).unwrap().id, session_id);
+        assert!(find_session("no-such-session").is_err());
         assert!(sessions[0].preview.is_some());
         assert!(
             sessions[0]
@@ -1695,6 +2569,7 @@ mod tests {
             true,
             false,
             None,
+            None,
         )
         .unwrap();
 
@@ -1729,4 +2604,87 @@ mod tests {
 
         delete_session(working_dir, &session_id).unwrap();
     }
+
+    #[test]
+    fn test_journal_recovery() {
+        let _lock = SESSION_TEST_LOCK.lock().unwrap();
+        let _sessions_dir = TestSessionsDir::new();
+        let temp_dir = TempDir::new().unwrap();
+        let working_dir = temp_dir.path();
+
+        let session_id = save_session(
+            working_dir,
+            &[Message::user("Read both files")],
+            &ModelProvider::Claude,
+            "claude-opus-4-5",
+            true,
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let tool_use = |id: &str| ContentBlock::ToolUse {
+            id: id.to_string(),
+            name: "file_read".to_string(),
+            input: serde_json::json!({}),
+            thought_signature: None,
+        };
+        let journal = Journal::default();
+        journal.open(working_dir, &session_id);
+        journal.append(&Message::assistant_blocks(vec![
+            tool_use("a"),
+            tool_use("b"),
+        ]));
+        journal.append(&Message {
+            role: Role::User,
+            content: MessageContent::Blocks(vec![ContentBlock::ToolResult {
+                tool_use_id: "a".to_string(),
+                content: "contents of a".to_string(),
+                is_error: false,
+                data: None,
+                mime_type: None,
+            }]),
+        });
+
+        // Henri stopped while reading the second file.
+        set_session_title(working_dir, &session_id, "Reading").unwrap();
+        let loaded = load_session_by_id(working_dir, &session_id).unwrap();
+        assert_eq!(loaded.meta.title.as_deref(), Some("Reading"));
+        let messages = restore_messages(&loaded);
+        assert_eq!(messages.len(), 3);
+        let MessageContent::Blocks(results) = &messages[2].content else {
+            panic!("Expected Blocks content");
+        };
+        let results: Vec<(&str, bool)> = results
+            .iter()
+            .map(|b| match b {
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    is_error,
+                    ..
+                } => (tool_use_id.as_str(), *is_error),
+                _ => panic!("Expected ToolResult block"),
+            })
+            .collect();
+        assert_eq!(results, vec![("a", false), ("b", true)]);
+
+        // A full save takes the journal's place.
+        save_session(
+            working_dir,
+            &messages,
+            &ModelProvider::Claude,
+            "claude-opus-4-5",
+            true,
+            false,
+            None,
+            Some(&session_id),
+        )
+        .unwrap();
+        let path = get_session_path(working_dir, &session_id);
+        assert!(!get_journal_path(&path).exists());
+        assert_eq!(load_session_from_path(&path).unwrap().messages.len(), 3);
+
+        delete_session(working_dir, &session_id).unwrap();
+    }
 }
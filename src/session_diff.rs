@@ -0,0 +1,338 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Side-by-side comparison of two sessions, for `henri session diff`.
+//!
+//! Meant for sessions started from the same prompt with a different model or
+//! system prompt. Turns are aligned by position: the first prompt of one
+//! session against the first of the other, and so on. For each turn the final
+//! answers and the tools used are shown next to each other, after a summary
+//! of tool calls, changed files, tokens and cost.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::cli::render::{display_width, wrap_text};
+use crate::provider::{ContentBlock, Message, MessageContent, Role};
+use crate::session::{RestoredSession, SessionState};
+
+/// Wrapped lines of an answer shown before the rest is left out.
+const MAX_ANSWER_LINES: usize = 20;
+
+/// Separator between the two columns.
+const SEPARATOR: &str = " │ ";
+
+/// One prompt and what the model did with it.
+#[derive(Debug, Default)]
+struct Turn {
+    prompt: String,
+    /// Text of the last response of the turn
+    answer: Option<String>,
+    tools: BTreeMap<String, usize>,
+    tool_errors: usize,
+}
+
+/// What is compared of a session.
+#[derive(Debug)]
+struct Summary {
+    id: String,
+    model: String,
+    turns: Vec<Turn>,
+    files: BTreeSet<String>,
+    /// Estimated tokens of the conversation
+    tokens: u64,
+    cost: Option<f64>,
+}
+
+fn message_text(message: &Message) -> String {
+    match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Blocks(blocks) => blocks
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+fn summarize(state: &SessionState) -> Summary {
+    let messages = RestoredSession::from_state(state).messages;
+    let mut turns: Vec<Turn> = Vec::new();
+    let mut files = BTreeSet::new();
+
+    for message in &messages {
+        if message.role == Role::User && !message.is_tool_result_only() {
+            turns.push(Turn {
+                prompt: message_text(message).trim().to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(turn) = turns.last_mut() else {
+            continue;
+        };
+        let MessageContent::Blocks(blocks) = &message.content else {
+            if message.role == Role::Assistant {
+                turn.answer = Some(message_text(message));
+            }
+            continue;
+        };
+        for block in blocks {
+            match block {
+                ContentBlock::ToolUse { name, input, .. } => {
+                    let name = crate::tools::canonicalize_builtin_tool_name(name).unwrap_or(name);
+                    *turn.tools.entry(name.to_string()).or_default() += 1;
+                    if matches!(name, "file_edit" | "file_write")
+                        && let Some(path) = input.get("filePath").and_then(|p| p.as_str())
+                    {
+                        files.insert(path.to_string());
+                    }
+                    if name == "apply_patch" {
+                        files.extend(crate::tools::patch_paths(input));
+                    }
+                }
+                ContentBlock::ToolResult { is_error: true, .. } => turn.tool_errors += 1,
+                _ => {}
+            }
+        }
+        if message.role == Role::Assistant {
+            let text = message_text(message);
+            if !text.trim().is_empty() {
+                turn.answer = Some(text);
+            }
+        }
+    }
+
+    Summary {
+        id: state.meta.session_id.clone(),
+        model: format!("{}/{}", state.meta.provider, state.meta.model_id),
+        turns,
+        files,
+        tokens: messages
+            .iter()
+            .map(crate::usage::estimate_message_tokens)
+            .sum(),
+        cost: state.meta.cost,
+    }
+}
+
+fn format_tools(tools: &BTreeMap<String, usize>) -> String {
+    if tools.is_empty() {
+        return "none".to_string();
+    }
+    tools
+        .iter()
+        .map(|(name, count)| format!("{} {}", name, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1000 {
+        format!("{:.1}k", tokens as f64 / 1000.0)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// Lines of `left` and `right` wrapped into two columns of `width` each.
+fn columns(left: &str, right: &str, width: usize) -> Vec<String> {
+    let left = wrap_text(left, width);
+    let right = wrap_text(right, width);
+    (0..left.len().max(right.len()))
+        .map(|i| {
+            let l = left.get(i).map(String::as_str).unwrap_or("");
+            let r = right.get(i).map(String::as_str).unwrap_or("");
+            let pad = width.saturating_sub(display_width(l));
+            format!("{}{}{}{}", l, " ".repeat(pad), SEPARATOR, r)
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// The first `MAX_ANSWER_LINES` wrapped lines of an answer.
+fn clip_answer(answer: Option<&str>, width: usize) -> String {
+    let Some(answer) = answer.map(str::trim).filter(|a| !a.is_empty()) else {
+        return "(no answer)".to_string();
+    };
+    let lines = wrap_text(answer, width);
+    let mut clipped = lines
+        .iter()
+        .take(MAX_ANSWER_LINES)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("\n");
+    if lines.len() > MAX_ANSWER_LINES {
+        clipped.push_str(&format!(
+            "\n... ({} more lines)",
+            lines.len() - MAX_ANSWER_LINES
+        ));
+    }
+    clipped
+}
+
+fn totals(summary: &Summary) -> String {
+    let mut tools = BTreeMap::new();
+    for turn in &summary.turns {
+        for (name, count) in &turn.tools {
+            *tools.entry(name.clone()).or_default() += count;
+        }
+    }
+    let calls: usize = tools.values().sum();
+    let errors: usize = summary.turns.iter().map(|t| t.tool_errors).sum();
+    let files = if summary.files.is_empty() {
+        "none".to_string()
+    } else {
+        summary.files.iter().cloned().collect::<Vec<_>>().join(", ")
+    };
+    format!(
+        "{}\n{}\nTurns: {}\nTool calls: {} ({} failed)\nTools: {}\nFiles changed: {}\nTokens (est.): {}\nCost: {}",
+        summary.id,
+        summary.model,
+        summary.turns.len(),
+        calls,
+        errors,
+        format_tools(&tools),
+        files,
+        format_tokens(summary.tokens),
+        summary
+            .cost
+            .map(crate::usage::format_cost)
+            .unwrap_or_else(|| "unknown".to_string()),
+    )
+}
+
+/// Render sessions `a` and `b` side by side in `width` columns.
+pub(crate) fn render(a: &SessionState, b: &SessionState, width: usize) -> String {
+    let a = summarize(a);
+    let b = summarize(b);
+    let column = (width.saturating_sub(display_width(SEPARATOR)) / 2).max(20);
+    let rule = "─".repeat(column * 2 + display_width(SEPARATOR));
+
+    let mut lines = Vec::new();
+    if a.turns.first().map(|t| &t.prompt) != b.turns.first().map(|t| &t.prompt) {
+        lines.push("Note: the sessions start from different prompts.".to_string());
+        lines.push(String::new());
+    }
+    lines.extend(columns(&totals(&a), &totals(&b), column));
+
+    for i in 0..a.turns.len().max(b.turns.len()) {
+        let (ta, tb) = (a.turns.get(i), b.turns.get(i));
+        lines.push(String::new());
+        lines.push(rule.clone());
+        match (ta, tb) {
+            (Some(ta), Some(tb)) if ta.prompt == tb.prompt => {
+                let prompt = crate::session::truncate_str(&ta.prompt.replace('\n', " "), 200);
+                lines.extend(wrap_text(&format!("Turn {}: {}", i + 1, prompt), width));
+            }
+            _ => {
+                lines.push(format!("Turn {} (prompts differ)", i + 1));
+                let prompt = |t: Option<&Turn>| {
+                    t.map(|t| format!("> {}", crate::session::truncate_str(&t.prompt, 200)))
+                        .unwrap_or_else(|| "(no turn)".to_string())
+                };
+                lines.extend(columns(&prompt(ta), &prompt(tb), column));
+            }
+        }
+        lines.push(rule.clone());
+
+        let answer = |t: Option<&Turn>| match t {
+            Some(t) => clip_answer(t.answer.as_deref(), column),
+            None => String::new(),
+        };
+        lines.extend(columns(&answer(ta), &answer(tb), column));
+        let tools = |t: Option<&Turn>| match t {
+            Some(t) if t.tool_errors > 0 => format!(
+                "Tools: {} ({} failed)",
+                format_tools(&t.tools),
+                t.tool_errors
+            ),
+            Some(t) => format!("Tools: {}", format_tools(&t.tools)),
+            None => String::new(),
+        };
+        lines.push(String::new());
+        lines.extend(columns(&tools(ta), &tools(tb), column));
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SerializableMessage;
+
+    fn state(id: &str, model: &str, messages: Vec<Message>) -> SessionState {
+        let meta = serde_json::json!({
+            "version": 3,
+            "session_id": id,
+            "working_directory": "/work",
+            "saved_at": "2026-03-01T00:00:00Z",
+            "provider": "claude",
+            "model_id": model,
+            "thinking_enabled": false,
+            "cost": 0.25,
+        });
+        SessionState {
+            meta: serde_json::from_value(meta).unwrap(),
+            messages: messages.iter().map(SerializableMessage::from).collect(),
+            cli_history: None,
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let a = state(
+            "a1",
+            "claude-opus-4-5",
+            vec![
+                Message::user("fix the bug"),
+                Message::assistant_blocks(vec![ContentBlock::ToolUse {
+                    id: "t1".to_string(),
+                    name: "file_edit".to_string(),
+                    input: serde_json::json!({"filePath": "src/lib.rs"}),
+                    thought_signature: None,
+                }]),
+                Message {
+                    role: Role::User,
+                    content: MessageContent::Blocks(vec![ContentBlock::ToolResult {
+                        tool_use_id: "t1".to_string(),
+                        content: "Successfully edited src/lib.rs".to_string(),
+                        is_error: false,
+                        data: None,
+                        mime_type: None,
+                    }]),
+                },
+                Message::assistant_blocks(vec![ContentBlock::Text {
+                    text: "Fixed it.".to_string(),
+                }]),
+            ],
+        );
+        let b = state(
+            "b2",
+            "claude-sonnet-4-5",
+            vec![
+                Message::user("fix the bug"),
+                Message::assistant_blocks(vec![ContentBlock::Text {
+                    text: "Which bug?".to_string(),
+                }]),
+            ],
+        );
+
+        let out = render(&a, &b, 80);
+        assert!(!out.contains("different prompts"));
+        assert!(out.contains("claude/claude-opus-4-5"));
+        assert!(out.contains("Files changed: src/lib.rs"));
+        assert!(out.contains("Turn 1: fix the bug"));
+        let answers = out
+            .lines()
+            .find(|line| line.starts_with("Fixed it."))
+            .unwrap();
+        assert!(answers.ends_with("│ Which bug?"));
+        assert!(out.contains("Tools: file_edit 1"));
+        assert!(out.contains("Tools: none"));
+    }
+}
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Runtime settings changed with `/set` and the settings menu.
+//!
+//! Each setting is described once in [`SETTINGS`], with its type and how to
+//! read and change it. `/set` completion and validation, and the settings
+//! menu, are all driven from this table.
+
+use std::fmt;
+
+use crate::config::ConfigFile;
+use crate::providers::ProviderManager;
+
+/// The values a setting accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SettingType {
+    /// `on` or `off`
+    Bool,
+    /// A whole number, or `off`
+    OptionalNumber,
+}
+
+impl SettingType {
+    /// Values offered by tab completion.
+    pub(crate) fn values(self) -> &'static [&'static str] {
+        match self {
+            SettingType::Bool => &["on", "off"],
+            SettingType::OptionalNumber => &["off"],
+        }
+    }
+
+    fn expected(self) -> &'static str {
+        match self {
+            SettingType::Bool => "on or off",
+            SettingType::OptionalNumber => "a number or off",
+        }
+    }
+
+    pub(crate) fn parse(self, value: &str) -> Option<SettingValue> {
+        match (self, value.to_lowercase().as_str()) {
+            (SettingType::Bool, "on" | "true" | "yes" | "enabled") => {
+                Some(SettingValue::Bool(true))
+            }
+            (SettingType::Bool, "off" | "false" | "no" | "disabled") => {
+                Some(SettingValue::Bool(false))
+            }
+            (SettingType::OptionalNumber, "off" | "none") => Some(SettingValue::Number(None)),
+            (SettingType::OptionalNumber, number) => {
+                number.parse().ok().map(|n| SettingValue::Number(Some(n)))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SettingValue {
+    Bool(bool),
+    Number(Option<u64>),
+}
+
+impl SettingValue {
+    fn as_bool(self) -> bool {
+        matches!(self, SettingValue::Bool(true))
+    }
+
+    fn as_number(self) -> Option<u64> {
+        match self {
+            SettingValue::Number(n) => n,
+            SettingValue::Bool(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for SettingValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingValue::Bool(true) => write!(f, "on"),
+            SettingValue::Bool(false) | SettingValue::Number(None) => write!(f, "off"),
+            SettingValue::Number(Some(n)) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// Where a setting is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Scope {
+    /// Saved to the config file
+    Config,
+    /// The current session only
+    Session,
+}
+
+/// What settings are read from and written to.
+pub(crate) struct Context<'a> {
+    pub config: ConfigFile,
+    pub provider_manager: Option<&'a mut ProviderManager>,
+}
+
+impl<'a> Context<'a> {
+    pub(crate) fn load(provider_manager: Option<&'a mut ProviderManager>) -> Self {
+        Self {
+            config: ConfigFile::load().unwrap_or_default(),
+            provider_manager,
+        }
+    }
+}
+
+pub(crate) struct Setting {
+    /// Name used with `/set`, the same as the config file key
+    pub key: &'static str,
+    /// Name shown in the settings menu
+    pub label: &'static str,
+    pub description: &'static str,
+    pub ty: SettingType,
+    pub scope: Scope,
+    get: fn(&Context) -> SettingValue,
+    /// Store the value and apply it to the running session
+    set: fn(&mut Context, SettingValue) -> Result<(), String>,
+}
+
+impl Setting {
+    pub(crate) fn get(&self, ctx: &Context) -> SettingValue {
+        (self.get)(ctx)
+    }
+
+    /// Change the setting, saving it to the config file if it is kept there.
+    pub(crate) fn set(&self, ctx: &mut Context, value: SettingValue) -> Result<(), String> {
+        (self.set)(ctx, value)?;
+        if self.scope == Scope::Config {
+            ctx.config.save().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Parse `value` for this setting, with a message saying what is expected
+    /// if it isn't valid.
+    pub(crate) fn parse(&self, value: &str) -> Result<SettingValue, String> {
+        self.ty.parse(value).ok_or_else(|| {
+            format!(
+                "Invalid value for {}: {} (expected {})",
+                self.key,
+                value,
+                self.ty.expected()
+            )
+        })
+    }
+}
+
+pub(crate) const SETTINGS: &[Setting] = &[
+    Setting {
+        key: "compact-mode",
+        label: "Compact Mode",
+        description: "Remove blank lines between output blocks",
+        ty: SettingType::Bool,
+        scope: Scope::Config,
+        get: |ctx| SettingValue::Bool(ctx.config.compact_mode),
+        set: |ctx, value| {
+            ctx.config.compact_mode = value.as_bool();
+            crate::cli::spacing::set_compact_mode(value.as_bool());
+            Ok(())
+        },
+    },
+    Setting {
+        key: "show-image-previews",
+        label: "Image Previews",
+        description: "Show previews of images in the terminal",
+        ty: SettingType::Bool,
+        scope: Scope::Config,
+        get: |ctx| SettingValue::Bool(ctx.config.show_image_previews),
+        set: |ctx, value| {
+            ctx.config.show_image_previews = value.as_bool();
+            crate::cli::listener::set_show_image_previews(value.as_bool());
+            Ok(())
+        },
+    },
+    Setting {
+        key: "lsp-enabled",
+        label: "LSP Integration",
+        description: "Report language server diagnostics after edits",
+        ty: SettingType::Bool,
+        scope: Scope::Config,
+        get: |ctx| SettingValue::Bool(ctx.config.lsp_enabled),
+        // Picked up by the prompt status refresh on redraw.
+        set: |ctx, value| {
+            ctx.config.lsp_enabled = value.as_bool();
+            Ok(())
+        },
+    },
+    Setting {
+        key: "hide-tool-output",
+        label: "Hide Tool Output",
+        description: "Hide tool output like bash command output and file read previews",
+        ty: SettingType::Bool,
+        scope: Scope::Config,
+        get: |ctx| SettingValue::Bool(ctx.config.hide_tool_output),
+        set: |ctx, value| {
+            ctx.config.hide_tool_output = value.as_bool();
+            crate::cli::listener::set_hide_tool_output(value.as_bool());
+            Ok(())
+        },
+    },
+    Setting {
+        key: "instant-paint",
+        label: "Instant Paint",
+        description: "Print each response block once it is complete instead of streaming it",
+        ty: SettingType::Bool,
+        scope: Scope::Config,
+        get: |ctx| SettingValue::Bool(ctx.config.instant_paint),
+        set: |ctx, value| {
+            ctx.config.instant_paint = value.as_bool();
+            crate::cli::listener::set_instant_paint(value.as_bool());
+            Ok(())
+        },
+    },
+    Setting {
+        key: "sliding-window",
+        label: "Sliding Window (this session)",
+        description: "Drop the oldest turns instead of compacting when the context fills up",
+        ty: SettingType::Bool,
+        scope: Scope::Session,
+        get: |_| SettingValue::Bool(crate::sliding_window::is_enabled()),
+        set: |_, value| {
+            crate::sliding_window::set_enabled(value.as_bool());
+            Ok(())
+        },
+    },
+    Setting {
+        key: "seed",
+        label: "Seed",
+        description: "Sampling seed sent with each request",
+        ty: SettingType::OptionalNumber,
+        scope: Scope::Session,
+        get: |ctx| SettingValue::Number(ctx.provider_manager.as_ref().and_then(|pm| pm.seed())),
+        set: |ctx, value| {
+            let pm = ctx
+                .provider_manager
+                .as_mut()
+                .ok_or("No model is configured")?;
+            pm.set_seed(value.as_number());
+            Ok(())
+        },
+    },
+];
+
+/// Find a setting by its key.
+pub(crate) fn find(key: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|s| s.key == key)
+}
+
+/// Completions for the arguments of `/set`, given the arguments before the
+/// one being typed.
+pub(crate) fn complete(previous: &[&str], prefix: &str) -> Vec<String> {
+    let candidates: Vec<&str> = match previous {
+        [] => SETTINGS.iter().map(|s| s.key).collect(),
+        [key] => find(key).map_or(Vec::new(), |s| s.ty.values().to_vec()),
+        _ => Vec::new(),
+    };
+    candidates
+        .into_iter()
+        .filter(|c| c.starts_with(prefix))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_complete() {
+        let seed = find("seed").unwrap();
+        assert_eq!(seed.parse("42"), Ok(SettingValue::Number(Some(42))));
+        assert_eq!(seed.parse("off"), Ok(SettingValue::Number(None)));
+        assert_eq!(
+            seed.parse("x").unwrap_err(),
+            "Invalid value for seed: x (expected a number or off)"
+        );
+        let compact = find("compact-mode").unwrap();
+        assert_eq!(compact.parse("On"), Ok(SettingValue::Bool(true)));
+        assert!(compact.parse("2").is_err());
+        assert_eq!(SettingValue::Bool(true).to_string(), "on");
+
+        assert_eq!(complete(&[], "se"), vec!["seed"]);
+        assert_eq!(complete(&["compact-mode"], "o"), vec!["on", "off"]);
+        assert_eq!(complete(&["compact-mode"], "of"), vec!["off"]);
+        assert!(complete(&["unknown"], "").is_empty());
+        assert!(complete(&["seed", "1"], "").is_empty());
+    }
+}
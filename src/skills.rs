@@ -102,7 +102,7 @@ pub(crate) fn load_skills() -> Vec<Skill> {
     }
 
     // Sort by name for consistent ordering
-    skills.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    skills.sort_by_key(|s| s.name.to_lowercase());
 
     skills
 }
@@ -0,0 +1,354 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Sliding-window alternative to compaction.
+//!
+//! When the context nears the model's limit, the oldest turns are dropped
+//! instead of being summarized by the model. The chat loop archives them with
+//! the session first, so `/expand` can still show them. A pinned preamble at
+//! the start of the conversation keeps key facts from the dropped turns: the
+//! original request, later requests and the files that were worked on. It is
+//! rebuilt each time more turns are dropped.
+//!
+//! The strategy is chosen per session; new sessions start with
+//! `auto-compact.strategy` from the config.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::{ConfigFile, ContextStrategy};
+use crate::provider::{ContentBlock, Message, MessageContent, Role};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// First line of the preamble, used to recognize it.
+const HEADER: &str = "[Sliding window] Older turns of this conversation were dropped to stay \
+within the context window. Key facts from them:";
+
+const ORIGINAL_REQUEST: &str = "## Original request";
+const EARLIER_REQUESTS: &str = "## Earlier requests";
+const FILES: &str = "## Files worked on";
+const EARLIER_SUMMARY: &str = "## Earlier summary";
+
+/// Longest request kept, in characters.
+const MAX_REQUEST_CHARS: usize = 300;
+
+/// Number of earlier requests kept.
+const MAX_REQUESTS: usize = 10;
+
+/// Number of file paths kept.
+const MAX_FILES: usize = 50;
+
+/// Tools whose file argument is recorded, with the name of that argument.
+const FILE_TOOLS: &[(&str, &str)] = &[
+    ("file_read", "filename"),
+    ("file_edit", "filePath"),
+    ("file_write", "filePath"),
+];
+
+/// Whether the current session uses the sliding window.
+pub(crate) fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub(crate) fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Reset to the configured strategy, for a new session.
+pub(crate) fn reset() {
+    let strategy = ConfigFile::load()
+        .map(|c| c.auto_compact.strategy)
+        .unwrap_or_default();
+    set_enabled(strategy == ContextStrategy::SlidingWindow);
+}
+
+/// Facts kept in the preamble.
+#[derive(Debug, Default, PartialEq)]
+struct Facts {
+    original_request: Option<String>,
+    requests: Vec<String>,
+    files: Vec<String>,
+    /// Summary from a compaction before the window started sliding
+    summary: Option<String>,
+}
+
+fn one_line(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(MAX_REQUEST_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+impl Facts {
+    /// Parse a preamble written by [`Facts::render`].
+    fn parse(text: &str) -> Option<Self> {
+        let body = text.strip_prefix(HEADER)?;
+        let mut facts = Facts::default();
+        let mut section = "";
+        let mut lines = body.lines();
+        while let Some(line) = lines.next() {
+            if line == EARLIER_SUMMARY {
+                let rest: Vec<&str> = lines.by_ref().collect();
+                facts.summary = Some(rest.join("\n").trim().to_string());
+                break;
+            }
+            if line.starts_with("## ") {
+                section = line;
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            match section {
+                ORIGINAL_REQUEST => facts.original_request = Some(line.to_string()),
+                EARLIER_REQUESTS => {
+                    if let Some(item) = line.strip_prefix("- ") {
+                        facts.requests.push(item.to_string());
+                    }
+                }
+                FILES => {
+                    if let Some(item) = line.strip_prefix("- ") {
+                        facts.files.push(item.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(facts)
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::from(HEADER);
+        if let Some(request) = &self.original_request {
+            out.push_str(&format!("\n\n{}\n{}", ORIGINAL_REQUEST, request));
+        }
+        if !self.requests.is_empty() {
+            out.push_str(&format!("\n\n{}", EARLIER_REQUESTS));
+            for request in &self.requests {
+                out.push_str(&format!("\n- {}", request));
+            }
+        }
+        if !self.files.is_empty() {
+            out.push_str(&format!("\n\n{}", FILES));
+            for file in &self.files {
+                out.push_str(&format!("\n- {}", file));
+            }
+        }
+        if let Some(summary) = &self.summary {
+            out.push_str(&format!("\n\n{}\n{}", EARLIER_SUMMARY, summary));
+        }
+        out
+    }
+
+    /// Record the facts from dropped messages.
+    fn add(&mut self, dropped: &[Message]) {
+        for message in dropped {
+            match message.role {
+                Role::User if !message.is_tool_result_only() => {
+                    let text = match &message.content {
+                        MessageContent::Text(text) => text.clone(),
+                        MessageContent::Blocks(blocks) => blocks
+                            .iter()
+                            .filter_map(|b| match b {
+                                ContentBlock::Text { text } => Some(text.as_str()),
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    };
+                    let text = one_line(&text);
+                    if text.is_empty() {
+                        continue;
+                    }
+                    if self.original_request.is_none() {
+                        self.original_request = Some(text);
+                    } else {
+                        self.requests.push(text);
+                    }
+                }
+                Role::Assistant => {
+                    let MessageContent::Blocks(blocks) = &message.content else {
+                        continue;
+                    };
+                    for block in blocks {
+                        if let ContentBlock::ToolUse { name, input, .. } = block
+                            && let Some((_, key)) = FILE_TOOLS.iter().find(|(tool, _)| tool == name)
+                            && let Some(path) = input.get(*key).and_then(|p| p.as_str())
+                        {
+                            self.files.retain(|f| f != path);
+                            self.files.push(path.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        let excess = self.requests.len().saturating_sub(MAX_REQUESTS);
+        self.requests.drain(..excess);
+        let excess = self.files.len().saturating_sub(MAX_FILES);
+        self.files.drain(..excess);
+    }
+}
+
+/// Drop the oldest turns, keeping the last `preserve_turns`, and update the
+/// preamble. Returns the number of messages dropped.
+pub(crate) fn slide(messages: &mut Vec<Message>, preserve_turns: usize) -> usize {
+    // An existing preamble, or a summary from an earlier compaction, is merged
+    let previous = match messages.first().map(|m| &m.content) {
+        Some(MessageContent::Blocks(blocks)) => match blocks.as_slice() {
+            [
+                ContentBlock::Summary {
+                    summary,
+                    messages_compacted,
+                },
+            ] => Some((summary.clone(), *messages_compacted)),
+            _ => None,
+        },
+        _ => None,
+    };
+    let (mut facts, previously_dropped, rest) = match previous {
+        Some((summary, count)) => {
+            let facts = Facts::parse(&summary).unwrap_or(Facts {
+                summary: Some(summary),
+                ..Facts::default()
+            });
+            (facts, count, &messages[1..])
+        }
+        None => (Facts::default(), 0, &messages[..]),
+    };
+
+    let (dropped, kept) = crate::compaction::segment_messages(rest, preserve_turns);
+    if dropped.is_empty() {
+        return 0;
+    }
+    facts.add(&dropped);
+
+    let preamble = Message {
+        role: Role::User,
+        content: MessageContent::Blocks(vec![ContentBlock::Summary {
+            summary: facts.render(),
+            messages_compacted: previously_dropped + dropped.len(),
+        }]),
+    };
+    let count = dropped.len();
+    *messages = std::iter::once(preamble).chain(kept).collect();
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A call of the tool `name` and its result.
+    fn tool_call(name: &str, input: serde_json::Value) -> [Message; 2] {
+        [
+            Message::assistant_blocks(vec![ContentBlock::ToolUse {
+                id: "t".to_string(),
+                name: name.to_string(),
+                input,
+                thought_signature: None,
+            }]),
+            Message {
+                role: Role::User,
+                content: MessageContent::Blocks(vec![ContentBlock::ToolResult {
+                    tool_use_id: "t".to_string(),
+                    content: String::new(),
+                    is_error: false,
+                    data: None,
+                    mime_type: None,
+                }]),
+            },
+        ]
+    }
+
+    fn preamble(messages: &[Message]) -> (String, usize) {
+        match &messages[0].content {
+            MessageContent::Blocks(blocks) => match &blocks[0] {
+                ContentBlock::Summary {
+                    summary,
+                    messages_compacted,
+                } => (summary.clone(), *messages_compacted),
+                other => panic!("unexpected block {:?}", other),
+            },
+            MessageContent::Text(_) => panic!("no preamble"),
+        }
+    }
+
+    #[test]
+    fn test_slide_keeps_facts() {
+        let mut messages = vec![Message::user("Add a   --verbose\nflag")];
+        messages.extend(tool_call(
+            "file_edit",
+            serde_json::json!({
+                "filePath": "src/main.rs",
+                "oldString": "fn main() {",
+                "newString": "fn main() -> ExitCode {",
+            }),
+        ));
+        messages.push(Message::user("Also update the docs"));
+        messages.extend(tool_call(
+            "file_read",
+            serde_json::json!({ "filename": "README.md" }),
+        ));
+        messages.extend([
+            Message::user("Now write tests"),
+            Message::assistant_blocks(vec![ContentBlock::Text {
+                text: "Done".to_string(),
+            }]),
+        ]);
+
+        assert_eq!(slide(&mut messages, 2), 3);
+        let (text, dropped) = preamble(&messages);
+        assert_eq!(dropped, 3);
+        assert_eq!(messages.len(), 6);
+        let facts = Facts::parse(&text).unwrap();
+        assert_eq!(
+            facts.original_request.as_deref(),
+            Some("Add a --verbose flag")
+        );
+        assert_eq!(facts.files, vec!["src/main.rs"]);
+
+        // Sliding again merges into the same preamble
+        messages.push(Message::user("Run them"));
+        assert_eq!(slide(&mut messages, 2), 3);
+        let (text, dropped) = preamble(&messages);
+        assert_eq!(dropped, 6);
+        assert_eq!(
+            Facts::parse(&text).unwrap(),
+            Facts {
+                original_request: Some("Add a --verbose flag".to_string()),
+                requests: vec!["Also update the docs".to_string()],
+                files: vec!["src/main.rs".to_string(), "README.md".to_string()],
+                summary: None,
+            }
+        );
+
+        // Nothing left to drop
+        assert_eq!(slide(&mut messages, 2), 0);
+    }
+
+    #[test]
+    fn test_slide_keeps_compaction_summary() {
+        let mut messages = vec![
+            Message {
+                role: Role::User,
+                content: MessageContent::Blocks(vec![ContentBlock::Summary {
+                    summary: "## Done\nStuff".to_string(),
+                    messages_compacted: 7,
+                }]),
+            },
+            Message::user("one"),
+            Message::assistant_blocks(vec![ContentBlock::Text {
+                text: "ok".to_string(),
+            }]),
+            Message::user("two"),
+        ];
+        assert_eq!(slide(&mut messages, 1), 2);
+        let (text, dropped) = preamble(&messages);
+        assert_eq!(dropped, 9);
+        let facts = Facts::parse(&text).unwrap();
+        assert_eq!(facts.summary.as_deref(), Some("## Done\nStuff"));
+        assert_eq!(facts.original_request.as_deref(), Some("one"));
+    }
+}
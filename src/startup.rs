@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Startup phase timings, printed by `--profile-startup`.
+//!
+//! Each call to [`mark`] records how long the phase that just finished took;
+//! [`report`] formats them once the prompt is about to be drawn.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+static START: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Finished phases with the time since start at which each ended.
+static PHASES: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+
+/// Start the clock. Call first thing in `main`.
+pub(crate) fn start() {
+    LazyLock::force(&START);
+}
+
+/// Record phase timings from now on.
+pub(crate) fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Record that `phase` has finished.
+pub(crate) fn mark(phase: &'static str) {
+    if ENABLED.load(Ordering::Relaxed) {
+        PHASES.lock().unwrap().push((phase, START.elapsed()));
+    }
+}
+
+/// The timings recorded so far, if profiling is enabled. Stops recording, so
+/// the report is only produced once.
+pub(crate) fn report() -> Option<String> {
+    if !ENABLED.swap(false, Ordering::Relaxed) {
+        return None;
+    }
+    Some(format_phases(&PHASES.lock().unwrap()))
+}
+
+fn format_phases(phases: &[(&'static str, Duration)]) -> String {
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let mut out = String::from("Startup profile:");
+    let mut previous = Duration::ZERO;
+    for (phase, at) in phases {
+        out.push_str(&format!(
+            "\n  {:<12} {:>8.1} ms",
+            phase,
+            ms(at.saturating_sub(previous))
+        ));
+        previous = *at;
+    }
+    out.push_str(&format!("\n  {:<12} {:>8.1} ms", "total", ms(previous)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_phases() {
+        let phases = [
+            ("config", Duration::from_millis(3)),
+            ("providers", Duration::from_micros(10_500)),
+        ];
+        assert_eq!(
+            format_phases(&phases),
+            "Startup profile:\n  config            3.0 ms\n  providers         7.5 ms\n  total            10.5 ms"
+        );
+    }
+}
@@ -6,23 +6,32 @@
 //! This module provides syntax highlighting for code using syntect. It uses a generic
 //! RGB color representation that can be converted to platform-specific color types
 //! (colored's truecolor, etc.).
+//!
+//! Without the `highlight` feature nothing is highlighted and code is shown as is.
 
 use std::path::Path;
+#[cfg(feature = "highlight")]
 use std::sync::OnceLock;
 
+#[cfg(feature = "highlight")]
 use syntect::highlighting::{Theme, ThemeSet};
+#[cfg(feature = "highlight")]
 use syntect::parsing::SyntaxSet;
 
 /// Global syntax set - loaded once on first use
+#[cfg(feature = "highlight")]
 static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
 
 /// Global theme - loaded once on first use
+#[cfg(feature = "highlight")]
 static THEME: OnceLock<Theme> = OnceLock::new();
 
+#[cfg(feature = "highlight")]
 fn syntax_set() -> &'static SyntaxSet {
     SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
 }
 
+#[cfg(feature = "highlight")]
 fn theme() -> &'static Theme {
     THEME.get_or_init(|| {
         let ts = ThemeSet::load_defaults();
@@ -33,6 +42,15 @@ fn theme() -> &'static Theme {
     })
 }
 
+/// Load the syntax definitions and theme, which otherwise happens on first use.
+pub(crate) fn warm() {
+    #[cfg(feature = "highlight")]
+    {
+        syntax_set();
+        theme();
+    }
+}
+
 /// RGB color for syntax highlighting
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct Rgb {
@@ -42,6 +60,7 @@ pub(crate) struct Rgb {
 }
 
 impl Rgb {
+    #[cfg(feature = "highlight")]
     pub(crate) const fn new(r: u8, g: u8, b: u8) -> Self {
         Self { r, g, b }
     }
@@ -56,6 +75,7 @@ pub(crate) struct HighlightSpan {
 }
 
 /// Highlight code and return spans with RGB colors.
+#[cfg(feature = "highlight")]
 pub(crate) fn highlight_code(code: &str, language: Option<&str>) -> Vec<HighlightSpan> {
     let ps = syntax_set();
     let theme = theme();
@@ -92,6 +112,12 @@ pub(crate) fn highlight_code(code: &str, language: Option<&str>) -> Vec<Highligh
     spans
 }
 
+/// Without highlighting support there are no spans.
+#[cfg(not(feature = "highlight"))]
+pub(crate) fn highlight_code(_code: &str, _language: Option<&str>) -> Vec<HighlightSpan> {
+    Vec::new()
+}
+
 /// Extract language identifier from file path extension
 pub(crate) fn language_from_path(path: &str) -> Option<String> {
     let ext = Path::new(path).extension()?.to_str()?;
@@ -136,6 +162,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "highlight")]
     fn test_highlight_code_rust() {
         let code = "let x = 42;";
         let spans = highlight_code(code, Some("rust"));
@@ -143,6 +170,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "highlight")]
     fn test_highlight_code_python() {
         let code = "def hello():\n    pass";
         let spans = highlight_code(code, Some("python"));
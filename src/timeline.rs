@@ -0,0 +1,505 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Where the time of each turn went, for `/timeline`.
+//!
+//! Every model request and tool call of a turn is recorded with when it
+//! started and how long it took. Requests also keep how long the model spent
+//! thinking, the tokens sent and received, and how often they were retried;
+//! tool calls keep the size of their result. Calls made by delegated tasks are
+//! part of the task's own tool call and aren't listed separately.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::cli::render::display_width;
+use crate::output::{OutputEvent, OutputListener};
+use crate::session::truncate_str;
+
+/// Turns kept before the oldest are dropped.
+const MAX_TURNS: usize = 100;
+
+/// Width of the bars showing when each step ran.
+const BAR_WIDTH: usize = 24;
+
+/// Width of the step column.
+const STEP_WIDTH: usize = 40;
+
+#[derive(Debug, Clone)]
+enum Step {
+    Model {
+        thinking: Duration,
+        input_tokens: u64,
+        output_tokens: u64,
+        retries: u32,
+        failed: bool,
+    },
+    Tool {
+        description: String,
+        bytes: usize,
+        is_error: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Span {
+    /// Time since the start of the turn
+    start: Duration,
+    duration: Duration,
+    step: Step,
+}
+
+#[derive(Debug)]
+struct Turn {
+    prompt: String,
+    started: Instant,
+    /// Time from the start to the end of the last step
+    elapsed: Duration,
+    spans: Vec<Span>,
+}
+
+/// The model request in progress.
+#[derive(Debug)]
+struct Request {
+    started: Instant,
+    thinking_since: Option<Instant>,
+    thinking: Duration,
+    input_tokens: u64,
+    output_tokens: u64,
+    retries: u32,
+}
+
+#[derive(Default)]
+struct Inner {
+    turns: Vec<Turn>,
+    request: Option<Request>,
+}
+
+impl Inner {
+    fn push(&mut self, started: Instant, step: Step) {
+        if self.turns.is_empty() {
+            self.start_turn(String::new(), started);
+        }
+        let Some(turn) = self.turns.last_mut() else {
+            return;
+        };
+        let start = started.saturating_duration_since(turn.started);
+        let duration = started.elapsed();
+        turn.elapsed = turn.elapsed.max(start + duration);
+        turn.spans.push(Span {
+            start,
+            duration,
+            step,
+        });
+    }
+
+    fn start_turn(&mut self, prompt: String, started: Instant) {
+        if self.turns.len() >= MAX_TURNS {
+            self.turns.remove(0);
+        }
+        self.turns.push(Turn {
+            prompt,
+            started,
+            elapsed: Duration::ZERO,
+            spans: Vec::new(),
+        });
+    }
+}
+
+/// The timeline of a session, shared through [`crate::services::Services`].
+/// It is also an [`OutputListener`] to follow the thinking and token counts
+/// of the request in progress.
+#[derive(Default)]
+pub(crate) struct Timeline {
+    inner: Mutex<Inner>,
+}
+
+impl Timeline {
+    /// Start a turn for `prompt`.
+    pub(crate) fn start_turn(&self, prompt: &str) {
+        self.inner
+            .lock()
+            .unwrap()
+            .start_turn(prompt.trim().replace('\n', " "), Instant::now());
+    }
+
+    /// A model request was sent.
+    pub(crate) fn begin_request(&self) {
+        self.inner.lock().unwrap().request = Some(Request {
+            started: Instant::now(),
+            thinking_since: None,
+            thinking: Duration::ZERO,
+            input_tokens: 0,
+            output_tokens: 0,
+            retries: 0,
+        });
+    }
+
+    /// The request in progress is sent again after a failure or rate limit.
+    pub(crate) fn retry(&self) {
+        if let Some(request) = &mut self.inner.lock().unwrap().request {
+            request.retries += 1;
+        }
+    }
+
+    /// The request in progress finished, with a response unless it `failed`.
+    pub(crate) fn end_request(&self, failed: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(request) = inner.request.take() else {
+            return;
+        };
+        let thinking = request.thinking
+            + request
+                .thinking_since
+                .map_or(Duration::ZERO, |since| since.elapsed());
+        inner.push(
+            request.started,
+            Step::Model {
+                thinking,
+                input_tokens: request.input_tokens,
+                output_tokens: request.output_tokens,
+                retries: request.retries,
+                failed,
+            },
+        );
+    }
+
+    /// A tool call described as `description` ran from `started` until now
+    /// and returned `bytes` of content.
+    pub(crate) fn record_tool(
+        &self,
+        description: &str,
+        started: Instant,
+        bytes: usize,
+        is_error: bool,
+    ) {
+        self.inner.lock().unwrap().push(
+            started,
+            Step::Tool {
+                description: description.to_string(),
+                bytes,
+                is_error,
+            },
+        );
+    }
+
+    /// The steps of the last turn, or `None` before the first.
+    pub(crate) fn render_turn(&self) -> Option<String> {
+        let inner = self.inner.lock().unwrap();
+        let number = inner.turns.len();
+        inner.turns.last().map(|turn| render_turn(number, turn))
+    }
+
+    /// A row for each turn of the session, or `None` before the first.
+    pub(crate) fn render_session(&self) -> Option<String> {
+        let inner = self.inner.lock().unwrap();
+        (!inner.turns.is_empty()).then(|| render_session(&inner.turns))
+    }
+}
+
+impl OutputListener for Timeline {
+    fn on_event(&self, event: &OutputEvent) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(request) = &mut inner.request else {
+            return;
+        };
+        match event {
+            OutputEvent::ThinkingStart => request.thinking_since = Some(Instant::now()),
+            OutputEvent::ThinkingEnd => {
+                if let Some(since) = request.thinking_since.take() {
+                    request.thinking += since.elapsed();
+                }
+            }
+            OutputEvent::UsageUpdate {
+                input_tokens,
+                output_tokens,
+                cache_read_tokens,
+                cache_write_tokens,
+            } => {
+                request.input_tokens += input_tokens + cache_read_tokens + cache_write_tokens;
+                request.output_tokens += output_tokens;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f64();
+    if secs < 60.0 {
+        format!("{:.1}s", secs)
+    } else {
+        format!(
+            "{}m{:02}s",
+            duration.as_secs() / 60,
+            duration.as_secs() % 60
+        )
+    }
+}
+
+fn format_count(count: u64) -> String {
+    if count >= 1000 {
+        format!("{:.1}k", count as f64 / 1000.0)
+    } else {
+        count.to_string()
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+fn plural(count: u64, word: &str) -> String {
+    format!("{} {}{}", count, word, if count == 1 { "" } else { "s" })
+}
+
+/// A bar covering `start..start + duration` of `total`.
+fn bar(start: Duration, duration: Duration, total: Duration) -> String {
+    let total = total.as_secs_f64().max(f64::EPSILON);
+    let offset = ((start.as_secs_f64() / total) * BAR_WIDTH as f64).round() as usize;
+    let offset = offset.min(BAR_WIDTH - 1);
+    let length = ((duration.as_secs_f64() / total) * BAR_WIDTH as f64).round() as usize;
+    let length = length.clamp(1, BAR_WIDTH - offset);
+    format!("{}{}", " ".repeat(offset), "█".repeat(length))
+}
+
+/// `text` cut or padded to `width` columns.
+fn column(text: &str, width: usize) -> String {
+    let text = truncate_str(text, width);
+    let pad = width.saturating_sub(display_width(&text));
+    format!("{}{}", text, " ".repeat(pad))
+}
+
+fn describe(step: &Step) -> (String, String) {
+    match step {
+        Step::Model {
+            thinking,
+            input_tokens,
+            output_tokens,
+            retries,
+            failed,
+        } => {
+            let mut label = "model".to_string();
+            if !thinking.is_zero() {
+                label.push_str(&format!(", thinking {}", format_duration(*thinking)));
+            }
+            if *retries > 0 {
+                label.push_str(&format!(", {}", plural(u64::from(*retries), "retry")));
+            }
+            if *failed {
+                label.push_str(" (failed)");
+            }
+            let detail = if *input_tokens > 0 || *output_tokens > 0 {
+                format!(
+                    "{} in / {} out",
+                    format_count(*input_tokens),
+                    format_count(*output_tokens)
+                )
+            } else {
+                String::new()
+            };
+            (label, detail)
+        }
+        Step::Tool {
+            description,
+            bytes,
+            is_error,
+        } => {
+            let label = if *is_error {
+                format!("{} (error)", description)
+            } else {
+                description.clone()
+            };
+            (label, format_bytes(*bytes))
+        }
+    }
+}
+
+/// Totals of the model requests and tool calls in `spans`.
+#[derive(Default)]
+struct Totals {
+    requests: u64,
+    model: Duration,
+    thinking: Duration,
+    retries: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    tools: u64,
+    tool_time: Duration,
+    bytes: usize,
+}
+
+impl Totals {
+    fn of(spans: &[Span]) -> Self {
+        let mut totals = Self::default();
+        for span in spans {
+            match &span.step {
+                Step::Model {
+                    thinking,
+                    input_tokens,
+                    output_tokens,
+                    retries,
+                    ..
+                } => {
+                    totals.requests += 1;
+                    totals.model += span.duration;
+                    totals.thinking += *thinking;
+                    totals.retries += u64::from(*retries);
+                    totals.input_tokens += input_tokens;
+                    totals.output_tokens += output_tokens;
+                }
+                Step::Tool { bytes, .. } => {
+                    totals.tools += 1;
+                    totals.tool_time += span.duration;
+                    totals.bytes += bytes;
+                }
+            }
+        }
+        totals
+    }
+}
+
+fn render_turn(number: usize, turn: &Turn) -> String {
+    let mut out = format!(
+        "Turn {} ({}): {}\n",
+        number,
+        format_duration(turn.elapsed),
+        truncate_str(&turn.prompt, 80)
+    );
+    out.push_str(&format!(
+        "{:>8} {:>7}  {} {:<16} when\n",
+        "start",
+        "time",
+        column("step", STEP_WIDTH),
+        "size"
+    ));
+    for span in &turn.spans {
+        let (label, detail) = describe(&span.step);
+        out.push_str(&format!(
+            "{:>8} {:>7}  {} {:<16} {}\n",
+            format_duration(span.start),
+            format_duration(span.duration),
+            column(&label, STEP_WIDTH),
+            detail,
+            bar(span.start, span.duration, turn.elapsed)
+        ));
+    }
+
+    let totals = Totals::of(&turn.spans);
+    let mut model = format!(
+        "Model: {} in {}",
+        plural(totals.requests, "request"),
+        format_duration(totals.model)
+    );
+    if !totals.thinking.is_zero() {
+        model.push_str(&format!(" (thinking {})", format_duration(totals.thinking)));
+    }
+    if totals.retries > 0 {
+        model.push_str(&format!(", {}", plural(totals.retries, "retry")));
+    }
+    out.push_str(&format!(
+        "{}; {} tokens in, {} out\nTools: {} in {}, {} returned",
+        model,
+        format_count(totals.input_tokens),
+        format_count(totals.output_tokens),
+        plural(totals.tools, "call"),
+        format_duration(totals.tool_time),
+        format_bytes(totals.bytes)
+    ));
+    out
+}
+
+fn render_session(turns: &[Turn]) -> String {
+    let longest = turns
+        .iter()
+        .map(|turn| turn.elapsed)
+        .max()
+        .unwrap_or_default();
+    let mut out = format!(
+        "{:>4} {:>7} {:>7} {:>6} {:>7} {:>7} {:>13}  {}\n",
+        "turn", "time", "model", "tools", "tool", "retries", "tokens in/out", "prompt"
+    );
+    for (i, turn) in turns.iter().enumerate() {
+        let totals = Totals::of(&turn.spans);
+        out.push_str(&format!(
+            "{:>4} {:>7} {:>7} {:>6} {:>7} {:>7} {:>13}  {} {}\n",
+            i + 1,
+            format_duration(turn.elapsed),
+            format_duration(totals.model),
+            totals.tools,
+            format_duration(totals.tool_time),
+            totals.retries,
+            format!(
+                "{}/{}",
+                format_count(totals.input_tokens),
+                format_count(totals.output_tokens)
+            ),
+            column(&turn.prompt, 40),
+            bar(Duration::ZERO, turn.elapsed, longest)
+        ));
+    }
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_turn() {
+        let timeline = Timeline::default();
+        timeline.start_turn("fix the\nfailing test");
+        timeline.begin_request();
+        timeline.on_event(&OutputEvent::ThinkingStart);
+        timeline.on_event(&OutputEvent::ThinkingEnd);
+        timeline.on_event(&OutputEvent::UsageUpdate {
+            input_tokens: 200,
+            output_tokens: 0,
+            cache_read_tokens: 1800,
+            cache_write_tokens: 0,
+        });
+        timeline.on_event(&OutputEvent::UsageUpdate {
+            input_tokens: 0,
+            output_tokens: 150,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+        });
+        timeline.retry();
+        timeline.end_request(false);
+        timeline.record_tool("bash: cargo test", Instant::now(), 3072, true);
+
+        let out = timeline.render_turn().unwrap();
+        assert!(out.starts_with("Turn 1 ("));
+        assert!(out.contains("fix the failing test"));
+        assert!(out.contains(", 1 retry"));
+        assert!(out.contains("2.0k in / 150 out"));
+        assert!(out.contains("bash: cargo test (error)"));
+        assert!(out.contains("3.0 KB"));
+        assert!(out.contains("Tools: 1 call in "));
+
+        let session = timeline.render_session().unwrap();
+        assert_eq!(session.lines().count(), 2);
+        assert!(session.contains("2.0k/150"));
+    }
+
+    #[test]
+    fn test_bar() {
+        let second = Duration::from_secs(1);
+        assert_eq!(bar(Duration::ZERO, second * 4, second * 4), "█".repeat(24));
+        assert_eq!(
+            bar(second * 2, second, second * 4),
+            format!("{}██████", " ".repeat(12))
+        );
+        // Short steps still get a mark.
+        assert_eq!(
+            bar(second * 4, Duration::ZERO, second * 4),
+            format!("{}█", " ".repeat(23))
+        );
+    }
+}
@@ -0,0 +1,204 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Tool call metrics.
+//!
+//! Every tool call made during a chat is appended to `~/.cache/henri/tool-metrics.jsonl`
+//! with its outcome and duration. `henri stats tools` aggregates the log per tool, for the
+//! most recent session and overall, to make tools that keep failing easy to spot.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Session that tool calls are currently attributed to. Nothing is recorded until set.
+static SESSION_ID: Mutex<Option<String>> = Mutex::new(None);
+
+/// One tool call in the metrics log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ToolCallRecord {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    pub tool: String,
+    pub is_error: bool,
+    pub duration_ms: u64,
+}
+
+/// Aggregated metrics for a single tool.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct ToolStats {
+    pub calls: usize,
+    pub failures: usize,
+    pub total_ms: u64,
+    pub max_ms: u64,
+}
+
+impl ToolStats {
+    pub(crate) fn failure_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.calls as f64
+        }
+    }
+
+    pub(crate) fn average_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.calls as u64).unwrap_or(0)
+    }
+}
+
+fn metrics_path() -> PathBuf {
+    dirs::home_dir()
+        .map(|home| home.join(".cache").join("henri").join("tool-metrics.jsonl"))
+        .unwrap_or_else(|| PathBuf::from(".cache/henri/tool-metrics.jsonl"))
+}
+
+/// Attribute subsequent tool calls to `session_id`.
+pub(crate) fn set_session(session_id: &str) {
+    *SESSION_ID.lock().unwrap() = Some(session_id.to_string());
+}
+
+/// Append a tool call to the metrics log. Failures to write are ignored.
+pub(crate) fn record(tool: &str, is_error: bool, duration: Duration) {
+    let Some(session_id) = SESSION_ID.lock().unwrap().clone() else {
+        return;
+    };
+    let record = ToolCallRecord {
+        timestamp: Utc::now(),
+        session_id,
+        tool: tool.to_string(),
+        is_error,
+        duration_ms: duration.as_millis() as u64,
+    };
+    let _ = append(&record);
+}
+
+fn append(record: &ToolCallRecord) -> std::io::Result<()> {
+    let path = metrics_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string(record)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", json)
+}
+
+/// Load all records from the metrics log, oldest first.
+pub(crate) fn load() -> Vec<ToolCallRecord> {
+    let Ok(file) = File::open(metrics_path()) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// Aggregate records per tool name.
+pub(crate) fn aggregate<'a>(
+    records: impl IntoIterator<Item = &'a ToolCallRecord>,
+) -> BTreeMap<String, ToolStats> {
+    let mut stats: BTreeMap<String, ToolStats> = BTreeMap::new();
+    for record in records {
+        let entry = stats.entry(record.tool.clone()).or_default();
+        entry.calls += 1;
+        if record.is_error {
+            entry.failures += 1;
+        }
+        entry.total_ms += record.duration_ms;
+        entry.max_ms = entry.max_ms.max(record.duration_ms);
+    }
+    stats
+}
+
+/// Format aggregated stats as a table, most-failing tools first.
+pub(crate) fn format_table(stats: &BTreeMap<String, ToolStats>) -> String {
+    let mut rows: Vec<(&String, &ToolStats)> = stats.iter().collect();
+    rows.sort_by(|a, b| {
+        b.1.failure_rate()
+            .total_cmp(&a.1.failure_rate())
+            .then(b.1.calls.cmp(&a.1.calls))
+    });
+
+    let name_width = rows
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Tool".len());
+    let mut out = format!(
+        "{:<name_width$}  {:>6}  {:>8}  {:>6}  {:>9}  {:>9}\n",
+        "Tool", "Calls", "Failures", "Fail%", "Avg", "Max"
+    );
+    for (name, s) in rows {
+        out.push_str(&format!(
+            "{:<name_width$}  {:>6}  {:>8}  {:>5.1}%  {:>9}  {:>9}\n",
+            name,
+            s.calls,
+            s.failures,
+            s.failure_rate() * 100.0,
+            format_ms(s.average_ms()),
+            format_ms(s.max_ms),
+        ));
+    }
+    out
+}
+
+fn format_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(tool: &str, is_error: bool, duration_ms: u64) -> ToolCallRecord {
+        ToolCallRecord {
+            timestamp: Utc::now(),
+            session_id: "s1".to_string(),
+            tool: tool.to_string(),
+            is_error,
+            duration_ms,
+        }
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let records = vec![
+            record("bash", false, 100),
+            record("bash", true, 300),
+            record("fetch", false, 2000),
+        ];
+        let stats = aggregate(&records);
+        let bash = &stats["bash"];
+        assert_eq!(bash.calls, 2);
+        assert_eq!(bash.failures, 1);
+        assert_eq!(bash.average_ms(), 200);
+        assert_eq!(bash.max_ms, 300);
+        assert_eq!(bash.failure_rate(), 0.5);
+        assert_eq!(stats["fetch"].failures, 0);
+    }
+
+    #[test]
+    fn test_format_table_orders_by_failure_rate() {
+        let records = vec![record("fetch", false, 1500), record("mcp_broken", true, 5)];
+        let table = format_table(&aggregate(&records));
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[0].starts_with("Tool"));
+        assert!(lines[1].starts_with("mcp_broken"));
+        assert!(lines[1].contains("100.0%"));
+        assert!(lines[2].contains("1.5s"));
+    }
+}
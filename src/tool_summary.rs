@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Summarizing an oversized tool result after the context window overflows.
+//!
+//! When a request is rejected for being too long, the largest tool result in
+//! the conversation is replaced with a summary written by a (possibly
+//! cheaper) model and the request is retried once.
+
+use crate::provider::{ContentBlock, Message, MessageContent, Role};
+
+/// Tool results smaller than this are not worth summarizing.
+const MIN_SUMMARY_CHARS: usize = 8 * 1024;
+
+/// Most of a tool result sent to the summarizing model; the middle is cut.
+const MAX_SUMMARY_INPUT_CHARS: usize = 200 * 1024;
+
+const SUMMARY_SYSTEM_PROMPT: &str = r#"You are condensing the output of a tool call made by a coding assistant. The output was too large to fit in the assistant's context window.
+
+Summarize it so the assistant can continue its task without the original. Keep exact file paths, line numbers, identifiers, error messages, and any values the assistant is likely to need. Drop repetition and boilerplate. Reply with the summary only."#;
+
+/// The location of a tool result in the conversation.
+#[derive(Debug, PartialEq)]
+pub(crate) struct OversizedResult {
+    message: usize,
+    block: usize,
+    pub tool_name: String,
+    pub size: usize,
+}
+
+/// Find the largest tool result worth summarizing.
+pub(crate) fn largest_tool_result(messages: &[Message]) -> Option<OversizedResult> {
+    let mut largest: Option<OversizedResult> = None;
+    for (message_idx, message) in messages.iter().enumerate() {
+        let MessageContent::Blocks(blocks) = &message.content else {
+            continue;
+        };
+        for (block_idx, block) in blocks.iter().enumerate() {
+            let ContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                ..
+            } = block
+            else {
+                continue;
+            };
+            if content.len() < MIN_SUMMARY_CHARS
+                || largest.as_ref().is_some_and(|l| l.size >= content.len())
+            {
+                continue;
+            }
+            largest = Some(OversizedResult {
+                message: message_idx,
+                block: block_idx,
+                tool_name: tool_name(&messages[..message_idx], tool_use_id),
+                size: content.len(),
+            });
+        }
+    }
+    largest
+}
+
+/// The name of the tool called with `tool_use_id`.
+fn tool_name(messages: &[Message], tool_use_id: &str) -> String {
+    messages
+        .iter()
+        .rev()
+        .filter(|m| m.role == Role::Assistant)
+        .find_map(|m| match &m.content {
+            MessageContent::Blocks(blocks) => blocks.iter().find_map(|b| match b {
+                ContentBlock::ToolUse { id, name, .. } if id == tool_use_id => Some(name.clone()),
+                _ => None,
+            }),
+            MessageContent::Text(_) => None,
+        })
+        .unwrap_or_else(|| "tool".to_string())
+}
+
+fn content<'a>(messages: &'a [Message], result: &OversizedResult) -> Option<&'a str> {
+    let MessageContent::Blocks(blocks) = &messages.get(result.message)?.content else {
+        return None;
+    };
+    match blocks.get(result.block)? {
+        ContentBlock::ToolResult { content, .. } => Some(content),
+        _ => None,
+    }
+}
+
+fn content_mut<'a>(
+    messages: &'a mut [Message],
+    result: &OversizedResult,
+) -> Option<&'a mut String> {
+    let MessageContent::Blocks(blocks) = &mut messages.get_mut(result.message)?.content else {
+        return None;
+    };
+    match blocks.get_mut(result.block)? {
+        ContentBlock::ToolResult { content, .. } => Some(content),
+        _ => None,
+    }
+}
+
+/// Build the request that asks for a summary of `result`.
+pub(crate) fn summary_request(messages: &[Message], result: &OversizedResult) -> Vec<Message> {
+    let output = content(messages, result).unwrap_or_default();
+    let output = if output.len() > MAX_SUMMARY_INPUT_CHARS {
+        let half = MAX_SUMMARY_INPUT_CHARS / 2;
+        let mut head_end = half;
+        while !output.is_char_boundary(head_end) {
+            head_end -= 1;
+        }
+        let mut tail_start = output.len() - half;
+        while !output.is_char_boundary(tail_start) {
+            tail_start += 1;
+        }
+        format!(
+            "{}\n[... {} bytes omitted ...]\n{}",
+            &output[..head_end],
+            tail_start - head_end,
+            &output[tail_start..]
+        )
+    } else {
+        output.to_string()
+    };
+    vec![
+        Message::system(SUMMARY_SYSTEM_PROMPT),
+        Message::user(format!(
+            "<tool_result name=\"{}\">\n{}\n</tool_result>",
+            result.tool_name, output
+        )),
+    ]
+}
+
+/// Replace the tool result with `summary`.
+pub(crate) fn replace(messages: &mut [Message], result: &OversizedResult, summary: &str) {
+    if let Some(content) = content_mut(messages, result) {
+        *content = format!(
+            "[The {} byte output was too large for the context window and was summarized]\n{}",
+            result.size,
+            summary.trim()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_result(id: &str, content: String) -> Message {
+        Message {
+            role: Role::User,
+            content: MessageContent::Blocks(vec![ContentBlock::ToolResult {
+                tool_use_id: id.to_string(),
+                content,
+                is_error: false,
+                data: None,
+                mime_type: None,
+            }]),
+        }
+    }
+
+    #[test]
+    fn test_largest_tool_result() {
+        let mut messages = vec![
+            Message::user("find the bug"),
+            Message::assistant_blocks(vec![ContentBlock::ToolUse {
+                id: "1".to_string(),
+                name: "bash".to_string(),
+                input: serde_json::json!({"command": "cat build.log"}),
+                thought_signature: None,
+            }]),
+            tool_result("1", "x".repeat(MIN_SUMMARY_CHARS * 2)),
+            tool_result("2", "small".to_string()),
+        ];
+
+        let result = largest_tool_result(&messages).unwrap();
+        assert_eq!(result.tool_name, "bash");
+        assert_eq!(result.size, MIN_SUMMARY_CHARS * 2);
+
+        replace(&mut messages, &result, "Build failed in parser.rs:10");
+        assert!(matches!(
+            &messages[2].content,
+            MessageContent::Blocks(blocks) if matches!(
+                &blocks[0],
+                ContentBlock::ToolResult { content, .. } if content.ends_with("parser.rs:10")
+            )
+        ));
+        assert_eq!(largest_tool_result(&messages), None);
+    }
+}
@@ -0,0 +1,1051 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Applying a patch that changes several places in one or more files.
+//!
+//! Two formats are accepted: a unified diff, as made by `diff -u` or `git
+//! diff`, and the `*** Begin Patch` format some models are trained to write.
+//! Hunks are placed where their lines are found, nearest to where the header
+//! says, so line numbers don't have to be right. When the lines aren't found
+//! as written, whitespace is ignored, then up to two lines of context at each
+//! end. Every file is checked before anything is written, so a patch applies
+//! completely or not at all.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use super::sandbox;
+use super::{Tool, ToolDefinition, ToolResult};
+
+/// Lines of context that may be left out at each end of a hunk that doesn't
+/// match as written.
+const MAX_FUZZ: usize = 2;
+
+/// Lines of a hunk quoted when it doesn't match.
+const QUOTED_LINES: usize = 3;
+
+pub(crate) struct ApplyPatch;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApplyPatchInput {
+    patch: String,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// A line of a hunk.
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+impl Line {
+    /// The line as it is in the file before the change.
+    fn old(&self) -> Option<&str> {
+        match self {
+            Line::Context(text) | Line::Remove(text) => Some(text),
+            Line::Add(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Hunk {
+    /// 1-based line of the old file, from a unified diff's `@@ -l,n` header
+    old_start: Option<usize>,
+    /// Line to find before the hunk, from a `@@ text` header
+    anchor: Option<String>,
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Change {
+    Add(String),
+    Delete,
+    Update {
+        hunks: Vec<Hunk>,
+        move_to: Option<String>,
+    },
+}
+
+/// The change to one file.
+#[derive(Debug, Clone, PartialEq)]
+struct FilePatch {
+    path: String,
+    change: Change,
+}
+
+/// Parse `patch` in either format.
+fn parse(patch: &str) -> Result<Vec<FilePatch>, String> {
+    // Models sometimes wrap the patch in a code block.
+    let mut lines: Vec<&str> = patch.lines().collect();
+    if lines
+        .first()
+        .is_some_and(|l| l.trim_start().starts_with("```"))
+    {
+        lines.remove(0);
+        if lines.last().is_some_and(|l| l.trim() == "```") {
+            lines.pop();
+        }
+    }
+    let patches = if lines.iter().any(|l| l.trim() == "*** Begin Patch") {
+        parse_structured(&lines)?
+    } else {
+        parse_unified(&lines)?
+    };
+    if patches.is_empty() {
+        return Err(
+            "No file changes found. Expected a unified diff with ---/+++ headers \
+            and @@ hunks, or a *** Begin Patch block."
+                .to_string(),
+        );
+    }
+    Ok(patches)
+}
+
+/// Parse a line of a hunk's body. Empty lines are taken as empty context,
+/// since editors often strip the space in front of them.
+fn hunk_line(line: &str) -> Option<Line> {
+    let (prefix, text) = match line.chars().next() {
+        None => return Some(Line::Context(String::new())),
+        Some(c) => (c, line[c.len_utf8()..].to_string()),
+    };
+    match prefix {
+        ' ' => Some(Line::Context(text)),
+        '-' => Some(Line::Remove(text)),
+        '+' => Some(Line::Add(text)),
+        _ => None,
+    }
+}
+
+/// The file named in a `---` or `+++` header, or `None` for `/dev/null`.
+fn header_path(header: &str) -> Option<String> {
+    // A timestamp may follow a tab.
+    let path = header.split('\t').next().unwrap_or("").trim();
+    let path = path.trim_matches('"');
+    (path != "/dev/null" && !path.is_empty()).then(|| path.to_string())
+}
+
+/// The old start line and, when given, the old and new line counts of a
+/// `@@ -l,n +l,n @@` header.
+fn parse_range(header: &str) -> (Option<usize>, Option<(usize, usize)>) {
+    let ranges = header
+        .trim_start_matches('@')
+        .split("@@")
+        .next()
+        .unwrap_or("");
+    let mut old = None;
+    let mut new = None;
+    for token in ranges.split_whitespace() {
+        let parse = |range: &str| -> Option<(usize, usize)> {
+            let (start, count) = range.split_once(',').unwrap_or((range, "1"));
+            Some((start.parse().ok()?, count.parse().ok()?))
+        };
+        if let Some(range) = token.strip_prefix('-') {
+            old = parse(range);
+        } else if let Some(range) = token.strip_prefix('+') {
+            new = parse(range);
+        }
+    }
+    let counts = old.zip(new).map(|((_, old), (_, new))| (old, new));
+    (old.map(|(start, _)| start), counts)
+}
+
+fn is_file_header(lines: &[&str], i: usize) -> bool {
+    lines[i].starts_with("--- ") && lines.get(i + 1).is_some_and(|l| l.starts_with("+++ "))
+}
+
+fn parse_unified(lines: &[&str]) -> Result<Vec<FilePatch>, String> {
+    let mut patches = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if !is_file_header(lines, i) {
+            i += 1;
+            continue;
+        }
+        let mut old = header_path(&lines[i][4..]);
+        let mut new = header_path(&lines[i + 1][4..]);
+        // Drop git's a/ and b/ prefixes.
+        if old.as_deref().is_none_or(|p| p.starts_with("a/"))
+            && new.as_deref().is_none_or(|p| p.starts_with("b/"))
+        {
+            old = old.map(|p| p[2..].to_string());
+            new = new.map(|p| p[2..].to_string());
+        }
+        i += 2;
+
+        let mut hunks = Vec::new();
+        loop {
+            while i < lines.len() && lines[i].starts_with('\\') {
+                i += 1;
+            }
+            if i >= lines.len() || !lines[i].starts_with("@@") {
+                break;
+            }
+            let (old_start, mut counts) = parse_range(lines[i]);
+            i += 1;
+            let mut hunk = Hunk {
+                old_start,
+                ..Default::default()
+            };
+            while i < lines.len() {
+                let line = lines[i];
+                match counts {
+                    Some((0, 0)) => break,
+                    Some(_) => {}
+                    None if line.starts_with("@@")
+                        || line.starts_with("diff ")
+                        || is_file_header(lines, i) =>
+                    {
+                        break;
+                    }
+                    None => {}
+                }
+                if line.starts_with('\\') {
+                    i += 1;
+                    continue;
+                }
+                let Some(parsed) = hunk_line(line) else {
+                    break;
+                };
+                if let Some((old, new)) = &mut counts {
+                    match parsed {
+                        Line::Context(_) => {
+                            *old = old.saturating_sub(1);
+                            *new = new.saturating_sub(1);
+                        }
+                        Line::Remove(_) => *old = old.saturating_sub(1),
+                        Line::Add(_) => *new = new.saturating_sub(1),
+                    }
+                }
+                hunk.lines.push(parsed);
+                i += 1;
+            }
+            hunks.push(hunk);
+        }
+
+        let change = match (&old, &new) {
+            (None, None) => return Err("A file header names /dev/null twice".to_string()),
+            (None, Some(_)) => Change::Add(added_content(&hunks)),
+            (Some(_), None) => Change::Delete,
+            (Some(old), Some(new)) => {
+                if hunks.is_empty() && old == new {
+                    return Err(format!("The diff of {} has no hunks", old));
+                }
+                Change::Update {
+                    hunks,
+                    move_to: (old != new).then(|| new.clone()),
+                }
+            }
+        };
+        patches.push(FilePatch {
+            path: old.or(new).unwrap_or_default(),
+            change,
+        });
+    }
+    Ok(patches)
+}
+
+/// Content of a created file: the added lines of its hunks.
+fn added_content(hunks: &[Hunk]) -> String {
+    let mut content = String::new();
+    for line in hunks.iter().flat_map(|h| &h.lines) {
+        if let Line::Add(text) = line {
+            content.push_str(text);
+            content.push('\n');
+        }
+    }
+    content
+}
+
+fn parse_structured(lines: &[&str]) -> Result<Vec<FilePatch>, String> {
+    let start = lines
+        .iter()
+        .position(|l| l.trim() == "*** Begin Patch")
+        .unwrap_or(0);
+    let mut patches = Vec::new();
+    let mut i = start + 1;
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        i += 1;
+        if line.trim() == "*** End Patch" {
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(path) = line.strip_prefix("*** Add File: ") {
+            let mut hunk = Hunk::default();
+            while i < lines.len() && !lines[i].starts_with("***") {
+                let text = lines[i].strip_prefix('+').unwrap_or(lines[i]);
+                hunk.lines.push(Line::Add(text.to_string()));
+                i += 1;
+            }
+            patches.push(FilePatch {
+                path: path.trim().to_string(),
+                change: Change::Add(added_content(&[hunk])),
+            });
+        } else if let Some(path) = line.strip_prefix("*** Delete File: ") {
+            patches.push(FilePatch {
+                path: path.trim().to_string(),
+                change: Change::Delete,
+            });
+        } else if let Some(path) = line.strip_prefix("*** Update File: ") {
+            let mut move_to = None;
+            if let Some(to) = lines.get(i).and_then(|l| l.strip_prefix("*** Move to: ")) {
+                move_to = Some(to.trim().to_string());
+                i += 1;
+            }
+            let mut hunks: Vec<Hunk> = Vec::new();
+            while i < lines.len() {
+                let line = lines[i];
+                if line.trim_end() == "*** End of File" {
+                    i += 1;
+                    continue;
+                }
+                if line.starts_with("***") {
+                    break;
+                }
+                i += 1;
+                if let Some(anchor) = line.strip_prefix("@@") {
+                    let anchor = anchor.trim();
+                    hunks.push(Hunk {
+                        anchor: (!anchor.is_empty()).then(|| anchor.to_string()),
+                        ..Default::default()
+                    });
+                    continue;
+                }
+                let parsed = hunk_line(line)
+                    .ok_or_else(|| format!("Unexpected line in the patch of {}: {}", path, line))?;
+                if hunks.is_empty() {
+                    hunks.push(Hunk::default());
+                }
+                hunks.last_mut().expect("a hunk").lines.push(parsed);
+            }
+            hunks.retain(|h| !h.lines.is_empty());
+            if hunks.is_empty() && move_to.is_none() {
+                return Err(format!("The patch of {} has no hunks", path.trim()));
+            }
+            patches.push(FilePatch {
+                path: path.trim().to_string(),
+                change: Change::Update { hunks, move_to },
+            });
+        } else {
+            return Err(format!("Unexpected line in the patch: {}", line));
+        }
+    }
+    Ok(patches)
+}
+
+/// How closely a hunk's lines had to match the file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Strictness {
+    Exact,
+    TrailingWhitespace,
+    Whitespace,
+}
+
+impl Strictness {
+    fn matches(self, file: &str, hunk: &str) -> bool {
+        match self {
+            Strictness::Exact => file == hunk,
+            Strictness::TrailingWhitespace => file.trim_end() == hunk.trim_end(),
+            Strictness::Whitespace => file.split_whitespace().eq(hunk.split_whitespace()),
+        }
+    }
+}
+
+/// Where a hunk was applied.
+#[derive(Debug, Clone, PartialEq)]
+struct Placement {
+    /// 1-based line of the file
+    line: usize,
+    /// Lines from where the header said, when it gave a line number
+    offset: isize,
+    /// Lines of context left out at each end
+    fuzz: usize,
+    strictness: Strictness,
+}
+
+impl Placement {
+    /// A note on how the hunk was applied, when not as written.
+    fn note(&self) -> Option<String> {
+        let mut notes = Vec::new();
+        if self.offset != 0 {
+            notes.push(format!("offset {:+}", self.offset));
+        }
+        match self.strictness {
+            Strictness::Exact => {}
+            Strictness::TrailingWhitespace => notes.push("ignoring trailing whitespace".into()),
+            Strictness::Whitespace => notes.push("ignoring whitespace".into()),
+        }
+        if self.fuzz > 0 {
+            notes.push(format!("fuzz {}", self.fuzz));
+        }
+        (!notes.is_empty()).then(|| format!("applied at line {} ({})", self.line, notes.join(", ")))
+    }
+}
+
+/// A replacement of lines `start..end` of the file.
+struct Edit {
+    start: usize,
+    end: usize,
+    lines: Vec<String>,
+}
+
+/// Find where `hunk` goes in `lines`, away from the `edits` already made.
+/// `cursor` is where the previous hunk ended.
+fn place(lines: &[&str], hunk: &Hunk, cursor: usize, edits: &[Edit]) -> Option<(Edit, Placement)> {
+    let anchored = hunk.anchor.as_deref().and_then(|anchor| {
+        let is_anchor = |i: &usize| lines[*i].trim() == anchor.trim();
+        (cursor..lines.len())
+            .find(is_anchor)
+            .or_else(|| (0..lines.len()).find(is_anchor))
+    });
+    // With a line number, the nearest match is taken; otherwise the first
+    // after the previous hunk (or the anchor), as the hunks are in order.
+    let (hint, exact_hint) = match (hunk.old_start, anchored) {
+        (Some(start), _) => (start.saturating_sub(1), true),
+        (None, Some(anchor)) => (anchor + 1, false),
+        (None, None) => (cursor, false),
+    };
+
+    if hunk.lines.iter().all(|l| l.old().is_none()) {
+        // Only additions: a unified diff says after which line.
+        let at = match hunk.old_start {
+            Some(start) => start.min(lines.len()),
+            None if anchored.is_some() => hint.min(lines.len()),
+            None => lines.len(),
+        };
+        let added = hunk.lines.iter().filter_map(|l| match l {
+            Line::Add(text) => Some(text.clone()),
+            _ => None,
+        });
+        let placement = Placement {
+            line: at + 1,
+            offset: 0,
+            fuzz: 0,
+            strictness: Strictness::Exact,
+        };
+        return Some((
+            Edit {
+                start: at,
+                end: at,
+                lines: added.collect(),
+            },
+            placement,
+        ));
+    }
+
+    for fuzz in 0..=MAX_FUZZ {
+        let lead = hunk
+            .lines
+            .iter()
+            .take(fuzz)
+            .take_while(|l| matches!(l, Line::Context(_)))
+            .count();
+        let trail = hunk
+            .lines
+            .iter()
+            .rev()
+            .take(fuzz)
+            .take_while(|l| matches!(l, Line::Context(_)))
+            .count();
+        if fuzz > 0 && lead < fuzz && trail < fuzz {
+            break;
+        }
+        if lead + trail >= hunk.lines.len() {
+            break;
+        }
+        let body = &hunk.lines[lead..hunk.lines.len() - trail];
+        let old: Vec<&str> = body.iter().filter_map(Line::old).collect();
+        if old.is_empty() || old.len() > lines.len() {
+            continue;
+        }
+        for strictness in [
+            Strictness::Exact,
+            Strictness::TrailingWhitespace,
+            Strictness::Whitespace,
+        ] {
+            let best = (0..=lines.len() - old.len())
+                .filter(|&p| {
+                    old.iter()
+                        .zip(&lines[p..])
+                        .all(|(hunk, file)| strictness.matches(file, hunk))
+                })
+                .filter(|&p| {
+                    let end = p + old.len();
+                    !edits.iter().any(|e| p < e.end && e.start < end)
+                })
+                .min_by_key(|&p| {
+                    let start = p.saturating_sub(lead);
+                    if exact_hint {
+                        (0, start.abs_diff(hint))
+                    } else if start >= hint {
+                        (0, start - hint)
+                    } else {
+                        (1, hint - start)
+                    }
+                });
+            let Some(p) = best else {
+                continue;
+            };
+
+            // Context is taken from the file, so whitespace the hunk got
+            // wrong isn't changed.
+            let mut replacement = Vec::new();
+            let mut at = p;
+            for line in body {
+                match line {
+                    Line::Context(_) => {
+                        replacement.push(lines[at].to_string());
+                        at += 1;
+                    }
+                    Line::Remove(_) => at += 1,
+                    Line::Add(text) => replacement.push(text.clone()),
+                }
+            }
+            let start = p.saturating_sub(lead);
+            let placement = Placement {
+                line: start + 1,
+                offset: if hunk.old_start.is_some() {
+                    start as isize - hint as isize
+                } else {
+                    0
+                },
+                fuzz,
+                strictness,
+            };
+            return Some((
+                Edit {
+                    start: p,
+                    end: p + old.len(),
+                    lines: replacement,
+                },
+                placement,
+            ));
+        }
+    }
+    None
+}
+
+/// Why hunk `number` of `hunk` isn't found in `lines`.
+fn mismatch(lines: &[&str], hunk: &Hunk, number: usize) -> String {
+    let old: Vec<&str> = hunk.lines.iter().filter_map(Line::old).collect();
+    let mut message = format!("hunk {} does not match the file", number);
+    if let Some(start) = hunk.old_start {
+        message.push_str(&format!(" (its header says line {})", start));
+    }
+    message.push_str(". It expects:\n");
+    for line in old.iter().take(QUOTED_LINES) {
+        message.push_str(&format!("    {}\n", line));
+    }
+    if let Some(first) = old.iter().find(|l| !l.trim().is_empty()) {
+        let found: Vec<String> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| l.trim() == first.trim())
+            .take(5)
+            .map(|(i, _)| (i + 1).to_string())
+            .collect();
+        if found.is_empty() {
+            message.push_str(&format!("The line '{}' is not in the file.", first.trim()));
+        } else {
+            message.push_str(&format!(
+                "The line '{}' is at line {}, but the lines around it differ.",
+                first.trim(),
+                found.join(", ")
+            ));
+        }
+    }
+    message
+}
+
+/// Apply `hunks` to `text`, keeping its line endings.
+fn apply_hunks(text: &str, hunks: &[Hunk]) -> Result<(String, Vec<Placement>), String> {
+    let eol = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let lines: Vec<&str> = text.lines().collect();
+    let mut edits: Vec<Edit> = Vec::new();
+    let mut placements = Vec::new();
+    let mut cursor = 0;
+    for (i, hunk) in hunks.iter().enumerate() {
+        let (edit, placement) =
+            place(&lines, hunk, cursor, &edits).ok_or_else(|| mismatch(&lines, hunk, i + 1))?;
+        cursor = edit.end;
+        edits.push(edit);
+        placements.push(placement);
+    }
+
+    edits.sort_by_key(|e| (e.start, e.end));
+    let mut result: Vec<&str> = Vec::new();
+    let mut at = 0;
+    for edit in &edits {
+        result.extend(&lines[at..edit.start]);
+        result.extend(edit.lines.iter().map(String::as_str));
+        at = edit.end;
+    }
+    result.extend(&lines[at..]);
+    let mut content = result.join(eol);
+    if !result.is_empty() && (text.ends_with('\n') || text.is_empty()) {
+        content.push_str(eol);
+    }
+    Ok((content, placements))
+}
+
+/// A file to write: its content before (`None` if it doesn't exist) and
+/// after (`None` to delete it).
+struct Write {
+    path: PathBuf,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+/// A checked change to one file, ready to be written.
+struct Planned {
+    /// `A`, `M`, `D` or `R`
+    status: char,
+    display: String,
+    /// Content the change is shown against, and after it
+    base: String,
+    result: String,
+    placements: Vec<Placement>,
+    writes: Vec<Write>,
+}
+
+/// The file patches the tool call in `input` changes, for summaries of the
+/// conversation.
+pub(crate) fn patch_paths(input: &serde_json::Value) -> Vec<String> {
+    let Some(patch) = input.get("patch").and_then(|p| p.as_str()) else {
+        return Vec::new();
+    };
+    let mut paths = Vec::new();
+    for file in parse(patch).unwrap_or_default() {
+        if let Change::Update {
+            move_to: Some(to), ..
+        } = &file.change
+        {
+            paths.push(to.clone());
+        }
+        paths.push(file.path);
+    }
+    paths
+}
+
+fn read(path: &std::path::Path, display: &str) -> Result<String, String> {
+    if !path.is_file() {
+        return Err(format!("{}: file not found", display));
+    }
+    fs::read_to_string(path).map_err(|e| format!("{}: failed to read: {}", display, e))
+}
+
+/// Check `file` against the files on disk.
+fn plan(tool_use_id: &str, file: FilePatch) -> Result<Planned, Box<ToolResult>> {
+    let error = |message: String| Box::new(ToolResult::error(tool_use_id, message));
+    let resolved = super::paths::resolve_tool_path(&file.path);
+    let (path, display) = (resolved.path, resolved.display);
+    match file.change {
+        Change::Add(content) => {
+            if path.exists() {
+                return Err(error(format!(
+                    "{}: already exists; patch it as a change to the file",
+                    display
+                )));
+            }
+            Ok(Planned {
+                status: 'A',
+                display,
+                base: String::new(),
+                result: content.clone(),
+                placements: Vec::new(),
+                writes: vec![Write {
+                    path,
+                    before: None,
+                    after: Some(content),
+                }],
+            })
+        }
+        Change::Delete => {
+            let before = read(&path, &display).map_err(error)?;
+            Ok(Planned {
+                status: 'D',
+                display,
+                base: before.clone(),
+                result: String::new(),
+                placements: Vec::new(),
+                writes: vec![Write {
+                    path,
+                    before: Some(before),
+                    after: None,
+                }],
+            })
+        }
+        Change::Update { hunks, move_to } => {
+            let before = read(&path, &display).map_err(error)?;
+            let (after, placements) = apply_hunks(&before, &hunks)
+                .map_err(|message| error(format!("{}: {}", display, message)))?;
+            let mut planned = Planned {
+                status: 'M',
+                display,
+                base: before.clone(),
+                result: after.clone(),
+                placements,
+                writes: Vec::new(),
+            };
+            match move_to {
+                Some(to) => {
+                    let target = super::paths::resolve_tool_path(&to);
+                    if target.path.exists() {
+                        return Err(error(format!("{}: already exists", target.display)));
+                    }
+                    planned.status = 'R';
+                    planned.display = format!("{} -> {}", planned.display, target.display);
+                    planned.writes.push(Write {
+                        path,
+                        before: Some(before),
+                        after: None,
+                    });
+                    planned.writes.push(Write {
+                        path: target.path,
+                        before: None,
+                        after: Some(after),
+                    });
+                }
+                None => planned.writes.push(Write {
+                    path,
+                    before: Some(before),
+                    after: Some(after),
+                }),
+            }
+            Ok(planned)
+        }
+    }
+}
+
+/// Put `path` in the state `content` describes.
+fn put(path: &std::path::Path, content: Option<&str>) -> std::io::Result<()> {
+    match content {
+        Some(content) => {
+            if let Some(parent) = path.parent()
+                && !parent.as_os_str().is_empty()
+            {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, content)
+        }
+        None => match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+    }
+}
+
+/// Lines reporting the planned changes, e.g. `M src/lib.rs (+3 -1)`.
+fn report(planned: &[Planned]) -> String {
+    let mut lines = Vec::new();
+    for p in planned {
+        let diff =
+            crate::diff::unified_diff(std::path::Path::new(&p.display), &p.base, &p.result, 0);
+        let counts = match p.status {
+            'D' => String::new(),
+            _ => format!(" (+{} -{})", diff.lines_added, diff.lines_removed),
+        };
+        lines.push(format!("{} {}{}", p.status, p.display, counts));
+        for (i, placement) in p.placements.iter().enumerate() {
+            if let Some(note) = placement.note() {
+                lines.push(format!("  hunk {} {}", i + 1, note));
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+impl Tool for ApplyPatch {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "apply_patch".to_string(),
+            description: "Apply a patch changing one or more files, as an alternative to file_edit for changes in many places. Accepts a unified diff (---/+++ headers, @@ hunks, /dev/null to create or delete a file) or a *** Begin Patch block with *** Add File:, *** Update File: (optionally *** Move to:) and *** Delete File: sections. Hunks are found by their context lines, so line numbers may be approximate and whitespace differences are tolerated. Every hunk is checked before any file is written: the patch applies completely or not at all. Use dryRun to check a patch without writing."
+                .to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "patch": {
+                        "type": "string",
+                        "description": "The patch, with file paths relative to the working directory"
+                    },
+                    "dryRun": {
+                        "type": "boolean",
+                        "description": "Only check that the patch applies and report what it would change (default false)"
+                    }
+                },
+                "required": ["patch"]
+            }),
+        }
+    }
+
+    async fn execute(
+        &self,
+        tool_use_id: &str,
+        input: serde_json::Value,
+        output: &crate::output::OutputContext,
+        services: &crate::services::Services,
+    ) -> ToolResult {
+        if services.is_read_only() {
+            return ToolResult::error(tool_use_id, "Read-only mode is enabled");
+        }
+        let input: ApplyPatchInput = match super::deserialize_input(tool_use_id, input) {
+            Ok(i) => i,
+            Err(e) => return *e,
+        };
+        let files = match parse(&input.patch) {
+            Ok(files) => files,
+            Err(e) => return ToolResult::error(tool_use_id, e),
+        };
+
+        let mut planned = Vec::new();
+        let mut seen = HashSet::new();
+        for file in files {
+            let p = match plan(tool_use_id, file) {
+                Ok(p) => p,
+                Err(e) => return e.with_summary("Patch does not apply"),
+            };
+            for write in &p.writes {
+                if !seen.insert(write.path.clone()) {
+                    return ToolResult::error(
+                        tool_use_id,
+                        format!("{} is changed more than once in the patch", p.display),
+                    );
+                }
+            }
+            planned.push(p);
+        }
+
+        let files = if planned.len() == 1 { "file" } else { "files" };
+        if input.dry_run {
+            return ToolResult::success(
+                tool_use_id,
+                format!(
+                    "The patch applies cleanly (dry run, nothing was written):\n{}",
+                    report(&planned)
+                ),
+            )
+            .with_summary(format!("Dry run: {} {}", planned.len(), files));
+        }
+
+        let cwd = super::paths::workspace_root();
+        for write in planned.iter().flat_map(|p| &p.writes) {
+            if let Err(message) = sandbox::check_write_access(
+                &write.path,
+                &cwd,
+                &services.permissions.allowed_paths(),
+                services.is_sandbox_enabled(),
+            ) && !services
+                .permissions
+                .approve_write(&write.path, services, output)
+                .await
+            {
+                return ToolResult::error(tool_use_id, message);
+            }
+        }
+
+        // Write everything, putting back what was written if a write fails.
+        let writes: Vec<&Write> = planned.iter().flat_map(|p| &p.writes).collect();
+        for write in &writes {
+            services.checkpoints.record(&write.path);
+        }
+        for (i, write) in writes.iter().enumerate() {
+            if let Err(e) = put(&write.path, write.after.as_deref()) {
+                for done in writes[..i].iter().rev() {
+                    let _ = put(&done.path, done.before.as_deref());
+                }
+                return ToolResult::error(
+                    tool_use_id,
+                    format!(
+                        "Failed to write {}: {}. No files were changed.",
+                        write.path.display(),
+                        e
+                    ),
+                );
+            }
+            if write.after.is_some() {
+                services.seen_files.record(&write.path);
+            }
+        }
+
+        let mut msg = format!(
+            "Applied the patch to {} {}:\n{}",
+            planned.len(),
+            files,
+            report(&planned)
+        );
+        for p in &planned {
+            let diff =
+                crate::diff::unified_diff(std::path::Path::new(&p.display), &p.base, &p.result, 3);
+            if diff.has_changes {
+                output.emit(crate::output::OutputEvent::FileDiff {
+                    diff: diff.unified_diff,
+                    language: crate::syntax::language_from_path(&p.display),
+                    summary: crate::diff::format_diff_summary(diff.lines_added, diff.lines_removed),
+                });
+            }
+        }
+        for write in &writes {
+            let Some(content) = &write.after else {
+                continue;
+            };
+            let mut content = content.clone();
+            if let Some(report) = services
+                .post_edit_checks
+                .run(&write.path, output, services)
+                .await
+            {
+                msg.push_str(&report);
+                content = fs::read_to_string(&write.path).unwrap_or(content);
+                services.seen_files.record(&write.path);
+            }
+            let diagnostics =
+                super::notify_lsp_and_get_diagnostics(&write.path, &content, services, output)
+                    .await;
+            msg = super::format_message_with_diagnostics(msg, &diagnostics, output);
+        }
+
+        ToolResult::success(tool_use_id, msg).with_summary(format!("{} {}", planned.len(), files))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let unified = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,2 @@\n fn a() {}\n-fn b() {}\n+fn c() {}\n--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1 @@\n+hello\n";
+        assert_eq!(
+            parse(unified).unwrap(),
+            vec![
+                FilePatch {
+                    path: "src/lib.rs".to_string(),
+                    change: Change::Update {
+                        hunks: vec![Hunk {
+                            old_start: Some(1),
+                            anchor: None,
+                            lines: vec![
+                                Line::Context("fn a() {}".to_string()),
+                                Line::Remove("fn b() {}".to_string()),
+                                Line::Add("fn c() {}".to_string()),
+                            ],
+                        }],
+                        move_to: None,
+                    },
+                },
+                FilePatch {
+                    path: "new.txt".to_string(),
+                    change: Change::Add("hello\n".to_string()),
+                },
+            ]
+        );
+
+        let structured = "*** Begin Patch\n*** Update File: src/lib.rs\n*** Move to: src/util.rs\n@@ impl Foo\n-    old\n+    new\n*** Delete File: gone.rs\n*** End Patch\n";
+        let files = parse(structured).unwrap();
+        assert_eq!(
+            files[0].change,
+            Change::Update {
+                hunks: vec![Hunk {
+                    old_start: None,
+                    anchor: Some("impl Foo".to_string()),
+                    lines: vec![
+                        Line::Remove("    old".to_string()),
+                        Line::Add("    new".to_string()),
+                    ],
+                }],
+                move_to: Some("src/util.rs".to_string()),
+            }
+        );
+        assert_eq!(files[1].change, Change::Delete);
+        assert!(parse("just some text\n").is_err());
+    }
+
+    #[test]
+    fn test_apply_hunks() {
+        let text = "one\ntwo\nthree\n  four\nfive\nsix\n";
+        let hunk = |old_start, lines: &[&str]| Hunk {
+            old_start,
+            anchor: None,
+            lines: lines.iter().map(|l| hunk_line(l).unwrap()).collect(),
+        };
+
+        // The header's line number is off by two.
+        let (result, placements) =
+            apply_hunks(text, &[hunk(Some(1), &[" three", "-  four", "+  4"])]).unwrap();
+        assert_eq!(result, "one\ntwo\nthree\n  4\nfive\nsix\n");
+        assert_eq!(
+            placements[0].note().unwrap(),
+            "applied at line 3 (offset +2)"
+        );
+
+        // Whitespace differences in context are kept as they are in the file.
+        let (result, _) = apply_hunks(text, &[hunk(None, &[" four", "-five", "+5"])]).unwrap();
+        assert_eq!(result, "one\ntwo\nthree\n  four\n5\nsix\n");
+
+        // Context that no longer matches is left out.
+        let (result, placements) =
+            apply_hunks(text, &[hunk(None, &[" changed", "-two", "+2", " three"])]).unwrap();
+        assert_eq!(result, "one\n2\nthree\n  four\nfive\nsix\n");
+        assert_eq!(placements[0].fuzz, 1);
+
+        let error = apply_hunks(text, &[hunk(Some(1), &[" one", "-seven"])]).unwrap_err();
+        assert!(error.contains("hunk 1 does not match"), "{}", error);
+        assert!(error.contains("'one' is at line 1"), "{}", error);
+
+        // Line endings are kept.
+        let (result, _) = apply_hunks("a\r\nb\r\n", &[hunk(None, &["-b", "+c"])]).unwrap();
+        assert_eq!(result, "a\r\nc\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        fs::write(&a, "alpha\nbeta\n").unwrap();
+        fs::write(&b, "gamma\n").unwrap();
+        let run = |patch: String, dry_run: bool| async move {
+            ApplyPatch
+                .execute(
+                    "test-id",
+                    serde_json::json!({ "patch": patch, "dryRun": dry_run }),
+                    &crate::output::OutputContext::null(),
+                    &crate::services::Services::null(),
+                )
+                .await
+        };
+        let (a_name, b_name) = (a.display(), b.display());
+
+        // The second hunk doesn't apply, so neither file is changed.
+        let patch = format!(
+            "--- {a_name}\n+++ {a_name}\n@@ -1 +1 @@\n-alpha\n+ALPHA\n--- {b_name}\n+++ {b_name}\n@@ -1 +1 @@\n-delta\n+DELTA\n"
+        );
+        let result = run(patch, false).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("hunk 1 does not match"));
+        assert_eq!(fs::read_to_string(&a).unwrap(), "alpha\nbeta\n");
+
+        let patch = format!(
+            "--- {a_name}\n+++ {a_name}\n@@ -1 +1 @@\n-alpha\n+ALPHA\n--- {b_name}\n+++ /dev/null\n@@ -1 +0,0 @@\n-gamma\n"
+        );
+        let result = run(patch.clone(), true).await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("dry run"));
+        assert!(b.exists());
+
+        let result = run(patch, false).await;
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "ALPHA\nbeta\n");
+        assert!(!b.exists());
+    }
+}
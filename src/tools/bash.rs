@@ -22,7 +22,7 @@ use std::path::PathBuf;
 use std::process::Stdio;
 
 use serde::Deserialize;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 
 use crate::output;
@@ -30,7 +30,7 @@ use crate::output;
 use super::sandbox;
 use super::{Tool, ToolDefinition, ToolResult};
 
-const DEFAULT_TIMEOUT_SECS: u64 = 120;
+pub(super) const DEFAULT_TIMEOUT_SECS: u64 = 120;
 
 // Keep tool-result payloads (what gets sent back to the model) bounded.
 // These are intended to match Pi defaults (50KB or 2000 lines, whichever hits first).
@@ -252,58 +252,75 @@ Web content fetching:
             Err(e) => return *e,
         };
 
-        let timeout_secs = input.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS);
+        if let Err(message) = services
+            .permissions
+            .approve_bash(&input.command, services, output)
+            .await
+        {
+            return ToolResult::error(tool_use_id, message);
+        }
 
-        // Determine effective working directory
-        let effective_cwd: PathBuf = if let Some(ref cwd) = input.cwd {
-            let path = std::path::Path::new(cwd);
-            if !path.is_dir() {
-                return ToolResult::error(
-                    tool_use_id,
-                    format!("Working directory does not exist: {}", cwd),
-                );
-            }
-            path.to_path_buf()
-        } else {
-            std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-        };
+        run(
+            tool_use_id,
+            &input.command,
+            input.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS),
+            input.cwd.as_deref(),
+            None,
+            output,
+            services,
+        )
+        .await
+    }
+}
 
-        let mut cmd = Command::new("bash");
-        cmd.arg("-c").arg(&input.command);
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
-
-        // Prevent interactive editors from opening (git rebase, git commit, etc.)
-        cmd.env("GIT_EDITOR", "true");
-        cmd.env("GIT_TERMINAL_PROMPT", "0");
-        cmd.env("EDITOR", "true");
-        cmd.env("VISUAL", "true");
-
-        cmd.current_dir(&effective_cwd);
-
-        if services.is_read_only() {
-            if let Some(ruleset) = sandbox::create_read_only_ruleset() {
-                let mut ruleset = Some(ruleset);
-                unsafe {
-                    cmd.pre_exec(move || {
-                        if let Some(rs) = ruleset.take() {
-                            sandbox::apply_ruleset(rs)
-                        } else {
-                            Ok(())
-                        }
-                    });
-                }
-            }
-        } else if services.is_sandbox_enabled()
-            && let Some(ruleset) = sandbox::create_bash_ruleset(&effective_cwd)
-        {
-            // Wrap in Option so we can take() it in the FnMut closure
-            let mut ruleset = Some(ruleset);
+/// Run a shell command with the sandbox policy of `services`: landlock
+/// read-only in read-only mode, writes limited to the working directory and
+/// allowed paths when the sandbox is on. `stdin`, if given, is written to
+/// the command's standard input.
+pub(super) async fn run(
+    tool_use_id: &str,
+    command: &str,
+    timeout_secs: u64,
+    cwd: Option<&str>,
+    stdin: Option<String>,
+    output: &crate::output::OutputContext,
+    services: &crate::services::Services,
+) -> ToolResult {
+    // Determine effective working directory
+    let effective_cwd: PathBuf = if let Some(cwd) = cwd {
+        let path = std::path::Path::new(cwd);
+        if !path.is_dir() {
+            return ToolResult::error(
+                tool_use_id,
+                format!("Working directory does not exist: {}", cwd),
+            );
+        }
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+    };
 
-            // SAFETY: The pre_exec closure runs after fork but before exec.
-            // sandbox::apply_ruleset only makes direct syscalls (landlock_restrict_self,
-            // prctl) which are async-signal-safe. The ruleset file descriptors were
-            // opened before fork.
+    let mut cmd = Command::new("bash");
+    cmd.arg("-c").arg(command);
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    if stdin.is_some() {
+        cmd.stdin(Stdio::piped());
+    }
+    // Kill the command if the tool call is cancelled and its future dropped.
+    cmd.kill_on_drop(true);
+
+    // Prevent interactive editors from opening (git rebase, git commit, etc.)
+    cmd.env("GIT_EDITOR", "true");
+    cmd.env("GIT_TERMINAL_PROMPT", "0");
+    cmd.env("EDITOR", "true");
+    cmd.env("VISUAL", "true");
+
+    cmd.current_dir(&effective_cwd);
+
+    if services.is_read_only() {
+        if let Some(ruleset) = sandbox::create_read_only_ruleset() {
+            let mut ruleset = Some(ruleset);
             unsafe {
                 cmd.pre_exec(move || {
                     if let Some(rs) = ruleset.take() {
@@ -314,167 +331,197 @@ Web content fetching:
                 });
             }
         }
+    } else if services.is_sandbox_enabled()
+        && let Some(ruleset) =
+            sandbox::create_bash_ruleset(&effective_cwd, &services.permissions.allowed_paths())
+    {
+        // Wrap in Option so we can take() it in the FnMut closure
+        let mut ruleset = Some(ruleset);
+
+        // SAFETY: The pre_exec closure runs after fork but before exec.
+        // sandbox::apply_ruleset only makes direct syscalls (landlock_restrict_self,
+        // prctl) which are async-signal-safe. The ruleset file descriptors were
+        // opened before fork.
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(rs) = ruleset.take() {
+                    sandbox::apply_ruleset(rs)
+                } else {
+                    Ok(())
+                }
+            });
+        }
+    }
 
-        let mut child = match cmd.spawn() {
-            Ok(c) => c,
-            Err(e) => {
-                return ToolResult::error(tool_use_id, format!("Failed to spawn command: {}", e));
-            }
-        };
+    let mut child = match cmd.spawn() {
+        Ok(c) => c,
+        Err(e) => {
+            return ToolResult::error(tool_use_id, format!("Failed to spawn command: {}", e));
+        }
+    };
+
+    if let Some(input) = stdin
+        && let Some(mut pipe) = child.stdin.take()
+    {
+        // Written from a task so a command that doesn't read its input can't
+        // block on a full pipe; dropping the pipe closes it.
+        tokio::spawn(async move {
+            let _ = pipe.write_all(input.as_bytes()).await;
+        });
+    }
 
-        let stdout = child.stdout.take().expect("stdout was piped");
-        let stderr = child.stderr.take().expect("stderr was piped");
+    let stdout = child.stdout.take().expect("stdout was piped");
+    let stderr = child.stderr.take().expect("stderr was piped");
 
-        let timeout_duration = std::time::Duration::from_secs(timeout_secs);
+    let timeout_duration = std::time::Duration::from_secs(timeout_secs);
 
-        let stdout_output = output.clone();
-        let stdout_task =
-            tokio::spawn(async move { capture_stream_output(stdout, stdout_output).await });
+    let stdout_output = output.clone();
+    let stdout_task =
+        tokio::spawn(async move { capture_stream_output(stdout, stdout_output).await });
 
-        let stderr_output = output.clone();
-        let stderr_task =
-            tokio::spawn(async move { capture_stream_output(stderr, stderr_output).await });
+    let stderr_output = output.clone();
+    let stderr_task =
+        tokio::spawn(async move { capture_stream_output(stderr, stderr_output).await });
 
-        // Wait for child with interrupt and timeout handling.
-        // We separate child.wait() from output collection so we can kill on interrupt/timeout.
-        enum WaitOutcome {
-            Completed(Result<std::process::ExitStatus, std::io::Error>),
-            Interrupted,
-            TimedOut,
-        }
+    // Wait for child with interrupt and timeout handling.
+    // We separate child.wait() from output collection so we can kill on interrupt/timeout.
+    enum WaitOutcome {
+        Completed(Result<std::process::ExitStatus, std::io::Error>),
+        Interrupted,
+        TimedOut,
+    }
 
-        let wait_result = tokio::select! {
-            biased;
-            // Check for interrupt every 100ms
-            _ = async {
-                while !services.is_interrupted() {
-                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                }
-            } => {
-                let _ = child.kill().await;
-                WaitOutcome::Interrupted
+    let wait_result = tokio::select! {
+        biased;
+        // Check for interrupt every 100ms
+        _ = async {
+            while !services.is_interrupted() {
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
             }
-            result = tokio::time::timeout(timeout_duration, child.wait()) => {
-                match result {
-                    Ok(status) => WaitOutcome::Completed(status),
-                    Err(_) => {
-                        let _ = child.kill().await;
-                        WaitOutcome::TimedOut
-                    }
+        } => {
+            let _ = child.kill().await;
+            WaitOutcome::Interrupted
+        }
+        result = tokio::time::timeout(timeout_duration, child.wait()) => {
+            match result {
+                Ok(status) => WaitOutcome::Completed(status),
+                Err(_) => {
+                    let _ = child.kill().await;
+                    WaitOutcome::TimedOut
                 }
             }
-        };
-
-        match wait_result {
-            WaitOutcome::Completed(Ok(status)) => {
-                // Collect output from spawned tasks
-                let stdout_output: CapturedOutput = stdout_task.await.unwrap_or_default();
-                let stderr_output: CapturedOutput = stderr_task.await.unwrap_or_default();
+        }
+    };
 
-                let total_lines = stdout_output.line_count + stderr_output.line_count;
-                let total_bytes = stdout_output.byte_count + stderr_output.byte_count;
+    match wait_result {
+        WaitOutcome::Completed(Ok(status)) => {
+            // Collect output from spawned tasks
+            let stdout_output: CapturedOutput = stdout_task.await.unwrap_or_default();
+            let stderr_output: CapturedOutput = stderr_task.await.unwrap_or_default();
+
+            let total_lines = stdout_output.line_count + stderr_output.line_count;
+            let total_bytes = stdout_output.byte_count + stderr_output.byte_count;
+
+            let exit_code = status.code().unwrap_or(-1);
+            let summary = if exit_code == 0 {
+                Some(format!("[Read {total_lines} lines, {total_bytes} bytes]"))
+            } else {
+                Some(format!(
+                    "[Read {total_lines} lines, {total_bytes} bytes, exit code {exit_code}]"
+                ))
+            };
+
+            let mut combined = stdout_output.text;
+            if !stderr_output.text.is_empty() {
+                combined.push_str(&stderr_output.text);
+            }
 
-                let exit_code = status.code().unwrap_or(-1);
-                let summary = if exit_code == 0 {
-                    Some(format!("[Read {total_lines} lines, {total_bytes} bytes]"))
+            let (mut content, truncation) =
+                truncate_tail_for_model(&combined, total_lines, total_bytes);
+
+            if truncation.did_truncate {
+                let mut notice = if truncation.kept_lines == 0 {
+                    format!(
+                        "[Output truncated: showing 0 lines of {} (kept 0 bytes of {}; limits: {} lines, {} bytes)]",
+                        truncation.total_lines,
+                        truncation.total_bytes,
+                        MAX_OUTPUT_LINES,
+                        MAX_OUTPUT_BYTES,
+                    )
                 } else {
-                    Some(format!(
-                        "[Read {total_lines} lines, {total_bytes} bytes, exit code {exit_code}]"
-                    ))
+                    format!(
+                        "[Output truncated: showing lines {}-{} of {} (kept {} lines / {} bytes; truncated {} lines / {} bytes; limits: {} lines, {} bytes)]",
+                        truncation.start_line,
+                        truncation.end_line,
+                        truncation.total_lines,
+                        truncation.kept_lines,
+                        truncation.kept_bytes,
+                        truncation.truncated_lines,
+                        truncation.truncated_bytes,
+                        MAX_OUTPUT_LINES,
+                        MAX_OUTPUT_BYTES,
+                    )
                 };
 
-                let mut combined = stdout_output.text;
-                if !stderr_output.text.is_empty() {
-                    combined.push_str(&stderr_output.text);
+                if truncation.first_line_partial {
+                    notice.insert_str(notice.len() - 1, "; first line is partial");
                 }
 
-                let (mut content, truncation) =
-                    truncate_tail_for_model(&combined, total_lines, total_bytes);
-
-                if truncation.did_truncate {
-                    let mut notice = if truncation.kept_lines == 0 {
-                        format!(
-                            "[Output truncated: showing 0 lines of {} (kept 0 bytes of {}; limits: {} lines, {} bytes)]",
-                            truncation.total_lines,
-                            truncation.total_bytes,
-                            MAX_OUTPUT_LINES,
-                            MAX_OUTPUT_BYTES,
-                        )
-                    } else {
-                        format!(
-                            "[Output truncated: showing lines {}-{} of {} (kept {} lines / {} bytes; truncated {} lines / {} bytes; limits: {} lines, {} bytes)]",
-                            truncation.start_line,
-                            truncation.end_line,
-                            truncation.total_lines,
-                            truncation.kept_lines,
-                            truncation.kept_bytes,
-                            truncation.truncated_lines,
-                            truncation.truncated_bytes,
-                            MAX_OUTPUT_LINES,
-                            MAX_OUTPUT_BYTES,
-                        )
-                    };
-
-                    if truncation.first_line_partial {
-                        notice.insert_str(notice.len() - 1, "; first line is partial");
-                    }
+                content.push_str("\n\n");
+                content.push_str(&notice);
+            }
 
-                    content.push_str("\n\n");
-                    content.push_str(&notice);
+            if exit_code == 0 {
+                ToolResult {
+                    tool_use_id: tool_use_id.to_string(),
+                    kind: "tool_result".to_string(),
+                    content,
+                    is_error: false,
+                    exit_code: Some(exit_code),
+                    summary,
+                    data: None,
+                    mime_type: None,
                 }
-
-                if exit_code == 0 {
-                    ToolResult {
-                        tool_use_id: tool_use_id.to_string(),
-                        kind: "tool_result".to_string(),
-                        content,
-                        is_error: false,
-                        exit_code: Some(exit_code),
-                        summary,
-                        data: None,
-                        mime_type: None,
-                    }
-                } else if content.is_empty() {
-                    ToolResult {
-                        tool_use_id: tool_use_id.to_string(),
-                        kind: "tool_result".to_string(),
-                        content: format!("[Exit code: {}]", exit_code),
-                        is_error: true,
-                        exit_code: Some(exit_code),
-                        summary,
-                        data: None,
-                        mime_type: None,
-                    }
-                } else {
-                    let error_output = format!("{}\n[Exit code: {}]", content, exit_code);
-                    ToolResult {
-                        tool_use_id: tool_use_id.to_string(),
-                        kind: "tool_result".to_string(),
-                        content: error_output,
-                        is_error: true,
-                        exit_code: Some(exit_code),
-                        summary,
-                        data: None,
-                        mime_type: None,
-                    }
+            } else if content.is_empty() {
+                ToolResult {
+                    tool_use_id: tool_use_id.to_string(),
+                    kind: "tool_result".to_string(),
+                    content: format!("[Exit code: {}]", exit_code),
+                    is_error: true,
+                    exit_code: Some(exit_code),
+                    summary,
+                    data: None,
+                    mime_type: None,
+                }
+            } else {
+                let error_output = format!("{}\n[Exit code: {}]", content, exit_code);
+                ToolResult {
+                    tool_use_id: tool_use_id.to_string(),
+                    kind: "tool_result".to_string(),
+                    content: error_output,
+                    is_error: true,
+                    exit_code: Some(exit_code),
+                    summary,
+                    data: None,
+                    mime_type: None,
                 }
             }
-            WaitOutcome::Completed(Err(e)) => {
-                ToolResult::error(tool_use_id, format!("Command execution failed: {}", e))
-            }
-            WaitOutcome::TimedOut => {
-                stdout_task.abort();
-                stderr_task.abort();
-                ToolResult::error(
-                    tool_use_id,
-                    format!("Command timed out after {} seconds", timeout_secs),
-                )
-            }
-            WaitOutcome::Interrupted => {
-                stdout_task.abort();
-                stderr_task.abort();
-                ToolResult::error(tool_use_id, "Interrupted by user").with_summary("Interrupted")
-            }
+        }
+        WaitOutcome::Completed(Err(e)) => {
+            ToolResult::error(tool_use_id, format!("Command execution failed: {}", e))
+        }
+        WaitOutcome::TimedOut => {
+            stdout_task.abort();
+            stderr_task.abort();
+            ToolResult::error(
+                tool_use_id,
+                format!("Command timed out after {} seconds", timeout_secs),
+            )
+        }
+        WaitOutcome::Interrupted => {
+            stdout_task.abort();
+            stderr_task.abort();
+            ToolResult::error(tool_use_id, "Interrupted by user").with_summary("Interrupted")
         }
     }
 }
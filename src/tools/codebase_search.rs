@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+use serde::Deserialize;
+
+use super::{Tool, ToolDefinition, ToolResult};
+use crate::index::{self, Match};
+
+/// Largest number of results the model may ask for.
+const MAX_LIMIT: usize = 100;
+
+pub(crate) struct CodebaseSearch;
+
+#[derive(Debug, Deserialize)]
+struct CodebaseSearchInput {
+    query: String,
+    #[serde(default, deserialize_with = "super::deserialize_optional_usize")]
+    limit: Option<usize>,
+}
+
+/// One line per match: definitions as `path:line: kind name`, files as
+/// `path: summary`.
+fn format_matches(matches: &[Match]) -> String {
+    matches
+        .iter()
+        .map(|m| match m {
+            Match::Symbol { path, symbol } => {
+                format!("{}:{}: {} {}", path, symbol.line, symbol.kind, symbol.name)
+            }
+            Match::File {
+                path,
+                summary: Some(summary),
+            } => format!("{}: {}", path, summary),
+            Match::File {
+                path,
+                summary: None,
+            } => path.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Tool for CodebaseSearch {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "codebase_search".to_string(),
+            description: "Find where things are defined in the workspace. Searches an index of the functions, types, classes and other definitions of every source file, and the file names and summaries (the first descriptive comment), and returns the best matches first as `path:line: kind name` or `path: summary`. Use it before reaching for grep to locate code by name or topic, e.g. \"FileIndex refresh\" or \"config loading\"."
+                .to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "Names or words to look for; identifiers may be written in any case style"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum number of results (default: 20, max: 100)"
+                    }
+                },
+                "required": ["query"]
+            }),
+        }
+    }
+
+    async fn execute(
+        &self,
+        tool_use_id: &str,
+        input: serde_json::Value,
+        output: &crate::output::OutputContext,
+        _services: &crate::services::Services,
+    ) -> ToolResult {
+        let input: CodebaseSearchInput = match super::deserialize_input(tool_use_id, input) {
+            Ok(i) => i,
+            Err(e) => return *e,
+        };
+        if input.query.trim().is_empty() {
+            return ToolResult::error(tool_use_id, "The query is empty");
+        }
+
+        let root = super::paths::workspace_root();
+        let limit = input
+            .limit
+            .unwrap_or(index::DEFAULT_LIMIT)
+            .clamp(1, MAX_LIMIT);
+        let query = input.query.clone();
+        let matches =
+            match tokio::task::spawn_blocking(move || index::search(&root, &query, limit)).await {
+                Ok(matches) => matches,
+                Err(e) => {
+                    return ToolResult::error(tool_use_id, format!("Search failed: {}", e));
+                }
+            };
+
+        if matches.is_empty() {
+            return ToolResult::success(
+                tool_use_id,
+                format!("No definitions or files match '{}'.", input.query),
+            )
+            .with_summary("No matches");
+        }
+        let content = format_matches(&matches);
+        for line in content.lines() {
+            crate::output::emit_tool_output(output, &format!("{}\n", line));
+        }
+        let summary = format!(
+            "{} {}",
+            matches.len(),
+            if matches.len() == 1 {
+                "match"
+            } else {
+                "matches"
+            }
+        );
+        ToolResult::success(tool_use_id, content).with_summary(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::symbols::Symbol;
+
+    #[test]
+    fn test_format_matches() {
+        let matches = vec![
+            Match::Symbol {
+                path: "src/walk.rs".to_string(),
+                symbol: Symbol {
+                    name: "FileIndex".to_string(),
+                    kind: "struct".to_string(),
+                    line: 124,
+                },
+            },
+            Match::File {
+                path: "src/walk.rs".to_string(),
+                summary: Some("Walking the working directory.".to_string()),
+            },
+            Match::File {
+                path: "src/main.rs".to_string(),
+                summary: None,
+            },
+        ];
+        assert_eq!(
+            format_matches(&matches),
+            "src/walk.rs:124: struct FileIndex\nsrc/walk.rs: Walking the working directory.\nsrc/main.rs"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Local executables registered as tools in `[tools.external]`.
+//!
+//! Each entry gives a description, a JSON schema for the arguments and a
+//! command template. The command runs through bash under the same sandbox
+//! policy as the bash tool, with `{name}` placeholders replaced by the
+//! shell-quoted argument values. The arguments are also written to the
+//! command's stdin as JSON.
+
+use crate::config::{ConfigFile, ExternalToolConfig};
+
+use super::{ToolDefinition, ToolResult, bash};
+
+/// Whether `name` can be used for an external tool: it must be a valid tool
+/// name for every provider and must not shadow a built-in or MCP tool.
+pub(super) fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && super::canonicalize_builtin_tool_name(name).is_none()
+        && !name.starts_with("mcp_")
+}
+
+fn definition(name: &str, tool: &ExternalToolConfig) -> ToolDefinition {
+    ToolDefinition {
+        name: name.to_string(),
+        description: tool.description.clone(),
+        input_schema: tool.input_schema.clone().unwrap_or_else(|| {
+            serde_json::json!({
+                "type": "object",
+                "properties": {}
+            })
+        }),
+    }
+}
+
+/// Definitions of the configured external tools that are not disabled.
+pub(crate) fn definitions(config: &ConfigFile) -> Vec<ToolDefinition> {
+    config
+        .tools
+        .external
+        .iter()
+        .filter(|(name, _)| is_valid_name(name) && !config.disabled_tools.contains(name))
+        .map(|(name, tool)| definition(name, tool))
+        .collect()
+}
+
+/// The external tool registered as `name`, if any.
+pub(crate) fn find<'a>(config: &'a ConfigFile, name: &str) -> Option<&'a ExternalToolConfig> {
+    if !is_valid_name(name) {
+        return None;
+    }
+    config.tools.external.get(name)
+}
+
+/// Quote a string for bash.
+pub(super) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Render an argument value for the command line. Arrays become one quoted
+/// word per element; a missing or null value renders as nothing.
+fn render_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => shell_quote(s),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(render_value)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+        other => shell_quote(&other.to_string()),
+    }
+}
+
+/// Replace `{name}` placeholders for the arguments declared in the schema or
+/// present in the input. Other braces, like `${VAR}`, are left alone.
+fn render_command(
+    template: &str,
+    schema: Option<&serde_json::Value>,
+    input: &serde_json::Value,
+) -> String {
+    let declared = |key: &str| {
+        input.get(key).is_some()
+            || schema
+                .and_then(|s| s.get("properties"))
+                .and_then(|p| p.get(key))
+                .is_some()
+    };
+
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        rendered.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        match after.find('}') {
+            Some(close)
+                if !rest[..open].ends_with('$')
+                    && !after[..close].is_empty()
+                    && after[..close]
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    && declared(&after[..close]) =>
+            {
+                let key = &after[..close];
+                rendered.push_str(&input.get(key).map(render_value).unwrap_or_default());
+                rest = &after[close + 1..];
+            }
+            _ => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Run an external tool.
+pub(crate) async fn execute(
+    tool: &ExternalToolConfig,
+    tool_use_id: &str,
+    input: serde_json::Value,
+    output: &crate::output::OutputContext,
+    services: &crate::services::Services,
+) -> ToolResult {
+    if !input.is_object() && !input.is_null() {
+        return ToolResult::error(tool_use_id, "Invalid input: expected an object");
+    }
+    let command = render_command(&tool.command, tool.input_schema.as_ref(), &input);
+    if let Err(message) = services
+        .permissions
+        .approve_bash(&command, services, output)
+        .await
+    {
+        return ToolResult::error(tool_use_id, message);
+    }
+    let stdin = if input.is_null() {
+        "{}".to_string()
+    } else {
+        input.to_string()
+    };
+    bash::run(
+        tool_use_id,
+        &command,
+        tool.timeout.unwrap_or(bash::DEFAULT_TIMEOUT_SECS),
+        None,
+        Some(stdin),
+        output,
+        services,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_command() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string"},
+                "paths": {"type": "array"},
+                "limit": {"type": "integer"}
+            }
+        });
+        let input = serde_json::json!({
+            "query": "it's here",
+            "paths": ["src", "docs"],
+            "limit": 5
+        });
+        assert_eq!(
+            render_command(
+                "rg {query} {paths} -m {limit} {unknown} ${HOME}",
+                Some(&schema),
+                &input
+            ),
+            r"rg 'it'\''s here' 'src' 'docs' -m '5' {unknown} ${HOME}"
+        );
+        assert_eq!(
+            render_command("ls {paths}", Some(&schema), &serde_json::json!({})),
+            "ls "
+        );
+    }
+
+    #[test]
+    fn test_names_cannot_shadow_other_tools() {
+        assert!(is_valid_name("run_tests"));
+        assert!(!is_valid_name("bash"));
+        assert!(!is_valid_name("read"));
+        assert!(!is_valid_name("mcp_github_search"));
+        assert!(!is_valid_name("has space"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_runs_command() {
+        let tool = ExternalToolConfig {
+            description: "Greet".to_string(),
+            command: "echo hello {name}".to_string(),
+            input_schema: None,
+            timeout: None,
+        };
+        let result = execute(
+            &tool,
+            "test-id",
+            serde_json::json!({"name": "world"}),
+            &crate::output::OutputContext::null(),
+            &crate::services::Services::null(),
+        )
+        .await;
+        assert!(!result.is_error);
+        assert_eq!(result.content.trim(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn test_execute_writes_input_to_stdin() {
+        let tool = ExternalToolConfig {
+            description: "Echo the input".to_string(),
+            command: "cat".to_string(),
+            input_schema: None,
+            timeout: None,
+        };
+        let result = execute(
+            &tool,
+            "test-id",
+            serde_json::json!({"name": "world"}),
+            &crate::output::OutputContext::null(),
+            &crate::services::Services::null(),
+        )
+        .await;
+        assert!(!result.is_error);
+        assert_eq!(result.content.trim(), r#"{"name":"world"}"#);
+    }
+}
@@ -4,6 +4,7 @@
 use serde::Deserialize;
 
 use super::{Tool, ToolDefinition, ToolResult};
+use crate::provider::{ContentBlock, Message, MessageContent, Role};
 use crate::version::VERSION;
 
 pub(crate) struct Fetch;
@@ -15,8 +16,64 @@ struct FetchInput {
     raw: Option<bool>,
 }
 
+/// Default for `tools.fetch-max-bytes`.
+const DEFAULT_MAX_BYTES: usize = 100 * 1024;
+
+/// Elements that never hold page content.
+const BOILERPLATE_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "nav", "aside", "footer",
+    "form", "button",
+];
+
+/// The `<main>` or `<article>` part of a page, if it has one, so that site
+/// headers and sidebars outside it are left out.
+fn main_content(html: &str) -> &str {
+    let lower = html.to_ascii_lowercase();
+    for tag in ["main", "article"] {
+        let open = format!("<{}", tag);
+        let close = format!("</{}>", tag);
+        if let Some(start) = lower.match_indices(&open).map(|(i, _)| i).find(|&i| {
+            matches!(
+                lower.as_bytes().get(i + open.len()),
+                Some(b'>' | b' ' | b'\t' | b'\n')
+            )
+        }) && let Some(end) = lower.rfind(&close)
+            && end > start
+        {
+            return &html[start..end + close.len()];
+        }
+    }
+    html
+}
+
 fn convert_html_to_markdown(html: &str) -> String {
-    htmd::convert(html).unwrap_or_else(|_| html.to_string())
+    let converter = htmd::HtmlToMarkdown::builder()
+        .skip_tags(BOILERPLATE_TAGS.to_vec())
+        .build();
+    converter
+        .convert(main_content(html))
+        .unwrap_or_else(|_| html.to_string())
+}
+
+/// Cut `content` to at most `max_bytes` on a line or character boundary,
+/// noting how much was left out.
+fn truncate(content: String, max_bytes: usize) -> String {
+    if content.len() <= max_bytes {
+        return content;
+    }
+    let mut end = max_bytes;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    if let Some(newline) = content[..end].rfind('\n') {
+        end = newline + 1;
+    }
+    format!(
+        "{}\n[Truncated: showing {} of {} bytes]",
+        &content[..end],
+        end,
+        content.len()
+    )
 }
 
 fn pretty_print_json(text: &str) -> String {
@@ -26,11 +83,57 @@ fn pretty_print_json(text: &str) -> String {
         .unwrap_or_else(|| text.to_string())
 }
 
+/// URLs successfully fetched during the most recent turn, deduplicated in order of use.
+pub(crate) fn last_turn_sources(messages: &[Message]) -> Vec<String> {
+    let turn_start = messages
+        .iter()
+        .rposition(|m| m.role == Role::User && !m.is_tool_result_only())
+        .unwrap_or(0);
+    let turn = &messages[turn_start..];
+
+    let failed: Vec<&str> = turn
+        .iter()
+        .filter_map(|m| match &m.content {
+            MessageContent::Blocks(blocks) => Some(blocks),
+            MessageContent::Text(_) => None,
+        })
+        .flatten()
+        .filter_map(|b| match b {
+            ContentBlock::ToolResult {
+                tool_use_id,
+                is_error: true,
+                ..
+            } => Some(tool_use_id.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut sources: Vec<String> = Vec::new();
+    for msg in turn.iter().filter(|m| m.role == Role::Assistant) {
+        let MessageContent::Blocks(blocks) = &msg.content else {
+            continue;
+        };
+        for block in blocks {
+            if let ContentBlock::ToolUse {
+                id, name, input, ..
+            } = block
+                && name == "fetch"
+                && !failed.contains(&id.as_str())
+                && let Some(url) = input.get("url").and_then(|u| u.as_str())
+                && !sources.iter().any(|s| s == url)
+            {
+                sources.push(url.to_string());
+            }
+        }
+    }
+    sources
+}
+
 impl Tool for Fetch {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: "fetch".to_string(),
-            description: "Fetch a URL and return its content. HTML is converted to Markdown, JSON is pretty-printed. Use raw=true to skip processing."
+            description: "Fetch a URL and return its content. HTML is converted to Markdown without navigation, scripts and other page boilerplate, JSON is pretty-printed. Long pages are truncated. Use raw=true to skip processing."
                 .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
@@ -110,6 +213,11 @@ impl Tool for Fetch {
         } else {
             body
         };
+        let max_bytes = crate::config::ConfigFile::load()
+            .ok()
+            .and_then(|c| c.tools.fetch_max_bytes)
+            .unwrap_or(DEFAULT_MAX_BYTES);
+        let content = truncate(content, max_bytes);
 
         for line in content.lines() {
             crate::output::emit_tool_output(output, &format!("{}\n", line));
@@ -141,6 +249,49 @@ mod tests {
         assert!(result.content.contains("Invalid URL"));
     }
 
+    fn fetch_use(id: &str, url: &str) -> ContentBlock {
+        ContentBlock::ToolUse {
+            id: id.to_string(),
+            name: "fetch".to_string(),
+            input: serde_json::json!({ "url": url }),
+            thought_signature: None,
+        }
+    }
+
+    fn tool_result(id: &str, is_error: bool) -> Message {
+        Message {
+            role: Role::User,
+            content: MessageContent::Blocks(vec![ContentBlock::ToolResult {
+                tool_use_id: id.to_string(),
+                content: String::new(),
+                is_error,
+                data: None,
+                mime_type: None,
+            }]),
+        }
+    }
+
+    #[test]
+    fn test_last_turn_sources() {
+        let messages = vec![
+            Message::user("first"),
+            Message::assistant_blocks(vec![fetch_use("a", "https://old.example")]),
+            tool_result("a", false),
+            Message::user("second"),
+            Message::assistant_blocks(vec![
+                fetch_use("b", "https://example.com"),
+                fetch_use("c", "https://broken.example"),
+            ]),
+            tool_result("b", false),
+            tool_result("c", true),
+            Message::assistant_blocks(vec![fetch_use("d", "https://example.com")]),
+            tool_result("d", false),
+        ];
+
+        assert_eq!(last_turn_sources(&messages), vec!["https://example.com"]);
+        assert!(last_turn_sources(&messages[..1]).is_empty());
+    }
+
     #[test]
     fn test_convert_html_to_markdown() {
         let html = "<h1>Hello</h1><p>World</p>";
@@ -149,6 +300,33 @@ mod tests {
         assert!(md.contains("World"));
     }
 
+    #[test]
+    fn test_convert_html_strips_boilerplate() {
+        let html = "<html><body><nav><a href=\"/\">Home</a></nav>\
+<script>track()</script>\
+<MAIN class=\"doc\"><h1>Guide</h1><p>Body text</p><aside>Related</aside></MAIN>\
+<footer>Copyright</footer></body></html>";
+        let md = convert_html_to_markdown(html);
+        assert!(md.contains("# Guide"));
+        assert!(md.contains("Body text"));
+        for boilerplate in ["Home", "track", "Related", "Copyright"] {
+            assert!(!md.contains(boilerplate), "{} in {:?}", boilerplate, md);
+        }
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short".to_string(), 10), "short");
+        assert_eq!(
+            truncate("line one\nline two\n".to_string(), 12),
+            "line one\n\n[Truncated: showing 9 of 18 bytes]"
+        );
+        assert_eq!(
+            truncate("ééé".to_string(), 3),
+            "é\n[Truncated: showing 2 of 6 bytes]"
+        );
+    }
+
     #[test]
     fn test_pretty_print_json() {
         let json = r#"{"key":"value","num":42}"#;
@@ -3,7 +3,6 @@
 
 use serde::Deserialize;
 use std::fs;
-use std::path::{Path, PathBuf};
 
 use super::sandbox;
 use super::{Tool, ToolDefinition, ToolResult};
@@ -16,9 +15,13 @@ pub(crate) struct FileEdit;
 struct FileEditInput {
     file_path: String,
     old_string: String,
-    new_string: String,
+    #[serde(default)]
+    new_string: Option<String>,
     #[serde(default)]
     replace_all: bool,
+    /// Have the code completion model generate the replacement
+    #[serde(default)]
+    fill: bool,
 }
 
 fn summary_from_message(message: &str) -> Option<String> {
@@ -48,6 +51,17 @@ fn attach_summary_if_missing(mut result: ToolResult) -> ToolResult {
     result
 }
 
+/// Offer the `fill` parameter, which replaces `oldString` with code generated
+/// by the fill-in-the-middle model from the rest of the file.
+pub(crate) fn add_fill_parameter(definition: &mut ToolDefinition) {
+    let schema = &mut definition.input_schema;
+    schema["properties"]["fill"] = serde_json::json!({
+        "type": "boolean",
+        "description": "Replace oldString, such as a placeholder comment where code is missing, with code generated from the surrounding file by a code completion model. newString is not used."
+    });
+    schema["required"] = serde_json::json!(["filePath", "oldString"]);
+}
+
 impl Tool for FileEdit {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
@@ -60,7 +74,7 @@ impl Tool for FileEdit {
                 "properties": {
                     "filePath": {
                         "type": "string",
-                        "description": "The absolute path to the file to modify"
+                        "description": "The path to the file to modify, relative to the working directory or absolute"
                     },
                     "oldString": {
                         "type": "string",
@@ -96,9 +110,24 @@ impl Tool for FileEdit {
             Err(e) => return attach_summary_if_missing(*e),
         };
 
-        // Validate that old_string != new_string
-        if input.old_string == input.new_string {
-            return error_with_summary(tool_use_id, "oldString and newString must be different");
+        if input.fill {
+            if input.replace_all {
+                return error_with_summary(tool_use_id, "replaceAll cannot be used with fill");
+            }
+        } else {
+            match &input.new_string {
+                None => {
+                    return error_with_summary(tool_use_id, "newString is required unless filling");
+                }
+                // Validate that old_string != new_string
+                Some(new_string) if *new_string == input.old_string => {
+                    return error_with_summary(
+                        tool_use_id,
+                        "oldString and newString must be different",
+                    );
+                }
+                Some(_) => {}
+            }
         }
 
         // Validate old_string is not empty
@@ -106,19 +135,28 @@ impl Tool for FileEdit {
             return error_with_summary(tool_use_id, "oldString cannot be empty");
         }
 
-        let expanded_path = super::expand_tilde(&input.file_path);
-        let path = Path::new(&expanded_path);
+        let resolved = super::paths::resolve_tool_path(&input.file_path);
+        let path = resolved.path.as_path();
+        let file_path = resolved.display.as_str();
 
-        if let Err(e) = super::validate_path_exists(tool_use_id, path, &input.file_path) {
+        if let Err(e) = super::validate_path_exists(tool_use_id, path, file_path) {
             return attach_summary_if_missing(*e);
         }
-        if let Err(e) = super::validate_is_file(tool_use_id, path, &input.file_path) {
+        if let Err(e) = super::validate_is_file(tool_use_id, path, file_path) {
             return attach_summary_if_missing(*e);
         }
 
-        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-
-        if let Err(message) = sandbox::check_write_access(path, &cwd, services.is_sandbox_enabled())
+        let cwd = super::paths::workspace_root();
+
+        if let Err(message) = sandbox::check_write_access(
+            path,
+            &cwd,
+            &services.permissions.allowed_paths(),
+            services.is_sandbox_enabled(),
+        ) && !services
+            .permissions
+            .approve_write(path, services, _output)
+            .await
         {
             return error_with_summary(tool_use_id, message);
         }
@@ -155,35 +193,58 @@ impl Tool for FileEdit {
         }
 
         // Perform the replacement
-        let new_contents = if input.replace_all {
-            old_contents.replace(&input.old_string, &input.new_string)
+        let new_string = input.new_string.as_deref().unwrap_or_default();
+        let new_contents = if input.fill {
+            let (prefix, suffix) = old_contents
+                .split_once(&input.old_string)
+                .unwrap_or((&old_contents, ""));
+            match crate::provider::mistral::fill_in_middle(prefix, suffix).await {
+                Ok(middle) => format!("{}{}{}", prefix, middle, suffix),
+                Err(e) => {
+                    return error_with_summary(
+                        tool_use_id,
+                        format!("Failed to fill in code: {}", e.display_message()),
+                    );
+                }
+            }
+        } else if input.replace_all {
+            old_contents.replace(&input.old_string, new_string)
         } else {
-            old_contents.replacen(&input.old_string, &input.new_string, 1)
+            old_contents.replacen(&input.old_string, new_string, 1)
         };
 
         // Write the file back
+        services.checkpoints.record(path);
         if let Err(e) = fs::write(path, &new_contents) {
             return error_with_summary(tool_use_id, format!("Failed to write file: {}", e));
         }
+        services.seen_files.record(path);
 
         let diff = crate::diff::unified_diff(path, &old_contents, &new_contents, 3);
         if diff.has_changes {
             _output.emit(crate::output::OutputEvent::FileDiff {
                 diff: diff.unified_diff,
-                language: crate::syntax::language_from_path(&input.file_path),
-                summary: crate::diff::format_diff_summary(diff._lines_added, diff._lines_removed),
+                language: crate::syntax::language_from_path(file_path),
+                summary: crate::diff::format_diff_summary(diff.lines_added, diff.lines_removed),
             });
         }
 
-        let msg = if input.replace_all && count > 1 {
+        let mut msg = if input.replace_all && count > 1 {
             format!(
                 "Successfully replaced {} occurrences in {}",
-                count, input.file_path
+                count, file_path
             )
         } else {
-            format!("Successfully edited {}", input.file_path)
+            format!("Successfully edited {}", file_path)
         };
 
+        let mut new_contents = new_contents;
+        if let Some(report) = services.post_edit_checks.run(path, _output, services).await {
+            msg.push_str(&report);
+            new_contents = fs::read_to_string(path).unwrap_or(new_contents);
+            services.seen_files.record(path);
+        }
+
         // Notify LSP of the change and get diagnostics immediately
         let diagnostics =
             super::notify_lsp_and_get_diagnostics(path, &new_contents, services, _output).await;
@@ -237,6 +298,35 @@ mod tests {
         assert!(result.content.contains("must be different"));
     }
 
+    #[tokio::test]
+    async fn test_edit_missing_new_string() {
+        let tool = FileEdit;
+        let result = tool
+            .execute(
+                "test-id",
+                serde_json::json!({
+                    "filePath": "/tmp/test.txt",
+                    "oldString": "foo"
+                }),
+                &crate::output::OutputContext::null(),
+                &crate::services::Services::null(),
+            )
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("newString is required"));
+
+        let mut definition = tool.definition();
+        add_fill_parameter(&mut definition);
+        assert_eq!(
+            definition.input_schema["properties"]["fill"]["type"],
+            "boolean"
+        );
+        assert_eq!(
+            definition.input_schema["required"],
+            serde_json::json!(["filePath", "oldString"])
+        );
+    }
+
     #[tokio::test]
     async fn test_edit_string_not_found() {
         let mut temp = NamedTempFile::new().unwrap();
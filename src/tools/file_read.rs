@@ -17,15 +17,19 @@
 //! Binary files (like images) are returned as base64 with metadata.
 
 use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use base64::{Engine, engine::general_purpose::STANDARD};
+#[cfg(feature = "images")]
 use image::GenericImageView;
+#[cfg(feature = "images")]
 use image::ImageFormat;
+#[cfg(feature = "images")]
 use image::imageops::FilterType;
 use serde::Deserialize;
 
 use super::{Tool, ToolDefinition, ToolResult};
+use crate::exploration_cache::{Key, Lookup};
 
 /// Maximum number of lines to return before requiring pagination.
 const MAX_LINES: usize = 2000;
@@ -37,15 +41,22 @@ const MAX_OUTPUT_SIZE: usize = 50 * 1024; // 50KB
 const MAX_BINARY_BYTES: Option<usize> = None;
 
 /// Image resizing limits (Codex-compatible).
+#[cfg(feature = "images")]
 const MAX_IMAGE_WIDTH: u32 = 2048;
+#[cfg(feature = "images")]
 const MAX_IMAGE_HEIGHT: u32 = 768;
 
 /// Maximum base64 length to include in tool output for images.
 ///
 /// Even resized screenshots can be too large to embed directly in a tool result without
 /// overflowing a model's context window.
+#[cfg(feature = "images")]
 const MAX_IMAGE_BASE64_LEN: usize = 200_000;
 
+/// Files larger than this are not put in the exploration cache, so they
+/// aren't read in full just to hash them.
+const MAX_CACHED_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
 /// Byte window size for detecting binary files.
 const BINARY_DETECT_BYTES: usize = 1024;
 
@@ -112,7 +123,7 @@ impl Tool for FileRead {
                 "properties": {
                     "filename": {
                         "type": "string",
-                        "description": "The path to the file to read (REQUIRED)"
+                        "description": "The path to the file to read, relative to the working directory or absolute (REQUIRED)"
                     },
                     "offset": {
                         "type": "integer",
@@ -133,20 +144,71 @@ impl Tool for FileRead {
         tool_use_id: &str,
         input: serde_json::Value,
         output: &crate::output::OutputContext,
-        _services: &crate::services::Services,
+        services: &crate::services::Services,
+    ) -> ToolResult {
+        match super::task::current_task() {
+            Some(task) => {
+                self.read_in_task(task, tool_use_id, input, output, services)
+                    .await
+            }
+            None => self.read(tool_use_id, input, output, services).await,
+        }
+    }
+}
+
+impl FileRead {
+    /// Read for a delegated task through the cache shared by tasks, so files
+    /// other tasks read aren't read again and a repeated read isn't sent twice.
+    async fn read_in_task(
+        &self,
+        task: u64,
+        tool_use_id: &str,
+        input: serde_json::Value,
+        output: &crate::output::OutputContext,
+        services: &crate::services::Services,
+    ) -> ToolResult {
+        let Some((key, path)) = cache_key(&input) else {
+            return self.read(tool_use_id, input, output, services).await;
+        };
+        match services.exploration.lookup(task, &key) {
+            Lookup::Hit(mut result) => {
+                services.seen_files.record(&path);
+                result.tool_use_id = tool_use_id.to_string();
+                result
+            }
+            Lookup::Repeat => ToolResult::success(
+                tool_use_id,
+                "[Unchanged since you read it earlier in this task; use that result.]",
+            )
+            .with_summary("[Unchanged since last read]"),
+            Lookup::Miss => {
+                let result = self.read(tool_use_id, input, output, services).await;
+                services.exploration.insert(task, key, &result);
+                result
+            }
+        }
+    }
+
+    async fn read(
+        &self,
+        tool_use_id: &str,
+        input: serde_json::Value,
+        output: &crate::output::OutputContext,
+        services: &crate::services::Services,
     ) -> ToolResult {
         let input: FileReadInput = match super::deserialize_input(tool_use_id, input) {
             Ok(i) => i,
             Err(e) => return attach_summary_if_missing(*e),
         };
 
-        let expanded_filename = super::expand_tilde(&input.filename);
-        let path = Path::new(&expanded_filename);
+        let resolved = super::paths::resolve_tool_path(&input.filename);
+        let path = resolved.path.as_path();
+        let filename = resolved.display.as_str();
 
-        if let Err(e) = super::validate_path_exists(tool_use_id, path, &input.filename) {
+        if let Err(e) = super::validate_path_exists(tool_use_id, path, filename) {
             return attach_summary_if_missing(*e);
         }
-        if let Err(e) = super::validate_is_file(tool_use_id, path, &input.filename) {
+        if let Err(e) = super::validate_is_file(tool_use_id, path, filename) {
             return attach_summary_if_missing(*e);
         }
 
@@ -172,7 +234,7 @@ impl Tool for FileRead {
         if let Some(image_mime) = detect_image_mime(&sniff_buf, path) {
             return read_image_file(
                 tool_use_id,
-                &input.filename,
+                filename,
                 file,
                 sniff_buf,
                 size_bytes,
@@ -192,7 +254,7 @@ impl Tool for FileRead {
 
             let base64_data = STANDARD.encode(&bytes);
             let output_buf = render_binary_output(
-                &input.filename,
+                filename,
                 "application/octet-stream",
                 size_bytes,
                 bytes.len(),
@@ -202,7 +264,7 @@ impl Tool for FileRead {
 
             for line in output_buf.lines().take(5) {
                 let formatted_line = format!("{}\n", line);
-                crate::output::emit_file_read_output(output, &input.filename, &formatted_line);
+                crate::output::emit_file_read_output(output, filename, &formatted_line);
             }
 
             let summary = build_binary_summary(
@@ -216,6 +278,8 @@ impl Tool for FileRead {
             return ToolResult::success(tool_use_id, output_buf).with_summary(summary);
         }
 
+        services.seen_files.record(path);
+
         if file.seek(SeekFrom::Start(0)).is_err() {
             file = match std::fs::File::open(path) {
                 Ok(f) => f,
@@ -337,7 +401,7 @@ impl Tool for FileRead {
         // Append footer (allowed to push output a bit beyond MAX_OUTPUT_SIZE).
         let summary = build_bracket_summary(offset, &output_lines, stop_reason);
         output_buf.push_str(&summary);
-        crate::output::emit_file_read_output(output, &input.filename, &output_buf);
+        crate::output::emit_file_read_output(output, filename, &output_buf);
 
         let tool_summary = format!(
             "[Read {} lines, {} bytes]",
@@ -348,6 +412,22 @@ impl Tool for FileRead {
     }
 }
 
+/// Key of a read in the exploration cache, with the resolved path. `None` when
+/// the read can't be cached; reading then reports any problem.
+fn cache_key(input: &serde_json::Value) -> Option<(Key, PathBuf)> {
+    let input: FileReadInput = serde_json::from_value(input.clone()).ok()?;
+    let resolved = super::paths::resolve_tool_path(&input.filename);
+    let path = std::fs::canonicalize(&resolved.path).ok()?;
+    if std::fs::metadata(&path).ok()?.len() > MAX_CACHED_FILE_SIZE {
+        return None;
+    }
+    let content = std::fs::read(&path).ok()?;
+    Some((
+        Key::new(&path, &content, input.offset, input.limit),
+        resolved.path,
+    ))
+}
+
 /// Handle reading an image file, including resizing and encoding for tool output.
 fn read_image_file(
     tool_use_id: &str,
@@ -365,6 +445,7 @@ fn read_image_file(
         }
     };
 
+    #[cfg(feature = "images")]
     let (final_bytes, final_mime, original_meta, final_meta) =
         match prepare_image_for_tool_output(&bytes, mime_type) {
             Ok(ImageToolOutput::AsIs { meta }) => (bytes, mime_type.to_string(), meta, None),
@@ -386,6 +467,20 @@ fn read_image_file(
             ),
         };
 
+    // Without image support the file is passed through undecoded and not previewed.
+    #[cfg(not(feature = "images"))]
+    let _ = output;
+    #[cfg(not(feature = "images"))]
+    let (final_bytes, final_mime, original_meta, final_meta) = (
+        bytes,
+        mime_type.to_string(),
+        ImageMeta {
+            width: 0,
+            height: 0,
+        },
+        None::<ImageMeta>,
+    );
+
     let base64_data = STANDARD.encode(&final_bytes);
 
     // Build content string for the model (full details).
@@ -410,6 +505,7 @@ fn read_image_file(
     };
 
     // Emit image preview for terminals that support inline images (e.g., Kitty).
+    #[cfg(feature = "images")]
     crate::output::emit_image_preview(output, base64_data.clone(), final_mime.clone());
 
     ToolResult::success(tool_use_id, content)
@@ -455,7 +551,9 @@ fn is_iso_bmff_heif(bytes: &[u8]) -> bool {
     false
 }
 
-fn detect_image_mime(bytes: &[u8], path: &Path) -> Option<&'static str> {
+/// Detect the MIME type of an image from its magic bytes, or its extension
+/// for formats without reliable ones.
+pub(crate) fn detect_image_mime(bytes: &[u8], path: &Path) -> Option<&'static str> {
     if bytes.len() >= 8 && bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
         return Some("image/png");
     }
@@ -480,6 +578,7 @@ fn detect_image_mime(bytes: &[u8], path: &Path) -> Option<&'static str> {
     }
 }
 
+#[cfg(feature = "images")]
 fn image_format_from_mime(mime_type: &str) -> Option<ImageFormat> {
     match mime_type {
         "image/png" => Some(ImageFormat::Png),
@@ -492,6 +591,7 @@ fn image_format_from_mime(mime_type: &str) -> Option<ImageFormat> {
     }
 }
 
+#[cfg(feature = "images")]
 fn fit_within_bounds(width: u32, height: u32, max_w: u32, max_h: u32) -> (u32, u32) {
     if width == 0 || height == 0 {
         return (0, 0);
@@ -516,6 +616,7 @@ struct ImageMeta {
     height: u32,
 }
 
+#[cfg(feature = "images")]
 enum ImageToolOutput {
     AsIs {
         meta: ImageMeta,
@@ -528,6 +629,7 @@ enum ImageToolOutput {
     },
 }
 
+#[cfg(feature = "images")]
 fn prepare_image_for_tool_output(bytes: &[u8], mime_type: &str) -> Result<ImageToolOutput, String> {
     let Some(input_format) = image_format_from_mime(mime_type) else {
         return Err("unknown image format".to_string());
@@ -870,8 +972,6 @@ mod tests {
 
     use tempfile::NamedTempFile;
 
-    use base64::Engine;
-
     use super::*;
 
     #[tokio::test]
@@ -931,6 +1031,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg(feature = "images")]
     async fn test_read_png_returns_base64() {
         let mut tmp = NamedTempFile::new().unwrap();
 
@@ -1006,6 +1107,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg(feature = "images")]
     async fn test_read_large_image_is_resized_not_cropped() {
         let mut tmp = NamedTempFile::new().unwrap();
 
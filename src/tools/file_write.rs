@@ -4,7 +4,6 @@
 use base64::Engine;
 use serde::Deserialize;
 use std::fs;
-use std::path::{Path, PathBuf};
 
 use super::sandbox;
 use super::{Tool, ToolDefinition, ToolResult};
@@ -42,7 +41,7 @@ impl Tool for FileWrite {
                 "properties": {
                     "filePath": {
                         "type": "string",
-                        "description": "The path to the file to write"
+                        "description": "The path to the file to write, relative to the working directory or absolute"
                     },
                     "content": {
                         "type": "string",
@@ -79,19 +78,25 @@ impl Tool for FileWrite {
             Err(e) => return *e,
         };
 
-        let expanded_path = super::expand_tilde(&input.file_path);
-        let path = Path::new(&expanded_path);
+        let resolved = super::paths::resolve_tool_path(&input.file_path);
+        let path = resolved.path.as_path();
+        let file_path = resolved.display.as_str();
 
         if path.is_dir() {
-            return ToolResult::error(
-                tool_use_id,
-                format!("Path is a directory: {}", input.file_path),
-            );
+            return ToolResult::error(tool_use_id, format!("Path is a directory: {}", file_path));
         }
 
-        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-
-        if let Err(message) = sandbox::check_write_access(path, &cwd, services.is_sandbox_enabled())
+        let cwd = super::paths::workspace_root();
+
+        if let Err(message) = sandbox::check_write_access(
+            path,
+            &cwd,
+            &services.permissions.allowed_paths(),
+            services.is_sandbox_enabled(),
+        ) && !services
+            .permissions
+            .approve_write(path, services, _output)
+            .await
         {
             return ToolResult::error(tool_use_id, message);
         }
@@ -142,9 +147,11 @@ impl Tool for FileWrite {
             String::new()
         };
 
+        services.checkpoints.record(path);
         if let Err(e) = fs::write(path, &bytes_to_write) {
             return ToolResult::error(tool_use_id, format!("Failed to write file: {}", e));
         }
+        services.seen_files.record(path);
 
         if input.encoding == ContentEncoding::Text {
             let new_content = String::from_utf8_lossy(&bytes_to_write);
@@ -152,21 +159,23 @@ impl Tool for FileWrite {
             if diff.has_changes {
                 _output.emit(crate::output::OutputEvent::FileDiff {
                     diff: diff.unified_diff,
-                    language: crate::syntax::language_from_path(&input.file_path),
-                    summary: crate::diff::format_diff_summary(
-                        diff._lines_added,
-                        diff._lines_removed,
-                    ),
+                    language: crate::syntax::language_from_path(file_path),
+                    summary: crate::diff::format_diff_summary(diff.lines_added, diff.lines_removed),
                 });
             }
         }
 
         let action = if file_existed { "Overwrote" } else { "Created" };
-        let msg = format!("{} {} ({} bytes)", action, input.file_path, bytes_written);
+        let mut msg = format!("{} {} ({} bytes)", action, file_path, bytes_written);
 
         // Notify LSP of the change and get diagnostics immediately for text files
         if input.encoding == ContentEncoding::Text {
-            let content_str = String::from_utf8_lossy(&bytes_to_write);
+            let mut content_str = String::from_utf8_lossy(&bytes_to_write).into_owned();
+            if let Some(report) = services.post_edit_checks.run(path, _output, services).await {
+                msg.push_str(&report);
+                content_str = fs::read_to_string(path).unwrap_or(content_str);
+                services.seen_files.record(path);
+            }
             let diagnostics =
                 super::notify_lsp_and_get_diagnostics(path, &content_str, services, _output).await;
             let final_msg = super::format_message_with_diagnostics(msg, &diagnostics, _output);
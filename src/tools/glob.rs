@@ -0,0 +1,366 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+
+use super::{Tool, ToolDefinition, ToolResult, paths};
+
+/// Files listed when the caller doesn't ask for a number.
+const DEFAULT_LIMIT: usize = 200;
+
+/// Most files that may be asked for.
+const MAX_LIMIT: usize = 5000;
+
+/// Most patterns a single brace pattern may expand to.
+const MAX_EXPANSIONS: usize = 1000;
+
+pub(crate) struct Glob;
+
+/// Order of the matched files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Sort {
+    /// By path, alphabetically
+    #[default]
+    Path,
+    /// Most recently modified first
+    Mtime,
+    /// Largest first
+    Size,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlobInput {
+    #[serde(deserialize_with = "one_or_many")]
+    patterns: Vec<String>,
+    /// Directories to search, relative to the workspace root
+    #[serde(default, deserialize_with = "one_or_many")]
+    paths: Vec<String>,
+    #[serde(default)]
+    sort: Sort,
+    #[serde(default, deserialize_with = "super::deserialize_optional_usize")]
+    limit: Option<usize>,
+}
+
+/// Accept a single string where a list is expected.
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(one) => vec![one],
+        OneOrMany::Many(many) => many,
+    })
+}
+
+/// Expand `{a,b}` alternatives, which may be nested, into separate patterns.
+/// Braces without a comma are kept as they are.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    // Find the first top-level `{...}` that holds a comma.
+    let bytes = pattern.as_bytes();
+    let mut start = None;
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut escaped = false;
+    for (i, &b) in bytes.iter().enumerate() {
+        if std::mem::take(&mut escaped) {
+            continue;
+        }
+        match b {
+            b'\\' => escaped = true,
+            b'{' => {
+                if depth == 0 {
+                    start = Some(i);
+                    commas.clear();
+                }
+                depth += 1;
+            }
+            b',' if depth == 1 => commas.push(i),
+            b'}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0
+                    && let Some(open) = start
+                    && !commas.is_empty()
+                {
+                    let (prefix, suffix) = (&pattern[..open], &pattern[i + 1..]);
+                    let mut bounds = vec![open];
+                    bounds.extend(&commas);
+                    bounds.push(i);
+                    let mut expanded = Vec::new();
+                    for pair in bounds.windows(2) {
+                        let alternative = &pattern[pair[0] + 1..pair[1]];
+                        let joined = format!("{}{}{}", prefix, alternative, suffix);
+                        expanded.extend(expand_braces(&joined));
+                        if expanded.len() >= MAX_EXPANSIONS {
+                            expanded.truncate(MAX_EXPANSIONS);
+                            return expanded;
+                        }
+                    }
+                    return expanded;
+                }
+            }
+            _ => {}
+        }
+    }
+    vec![pattern.to_string()]
+}
+
+/// Patterns to include and to exclude (those starting with `!`).
+#[derive(Debug)]
+struct Matcher {
+    include: GlobSet,
+    exclude: GlobSet,
+}
+
+impl Matcher {
+    fn new(patterns: &[String]) -> Result<Self, String> {
+        let mut include = GlobSetBuilder::new();
+        let mut exclude = GlobSetBuilder::new();
+        let mut includes = 0;
+        for pattern in patterns {
+            let (set, raw) = match pattern.trim().strip_prefix('!') {
+                Some(raw) => (&mut exclude, raw),
+                None => {
+                    includes += 1;
+                    (&mut include, pattern.trim())
+                }
+            };
+            let raw = raw.strip_prefix("./").unwrap_or(raw);
+            for expanded in expand_braces(raw) {
+                let glob = GlobBuilder::new(&expanded)
+                    .literal_separator(true)
+                    .build()
+                    .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+                set.add(glob);
+            }
+        }
+        if includes == 0 {
+            return Err("At least one pattern without '!' is needed".to_string());
+        }
+        let build = |set: GlobSetBuilder| set.build().map_err(|e| e.to_string());
+        Ok(Self {
+            include: build(include)?,
+            exclude: build(exclude)?,
+        })
+    }
+
+    /// Whether `path`, relative to the searched directory, is selected.
+    fn is_match(&self, path: &str) -> bool {
+        self.include.is_match(path) && !self.exclude.is_match(path)
+    }
+}
+
+/// A matched file.
+struct Found {
+    path: PathBuf,
+    mtime: Option<SystemTime>,
+    size: u64,
+}
+
+/// Files under `roots` matching `matcher`, in `sort` order.
+fn find(roots: &[PathBuf], matcher: &Matcher, sort: Sort) -> Vec<Found> {
+    let mut found: Vec<Found> = Vec::new();
+    let mut seen = HashSet::new();
+    for root in roots {
+        for entry in crate::walk::walk(root, Default::default()).flatten() {
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let Some(relative) = entry
+                .path()
+                .strip_prefix(root)
+                .ok()
+                .and_then(|p| p.to_str())
+            else {
+                continue;
+            };
+            if !matcher.is_match(relative) || !seen.insert(entry.path().to_path_buf()) {
+                continue;
+            }
+            let metadata = entry.metadata().ok();
+            found.push(Found {
+                path: entry.path().to_path_buf(),
+                mtime: metadata.as_ref().and_then(|m| m.modified().ok()),
+                size: metadata.map_or(0, |m| m.len()),
+            });
+        }
+    }
+    match sort {
+        Sort::Path => found.sort_by(|a, b| a.path.cmp(&b.path)),
+        Sort::Mtime => found.sort_by(|a, b| b.mtime.cmp(&a.mtime).then(a.path.cmp(&b.path))),
+        Sort::Size => found.sort_by(|a, b| b.size.cmp(&a.size).then(a.path.cmp(&b.path))),
+    }
+    found
+}
+
+impl Tool for Glob {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "glob".to_string(),
+            description: "List files matching glob patterns, in one call. `*` matches within a path component and `**` across directories; `{a,b}` alternatives may be nested. Patterns starting with `!` exclude files, e.g. [\"src/**/*.{rs,toml}\", \"!**/tests/**\"]. Patterns are relative to each searched directory. Ignored and hidden files are skipped."
+                .to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "patterns": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Glob patterns; prefix with ! to exclude"
+                    },
+                    "paths": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Directories to search (default: the working directory)"
+                    },
+                    "sort": {
+                        "type": "string",
+                        "enum": ["path", "mtime", "size"],
+                        "description": "Order: path (default), mtime (newest first) or size (largest first)"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum number of files to list (default: 200)"
+                    }
+                },
+                "required": ["patterns"]
+            }),
+        }
+    }
+
+    async fn execute(
+        &self,
+        tool_use_id: &str,
+        input: serde_json::Value,
+        output: &crate::output::OutputContext,
+        _services: &crate::services::Services,
+    ) -> ToolResult {
+        let input: GlobInput = match super::deserialize_input(tool_use_id, input) {
+            Ok(i) => i,
+            Err(e) => return *e,
+        };
+        let matcher = match Matcher::new(&input.patterns) {
+            Ok(matcher) => matcher,
+            Err(e) => return ToolResult::error(tool_use_id, e),
+        };
+
+        let workspace = paths::workspace_root();
+        let mut roots = Vec::new();
+        for raw in &input.paths {
+            let resolved = paths::resolve(raw, &workspace);
+            if !resolved.path.is_dir() {
+                return ToolResult::error(tool_use_id, format!("Not a directory: {}", raw));
+            }
+            roots.push(resolved.path);
+        }
+        if roots.is_empty() {
+            roots.push(workspace.clone());
+        }
+
+        let limit = input.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let sort = input.sort;
+        let found = match tokio::task::spawn_blocking(move || find(&roots, &matcher, sort)).await {
+            Ok(found) => found,
+            Err(e) => return ToolResult::error(tool_use_id, format!("Glob failed: {}", e)),
+        };
+        if found.is_empty() {
+            return ToolResult::success(tool_use_id, "No files match.").with_summary("No matches");
+        }
+
+        let mut content = found
+            .iter()
+            .take(limit)
+            .map(|f| paths::display(&f.path, &workspace))
+            .collect::<Vec<_>>()
+            .join("\n");
+        for line in content.lines() {
+            crate::output::emit_tool_output(output, &format!("{}\n", line));
+        }
+        if found.len() > limit {
+            content.push_str(&format!(
+                "\n... ({} more files; narrow the patterns or raise the limit)",
+                found.len() - limit
+            ));
+        }
+        let summary = format!(
+            "{} {}",
+            found.len(),
+            if found.len() == 1 { "file" } else { "files" }
+        );
+        ToolResult::success(tool_use_id, content).with_summary(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(expand_braces("src/*.rs"), vec!["src/*.rs"]);
+        assert_eq!(
+            expand_braces("{src,tests}/**/*.{rs,toml}"),
+            vec![
+                "src/**/*.rs",
+                "src/**/*.toml",
+                "tests/**/*.rs",
+                "tests/**/*.toml"
+            ]
+        );
+        assert_eq!(
+            expand_braces("*.{js,{ts,tsx}}"),
+            vec!["*.js", "*.ts", "*.tsx"]
+        );
+        assert_eq!(expand_braces("a{b}c"), vec!["a{b}c"]);
+    }
+
+    #[test]
+    fn test_find() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/tests")).unwrap();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(root.join("src/tests/parse.rs"), "").unwrap();
+        std::fs::write(root.join("docs/guide.md"), "# A longer guide\n\nText.\n").unwrap();
+        std::fs::write(root.join("Cargo.toml"), "").unwrap();
+
+        let matcher =
+            Matcher::new(&["**/*.{rs,md}".to_string(), "!**/tests/**".to_string()]).unwrap();
+        let names = |found: Vec<Found>| -> Vec<String> {
+            found
+                .iter()
+                .map(|f| paths::display(&f.path, root))
+                .collect()
+        };
+        let roots = vec![root.to_path_buf()];
+        assert_eq!(
+            names(find(&roots, &matcher, Sort::Path)),
+            vec!["docs/guide.md", "src/main.rs"]
+        );
+        assert_eq!(
+            names(find(&roots, &matcher, Sort::Size)),
+            vec!["docs/guide.md", "src/main.rs"]
+        );
+
+        // Patterns are relative to each searched directory.
+        let matcher = Matcher::new(&["*.rs".to_string()]).unwrap();
+        let roots = vec![root.join("src"), root.join("src/tests")];
+        assert_eq!(
+            names(find(&roots, &matcher, Sort::Path)),
+            vec!["src/main.rs", "src/tests/parse.rs"]
+        );
+
+        assert!(Matcher::new(&["!*.rs".to_string()]).is_err());
+    }
+}
@@ -1,35 +1,65 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Jason Ish
 
+//! Tools the model can call.
+//!
+//! Embedders can list the tools offered to the model with
+//! [`all_definitions`]; the agent loop runs them.
+
+mod apply_patch;
 mod bash;
+mod codebase_search;
+mod external;
 mod fetch;
 mod file_edit;
 mod file_read;
 mod file_write;
+mod glob;
+pub(crate) mod paths;
+#[cfg(feature = "plugins")]
+pub(crate) mod plugin;
+pub(crate) mod post_edit;
 mod sandbox;
+pub(crate) mod schema;
+mod semantic_search;
+pub(crate) mod task;
 
+pub(crate) use apply_patch::{ApplyPatch, patch_paths};
 pub(crate) use bash::Bash;
-pub(crate) use fetch::Fetch;
+pub(crate) use codebase_search::CodebaseSearch;
+pub(crate) use fetch::{Fetch, last_turn_sources};
 pub(crate) use file_edit::FileEdit;
-pub(crate) use file_read::FileRead;
+pub(crate) use file_read::{FileRead, detect_image_mime};
 pub(crate) use file_write::FileWrite;
+pub(crate) use glob::Glob;
+pub(crate) use semantic_search::SemanticSearch;
+pub(crate) use task::Task;
 
 use serde::{Deserialize, Serialize, de};
 
-pub(crate) const READ_ONLY_DISABLED_TOOLS: &[&str] = &["file_edit", "file_write"];
+pub(crate) const READ_ONLY_DISABLED_TOOLS: &[&str] = &["apply_patch", "file_edit", "file_write"];
 
 /// Built-in tool names and their human-readable descriptions.
 /// This is the single source of truth for tool metadata used in menus and UIs.
 pub(crate) const TOOL_INFO: &[(&str, &str)] = &[
+    ("apply_patch", "Apply a diff to one or more files"),
     ("bash", "Execute shell commands"),
+    (
+        "codebase_search",
+        "Search an index of definitions and files",
+    ),
     ("fetch", "Fetch URLs and convert to markdown"),
     ("file_edit", "Edit files with string replacements"),
     ("file_read", "Read file contents"),
     ("file_write", "Write content to files"),
+    ("glob", "List files matching glob patterns"),
+    ("semantic_search", "Search code by meaning with embeddings"),
+    ("task", "Delegate exploration to a sub-agent"),
 ];
 
 const BUILTIN_TOOL_ALIASES: &[(&str, &str)] = &[
     ("edit", "file_edit"),
+    ("patch", "apply_patch"),
     ("read", "file_read"),
     ("cat", "file_read"),
     ("write", "file_write"),
@@ -37,12 +67,45 @@ const BUILTIN_TOOL_ALIASES: &[(&str, &str)] = &[
 
 /// Tool definition for AI model consumption
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub(crate) struct ToolDefinition {
+pub struct ToolDefinition {
     pub name: String,
     pub description: String,
+    /// JSON Schema of the tool's input
     pub input_schema: serde_json::Value,
 }
 
+impl ToolDefinition {
+    /// The definition with only the first sentence of each description, for
+    /// models with small context windows.
+    pub(crate) fn condensed(mut self) -> Self {
+        self.description = first_sentence(&self.description);
+        if let Some(properties) = self
+            .input_schema
+            .get_mut("properties")
+            .and_then(|p| p.as_object_mut())
+        {
+            for property in properties.values_mut() {
+                if let Some(serde_json::Value::String(description)) =
+                    property.get_mut("description")
+                {
+                    *description = first_sentence(description);
+                }
+            }
+        }
+        self
+    }
+}
+
+fn first_sentence(text: &str) -> String {
+    let text = text.trim();
+    let end = text
+        .find(". ")
+        .map(|i| i + 1)
+        .or_else(|| text.find('\n'))
+        .unwrap_or(text.len());
+    text[..end].trim_end().to_string()
+}
+
 /// Result of executing a tool
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct ToolResult {
@@ -212,11 +275,16 @@ pub(crate) fn optional_usize_from_value(value: Option<&serde_json::Value>) -> Op
 pub(crate) fn canonicalize_builtin_tool_name(name: &str) -> Option<&'static str> {
     let lower = name.to_ascii_lowercase();
     match lower.as_str() {
+        "apply_patch" => Some("apply_patch"),
         "bash" => Some("bash"),
+        "codebase_search" => Some("codebase_search"),
         "fetch" => Some("fetch"),
         "file_edit" => Some("file_edit"),
         "file_read" => Some("file_read"),
         "file_write" => Some("file_write"),
+        "glob" => Some("glob"),
+        "semantic_search" => Some("semantic_search"),
+        "task" => Some("task"),
         _ => BUILTIN_TOOL_ALIASES
             .iter()
             .find_map(|(alias, canonical)| (*alias == lower).then_some(*canonical)),
@@ -287,6 +355,11 @@ pub(crate) fn expand_tilde(path: &str) -> String {
     path.to_string()
 }
 
+/// Render a tool path for display: relative to the workspace when inside it.
+fn display_tool_path(path: &str) -> String {
+    paths::resolve(path, &paths::workspace_root()).display
+}
+
 /// Collapse the user's home directory to `~` for display purposes.
 fn collapse_home_for_display(path: &str) -> String {
     // If the input already uses ~, don't expand and re-collapse it.
@@ -320,30 +393,32 @@ fn collapse_home_for_display(path: &str) -> String {
 /// 1. Checking if LSP handles this file type
 /// 2. Notifying the LSP of the file change
 /// 3. Emitting info messages when new LSP servers are activated
-/// 4. Waiting for and returning diagnostics
+/// 4. Waiting for the diagnostics published for the new content
 ///
-/// Returns an empty Vec if LSP doesn't handle this file.
+/// The diagnostics from before the change are returned with them so the ones
+/// the edit introduced can be told apart. Both are empty if LSP doesn't
+/// handle this file.
 pub(crate) async fn notify_lsp_and_get_diagnostics(
     path: &std::path::Path,
     content: &str,
     services: &crate::services::Services,
     output: &crate::output::OutputContext,
-) -> Vec<crate::lsp::FileDiagnostic> {
+) -> crate::lsp::ChangeDiagnostics {
     if !services.lsp.handles_file(path).await {
-        return Vec::new();
+        return Default::default();
     }
 
-    if let Ok(started_servers) = services.lsp.notify_file_changed(path, content).await {
-        for server in &started_servers {
-            let extensions = server.file_extensions.join(", ");
-            output.emit(crate::output::OutputEvent::Info(format!(
-                "[LSP activated: {} ({})]",
-                server.name, extensions
-            )));
-        }
+    let change = services.lsp.diagnose_change(path, content).await;
+    for server in &change.started {
+        let extensions = server.file_extensions.join(", ");
+        output.emit(crate::output::OutputEvent::Info(format!(
+            "[LSP activated: {} ({})]",
+            server.name, extensions
+        )));
     }
 
-    services.lsp.get_diagnostics_with_wait(path).await
+    crate::lsp::quickfix::record(path, &change.current);
+    change
 }
 
 /// Format a message with LSP diagnostics appended.
@@ -352,20 +427,17 @@ pub(crate) async fn notify_lsp_and_get_diagnostics(
 /// 1. Emitting a diagnostic summary as an info message
 /// 2. Returning the message with diagnostics appended
 ///
-/// If there are no diagnostics, returns the original message unchanged.
+/// If there are no diagnostics and none were fixed, returns the original
+/// message unchanged.
 pub(crate) fn format_message_with_diagnostics(
     msg: String,
-    diagnostics: &[crate::lsp::FileDiagnostic],
+    change: &crate::lsp::ChangeDiagnostics,
     output: &crate::output::OutputContext,
 ) -> String {
-    if diagnostics.is_empty() {
-        return msg;
-    }
-
-    if let Some(summary) = crate::lsp::diagnostic_summary(diagnostics) {
+    if let Some(summary) = crate::lsp::diagnostic_summary(&change.current) {
         output.emit(crate::output::OutputEvent::Info(summary));
     }
-    format!("{}{}", msg, crate::lsp::format_diagnostics(diagnostics))
+    format!("{}{}", msg, crate::lsp::format_diagnostics(change))
 }
 
 /// Generates a one-liner description for a tool call (used for UI display)
@@ -403,7 +475,7 @@ pub(crate) fn format_tool_call_description(tool_name: &str, input: &serde_json::
                 .get("filename")
                 .and_then(|v| v.as_str())
                 .unwrap_or("file");
-            let filename = collapse_home_for_display(filename);
+            let filename = display_tool_path(filename);
             let offset = optional_usize_from_value(input.get("offset"));
             let limit = optional_usize_from_value(input.get("limit"));
             match (offset, limit) {
@@ -418,7 +490,7 @@ pub(crate) fn format_tool_call_description(tool_name: &str, input: &serde_json::
                 .get("filePath")
                 .and_then(|v| v.as_str())
                 .unwrap_or("file");
-            let filepath = collapse_home_for_display(filepath);
+            let filepath = display_tool_path(filepath);
             format!("Editing {}", filepath)
         }
         "file_write" => {
@@ -426,13 +498,49 @@ pub(crate) fn format_tool_call_description(tool_name: &str, input: &serde_json::
                 .get("filePath")
                 .and_then(|v| v.as_str())
                 .unwrap_or("file");
-            let filepath = collapse_home_for_display(filepath);
+            let filepath = display_tool_path(filepath);
             format!("Writing {}", filepath)
         }
+        "apply_patch" => {
+            let paths = patch_paths(input);
+            if paths.is_empty() {
+                "Applying patch".to_string()
+            } else {
+                let paths: Vec<String> = paths.iter().map(|p| display_tool_path(p)).collect();
+                format!("Patching {}", paths.join(", "))
+            }
+        }
+        "codebase_search" => {
+            let query = input.get("query").and_then(|v| v.as_str()).unwrap_or("");
+            format!("Searching codebase: {}", query)
+        }
         "fetch" => {
             let url = input.get("url").and_then(|v| v.as_str()).unwrap_or("url");
             format!("Fetching {}", url)
         }
+        "glob" => {
+            let patterns = match input.get("patterns") {
+                Some(serde_json::Value::Array(patterns)) => patterns
+                    .iter()
+                    .filter_map(|p| p.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                Some(serde_json::Value::String(pattern)) => pattern.clone(),
+                _ => "files".to_string(),
+            };
+            format!("Globbing {}", patterns)
+        }
+        "semantic_search" => {
+            let query = input.get("query").and_then(|v| v.as_str()).unwrap_or("");
+            format!("Searching by meaning: {}", query)
+        }
+        "task" => {
+            let description = input
+                .get("description")
+                .and_then(|v| v.as_str())
+                .unwrap_or("task");
+            format!("Delegating: {}", description)
+        }
         name if name.starts_with("mcp_") => {
             // MCP tools: format as "tool_name via server_name"
             // Format: mcp_{server}_{tool}
@@ -466,9 +574,15 @@ pub(crate) fn builtin_definitions(
 
     let mut tools = Vec::new();
 
+    if !is_disabled("apply_patch") {
+        tools.push(ApplyPatch.definition());
+    }
     if !is_disabled("bash") {
         tools.push(Bash.definition());
     }
+    if !is_disabled("codebase_search") {
+        tools.push(CodebaseSearch.definition());
+    }
     if !is_disabled("fetch") {
         tools.push(Fetch.definition());
     }
@@ -481,14 +595,38 @@ pub(crate) fn builtin_definitions(
     if !is_disabled("file_write") {
         tools.push(FileWrite.definition());
     }
+    if !is_disabled("glob") {
+        tools.push(Glob.definition());
+    }
+    if !is_disabled("task") {
+        tools.push(Task.definition());
+    }
     tools
 }
 
 /// Get all available tool definitions including MCP tools
-pub(crate) async fn all_definitions(services: &crate::services::Services) -> Vec<ToolDefinition> {
+pub async fn all_definitions(services: &crate::services::Services) -> Vec<ToolDefinition> {
     // Load config once and extract all needed values
     let config = crate::config::ConfigFile::load().unwrap_or_default();
     let mut defs = builtin_definitions(&config.disabled_tools, services.is_read_only());
+    // Only offered when there is an endpoint to compute embeddings with.
+    if config.embeddings.is_some() && !config.disabled_tools.iter().any(|t| t == "semantic_search")
+    {
+        defs.push(SemanticSearch.definition());
+    }
+    if task::in_task() {
+        // Sub-agents get a fixed read-only toolset
+        defs.retain(|d| task::TASK_TOOLS.contains(&d.name.as_str()));
+        return defs;
+    }
+    if crate::provider::mistral::fim_provider(&config).is_some()
+        && let Some(def) = defs.iter_mut().find(|d| d.name == "file_edit")
+    {
+        file_edit::add_fill_parameter(def);
+    }
+    defs.extend(external::definitions(&config));
+    #[cfg(feature = "plugins")]
+    defs.extend(plugin::definitions(&config));
     let mcp_defs = services.mcp.all_tool_definitions().await;
     defs.extend(mcp_defs);
     defs
@@ -524,7 +662,21 @@ pub(crate) async fn execute(
 
         // First try built-in tools (including aliases)
         match name {
+            "apply_patch" => {
+                return Some(
+                    ApplyPatch
+                        .execute(tool_use_id, input, output, services)
+                        .await,
+                );
+            }
             "bash" => return Some(Bash.execute(tool_use_id, input, output, services).await),
+            "codebase_search" => {
+                return Some(
+                    CodebaseSearch
+                        .execute(tool_use_id, input, output, services)
+                        .await,
+                );
+            }
             "fetch" => return Some(Fetch.execute(tool_use_id, input, output, services).await),
             "file_edit" => {
                 return Some(FileEdit.execute(tool_use_id, input, output, services).await);
@@ -539,10 +691,33 @@ pub(crate) async fn execute(
                         .await,
                 );
             }
+            "glob" => return Some(Glob.execute(tool_use_id, input, output, services).await),
+            "semantic_search" => {
+                return Some(
+                    SemanticSearch
+                        .execute(tool_use_id, input, output, services)
+                        .await,
+                );
+            }
             _ => {}
         }
     }
 
+    if let Some(tool) = external::find(&config, name) {
+        if config.disabled_tools.iter().any(|t| t == name) {
+            return Some(ToolResult::error(
+                tool_use_id,
+                format!("Tool '{}' is disabled in configuration", name),
+            ));
+        }
+        return Some(external::execute(tool, tool_use_id, input, output, services).await);
+    }
+
+    #[cfg(feature = "plugins")]
+    if let Some(plugin) = plugin::find(&config, name) {
+        return Some(plugin::execute(plugin, tool_use_id, input, output).await);
+    }
+
     // Try MCP tools
     services.mcp.execute_tool(name, tool_use_id, input).await
 }
@@ -582,6 +757,20 @@ mod tests {
         assert_eq!(result, "foo/~bar");
     }
 
+    #[test]
+    fn test_condensed_definition() {
+        let def = FileRead.definition().condensed();
+        assert_eq!(def.description, "Read the contents of a file.");
+        let edit = FileEdit.definition();
+        let condensed = edit.clone().condensed();
+        assert!(condensed.description.len() < edit.description.len());
+        assert!(!condensed.description.contains('\n'));
+        assert_eq!(
+            condensed.input_schema["required"],
+            edit.input_schema["required"]
+        );
+    }
+
     #[test]
     fn test_builtin_definitions_read_only() {
         let defs = builtin_definitions(&[], true);
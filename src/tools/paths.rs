@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Workspace-relative path handling for tool input and output.
+//!
+//! Tools accept absolute, relative and `~` paths. They resolve them against
+//! the workspace root (the working directory) and report paths inside the
+//! workspace relative to it. Paths outside the workspace resolve like any
+//! other; writing to them is up to the sandbox and the write approval prompt.
+
+use std::path::{Component, Path, PathBuf};
+
+/// A tool path resolved against the workspace root.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct WorkspacePath {
+    /// Absolute, normalized path
+    pub path: PathBuf,
+    /// Path to show the model and the user
+    pub display: String,
+}
+
+/// The workspace root, which is the current working directory.
+pub(crate) fn workspace_root() -> PathBuf {
+    std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Resolve a tool path against `root`.
+pub(crate) fn resolve(raw: &str, root: &Path) -> WorkspacePath {
+    let expanded = PathBuf::from(super::expand_tilde(raw.trim()));
+    let path = normalize(&root.join(&expanded));
+    let display = display(&path, root);
+    WorkspacePath { path, display }
+}
+
+/// Render a path relative to `root` when inside it, otherwise absolute with
+/// the home directory collapsed to `~`.
+pub(crate) fn display(path: &Path, root: &Path) -> String {
+    match path.strip_prefix(root) {
+        Ok(rest) if rest.as_os_str().is_empty() => ".".to_string(),
+        Ok(rest) => rest.display().to_string(),
+        Err(_) => super::collapse_home_for_display(&path.to_string_lossy()),
+    }
+}
+
+/// Resolve a tool path against the workspace root.
+pub(crate) fn resolve_tool_path(raw: &str) -> WorkspacePath {
+    resolve(raw, &workspace_root())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_relative_and_absolute() {
+        let root = Path::new("/work/project");
+
+        let rel = resolve("./src/../src/main.rs", root);
+        assert_eq!(rel.path, PathBuf::from("/work/project/src/main.rs"));
+        assert_eq!(rel.display, "src/main.rs");
+
+        let abs = resolve("/work/project/Cargo.toml", root);
+        assert_eq!(abs.display, "Cargo.toml");
+
+        let outside = resolve("/etc/hosts", root);
+        assert_eq!(outside.display, "/etc/hosts");
+
+        assert_eq!(resolve(".", root).display, ".");
+    }
+
+    #[test]
+    fn test_resolve_relative_traversal() {
+        let root = Path::new("/work/project");
+        let outside = resolve("src/../../other/file.rs", root);
+        assert_eq!(outside.path, PathBuf::from("/work/other/file.rs"));
+        assert_eq!(outside.display, "/work/other/file.rs");
+    }
+}
@@ -0,0 +1,571 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Experimental WASM tool plugins.
+//!
+//! When `plugins.enabled` is set, every `*.wasm` file in the `plugins`
+//! directory of the config directory is loaded as a tool. A plugin is a
+//! WebAssembly module that exports:
+//!
+//! - `memory`
+//! - `henri_alloc(len: i32) -> i32`, returning a buffer of `len` bytes
+//! - `henri_tool() -> i64`, returning the tool definition as JSON
+//!   (`{"name", "description", "input_schema"}`)
+//! - `henri_execute(ptr: i32, len: i32) -> i64`, taking the tool input as
+//!   JSON and returning the result as JSON (`{"content", "is_error"}`)
+//!
+//! Strings are returned packed as `(ptr << 32) | len`. Modules built for
+//! WASI preview 1 get output, clocks and random numbers, but no files,
+//! network or environment, so a plugin can only see its input. Each call
+//! runs in a fresh instance with a fuel (instruction) budget and a memory
+//! limit.
+
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use wasmi::{
+    Caller, Config, Engine, Error, Extern, ExternType, Instance, Linker, Memory, Module, Store,
+    StoreLimits, StoreLimitsBuilder, Val, ValType,
+};
+
+use super::{ToolDefinition, ToolResult};
+use crate::config::{ConfigFile, PluginsConfig};
+
+/// Directory, relative to the config directory, holding plugins.
+const PLUGIN_DIR: &str = "plugins";
+
+const DEFAULT_FUEL: u64 = 1_000_000_000;
+const DEFAULT_MEMORY_MB: usize = 64;
+
+/// Maximum bytes of plugin stdout and stderr kept per call.
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+const WASI_MODULE: &str = "wasi_snapshot_preview1";
+const ERRNO_SUCCESS: i32 = 0;
+const ERRNO_BADF: i32 = 8;
+const ERRNO_NOSYS: i32 = 52;
+
+/// WASI functions implemented by the host. Other imports are stubbed.
+const WASI_FUNCTIONS: &[&str] = &[
+    "args_get",
+    "args_sizes_get",
+    "clock_time_get",
+    "environ_get",
+    "environ_sizes_get",
+    "fd_prestat_get",
+    "fd_write",
+    "proc_exit",
+    "random_get",
+];
+
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    fuel: u64,
+    memory_bytes: usize,
+}
+
+impl From<&PluginsConfig> for Limits {
+    fn from(config: &PluginsConfig) -> Self {
+        Self {
+            fuel: config.fuel.unwrap_or(DEFAULT_FUEL),
+            memory_bytes: config.memory_mb.unwrap_or(DEFAULT_MEMORY_MB) * 1024 * 1024,
+        }
+    }
+}
+
+struct HostState {
+    limits: StoreLimits,
+    /// Bytes written to stdout and stderr
+    output: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginOutput {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    is_error: bool,
+}
+
+/// A loaded plugin.
+pub(crate) struct Plugin {
+    definition: ToolDefinition,
+    module: Module,
+    limits: Limits,
+}
+
+impl Plugin {
+    fn load(engine: &Engine, wasm: &[u8], limits: Limits) -> Result<Self, String> {
+        let module = Module::new(engine, wasm).map_err(|e| e.to_string())?;
+        let mut plugin = Self {
+            definition: ToolDefinition {
+                name: String::new(),
+                description: String::new(),
+                input_schema: serde_json::Value::Null,
+            },
+            module,
+            limits,
+        };
+
+        let (mut store, instance) = plugin.instantiate()?;
+        let tool = instance
+            .get_typed_func::<(), i64>(&store, "henri_tool")
+            .map_err(|e| format!("henri_tool: {}", e))?;
+        let packed = tool
+            .call(&mut store, ())
+            .map_err(|e| format!("henri_tool: {}", e))?;
+        let json = read_packed(&store, &instance, packed)?;
+        plugin.definition =
+            serde_json::from_slice(&json).map_err(|e| format!("invalid tool definition: {}", e))?;
+        if !super::external::is_valid_name(&plugin.definition.name) {
+            return Err(format!("invalid tool name: {:?}", plugin.definition.name));
+        }
+        Ok(plugin)
+    }
+
+    /// Create a fresh instance with the plugin's limits applied.
+    fn instantiate(&self) -> Result<(Store<HostState>, Instance), String> {
+        let engine = self.module.engine();
+        let mut store = Store::new(
+            engine,
+            HostState {
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(self.limits.memory_bytes)
+                    .build(),
+                output: Vec::new(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store
+            .set_fuel(self.limits.fuel)
+            .map_err(|e| e.to_string())?;
+
+        let linker = linker(engine, &self.module).map_err(|e| e.to_string())?;
+        let instance = linker
+            .instantiate_and_start(&mut store, &self.module)
+            .map_err(|e| e.to_string())?;
+        if let Ok(init) = instance.get_typed_func::<(), ()>(&store, "_initialize") {
+            init.call(&mut store, ()).map_err(|e| e.to_string())?;
+        }
+        Ok((store, instance))
+    }
+
+    /// Run the plugin on `input`. Returns the result and anything the
+    /// plugin printed.
+    fn call(&self, input: &serde_json::Value) -> Result<(PluginOutput, String), String> {
+        let (mut store, instance) = self.instantiate()?;
+        let input = serde_json::to_vec(input).map_err(|e| e.to_string())?;
+
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "henri_alloc")
+            .map_err(|e| format!("henri_alloc: {}", e))?;
+        let execute = instance
+            .get_typed_func::<(i32, i32), i64>(&store, "henri_execute")
+            .map_err(|e| format!("henri_execute: {}", e))?;
+
+        let ptr = alloc
+            .call(&mut store, input.len() as i32)
+            .map_err(|e| format!("henri_alloc: {}", e))?;
+        guest_memory(&store, &instance)?
+            .write(&mut store, ptr as u32 as usize, &input)
+            .map_err(|e| e.to_string())?;
+        let packed = execute
+            .call(&mut store, (ptr, input.len() as i32))
+            .map_err(|e| format!("henri_execute: {}", e))?;
+
+        let json = read_packed(&store, &instance, packed)?;
+        let result: PluginOutput =
+            serde_json::from_slice(&json).map_err(|e| format!("invalid result: {}", e))?;
+        let printed = String::from_utf8_lossy(&store.data().output).into_owned();
+        Ok((result, printed))
+    }
+}
+
+fn guest_memory(store: &Store<HostState>, instance: &Instance) -> Result<Memory, String> {
+    instance
+        .get_memory(store, "memory")
+        .ok_or_else(|| "module does not export its memory".to_string())
+}
+
+/// Read a string returned as `(ptr << 32) | len`.
+fn read_packed(
+    store: &Store<HostState>,
+    instance: &Instance,
+    packed: i64,
+) -> Result<Vec<u8>, String> {
+    let ptr = (packed as u64 >> 32) as usize;
+    let len = (packed as u64 & 0xffff_ffff) as usize;
+    guest_memory(store, instance)?
+        .data(store)
+        .get(ptr..ptr + len)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| "returned string is out of bounds".to_string())
+}
+
+fn caller_memory(caller: &Caller<'_, HostState>) -> Option<Memory> {
+    caller.get_export("memory").and_then(Extern::into_memory)
+}
+
+fn write_u32(caller: &mut Caller<'_, HostState>, ptr: i32, value: u32) -> bool {
+    caller_memory(caller).is_some_and(|memory| {
+        memory
+            .write(&mut *caller, ptr as u32 as usize, &value.to_le_bytes())
+            .is_ok()
+    })
+}
+
+/// Build a linker with the host's WASI functions, and stubs that fail with
+/// ENOSYS (or trap) for any other function the module imports.
+fn linker(engine: &Engine, module: &Module) -> Result<Linker<HostState>, Error> {
+    let mut linker = Linker::new(engine);
+
+    linker.func_wrap(
+        WASI_MODULE,
+        "fd_write",
+        |mut caller: Caller<'_, HostState>, fd: i32, iovs: i32, iovs_len: i32, nwritten: i32| {
+            if fd != 1 && fd != 2 {
+                return ERRNO_BADF;
+            }
+            let Some(memory) = caller_memory(&caller) else {
+                return ERRNO_BADF;
+            };
+            let mut written = 0u32;
+            for i in 0..iovs_len.max(0) as usize {
+                let mut iov = [0u8; 8];
+                if memory
+                    .read(&caller, iovs as u32 as usize + i * 8, &mut iov)
+                    .is_err()
+                {
+                    return ERRNO_BADF;
+                }
+                let ptr = u32::from_le_bytes([iov[0], iov[1], iov[2], iov[3]]) as usize;
+                let len = u32::from_le_bytes([iov[4], iov[5], iov[6], iov[7]]) as usize;
+                let room = MAX_OUTPUT_BYTES.saturating_sub(caller.data().output.len());
+                let Some(bytes) = memory.data(&caller).get(ptr..ptr + len) else {
+                    return ERRNO_BADF;
+                };
+                let bytes = bytes[..len.min(room)].to_vec();
+                caller.data_mut().output.extend_from_slice(&bytes);
+                written += len as u32;
+            }
+            if write_u32(&mut caller, nwritten, written) {
+                ERRNO_SUCCESS
+            } else {
+                ERRNO_BADF
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        WASI_MODULE,
+        "random_get",
+        |mut caller: Caller<'_, HostState>, buf: i32, len: i32| {
+            let Some(memory) = caller_memory(&caller) else {
+                return ERRNO_BADF;
+            };
+            let (buf, len) = (buf as u32 as usize, len as u32 as usize);
+            match memory.data_mut(&mut caller).get_mut(buf..buf + len) {
+                Some(bytes) => {
+                    rand::fill(bytes);
+                    ERRNO_SUCCESS
+                }
+                None => ERRNO_BADF,
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        WASI_MODULE,
+        "clock_time_get",
+        |mut caller: Caller<'_, HostState>, _id: i32, _precision: i64, out: i32| {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(0);
+            match caller_memory(&caller) {
+                Some(memory)
+                    if memory
+                        .write(&mut caller, out as u32 as usize, &nanos.to_le_bytes())
+                        .is_ok() =>
+                {
+                    ERRNO_SUCCESS
+                }
+                _ => ERRNO_BADF,
+            }
+        },
+    )?;
+
+    // No arguments or environment variables
+    for name in ["args_sizes_get", "environ_sizes_get"] {
+        linker.func_wrap(
+            WASI_MODULE,
+            name,
+            |mut caller: Caller<'_, HostState>, count: i32, size: i32| {
+                if write_u32(&mut caller, count, 0) && write_u32(&mut caller, size, 0) {
+                    ERRNO_SUCCESS
+                } else {
+                    ERRNO_BADF
+                }
+            },
+        )?;
+    }
+    for name in ["args_get", "environ_get"] {
+        linker.func_wrap(WASI_MODULE, name, |_: i32, _: i32| ERRNO_SUCCESS)?;
+    }
+
+    // No preopened directories, so no filesystem access
+    linker.func_wrap(WASI_MODULE, "fd_prestat_get", |_: i32, _: i32| ERRNO_BADF)?;
+
+    linker.func_wrap(WASI_MODULE, "proc_exit", |code: i32| -> Result<(), Error> {
+        Err(Error::i32_exit(code))
+    })?;
+
+    for import in module.imports() {
+        let ExternType::Func(ty) = import.ty() else {
+            continue;
+        };
+        if import.module() == WASI_MODULE && WASI_FUNCTIONS.contains(&import.name()) {
+            continue;
+        }
+        let errno = ty.results() == [ValType::I32];
+        let name = format!("{}::{}", import.module(), import.name());
+        linker.func_new(
+            import.module(),
+            import.name(),
+            ty.clone(),
+            move |_, _, results| {
+                if errno {
+                    results[0] = Val::I32(ERRNO_NOSYS);
+                    Ok(())
+                } else {
+                    Err(Error::new(format!("{} is not available to plugins", name)))
+                }
+            },
+        )?;
+    }
+
+    Ok(linker)
+}
+
+#[derive(Default)]
+struct Registry {
+    plugins: Vec<Plugin>,
+    /// Plugins that failed to load, with the reason
+    errors: Vec<String>,
+}
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+fn load_dir(dir: &Path, limits: Limits) -> Registry {
+    let mut registry = Registry::default();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return registry;
+    };
+    let mut paths: Vec<_> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    paths.sort();
+
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+
+    for path in paths {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let plugin = std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|wasm| Plugin::load(&engine, &wasm, limits));
+        match plugin {
+            Ok(plugin)
+                if registry
+                    .plugins
+                    .iter()
+                    .any(|p| p.definition.name == plugin.definition.name) =>
+            {
+                registry.errors.push(format!(
+                    "{}: duplicate tool name {}",
+                    file_name, plugin.definition.name
+                ));
+            }
+            Ok(plugin) => registry.plugins.push(plugin),
+            Err(e) => registry.errors.push(format!("{}: {}", file_name, e)),
+        }
+    }
+    registry
+}
+
+/// The loaded plugins. They are loaded once, on first use.
+fn registry(config: &ConfigFile) -> &'static Registry {
+    REGISTRY.get_or_init(|| {
+        if !config.plugins.enabled {
+            return Registry::default();
+        }
+        load_dir(
+            &crate::config::config_dir().join(PLUGIN_DIR),
+            Limits::from(&config.plugins),
+        )
+    })
+}
+
+/// Whether a plugin tool is offered: external tools take precedence.
+fn is_offered(config: &ConfigFile, name: &str) -> bool {
+    !config.disabled_tools.iter().any(|t| t == name) && !config.tools.external.contains_key(name)
+}
+
+/// Definitions of the loaded plugins that are not disabled.
+pub(crate) fn definitions(config: &ConfigFile) -> Vec<ToolDefinition> {
+    if !config.plugins.enabled {
+        return Vec::new();
+    }
+    registry(config)
+        .plugins
+        .iter()
+        .filter(|p| is_offered(config, &p.definition.name))
+        .map(|p| p.definition.clone())
+        .collect()
+}
+
+/// The plugin providing tool `name`, if any.
+pub(crate) fn find(config: &ConfigFile, name: &str) -> Option<&'static Plugin> {
+    if !config.plugins.enabled || !is_offered(config, name) {
+        return None;
+    }
+    registry(config)
+        .plugins
+        .iter()
+        .find(|p| p.definition.name == name)
+}
+
+/// One-line plugin status for `/status`, or `None` when plugins are off.
+pub(crate) fn status(config: &ConfigFile) -> Option<String> {
+    if !config.plugins.enabled {
+        return None;
+    }
+    let registry = registry(config);
+    let mut status = if registry.plugins.is_empty() {
+        "none".to_string()
+    } else {
+        registry
+            .plugins
+            .iter()
+            .map(|p| p.definition.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if !registry.errors.is_empty() {
+        status.push_str(&format!("; failed: {}", registry.errors.join("; ")));
+    }
+    Some(status)
+}
+
+/// Run a plugin tool.
+pub(crate) async fn execute(
+    plugin: &'static Plugin,
+    tool_use_id: &str,
+    input: serde_json::Value,
+    output: &crate::output::OutputContext,
+) -> ToolResult {
+    let result = tokio::task::spawn_blocking(move || plugin.call(&input)).await;
+    match result {
+        Ok(Ok((result, printed))) => {
+            if !printed.is_empty() {
+                crate::output::emit_tool_output(output, &printed);
+            }
+            if result.is_error {
+                ToolResult::error(tool_use_id, result.content)
+            } else {
+                ToolResult::success(tool_use_id, result.content)
+            }
+        }
+        Ok(Err(e)) => ToolResult::error(tool_use_id, format!("Plugin failed: {}", e)),
+        Err(e) => ToolResult::error(tool_use_id, format!("Plugin panicked: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plugin that returns its input as the result.
+    const ECHO: &str = r#"
+(module
+  (import "wasi_snapshot_preview1" "fd_write"
+    (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "sock_accept"
+    (func $sock_accept (param i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 4096))
+  (data (i32.const 0)
+    "{\"name\":\"echo_input\",\"description\":\"Echo\",\"input_schema\":{\"type\":\"object\"}}")
+  ;; iovec for "hi\n" at 200
+  (data (i32.const 100) "\c8\00\00\00\03\00\00\00")
+  (data (i32.const 200) "hi\n")
+  (func (export "henri_alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (local.get $ptr))
+  (func (export "henri_tool") (result i64)
+    (i64.const 75))
+  (func (export "henri_execute") (param $ptr i32) (param $len i32) (result i64)
+    (drop (call $fd_write (i32.const 1) (i32.const 100) (i32.const 1) (i32.const 300)))
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+      (i64.extend_i32_u (local.get $len)))))
+"#;
+
+    /// A plugin that never returns.
+    const SPIN: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "{\"name\":\"spin\",\"description\":\"Spin\",\"input_schema\":{}}")
+  (func (export "henri_alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "henri_tool") (result i64) (i64.const 54))
+  (func (export "henri_execute") (param i32 i32) (result i64)
+    (loop $forever (br $forever))
+    (i64.const 0)))
+"#;
+
+    fn load(wat: &str) -> Result<Plugin, String> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let limits = Limits {
+            fuel: 1_000_000,
+            memory_bytes: 16 * 1024 * 1024,
+        };
+        Plugin::load(&Engine::new(&config), &wat::parse_str(wat).unwrap(), limits)
+    }
+
+    #[test]
+    fn test_plugin_definition_and_call() {
+        let plugin = load(ECHO).unwrap();
+        assert_eq!(plugin.definition.name, "echo_input");
+        assert_eq!(plugin.definition.input_schema["type"], "object");
+
+        let (result, printed) = plugin
+            .call(&serde_json::json!({"content": "hello", "is_error": true}))
+            .unwrap();
+        assert_eq!(result.content, "hello");
+        assert!(result.is_error);
+        assert_eq!(printed, "hi\n");
+    }
+
+    #[test]
+    fn test_plugin_runs_out_of_fuel() {
+        let plugin = load(SPIN).unwrap();
+        let err = plugin.call(&serde_json::json!({})).unwrap_err();
+        assert!(err.contains("fuel"), "{}", err);
+    }
+
+    #[test]
+    fn test_plugin_without_exports_fails_to_load() {
+        assert!(load("(module)").is_err());
+    }
+}
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Formatters and linters run on files changed by the edit and write tools.
+//!
+//! Checks are configured in `post-edit-checks` and matched by file
+//! extension. Each runs like a bash tool call, under the same sandbox, from
+//! the working directory with the changed file as its argument. What a
+//! check reports when it fails, and the fact that it rewrote the file, is
+//! appended to the tool result so the model doesn't keep working from the
+//! content it wrote.
+
+use std::path::Path;
+
+use super::bash;
+use super::external::shell_quote;
+use crate::config::{ConfigFile, PostEditCheckConfig};
+
+/// Default seconds a check may run before it is killed.
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// Output lines of a failed check returned to the model.
+const MAX_OUTPUT_LINES: usize = 50;
+
+/// The configured checks, shared through [`crate::services::Services`].
+#[derive(Default)]
+pub(crate) struct PostEditChecks {
+    checks: Vec<PostEditCheckConfig>,
+}
+
+impl PostEditChecks {
+    pub(crate) fn new(checks: Vec<PostEditCheckConfig>) -> Self {
+        Self { checks }
+    }
+
+    /// Load the checks from the config file.
+    pub(crate) fn load() -> Self {
+        Self::new(ConfigFile::load().unwrap_or_default().post_edit_checks)
+    }
+
+    /// Run the checks for `path`. Returns what to tell the model, or `None`
+    /// if every check passed without changing the file.
+    pub(crate) async fn run(
+        &self,
+        path: &Path,
+        output: &crate::output::OutputContext,
+        services: &crate::services::Services,
+    ) -> Option<String> {
+        let extension = path.extension()?.to_str()?;
+        let checks: Vec<_> = self
+            .checks
+            .iter()
+            .filter(|check| check.extensions.iter().any(|e| e == extension))
+            .collect();
+        if checks.is_empty() {
+            return None;
+        }
+
+        let before = std::fs::read(path).ok();
+        let mut report = String::new();
+        for check in checks {
+            let command = render_command(&check.command, path);
+            if let Some(problem) = run_check(&command, check.timeout, services).await {
+                output.emit(crate::output::OutputEvent::Info(format!(
+                    "[Post-edit check failed: {}]",
+                    check.command
+                )));
+                report.push_str(&format!("\n\n`{}` {}", command, problem));
+            }
+        }
+        if std::fs::read(path).ok() != before {
+            report.insert_str(
+                0,
+                "\n\nPost-edit checks changed the file; read it again before editing it.",
+            );
+        }
+        (!report.is_empty()).then_some(report)
+    }
+}
+
+/// Replace `{file}` in `template` with the quoted path, or append the path
+/// when there is no placeholder.
+fn render_command(template: &str, path: &Path) -> String {
+    let file = shell_quote(&path.display().to_string());
+    if template.contains("{file}") {
+        template.replace("{file}", &file)
+    } else {
+        format!("{} {}", template, file)
+    }
+}
+
+/// Run `command`. Returns a description of the failure, with its output, if
+/// it didn't succeed.
+async fn run_check(
+    command: &str,
+    timeout: Option<u64>,
+    services: &crate::services::Services,
+) -> Option<String> {
+    // The output goes to the model with the tool result, not to the screen
+    let result = bash::run(
+        "post-edit-check",
+        command,
+        timeout.unwrap_or(DEFAULT_TIMEOUT_SECS),
+        None,
+        None,
+        &crate::output::OutputContext::null(),
+        services,
+    )
+    .await;
+    if !result.is_error {
+        return None;
+    }
+    let Some(code) = result.exit_code else {
+        return Some(format!("failed: {}", result.content));
+    };
+
+    let text = result
+        .content
+        .strip_suffix(&format!("[Exit code: {}]", code))
+        .unwrap_or(&result.content);
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    let status = if code < 0 {
+        "a signal".to_string()
+    } else {
+        format!("status {}", code)
+    };
+    let mut problem = format!("exited with {}:", status);
+    for line in lines.iter().take(MAX_OUTPUT_LINES) {
+        problem.push('\n');
+        problem.push_str(line);
+    }
+    if lines.len() > MAX_OUTPUT_LINES {
+        problem.push_str(&format!(
+            "\n... ({} more lines)",
+            lines.len() - MAX_OUTPUT_LINES
+        ));
+    }
+    Some(problem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(command: &str, extensions: &[&str]) -> PostEditCheckConfig {
+        PostEditCheckConfig {
+            command: command.to_string(),
+            extensions: extensions.iter().map(|e| e.to_string()).collect(),
+            timeout: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.rs");
+        std::fs::write(&path, "fn main(){}\n").unwrap();
+        let output = crate::output::OutputContext::null();
+        let services = crate::services::Services::null();
+
+        let checks = PostEditChecks::new(vec![
+            check("sed -i 's/(){/() {/'", &["rs"]),
+            check("echo warning: unused in {file}; exit 1", &["rs"]),
+            check("exit 1", &["js"]),
+        ]);
+        let report = checks.run(&path, &output, &services).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "fn main() {}\n");
+        assert!(report.contains("changed the file"));
+        assert!(report.contains(&format!(
+            "exited with status 1:\nwarning: unused in {}",
+            path.display()
+        )));
+
+        // Nothing to report once the file is clean.
+        let checks = PostEditChecks::new(vec![check("sed -i 's/(){/() {/'", &["rs"])]);
+        assert_eq!(checks.run(&path, &output, &services).await, None);
+    }
+}
@@ -58,12 +58,16 @@ fn canonicalize_existing(path: &Path) -> PathBuf {
     path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
 }
 
-fn allowed_write_paths(cwd: &Path) -> AllowedWritePaths {
+fn allowed_write_paths(cwd: &Path, extra: &[PathBuf]) -> AllowedWritePaths {
     let mut allowed = AllowedWritePaths::default();
     let cwd = canonicalize_existing(cwd);
 
     allowed.directories.push(cwd.clone());
 
+    for dir in extra {
+        allowed.directories.push(canonicalize_existing(dir));
+    }
+
     if let Some(git_dir) = get_git_worktree_dir(&cwd) {
         allowed.directories.push(canonicalize_existing(&git_dir));
     }
@@ -81,8 +85,8 @@ fn allowed_write_paths(cwd: &Path) -> AllowedWritePaths {
     allowed
 }
 
-fn allowed_write_paths_for_ruleset(cwd: &Path) -> Vec<PathBuf> {
-    let allowed = allowed_write_paths(cwd);
+fn allowed_write_paths_for_ruleset(cwd: &Path, extra: &[PathBuf]) -> Vec<PathBuf> {
+    let allowed = allowed_write_paths(cwd, extra);
     allowed
         .directories
         .into_iter()
@@ -112,7 +116,7 @@ fn canonicalize_for_write(path: &Path) -> Result<PathBuf, String> {
     Err(format!("Failed to resolve path: {}", path.display()))
 }
 
-fn validate_write_path(target: &Path, cwd: &Path) -> Result<(), String> {
+fn validate_write_path(target: &Path, cwd: &Path, extra: &[PathBuf]) -> Result<(), String> {
     let absolute_target = if target.is_absolute() {
         target.to_path_buf()
     } else {
@@ -120,7 +124,7 @@ fn validate_write_path(target: &Path, cwd: &Path) -> Result<(), String> {
     };
 
     let canonical_target = canonicalize_for_write(&absolute_target)?;
-    let allowed = allowed_write_paths(cwd);
+    let allowed = allowed_write_paths(cwd, extra);
 
     if allowed.files.contains(&canonical_target) {
         return Ok(());
@@ -140,16 +144,19 @@ fn validate_write_path(target: &Path, cwd: &Path) -> Result<(), String> {
     ))
 }
 
+/// Check a write against the sandbox. `extra` lists additional writable
+/// directories, such as those the user allowed permanently.
 pub(crate) fn check_write_access(
     target: &Path,
     cwd: &Path,
+    extra: &[PathBuf],
     sandbox_enabled: bool,
 ) -> Result<(), String> {
     if !sandbox_enabled || !is_available() {
         return Ok(());
     }
 
-    validate_write_path(target, cwd)
+    validate_write_path(target, cwd, extra)
 }
 
 pub(crate) fn create_read_only_ruleset() -> Option<RulesetCreated> {
@@ -185,11 +192,12 @@ pub(crate) fn create_read_only_ruleset() -> Option<RulesetCreated> {
 /// The ruleset restricts only write operations:
 /// - Write access allowed to the working directory and subdirectories
 /// - Write access allowed to git worktree directory (if in a worktree)
+/// - Write access allowed to the `extra` directories
 /// - Write access allowed to /tmp, /var/tmp, /dev/null, /dev/tty
 /// - Read access is unrestricted
 ///
 /// Returns `None` if Landlock is not supported or paths can't be accessed.
-pub(crate) fn create_bash_ruleset(cwd: &Path) -> Option<RulesetCreated> {
+pub(crate) fn create_bash_ruleset(cwd: &Path, extra: &[PathBuf]) -> Option<RulesetCreated> {
     let abi = ABI::V5;
 
     // Only restrict write operations - reads are unrestricted
@@ -202,7 +210,7 @@ pub(crate) fn create_bash_ruleset(cwd: &Path) -> Option<RulesetCreated> {
         .ok()?;
 
     // Allow writes to cwd and other permitted paths
-    let allowed_paths = allowed_write_paths_for_ruleset(cwd);
+    let allowed_paths = allowed_write_paths_for_ruleset(cwd, extra);
     let ruleset = ruleset
         .add_rules(path_beneath_rules(allowed_paths, write_access))
         .ok()?;
@@ -243,7 +251,7 @@ mod tests {
     fn test_create_ruleset() {
         let cwd = env::current_dir().unwrap();
         // This may return None on systems without Landlock support
-        let _ruleset = create_bash_ruleset(&cwd);
+        let _ruleset = create_bash_ruleset(&cwd, &[]);
         // Just verify it doesn't panic
     }
 
@@ -252,7 +260,7 @@ mod tests {
         let cwd = env::current_dir().unwrap();
         let allowed_path = cwd.join("sandbox-test.txt");
 
-        assert!(validate_write_path(&allowed_path, &cwd).is_ok());
+        assert!(validate_write_path(&allowed_path, &cwd, &[]).is_ok());
     }
 
     #[test]
@@ -260,6 +268,6 @@ mod tests {
         let cwd = env::current_dir().unwrap();
         let allowed_path = PathBuf::from("/tmp/henri-sandbox-test.txt");
 
-        assert!(validate_write_path(&allowed_path, &cwd).is_ok());
+        assert!(validate_write_path(&allowed_path, &cwd, &[]).is_ok());
     }
 }
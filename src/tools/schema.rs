@@ -0,0 +1,432 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Tool input schemas, as each provider wants them.
+//!
+//! Tools describe their inputs with JSON Schema, from whichever draft their
+//! author used. Before a schema is sent it is brought into one form (an object
+//! at the top, local `$ref`s inlined, draft metadata dropped) and then adapted
+//! for the provider: Anthropic and OpenAI take it as is, OpenAI's strict mode
+//! needs every property required and no extra properties, and Gemini and most
+//! OpenAI-compatible servers reject keywords they don't know.
+//!
+//! The arguments a model sends are checked against the tool's schema before
+//! the tool runs, so a bad call gets an error the model can act on instead of
+//! whatever the tool makes of it.
+
+use serde_json::{Map, Value, json};
+
+use super::ToolResult;
+
+/// How deep `$ref`s are inlined, so recursive definitions stay finite.
+const MAX_REF_DEPTH: usize = 8;
+
+/// Keywords rejected by OpenAI-compatible servers that only take a subset.
+const COMPAT_UNSUPPORTED: &[&str] = &["$schema", "$id", "$comment", "additionalProperties"];
+
+/// Keywords rejected by Gemini function declarations.
+const GEMINI_UNSUPPORTED: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "$ref",
+    "$defs",
+    "definitions",
+    "const",
+    "additionalProperties",
+    "propertyNames",
+    "title",
+];
+
+/// Keywords allowed in OpenAI strict mode schemas, as far as we use it.
+const STRICT_KEYWORDS: &[&str] = &[
+    "type",
+    "description",
+    "properties",
+    "required",
+    "items",
+    "enum",
+    "additionalProperties",
+];
+
+/// The schema flavour a provider accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Dialect {
+    Anthropic,
+    OpenAi,
+    /// OpenAI-compatible servers, which often take only a subset
+    Compat,
+    Gemini,
+}
+
+/// A tool's schema adapted for a provider.
+pub(crate) fn adapt(schema: &Value, dialect: Dialect) -> Value {
+    let schema = normalize(schema);
+    match dialect {
+        Dialect::Anthropic | Dialect::OpenAi => schema,
+        Dialect::Compat => strip(schema, COMPAT_UNSUPPORTED),
+        Dialect::Gemini => strip(schema, GEMINI_UNSUPPORTED),
+    }
+}
+
+/// A tool's schema for OpenAI strict mode, or `None` if it uses keywords strict
+/// mode doesn't allow and has to be sent without it.
+///
+/// Optional properties become required but nullable; [`validate`] drops the
+/// nulls again before the tool sees them.
+pub(crate) fn strict(schema: &Value) -> Option<Value> {
+    strict_schema(normalize(schema), false)
+}
+
+/// Bring a schema from any draft into the form sent to providers.
+fn normalize(schema: &Value) -> Value {
+    let Value::Object(map) = schema else {
+        return json!({"type": "object", "properties": {}});
+    };
+    let mut root = Value::Object(map.clone());
+    inline_refs(&mut root, schema, 0);
+    let Value::Object(mut map) = root else {
+        unreachable!()
+    };
+    for keyword in ["$schema", "$id", "$defs", "definitions"] {
+        map.remove(keyword);
+    }
+    if !map.contains_key("type") {
+        map.insert("type".to_string(), json!("object"));
+    }
+    if map.get("type") == Some(&json!("object")) && !map.contains_key("properties") {
+        map.insert("properties".to_string(), json!({}));
+    }
+    Value::Object(map)
+}
+
+/// The schemas nested directly inside a schema object.
+fn subschemas_mut(map: &mut Map<String, Value>) -> Vec<&mut Value> {
+    let mut children = Vec::new();
+    for (key, value) in map.iter_mut() {
+        match (key.as_str(), value) {
+            ("properties" | "patternProperties" | "$defs" | "definitions", Value::Object(map)) => {
+                children.extend(map.values_mut());
+            }
+            ("anyOf" | "oneOf" | "allOf" | "prefixItems" | "items", Value::Array(list)) => {
+                children.extend(list.iter_mut());
+            }
+            ("items" | "additionalProperties" | "not", value @ Value::Object(_)) => {
+                children.push(value);
+            }
+            _ => {}
+        }
+    }
+    children
+}
+
+/// Replace local `$ref`s (`#/$defs/...` or `#/definitions/...`) with what
+/// they point to, keeping any keywords next to the `$ref`.
+fn inline_refs(schema: &mut Value, root: &Value, depth: usize) {
+    let Value::Object(map) = schema else {
+        return;
+    };
+    if let Some(Value::String(reference)) = map.get("$ref")
+        && let Some(Value::Object(target)) = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+    {
+        map.remove("$ref");
+        if depth < MAX_REF_DEPTH {
+            for (key, value) in target {
+                map.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+        inline_refs(schema, root, depth + 1);
+        return;
+    }
+    for child in subschemas_mut(map) {
+        inline_refs(child, root, depth);
+    }
+}
+
+/// Remove keywords from a schema and everything nested in it.
+fn strip(mut schema: Value, keywords: &[&str]) -> Value {
+    if let Value::Object(map) = &mut schema {
+        map.retain(|key, _| !keywords.contains(&key.as_str()));
+        for child in subschemas_mut(map) {
+            *child = strip(child.take(), keywords);
+        }
+    }
+    schema
+}
+
+fn strict_schema(schema: Value, nullable: bool) -> Option<Value> {
+    let Value::Object(mut map) = schema else {
+        return None;
+    };
+    if map
+        .keys()
+        .any(|key| !STRICT_KEYWORDS.contains(&key.as_str()))
+    {
+        return None;
+    }
+    let kind = map.get("type")?.as_str()?.to_string();
+    match kind.as_str() {
+        "object" => {
+            if map
+                .get("additionalProperties")
+                .is_some_and(|v| v != &json!(false))
+            {
+                return None;
+            }
+            let required = required(&map);
+            let Some(Value::Object(properties)) = map.remove("properties") else {
+                return None;
+            };
+            let mut names = Vec::new();
+            let mut strict_properties = Map::new();
+            for (name, property) in properties {
+                let optional = !required.contains(&name);
+                strict_properties.insert(name.clone(), strict_schema(property, optional)?);
+                names.push(Value::String(name));
+            }
+            map.insert("properties".to_string(), Value::Object(strict_properties));
+            map.insert("required".to_string(), Value::Array(names));
+            map.insert("additionalProperties".to_string(), json!(false));
+        }
+        "array" => {
+            let items = strict_schema(map.remove("items")?, false)?;
+            map.insert("items".to_string(), items);
+        }
+        _ => {}
+    }
+    if nullable {
+        map.insert("type".to_string(), json!([kind, "null"]));
+        if let Some(Value::Array(values)) = map.get_mut("enum") {
+            values.push(Value::Null);
+        }
+    }
+    Some(Value::Object(map))
+}
+
+fn required(schema: &Map<String, Value>) -> Vec<String> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(|name| name.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Check a model's arguments against a tool's schema.
+///
+/// Nulls given for optional properties are removed, as if they had been left
+/// out. Numbers may be given as strings, since the tools accept those. Only
+/// the top-level properties are checked.
+pub(crate) fn validate(schema: &Value, input: &mut Value) -> Result<(), Vec<String>> {
+    let Value::Object(schema) = normalize(schema) else {
+        unreachable!()
+    };
+    let Value::Object(args) = input else {
+        return Err(vec![format!(
+            "arguments must be a JSON object, not {}",
+            type_name(input)
+        )]);
+    };
+    let required = required(&schema);
+    args.retain(|name, value| !value.is_null() || required.contains(name));
+
+    let mut errors = Vec::new();
+    for name in &required {
+        if args.get(name).is_none_or(Value::is_null) {
+            errors.push(format!("missing required property `{}`", name));
+        }
+    }
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let closed = schema.get("additionalProperties") == Some(&json!(false));
+    for (name, value) in args.iter() {
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(property) => {
+                if let Some(error) = check_value(property, value) {
+                    errors.push(format!("`{}` {}", name, error));
+                }
+            }
+            None if closed => errors.push(format!("unknown property `{}`", name)),
+            None => {}
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Why a value doesn't match a property's schema, if it doesn't.
+fn check_value(schema: &Value, value: &Value) -> Option<String> {
+    if value.is_null() {
+        return None;
+    }
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(kind)) => vec![kind.as_str()],
+        Some(Value::Array(kinds)) => kinds.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|kind| is_type(value, kind)) {
+        return Some(format!(
+            "must be {}, not {}",
+            types
+                .iter()
+                .map(|kind| article(kind))
+                .collect::<Vec<_>>()
+                .join(" or "),
+            type_name(value)
+        ));
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum")
+        && !allowed.contains(value)
+    {
+        let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+        return Some(format!("must be one of {}", allowed.join(", ")));
+    }
+    None
+}
+
+fn is_type(value: &Value, kind: &str) -> bool {
+    let numeric_string = || {
+        value
+            .as_str()
+            .is_some_and(|s| s.trim().parse::<f64>().is_ok())
+    };
+    match kind {
+        "string" => value.is_string(),
+        "integer" => {
+            value.as_f64().is_some_and(|n| n.fract() == 0.0)
+                || (numeric_string() && !value.as_str().unwrap_or_default().contains('.'))
+        }
+        "number" => value.is_number() || numeric_string(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn article(kind: &str) -> String {
+    match kind {
+        "integer" | "array" | "object" => format!("an {}", kind),
+        "null" => kind.to_string(),
+        _ => format!("a {}", kind),
+    }
+}
+
+/// The result returned to the model for a call whose arguments didn't validate.
+pub(crate) fn invalid_arguments(
+    tool_use_id: &str,
+    tool_name: &str,
+    errors: &[String],
+) -> ToolResult {
+    let mut message = format!("Invalid arguments for {}:\n", tool_name);
+    for error in errors {
+        message.push_str(&format!("- {}\n", error));
+    }
+    message.push_str("Call the tool again with arguments that match its schema.");
+    ToolResult::error(tool_use_id, message).with_summary("Invalid arguments")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_read_schema() -> Value {
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": {
+                "filename": {"type": "string"},
+                "offset": {"type": "integer"},
+                "mode": {"$ref": "#/definitions/mode"}
+            },
+            "required": ["filename"],
+            "additionalProperties": false,
+            "definitions": {
+                "mode": {"type": "string", "enum": ["text", "title"]}
+            }
+        })
+    }
+
+    #[test]
+    fn test_adapt_per_dialect() {
+        let schema = file_read_schema();
+
+        let anthropic = adapt(&schema, Dialect::Anthropic);
+        assert!(anthropic.get("$schema").is_none());
+        assert!(anthropic.get("definitions").is_none());
+        assert_eq!(
+            anthropic["properties"]["mode"]["enum"],
+            json!(["text", "title"])
+        );
+        assert_eq!(anthropic["additionalProperties"], json!(false));
+
+        let gemini = adapt(&schema, Dialect::Gemini);
+        assert!(gemini.get("additionalProperties").is_none());
+        assert_eq!(gemini["properties"]["mode"]["type"], "string");
+
+        let openai = strict(&schema).unwrap();
+        assert_eq!(openai["required"], json!(["filename", "mode", "offset"]));
+        assert_eq!(
+            openai["properties"]["offset"]["type"],
+            json!(["integer", "null"])
+        );
+        assert_eq!(
+            openai["properties"]["mode"]["enum"],
+            json!(["text", "title", null])
+        );
+        let bounded = json!({"type": "object", "properties": {"n": {"minimum": 0}}});
+        assert!(strict(&bounded).is_none());
+
+        assert_eq!(
+            adapt(&Value::Null, Dialect::Compat),
+            json!({"type": "object", "properties": {}})
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = file_read_schema();
+
+        let mut input = json!({"filename": "a.rs", "offset": null, "mode": "text"});
+        assert!(validate(&schema, &mut input).is_ok());
+        assert_eq!(input, json!({"filename": "a.rs", "mode": "text"}));
+
+        let mut input = json!({"filename": "a.rs", "offset": "10"});
+        assert!(validate(&schema, &mut input).is_ok());
+
+        let mut input = json!({"offset": true, "mode": "binary", "extra": 1});
+        let errors = validate(&schema, &mut input).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                "missing required property `filename`",
+                "unknown property `extra`",
+                "`mode` must be one of \"text\", \"title\"",
+                "`offset` must be an integer, not a boolean",
+            ]
+        );
+
+        let mut input = json!("a.rs");
+        assert!(validate(&schema, &mut input).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use super::{Tool, ToolDefinition, ToolResult};
+use crate::index::embeddings::{self, Hit};
+
+/// Largest number of results the model may ask for.
+const MAX_LIMIT: usize = 30;
+
+/// Lines of each result shown.
+const PREVIEW_LINES: usize = 8;
+
+pub(crate) struct SemanticSearch;
+
+#[derive(Debug, Deserialize)]
+struct SemanticSearchInput {
+    query: String,
+    #[serde(default, deserialize_with = "super::deserialize_optional_usize")]
+    limit: Option<usize>,
+}
+
+/// Each hit as `path:start-end (score)` followed by its first lines, read
+/// from the file under `root`.
+fn format_hits(hits: &[Hit], root: &Path) -> String {
+    hits.iter()
+        .map(|hit| {
+            let mut block = format!("{}:{}-{} ({:.2})", hit.path, hit.start, hit.end, hit.score);
+            if let Ok(text) = std::fs::read_to_string(root.join(&hit.path)) {
+                let lines: Vec<&str> = text
+                    .lines()
+                    .skip(hit.start - 1)
+                    .take((hit.end + 1 - hit.start).min(PREVIEW_LINES))
+                    .collect();
+                for (i, line) in lines.iter().enumerate() {
+                    block.push_str(&format!("\n{:>6}\t{}", hit.start + i, line));
+                }
+            }
+            block
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+impl Tool for SemanticSearch {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "semantic_search".to_string(),
+            description: "Find code by what it does rather than what it is called. Compares the meaning of the query with embeddings of every source and documentation file in the workspace, and returns the closest ranges of lines as `path:start-end (score)` with their first lines. Use it for questions like \"where are retries handled\" when the names involved are unknown; use codebase_search or grep when they are."
+                .to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "A description of the code to find, in plain words"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum number of results (default: 8, max: 30)"
+                    }
+                },
+                "required": ["query"]
+            }),
+        }
+    }
+
+    async fn execute(
+        &self,
+        tool_use_id: &str,
+        input: serde_json::Value,
+        output: &crate::output::OutputContext,
+        _services: &crate::services::Services,
+    ) -> ToolResult {
+        let input: SemanticSearchInput = match super::deserialize_input(tool_use_id, input) {
+            Ok(i) => i,
+            Err(e) => return *e,
+        };
+        if input.query.trim().is_empty() {
+            return ToolResult::error(tool_use_id, "The query is empty");
+        }
+        let config = crate::config::ConfigFile::load().unwrap_or_default();
+        let Some(embeddings_config) = config.embeddings else {
+            return ToolResult::error(
+                tool_use_id,
+                "Semantic search is not configured; add an [embeddings] section to the config",
+            );
+        };
+
+        let root = super::paths::workspace_root();
+        let limit = input
+            .limit
+            .unwrap_or(embeddings::DEFAULT_LIMIT)
+            .clamp(1, MAX_LIMIT);
+        let hits = match embeddings::search(&embeddings_config, &root, &input.query, limit).await {
+            Ok(hits) => hits,
+            Err(e) => return ToolResult::error(tool_use_id, format!("Search failed: {}", e)),
+        };
+
+        if hits.is_empty() {
+            return ToolResult::success(tool_use_id, "No files have been embedded.")
+                .with_summary("No matches");
+        }
+        for hit in &hits {
+            crate::output::emit_tool_output(
+                output,
+                &format!(
+                    "{}:{}-{} ({:.2})\n",
+                    hit.path, hit.start, hit.end, hit.score
+                ),
+            );
+        }
+        let summary = format!(
+            "{} {}",
+            hits.len(),
+            if hits.len() == 1 { "match" } else { "matches" }
+        );
+        ToolResult::success(tool_use_id, format_hits(&hits, &root)).with_summary(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_hits() {
+        let dir = tempfile::tempdir().unwrap();
+        let text: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(dir.path().join("a.rs"), text).unwrap();
+        let hits = vec![
+            Hit {
+                path: "a.rs".to_string(),
+                start: 11,
+                end: 12,
+                score: 0.8123,
+            },
+            Hit {
+                path: "gone.rs".to_string(),
+                start: 1,
+                end: 60,
+                score: 0.5,
+            },
+        ];
+        assert_eq!(
+            format_hits(&hits, dir.path()),
+            "a.rs:11-12 (0.81)\n    11\tline 11\n    12\tline 12\n\ngone.rs:1-60 (0.50)"
+        );
+    }
+}
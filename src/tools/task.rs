@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Delegation of self-contained tasks to a sub-agent.
+//!
+//! The sub-agent runs its own chat loop against the same provider, starting
+//! from an empty conversation, so the files it reads never enter the parent
+//! context. Only its final answer is returned as the tool result. It works
+//! in read-only mode with a small set of tools and cannot delegate further.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+use futures::future::BoxFuture;
+use serde::Deserialize;
+
+use super::{ToolDefinition, ToolResult, deserialize_input};
+use crate::chat::{ChatIterationResult, run_chat_iteration};
+use crate::output::{OutputContext, OutputEvent, OutputListener};
+use crate::provider::{ContentBlock, Message, MessageContent, Provider, Role};
+use crate::services::Services;
+
+/// Tools offered to the sub-agent.
+pub(crate) const TASK_TOOLS: &[&str] = &[
+    "bash",
+    "codebase_search",
+    "fetch",
+    "file_read",
+    "glob",
+    "semantic_search",
+];
+
+/// Maximum model calls for a single task.
+const MAX_ITERATIONS: usize = 50;
+
+const SUMMARY_INSTRUCTION: &str = "You are a sub-agent working on a task delegated by another \
+agent. You are in read-only mode. When you are done, reply with a concise summary of your \
+findings, including the file paths and line numbers that matter. This summary is the only \
+part of your work the other agent will see.";
+
+tokio::task_local! {
+    static IN_TASK: u64;
+}
+
+/// Identifier of the next task to run.
+static NEXT_TASK: AtomicU64 = AtomicU64::new(1);
+
+/// Whether the current code runs inside a delegated task.
+pub(crate) fn in_task() -> bool {
+    current_task().is_some()
+}
+
+/// Identifier of the delegated task the current code runs in, unique for the
+/// process.
+pub(crate) fn current_task() -> Option<u64> {
+    IN_TASK.try_with(|id| *id).ok()
+}
+
+pub(crate) struct Task;
+
+#[derive(Debug, Deserialize)]
+struct TaskInput {
+    description: String,
+    prompt: String,
+}
+
+impl Task {
+    pub(crate) fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "task".to_string(),
+            description: "Delegate a self-contained task to a sub-agent with its own context \
+window. The sub-agent can read files, run read-only shell commands and fetch URLs, but \
+cannot edit files. Only its final summary is returned, so use this for broad codebase \
+exploration or research that would otherwise fill the conversation with file contents. \
+The prompt must be self-contained: the sub-agent does not see this conversation."
+                .to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "description": {
+                        "type": "string",
+                        "description": "A short (3-5 word) description of the task"
+                    },
+                    "prompt": {
+                        "type": "string",
+                        "description": "The task for the sub-agent, with all the context it needs"
+                    }
+                },
+                "required": ["description", "prompt"]
+            }),
+        }
+    }
+}
+
+/// Forwards a sub-agent's tool calls and warnings to the parent output.
+struct TaskListener {
+    parent: OutputContext,
+    tool_calls: AtomicUsize,
+}
+
+impl OutputListener for TaskListener {
+    fn on_event(&self, event: &OutputEvent) {
+        match event {
+            OutputEvent::ToolCall { description } => {
+                self.tool_calls.fetch_add(1, Ordering::SeqCst);
+                self.parent
+                    .emit(OutputEvent::Info(format!("  task: {}", description)));
+            }
+            OutputEvent::Warning(message) => {
+                self.parent
+                    .emit(OutputEvent::Warning(format!("task: {}", message)));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Text of the last assistant message.
+pub(crate) fn final_answer(messages: &[Message]) -> Option<String> {
+    let message = messages.iter().rev().find(|m| m.role == Role::Assistant)?;
+    let text = match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Blocks(blocks) => blocks
+            .iter()
+            .filter_map(|b| match b {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Run a delegated task to completion and return its summary.
+///
+/// Boxed because the sub-agent's chat loop can call back into this function.
+pub(crate) fn run<'a, P: Provider>(
+    provider: &'a P,
+    tool_use_id: &'a str,
+    input: serde_json::Value,
+    interrupted: &'a Arc<AtomicBool>,
+    output: &'a OutputContext,
+    services: &'a Services,
+) -> BoxFuture<'a, ToolResult> {
+    Box::pin(async move {
+        let input: TaskInput = match deserialize_input(tool_use_id, input) {
+            Ok(input) => input,
+            Err(result) => return *result,
+        };
+
+        let config = crate::config::ConfigFile::load().unwrap_or_default();
+        if config.disabled_tools.iter().any(|t| t == "task") {
+            return ToolResult::error(tool_use_id, "Tool 'task' is disabled in configuration");
+        }
+
+        if in_task() {
+            return ToolResult::error(tool_use_id, "A task cannot delegate further tasks");
+        }
+
+        let listener = Arc::new(TaskListener {
+            parent: output.clone(),
+            tool_calls: AtomicUsize::new(0),
+        });
+        let task_output = OutputContext::new_cli(listener.clone());
+        let services = services.read_only_view();
+        let mut messages = vec![Message::user(format!(
+            "{}\n\n{}",
+            SUMMARY_INSTRUCTION, input.prompt
+        ))];
+
+        let finished = IN_TASK
+            .scope(NEXT_TASK.fetch_add(1, Ordering::SeqCst), async {
+                for _ in 0..MAX_ITERATIONS {
+                    match run_chat_iteration(
+                        provider,
+                        &mut messages,
+                        interrupted,
+                        &task_output,
+                        &services,
+                    )
+                    .await?
+                    {
+                        ChatIterationResult::Continue => {}
+                        ChatIterationResult::Done | ChatIterationResult::Truncated => {
+                            return Ok(true);
+                        }
+                    }
+                }
+                Ok::<_, crate::error::Error>(false)
+            })
+            .await;
+
+        let tool_calls = listener.tool_calls.load(Ordering::SeqCst);
+        let summary = format!(
+            "{} ({} tool call{})",
+            input.description,
+            tool_calls,
+            if tool_calls == 1 { "" } else { "s" }
+        );
+
+        match finished {
+            Ok(true) => match final_answer(&messages) {
+                Some(answer) => ToolResult::success(tool_use_id, answer).with_summary(summary),
+                None => ToolResult::error(tool_use_id, "The task finished without a summary")
+                    .with_summary(summary),
+            },
+            Ok(false) => {
+                let partial = final_answer(&messages).unwrap_or_default();
+                ToolResult::error(
+                    tool_use_id,
+                    format!(
+                        "The task did not finish within {} steps. Last response:\n{}",
+                        MAX_ITERATIONS, partial
+                    ),
+                )
+                .with_summary(summary)
+            }
+            Err(e) => {
+                ToolResult::error(tool_use_id, format!("Task failed: {}", e)).with_summary(summary)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_final_answer_uses_last_assistant_text() {
+        let messages = vec![
+            Message::user("find it"),
+            Message::assistant_blocks(vec![ContentBlock::Text {
+                text: "looking".to_string(),
+            }]),
+            Message::user("tool results"),
+            Message::assistant_blocks(vec![
+                ContentBlock::Thinking {
+                    thinking: "hmm".to_string(),
+                    provider_data: None,
+                },
+                ContentBlock::Text {
+                    text: " It is in src/main.rs:10 ".to_string(),
+                },
+            ]),
+        ];
+        assert_eq!(
+            final_answer(&messages).as_deref(),
+            Some("It is in src/main.rs:10")
+        );
+        assert_eq!(final_answer(&messages[..1]), None);
+    }
+
+    #[tokio::test]
+    async fn test_in_task_scope() {
+        assert!(!in_task());
+        IN_TASK
+            .scope(7, async { assert_eq!(current_task(), Some(7)) })
+            .await;
+    }
+}
@@ -1,17 +1,23 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Jason Ish
 
+use std::collections::BTreeMap;
+use std::sync::Mutex;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use chrono::{DateTime, TimeZone, Utc};
 use colored::Colorize;
+use reqwest::header::HeaderMap;
 use serde::Serialize;
 
+use crate::config::{CostConfig, ModelPricing};
 use crate::error::{Error, Result};
-use crate::provider::anthropic::{ANTHROPIC_BETA, ANTHROPIC_VERSION, API_URL, AnthropicClient};
+use crate::provider::anthropic::AnthropicClient;
+use crate::provider::{ContentBlock, Message, MessageContent};
+use crate::providers::ModelProvider;
 
 /// Rate limit information from Anthropic API
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub(crate) struct RateLimits {
     pub unified_5h_reset: Option<i64>,
     pub unified_5h_utilization: Option<f64>,
@@ -22,6 +28,55 @@ pub(crate) struct RateLimits {
 }
 
 impl RateLimits {
+    /// Parse the unified rate limit headers returned by the Anthropic API.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        let mut limits = RateLimits::default();
+
+        if let Some(val) = headers.get("anthropic-ratelimit-unified-5h-reset")
+            && let Ok(s) = val.to_str()
+        {
+            limits.unified_5h_reset = s.parse().ok();
+        }
+        if let Some(val) = headers.get("anthropic-ratelimit-unified-5h-utilization")
+            && let Ok(s) = val.to_str()
+        {
+            limits.unified_5h_utilization = s.parse().ok();
+        }
+        if let Some(val) = headers.get("anthropic-ratelimit-unified-7d-reset")
+            && let Ok(s) = val.to_str()
+        {
+            limits.unified_7d_reset = s.parse().ok();
+        }
+        if let Some(val) = headers.get("anthropic-ratelimit-unified-7d-utilization")
+            && let Ok(s) = val.to_str()
+        {
+            limits.unified_7d_utilization = s.parse().ok();
+        }
+        if let Some(val) = headers.get("anthropic-ratelimit-unified-7d_sonnet-reset")
+            && let Ok(s) = val.to_str()
+        {
+            limits.unified_7d_sonnet_reset = s.parse().ok();
+        }
+        if let Some(val) = headers.get("anthropic-ratelimit-unified-7d_sonnet-utilization")
+            && let Ok(s) = val.to_str()
+        {
+            limits.unified_7d_sonnet_utilization = s.parse().ok();
+        }
+
+        limits
+    }
+
+    /// Highest utilization across the 5-hour and weekly windows, with the window name.
+    pub(crate) fn max_utilization(&self) -> Option<(&'static str, f64)> {
+        [
+            ("5-hour", self.unified_5h_utilization),
+            ("weekly", self.unified_7d_utilization),
+        ]
+        .into_iter()
+        .filter_map(|(name, util)| util.map(|u| (name, u)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
     fn format_reset_time(timestamp: i64) -> String {
         let dt: DateTime<Utc> = Utc.timestamp_opt(timestamp, 0).unwrap();
         let now = Utc::now();
@@ -137,55 +192,22 @@ pub async fn fetch_anthropic_rate_limits() -> Result<RateLimits> {
         max_tokens: 5,
     };
 
+    let headers = client.request_headers(&request.model, &access_token);
     let response = client
-        .http_client()
-        .post(API_URL)
-        .header("content-type", "application/json")
-        .header("user-agent", "claude-cli/2.1.2 (external, cli)")
-        .header("anthropic-version", ANTHROPIC_VERSION)
-        .header("anthropic-beta", ANTHROPIC_BETA)
-        .header("authorization", format!("Bearer {}", access_token))
-        .json(&request)
-        .send()
+        .post(
+            &client.messages_url(),
+            &headers,
+            serde_json::to_vec(&request)?,
+        )
         .await
         .map_err(|e| Error::Auth(e.to_string()))?;
 
     // Extract headers before consuming the response
     // Rate limit headers are present even in error responses (e.g., 429 rate limit exceeded)
-    let headers = response.headers().clone();
+    let headers = response.headers.clone();
 
-    let mut limits = RateLimits::default();
-
-    if let Some(val) = headers.get("anthropic-ratelimit-unified-5h-reset")
-        && let Ok(s) = val.to_str()
-    {
-        limits.unified_5h_reset = s.parse().ok();
-    }
-    if let Some(val) = headers.get("anthropic-ratelimit-unified-5h-utilization")
-        && let Ok(s) = val.to_str()
-    {
-        limits.unified_5h_utilization = s.parse().ok();
-    }
-    if let Some(val) = headers.get("anthropic-ratelimit-unified-7d-reset")
-        && let Ok(s) = val.to_str()
-    {
-        limits.unified_7d_reset = s.parse().ok();
-    }
-    if let Some(val) = headers.get("anthropic-ratelimit-unified-7d-utilization")
-        && let Ok(s) = val.to_str()
-    {
-        limits.unified_7d_utilization = s.parse().ok();
-    }
-    if let Some(val) = headers.get("anthropic-ratelimit-unified-7d_sonnet-reset")
-        && let Ok(s) = val.to_str()
-    {
-        limits.unified_7d_sonnet_reset = s.parse().ok();
-    }
-    if let Some(val) = headers.get("anthropic-ratelimit-unified-7d_sonnet-utilization")
-        && let Ok(s) = val.to_str()
-    {
-        limits.unified_7d_sonnet_utilization = s.parse().ok();
-    }
+    let limits = RateLimits::from_headers(&headers);
+    record_anthropic_rate_limits(&limits);
 
     // Consume the response body to complete the request
     // We don't care about success/error - we only need the rate limit headers
@@ -208,6 +230,9 @@ pub(crate) struct Usage {
     turn_total_tokens: AtomicU64,
     turn_cache_creation_tokens: AtomicU64,
     turn_cache_read_tokens: AtomicU64,
+    // Requests that read a cached prompt prefix, and requests that had to write one
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
 }
 
 impl Usage {
@@ -259,13 +284,143 @@ impl Usage {
             .fetch_add(tokens, Ordering::Relaxed);
     }
 
+    /// Record the prompt cache usage of a request. A request that reads from
+    /// the cache is a hit; one that only writes to it is a miss.
+    pub(crate) fn record_cache(&self, creation: u64, read: u64) {
+        self.add_cache_creation(creation);
+        self.add_cache_read(read);
+        if read > 0 {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+        } else if creation > 0 {
+            self.cache_misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Prompt cache hits and misses since startup.
+    pub(crate) fn cache_stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.cache_hits.load(Ordering::Relaxed),
+            misses: self.cache_misses.load(Ordering::Relaxed),
+            tokens: self.counts(),
+        }
+    }
+
     pub(crate) fn last_input(&self) -> u64 {
         self.last_input_tokens.load(Ordering::Relaxed)
     }
 
+    /// Total (input, output) tokens recorded since startup.
+    pub(crate) fn totals(&self) -> (u64, u64) {
+        (
+            self.total_input_tokens.load(Ordering::Relaxed),
+            self.total_output_tokens.load(Ordering::Relaxed),
+        )
+    }
+
     pub(crate) fn turn_total(&self) -> u64 {
         self.turn_total_tokens.load(Ordering::Relaxed)
     }
+
+    /// Tokens of each kind reported for the most recent request.
+    pub(crate) fn last(&self) -> TokenCounts {
+        TokenCounts {
+            input: self.last_input_tokens.load(Ordering::Relaxed),
+            output: self.last_output_tokens.load(Ordering::Relaxed),
+            cache_creation: self.last_cache_creation_tokens.load(Ordering::Relaxed),
+            cache_read: self.last_cache_read_tokens.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Total tokens of each kind recorded since startup.
+    pub(crate) fn counts(&self) -> TokenCounts {
+        TokenCounts {
+            input: self.total_input_tokens.load(Ordering::Relaxed),
+            output: self.total_output_tokens.load(Ordering::Relaxed),
+            cache_creation: self.total_cache_creation_tokens.load(Ordering::Relaxed),
+            cache_read: self.total_cache_read_tokens.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Prompt cache effectiveness for an Anthropic-style API, where cache reads
+/// and writes are reported apart from the uncached input.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub tokens: TokenCounts,
+}
+
+impl CacheStats {
+    /// Fraction of prompt tokens read from the cache.
+    pub(crate) fn read_ratio(&self) -> f64 {
+        let prompt = self.tokens.input + self.tokens.cache_creation + self.tokens.cache_read;
+        if prompt == 0 {
+            0.0
+        } else {
+            self.tokens.cache_read as f64 / prompt as f64
+        }
+    }
+
+    pub(crate) fn format(&self) -> String {
+        format!(
+            "{} hits, {} misses; {:.0}% of prompt tokens read from cache ({} read, {} written)",
+            self.hits,
+            self.misses,
+            self.read_ratio() * 100.0,
+            self.tokens.cache_read,
+            self.tokens.cache_creation,
+        )
+    }
+}
+
+/// Token counts by kind, as recorded by a provider.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct TokenCounts {
+    pub input: u64,
+    pub output: u64,
+    pub cache_creation: u64,
+    pub cache_read: u64,
+}
+
+impl TokenCounts {
+    /// Tokens recorded between `earlier` and `self`.
+    pub(crate) fn since(&self, earlier: &TokenCounts) -> TokenCounts {
+        TokenCounts {
+            input: self.input.saturating_sub(earlier.input),
+            output: self.output.saturating_sub(earlier.output),
+            cache_creation: self.cache_creation.saturating_sub(earlier.cache_creation),
+            cache_read: self.cache_read.saturating_sub(earlier.cache_read),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        *self == TokenCounts::default()
+    }
+
+    fn add(&mut self, other: &TokenCounts) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_creation += other.cache_creation;
+        self.cache_read += other.cache_read;
+    }
+}
+
+static ANTHROPIC_RATE_LIMITS: std::sync::Mutex<Option<RateLimits>> = std::sync::Mutex::new(None);
+
+/// Remember the most recent rate limits seen on an Anthropic response.
+pub(crate) fn record_anthropic_rate_limits(limits: &RateLimits) {
+    if limits.max_utilization().is_none() {
+        return;
+    }
+    if let Ok(mut latest) = ANTHROPIC_RATE_LIMITS.lock() {
+        *latest = Some(limits.clone());
+    }
+}
+
+/// The most recent rate limits seen on an Anthropic response, if any.
+pub(crate) fn latest_anthropic_rate_limits() -> Option<RateLimits> {
+    ANTHROPIC_RATE_LIMITS.lock().ok().and_then(|l| l.clone())
 }
 
 static ANTHROPIC_USAGE: std::sync::OnceLock<Usage> = std::sync::OnceLock::new();
@@ -304,6 +459,24 @@ pub(crate) fn antigravity() -> &'static Usage {
     ANTIGRAVITY_USAGE.get_or_init(Usage::default)
 }
 
+static OLLAMA_USAGE: std::sync::OnceLock<Usage> = std::sync::OnceLock::new();
+
+pub(crate) fn ollama() -> &'static Usage {
+    OLLAMA_USAGE.get_or_init(Usage::default)
+}
+
+static VERTEX_USAGE: std::sync::OnceLock<Usage> = std::sync::OnceLock::new();
+
+pub(crate) fn vertex() -> &'static Usage {
+    VERTEX_USAGE.get_or_init(Usage::default)
+}
+
+static MISTRAL_USAGE: std::sync::OnceLock<Usage> = std::sync::OnceLock::new();
+
+pub(crate) fn mistral() -> &'static Usage {
+    MISTRAL_USAGE.get_or_init(Usage::default)
+}
+
 pub(crate) fn reset_last_context_usage() {
     anthropic().reset_last_usage();
     zen().reset_last_usage();
@@ -311,4 +484,413 @@ pub(crate) fn reset_last_context_usage() {
     openai().reset_last_usage();
     openrouter().reset_last_usage();
     antigravity().reset_last_usage();
+    ollama().reset_last_usage();
+    vertex().reset_last_usage();
+    mistral().reset_last_usage();
+}
+
+/// Price of a model in US dollars per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Pricing {
+    input: f64,
+    output: f64,
+    cache_write: f64,
+    cache_read: f64,
+}
+
+impl Pricing {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    /// Cost of `tokens`. Some APIs count cache reads as part of the input
+    /// tokens (`input_includes_cached`), others report them separately.
+    fn cost(&self, tokens: &TokenCounts, input_includes_cached: bool) -> f64 {
+        let input = if input_includes_cached {
+            tokens.input.saturating_sub(tokens.cache_read)
+        } else {
+            tokens.input
+        };
+        (input as f64 * self.input
+            + tokens.output as f64 * self.output
+            + tokens.cache_creation as f64 * self.cache_write
+            + tokens.cache_read as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+impl From<&ModelPricing> for Pricing {
+    fn from(p: &ModelPricing) -> Self {
+        Self::new(
+            p.input,
+            p.output,
+            p.cache_write.unwrap_or(p.input),
+            p.cache_read.unwrap_or(p.input),
+        )
+    }
+}
+
+/// Published API prices by model ID prefix, most specific first. IDs are
+/// matched after dropping any vendor prefix (`anthropic/`) and turning dots
+/// into dashes, so `claude-sonnet-4.5` matches `claude-sonnet-4-5`.
+const PRICING: &[(&str, Pricing)] = &[
+    ("claude-opus-4-6", Pricing::new(5.0, 25.0, 6.25, 0.5)),
+    ("claude-opus-4-5", Pricing::new(5.0, 25.0, 6.25, 0.5)),
+    ("claude-opus-4", Pricing::new(15.0, 75.0, 18.75, 1.5)),
+    ("claude-sonnet-4", Pricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-7-sonnet", Pricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-haiku-4", Pricing::new(1.0, 5.0, 1.25, 0.1)),
+    ("claude-3-5-haiku", Pricing::new(0.8, 4.0, 1.0, 0.08)),
+    ("gpt-5-2", Pricing::new(1.75, 14.0, 1.75, 0.175)),
+    ("gpt-5-mini", Pricing::new(0.25, 2.0, 0.25, 0.025)),
+    ("gpt-5-nano", Pricing::new(0.05, 0.4, 0.05, 0.005)),
+    ("gpt-5", Pricing::new(1.25, 10.0, 1.25, 0.125)),
+    ("gpt-4-1-mini", Pricing::new(0.4, 1.6, 0.4, 0.1)),
+    ("gpt-4-1-nano", Pricing::new(0.1, 0.4, 0.1, 0.025)),
+    ("gpt-4-1", Pricing::new(2.0, 8.0, 2.0, 0.5)),
+    ("gpt-4o-mini", Pricing::new(0.15, 0.6, 0.15, 0.075)),
+    ("gpt-4o", Pricing::new(2.5, 10.0, 2.5, 1.25)),
+    ("o4-mini", Pricing::new(1.1, 4.4, 1.1, 0.275)),
+    ("o3", Pricing::new(2.0, 8.0, 2.0, 0.5)),
+    ("gemini-3-pro", Pricing::new(2.0, 12.0, 2.0, 0.2)),
+    ("gemini-3-flash", Pricing::new(0.5, 3.0, 0.5, 0.05)),
+    ("gemini-2-5-pro", Pricing::new(1.25, 10.0, 1.25, 0.125)),
+    ("gemini-2-5-flash", Pricing::new(0.3, 2.5, 0.3, 0.03)),
+];
+
+fn builtin_pricing(model: &str) -> Option<Pricing> {
+    let model = model.split('#').next().unwrap_or(model);
+    let model = model.rsplit('/').next().unwrap_or(model);
+    let model = model.to_lowercase().replace('.', "-");
+    PRICING
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, pricing)| *pricing)
+}
+
+/// Dollar cost of `tokens` used with `model`, or None when the price is
+/// unknown. `label` is the `provider/model` name shown to the user.
+///
+/// Prices from the `[cost.pricing]` config come first. Ollama and the
+/// subscription providers (Claude, OpenAI Codex, Copilot, Antigravity) are
+/// free unless priced there.
+pub(crate) fn price(
+    provider: ModelProvider,
+    label: &str,
+    model: &str,
+    tokens: &TokenCounts,
+    config: &CostConfig,
+) -> Option<f64> {
+    let pricing = match config
+        .pricing
+        .get(label)
+        .or_else(|| config.pricing.get(model))
+    {
+        Some(pricing) => Pricing::from(pricing),
+        None if matches!(
+            provider,
+            ModelProvider::Ollama
+                | ModelProvider::Claude
+                | ModelProvider::OpenAi
+                | ModelProvider::GitHubCopilot
+                | ModelProvider::Antigravity
+        ) =>
+        {
+            return Some(0.0);
+        }
+        None => builtin_pricing(model)?,
+    };
+    Some(pricing.cost(tokens, input_includes_cached(provider, model)))
+}
+
+/// Whether the API counts cache reads as part of the input tokens. Anthropic-style
+/// APIs report them separately.
+fn input_includes_cached(provider: ModelProvider, model: &str) -> bool {
+    !(provider == ModelProvider::Claude
+        || (matches!(provider, ModelProvider::OpenCodeZen | ModelProvider::Vertex)
+            && model.starts_with("claude")))
+}
+
+/// Prompt tokens of a request, cached or not.
+pub(crate) fn prompt_tokens(provider: ModelProvider, model: &str, tokens: &TokenCounts) -> u64 {
+    if input_includes_cached(provider, model) {
+        tokens.input
+    } else {
+        tokens.input + tokens.cache_read + tokens.cache_creation
+    }
+}
+
+/// Tokens counted for an image; roughly what providers charge for a
+/// screenshot-sized image.
+const IMAGE_TOKENS: u64 = 1_500;
+
+/// Rough token count of `text`, at about four characters per token.
+pub(crate) fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+/// Rough token count of a message as sent to the model.
+pub(crate) fn estimate_message_tokens(message: &Message) -> u64 {
+    let blocks = match &message.content {
+        MessageContent::Text(text) => return estimate_tokens(text),
+        MessageContent::Blocks(blocks) => blocks,
+    };
+    blocks
+        .iter()
+        .map(|block| match block {
+            ContentBlock::Text { text } => estimate_tokens(text),
+            ContentBlock::Image { .. } => IMAGE_TOKENS,
+            ContentBlock::Thinking { thinking, .. } => estimate_tokens(thinking),
+            ContentBlock::ToolUse { name, input, .. } => {
+                estimate_tokens(name) + estimate_tokens(&input.to_string())
+            }
+            ContentBlock::ToolResult { content, data, .. } => {
+                estimate_tokens(content) + if data.is_some() { IMAGE_TOKENS } else { 0 }
+            }
+            ContentBlock::Summary { summary, .. } => estimate_tokens(summary),
+        })
+        .sum()
+}
+
+/// Tokens and dollar cost of one model in this session.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ModelCost {
+    pub tokens: TokenCounts,
+    /// None when some of the tokens could not be priced
+    pub cost: Option<f64>,
+}
+
+#[derive(Default)]
+struct SessionCost {
+    total: f64,
+    turn: f64,
+    models: BTreeMap<String, ModelCost>,
+    limit_warned: bool,
+}
+
+static SESSION_COST: Mutex<Option<SessionCost>> = Mutex::new(None);
+
+fn with_session_cost<T>(f: impl FnOnce(&mut SessionCost) -> T) -> T {
+    let mut guard = SESSION_COST.lock().unwrap();
+    f(guard.get_or_insert_with(SessionCost::default))
+}
+
+/// Reset the turn cost. Call this at the start of each user interaction.
+pub(crate) fn start_cost_turn() {
+    with_session_cost(|c| c.turn = 0.0);
+}
+
+/// Add the tokens used with `label` (`provider/model`) and their cost.
+pub(crate) fn record_cost(label: &str, tokens: &TokenCounts, cost: Option<f64>) {
+    with_session_cost(|c| {
+        let entry = c
+            .models
+            .entry(label.to_string())
+            .or_insert_with(|| ModelCost {
+                cost: Some(0.0),
+                ..Default::default()
+            });
+        entry.tokens.add(tokens);
+        entry.cost = entry.cost.zip(cost).map(|(a, b)| a + b);
+        if let Some(cost) = cost {
+            c.total += cost;
+            c.turn += cost;
+        }
+    });
+}
+
+/// Dollar cost of this session so far.
+pub(crate) fn session_cost() -> f64 {
+    with_session_cost(|c| c.total)
+}
+
+/// Dollar cost of the current (or last) turn.
+pub(crate) fn turn_cost() -> f64 {
+    with_session_cost(|c| c.turn)
+}
+
+/// Tokens and cost by `provider/model` for this session.
+pub(crate) fn session_cost_by_model() -> Vec<(String, ModelCost)> {
+    with_session_cost(|c| c.models.clone().into_iter().collect())
+}
+
+/// Start a new session with no cost.
+pub(crate) fn reset_session_cost() {
+    *SESSION_COST.lock().unwrap() = None;
+}
+
+/// Continue a resumed session from the cost saved with it.
+pub(crate) fn restore_session_cost(total: f64) {
+    reset_session_cost();
+    with_session_cost(|c| c.total = total);
+}
+
+/// Message for when the session cost has reached `config.max_session_cost`.
+pub(crate) fn cost_limit_reached(config: &CostConfig) -> Option<String> {
+    let limit = config.max_session_cost?;
+    let cost = session_cost();
+    (cost >= limit).then(|| {
+        format!(
+            "Session cost {} has reached the limit of {}",
+            format_cost(cost),
+            format_cost(limit)
+        )
+    })
+}
+
+/// Whether the cost limit warning should be shown; true only once per session.
+pub(crate) fn take_cost_limit_warning() -> bool {
+    with_session_cost(|c| !std::mem::replace(&mut c.limit_warned, true))
+}
+
+/// Format a dollar amount, with more precision for small amounts.
+pub(crate) fn format_cost(cost: f64) -> String {
+    if cost >= 1.0 || cost == 0.0 {
+        format!("${:.2}", cost)
+    } else {
+        format!("${:.4}", cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(input: u64, output: u64, cache_creation: u64, cache_read: u64) -> TokenCounts {
+        TokenCounts {
+            input,
+            output,
+            cache_creation,
+            cache_read,
+        }
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let usage = Usage::default();
+        usage.record_input(1000);
+        usage.record_cache(3000, 0);
+        usage.record_input(1000);
+        usage.record_cache(0, 4000);
+        usage.record_input(1000);
+        usage.record_cache(0, 0);
+
+        let stats = usage.cache_stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        assert_eq!(usage.last().cache_read, 0);
+        assert_eq!(stats.read_ratio(), 0.4);
+        assert!(
+            stats
+                .format()
+                .starts_with("1 hits, 1 misses; 40% of prompt tokens")
+        );
+    }
+
+    #[test]
+    fn test_estimate_message_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("hello"), 2);
+        assert_eq!(estimate_message_tokens(&Message::user("12345678")), 2);
+
+        let message = Message::assistant_blocks(vec![
+            ContentBlock::Text {
+                text: "abcd".to_string(),
+            },
+            ContentBlock::Image {
+                mime_type: "image/png".to_string(),
+                data: Vec::new(),
+            },
+        ]);
+        assert_eq!(estimate_message_tokens(&message), 1 + IMAGE_TOKENS);
+    }
+
+    #[test]
+    fn test_builtin_pricing() {
+        let sonnet = Pricing::new(3.0, 15.0, 3.75, 0.3);
+        assert_eq!(builtin_pricing("claude-sonnet-4-5-20250929"), Some(sonnet));
+        assert_eq!(builtin_pricing("anthropic/claude-sonnet-4.5"), Some(sonnet));
+        assert_eq!(
+            builtin_pricing("gpt-5-mini#high"),
+            Some(Pricing::new(0.25, 2.0, 0.25, 0.025))
+        );
+        assert_eq!(builtin_pricing("llama3.2"), None);
+    }
+
+    #[test]
+    fn test_price() {
+        let mut config = CostConfig::default();
+        let usage = tokens(1_000_000, 100_000, 0, 500_000);
+
+        // Anthropic reports cache reads apart from input; OpenAI includes them.
+        let claude = price(
+            ModelProvider::Vertex,
+            "vertex/claude-sonnet-4-5",
+            "claude-sonnet-4-5",
+            &usage,
+            &config,
+        );
+        assert!((claude.unwrap() - 4.65).abs() < 1e-9);
+        let openai = price(
+            ModelProvider::OpenRouter,
+            "openrouter/openai/gpt-5",
+            "openai/gpt-5",
+            &usage,
+            &config,
+        );
+        assert!((openai.unwrap() - 1.6875).abs() < 1e-9);
+
+        assert_eq!(
+            price(
+                ModelProvider::Ollama,
+                "local/qwen3",
+                "qwen3",
+                &usage,
+                &config
+            ),
+            Some(0.0)
+        );
+        assert_eq!(
+            price(
+                ModelProvider::Claude,
+                "claude/claude-sonnet-4-5",
+                "claude-sonnet-4-5",
+                &usage,
+                &config
+            ),
+            Some(0.0)
+        );
+        assert_eq!(
+            price(
+                ModelProvider::OpenAiCompat,
+                "local/qwen3",
+                "qwen3",
+                &usage,
+                &config
+            ),
+            None
+        );
+
+        config.pricing.insert(
+            "local/qwen3".to_string(),
+            ModelPricing {
+                input: 1.0,
+                output: 2.0,
+                cache_write: None,
+                cache_read: None,
+            },
+        );
+        let priced = price(
+            ModelProvider::OpenAiCompat,
+            "local/qwen3",
+            "qwen3",
+            &usage,
+            &config,
+        );
+        assert!((priced.unwrap() - 1.2).abs() < 1e-9);
+    }
 }
@@ -3,5 +3,40 @@
 
 //! Version information for the Henri application.
 //! This module provides compile-time version constants.
+
+use std::sync::LazyLock;
+
 /// The version string from Cargo.toml (e.g., "0.3.0")
 pub(crate) const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Optional cargo features and whether this binary was built with them.
+const FEATURES: &[(&str, bool)] = &[
+    ("highlight", cfg!(feature = "highlight")),
+    ("images", cfg!(feature = "images")),
+    ("plugins", cfg!(feature = "plugins")),
+];
+
+/// The version followed by the enabled and disabled features, shown by `--version`.
+pub(crate) static LONG_VERSION: LazyLock<String> =
+    LazyLock::new(|| format!("{} ({})", VERSION, feature_list(FEATURES)));
+
+fn feature_list(features: &[(&str, bool)]) -> String {
+    features
+        .iter()
+        .map(|(name, enabled)| format!("{}{}", if *enabled { '+' } else { '-' }, name))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_list() {
+        assert_eq!(
+            feature_list(&[("highlight", true), ("images", false)]),
+            "+highlight -images"
+        );
+    }
+}
@@ -0,0 +1,432 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Walking the working directory with the user's ignore rules.
+//!
+//! Everything that lists files (the project structure in the system prompt,
+//! `@` completion) goes through here, so ignored files are left out the same
+//! way everywhere. A path is ignored when it matches `.gitignore`, `.ignore`
+//! or `.henriignore` (in that order of precedence, lowest first), the global
+//! git excludes, or is hidden.
+//!
+//! [`find`] searches a [`FileIndex`] of the whole tree instead of walking it
+//! each time. The index is built with a parallel walk and kept for the life of
+//! the process; before each use, directories whose modification time (or
+//! whose ignore files' modification time) changed are walked again. To measure
+//! it on a synthetic tree:
+//!
+//! ```text
+//! HENRI_BENCH_FILES=200000 cargo test --release bench_file_index -- --ignored --nocapture
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, mpsc};
+use std::time::SystemTime;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{Walk, WalkBuilder, WalkState};
+
+/// Henri's own ignore file, using `.gitignore` syntax.
+pub(crate) const IGNORE_FILE: &str = ".henriignore";
+
+/// Options for [`walk`].
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct WalkOptions {
+    /// Include files matched by ignore files.
+    pub include_ignored: bool,
+    /// Include hidden files and directories.
+    pub include_hidden: bool,
+    /// How deep to descend below the root, where its entries are depth 1.
+    pub max_depth: Option<usize>,
+}
+
+/// Walk `root`, yielding the root itself first, then its entries depth first.
+pub(crate) fn walk(root: &Path, options: WalkOptions) -> Walk {
+    builder(root, options).build()
+}
+
+fn builder(root: &Path, options: WalkOptions) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .standard_filters(!options.include_ignored)
+        .hidden(!options.include_hidden)
+        .require_git(false)
+        .max_depth(options.max_depth);
+    if !options.include_ignored {
+        builder.add_custom_ignore_filename(IGNORE_FILE);
+    }
+    builder
+}
+
+/// Matcher for the `.ignore` and `.henriignore` files at `root`.
+///
+/// Useful for filtering a file list that already honors `.gitignore`, such
+/// as the output of `git ls-files`.
+pub(crate) fn ignore_files(root: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    for name in [".ignore", IGNORE_FILE] {
+        let path = root.join(name);
+        if path.is_file() {
+            builder.add(path);
+        }
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// Whether `path`, relative to the root of `matcher`, or any of its parent
+/// directories is ignored.
+pub(crate) fn is_ignored(matcher: &Gitignore, path: &str) -> bool {
+    matcher
+        .matched_path_or_any_parents(path, path.ends_with('/'))
+        .is_ignore()
+}
+
+/// Most entries indexed under one root, so starting in a huge directory
+/// such as `$HOME` can't use unbounded time and memory.
+const MAX_INDEX_ENTRIES: usize = 500_000;
+
+/// Files whose changes invalidate a directory's cached listing.
+const STAMP_FILES: &[&str] = &[".gitignore", ".ignore", IGNORE_FILE];
+
+/// Indexes by root, kept between calls.
+static INDEXES: Mutex<Option<HashMap<PathBuf, FileIndex>>> = Mutex::new(None);
+
+/// Modification times of a directory and its ignore files.
+type Stamp = [Option<SystemTime>; 4];
+
+fn stamp(dir: &Path) -> Stamp {
+    let mtime = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    [
+        mtime(dir),
+        mtime(&dir.join(STAMP_FILES[0])),
+        mtime(&dir.join(STAMP_FILES[1])),
+        mtime(&dir.join(STAMP_FILES[2])),
+    ]
+}
+
+/// An entry of a cached directory listing.
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    is_dir: bool,
+}
+
+#[derive(Debug)]
+struct Listing {
+    stamp: Stamp,
+    entries: Vec<Entry>,
+}
+
+/// Every directory under a root that [`walk`] would visit with the default
+/// options, with its entries.
+#[derive(Debug, Default)]
+pub(crate) struct FileIndex {
+    dirs: HashMap<PathBuf, Listing>,
+}
+
+impl FileIndex {
+    /// Walk `root` in parallel and index it.
+    fn build(root: &Path) -> Self {
+        let mut index = Self::default();
+        index.add_tree(root);
+        index
+    }
+
+    /// Walk `dir` in parallel and add it and everything below it.
+    fn add_tree(&mut self, dir: &Path) {
+        let (tx, rx) = mpsc::channel::<(PathBuf, bool)>();
+        let count = AtomicUsize::new(self.len());
+        builder(dir, WalkOptions::default())
+            .build_parallel()
+            .run(|| {
+                let tx = tx.clone();
+                let count = &count;
+                Box::new(move |entry| {
+                    if count.fetch_add(1, Ordering::Relaxed) >= MAX_INDEX_ENTRIES {
+                        return WalkState::Quit;
+                    }
+                    if let Ok(entry) = entry {
+                        let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                        let _ = tx.send((entry.into_path(), is_dir));
+                    }
+                    WalkState::Continue
+                })
+            });
+        drop(tx);
+
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        let mut entries: HashMap<PathBuf, Vec<Entry>> = HashMap::new();
+        for (path, is_dir) in rx {
+            if is_dir {
+                dirs.push(path.clone());
+            }
+            if path == dir {
+                continue;
+            }
+            if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+                entries
+                    .entry(parent.to_path_buf())
+                    .or_default()
+                    .push(Entry {
+                        name: name.to_string_lossy().into_owned(),
+                        is_dir,
+                    });
+            }
+        }
+
+        let stamps = stamps(&dirs);
+        for (dir, stamp) in dirs.into_iter().zip(stamps) {
+            let entries = entries.remove(&dir).unwrap_or_default();
+            self.dirs.insert(dir, Listing { stamp, entries });
+        }
+    }
+
+    /// Number of indexed entries.
+    fn len(&self) -> usize {
+        self.dirs.values().map(|l| l.entries.len()).sum()
+    }
+
+    /// Walk again the directories that changed since they were indexed.
+    fn refresh(&mut self) {
+        let dirs: Vec<PathBuf> = self.dirs.keys().cloned().collect();
+        let stamps = stamps(&dirs);
+        let mut stale: Vec<PathBuf> = dirs
+            .into_iter()
+            .zip(stamps)
+            .filter(|(dir, stamp)| self.dirs.get(dir).is_some_and(|l| l.stamp != *stamp))
+            .map(|(dir, _)| dir)
+            .collect();
+        if stale.is_empty() {
+            return;
+        }
+
+        // Walking a directory again covers everything below it.
+        stale.sort();
+        stale.dedup_by(|dir, parent| dir.starts_with(parent));
+        for dir in stale {
+            self.dirs.retain(|path, _| !path.starts_with(&dir));
+            if dir.is_dir() {
+                self.add_tree(&dir);
+            }
+        }
+    }
+
+    /// Paths under `root` whose file name starts with `prefix`, relative to
+    /// `root`, shortest first. Directories end with `/`.
+    fn find(&self, root: &Path, prefix: &str, limit: usize) -> Vec<String> {
+        let mut found: Vec<String> = self
+            .dirs
+            .iter()
+            .flat_map(|(dir, listing)| listing.entries.iter().map(move |e| (dir, e)))
+            .filter(|(_, entry)| entry.name.starts_with(prefix))
+            .filter_map(|(dir, entry)| {
+                let path = dir.join(&entry.name);
+                let relative = path.strip_prefix(root).ok()?.to_str()?.to_string();
+                Some(if entry.is_dir {
+                    format!("{}/", relative)
+                } else {
+                    relative
+                })
+            })
+            .collect();
+        found.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        found.truncate(limit);
+        found
+    }
+
+    /// Every indexed file.
+    fn files(&self) -> Vec<PathBuf> {
+        self.dirs
+            .iter()
+            .flat_map(|(dir, listing)| {
+                listing
+                    .entries
+                    .iter()
+                    .filter(|entry| !entry.is_dir)
+                    .map(move |entry| dir.join(&entry.name))
+            })
+            .collect()
+    }
+}
+
+/// Stamp `dirs` using a few threads, in order.
+fn stamps(dirs: &[PathBuf]) -> Vec<Stamp> {
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let chunk = dirs.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = dirs
+            .chunks(chunk)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|d| stamp(d)).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Run `f` on the index of `root`, building it or refreshing it first.
+fn with_index<T>(root: &Path, f: impl FnOnce(&FileIndex) -> T) -> Option<T> {
+    let mut indexes = INDEXES.lock().ok()?;
+    let indexes = indexes.get_or_insert_with(HashMap::new);
+    let index = indexes
+        .entry(root.to_path_buf())
+        .and_modify(FileIndex::refresh)
+        .or_insert_with(|| FileIndex::build(root));
+    Some(f(index))
+}
+
+/// Build the index of `root` in the background, so the first [`find`] is fast.
+pub(crate) fn warm(root: &Path) {
+    let root = root.to_path_buf();
+    std::thread::spawn(move || with_index(&root, |_| ()));
+}
+
+/// Files and directories under `root` whose name starts with `prefix`,
+/// relative to `root` and shortest first, from the cached index of `root`.
+pub(crate) fn find(root: &Path, prefix: &str, limit: usize) -> Vec<String> {
+    with_index(root, |index| index.find(root, prefix, limit)).unwrap_or_default()
+}
+
+/// Every file under `root` that isn't ignored, from the cached index of `root`.
+pub(crate) fn files(root: &Path) -> Vec<PathBuf> {
+    with_index(root, FileIndex::files).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(root: &Path, options: WalkOptions) -> Vec<String> {
+        let mut names: Vec<String> = walk(root, options)
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let relative = entry.path().strip_prefix(root).ok()?;
+                let name = relative.to_str()?.to_string();
+                (!name.is_empty()).then_some(name)
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("node_modules/pkg/index.js"), "").unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        std::fs::write(root.join("secrets.env"), "").unwrap();
+        std::fs::write(root.join(".hidden"), "").unwrap();
+        std::fs::write(root.join(".gitignore"), "node_modules/\n").unwrap();
+        std::fs::write(root.join(IGNORE_FILE), "*.env\n").unwrap();
+
+        assert_eq!(
+            names(root, WalkOptions::default()),
+            vec!["src", "src/main.rs"]
+        );
+        assert_eq!(
+            names(
+                root,
+                WalkOptions {
+                    max_depth: Some(1),
+                    ..Default::default()
+                }
+            ),
+            vec!["src"]
+        );
+
+        let all = names(
+            root,
+            WalkOptions {
+                include_ignored: true,
+                include_hidden: true,
+                max_depth: None,
+            },
+        );
+        assert!(all.contains(&"node_modules/pkg/index.js".to_string()));
+        assert!(all.contains(&"secrets.env".to_string()));
+        assert!(all.contains(&".hidden".to_string()));
+    }
+
+    #[test]
+    fn test_file_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/parser")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join("src/parser/mod.rs"), "").unwrap();
+        std::fs::write(root.join("src/parse.rs"), "").unwrap();
+        std::fs::write(root.join("target/parse.o"), "").unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+
+        let mut index = FileIndex::build(root);
+        assert_eq!(
+            index.find(root, "parse", 10),
+            vec!["src/parser/", "src/parse.rs"]
+        );
+
+        // A new file changes the directory's mtime, which is all a refresh checks.
+        std::fs::write(root.join("src/parser/parse_expr.rs"), "").unwrap();
+        let listing = index.dirs.get_mut(&root.join("src/parser")).unwrap();
+        listing.stamp[0] = None;
+        index.refresh();
+        assert_eq!(
+            index.find(root, "parse", 10),
+            vec!["src/parser/", "src/parse.rs", "src/parser/parse_expr.rs"]
+        );
+
+        std::fs::remove_dir_all(root.join("src/parser")).unwrap();
+        index.refresh();
+        assert_eq!(index.find(root, "parse", 10), vec!["src/parse.rs"]);
+    }
+
+    /// Times building and refreshing the index of a synthetic tree. Run with
+    /// `cargo test --release bench_file_index -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_file_index() {
+        let files: usize = std::env::var("HENRI_BENCH_FILES")
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(100_000);
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for i in 0..files {
+            let sub = root.join(format!("pkg{}/src/mod{}", i % 100, (i / 100) % 50));
+            if i < 5_000 {
+                std::fs::create_dir_all(&sub).unwrap();
+            }
+            std::fs::write(sub.join(format!("file{}.rs", i)), "").unwrap();
+        }
+
+        let start = std::time::Instant::now();
+        let mut index = FileIndex::build(root);
+        println!("build {} files: {:?}", files, start.elapsed());
+
+        let start = std::time::Instant::now();
+        index.refresh();
+        println!("refresh: {:?}", start.elapsed());
+
+        let start = std::time::Instant::now();
+        let found = index.find(root, "file12", 50);
+        println!("find ({} results): {:?}", found.len(), start.elapsed());
+    }
+
+    #[test]
+    fn test_ignore_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".ignore"), "fixtures/\n").unwrap();
+        std::fs::write(dir.path().join(IGNORE_FILE), "*.snap\n").unwrap();
+        let matcher = ignore_files(dir.path());
+        assert!(is_ignored(&matcher, "fixtures/big.json"));
+        assert!(is_ignored(&matcher, "tests/output.snap"));
+        assert!(!is_ignored(&matcher, "src/main.rs"));
+    }
+}
@@ -0,0 +1,355 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2026 Jason Ish
+
+//! Detection of runaway agent loops.
+//!
+//! The watchdog looks at each tool iteration of a turn and trips when the model
+//! appears stuck: the same tool called with identical arguments several times
+//! without any file changing in between, edits that undo earlier edits, or a
+//! long run of iterations without changing any file. When it trips, the chat
+//! loop pauses and asks the user whether to keep going.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use crate::config::LoopWatchdogConfig;
+use crate::output;
+use crate::provider::{ContentBlock, Message, MessageContent};
+
+/// Tracks tool calls across the iterations of a single turn.
+pub(crate) struct LoopWatchdog {
+    config: LoopWatchdogConfig,
+    /// Number of times each (tool, arguments) pair has been called since a
+    /// file was last changed
+    calls: HashMap<String, usize>,
+    /// Successful edits as (path, old, new), used to spot reverts
+    edits: Vec<(String, String, String)>,
+    /// Hashes of content written to each path, oldest first
+    writes: HashMap<String, Vec<u64>>,
+    reverts: usize,
+    /// Iterations since a file was last changed
+    idle_iterations: usize,
+}
+
+impl LoopWatchdog {
+    pub(crate) fn new(config: LoopWatchdogConfig) -> Self {
+        Self {
+            config,
+            calls: HashMap::new(),
+            edits: Vec::new(),
+            writes: HashMap::new(),
+            reverts: 0,
+            idle_iterations: 0,
+        }
+    }
+
+    /// Forget what has been seen so far, e.g. after the user chose to continue.
+    pub(crate) fn reset(&mut self) {
+        self.calls.clear();
+        self.edits.clear();
+        self.writes.clear();
+        self.reverts = 0;
+        self.idle_iterations = 0;
+    }
+
+    /// Record a tool iteration: the assistant message with its tool calls and the
+    /// message holding their results. Returns a reason if the agent looks stuck.
+    pub(crate) fn observe(&mut self, assistant: &Message, results: &Message) -> Option<String> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let failed: HashSet<&str> = blocks(results)
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    is_error: true,
+                    ..
+                } => Some(tool_use_id.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        let mut reason = None;
+        let mut changed_file = false;
+
+        for block in blocks(assistant) {
+            let ContentBlock::ToolUse {
+                id, name, input, ..
+            } = block
+            else {
+                continue;
+            };
+
+            let key = format!("{}:{}", name, input);
+            let count = self.calls.entry(key).or_insert(0);
+            *count += 1;
+            if self.config.repeat_limit > 0 && *count == self.config.repeat_limit {
+                reason.get_or_insert(format!(
+                    "{} was called {} times with identical arguments",
+                    name, count
+                ));
+            }
+
+            if failed.contains(id.as_str()) {
+                continue;
+            }
+            let path = input.get("filePath").and_then(|v| v.as_str());
+            match (name.as_str(), path) {
+                ("file_edit", Some(path)) => {
+                    changed_file = true;
+                    let old = input.get("oldString").and_then(|v| v.as_str());
+                    let new = input.get("newString").and_then(|v| v.as_str());
+                    if let (Some(old), Some(new)) = (old, new) {
+                        if self
+                            .edits
+                            .iter()
+                            .any(|(p, o, n)| p == path && o == new && n == old)
+                        {
+                            self.reverts += 1;
+                        }
+                        self.edits
+                            .push((path.to_string(), old.to_string(), new.to_string()));
+                    }
+                }
+                ("apply_patch", _) => {
+                    let dry_run = input.get("dryRun").and_then(|v| v.as_bool());
+                    changed_file |= dry_run != Some(true);
+                }
+                ("file_write", Some(path)) => {
+                    changed_file = true;
+                    if let Some(content) = input.get("content").and_then(|v| v.as_str()) {
+                        let hash = hash_str(content);
+                        let history = self.writes.entry(path.to_string()).or_default();
+                        // Writing back an earlier version (but not the latest) undoes work.
+                        if history.len() > 1 && history[..history.len() - 1].contains(&hash) {
+                            self.reverts += 1;
+                        }
+                        history.push(hash);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if self.config.revert_limit > 0 && self.reverts >= self.config.revert_limit {
+            reason.get_or_insert(format!(
+                "{} edits undid earlier edits to the same file",
+                self.reverts
+            ));
+        }
+
+        if changed_file {
+            // Running the same command after each edit, such as the tests, is progress.
+            self.calls.clear();
+            self.idle_iterations = 0;
+        } else {
+            self.idle_iterations += 1;
+            if self.config.idle_iterations > 0
+                && self.idle_iterations >= self.config.idle_iterations
+            {
+                reason.get_or_insert(format!(
+                    "{} tool iterations without changing a file",
+                    self.idle_iterations
+                ));
+            }
+        }
+
+        reason
+    }
+}
+
+fn blocks(message: &Message) -> &[ContentBlock] {
+    match &message.content {
+        MessageContent::Blocks(blocks) => blocks,
+        MessageContent::Text(_) => &[],
+    }
+}
+
+fn hash_str(s: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    s.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Whether a user is present to answer the continue prompt (false in batch mode).
+static INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Sender for the answer to the continue prompt currently being shown.
+static PENDING_ANSWER: Mutex<Option<oneshot::Sender<bool>>> = Mutex::new(None);
+
+pub(crate) fn set_interactive(interactive: bool) {
+    INTERACTIVE.store(interactive, Ordering::Relaxed);
+}
+
+/// Whether the chat loop is paused waiting for the user to answer.
+pub(crate) fn awaiting_answer() -> bool {
+    PENDING_ANSWER.lock().unwrap().is_some()
+}
+
+/// Answer the continue prompt.
+pub(crate) fn answer(continue_loop: bool) {
+    if let Some(tx) = PENDING_ANSWER.lock().unwrap().take() {
+        let _ = tx.send(continue_loop);
+    }
+}
+
+/// Whether a user is present to answer a prompt.
+pub(crate) fn is_interactive() -> bool {
+    INTERACTIVE.load(Ordering::Relaxed)
+}
+
+/// Pause the agent and ask the user whether to continue.
+///
+/// Returns false when the user declines, interrupts, or nobody can answer.
+pub(crate) async fn ask_to_continue(
+    reason: &str,
+    interrupted: &AtomicBool,
+    output: &output::OutputContext,
+) -> bool {
+    if !is_interactive() {
+        output::emit_warning(
+            output,
+            &format!("Possible agent loop: {}. Stopping.", reason),
+        );
+        return false;
+    }
+
+    confirm(
+        &format!("Possible agent loop: {}. Continue?", reason),
+        interrupted,
+        output,
+    )
+    .await
+}
+
+/// Show `question` as a warning and wait for a y/n answer from the user.
+///
+/// Returns false when the user declines or interrupts.
+pub(crate) async fn confirm(
+    question: &str,
+    interrupted: &AtomicBool,
+    output: &output::OutputContext,
+) -> bool {
+    let (tx, rx) = oneshot::channel();
+    *PENDING_ANSWER.lock().unwrap() = Some(tx);
+    output::emit_warning(output, &format!("{} [y/n]", question));
+
+    let answer = tokio::select! {
+        biased;
+        _ = async {
+            while !interrupted.load(Ordering::SeqCst) {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        } => false,
+        answer = rx => answer.unwrap_or(false),
+    };
+    PENDING_ANSWER.lock().unwrap().take();
+    answer
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::provider::Role;
+
+    fn tool_turn(name: &str, input: serde_json::Value, is_error: bool) -> (Message, Message) {
+        let assistant = Message::assistant_blocks(vec![ContentBlock::ToolUse {
+            id: "call_1".to_string(),
+            name: name.to_string(),
+            input,
+            thought_signature: None,
+        }]);
+        let results = Message {
+            role: Role::User,
+            content: MessageContent::Blocks(vec![ContentBlock::ToolResult {
+                tool_use_id: "call_1".to_string(),
+                content: String::new(),
+                is_error,
+                data: None,
+                mime_type: None,
+            }]),
+        };
+        (assistant, results)
+    }
+
+    #[test]
+    fn test_identical_calls_trip() {
+        let mut watchdog = LoopWatchdog::new(LoopWatchdogConfig::default());
+        let (a, r) = tool_turn("bash", json!({"command": "cargo test"}), false);
+        assert!(watchdog.observe(&a, &r).is_none());
+        assert!(watchdog.observe(&a, &r).is_none());
+        let reason = watchdog.observe(&a, &r).unwrap();
+        assert!(reason.contains("bash was called 3 times"));
+
+        watchdog.reset();
+        assert!(watchdog.observe(&a, &r).is_none());
+    }
+
+    #[test]
+    fn test_edit_test_cycle_does_not_trip() {
+        let mut watchdog = LoopWatchdog::new(LoopWatchdogConfig::default());
+        let (test, test_result) = tool_turn("bash", json!({"command": "cargo test"}), false);
+        for i in 0..5 {
+            let edit = json!({"filePath": "a.rs", "oldString": format!("{}", i), "newString": format!("{}", i + 1)});
+            let (a, r) = tool_turn("file_edit", edit, false);
+            assert!(watchdog.observe(&a, &r).is_none());
+            assert!(watchdog.observe(&test, &test_result).is_none());
+        }
+    }
+
+    #[test]
+    fn test_edit_revert_trips() {
+        let mut watchdog = LoopWatchdog::new(LoopWatchdogConfig::default());
+        let forward = json!({"filePath": "a.rs", "oldString": "x", "newString": "y"});
+        let back = json!({"filePath": "a.rs", "oldString": "y", "newString": "x"});
+        let (a, r) = tool_turn("file_edit", forward.clone(), false);
+        assert!(watchdog.observe(&a, &r).is_none());
+        let (a, r) = tool_turn("file_edit", back, false);
+        assert!(watchdog.observe(&a, &r).is_none());
+        // Re-applying the first edit undoes the revert.
+        let (a, r) = tool_turn("file_edit", forward, false);
+        let reason = watchdog.observe(&a, &r).unwrap();
+        assert!(reason.contains("undid earlier edits"));
+    }
+
+    #[test]
+    fn test_failed_edits_are_not_progress() {
+        let config = LoopWatchdogConfig {
+            idle_iterations: 2,
+            ..LoopWatchdogConfig::default()
+        };
+        let mut watchdog = LoopWatchdog::new(config);
+        let (a, r) = tool_turn(
+            "file_edit",
+            json!({"filePath": "a.rs", "oldString": "x", "newString": "y"}),
+            true,
+        );
+        assert!(watchdog.observe(&a, &r).is_none());
+        let (a, r) = tool_turn("file_read", json!({"filename": "a.rs"}), false);
+        let reason = watchdog.observe(&a, &r).unwrap();
+        assert!(reason.contains("2 tool iterations without changing a file"));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let config = LoopWatchdogConfig {
+            enabled: false,
+            ..LoopWatchdogConfig::default()
+        };
+        let mut watchdog = LoopWatchdog::new(config);
+        let (a, r) = tool_turn("bash", json!({"command": "ls"}), false);
+        for _ in 0..5 {
+            assert!(watchdog.observe(&a, &r).is_none());
+        }
+    }
+}